@@ -0,0 +1,63 @@
+//! Benchmarks of the executor, statistics engine and formatter
+//!
+//! The same simulated workloads as the hidden `bench-internal` command, under
+//! criterion, to check that a redesign of one of them pays off. No network
+//! traffic is sent.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use network_latency_tester::bench::{self, BenchSize};
+use std::hint::black_box;
+
+const SIZES: [BenchSize; 2] = [
+    BenchSize { targets: 5, configs: 5, iterations: 20 },
+    BenchSize { targets: 20, configs: 10, iterations: 50 },
+];
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Runtime::new().unwrap()
+}
+
+fn benchmark_scheduling(c: &mut Criterion) {
+    let runtime = runtime();
+    let mut group = c.benchmark_group("executor_scheduling");
+    for size in SIZES {
+        let executor = runtime.block_on(bench::simulated_executor(&size)).unwrap();
+        group.throughput(Throughput::Elements(size.samples()));
+        group.bench_with_input(BenchmarkId::from_parameter(size.samples()), &size, |b, size| {
+            b.iter(|| runtime.block_on(bench::schedule(&executor, black_box(size))).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn benchmark_statistics(c: &mut Criterion) {
+    let runtime = runtime();
+    let mut group = c.benchmark_group("statistics_engine");
+    for size in SIZES {
+        let executor = runtime.block_on(bench::simulated_executor(&size)).unwrap();
+        let results = runtime.block_on(bench::schedule(&executor, &size)).unwrap();
+        group.throughput(Throughput::Elements(size.samples()));
+        group.bench_with_input(BenchmarkId::from_parameter(size.samples()), &results, |b, results| {
+            b.iter(|| bench::analyze(black_box(results)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn benchmark_formatting(c: &mut Criterion) {
+    let runtime = runtime();
+    let mut group = c.benchmark_group("formatter_rendering");
+    for size in SIZES {
+        let executor = runtime.block_on(bench::simulated_executor(&size)).unwrap();
+        let results = runtime.block_on(bench::schedule(&executor, &size)).unwrap();
+        let analysis = bench::analyze(&results).unwrap();
+        let results = bench::execution_results(results, Some(analysis));
+        group.bench_with_input(BenchmarkId::from_parameter(size.samples()), &results, |b, results| {
+            b.iter(|| runtime.block_on(bench::render(black_box(results))).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_scheduling, benchmark_statistics, benchmark_formatting);
+criterion_main!(benches);
@@ -4,7 +4,7 @@
 //! them to expected baselines to ensure the Rust implementation meets
 //! or exceeds the original bash script performance.
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use network_latency_tester::{
     cli::Cli,
     config::parser::ConfigParser,
@@ -13,7 +13,7 @@ use network_latency_tester::{
     stats::StatisticsEngine,
     types::DnsConfig,
 };
-use std::{net::IpAddr, time::Duration};
+use std::{hint::black_box, net::IpAddr, time::Duration};
 use chrono::Utc;
 use clap::Parser;
 
@@ -24,10 +24,11 @@ fn create_benchmark_config() -> Config {
         dns_servers: vec!["8.8.8.8".to_string()],
         doh_providers: vec![],
         test_count: 1, // Single iteration for benchmarking
-        timeout_seconds: 5,
+        timeout: Duration::from_secs(5),
         enable_color: false,
         verbose: false,
         debug: false,
+        ..Default::default()
     }
 }
 
@@ -64,15 +65,17 @@ fn create_sample_results(count: usize) -> Vec<TestResult> {
             };
             
             TestResult {
-                config_name: format!("DNS Config {}", i % 3),
+                config_name: format!("DNS Config {}", i % 3).into(),
                 dns_config: DnsConfig::System,
-                url: "https://example.com".to_string(),
+                url: "https://example.com".into(),
                 individual_results,
                 statistics: None, // Will be calculated
                 success_count: if i % 10 == 0 { 0 } else { 1 },
                 total_count: 1,
                 started_at: Utc::now(),
                 completed_at: Some(Utc::now()),
+                interface: None,
+                ping: Vec::new(),
             }
         })
         .collect()
@@ -144,7 +147,7 @@ fn benchmark_config_parsing(c: &mut Criterion) {
         let config = create_benchmark_config();
         b.iter(|| {
             let result = config.validate();
-            black_box(result);
+            let _ = black_box(result);
         });
     });
     
@@ -181,7 +184,7 @@ fn benchmark_statistics_calculation(c: &mut Criterion) {
                 let mut engine = StatisticsEngine::with_defaults();
                 engine.add_results(black_box(results.clone()));
                 let analysis = engine.analyze();
-                black_box(analysis);
+                let _ = black_box(analysis);
             });
         });
         
@@ -223,12 +226,10 @@ fn benchmark_result_processing(c: &mut Criterion) {
     
     // Benchmark URL processing
     group.bench_function("process_urls", |b| {
-        let urls = vec![
-            "https://example1.com",
+        let urls = ["https://example1.com",
             "https://example2.com",
             "https://example3.com/path",
-            "https://example4.com/path?param=value",
-        ];
+            "https://example4.com/path?param=value"];
         
         b.iter(|| {
             let processed: Vec<String> = urls.iter()
@@ -261,8 +262,8 @@ fn benchmark_application_workflow(c: &mut Criterion) {
             let cli = Cli::try_parse_from(black_box(&args)).unwrap();
             let parser = ConfigParser::new(cli);
             let config = parser.parse().unwrap();
-            let validated = config.validate().unwrap();
-            black_box(validated);
+            config.validate().unwrap();
+            black_box(());
         });
     });
     
@@ -335,7 +336,7 @@ fn benchmark_performance_regression(c: &mut Criterion) {
             let mut engine = StatisticsEngine::with_defaults();
             engine.add_results(results.clone());
             let analysis = engine.analyze();
-            black_box(analysis);
+            let _ = black_box(analysis);
         });
     });
     
@@ -346,7 +347,7 @@ fn benchmark_performance_regression(c: &mut Criterion) {
             let config = DnsConfig::Custom { 
                 servers: black_box(servers.clone()) 
             };
-            black_box(config);
+            let _ = black_box(config);
         });
     });
     
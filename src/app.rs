@@ -51,7 +51,7 @@ impl App {
                 config.dns_servers.len(), 
                 config.doh_providers.len());
         println!("Test iterations per configuration: {}", config.test_count);
-        println!("Request timeout: {}s", config.timeout_seconds);
+        println!("Request timeout: {}", crate::utils::duration::format_duration(config.timeout));
         
         // TODO: This is a placeholder - actual testing will be implemented in future tasks
         println!("\n✅ Configuration loaded and validated successfully!");
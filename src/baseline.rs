@@ -0,0 +1,364 @@
+//! Public latency measurements to compare a run against
+//!
+//! A connect time of 80ms says little on its own: it is fine for a server on
+//! another continent and alarming for one in the same city. `--compare-public`
+//! looks up what others measure to the same hosts and shows the run's latency
+//! next to it, as a [`BaselineComparison`] per host.
+//!
+//! Public numbers come from a [`BaselineSource`]. [`RipeAtlas`] uses the
+//! latest results of an ongoing public RIPE Atlas ping measurement to the host,
+//! optionally only those of probes in one country; [`BaselineFile`] reads
+//! medians collected elsewhere. Pings and TCP connects both take one round
+//! trip, so the run is represented by the median connect time of its fresh
+//! connections.
+
+use crate::{
+    error::{AppError, Result},
+    models::{BaselineComparison, TestResult, TimingMetrics},
+};
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// RIPE Atlas REST API
+pub const RIPE_ATLAS_API: &str = "https://atlas.ripe.net/api/v2";
+/// Most probes of a country whose results are requested
+const MAX_REGION_PROBES: usize = 500;
+
+/// What others measure to a host
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PublicLatency {
+    /// Median round-trip time (milliseconds)
+    pub median_ms: f64,
+    /// Probes that contributed a measurement
+    #[serde(default)]
+    pub probes: usize,
+    /// Region the probes were in
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Where the measurement came from, e.g. `RIPE Atlas #1001`
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Somewhere to look up public latency measurements
+#[async_trait]
+pub trait BaselineSource: Send + Sync {
+    /// Name shown for measurements that do not name their own source
+    fn name(&self) -> &str;
+
+    /// Recent measurements to `host`, or `None` when the source has none
+    async fn lookup(&self, host: &str) -> Result<Option<PublicLatency>>;
+}
+
+/// Source given to `--compare-public`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaselineSpec {
+    /// RIPE Atlas, optionally only probes in a country (ISO 3166 code)
+    Atlas { country: Option<String> },
+    /// A JSON file of medians by host
+    File(PathBuf),
+}
+
+impl BaselineSpec {
+    /// The source to look measurements up in
+    pub fn source(&self, timeout: Duration) -> Result<Box<dyn BaselineSource>> {
+        Ok(match self {
+            BaselineSpec::Atlas { country } => Box::new(RipeAtlas::new(country.clone(), timeout)?),
+            BaselineSpec::File(path) => Box::new(BaselineFile::load(path)?),
+        })
+    }
+}
+
+impl FromStr for BaselineSpec {
+    type Err = AppError;
+
+    /// `atlas`, `atlas:CC` for probes in country CC, or the path of a JSON file
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let Some(rest) = s.strip_prefix("atlas") else {
+            if s.is_empty() {
+                return Err(AppError::validation("Public baseline source cannot be empty"));
+            }
+            return Ok(BaselineSpec::File(PathBuf::from(s)));
+        };
+        match rest.strip_prefix(':') {
+            None if rest.is_empty() => Ok(BaselineSpec::Atlas { country: None }),
+            Some(country) if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Ok(BaselineSpec::Atlas { country: Some(country.to_ascii_uppercase()) })
+            }
+            _ => Err(AppError::validation(format!(
+                "Invalid public baseline source '{}': use atlas, atlas:CC with a two-letter country code, or a JSON file", s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for BaselineSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaselineSpec::Atlas { country: None } => write!(f, "RIPE Atlas"),
+            BaselineSpec::Atlas { country: Some(country) } => write!(f, "RIPE Atlas ({})", country),
+            BaselineSpec::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Ongoing public ping measurements of the RIPE Atlas network
+#[derive(Debug, Clone)]
+pub struct RipeAtlas {
+    client: Client,
+    api: String,
+    country: Option<String>,
+}
+
+/// A page of results of the RIPE Atlas API
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    results: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Identified {
+    id: u64,
+}
+
+/// A probe's latest result of a ping measurement
+#[derive(Debug, Deserialize)]
+struct PingResult {
+    /// Average round-trip time, or -1 when no packet came back
+    #[serde(default)]
+    avg: Option<f64>,
+}
+
+impl RipeAtlas {
+    pub fn new(country: Option<String>, timeout: Duration) -> Result<Self> {
+        Self::with_api(RIPE_ATLAS_API, country, timeout)
+    }
+
+    /// RIPE Atlas served at `api`, for mirrors and tests
+    pub fn with_api(api: &str, country: Option<String>, timeout: Duration) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
+        Ok(Self { client, api: api.trim_end_matches('/').to_string(), country })
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let url = format!("{}{}", self.api, path);
+        let failed = |e: reqwest::Error| AppError::network(format!("RIPE Atlas request {} failed: {}", path, e)).with_source(e);
+        self.client.get(&url).query(query).send().await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(failed)?
+            .json().await
+            .map_err(failed)
+    }
+}
+
+#[async_trait]
+impl BaselineSource for RipeAtlas {
+    fn name(&self) -> &str {
+        "RIPE Atlas"
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Option<PublicLatency>> {
+        let measurements: Page<Identified> = self.get("/measurements/", &[
+            ("target", host.to_string()),
+            ("type", "ping".to_string()),
+            // Ongoing, so the latest results are recent
+            ("status", "2".to_string()),
+            ("is_public", "true".to_string()),
+            ("sort", "-id".to_string()),
+            ("page_size", "1".to_string()),
+        ]).await?;
+        let Some(measurement) = measurements.results.first() else {
+            return Ok(None);
+        };
+
+        let mut query = Vec::new();
+        if let Some(ref country) = self.country {
+            let probes: Page<Identified> = self.get("/probes/", &[
+                ("country_code", country.clone()),
+                // Connected
+                ("status", "1".to_string()),
+                ("fields", "id".to_string()),
+                ("page_size", MAX_REGION_PROBES.to_string()),
+            ]).await?;
+            if probes.results.is_empty() {
+                return Ok(None);
+            }
+            let ids: Vec<String> = probes.results.iter().map(|probe| probe.id.to_string()).collect();
+            query.push(("probe_ids", ids.join(",")));
+        }
+        let latest: Vec<PingResult> = self.get(&format!("/measurements/{}/latest/", measurement.id), &query).await?;
+        Ok(ping_median(&latest).map(|(median_ms, probes)| PublicLatency {
+            median_ms,
+            probes,
+            region: self.country.clone(),
+            source: Some(format!("RIPE Atlas #{}", measurement.id)),
+        }))
+    }
+}
+
+/// Median of the probes' average round trips, and how many probes got an answer
+fn ping_median(results: &[PingResult]) -> Option<(f64, usize)> {
+    let mut rtts: Vec<f64> = results.iter().filter_map(|result| result.avg).filter(|&avg| avg > 0.0).collect();
+    if rtts.is_empty() {
+        return None;
+    }
+    rtts.sort_by(f64::total_cmp);
+    let middle = rtts.len() / 2;
+    let median = if rtts.len().is_multiple_of(2) { (rtts[middle - 1] + rtts[middle]) / 2.0 } else { rtts[middle] };
+    Some((median, rtts.len()))
+}
+
+/// Public medians collected elsewhere, as a JSON object keyed by host, e.g.
+/// `{"example.com": {"median_ms": 24.5, "probes": 40, "region": "DE"}}`
+#[derive(Debug, Clone, Default)]
+pub struct BaselineFile {
+    name: String,
+    hosts: BTreeMap<String, PublicLatency>,
+}
+
+impl BaselineFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::io(format!("Failed to read {}: {}", path.display(), e)).with_source(e))?;
+        let hosts = serde_json::from_str(&content)
+            .map_err(|e| AppError::parse(format!("Invalid public baseline file {}: {}", path.display(), e)).with_source(e))?;
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+        Ok(Self { name, hosts })
+    }
+}
+
+#[async_trait]
+impl BaselineSource for BaselineFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Option<PublicLatency>> {
+        Ok(self.hosts.get(host).cloned())
+    }
+}
+
+/// Compare the run's connect times with `source`, one host at a time
+///
+/// Hosts the source knows nothing about are left out; lookups that fail are
+/// returned as errors alongside the comparisons that worked.
+pub async fn compare(results: &[&TestResult], source: &dyn BaselineSource) -> (Vec<BaselineComparison>, Vec<AppError>) {
+    let mut hosts: BTreeMap<String, Vec<&TimingMetrics>> = BTreeMap::new();
+    for result in results {
+        let Some(host) = url::Url::parse(&result.url).ok().and_then(|url| url.host_str().map(str::to_string)) else {
+            continue;
+        };
+        hosts.entry(host).or_default().extend(&result.individual_results);
+    }
+
+    let lookups = join_all(hosts.keys().map(|host| source.lookup(host))).await;
+    let mut comparisons = Vec::new();
+    let mut errors = Vec::new();
+    for ((host, metrics), lookup) in hosts.into_iter().zip(lookups) {
+        match lookup {
+            Ok(Some(public)) => {
+                comparisons.push(BaselineComparison {
+                    target: host,
+                    source: public.source.unwrap_or_else(|| source.name().to_string()),
+                    region: public.region,
+                    probes: public.probes,
+                    public_median_ms: public.median_ms,
+                    local_median_ms: TimingMetrics::median_connect_ms(metrics),
+                });
+            }
+            Ok(None) => {}
+            Err(e) => errors.push(AppError::network(format!("No public baseline for {}: {}", host, e.message())).with_source(e)),
+        }
+    }
+    (comparisons, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::TimingMetrics, types::DnsConfig};
+
+    #[test]
+    fn test_baseline_spec() {
+        assert_eq!("atlas".parse::<BaselineSpec>().unwrap(), BaselineSpec::Atlas { country: None });
+        assert_eq!("atlas:de".parse::<BaselineSpec>().unwrap(), BaselineSpec::Atlas { country: Some("DE".to_string()) });
+        assert_eq!("medians.json".parse::<BaselineSpec>().unwrap(), BaselineSpec::File(PathBuf::from("medians.json")));
+        assert!("atlas:germany".parse::<BaselineSpec>().is_err());
+        assert!("atlasx".parse::<BaselineSpec>().is_err());
+        assert!("".parse::<BaselineSpec>().is_err());
+
+        let latest: Vec<PingResult> = serde_json::from_str(r#"[{"prb_id": 1, "avg": 30.0}, {"prb_id": 2, "avg": -1}, {"prb_id": 3, "avg": 10.0}, {"prb_id": 4}]"#).unwrap();
+        assert_eq!(ping_median(&latest), Some((20.0, 2)));
+        assert_eq!(ping_median(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_ripe_atlas_lookup() {
+        use wiremock::{matchers::{method, path, query_param}, Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let json = |body: &str| ResponseTemplate::new(200).set_body_raw(body.to_string(), "application/json");
+        Mock::given(method("GET")).and(path("/measurements/")).and(query_param("target", "a.example"))
+            .respond_with(json(r#"{"count": 1, "results": [{"id": 1001, "target": "a.example"}]}"#))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/measurements/")).and(query_param("target", "b.example"))
+            .respond_with(json(r#"{"count": 0, "results": []}"#))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/probes/")).and(query_param("country_code", "DE"))
+            .respond_with(json(r#"{"results": [{"id": 7}, {"id": 9}]}"#))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/measurements/1001/latest/")).and(query_param("probe_ids", "7,9"))
+            .respond_with(json(r#"[{"prb_id": 7, "avg": 12.0}, {"prb_id": 9, "avg": 18.0}]"#))
+            .mount(&server).await;
+
+        let atlas = RipeAtlas::with_api(&server.uri(), Some("DE".to_string()), Duration::from_secs(5)).unwrap();
+        let public = atlas.lookup("a.example").await.unwrap().unwrap();
+        assert_eq!(public.median_ms, 15.0);
+        assert_eq!(public.probes, 2);
+        assert_eq!(public.source.as_deref(), Some("RIPE Atlas #1001"));
+        assert_eq!(atlas.lookup("b.example").await.unwrap(), None);
+        // Anything unexpected from the API is an error, not a missing baseline
+        assert!(atlas.lookup("c.example").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compare_with_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("medians.json");
+        std::fs::write(&path, r#"{"a.example": {"median_ms": 20.0, "probes": 40, "region": "DE"}, "b.example": {"median_ms": 5.0}}"#).unwrap();
+        let source = BaselineFile::load(&path).unwrap();
+
+        let connect = |ms: u64, first_use: bool| {
+            let tcp = Duration::from_millis(ms);
+            TimingMetrics::success(Duration::ZERO, tcp, None, tcp, tcp, 200).with_first_use(first_use)
+        };
+        let mut a = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://a.example/path".to_string());
+        for metrics in [connect(40, true), connect(60, true), connect(500, false)] {
+            a.add_measurement(metrics);
+        }
+        let b = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://b.example".to_string());
+        let c = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://c.example".to_string());
+
+        let (comparisons, errors) = compare(&[&a, &b, &c], &source).await;
+        assert!(errors.is_empty());
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].local_median_ms, Some(50.0));
+        assert_eq!(comparisons[0].ratio(), Some(2.5));
+        assert!(comparisons[0].is_abnormal());
+        assert_eq!(comparisons[0].to_string(), "connect 50.0ms vs median 20.0ms from 40 probes in DE (medians.json), 2.5x");
+        assert_eq!(comparisons[1].local_median_ms, None);
+        assert!(!comparisons[1].is_abnormal());
+    }
+}
@@ -0,0 +1,195 @@
+//! Internal performance benchmarks for `bench-internal` and `benches/internal.rs`
+//!
+//! A redesign made for speed needs numbers from before and after it. The
+//! workloads here exercise the three parts of a run whose cost grows with its
+//! size, without any network traffic:
+//!
+//! - scheduling: the executor runs every target and DNS configuration against
+//!   a [`SimulatedTransport`] that answers at once, so the time is the
+//!   executor's own overhead per request
+//! - statistics: the statistics engine analyzes the results of that run
+//! - formatting: the plain-text report of the analyzed results is rendered
+//!
+//! The simulated samples are seeded, so every run of a workload sees the same
+//! data. The hidden `bench-internal` command prints the median time and
+//! throughput of each workload; the criterion benchmarks in
+//! `benches/internal.rs` run the same functions.
+
+use crate::{
+    client::simulate::{LatencyModel, Simulation, SimulatedTransport},
+    error::Result,
+    executor::{ExecutionResults, ExecutionSummary, OptimizedExecutor},
+    models::{Config, TestResult},
+    output::{OutputCoordinator, OutputFormatterFactory},
+    stats::{StatisticalAnalysis, StatisticsEngine},
+    types::DnsConfig,
+    utils::duration::format_duration,
+};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Size of the synthetic run the workloads operate on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchSize {
+    pub targets: usize,
+    pub configs: usize,
+    /// Requests per target and DNS configuration
+    pub iterations: u32,
+}
+
+impl BenchSize {
+    /// Samples in a run of this size
+    pub fn samples(&self) -> u64 {
+        (self.targets * self.configs) as u64 * u64::from(self.iterations)
+    }
+
+    /// Targets of the run, on the reserved `.example` domain
+    pub fn urls(&self) -> Vec<String> {
+        (0..self.targets).map(|target| format!("https://service{}.example/", target)).collect()
+    }
+
+    /// DNS configurations of the run, each a server of TEST-NET-1
+    pub fn dns_configs(&self) -> Vec<DnsConfig> {
+        (0..self.configs)
+            .map(|config| DnsConfig::Custom {
+                servers: vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, (config % 254 + 1) as u8))],
+            })
+            .collect()
+    }
+}
+
+/// Executor answering every request from a seeded simulation
+pub async fn simulated_executor(size: &BenchSize) -> Result<OptimizedExecutor> {
+    let config = Config {
+        test_count: size.iterations,
+        enable_color: false,
+        ..Default::default()
+    };
+    let simulation = Simulation {
+        model: LatencyModel::LogNormal { median: Duration::from_millis(60), sigma: 0.5 },
+        spikes: 0.02,
+        spike_factor: 10.0,
+        loss: 0.01,
+        burst: 3,
+        seed: Some(1),
+    };
+    OptimizedExecutor::with_client(&config, Arc::new(SimulatedTransport::new(simulation))).await
+}
+
+/// Run every target and DNS configuration of `size` once
+pub async fn schedule(executor: &OptimizedExecutor, size: &BenchSize) -> Result<Vec<TestResult>> {
+    executor.execute_optimized_tests(&size.urls(), &size.dns_configs()).await
+}
+
+/// Analyze the results with the default statistics settings
+pub fn analyze(results: &[TestResult]) -> Result<StatisticalAnalysis> {
+    let mut engine = StatisticsEngine::with_defaults();
+    engine.add_results(results.to_vec());
+    engine.analyze()
+}
+
+/// Results of a run ready to be rendered, with their analysis
+pub fn execution_results(results: Vec<TestResult>, analysis: Option<StatisticalAnalysis>) -> ExecutionResults {
+    let total_tests = results.len() as u32;
+    let successful_tests = results.iter().filter(|result| result.success_count > 0).count() as u32;
+    let summary = ExecutionSummary {
+        total_tests,
+        successful_tests,
+        failed_tests: total_tests - successful_tests,
+        success_rate: if total_tests > 0 { successful_tests as f64 / total_tests as f64 * 100.0 } else { 0.0 },
+        ..Default::default()
+    };
+    let mut execution_results = ExecutionResults::new(summary, results);
+    execution_results.statistical_analysis = analysis;
+    execution_results
+}
+
+/// Render the plain-text report of the results
+pub async fn render(results: &ExecutionResults) -> Result<String> {
+    OutputCoordinator::new(OutputFormatterFactory::create_plain_formatter())
+        .display_results(results)
+        .await
+}
+
+/// Timings of one workload
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchMeasurement {
+    pub name: &'static str,
+    /// What one run processes, e.g. `samples`
+    pub unit: &'static str,
+    /// How many of them one run processes
+    pub items: u64,
+    /// Time of each run
+    pub times: Vec<Duration>,
+}
+
+impl BenchMeasurement {
+    pub fn median(&self) -> Duration {
+        let mut times = self.times.clone();
+        times.sort();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+
+    /// Items processed per second at the median time
+    pub fn throughput(&self) -> f64 {
+        let median = self.median().as_secs_f64();
+        if median > 0.0 { self.items as f64 / median } else { 0.0 }
+    }
+}
+
+impl fmt::Display for BenchMeasurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let min = self.times.iter().min().copied().unwrap_or_default();
+        let max = self.times.iter().max().copied().unwrap_or_default();
+        write!(f, "{:<11} {:>10} median ({} to {}), {:.0} {}/s over {} runs",
+            self.name, format_duration(self.median()), format_duration(min), format_duration(max),
+            self.throughput(), self.unit, self.times.len())
+    }
+}
+
+/// Time `runs` runs of each workload at `size`, after one run of each to warm up
+pub async fn run_all(size: &BenchSize, runs: u32) -> Result<Vec<BenchMeasurement>> {
+    let executor = simulated_executor(size).await?;
+    let results = schedule(&executor, size).await?;
+    let analysis = analyze(&results)?;
+    let execution_results = execution_results(results.clone(), Some(analysis));
+    render(&execution_results).await?;
+
+    let mut scheduling = BenchMeasurement { name: "scheduling", unit: "requests", items: size.samples(), times: Vec::new() };
+    let mut statistics = BenchMeasurement { name: "statistics", unit: "samples", items: size.samples(), times: Vec::new() };
+    let mut formatting = BenchMeasurement { name: "formatting", unit: "results", items: results.len() as u64, times: Vec::new() };
+    for _ in 0..runs {
+        let start = Instant::now();
+        schedule(&executor, size).await?;
+        scheduling.times.push(start.elapsed());
+
+        let start = Instant::now();
+        analyze(&results)?;
+        statistics.times.push(start.elapsed());
+
+        let start = Instant::now();
+        render(&execution_results).await?;
+        formatting.times.push(start.elapsed());
+    }
+    Ok(vec![scheduling, statistics, formatting])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_all() {
+        let size = BenchSize { targets: 2, configs: 3, iterations: 4 };
+        assert_eq!(size.samples(), 24);
+        assert_eq!(size.dns_configs().len(), 3);
+
+        let measurements = run_all(&size, 2).await.unwrap();
+        let names: Vec<&str> = measurements.iter().map(|measurement| measurement.name).collect();
+        assert_eq!(names, ["scheduling", "statistics", "formatting"]);
+        assert!(measurements.iter().all(|measurement| measurement.times.len() == 2 && measurement.items > 0));
+        assert!(measurements[0].to_string().contains("requests/s over 2 runs"));
+    }
+}
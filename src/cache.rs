@@ -0,0 +1,92 @@
+//! The caches kept on disk
+//!
+//! `cache stats` and `cache clear` go over [`known_caches`]: the release data
+//! used by `--update` (with the `updater` feature) and the per-network
+//! history of `--network-history`. Each is rebuilt the next time it is needed.
+
+use crate::network::NetworkStore;
+#[cfg(feature = "updater")]
+use crate::updater::cache::CacheManager;
+use crate::{AppError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A cache kept on disk
+pub enum KnownCache {
+    /// Release data used by `--update`
+    #[cfg(feature = "updater")]
+    Releases(CacheManager),
+    /// Results per network, kept by `--network-history` at this path
+    Networks(PathBuf),
+}
+
+impl KnownCache {
+    /// Name the `cache` command shows the cache under
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "updater")]
+            Self::Releases(_) => "releases",
+            Self::Networks(_) => "networks",
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            #[cfg(feature = "updater")]
+            Self::Releases(cache) => cache.path(),
+            Self::Networks(path) => path,
+        }
+    }
+
+    /// Location, status, size and entries of the cache for display
+    pub fn format_stats(&self, use_colors: bool) -> Result<String> {
+        match self {
+            #[cfg(feature = "updater")]
+            Self::Releases(cache) => Ok(cache.get_cache_stats()?.format_stats(use_colors)),
+            Self::Networks(path) => Ok(NetworkStore::stats(path).format_stats(use_colors)),
+        }
+    }
+
+    /// Delete the cache, returning its size in bytes, or `None` if there was none
+    pub fn clear(&self) -> Result<Option<u64>> {
+        let Ok(metadata) = fs::metadata(self.path()) else {
+            return Ok(None);
+        };
+        match self {
+            #[cfg(feature = "updater")]
+            Self::Releases(cache) => cache.clear_cache()?,
+            Self::Networks(path) => fs::remove_file(path)
+                .map_err(|e| AppError::cache(format!("Failed to remove cache file '{}': {}", path.display(), e)).with_source(e))?,
+        }
+        Ok(Some(metadata.len()))
+    }
+}
+
+/// Every cache kept on disk, in the order the `cache` command shows them
+pub fn known_caches() -> Vec<KnownCache> {
+    let mut caches = Vec::new();
+    #[cfg(feature = "updater")]
+    caches.push(KnownCache::Releases(CacheManager::default()));
+    caches.extend(NetworkStore::locate().map(KnownCache::Networks));
+    caches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clear_networks_cache() {
+        let dir = TempDir::new().unwrap();
+        let cache = KnownCache::Networks(dir.path().join("networks.json"));
+        assert_eq!(cache.name(), "networks");
+        assert!(cache.format_stats(false).unwrap().contains("Status: Not Found"));
+        assert_eq!(cache.clear().unwrap(), None);
+
+        fs::write(cache.path(), "{}").unwrap();
+        assert!(cache.format_stats(false).unwrap().contains("Status: Valid"));
+        assert_eq!(cache.clear().unwrap(), Some(2));
+        assert!(!cache.path().exists());
+    }
+}
@@ -0,0 +1,69 @@
+//! Cooperative cancellation of long-running operations
+//!
+//! Ctrl-C, quitting an interactive view and a `--serve` client ending a run
+//! all need the same thing: whatever is in flight stops at its next await
+//! point and the caller reports what it has so far. [`global`] is the token of
+//! the whole process, which [`cancel_on_ctrl_c`] cancels. The DNS manager, the
+//! HTTP client, the executor, diagnostics and the updater each hold a child of
+//! it by default, or of the token given to their `with_cancellation`, so that
+//! one run can be cancelled without touching the others.
+
+use crate::error::{AppError, Result};
+use std::future::Future;
+use std::sync::OnceLock;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// The token of the whole process
+pub fn global() -> &'static CancellationToken {
+    static GLOBAL: OnceLock<CancellationToken> = OnceLock::new();
+    GLOBAL.get_or_init(CancellationToken::new)
+}
+
+/// Run `operation` unless `token` is cancelled first, in which case the
+/// operation is dropped and `what` is reported as cancelled
+pub async fn cancellable<T>(token: &CancellationToken, what: &str, operation: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(AppError::cancelled(what)),
+        result = operation => result,
+    }
+}
+
+/// Cancel the process token on the first Ctrl-C and exit on the second
+pub fn cancel_on_ctrl_c() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        eprintln!("\nStopping; press Ctrl-C again to quit at once");
+        global().cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(AppError::cancelled("").exit_code());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellable() {
+        let token = global().child_token();
+        assert_eq!(cancellable(&token, "lookup", async { Ok(1) }).await.unwrap(), 1);
+
+        let waiting = tokio::spawn({
+            let token = token.clone();
+            async move { cancellable(&token, "lookup", std::future::pending::<Result<()>>()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+        let error = waiting.await.unwrap().unwrap_err();
+        assert!(error.is_cancelled());
+        assert_eq!(error.to_string(), "Cancelled: lookup");
+        // Cancelling a child leaves the process token alone
+        assert!(!global().is_cancelled());
+    }
+}
@@ -0,0 +1,277 @@
+//! Packet captures of anomalous requests for `--capture-on-anomaly`
+//!
+//! A spike that turns up once an hour is over before anyone can start
+//! Wireshark. With `--capture-on-anomaly THRESHOLD`, a request that fails or
+//! takes longer than the threshold starts a capture of the traffic to the
+//! address and port it was sent to, with `tcpdump` or, where that is missing
+//! (as on Windows), Wireshark's `dumpcap`. A capture cannot go back in time,
+//! so it records the requests that follow for [`CAPTURE_LENGTH`]; sporadic
+//! problems tend to come in bursts. Each capture file is listed in the report
+//! with the request that triggered it.
+
+use crate::{
+    error::{AppError, Result},
+    executor::{EventSubscriber, ExecutionEvent},
+    models::TimingMetrics,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+/// How long each capture records
+pub const CAPTURE_LENGTH: Duration = Duration::from_secs(10);
+/// Captures started in one run at most
+pub const MAX_CAPTURES: usize = 5;
+/// Directory capture files are written to
+pub const CAPTURE_DIR: &str = "captures";
+
+/// A capture started by an anomalous request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacketCapture {
+    pub url: String,
+    pub config_name: String,
+    /// What was wrong with the request, e.g. "took 812.0ms"
+    pub trigger: String,
+    /// Address and port the capture was limited to
+    pub target: SocketAddr,
+    pub file: PathBuf,
+    pub started_at: DateTime<Utc>,
+    /// Why no capture was written, when the capture tool could not run
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl fmt::Display for PacketCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} with {} {} at {}: ", self.url, self.config_name, self.trigger, self.started_at.format("%H:%M:%S"))?;
+        match self.error {
+            Some(ref error) => write!(f, "no capture ({})", error),
+            None => write!(f, "{} ({})", self.file.display(), self.target),
+        }
+    }
+}
+
+/// Running and finished captures of a run
+#[derive(Debug, Default)]
+struct Captures {
+    running: Vec<JoinHandle<PacketCapture>>,
+    /// Targets a capture is running for
+    busy: HashSet<SocketAddr>,
+    finished: Vec<PacketCapture>,
+    started: usize,
+}
+
+/// Starts captures when requests are slower than a threshold or fail
+#[derive(Debug)]
+pub struct CaptureTrigger {
+    threshold: Duration,
+    directory: PathBuf,
+    captures: Mutex<Captures>,
+}
+
+impl CaptureTrigger {
+    pub fn new(threshold: Duration) -> Self {
+        Self::writing_to(threshold, Path::new(CAPTURE_DIR))
+    }
+
+    fn writing_to(threshold: Duration, directory: &Path) -> Self {
+        Self { threshold, directory: directory.to_path_buf(), captures: Mutex::default() }
+    }
+
+    /// What makes a request worth a capture, if anything does
+    fn anomaly(&self, metrics: &TimingMetrics) -> Option<String> {
+        if !metrics.is_successful() {
+            return Some(match metrics.error_message {
+                Some(ref error) => format!("failed ({})", error),
+                None => "failed".to_string(),
+            });
+        }
+        (metrics.total_duration > self.threshold).then(|| format!("took {:.1}ms", metrics.total_ms()))
+    }
+
+    /// Start a capture for an anomalous request, unless one is running for
+    /// its target or the run has had its share of captures
+    fn observe(&self, url: &str, config_name: &str, metrics: &TimingMetrics) {
+        let Some(trigger) = self.anomaly(metrics) else { return };
+        // Without the address there is nothing to limit the capture to
+        let Some(ip) = metrics.resolved_ip else { return };
+        let Some(port) = url::Url::parse(url).ok().and_then(|url| url.port_or_known_default()) else { return };
+        let target = SocketAddr::new(ip, port);
+
+        let mut captures = self.captures.lock().unwrap();
+        if captures.started >= MAX_CAPTURES || !captures.busy.insert(target) {
+            return;
+        }
+        captures.started += 1;
+        let started_at = Utc::now();
+        let file = self.directory.join(format!(
+            "capture-{}-{}-{}.pcap",
+            started_at.format("%Y%m%d-%H%M%S"),
+            ip.to_string().replace([':', '.'], "_"),
+            port,
+        ));
+        let mut capture = PacketCapture {
+            url: url.to_string(),
+            config_name: config_name.to_string(),
+            trigger,
+            target,
+            file,
+            started_at,
+            error: None,
+        };
+        captures.running.push(tokio::spawn(async move {
+            capture.error = record(target, &capture.file, CAPTURE_LENGTH).await.err().map(|e| e.message().to_string());
+            capture
+        }));
+    }
+
+    /// Wait for the running captures to write their files
+    pub async fn finish(&self) {
+        let running = std::mem::take(&mut self.captures.lock().unwrap().running);
+        let done = futures::future::join_all(running).await;
+        let mut captures = self.captures.lock().unwrap();
+        for capture in done.into_iter().flatten() {
+            captures.busy.remove(&capture.target);
+            captures.finished.push(capture);
+        }
+    }
+
+    /// Captures finished so far
+    pub fn captures(&self) -> Vec<PacketCapture> {
+        self.captures.lock().unwrap().finished.clone()
+    }
+}
+
+impl EventSubscriber for CaptureTrigger {
+    fn on_event(&self, event: &ExecutionEvent) {
+        if let ExecutionEvent::SampleCompleted { url, config_name, metrics, .. } = event {
+            self.observe(url, config_name, metrics);
+        }
+    }
+}
+
+/// Capture the traffic to `target` into `file` for `length`
+async fn record(target: SocketAddr, file: &Path, length: Duration) -> Result<()> {
+    if let Some(directory) = file.parent() {
+        tokio::fs::create_dir_all(directory).await
+            .map_err(|e| AppError::io(format!("Cannot create {}: {}", directory.display(), e)).with_source(e))?;
+    }
+    let filter = format!("host {} and port {}", target.ip(), target.port());
+    let path = file.display().to_string();
+    let seconds = length.as_secs().max(1).to_string();
+    let mut tcpdump = vec!["-n", "-U", "-w", &path];
+    // Only Linux can listen on every interface at once
+    if cfg!(target_os = "linux") {
+        tcpdump.extend(["-i", "any"]);
+    }
+    tcpdump.push(&filter);
+    let duration = format!("duration:{}", seconds);
+    let attempts = [
+        ("tcpdump", tcpdump),
+        ("dumpcap", vec!["-q", "-a", &duration, "-f", &filter, "-w", &path]),
+    ];
+
+    let mut failures = Vec::new();
+    for (program, args) in attempts {
+        let mut child = match Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                failures.push(format!("{} is not available: {}", program, e));
+                continue;
+            }
+        };
+        // dumpcap stops by itself; tcpdump records until it is stopped
+        match tokio::time::timeout(length + Duration::from_secs(2), child.wait()).await {
+            Err(_) => {
+                let _ = child.kill().await;
+                return Ok(());
+            }
+            Ok(Ok(status)) if status.success() => return Ok(()),
+            Ok(result) => {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr).await;
+                }
+                let reason = match result {
+                    Ok(status) => stderr.lines().last().unwrap_or(&status.to_string()).trim().to_string(),
+                    Err(e) => e.to_string(),
+                };
+                failures.push(format!("{} failed: {}", program, reason));
+            }
+        }
+    }
+    Err(AppError::io(format!("Could not capture packets ({})", failures.join("; "))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::success;
+
+    fn metrics(total_ms: u64) -> TimingMetrics {
+        let mut metrics = success(total_ms);
+        metrics.resolved_ip = Some("127.0.0.1".parse().unwrap());
+        metrics
+    }
+
+    #[tokio::test]
+    async fn test_capture_trigger() {
+        let directory = tempfile::tempdir().unwrap();
+        let trigger = CaptureTrigger::writing_to(Duration::from_millis(500), directory.path());
+        assert_eq!(trigger.anomaly(&metrics(100)), None);
+        assert_eq!(trigger.anomaly(&metrics(812)).as_deref(), Some("took 812.0ms"));
+        assert_eq!(trigger.anomaly(&TimingMetrics::failed("Connection refused".to_string())).as_deref(),
+            Some("failed (Connection refused)"));
+
+        trigger.observe("https://example.com/", "System DNS", &metrics(100));
+        // The second anomaly of a target comes while its capture still runs
+        trigger.observe("https://example.com/", "System DNS", &metrics(900));
+        trigger.observe("https://example.com/", "System DNS", &metrics(950));
+        // Failed before an address was known
+        trigger.observe("https://example.com/", "System DNS", &TimingMetrics::failed("DNS error".to_string()));
+        {
+            let captures = trigger.captures.lock().unwrap();
+            assert_eq!(captures.started, 1);
+            assert!(captures.busy.contains(&"127.0.0.1:443".parse().unwrap()));
+        }
+        // Stop the capture rather than wait for it
+        for capture in std::mem::take(&mut trigger.captures.lock().unwrap().running) {
+            capture.abort();
+        }
+        trigger.finish().await;
+        assert!(trigger.captures().is_empty());
+    }
+
+    #[test]
+    fn test_capture_display() {
+        let capture = PacketCapture {
+            url: "https://example.com/".to_string(),
+            config_name: "System DNS".to_string(),
+            trigger: "took 812.0ms".to_string(),
+            target: "93.184.215.14:443".parse().unwrap(),
+            file: PathBuf::from("captures/capture-20240501-101500-93_184_215_14-443.pcap"),
+            started_at: "2024-05-01T10:15:00Z".parse().unwrap(),
+            error: None,
+        };
+        assert_eq!(capture.to_string(), "https://example.com/ with System DNS took 812.0ms at 10:15:00: \
+            captures/capture-20240501-101500-93_184_215_14-443.pcap (93.184.215.14:443)");
+        let failed = PacketCapture { error: Some("tcpdump failed: permission denied".to_string()), ..capture };
+        assert!(failed.to_string().ends_with("no capture (tcpdump failed: permission denied)"));
+    }
+}
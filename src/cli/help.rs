@@ -4,7 +4,7 @@
 //! to help users effectively use the network latency tester.
 
 use crate::{
-    config::env::EnvManager,
+    config::env::{EnvManager, ENV_PREFIX, PREFIXED_OPTIONS, PREFIX_ALIASES},
     dns::platform::get_platform_name,
 };
 use colored::*;
@@ -105,6 +105,8 @@ impl HelpSystem {
             "network-latency-tester --url <URL> [OPTIONS]", 
             "network-latency-tester --test-original [OPTIONS]",
             "network-latency-tester --update [--version <VERSION>] [--force]",
+            "network-latency-tester query <FILE> [--where <EXPR>] [--metric <FIELD>] [--group-by <FIELD>]",
+            "network-latency-tester init [--output <FILE>] [--force] [--no-check]",
             "network-latency-tester --help [TOPIC]",
         ];
 
@@ -136,6 +138,13 @@ impl HelpSystem {
                 description: "Target URL to test (can be used multiple times)",
                 example: Some("--url https://google.com --url https://github.com"),
             },
+            OptionHelp {
+                short: None,
+                long: "url-file",
+                value: "<PATH>",
+                description: "Read target URLs from a file, one per line with optional comma-separated tags",
+                example: Some("--url-file targets.csv"),
+            },
             OptionHelp {
                 short: Some("c"),
                 long: "count",
@@ -146,15 +155,43 @@ impl HelpSystem {
             OptionHelp {
                 short: Some("t"),
                 long: "timeout",
-                value: "<SECONDS>",
-                description: "Request timeout in seconds (1-300)",
-                example: Some("--timeout 30"),
+                value: "<DURATION>",
+                description: "Request timeout, e.g. 30, 2s, 500ms or 1m30s (max 300s)",
+                example: Some("--timeout 500ms"),
+            },
+            OptionHelp {
+                short: None,
+                long: "max-runtime",
+                value: "<DURATION>",
+                description: "Time budget for the whole run; iteration counts shrink to fit",
+                example: Some("--max-runtime 10m"),
+            },
+            OptionHelp {
+                short: None,
+                long: "auto-count",
+                value: "[PRECISION]",
+                description: "Keep adding iterations past --count until the mean and p95 are within the precision (default 5%), up to 200",
+                example: Some("--auto-count=2%"),
+            },
+            OptionHelp {
+                short: None,
+                long: "quick",
+                value: "",
+                description: "Sanity check in about 10 seconds: one target per tag group, 3 iterations, the 3 fastest resolvers, compact summary",
+                example: Some("--quick"),
+            },
+            OptionHelp {
+                short: None,
+                long: "thorough",
+                value: "",
+                description: "Evidence for a support ticket: maximum samples, full diagnostics, traceroutes and certificates, saved as HTML + JSON",
+                example: Some("--thorough"),
             },
             OptionHelp {
                 short: None,
                 long: "dns-servers",
                 value: "<IPS>",
-                description: "Custom DNS servers (comma-separated IP addresses)",
+                description: "Custom DNS servers (comma-separated IP addresses; join with + for one multi-server configuration)",
                 example: Some("--dns-servers 8.8.8.8,1.1.1.1"),
             },
             OptionHelp {
@@ -164,6 +201,384 @@ impl HelpSystem {
                 description: "DNS-over-HTTPS providers (comma-separated HTTPS URLs)",
                 example: Some("--doh-providers https://dns.google/dns-query"),
             },
+            OptionHelp {
+                short: None,
+                long: "dns-chain",
+                value: "<CHAIN>",
+                description: "Resolvers tried in order until one answers, joined by '>' (system, server addresses or an HTTPS DoH URL); repeatable",
+                example: Some("--dns-chain 223.5.5.5>8.8.8.8>https://dns.google/dns-query"),
+            },
+            OptionHelp {
+                short: None,
+                long: "search-domains",
+                value: "<DOMAINS>",
+                description: "Search domains for short host names such as http://intranet, in place of the system's (comma-separated; \"\" for none)",
+                example: Some("--search-domains corp.example.com,example.com"),
+            },
+            OptionHelp {
+                short: None,
+                long: "ndots",
+                value: "<N>",
+                description: "Short names with fewer dots than N are tried with the search domains first (0-15)",
+                example: Some("--ndots 2"),
+            },
+            OptionHelp {
+                short: None,
+                long: "test-search-paths",
+                value: "",
+                description: "Also test each short target under its names qualified with the search domains",
+                example: Some("--url http://intranet --test-search-paths"),
+            },
+            OptionHelp {
+                short: None,
+                long: "export",
+                value: "<FILE>",
+                description: "Write the full results as JSON for later querying, or one row per request to a .csv file",
+                example: Some("--export results.json"),
+            },
+            OptionHelp {
+                short: None,
+                long: "export-csv",
+                value: "<FILE>",
+                description: "Also write one row per request with its phase timings to a CSV file",
+                example: Some("--export-csv requests.csv"),
+            },
+            OptionHelp {
+                short: None,
+                long: "slowest",
+                value: "<N>",
+                description: "List the N slowest requests with phase breakdown (default: 5, 0 to hide)",
+                example: Some("--slowest 10"),
+            },
+            OptionHelp {
+                short: None,
+                long: "sort-by",
+                value: "<METRIC>",
+                description: "Rank each target's configurations in the results table by mean, p95, success or score",
+                example: Some("--sort-by p95"),
+            },
+            OptionHelp {
+                short: None,
+                long: "top",
+                value: "<N>",
+                description: "Show only the best and worst N configurations per target and summarize the rest",
+                example: Some("--top 5 --sort-by score"),
+            },
+            OptionHelp {
+                short: None,
+                long: "columns",
+                value: "<COLUMNS>",
+                description: "Metric columns of the results table: dns, tcp, ttfb, total, min-max, p95, success, level, early-hints (comma-separated)",
+                example: Some("--columns dns,tcp,ttfb,total,p95,success"),
+            },
+            OptionHelp {
+                short: None,
+                long: "censor-timeouts",
+                value: "",
+                description: "Count timed-out requests as at least the timeout in percentiles (p95 shows as ≥)",
+                example: Some("--censor-timeouts"),
+            },
+            OptionHelp {
+                short: None,
+                long: "socket-stats",
+                value: "",
+                description: "Report kernel TCP RTT, retransmissions and congestion window per request (Linux only)",
+                example: Some("--socket-stats"),
+            },
+            OptionHelp {
+                short: None,
+                long: "loss-probe",
+                value: "[PACKETS]",
+                description: "Measure packet loss and reordering with UDP bursts to each route and DNS server (default 50 packets)",
+                example: Some("--loss-probe=100"),
+            },
+            OptionHelp {
+                short: None,
+                long: "ping",
+                value: "[COUNT]",
+                description: "Ping each address the requests went to and show the ICMP round trip next to HTTP latency (default 10)",
+                example: Some("--ping=20"),
+            },
+            OptionHelp {
+                short: None,
+                long: "redirect-policy",
+                value: "<POLICY>",
+                description: "Follow redirects (follow), measure the redirect itself (none) or fail redirects off HTTPS (https-only)",
+                example: Some("--redirect-policy none"),
+            },
+            OptionHelp {
+                short: None,
+                long: "max-redirects",
+                value: "<N>",
+                description: "Redirects a request may follow before it fails (default: 10, max 50)",
+                example: Some("--max-redirects 3"),
+            },
+            OptionHelp {
+                short: None,
+                long: "user-agent",
+                value: "<PRESET>",
+                description: "User-Agent and headers to send: tool, curl, chrome, mobile-safari, or a custom User-Agent string",
+                example: Some("--user-agent mobile-safari"),
+            },
+            OptionHelp {
+                short: None,
+                long: "http2-prior-knowledge",
+                value: "",
+                description: "Speak HTTP/2 without negotiating it (h2c on http:// URLs, only h2 offered on https://)",
+                example: Some("--http2-prior-knowledge"),
+            },
+            OptionHelp {
+                short: None,
+                long: "no-alpn-fallback",
+                value: "",
+                description: "Fail requests not answered over HTTP/2 instead of measuring an HTTP/1.1 fallback",
+                example: Some("--no-alpn-fallback"),
+            },
+            OptionHelp {
+                short: None,
+                long: "tls-min-version",
+                value: "<VERSION>",
+                description: "Oldest TLS version to negotiate (1.2 or 1.3)",
+                example: Some("--tls-min-version 1.3"),
+            },
+            OptionHelp {
+                short: None,
+                long: "tls-max-version",
+                value: "<VERSION>",
+                description: "Newest TLS version to negotiate (1.2 or 1.3)",
+                example: Some("--tls-max-version 1.2"),
+            },
+            OptionHelp {
+                short: None,
+                long: "tls-resumption",
+                value: "<on|off>",
+                description: "Resume TLS sessions or always make full handshakes, and report resumed handshakes apart",
+                example: Some("--tls-resumption off"),
+            },
+            OptionHelp {
+                short: None,
+                long: "doh-fresh-connections",
+                value: "",
+                description: "Open a new connection to the DoH provider for every query instead of reusing one",
+                example: Some("--doh-fresh-connections"),
+            },
+            OptionHelp {
+                short: None,
+                long: "doh-bootstrap",
+                value: "<PROVIDER=IP|system>",
+                description: "Resolve a DoH provider's host up front, to a pinned IP or through the system resolver",
+                example: Some("--doh-bootstrap dns.google=8.8.8.8"),
+            },
+            OptionHelp {
+                short: None,
+                long: "sandbox-dns",
+                value: "",
+                description: "Verify custom DNS configurations use only their own servers and that system DNS settings stay unchanged",
+                example: Some("--sandbox-dns"),
+            },
+            OptionHelp {
+                short: None,
+                long: "flush-dns-cache",
+                value: "",
+                description: "Flush the OS resolver cache before testing so System DNS starts cold; usually needs administrator rights",
+                example: Some("--flush-dns-cache"),
+            },
+            OptionHelp {
+                short: None,
+                long: "single-thread",
+                value: "",
+                description: "Run on one thread with smaller pools and buffers (automatic on machines with ≤2 cores or <512 MiB free memory)",
+                example: Some("--single-thread"),
+            },
+            OptionHelp {
+                short: None,
+                long: "ntp-server",
+                value: "<HOST>",
+                description: "Check the system clock against an NTP server and report the offset with the results",
+                example: Some("--ntp-server pool.ntp.org"),
+            },
+            OptionHelp {
+                short: None,
+                long: "watch",
+                value: "<INTERVAL>",
+                description: "Repeat the tests every INTERVAL; edits to .env apply from the next cycle",
+                example: Some("--watch 5m"),
+            },
+            OptionHelp {
+                short: None,
+                long: "log-file",
+                value: "<FILE>",
+                description: "In watch mode, append a line per cycle and control command (rotate with `ctl rotate-log`)",
+                example: Some("--watch 5m --log-file probe.log"),
+            },
+            OptionHelp {
+                short: None,
+                long: "serve",
+                value: "<ADDR>",
+                description: "In watch mode, serve recent samples over HTTP for the Grafana JSON and Infinity datasources",
+                example: Some("--watch 1m --serve 127.0.0.1:9180"),
+            },
+            OptionHelp {
+                short: None,
+                long: "serve-cors",
+                value: "<ORIGIN>",
+                description: "Let web pages from ORIGIN read the --serve endpoints from a browser",
+                example: Some("--serve-cors https://grafana.example.com"),
+            },
+            OptionHelp {
+                short: None,
+                long: "notify",
+                value: "<TARGET>",
+                description: "In watch mode, show a desktop notification when a cycle fails its health check and when it recovers",
+                example: Some("--watch 5m --notify desktop"),
+            },
+            OptionHelp {
+                short: None,
+                long: "honor-retry-after",
+                value: "",
+                description: "In watch mode, wait as long as a throttling server asks with Retry-After before the next cycle",
+                example: Some("--watch 1m --honor-retry-after"),
+            },
+            OptionHelp {
+                short: None,
+                long: "control-socket",
+                value: "<PATH>",
+                description: "Control endpoint of a watch-mode run, used by `ctl pause|resume|flush-report|rotate-log|windows`",
+                example: Some("ctl pause --control-socket /run/nlt.sock"),
+            },
+            OptionHelp {
+                short: None,
+                long: "shard",
+                value: "<I/N>",
+                description: "Test only shard I of N of the target list; combine the shards' exports with `merge`",
+                example: Some("--shard 2/4 --export shard2.json"),
+            },
+            OptionHelp {
+                short: None,
+                long: "locale",
+                value: "<LOCALE>",
+                description: "Language of DNS configuration names: en or zh (follows LANG by default)",
+                example: Some("--locale zh"),
+            },
+            OptionHelp {
+                short: None,
+                long: "dns-label",
+                value: "<ID=LABEL>",
+                description: "Report a DNS configuration (system, a DNS server or a DoH URL) under LABEL; repeatable",
+                example: Some("--dns-label 8.8.8.8=Google --dns-label system=ISP"),
+            },
+            OptionHelp {
+                short: None,
+                long: "resolver-options",
+                value: "<ID=OPTIONS>",
+                description: "Resolver settings for system or DNS servers: timeout:DURATION attempts:N rotate cache:N strategy:NAME; repeatable",
+                example: Some("--resolver-options \"8.8.8.8=timeout:1 attempts:0 cache:0\""),
+            },
+            OptionHelp {
+                short: None,
+                long: "compare-public",
+                value: "<SOURCE>",
+                description: "Compare each host's connect time with public measurements (atlas, atlas:CC or a JSON file)",
+                example: Some("--compare-public atlas:DE"),
+            },
+            OptionHelp {
+                short: None,
+                long: "geo",
+                value: "[LAT,LON]",
+                description: "Show how far connect times are above the speed-of-light minimum for each server's distance",
+                example: Some("--geo=52.52,13.40"),
+            },
+            OptionHelp {
+                short: None,
+                long: "bind",
+                value: "<INTERFACE|ADDRESS>",
+                description: "Send requests from a network interface (Linux, macOS) or local address",
+                example: Some("--bind wlan0"),
+            },
+            OptionHelp {
+                short: None,
+                long: "all-interfaces",
+                value: "",
+                description: "Run the tests from each usable network interface and compare them per DNS configuration",
+                example: Some("--all-interfaces"),
+            },
+            OptionHelp {
+                short: None,
+                long: "pool-options",
+                value: "<OPTIONS>",
+                description: "Connection pool settings: idle-per-host:N idle-timeout:DURATION|off keepalive:DURATION|off nodelay:on|off",
+                example: Some("--pool-options \"idle-per-host:0\""),
+            },
+            OptionHelp {
+                short: None,
+                long: "upload",
+                value: "<[METHOD:]SIZE>",
+                description: "POST (or PUT) a generated body of SIZE and time the upload and the server's answer separately",
+                example: Some("--upload put:1MiB"),
+            },
+            OptionHelp {
+                short: None,
+                long: "early-hints",
+                value: "",
+                description: "Time 1xx responses such as 103 Early Hints ahead of the final response, with an extra HTTP/1.1 request",
+                example: Some("--early-hints"),
+            },
+            OptionHelp {
+                short: None,
+                long: "capture-on-anomaly",
+                value: "<DURATION>",
+                description: "After a request fails or takes longer than this, capture the traffic to its target with tcpdump or dumpcap",
+                example: Some("--capture-on-anomaly 500ms"),
+            },
+            OptionHelp {
+                short: None,
+                long: "trace-requests",
+                value: "",
+                description: "Write a debug trace of every request to traces/trace-RUN_ID.jsonl, keyed by run and sample",
+                example: Some("--trace-requests"),
+            },
+            OptionHelp {
+                short: None,
+                long: "network-history",
+                value: "",
+                description: "Compare results with earlier runs on the same network (Wi-Fi, gateway, public network)",
+                example: Some("--network-history"),
+            },
+            OptionHelp {
+                short: None,
+                long: "network-baseline",
+                value: "",
+                description: "Make the current network the baseline for --network-history",
+                example: Some("--network-history --network-baseline"),
+            },
+            OptionHelp {
+                short: None,
+                long: "plan",
+                value: "",
+                description: "Print the execution plan and estimated duration without sending requests",
+                example: Some("--count 100 --plan"),
+            },
+            OptionHelp {
+                short: None,
+                long: "simulate",
+                value: "<SPEC>",
+                description: "Send no requests; draw response times from a normal or lognormal distribution with spikes and loss bursts",
+                example: Some("--simulate lognormal:median=60ms,spikes=2%,loss=1%"),
+            },
+            OptionHelp {
+                short: None,
+                long: "check-config",
+                value: "",
+                description: "Validate the configuration and show where each setting came from",
+                example: Some("--url https://example.com --count 20 --check-config"),
+            },
+            OptionHelp {
+                short: None,
+                long: "strict",
+                value: "",
+                description: "Fail on unknown .env keys, unusable target URLs and unreachable DoH providers instead of warning",
+                example: Some("--strict --check-config"),
+            },
             OptionHelp {
                 short: None,
                 long: "test-original",
@@ -270,6 +685,16 @@ impl HelpSystem {
                 command: "network-latency-tester --url https://example.com --debug --no-color",
                 description: "Run with debug output and no color formatting",
             },
+            ExampleHelp {
+                title: "Guided setup",
+                command: "network-latency-tester init",
+                description: "Answer a few questions to create a checked .env configuration file",
+            },
+            ExampleHelp {
+                title: "Query exported results",
+                command: "network-latency-tester query results.json --where \"config~cloudflare AND url~api\" --metric p95 --group-by url",
+                description: "Show p95 latency of Cloudflare configurations for API URLs, grouped by URL",
+            },
             ExampleHelp {
                 title: "Check for updates",
                 command: "network-latency-tester --update",
@@ -474,6 +899,19 @@ impl HelpSystem {
         help.push_str("2. .env file in current directory (if present)\n");
         help.push_str("3. Command-line arguments (override both)\n\n");
 
+        help.push_str("PREFIXED NAMES:\n");
+        help.push_str(&format!(
+            "Each variable can also be set as {}<NAME>, which wins over the plain name. Short forms:\n",
+            ENV_PREFIX
+        ));
+        for (alias, key) in PREFIX_ALIASES {
+            help.push_str(&format!("  {}{} = {}\n", ENV_PREFIX, alias, key));
+        }
+        for (name, option) in PREFIXED_OPTIONS {
+            help.push_str(&format!("  {} = {}\n", name, option));
+        }
+        help.push('\n');
+
         help.push_str("SUPPORTED VARIABLES:\n");
         let env_vars = EnvManager::get_supported_env_vars();
         for (var_name, description, example) in env_vars {
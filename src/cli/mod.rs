@@ -4,7 +4,22 @@ pub mod help;
 
 pub use help::HelpSystem;
 
-use clap::{Parser, ArgAction};
+use crate::{
+    baseline::BaselineSpec,
+    client::simulate::Simulation,
+    geo::ClientLocation,
+    control::ControlCommand,
+    notification::NotifyTarget,
+    models::Config,
+    types::{BindTarget, DisplayNames, DnsConfig, DohBootstrap, Locale, PoolOptions, ResolverOptions, MetricColumn, RedirectPolicy, Shard, SortKey, TlsVersion, Upload, UserAgent},
+    utils::duration::format_duration,
+};
+use clap::{Parser, Args, Subcommand, ArgAction};
+use std::net::SocketAddr;
+#[cfg(feature = "mock")]
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Network Latency Tester - A high-performance tool for measuring network connectivity
 #[derive(Parser, Debug, Clone)]
@@ -16,9 +31,31 @@ pub struct Cli {
     #[arg(short, long, default_value_t = crate::defaults::DEFAULT_TEST_COUNT)]
     pub count: u32,
 
-    /// Request timeout in seconds
-    #[arg(short, long, value_parser = parse_duration, default_value_t = crate::defaults::DEFAULT_TIMEOUT.as_secs())]
-    pub timeout: u64,
+    /// Request timeout, e.g. "10", "2s", "500ms" or "1m30s" (bare numbers are seconds) [default: 10s]
+    #[arg(short, long, value_parser = parse_duration, value_name = "DURATION")]
+    pub timeout: Option<Duration>,
+
+    /// Time budget for the whole run, e.g. "10m"; iteration counts are reduced to fit it
+    #[arg(long, value_parser = parse_max_runtime, value_name = "DURATION")]
+    pub max_runtime: Option<Duration>,
+
+    /// Keep adding iterations past --count until the mean and p95 are within
+    /// this precision (default 5%), up to 200 iterations
+    #[arg(long, value_parser = parse_precision, value_name = "PRECISION",
+          num_args = 0..=1, default_missing_value = "5%")]
+    pub auto_count: Option<f64>,
+
+    /// Sanity check in about 10 seconds: one target per tag group, 3
+    /// iterations, the 3 fastest resolvers and a 2s timeout, with a compact
+    /// summary; options given explicitly still apply
+    #[arg(long)]
+    pub quick: bool,
+
+    /// Collect evidence for a support ticket: up to 200 iterations, packet
+    /// loss, full diagnostics with traceroutes and certificates, saved as an
+    /// HTML and JSON bundle; options given explicitly still apply
+    #[arg(long, conflicts_with = "quick")]
+    pub thorough: bool,
 
     /// Force colored output
     #[arg(long)]
@@ -40,11 +77,17 @@ pub struct Cli {
     #[arg(long = "url", action = ArgAction::Append)]
     pub urls: Vec<String>,
 
+    /// File listing target URLs, one per line with optional comma-separated tags
+    /// ("#" starts a comment); combined with any --url
+    #[arg(long, value_name = "PATH")]
+    pub url_file: Option<PathBuf>,
+
     /// Test the original target URL from bash script
     #[arg(long)]
     pub test_original: bool,
 
-    /// Custom DNS servers (comma-separated)
+    /// Custom DNS servers (comma-separated); addresses joined with "+" are one
+    /// configuration with several servers
     #[arg(long)]
     pub dns_servers: Option<String>,
 
@@ -52,6 +95,201 @@ pub struct Cli {
     #[arg(long)]
     pub doh_providers: Option<String>,
 
+    /// Resolver chain tried in order until one answers, e.g.
+    /// "223.5.5.5>8.8.8.8>https://dns.google/dns-query" (repeatable)
+    #[arg(long = "dns-chain", value_parser = parse_dns_chain, value_name = "CHAIN")]
+    pub dns_chains: Vec<DnsConfig>,
+
+    /// Search domains for short host names such as http://intranet, in place
+    /// of the system's (comma-separated; "" for none)
+    #[arg(long, value_parser = parse_search_domains, value_name = "DOMAINS")]
+    pub search_domains: Option<String>,
+
+    /// Short names with fewer dots than this are tried with the search domains first
+    #[arg(long, value_parser = parse_ndots, value_name = "N")]
+    pub ndots: Option<u8>,
+
+    /// Also test each short target under its names qualified with the search domains
+    #[arg(long)]
+    pub test_search_paths: bool,
+
+    /// Ranking score weights, e.g. "speed=0.5,reliability=0.3,consistency=0.2"
+    /// (dimensions: speed, reliability, consistency, p95, jitter, loss)
+    #[arg(long, value_name = "WEIGHTS")]
+    pub scoring: Option<String>,
+
+    /// Count timed-out requests as lasting at least the timeout in percentiles
+    #[arg(long)]
+    pub censor_timeouts: bool,
+
+    /// Read kernel TCP statistics (RTT, retransmissions, congestion window)
+    /// after each request; Linux only
+    #[arg(long)]
+    pub socket_stats: bool,
+
+    /// After the tests, send a burst of UDP packets (default 50) to each route
+    /// and custom DNS server to measure packet loss and reordering
+    #[arg(long, value_parser = parse_loss_probe, value_name = "PACKETS",
+          num_args = 0..=1, default_missing_value = "50")]
+    pub loss_probe: Option<u16>,
+
+    /// After the tests, send ICMP echo requests (default 10) to each address
+    /// the requests went to, and show the round trip next to HTTP latency
+    #[arg(long, value_parser = parse_ping, value_name = "COUNT",
+          num_args = 0..=1, default_missing_value = "10")]
+    pub ping: Option<u16>,
+
+    /// After the tests, compare each host's connect time with public measurements:
+    /// atlas (RIPE Atlas), atlas:CC (probes in country CC) or a JSON file of medians
+    #[arg(long, value_parser = parse_baseline_spec, value_name = "SOURCE")]
+    pub compare_public: Option<BaselineSpec>,
+
+    /// After the tests, locate the client and each server with an IP geolocation
+    /// service and show how far connect times are above the speed-of-light minimum;
+    /// give LAT,LON to set the client's position instead of looking it up
+    #[arg(long, value_parser = parse_client_location, value_name = "LAT,LON",
+          num_args = 0..=1, default_missing_value = "auto")]
+    pub geo: Option<ClientLocation>,
+
+    /// Identify the network (Wi-Fi name, gateway, public network) and compare
+    /// results with earlier runs on the same network, kept across runs
+    #[arg(long)]
+    pub network_history: bool,
+
+    /// Make the current network the baseline that runs on other networks are
+    /// pointed out against (the first network recorded is by default)
+    #[arg(long, requires = "network_history")]
+    pub network_baseline: bool,
+
+    /// Whether requests follow redirects: follow, none (measure the redirect
+    /// response itself) or https-only (fail a request redirected off HTTPS)
+    #[arg(long, value_parser = parse_redirect_policy, value_name = "POLICY")]
+    pub redirect_policy: Option<RedirectPolicy>,
+
+    /// Redirects a request may follow before it fails (default: 10)
+    #[arg(long, value_parser = parse_max_redirects, value_name = "N")]
+    pub max_redirects: Option<u32>,
+
+    /// User-Agent and headers to send: tool, curl, chrome (desktop Chrome with
+    /// its navigation headers), mobile-safari, or a custom User-Agent string
+    #[arg(long, value_parser = parse_user_agent, value_name = "PRESET")]
+    pub user_agent: Option<UserAgent>,
+
+    /// Speak HTTP/2 without negotiating it: h2c on http:// URLs, and only h2
+    /// offered through ALPN on https:// URLs
+    #[arg(long)]
+    pub http2_prior_knowledge: bool,
+
+    /// Fail requests that are not answered over HTTP/2 instead of measuring
+    /// the HTTP/1.1 connection ALPN fell back to
+    #[arg(long)]
+    pub no_alpn_fallback: bool,
+
+    /// Oldest TLS version to negotiate (1.2 or 1.3)
+    #[arg(long, value_parser = parse_tls_version, value_name = "VERSION")]
+    pub tls_min_version: Option<TlsVersion>,
+
+    /// Newest TLS version to negotiate (1.2 or 1.3)
+    #[arg(long, value_parser = parse_tls_version, value_name = "VERSION")]
+    pub tls_max_version: Option<TlsVersion>,
+
+    /// Resume TLS sessions from earlier connections (on) or always make a
+    /// full handshake (off); either way requests use rustls and resumed
+    /// handshakes are reported apart from full ones
+    #[arg(long, value_parser = parse_tls_resumption, value_name = "on|off")]
+    pub tls_resumption: Option<bool>,
+
+    /// Open a new connection to each DoH provider for every query instead of
+    /// reusing one, so lookups include the provider's TLS setup
+    #[arg(long)]
+    pub doh_fresh_connections: bool,
+
+    /// Resolve a DoH provider's host name up front, to a pinned IP or through
+    /// the system resolver, so no timed query includes the lookup; PROVIDER is
+    /// a DoH URL or its host name (repeatable)
+    #[arg(long, value_parser = parse_doh_bootstrap, value_name = "PROVIDER=IP|system")]
+    pub doh_bootstrap: Vec<(String, DohBootstrap)>,
+
+    /// Before each run, verify that custom DNS configurations resolve only
+    /// through their own servers; afterwards, check the system DNS settings are unchanged
+    #[arg(long)]
+    pub sandbox_dns: bool,
+
+    /// Flush the OS resolver cache before testing, so that System DNS is
+    /// measured with a cold cache (usually needs administrator rights)
+    #[arg(long)]
+    pub flush_dns_cache: bool,
+
+    /// Run on a single thread with smaller buffers, for routers and small VMs
+    /// (chosen automatically on machines with few cores or little memory)
+    #[arg(long)]
+    pub single_thread: bool,
+
+    /// Compare the system clock against this NTP server (host or host:port)
+    /// and report the offset with the results
+    #[arg(long, value_parser = parse_ntp_server, value_name = "HOST")]
+    pub ntp_server: Option<String>,
+
+    /// Language of DNS configuration names (en, zh); follows LANG by default
+    #[arg(long, value_parser = parse_locale, value_name = "LOCALE")]
+    pub locale: Option<Locale>,
+
+    /// Report a DNS configuration under LABEL; ID is "system", a DNS server,
+    /// a DoH URL or a chain (repeatable)
+    #[arg(long = "dns-label", value_parser = parse_dns_label, value_name = "ID=LABEL")]
+    pub dns_labels: Vec<(String, String)>,
+
+    /// Resolver settings for a DNS configuration, like resolv.conf options:
+    /// "8.8.8.8=timeout:1 attempts:0 rotate cache:0"; ID is "system" or DNS
+    /// servers joined by "+", whose lookups "strategy:first|round-robin|race-all|random"
+    /// spreads over them (repeatable)
+    #[arg(long = "resolver-options", value_parser = parse_resolver_options, value_name = "ID=OPTIONS")]
+    pub resolver_options: Vec<(String, ResolverOptions)>,
+
+    /// Test only shard I of N of the target list, e.g. "2/4"; combine the
+    /// exports of all shards with the `merge` subcommand
+    #[arg(long, value_parser = parse_shard, value_name = "I/N")]
+    pub shard: Option<Shard>,
+
+    /// Send requests from this network interface (Linux and macOS) or local
+    /// address instead of the one the routing table picks
+    #[arg(long, value_parser = parse_bind_target, value_name = "INTERFACE|ADDRESS")]
+    pub bind: Option<BindTarget>,
+
+    /// Run the tests once from each network interface that is up and has an
+    /// address (ethernet, Wi-Fi, LTE...) and compare the interfaces per DNS
+    /// configuration; Linux and macOS
+    #[arg(long, conflicts_with = "bind")]
+    pub all_interfaces: bool,
+
+    /// Connection pool settings as space-separated options: idle-per-host:N,
+    /// idle-timeout:DURATION|off, keepalive:DURATION|off, nodelay:on|off,
+    /// e.g. "idle-per-host:0" to time a new connection on every request
+    #[arg(long, value_parser = parse_pool_options, value_name = "OPTIONS")]
+    pub pool_options: Option<PoolOptions>,
+
+    /// Send each request as a POST (or PUT) with a generated body of this
+    /// size and time sending the body and the server's answer separately,
+    /// e.g. 256KiB or put:10MB
+    #[arg(long, value_parser = parse_upload, value_name = "[METHOD:]SIZE")]
+    pub upload: Option<Upload>,
+
+    /// After each successful request, send the same GET over HTTP/1.1 to the
+    /// same address and time informational responses such as 103 Early
+    /// Hints ahead of the final response
+    #[arg(long)]
+    pub early_hints: bool,
+
+    /// When a request fails or takes longer than this, e.g. "500ms", capture
+    /// the traffic to its target with tcpdump or dumpcap for a few seconds
+    #[arg(long, value_parser = parse_capture_threshold, value_name = "DURATION")]
+    pub capture_on_anomaly: Option<Duration>,
+
+    /// Write a debug trace of every request (addresses, connection, TLS,
+    /// redirects, response headers) to traces/trace-RUN_ID.jsonl
+    #[arg(long)]
+    pub trace_requests: bool,
+
     /// Show help for specific topic (config, dns, examples, timeout, output)
     #[arg(long, value_name = "TOPIC")]
     pub help_topic: Option<String>,
@@ -67,6 +305,322 @@ pub struct Cli {
     /// Force version change, including downgrades
     #[arg(short = 'f', long, requires = "update")]
     pub force: bool,
+
+    /// Write the full results as JSON to this file (for use with `query`), or
+    /// one row per request with its timestamps when the file ends in .csv
+    #[arg(long, value_name = "FILE", env = "NLT_EXPORT")]
+    pub export: Option<PathBuf>,
+
+    /// Also write one row per request to this CSV file, next to any --export
+    #[arg(long, value_name = "FILE", env = "NLT_EXPORT_CSV")]
+    pub export_csv: Option<PathBuf>,
+
+    /// Number of slowest individual requests to list with their phase breakdown (0 to hide)
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub slowest: usize,
+
+    /// Rank each target's configurations in the results table by this metric
+    /// (mean, p95, success, score)
+    #[arg(long, value_parser = parse_sort_key, value_name = "METRIC")]
+    pub sort_by: Option<SortKey>,
+
+    /// Show only the best and the worst N configurations of each target in the
+    /// results table and summarize the rest in one row
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), value_name = "N")]
+    pub top: Option<u32>,
+
+    /// Metric columns of the results table, e.g. "dns,tcp,ttfb,total,p95,success"
+    /// (also min-max, level and early-hints; defaults depend on --verbose)
+    #[arg(long, value_delimiter = ',', value_parser = parse_metric_column, value_name = "COLUMNS")]
+    pub columns: Vec<MetricColumn>,
+
+    /// Print the execution plan (URLs × DNS configs × iterations, estimated duration) without sending requests
+    #[arg(long)]
+    pub plan: bool,
+
+    /// Send no requests; draw each response time from DISTRIBUTION[:KEY=VALUE,...],
+    /// e.g. "lognormal:median=60ms,spikes=2%,loss=1%", to rehearse thresholds,
+    /// dashboards and notifications
+    #[arg(long, value_parser = parse_simulation, value_name = "SPEC")]
+    pub simulate: Option<Simulation>,
+
+    /// Validate the configuration and print every setting with the layer it came from
+    /// (command line, environment, .env, preferences or default), without sending requests
+    #[arg(long)]
+    pub check_config: bool,
+
+    /// Fail instead of warning or skipping: on unknown keys in .env or the preferences
+    /// file, target URLs that cannot be used, and DoH providers that do not answer
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Repeat the tests every INTERVAL until interrupted, reloading .env when it changes
+    /// (targets may then come from TARGET_URLS instead of --url)
+    #[arg(long, value_parser = parse_watch_interval, value_name = "INTERVAL", env = "NLT_INTERVAL")]
+    pub watch: Option<Duration>,
+
+    /// Append a line per watch cycle to this file (start a new one with `ctl rotate-log`)
+    #[arg(long, value_name = "FILE", requires = "watch", env = "NLT_LOG_FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Serve the samples of recent cycles over HTTP on ADDR, for the Grafana JSON
+    /// and Infinity datasources (e.g. 127.0.0.1:9180)
+    #[arg(long, value_name = "ADDR", requires = "watch", env = "NLT_SERVE")]
+    pub serve: Option<SocketAddr>,
+
+    /// Let web pages from ORIGIN read the --serve endpoints from a browser
+    /// (e.g. https://grafana.example.com); no page can without it
+    #[arg(long, value_name = "ORIGIN", requires = "serve", env = "NLT_SERVE_CORS")]
+    pub serve_cors: Option<String>,
+
+    /// In watch mode, notify TARGET (desktop) when a cycle fails its health check and when it recovers
+    #[arg(long, value_parser = parse_notify_target, value_name = "TARGET", requires = "watch", env = "NLT_NOTIFY")]
+    pub notify: Option<NotifyTarget>,
+
+    /// In watch mode, wait before the next cycle as long as a throttling server asked
+    /// with Retry-After, when that is longer than the interval
+    #[arg(long, requires = "watch", env = "NLT_HONOR_RETRY_AFTER")]
+    pub honor_retry_after: bool,
+
+    /// Control endpoint of a watch-mode run: a Unix socket, or a named pipe on Windows
+    #[arg(long, value_name = "PATH", global = true)]
+    pub control_socket: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands that operate without running a test
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Filter, slice and summarize results written with --export
+    Query(QueryArgs),
+    /// Interactively create a .env configuration file
+    Init(InitArgs),
+    /// Send a command to a running --watch instance
+    Ctl(CtlArgs),
+    /// Combine results exported by the shards of a --shard run into one report
+    Merge(MergeArgs),
+    /// Feed results exported with --export back through the statistics, output and alerts
+    Replay(ReplayArgs),
+    /// Show latency by hour of day and day of week from results exported with --export
+    Heatmap(HeatmapArgs),
+    /// Time the configured DoH providers, splitting connection setup from query time
+    DnsBench(DnsBenchArgs),
+    /// Run two configurations interleaved in one process and compare them round by round
+    Ab(AbArgs),
+    /// Show or clear the caches kept on disk
+    Cache(CacheArgs),
+    /// Show or change the preferences saved in the platform's config directory
+    Config(ConfigArgs),
+    /// Time the executor, statistics engine and formatter on simulated runs
+    #[command(hide = true)]
+    BenchInternal(BenchInternalArgs),
+    /// Answer DNS queries over UDP and DoH from fixed records, to check a resolver setup offline
+    #[cfg(feature = "mock")]
+    ServeMockDns(ServeMockDnsArgs),
+}
+
+/// Arguments for the `serve-mock-dns` subcommand
+#[cfg(feature = "mock")]
+#[derive(Args, Debug, Clone)]
+pub struct ServeMockDnsArgs {
+    /// Address to answer plain DNS queries on, over UDP
+    #[arg(long, default_value = "127.0.0.1:5300", value_name = "ADDR")]
+    pub listen: std::net::SocketAddr,
+
+    /// Address to serve the JSON DoH API on, at `http://ADDR/dns-query`
+    #[arg(long, default_value = "127.0.0.1:8053", value_name = "ADDR")]
+    pub doh_listen: std::net::SocketAddr,
+
+    /// Record to answer, e.g. `app.test=192.0.2.1,2001:db8::1`; `*` answers any name (repeatable, default `*=127.0.0.1`)
+    #[arg(long = "record", value_parser = parse_mock_record, value_name = "NAME=IP[,IP...]")]
+    pub records: Vec<(String, Vec<IpAddr>)>,
+
+    /// Time to wait before answering each query, e.g. `50ms`
+    #[arg(long, value_parser = parse_mock_delay, value_name = "DURATION")]
+    pub delay: Option<Duration>,
+}
+
+/// Arguments for the `config` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+/// What the `config` subcommand does
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Show the saved preferences and where they are kept
+    Show,
+    /// Save a preference, e.g. `config set DNS_SERVERS 8.8.8.8,1.1.1.1`; an empty value removes it
+    Set {
+        /// ENABLE_COLOR, DISPLAY_LOCALE, DNS_SERVERS, DOH_PROVIDERS or DNS_CHAINS
+        #[arg(value_name = "KEY")]
+        key: String,
+        #[arg(value_name = "VALUE")]
+        value: String,
+    },
+}
+
+/// Arguments for the `cache` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+/// What the `cache` subcommand does
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAction {
+    /// Show the location, size, entries and age of each cache
+    Stats,
+    /// Delete every cache; each is rebuilt the next time it is needed
+    Clear,
+}
+
+/// Arguments for the `merge` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct MergeArgs {
+    /// Results files written with --export, one per shard
+    #[arg(value_name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Write the merged results as JSON to this file
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the `replay` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct ReplayArgs {
+    /// Results files written with --export, replayed one after another as watch cycles
+    #[arg(value_name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Keep the pace the requests were recorded at, including the gaps between files
+    #[arg(long)]
+    pub realtime: bool,
+
+    /// With --realtime, replay this many times as fast as recorded
+    #[arg(long, value_parser = parse_speed, default_value = "1", value_name = "FACTOR", requires = "realtime")]
+    pub speed: f64,
+
+    /// Notify TARGET (desktop) when a replayed run fails its health check and when it recovers
+    #[arg(long, value_parser = parse_notify_target, value_name = "TARGET")]
+    pub notify: Option<NotifyTarget>,
+}
+
+/// Arguments for the `heatmap` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct HeatmapArgs {
+    /// Results files written with --export, such as those of a long watch session
+    #[arg(value_name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Also write the heatmap as an HTML chart to this file
+    #[arg(long, value_name = "FILE")]
+    pub html: Option<PathBuf>,
+
+    /// Count hours in UTC instead of the local time zone
+    #[arg(long)]
+    pub utc: bool,
+}
+
+/// Arguments for the `dns-bench` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct DnsBenchArgs {
+    /// Domain to look up
+    #[arg(long, default_value = "example.com", value_name = "DOMAIN")]
+    pub domain: String,
+
+    /// Queries to send over each warm connection, after the first one
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_DNS_BENCH_QUERIES,
+          value_parser = clap::value_parser!(u32).range(1..=100), value_name = "N")]
+    pub queries: u32,
+}
+
+/// Arguments for the `ab` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct AbArgs {
+    /// TOML file of configuration variables for side A, e.g. `HTTP2_PRIOR_KNOWLEDGE = false`
+    #[arg(long, value_name = "FILE")]
+    pub config_a: PathBuf,
+
+    /// TOML file of configuration variables for side B
+    #[arg(long, value_name = "FILE")]
+    pub config_b: PathBuf,
+
+    /// Rounds to run; each sends one request per target and DNS configuration for both sides
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_AB_ROUNDS,
+          value_parser = clap::value_parser!(u32).range(2..=10000), value_name = "N")]
+    pub rounds: u32,
+}
+
+/// Arguments for the hidden `bench-internal` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct BenchInternalArgs {
+    /// Timed runs of each workload
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_BENCH_RUNS,
+          value_parser = clap::value_parser!(u32).range(1..=1000), value_name = "N")]
+    pub runs: u32,
+
+    /// Simulated targets
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..=1000), value_name = "N")]
+    pub targets: u32,
+
+    /// Simulated DNS configurations
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=254), value_name = "N")]
+    pub configs: u32,
+
+    /// Requests per target and DNS configuration
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u32).range(1..=1000), value_name = "N")]
+    pub iterations: u32,
+}
+
+/// Arguments for the `ctl` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct CtlArgs {
+    /// Command to send: pause, resume, flush-report, rotate-log or windows
+    #[arg(value_parser = parse_control_command, value_name = "COMMAND")]
+    pub command: ControlCommand,
+}
+
+/// Arguments for the `init` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct InitArgs {
+    /// Configuration file to write
+    #[arg(long, value_name = "FILE", default_value = ".env")]
+    pub output: PathBuf,
+
+    /// Overwrite an existing file without asking
+    #[arg(long)]
+    pub force: bool,
+
+    /// Skip the reachability checks on URLs, DNS servers and DoH providers
+    #[arg(long)]
+    pub no_check: bool,
+}
+
+/// Arguments for the `query` subcommand
+#[derive(Args, Debug, Clone)]
+pub struct QueryArgs {
+    /// Results file written with --export
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Filter expression, e.g. "config~cloudflare AND url~api"
+    #[arg(long = "where", value_name = "EXPR")]
+    pub filter: Option<String>,
+
+    /// Metric to report (avg, min, max, std_dev, p50, p90, p95, p99, dns_ms, tcp_ms, first_byte_ms, success_rate, samples, attempts)
+    #[arg(long, value_name = "FIELD", default_value = "avg")]
+    pub metric: String,
+
+    /// Group rows by config, url or dns
+    #[arg(long, value_name = "FIELD")]
+    pub group_by: Option<String>,
 }
 
 impl Cli {
@@ -77,14 +631,23 @@ impl Cli {
             return Err("Cannot specify both --color and --no-color".to_string());
         }
 
-        // Skip URL validation if in update mode
-        if !self.update {
-            // Check that at least one URL is provided (either via --url or --test-original)
-            if self.urls.is_empty() && !self.test_original {
-                return Err("Must specify at least one URL via --url or use --test-original".to_string());
+        // Skip URL validation if in update mode or running a subcommand
+        if !self.update && self.command.is_none() {
+            // Check that at least one URL is provided (either via --url or --test-original);
+            // watch mode can take its targets from TARGET_URLS so that they can be reloaded
+            if self.urls.is_empty() && self.url_file.is_none() && !self.test_original && self.watch.is_none() {
+                return Err("Must specify at least one URL via --url or --url-file, or use --test-original".to_string());
             }
         }
 
+        if self.watch.is_some() && self.plan {
+            return Err("--plan cannot be combined with --watch".to_string());
+        }
+
+        if self.watch.is_some() && self.check_config {
+            return Err("--check-config cannot be combined with --watch".to_string());
+        }
+
         // Validate update-related arguments
         if self.version.is_some() && !self.update {
             return Err("--version requires --update to be specified".to_string());
@@ -94,7 +657,13 @@ impl Cli {
             return Err("--force requires --update to be specified".to_string());
         }
 
+        #[cfg(not(feature = "updater"))]
+        if self.update {
+            return Err("--update is not available: this build was compiled without the `updater` feature".to_string());
+        }
+
         // Validate version format if provided
+        #[cfg(feature = "updater")]
         if let Some(ref version_str) = self.version {
             if let Err(e) = crate::updater::Version::parse(version_str) {
                 return Err(format!("Invalid version format '{}': {}", version_str, e));
@@ -157,7 +726,7 @@ impl Cli {
         
         summary.push_str("Configuration Summary:\n");
         summary.push_str(&format!("  Test count: {}\n", self.count));
-        summary.push_str(&format!("  Timeout: {}s\n", self.timeout));
+        summary.push_str(&format!("  Timeout: {}\n", format_duration(self.timeout.unwrap_or(crate::defaults::DEFAULT_TIMEOUT))));
         summary.push_str(&format!("  Colored output: {}\n", self.use_colors()));
         summary.push_str(&format!("  Verbose mode: {}\n", self.verbose));
         summary.push_str(&format!("  Debug mode: {}\n", self.debug));
@@ -165,10 +734,18 @@ impl Cli {
         if !self.urls.is_empty() {
             summary.push_str(&format!("  Custom URLs: {}\n", self.urls.join(", ")));
         }
+
+        if let Some(ref url_file) = self.url_file {
+            summary.push_str(&format!("  URL file: {}\n", url_file.display()));
+        }
         
         if self.test_original {
             summary.push_str("  Testing original URL: Yes\n");
         }
+
+        if self.strict {
+            summary.push_str("  Strict mode: true\n");
+        }
         
         if let Some(ref dns_servers) = self.dns_servers {
             summary.push_str(&format!("  DNS servers: {}\n", dns_servers));
@@ -177,11 +754,210 @@ impl Cli {
         if let Some(ref doh_providers) = self.doh_providers {
             summary.push_str(&format!("  DoH providers: {}\n", doh_providers));
         }
+
+        for chain in &self.dns_chains {
+            summary.push_str(&format!("  DNS chain: {}\n", chain.id()));
+        }
+
+        if let Some(ref search_domains) = self.search_domains {
+            summary.push_str(&format!("  Search domains: {}\n", if search_domains.is_empty() { "none" } else { search_domains }));
+        }
+
+        if let Some(ndots) = self.ndots {
+            summary.push_str(&format!("  Ndots: {}\n", ndots));
+        }
+
+        if self.test_search_paths {
+            summary.push_str("  Testing search paths: Yes\n");
+        }
+        
+        if let Some(ref scoring) = self.scoring {
+            summary.push_str(&format!("  Scoring weights: {}\n", scoring));
+        }
+
+        if let Some(max_runtime) = self.max_runtime {
+            summary.push_str(&format!("  Max runtime: {}\n", format_duration(max_runtime)));
+        }
+
+        if let Some(precision) = self.auto_count {
+            summary.push_str(&format!("  Auto count: until ±{}%\n", precision));
+        }
+
+        if self.quick {
+            summary.push_str("  Quick: yes\n");
+        }
+
+        if self.thorough {
+            summary.push_str("  Thorough: yes\n");
+        }
+
+        if self.censor_timeouts {
+            summary.push_str("  Censor timeouts: yes\n");
+        }
+
+        if self.socket_stats {
+            summary.push_str("  Socket stats: yes\n");
+        }
+
+        if let Some(packets) = self.loss_probe {
+            summary.push_str(&format!("  Loss probe: {} packets\n", packets));
+        }
+
+        if let Some(count) = self.ping {
+            summary.push_str(&format!("  Ping: {} echo requests\n", count));
+        }
+
+        if let Some(ref source) = self.compare_public {
+            summary.push_str(&format!("  Public baseline: {}\n", source));
+        }
+
+        match self.geo {
+            Some(ClientLocation::Detect) => summary.push_str("  Geolocation: yes\n"),
+            Some(ClientLocation::At(point)) => summary.push_str(&format!("  Geolocation: yes, client at {}\n", point)),
+            None => {}
+        }
+
+        if self.network_history {
+            summary.push_str(if self.network_baseline {
+                "  Network history: yes, current network as baseline\n"
+            } else {
+                "  Network history: yes\n"
+            });
+        }
+
+        if let Some(policy) = self.redirect_policy {
+            summary.push_str(&format!("  Redirect policy: {}\n", policy));
+        }
+
+        if let Some(max_redirects) = self.max_redirects {
+            summary.push_str(&format!("  Max redirects: {}\n", max_redirects));
+        }
+
+        if let Some(ref user_agent) = self.user_agent {
+            summary.push_str(&format!("  User agent: {}\n", user_agent));
+        }
+
+        if self.http2_prior_knowledge {
+            summary.push_str("  HTTP/2 prior knowledge: yes\n");
+        }
+
+        if self.no_alpn_fallback {
+            summary.push_str("  ALPN fallback: no\n");
+        }
+
+        if let Some(version) = self.tls_min_version {
+            summary.push_str(&format!("  Min TLS version: {}\n", version));
+        }
+
+        if let Some(version) = self.tls_max_version {
+            summary.push_str(&format!("  Max TLS version: {}\n", version));
+        }
+
+        if let Some(resumption) = self.tls_resumption {
+            summary.push_str(&format!("  TLS resumption: {}\n", if resumption { "on" } else { "off" }));
+        }
+
+        if self.doh_fresh_connections {
+            summary.push_str("  Fresh DoH connections: yes\n");
+        }
+
+        for (host, bootstrap) in &self.doh_bootstrap {
+            summary.push_str(&format!("  DoH bootstrap: {} = {}\n", host, bootstrap));
+        }
+
+        if self.sandbox_dns {
+            summary.push_str("  DNS sandbox check: yes\n");
+        }
+
+        if self.flush_dns_cache {
+            summary.push_str("  Flush DNS cache: yes\n");
+        }
+
+        if self.single_thread {
+            summary.push_str("  Single thread: yes\n");
+        }
+
+        if let Some(ref ntp_server) = self.ntp_server {
+            summary.push_str(&format!("  NTP server: {}\n", ntp_server));
+        }
+
+        if let Some(locale) = self.locale {
+            summary.push_str(&format!("  Locale: {}\n", locale));
+        }
+
+        if let Some(shard) = self.shard {
+            summary.push_str(&format!("  Shard: {}\n", shard));
+        }
+
+        if let Some(ref bind) = self.bind {
+            summary.push_str(&format!("  Bind: {}\n", bind));
+        }
+
+        if self.all_interfaces {
+            summary.push_str("  All interfaces: yes\n");
+        }
+
+        if let Some(options) = self.pool_options {
+            summary.push_str(&format!("  Pool options: {}\n", options));
+        }
+
+        if let Some(upload) = self.upload {
+            summary.push_str(&format!("  Upload: {}\n", upload));
+        }
+
+        if self.early_hints {
+            summary.push_str("  Early hints: yes\n");
+        }
+
+        if let Some(threshold) = self.capture_on_anomaly {
+            summary.push_str(&format!("  Capture on anomaly: over {}\n", format_duration(threshold)));
+        }
+
+        if self.trace_requests {
+            summary.push_str("  Request trace: yes\n");
+        }
+
+        if let Some(interval) = self.watch {
+            summary.push_str(&format!("  Watch interval: {}\n", format_duration(interval)));
+        }
+
+        if let Some(ref log_file) = self.log_file {
+            summary.push_str(&format!("  Log file: {}\n", log_file.display()));
+        }
+
+        if let Some(serve) = self.serve {
+            summary.push_str(&format!("  Serving results on: {}\n", serve));
+        }
+
+        if let Some(ref origin) = self.serve_cors {
+            summary.push_str(&format!("  Serving to browsers from: {}\n", origin));
+        }
+
+        if let Some(target) = self.notify {
+            summary.push_str(&format!("  Notify: {}\n", target));
+        }
+
+        if self.honor_retry_after {
+            summary.push_str("  Honor Retry-After: yes\n");
+        }
+
+        if let Some(ref simulation) = self.simulate {
+            summary.push_str(&format!("  Simulated: {}\n", simulation));
+        }
+
+        for (id, label) in &self.dns_labels {
+            summary.push_str(&format!("  DNS label: {} = {}\n", id, label));
+        }
+
+        for (id, options) in &self.resolver_options {
+            summary.push_str(&format!("  Resolver options: {} = {}\n", id, options));
+        }
         
         summary
     }
 
     /// Convert CLI args to UpdateArgs for update operations
+    #[cfg(feature = "updater")]
     pub fn to_update_args(&self) -> crate::updater::UpdateArgs {
         crate::updater::UpdateArgs::new(self.update, self.version.clone(), self.force)
     }
@@ -191,6 +967,103 @@ impl Cli {
         self.update
     }
 
+    /// Get the query arguments if the `query` subcommand was given
+    pub fn query_args(&self) -> Option<&QueryArgs> {
+        match &self.command {
+            Some(Command::Query(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the init arguments if the `init` subcommand was given
+    pub fn init_args(&self) -> Option<&InitArgs> {
+        match &self.command {
+            Some(Command::Init(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the merge arguments if the `merge` subcommand was given
+    pub fn merge_args(&self) -> Option<&MergeArgs> {
+        match &self.command {
+            Some(Command::Merge(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the replay arguments if the `replay` subcommand was given
+    pub fn replay_args(&self) -> Option<&ReplayArgs> {
+        match &self.command {
+            Some(Command::Replay(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the heatmap arguments if the `heatmap` subcommand was given
+    pub fn heatmap_args(&self) -> Option<&HeatmapArgs> {
+        match &self.command {
+            Some(Command::Heatmap(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the dns-bench arguments if the `dns-bench` subcommand was given
+    pub fn dns_bench_args(&self) -> Option<&DnsBenchArgs> {
+        match &self.command {
+            Some(Command::DnsBench(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the ab arguments if the `ab` subcommand was given
+    pub fn ab_args(&self) -> Option<&AbArgs> {
+        match &self.command {
+            Some(Command::Ab(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the cache arguments if the `cache` subcommand was given
+    pub fn cache_args(&self) -> Option<&CacheArgs> {
+        match &self.command {
+            Some(Command::Cache(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the bench-internal arguments if the hidden `bench-internal` subcommand was given
+    pub fn bench_internal_args(&self) -> Option<&BenchInternalArgs> {
+        match &self.command {
+            Some(Command::BenchInternal(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the config arguments if the `config` subcommand was given
+    pub fn config_args(&self) -> Option<&ConfigArgs> {
+        match &self.command {
+            Some(Command::Config(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the serve-mock-dns arguments if the `serve-mock-dns` subcommand was given
+    #[cfg(feature = "mock")]
+    pub fn serve_mock_dns_args(&self) -> Option<&ServeMockDnsArgs> {
+        match &self.command {
+            Some(Command::ServeMockDns(args)) => Some(args),
+            _ => None,
+        }
+    }
+
+    /// Get the ctl arguments if the `ctl` subcommand was given
+    pub fn ctl_args(&self) -> Option<&CtlArgs> {
+        match &self.command {
+            Some(Command::Ctl(args)) => Some(args),
+            _ => None,
+        }
+    }
+
     /// Check if interactive update mode (update without specific version)
     pub fn is_interactive_update(&self) -> bool {
         self.update && self.version.is_none()
@@ -202,24 +1075,203 @@ impl Cli {
     }
 }
 
-/// Parse duration from seconds string
-fn parse_duration(s: &str) -> Result<u64, String> {
-    // Reject strings with leading + sign or other invalid formats
-    if s.starts_with('+') || s.starts_with("0x") || s.starts_with("0X") {
-        return Err(format!("Invalid duration: {}", s));
+/// Parse a request timeout such as "10", "500ms" or "1m30s"
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let duration = crate::utils::duration::parse_duration(s).map_err(|e| e.to_string())?;
+    Config::validate_timeout(duration).map_err(|e| e.to_string())?;
+    Ok(duration)
+}
+
+/// Parse the run time budget, which must be greater than zero
+fn parse_max_runtime(s: &str) -> Result<Duration, String> {
+    let duration = crate::utils::duration::parse_duration(s).map_err(|e| e.to_string())?;
+    if duration.is_zero() {
+        return Err("Maximum run time must be greater than 0".to_string());
     }
-    
-    s.parse::<u64>()
-        .map_err(|_| format!("Invalid duration: {}", s))
-        .and_then(|secs| {
-            if secs == 0 {
-                Err("Duration must be greater than 0".to_string())
-            } else if secs > 300 {
-                Err("Duration cannot exceed 300 seconds".to_string())
-            } else {
-                Ok(secs)
-            }
-        })
+    Ok(duration)
+}
+
+/// Parse the `--capture-on-anomaly` threshold, which must be greater than zero
+fn parse_capture_threshold(s: &str) -> Result<Duration, String> {
+    Config::parse_capture_threshold(s).map_err(|e| e.message().to_string())
+}
+
+/// Parse an `--auto-count` precision such as "5%" or "2.5"
+fn parse_precision(s: &str) -> Result<f64, String> {
+    Config::parse_precision(s).map_err(|e| e.message().to_string())
+}
+
+/// Parse a `--loss-probe` packet count
+fn parse_loss_probe(s: &str) -> Result<u16, String> {
+    let packets: u16 = s.trim().parse().map_err(|_| format!("Invalid packet count '{}'", s.trim()))?;
+    Config::validate_loss_probe(packets).map_err(|e| e.message().to_string())?;
+    Ok(packets)
+}
+
+/// Parse a `--ping` echo request count
+fn parse_ping(s: &str) -> Result<u16, String> {
+    let count: u16 = s.trim().parse().map_err(|_| format!("Invalid echo request count '{}'", s.trim()))?;
+    Config::validate_ping(count).map_err(|e| e.message().to_string())?;
+    Ok(count)
+}
+
+/// Parse a resolver chain such as "223.5.5.5>8.8.8.8"
+fn parse_dns_chain(s: &str) -> Result<DnsConfig, String> {
+    DnsConfig::parse_chain(s).map_err(|e| e.message().to_string())
+}
+
+/// Check search domains such as "corp.example.com,example.com", normalized to a comma-separated list
+fn parse_search_domains(s: &str) -> Result<String, String> {
+    Config::parse_search_domains(s)
+        .map(|domains| domains.join(","))
+        .map_err(|e| e.message().to_string())
+}
+
+/// Parse an `--ndots` threshold
+fn parse_ndots(s: &str) -> Result<u8, String> {
+    let ndots: u8 = s.trim().parse().map_err(|_| format!("Invalid ndots '{}'", s.trim()))?;
+    Config::validate_ndots(ndots).map_err(|e| e.message().to_string())?;
+    Ok(ndots)
+}
+
+/// Parse a results table sort metric such as "p95"
+fn parse_sort_key(s: &str) -> Result<SortKey, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a results table column such as "ttfb"
+fn parse_metric_column(s: &str) -> Result<MetricColumn, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a redirect policy such as "https-only"
+fn parse_redirect_policy(s: &str) -> Result<RedirectPolicy, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `--max-redirects` count
+fn parse_max_redirects(s: &str) -> Result<u32, String> {
+    let max_redirects: u32 = s.trim().parse().map_err(|_| format!("Invalid redirect count '{}'", s.trim()))?;
+    if max_redirects > crate::defaults::MAX_REDIRECTS {
+        return Err(format!("Maximum redirects cannot exceed {}, got: {}", crate::defaults::MAX_REDIRECTS, max_redirects));
+    }
+    Ok(max_redirects)
+}
+
+/// Parse a User-Agent preset such as "chrome", or a custom User-Agent string
+fn parse_user_agent(s: &str) -> Result<UserAgent, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a TLS version such as "1.3"
+fn parse_tls_version(s: &str) -> Result<TlsVersion, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse `--tls-resumption on|off`
+fn parse_tls_resumption(s: &str) -> Result<bool, String> {
+    crate::types::parse_switch(s).ok_or_else(|| format!("Invalid TLS resumption setting '{}': expected on or off", s))
+}
+
+/// Parse an NTP server given as host or host:port
+fn parse_ntp_server(s: &str) -> Result<String, String> {
+    Config::validate_ntp_server(s).map_err(|e| e.to_string())?;
+    Ok(s.to_string())
+}
+
+/// Parse the interval between watch mode cycles, which must be greater than zero
+fn parse_watch_interval(s: &str) -> Result<Duration, String> {
+    let duration = crate::utils::duration::parse_duration(s).map_err(|e| e.to_string())?;
+    if duration.is_zero() {
+        return Err("Watch interval must be greater than 0".to_string());
+    }
+    Ok(duration)
+}
+
+/// Parse a control command such as "pause" or "flush-report"
+fn parse_control_command(s: &str) -> Result<ControlCommand, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `replay --speed` factor such as "10" or "0.5"
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|_| format!("Invalid speed '{}': expected a number such as 10 or 0.5", s))?;
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(format!("Invalid speed '{}': must be greater than 0", s));
+    }
+    Ok(speed)
+}
+
+/// Parse a `--notify` target such as "desktop"
+fn parse_notify_target(s: &str) -> Result<NotifyTarget, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `--simulate` specification such as "normal:mean=80ms,loss=1%"
+fn parse_simulation(s: &str) -> Result<Simulation, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `--compare-public` source such as "atlas:DE"
+fn parse_baseline_spec(s: &str) -> Result<BaselineSpec, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `--geo` client position such as "52.52,13.40"
+fn parse_client_location(s: &str) -> Result<ClientLocation, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a shard such as "2/4"
+fn parse_shard(s: &str) -> Result<Shard, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a `--bind` interface name or local address
+fn parse_bind_target(s: &str) -> Result<BindTarget, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse `--pool-options` such as "idle-per-host:0 keepalive:off"
+fn parse_pool_options(s: &str) -> Result<PoolOptions, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse an `--upload` body size with an optional method
+fn parse_upload(s: &str) -> Result<Upload, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.message().to_string())
+}
+
+/// Parse a display locale such as "en", "zh" or "zh_CN.UTF-8"
+fn parse_locale(s: &str) -> Result<Locale, String> {
+    s.parse().map_err(|e: crate::error::AppError| e.to_string())
+}
+
+/// Parse a DNS label given as ID=LABEL
+fn parse_dns_label(s: &str) -> Result<(String, String), String> {
+    DisplayNames::parse_label(s).map_err(|e| e.to_string())
+}
+
+/// Parse a `--resolver-options` pair such as "system=timeout:2 attempts:1"
+fn parse_resolver_options(s: &str) -> Result<(String, ResolverOptions), String> {
+    ResolverOptions::parse_pair(s).map_err(|e| e.to_string())
+}
+
+/// Parse a `--doh-bootstrap` pair such as "dns.google=8.8.8.8"
+fn parse_doh_bootstrap(s: &str) -> Result<(String, DohBootstrap), String> {
+    DohBootstrap::parse_pair(s).map_err(|e| e.to_string())
+}
+
+/// Parse a `serve-mock-dns --record` such as "app.test=192.0.2.1,2001:db8::1"
+#[cfg(feature = "mock")]
+fn parse_mock_record(s: &str) -> Result<(String, Vec<IpAddr>), String> {
+    crate::dns::mock_server::MockDnsServer::parse_record(s).map_err(|e| e.to_string())
+}
+
+/// Parse a `serve-mock-dns --delay` such as "50ms"
+#[cfg(feature = "mock")]
+fn parse_mock_delay(s: &str) -> Result<Duration, String> {
+    crate::utils::duration::parse_duration(s).map_err(|e| e.to_string())
 }
 
 /// Check if the terminal supports color output
@@ -267,16 +1319,16 @@ mod tests {
 
     #[test]
     fn test_cli_parsing_basic() {
-        let cli = Cli::parse_from(&["test", "--count", "5", "--timeout", "10"]);
+        let cli = Cli::parse_from(["test", "--count", "5", "--timeout", "10"]);
         assert_eq!(cli.count, 5);
-        assert_eq!(cli.timeout, 10);
+        assert_eq!(cli.timeout, Some(Duration::from_secs(10)));
         assert!(!cli.verbose);
         assert!(!cli.debug);
     }
 
     #[test]
     fn test_cli_parsing_all_options() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "test",
             "--count", "10",
             "--timeout", "30",
@@ -291,7 +1343,7 @@ mod tests {
         ]);
 
         assert_eq!(cli.count, 10);
-        assert_eq!(cli.timeout, 30);
+        assert_eq!(cli.timeout, Some(Duration::from_secs(30)));
         assert!(cli.no_color);
         assert!(cli.verbose);
         assert!(cli.debug);
@@ -305,11 +1357,11 @@ mod tests {
 
     #[test]
     fn test_cli_help_topic_methods() {
-        let cli_with_topic = Cli::parse_from(&["test", "--help-topic", "dns"]);
+        let cli_with_topic = Cli::parse_from(["test", "--help-topic", "dns"]);
         assert!(cli_with_topic.should_show_topic_help());
         assert_eq!(cli_with_topic.get_help_topic(), Some("dns"));
 
-        let cli_without_topic = Cli::parse_from(&["test"]);
+        let cli_without_topic = Cli::parse_from(["test"]);
         assert!(!cli_without_topic.should_show_topic_help());
         assert_eq!(cli_without_topic.get_help_topic(), None);
     }
@@ -330,20 +1382,24 @@ mod tests {
     #[test]
     fn test_duration_parsing() {
         // Valid durations
-        assert_eq!(parse_duration("10").unwrap(), 10);
-        assert_eq!(parse_duration("300").unwrap(), 300);
-        assert_eq!(parse_duration("1").unwrap(), 1);
+        assert_eq!(parse_duration("10").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("300").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
 
         // Invalid durations
         assert!(parse_duration("0").is_err());
         assert!(parse_duration("301").is_err());
         assert!(parse_duration("abc").is_err());
         assert!(parse_duration("-5").is_err());
+        assert!(parse_duration("0ms").is_err());
+        assert!(parse_duration("5m1s").is_err());
     }
 
     #[test]
     fn test_config_summary() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "test",
             "--count", "5",
             "--timeout", "20",
@@ -360,29 +1416,29 @@ mod tests {
 
     #[test]
     fn test_help_display() {
-        let cli = Cli::parse_from(&["test"]);
+        let cli = Cli::parse_from(["test"]);
         let help = cli.display_help();
         assert!(help.contains("Network Latency Tester"));
         assert!(help.contains("USAGE:"));
 
-        let cli_with_topic = Cli::parse_from(&["test", "--help-topic", "config"]);
+        let cli_with_topic = Cli::parse_from(["test", "--help-topic", "config"]);
         let topic_help = cli_with_topic.display_help();
         assert!(topic_help.contains("CONFIGURATION REFERENCE"));
 
-        let cli_invalid_topic = Cli::parse_from(&["test", "--help-topic", "invalid"]);
+        let cli_invalid_topic = Cli::parse_from(["test", "--help-topic", "invalid"]);
         let invalid_help = cli_invalid_topic.display_help();
         assert!(invalid_help.contains("Unknown help topic"));
     }
 
     #[test]
     fn test_use_colors_method() {
-        let cli_no_color = Cli::parse_from(&["test", "--no-color", "--test-original"]);
+        let cli_no_color = Cli::parse_from(["test", "--no-color", "--test-original"]);
         assert!(!cli_no_color.use_colors());
 
-        let cli_color = Cli::parse_from(&["test", "--color", "--test-original"]);
+        let cli_color = Cli::parse_from(["test", "--color", "--test-original"]);
         assert!(cli_color.use_colors());
 
-        let cli_default = Cli::parse_from(&["test", "--test-original"]);
+        let cli_default = Cli::parse_from(["test", "--test-original"]);
         // Result depends on environment, but should not panic
         let _uses_colors = cli_default.use_colors();
     }
@@ -390,8 +1446,8 @@ mod tests {
     #[test]
     fn test_duration_parsing_edge_cases() {
         // Test boundary values
-        assert_eq!(parse_duration("1").unwrap(), 1);     // Minimum valid
-        assert_eq!(parse_duration("300").unwrap(), 300); // Maximum valid
+        assert_eq!(parse_duration("1ms").unwrap(), Duration::from_millis(1)); // Minimum valid
+        assert_eq!(parse_duration("300").unwrap(), Duration::from_secs(300)); // Maximum valid
         
         // Test edge cases around boundaries
         assert!(parse_duration("0").is_err());   // Just below minimum
@@ -411,27 +1467,534 @@ mod tests {
     #[test]
     fn test_cli_argument_combinations() {
         // Test all boolean flags together
-        let cli = Cli::parse_from(&["test", "--verbose", "--debug", "--no-color", "--test-original"]);
+        let cli = Cli::parse_from(["test", "--verbose", "--debug", "--no-color", "--test-original"]);
         assert!(cli.verbose);
         assert!(cli.debug);
         assert!(cli.no_color);
         assert!(cli.test_original);
         
         // Test with custom DNS and DoH providers
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "test", 
             "--dns-servers", "8.8.8.8,1.1.1.1",
             "--doh-providers", "https://dns.google/dns-query"
         ]);
         assert!(cli.dns_servers.is_some());
         assert!(cli.doh_providers.is_some());
+        
+        // Test scoring weights
+        let cli = Cli::parse_from(["test", "--scoring", "speed=0.5,reliability=0.3,consistency=0.2"]);
+        assert_eq!(cli.scoring.as_deref(), Some("speed=0.5,reliability=0.3,consistency=0.2"));
+        assert!(cli.get_config_summary().contains("Scoring weights"));
+
+        // Test run time budget
+        let cli = Cli::parse_from(["test", "--max-runtime", "10m"]);
+        assert_eq!(cli.max_runtime, Some(Duration::from_secs(600)));
+        assert!(cli.get_config_summary().contains("Max runtime: 10m"));
+        assert!(Cli::try_parse_from(["test", "--max-runtime", "0s"]).is_err());
+
+        // Test automatic iteration counts
+        let cli = Cli::parse_from(["test", "--auto-count"]);
+        assert_eq!(cli.auto_count, Some(5.0));
+        assert!(cli.get_config_summary().contains("Auto count: until ±5%"));
+        let cli = Cli::parse_from(["test", "--auto-count=2%", "--url", "https://example.com"]);
+        assert_eq!(cli.auto_count, Some(2.0));
+        assert!(Cli::try_parse_from(["test", "--auto-count=0"]).is_err());
+
+        // Test the quick profile
+        let cli = Cli::parse_from(["test", "--quick", "--count", "5"]);
+        assert!(cli.quick);
+        assert!(cli.get_config_summary().contains("Quick: yes"));
+
+        // Test the thorough profile
+        let cli = Cli::parse_from(["test", "--thorough"]);
+        assert!(cli.thorough);
+        assert!(cli.get_config_summary().contains("Thorough: yes"));
+        assert!(Cli::try_parse_from(["test", "--thorough", "--quick"]).is_err());
+
+        // Test timeout censoring
+        let cli = Cli::parse_from(["test", "--censor-timeouts"]);
+        assert!(cli.censor_timeouts);
+        assert!(cli.get_config_summary().contains("Censor timeouts"));
+
+        // Test socket statistics
+        let cli = Cli::parse_from(["test", "--socket-stats"]);
+        assert!(cli.socket_stats);
+        assert!(cli.get_config_summary().contains("Socket stats"));
+
+        // Test packet loss probing
+        let cli = Cli::parse_from(["test", "--loss-probe"]);
+        assert_eq!(cli.loss_probe, Some(50));
+        assert!(cli.get_config_summary().contains("Loss probe: 50 packets"));
+        let cli = Cli::parse_from(["test", "--loss-probe=200", "--url", "https://example.com"]);
+        assert_eq!(cli.loss_probe, Some(200));
+        assert!(Cli::try_parse_from(["test", "--loss-probe=0"]).is_err());
+
+        // Test ICMP ping
+        let cli = Cli::parse_from(["test", "--ping"]);
+        assert_eq!(cli.ping, Some(10));
+        assert!(cli.get_config_summary().contains("Ping: 10 echo requests"));
+        assert_eq!(Cli::parse_from(["test", "--ping=3"]).ping, Some(3));
+        assert!(Cli::try_parse_from(["test", "--ping=101"]).is_err());
+
+        // Test the redirect policy
+        let cli = Cli::parse_from(["test", "--redirect-policy", "https-only", "--max-redirects", "3"]);
+        assert_eq!(cli.redirect_policy, Some(RedirectPolicy::HttpsOnly));
+        assert_eq!(cli.max_redirects, Some(3));
+        assert!(cli.get_config_summary().contains("Redirect policy: https-only"));
+        assert!(Cli::try_parse_from(["test", "--redirect-policy", "manual"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--max-redirects", "51"]).is_err());
+
+        // Test User-Agent presets
+        let cli = Cli::parse_from(["test", "--user-agent", "chrome"]);
+        assert_eq!(cli.user_agent, Some(UserAgent::Chrome));
+        assert!(cli.get_config_summary().contains("User agent: chrome"));
+        let cli = Cli::parse_from(["test", "--user-agent", "my-probe/1.0"]);
+        assert_eq!(cli.user_agent, Some(UserAgent::Custom("my-probe/1.0".to_string())));
+        assert!(Cli::try_parse_from(["test", "--user-agent", "firefox"]).is_err());
+
+        // Test protocol control
+        let cli = Cli::parse_from(["test", "--http2-prior-knowledge", "--no-alpn-fallback", "--tls-min-version", "1.3"]);
+        assert!(cli.http2_prior_knowledge && cli.no_alpn_fallback);
+        assert_eq!(cli.tls_min_version, Some(TlsVersion::V1_3));
+        assert!(cli.get_config_summary().contains("Min TLS version: 1.3"));
+        assert!(Cli::try_parse_from(["test", "--tls-max-version", "1.0"]).is_err());
+        let cli = Cli::parse_from(["test", "--tls-resumption", "off"]);
+        assert_eq!(cli.tls_resumption, Some(false));
+        assert!(cli.get_config_summary().contains("TLS resumption: off"));
+        assert!(Cli::try_parse_from(["test", "--tls-resumption", "sometimes"]).is_err());
+
+        // Test fresh DoH connections
+        let cli = Cli::parse_from(["test", "--doh-fresh-connections"]);
+        assert!(cli.doh_fresh_connections);
+        assert!(cli.get_config_summary().contains("Fresh DoH connections"));
+
+        let cli = Cli::parse_from(["test", "--doh-bootstrap", "https://dns.google/dns-query=8.8.8.8", "--doh-bootstrap", "cloudflare-dns.com=system"]);
+        assert_eq!(cli.doh_bootstrap[0], ("dns.google".to_string(), DohBootstrap::Pinned("8.8.8.8".parse().unwrap())));
+        assert!(cli.get_config_summary().contains("DoH bootstrap: cloudflare-dns.com = system"));
+        assert!(Cli::try_parse_from(["test", "--doh-bootstrap", "dns.google=nearby"]).is_err());
+
+        // Test resolver chains
+        let cli = Cli::parse_from(["test", "--dns-chain", "223.5.5.5 > 8.8.8.8", "--dns-chain", "system>https://dns.google/dns-query"]);
+        assert_eq!(cli.dns_chains.len(), 2);
+        assert!(cli.get_config_summary().contains("DNS chain: 223.5.5.5>8.8.8.8"));
+        assert!(Cli::try_parse_from(["test", "--dns-chain", "223.5.5.5"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--dns-chain", "223.5.5.5>http://dns.example/dns-query"]).is_err());
+
+        // Test search domains
+        let cli = Cli::parse_from(["test", "--search-domains", "corp.example.com,example.com", "--ndots", "2", "--test-search-paths"]);
+        assert_eq!(cli.search_domains.as_deref(), Some("corp.example.com,example.com"));
+        assert_eq!(cli.ndots, Some(2));
+        assert!(cli.test_search_paths);
+        assert!(cli.get_config_summary().contains("Search domains: corp.example.com,example.com"));
+        assert_eq!(Cli::parse_from(["test", "--search-domains", ""]).search_domains.as_deref(), Some(""));
+        assert!(Cli::try_parse_from(["test", "--search-domains", "https://corp.example.com"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--ndots", "16"]).is_err());
+
+        // Test the DNS sandbox check
+        let cli = Cli::parse_from(["test", "--sandbox-dns"]);
+        assert!(cli.sandbox_dns);
+        assert!(cli.get_config_summary().contains("DNS sandbox check"));
+
+        let cli = Cli::parse_from(["test", "--flush-dns-cache"]);
+        assert!(cli.flush_dns_cache);
+        assert!(cli.get_config_summary().contains("Flush DNS cache"));
+
+        // Test single-thread mode
+        let cli = Cli::parse_from(["test", "--single-thread"]);
+        assert!(cli.single_thread);
+        assert!(cli.get_config_summary().contains("Single thread"));
+
+        // Test clock check
+        let cli = Cli::parse_from(["test", "--ntp-server", "pool.ntp.org"]);
+        assert_eq!(cli.ntp_server.as_deref(), Some("pool.ntp.org"));
+        assert!(cli.get_config_summary().contains("NTP server: pool.ntp.org"));
+        assert!(Cli::try_parse_from(["test", "--ntp-server", ""]).is_err());
+
+        // Test display names
+        let cli = Cli::parse_from(["test", "--locale", "zh", "--dns-label", "8.8.8.8=Google", "--dns-label", "system=ISP"]);
+        assert_eq!(cli.locale, Some(Locale::Zh));
+        assert_eq!(cli.dns_labels, [
+            ("8.8.8.8".to_string(), "Google".to_string()),
+            ("system".to_string(), "ISP".to_string()),
+        ]);
+        assert!(cli.get_config_summary().contains("DNS label: 8.8.8.8 = Google"));
+        assert!(Cli::try_parse_from(["test", "--dns-label", "Google"]).is_err());
+
+        let cli = Cli::parse_from(["test", "--resolver-options", "System=timeout:2 rotate"]);
+        assert_eq!(cli.resolver_options[0].0, "system");
+        assert!(cli.get_config_summary().contains("Resolver options: system = timeout:2s rotate"));
+        assert!(Cli::try_parse_from(["test", "--resolver-options", "system=ndots:2"]).is_err());
+
+        let cli = Cli::parse_from(["test", "--pool-options", "idle-per-host:0 nodelay:off"]);
+        assert_eq!(cli.pool_options.unwrap().max_idle_per_host, Some(0));
+        assert!(cli.get_config_summary().contains("Pool options: idle-per-host:0 nodelay:off"));
+        assert!(Cli::try_parse_from(["test", "--pool-options", "keepalive:0s"]).is_err());
+
+        // Test sharding
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--shard", "2/4"]);
+        assert_eq!(cli.shard, Some(Shard { index: 2, count: 4 }));
+        assert!(cli.get_config_summary().contains("Shard: 2/4"));
+        assert!(Cli::try_parse_from(["test", "--shard", "0/4"]).is_err());
+    }
+
+    #[test]
+    fn test_merge_subcommand() {
+        let cli = Cli::parse_from(["test", "merge", "shard1.json", "shard2.json", "--output", "all.json"]);
+        assert!(cli.validate().is_ok());
+        let args = cli.merge_args().unwrap();
+        assert_eq!(args.files.len(), 2);
+        assert_eq!(args.output.as_deref(), Some(std::path::Path::new("all.json")));
+        assert!(Cli::try_parse_from(["test", "merge"]).is_err());
+    }
+
+    #[test]
+    fn test_replay_subcommand() {
+        let cli = Cli::parse_from(["test", "replay", "monday.json", "tuesday.json", "--realtime", "--speed", "60", "--notify", "desktop"]);
+        let args = cli.replay_args().unwrap();
+        assert_eq!(args.files.len(), 2);
+        assert!(args.realtime);
+        assert_eq!(args.speed, 60.0);
+        assert_eq!(args.notify, Some(NotifyTarget::Desktop));
+        assert_eq!(Cli::parse_from(["test", "replay", "run.json"]).replay_args().unwrap().speed, 1.0);
+        assert!(Cli::try_parse_from(["test", "replay"]).is_err());
+        assert!(Cli::try_parse_from(["test", "replay", "run.json", "--speed", "2"]).is_err());
+        assert!(Cli::try_parse_from(["test", "replay", "run.json", "--realtime", "--speed", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_subcommand() {
+        let cli = Cli::parse_from(["test", "heatmap", "monday.json", "tuesday.json", "--html", "heatmap.html", "--utc"]);
+        let args = cli.heatmap_args().unwrap();
+        assert_eq!(args.files.len(), 2);
+        assert_eq!(args.html.as_deref(), Some(std::path::Path::new("heatmap.html")));
+        assert!(args.utc);
+        assert!(Cli::try_parse_from(["test", "heatmap"]).is_err());
+    }
+
+    #[test]
+    fn test_dns_bench_subcommand() {
+        let cli = Cli::parse_from(["test", "dns-bench"]);
+        let args = cli.dns_bench_args().unwrap();
+        assert_eq!(args.domain, "example.com");
+        assert_eq!(args.queries, crate::defaults::DEFAULT_DNS_BENCH_QUERIES);
+
+        let cli = Cli::parse_from(["test", "dns-bench", "--domain", "github.com", "--queries", "10"]);
+        let args = cli.dns_bench_args().unwrap();
+        assert_eq!(args.domain, "github.com");
+        assert_eq!(args.queries, 10);
+        assert!(Cli::try_parse_from(["test", "dns-bench", "--queries", "0"]).is_err());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_serve_mock_dns_subcommand() {
+        let cli = Cli::parse_from(["test", "serve-mock-dns"]);
+        let args = cli.serve_mock_dns_args().unwrap();
+        assert_eq!(args.listen, "127.0.0.1:5300".parse().unwrap());
+        assert_eq!(args.doh_listen, "127.0.0.1:8053".parse().unwrap());
+        assert!(args.records.is_empty() && args.delay.is_none());
+
+        let cli = Cli::parse_from([
+            "test", "serve-mock-dns", "--listen", "127.0.0.1:53", "--record", "app.test=192.0.2.1,2001:db8::1",
+            "--record", "*=127.0.0.1", "--delay", "50ms",
+        ]);
+        let args = cli.serve_mock_dns_args().unwrap();
+        assert_eq!(args.records.len(), 2);
+        assert_eq!(args.records[0].1.len(), 2);
+        assert_eq!(args.delay, Some(Duration::from_millis(50)));
+        assert!(Cli::try_parse_from(["test", "serve-mock-dns", "--record", "app.test=nowhere"]).is_err());
+    }
+
+    #[test]
+    fn test_ab_subcommand() {
+        let cli = Cli::parse_from(["test", "ab", "--config-a", "a.toml", "--config-b", "b.toml"]);
+        let args = cli.ab_args().unwrap();
+        assert_eq!(args.config_a, PathBuf::from("a.toml"));
+        assert_eq!(args.config_b, PathBuf::from("b.toml"));
+        assert_eq!(args.rounds, crate::defaults::DEFAULT_AB_ROUNDS);
+
+        let cli = Cli::parse_from(["test", "ab", "--config-a", "a.toml", "--config-b", "b.toml", "--rounds", "50"]);
+        assert_eq!(cli.ab_args().unwrap().rounds, 50);
+        assert!(Cli::try_parse_from(["test", "ab", "--config-a", "a.toml"]).is_err());
+        assert!(Cli::try_parse_from(["test", "ab", "--config-a", "a.toml", "--config-b", "b.toml", "--rounds", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_bench_internal_subcommand() {
+        use clap::CommandFactory;
+
+        let cli = Cli::parse_from(["test", "bench-internal"]);
+        let args = cli.bench_internal_args().unwrap();
+        assert_eq!(args.runs, crate::defaults::DEFAULT_BENCH_RUNS);
+        assert_eq!((args.targets, args.configs, args.iterations), (20, 10, 50));
+
+        let cli = Cli::parse_from(["test", "bench-internal", "--runs", "3", "--iterations", "200"]);
+        let args = cli.bench_internal_args().unwrap();
+        assert_eq!((args.runs, args.iterations), (3, 200));
+        assert!(Cli::try_parse_from(["test", "bench-internal", "--configs", "255"]).is_err());
+        // Hidden from the list of subcommands
+        assert!(!Cli::command().render_help().to_string().contains("bench-internal"));
+    }
+
+    #[test]
+    fn test_cache_subcommand() {
+        let cli = Cli::parse_from(["test", "cache", "stats"]);
+        assert_eq!(cli.cache_args().unwrap().action, CacheAction::Stats);
+        let cli = Cli::parse_from(["test", "cache", "clear"]);
+        assert_eq!(cli.cache_args().unwrap().action, CacheAction::Clear);
+        assert!(Cli::try_parse_from(["test", "cache"]).is_err());
+    }
+
+    #[test]
+    fn test_config_subcommand() {
+        let cli = Cli::parse_from(["test", "config", "show"]);
+        assert_eq!(cli.config_args().unwrap().action, ConfigAction::Show);
+        let cli = Cli::parse_from(["test", "config", "set", "DNS_SERVERS", "8.8.8.8,1.1.1.1"]);
+        assert_eq!(cli.config_args().unwrap().action, ConfigAction::Set {
+            key: "DNS_SERVERS".to_string(),
+            value: "8.8.8.8,1.1.1.1".to_string(),
+        });
+        assert!(Cli::try_parse_from(["test", "config", "set", "DNS_SERVERS"]).is_err());
+    }
+
+    #[test]
+    fn test_query_subcommand() {
+        let cli = Cli::parse_from([
+            "test", "query", "results.json",
+            "--where", "config=Cloudflare AND url~api",
+            "--metric", "p95",
+            "--group-by", "url",
+        ]);
+        assert!(cli.validate().is_ok());
+
+        let args = cli.query_args().unwrap();
+        assert_eq!(args.file, PathBuf::from("results.json"));
+        assert_eq!(args.filter.as_deref(), Some("config=Cloudflare AND url~api"));
+        assert_eq!(args.metric, "p95");
+        assert_eq!(args.group_by.as_deref(), Some("url"));
+
+        let cli = Cli::parse_from(["test", "query", "results.json"]);
+        assert_eq!(cli.query_args().unwrap().metric, "avg");
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--export", "out.json", "--export-csv", "requests.csv"]);
+        assert!(cli.query_args().is_none());
+        assert_eq!(cli.export, Some(PathBuf::from("out.json")));
+        assert_eq!(cli.export_csv, Some(PathBuf::from("requests.csv")));
+    }
+
+    #[test]
+    fn test_slowest_requests_option() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
+        assert_eq!(cli.slowest, 5);
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--slowest", "0"]);
+        assert_eq!(cli.slowest, 0);
+    }
+
+    #[test]
+    fn test_table_view_options() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
+        assert_eq!((cli.sort_by, cli.top), (None, None));
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--sort-by", "p95", "--top", "5"]);
+        assert_eq!((cli.sort_by, cli.top), (Some(SortKey::P95), Some(5)));
+        assert!(Cli::try_parse_from(["test", "--sort-by", "median"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--top", "0"]).is_err());
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--columns", "dns, ttfb,total"]);
+        assert_eq!(cli.columns, [MetricColumn::Dns, MetricColumn::Ttfb, MetricColumn::Total]);
+        assert!(Cli::try_parse_from(["test", "--columns", "dns,tls"]).is_err());
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        let cli = Cli::parse_from(["test", "init"]);
+        assert!(cli.validate().is_ok());
+        assert!(cli.query_args().is_none());
+
+        let args = cli.init_args().unwrap();
+        assert_eq!(args.output, PathBuf::from(".env"));
+        assert!(!args.force);
+        assert!(!args.no_check);
+
+        let cli = Cli::parse_from(["test", "init", "--output", "lab.env", "--force", "--no-check"]);
+        let args = cli.init_args().unwrap();
+        assert_eq!(args.output, PathBuf::from("lab.env"));
+        assert!(args.force && args.no_check);
+    }
+
+    #[test]
+    fn test_plan_flag() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
+        assert!(!cli.plan);
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--plan"]);
+        assert!(cli.plan);
+        assert!(cli.validate().is_ok());
+    }
+
+    #[test]
+    fn test_check_config_flag() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--count", "20", "--check-config"]);
+        assert!(cli.check_config);
+        assert!(cli.validate().is_ok());
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--check-config"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_strict_flag() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
+        assert!(!cli.strict);
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--strict", "--check-config"]);
+        assert!(cli.strict);
+        assert!(cli.get_config_summary().contains("Strict mode: true"));
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let cli = Cli::parse_from(["test", "--watch", "5m"]);
+        assert_eq!(cli.watch, Some(Duration::from_secs(300)));
+        assert!(cli.validate().is_ok());
+        assert!(cli.get_config_summary().contains("Watch interval: 5m"));
+
+        assert!(Cli::try_parse_from(["test", "--watch", "0s"]).is_err());
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--watch", "1m", "--plan"]);
+        assert!(cli.validate().is_err());
+
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--log-file", "probe.log"]);
+        assert!(cli.get_config_summary().contains("Log file: probe.log"));
+        assert!(Cli::try_parse_from(["test", "--url", "https://example.com", "--log-file", "probe.log"]).is_err());
+
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--notify", "desktop"]);
+        assert_eq!(cli.notify, Some(NotifyTarget::Desktop));
+        assert!(cli.get_config_summary().contains("Notify: desktop"));
+        assert!(Cli::try_parse_from(["test", "--url", "https://example.com", "--notify", "desktop"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--watch", "1m", "--notify", "email"]).is_err());
+
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--honor-retry-after"]);
+        assert!(cli.honor_retry_after);
+        assert!(cli.get_config_summary().contains("Honor Retry-After: yes"));
+        assert!(Cli::try_parse_from(["test", "--url", "https://example.com", "--honor-retry-after"]).is_err());
+    }
+
+    #[test]
+    fn test_serve_flag() {
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--serve", "127.0.0.1:9180"]);
+        assert_eq!(cli.serve, Some("127.0.0.1:9180".parse().unwrap()));
+        assert!(cli.get_config_summary().contains("Serving results on: 127.0.0.1:9180"));
+        assert!(Cli::try_parse_from(["test", "--url", "https://example.com", "--serve", "127.0.0.1:9180"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--watch", "1m", "--serve", "localhost"]).is_err());
+        let cli = Cli::parse_from(["test", "--watch", "1m", "--serve", "127.0.0.1:9180", "--serve-cors", "https://grafana.example"]);
+        assert_eq!(cli.serve_cors.as_deref(), Some("https://grafana.example"));
+        assert!(Cli::try_parse_from(["test", "--watch", "1m", "--serve-cors", "https://grafana.example"]).is_err());
+    }
+
+    #[test]
+    fn test_compare_public_option() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--compare-public", "atlas:nl"]);
+        assert_eq!(cli.compare_public, Some(BaselineSpec::Atlas { country: Some("NL".to_string()) }));
+        assert!(cli.get_config_summary().contains("Public baseline: RIPE Atlas (NL)"));
+        assert!(Cli::try_parse_from(["test", "--compare-public", "atlas:netherlands"]).is_err());
+    }
+
+    #[test]
+    fn test_geo_option() {
+        let cli = Cli::parse_from(["test", "--geo"]);
+        assert_eq!(cli.geo, Some(ClientLocation::Detect));
+        let cli = Cli::parse_from(["test", "--geo=52.52,13.40", "--url", "https://example.com"]);
+        assert!(matches!(cli.geo, Some(ClientLocation::At(_))));
+        assert!(cli.get_config_summary().contains("Geolocation: yes, client at 52.5200,13.4000"));
+        assert!(Cli::try_parse_from(["test", "--geo=95,0"]).is_err());
+    }
+
+    #[test]
+    fn test_bind_options() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--bind", "wlan0"]);
+        assert_eq!(cli.bind, Some(BindTarget::Interface("wlan0".to_string())));
+        assert!(cli.get_config_summary().contains("Bind: wlan0"));
+        let cli = Cli::parse_from(["test", "--all-interfaces"]);
+        assert!(cli.all_interfaces);
+        assert!(Cli::try_parse_from(["test", "--all-interfaces", "--bind", "eth0"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--bind", "Wi Fi"]).is_err());
+    }
+
+    #[test]
+    fn test_upload_option() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--upload", "put:1MiB"]);
+        assert_eq!(cli.upload, Some(Upload { method: crate::types::UploadMethod::Put, bytes: 1 << 20 }));
+        assert!(cli.get_config_summary().contains("Upload: put:1MiB"));
+        assert!(Cli::try_parse_from(["test", "--upload", "0"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--upload", "delete:1KiB"]).is_err());
+    }
+
+    #[test]
+    fn test_early_hints_option() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--early-hints", "--columns", "total,early-hints"]);
+        assert!(cli.early_hints);
+        assert!(cli.get_config_summary().contains("Early hints: yes"));
+        assert_eq!(cli.columns, [MetricColumn::Total, MetricColumn::EarlyHints]);
+    }
+
+    #[test]
+    fn test_capture_on_anomaly_option() {
+        let cli = Cli::parse_from(["test", "--capture-on-anomaly", "500ms"]);
+        assert_eq!(cli.capture_on_anomaly, Some(Duration::from_millis(500)));
+        assert!(cli.get_config_summary().contains("Capture on anomaly: over 500ms"));
+        assert!(Cli::try_parse_from(["test", "--capture-on-anomaly", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_trace_requests_option() {
+        let cli = Cli::parse_from(["test", "--trace-requests"]);
+        assert!(cli.trace_requests);
+        assert!(cli.get_config_summary().contains("Request trace: yes"));
+    }
+
+    #[test]
+    fn test_network_history_options() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--network-history", "--network-baseline"]);
+        assert!(cli.network_history && cli.network_baseline);
+        assert!(cli.get_config_summary().contains("Network history: yes, current network as baseline"));
+        assert!(Cli::try_parse_from(["test", "--network-baseline"]).is_err());
+    }
+
+    #[test]
+    fn test_simulate_option() {
+        let cli = Cli::parse_from(["test", "--url", "https://example.com", "--simulate", "lognormal:median=60ms,loss=1%"]);
+        let simulation = cli.simulate.as_ref().unwrap();
+        assert_eq!(simulation.loss, 0.01);
+        assert!(cli.get_config_summary().contains("Simulated: lognormal:median=60ms"));
+        assert!(Cli::try_parse_from(["test", "--url", "https://example.com", "--simulate", "pareto"]).is_err());
+    }
+
+    #[test]
+    fn test_prefixed_option_variables() {
+        use clap::CommandFactory;
+        let command = Cli::command();
+        for (name, option) in crate::config::env::PREFIXED_OPTIONS {
+            let arg = command.get_arguments()
+                .find(|arg| arg.get_long().map(|long| format!("--{}", long)).as_deref() == Some(*option))
+                .unwrap();
+            assert_eq!(arg.get_env().and_then(|env| env.to_str()), Some(*name));
+        }
+    }
+
+    #[test]
+    fn test_ctl_subcommand() {
+        let cli = Cli::parse_from(["test", "ctl", "flush-report", "--control-socket", "/tmp/nlt.sock"]);
+        assert!(cli.validate().is_ok());
+        assert_eq!(cli.ctl_args().unwrap().command, ControlCommand::FlushReport);
+        assert_eq!(cli.control_socket.as_deref(), Some(std::path::Path::new("/tmp/nlt.sock")));
+        assert!(Cli::try_parse_from(["test", "ctl", "stop"]).is_err());
     }
 
     #[test]
     fn test_help_topic_edge_cases() {
         // Test all valid help topics
         for topic in &["config", "dns", "examples", "timeout", "output", "update"] {
-            let cli = Cli::parse_from(&["test", "--help-topic", topic]);
+            let cli = Cli::parse_from(["test", "--help-topic", topic]);
             assert!(cli.should_show_topic_help());
             assert_eq!(cli.get_help_topic(), Some(*topic));
             
@@ -443,14 +2006,14 @@ mod tests {
         }
         
         // Test case insensitivity - uppercase should work (function converts to lowercase)
-        let cli = Cli::parse_from(&["test", "--help-topic", "CONFIG"]);
+        let cli = Cli::parse_from(["test", "--help-topic", "CONFIG"]);
         let help = cli.display_help();
         assert!(!help.contains("Unknown help topic")); // Should be case insensitive
         // Check for content from config help
         assert!(help.contains("CONFIGURATION REFERENCE")); // Should show config help
         
         // Test completely invalid topic
-        let cli = Cli::parse_from(&["test", "--help-topic", "invalid_topic"]);
+        let cli = Cli::parse_from(["test", "--help-topic", "invalid_topic"]);
         let help = cli.display_help();
         assert!(help.contains("Unknown help topic"));
         assert!(help.contains("invalid_topic"));
@@ -459,7 +2022,7 @@ mod tests {
 
     #[test]
     fn test_multiple_url_parsing() {
-        let cli = Cli::parse_from(&[
+        let cli = Cli::parse_from([
             "test",
             "--url", "https://example.com",
             "--url", "https://test.com",
@@ -475,33 +2038,36 @@ mod tests {
     #[test]
     fn test_cli_validation() {
         // Test conflicting color flags
-        let cli_conflict = Cli::parse_from(&["test", "--color", "--no-color", "--test-original"]);
+        let cli_conflict = Cli::parse_from(["test", "--color", "--no-color", "--test-original"]);
         assert!(cli_conflict.validate().is_err());
         assert!(cli_conflict.validate().unwrap_err().contains("Cannot specify both --color and --no-color"));
 
         // Test no URLs provided
-        let cli_no_urls = Cli::parse_from(&["test"]);
+        let cli_no_urls = Cli::parse_from(["test"]);
         assert!(cli_no_urls.validate().is_err());
         assert!(cli_no_urls.validate().unwrap_err().contains("Must specify at least one URL"));
+        let cli_url_file = Cli::parse_from(["test", "--url-file", "targets.csv"]);
+        assert!(cli_url_file.validate().is_ok());
+        assert!(cli_url_file.get_config_summary().contains("URL file: targets.csv"));
 
         // Test valid configurations
-        let cli_with_url = Cli::parse_from(&["test", "--url", "https://example.com"]);
+        let cli_with_url = Cli::parse_from(["test", "--url", "https://example.com"]);
         assert!(cli_with_url.validate().is_ok());
 
-        let cli_with_original = Cli::parse_from(&["test", "--test-original"]);
+        let cli_with_original = Cli::parse_from(["test", "--test-original"]);
         assert!(cli_with_original.validate().is_ok());
 
-        let cli_color_only = Cli::parse_from(&["test", "--color", "--test-original"]);
+        let cli_color_only = Cli::parse_from(["test", "--color", "--test-original"]);
         assert!(cli_color_only.validate().is_ok());
 
-        let cli_no_color_only = Cli::parse_from(&["test", "--no-color", "--test-original"]);
+        let cli_no_color_only = Cli::parse_from(["test", "--no-color", "--test-original"]);
         assert!(cli_no_color_only.validate().is_ok());
     }
 
     #[test]
     fn test_get_urls_method() {
         // Test with custom URLs only
-        let cli_custom = Cli::parse_from(&[
+        let cli_custom = Cli::parse_from([
             "test",
             "--url", "https://example.com",
             "--url", "https://test.com"
@@ -512,13 +2078,13 @@ mod tests {
         assert_eq!(urls[1], "https://test.com");
 
         // Test with test-original only
-        let cli_original = Cli::parse_from(&["test", "--test-original"]);
+        let cli_original = Cli::parse_from(["test", "--test-original"]);
         let urls = cli_original.get_urls();
         assert_eq!(urls.len(), 1);
         assert_eq!(urls[0], "https://target");
 
         // Test with both custom URLs and test-original (test-original takes precedence)
-        let cli_both = Cli::parse_from(&[
+        let cli_both = Cli::parse_from([
             "test",
             "--url", "https://example.com",
             "--test-original"
@@ -531,18 +2097,18 @@ mod tests {
     #[test]  
     fn test_count_boundary_values() {
         // Test minimum count
-        let cli = Cli::parse_from(&["test", "--count", "1"]);
+        let cli = Cli::parse_from(["test", "--count", "1"]);
         assert_eq!(cli.count, 1);
         
         // Test maximum reasonable count (clap handles u32 max automatically)
-        let cli = Cli::parse_from(&["test", "--count", "1000"]);
+        let cli = Cli::parse_from(["test", "--count", "1000"]);
         assert_eq!(cli.count, 1000);
     }
 
     // Update-related tests
     #[test]
     fn test_update_short_parameters() {
-        let cli = Cli::parse_from(&["test", "-u"]);
+        let cli = Cli::parse_from(["test", "-u"]);
         assert!(cli.update);
         assert!(cli.is_update_mode());
         assert!(cli.is_interactive_update());
@@ -553,7 +2119,7 @@ mod tests {
 
     #[test]
     fn test_update_with_version_short() {
-        let cli = Cli::parse_from(&["test", "-u", "-v", "0.1.7"]);
+        let cli = Cli::parse_from(["test", "-u", "-v", "0.1.7"]);
         assert!(cli.update);
         assert!(cli.is_update_mode());
         assert!(!cli.is_interactive_update());
@@ -564,7 +2130,7 @@ mod tests {
 
     #[test]
     fn test_update_with_force_short() {
-        let cli = Cli::parse_from(&["test", "-u", "-v", "0.1.5", "-f"]);
+        let cli = Cli::parse_from(["test", "-u", "-v", "0.1.5", "-f"]);
         assert!(cli.update);
         assert!(cli.is_update_mode());
         assert!(!cli.is_interactive_update());
@@ -575,7 +2141,7 @@ mod tests {
 
     #[test]
     fn test_update_long_parameters() {
-        let cli = Cli::parse_from(&["test", "--update", "--version", "v1.2.3", "--force"]);
+        let cli = Cli::parse_from(["test", "--update", "--version", "v1.2.3", "--force"]);
         assert!(cli.update);
         assert!(cli.is_update_mode());
         assert!(!cli.is_interactive_update());
@@ -587,21 +2153,18 @@ mod tests {
     #[test]
     fn test_update_parameter_validation() {
         // Test version without update fails
-        let result = std::panic::catch_unwind(|| {
-            Cli::parse_from(&["test", "--version", "1.0.0"])
-        });
+        let result = Cli::try_parse_from(["test", "--version", "1.0.0"]);
         assert!(result.is_err()); // clap should error due to requires = "update"
 
         // Test force without update fails  
-        let result = std::panic::catch_unwind(|| {
-            Cli::parse_from(&["test", "--force"])
-        });
+        let result = Cli::try_parse_from(["test", "--force"]);
         assert!(result.is_err()); // clap should error due to requires = "update"
     }
 
     #[test]
+    #[cfg(feature = "updater")]
     fn test_update_args_conversion() {
-        let cli = Cli::parse_from(&["test", "-u", "-v", "1.2.3", "-f"]);
+        let cli = Cli::parse_from(["test", "-u", "-v", "1.2.3", "-f"]);
         let update_args = cli.to_update_args();
         
         assert!(update_args.update);
@@ -611,8 +2174,9 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "updater")]
     fn test_update_interactive_args_conversion() {
-        let cli = Cli::parse_from(&["test", "--update"]);
+        let cli = Cli::parse_from(["test", "--update"]);
         let update_args = cli.to_update_args();
         
         assert!(update_args.update);
@@ -622,30 +2186,32 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "updater")]
     fn test_update_mode_validation_skip_urls() {
         // In update mode, URLs should not be required
-        let cli = Cli::parse_from(&["test", "--update"]);
+        let cli = Cli::parse_from(["test", "--update"]);
         assert!(cli.validate().is_ok());
         
-        let cli_with_version = Cli::parse_from(&["test", "--update", "--version", "1.0.0"]);
+        let cli_with_version = Cli::parse_from(["test", "--update", "--version", "1.0.0"]);
         assert!(cli_with_version.validate().is_ok());
     }
 
     #[test]
+    #[cfg(feature = "updater")]
     fn test_update_version_format_validation() {
         // Valid version formats should pass
-        let cli_valid_v = Cli::parse_from(&["test", "--update", "--version", "v1.2.3"]);
+        let cli_valid_v = Cli::parse_from(["test", "--update", "--version", "v1.2.3"]);
         assert!(cli_valid_v.validate().is_ok());
         
-        let cli_valid_no_v = Cli::parse_from(&["test", "--update", "--version", "1.2.3"]);
+        let cli_valid_no_v = Cli::parse_from(["test", "--update", "--version", "1.2.3"]);
         assert!(cli_valid_no_v.validate().is_ok());
         
         // Invalid version formats should fail
-        let cli_invalid = Cli::parse_from(&["test", "--update", "--version", "invalid"]);
+        let cli_invalid = Cli::parse_from(["test", "--update", "--version", "invalid"]);
         assert!(cli_invalid.validate().is_err());
         assert!(cli_invalid.validate().unwrap_err().contains("Invalid version format"));
         
-        let cli_incomplete = Cli::parse_from(&["test", "--update", "--version", "1.2"]);
+        let cli_incomplete = Cli::parse_from(["test", "--update", "--version", "1.2"]);
         assert!(cli_incomplete.validate().is_err());
         assert!(cli_incomplete.validate().unwrap_err().contains("Invalid version format"));
     }
@@ -653,25 +2219,25 @@ mod tests {
     #[test] 
     fn test_update_utility_methods() {
         // Test interactive update
-        let cli_interactive = Cli::parse_from(&["test", "--update"]);
+        let cli_interactive = Cli::parse_from(["test", "--update"]);
         assert!(cli_interactive.is_update_mode());
         assert!(cli_interactive.is_interactive_update());
         assert!(!cli_interactive.is_force_downgrade());
         
         // Test direct version update
-        let cli_direct = Cli::parse_from(&["test", "--update", "--version", "1.0.0"]);
+        let cli_direct = Cli::parse_from(["test", "--update", "--version", "1.0.0"]);
         assert!(cli_direct.is_update_mode());
         assert!(!cli_direct.is_interactive_update());
         assert!(!cli_direct.is_force_downgrade());
         
         // Test forced downgrade
-        let cli_forced = Cli::parse_from(&["test", "--update", "--version", "0.9.0", "--force"]);
+        let cli_forced = Cli::parse_from(["test", "--update", "--version", "0.9.0", "--force"]);
         assert!(cli_forced.is_update_mode());
         assert!(!cli_forced.is_interactive_update());
         assert!(cli_forced.is_force_downgrade());
         
         // Test non-update mode
-        let cli_normal = Cli::parse_from(&["test", "--test-original"]);
+        let cli_normal = Cli::parse_from(["test", "--test-original"]);
         assert!(!cli_normal.is_update_mode());
         assert!(!cli_normal.is_interactive_update());
         assert!(!cli_normal.is_force_downgrade());
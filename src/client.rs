@@ -4,26 +4,40 @@ pub mod platform;
 pub mod windows;
 pub mod cert_validation;
 pub mod timeouts;
+pub mod socket_stats;
+pub mod certificate;
+pub mod upload;
+pub mod informational;
+pub mod resumption;
+pub mod simulate;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 #[cfg(test)]
 mod integration_tests;
 
 use crate::{
+    cancel::{cancellable, CancellationToken},
     error::{AppError, Result},
-    types::{DnsConfig, TestStatus},
-    dns::DnsManager,
-    models::metrics::TimingMetrics,
+    types::{DnsConfig, ProtocolOptions, TestStatus, TlsVersion},
+    dns::NameResolver,
+    models::metrics::{CacheStatus, ResponseBody, ServerTiming, Throttling, TimingMetrics},
+    probe::{Fetch, FetchRequest, FetchResponse},
 };
 use std::{
     net::IpAddr,
     time::{Duration, Instant},
     sync::Arc,
 };
-use reqwest::{Client, Method, Url};
+use reqwest::{Client, ClientBuilder, Method, Url};
 use tokio::time::timeout;
 use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 
+/// `Accept-Encoding` sent with test requests: the encodings browsers commonly
+/// accept that [`HttpUtils::response_body`] can also decode
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
 /// HTTP client trait for abstraction and testing
 #[async_trait]
 pub trait HttpClient: Send + Sync {
@@ -147,49 +161,51 @@ pub struct ConnectivityTest {
 
 /// Network latency tester HTTP client implementation
 pub struct NetworkClient {
-    dns_manager: Arc<DnsManager>,
+    resolver: Arc<dyn NameResolver>,
     #[allow(dead_code)]
     client: Client,
     default_timeout: Duration,
+    /// Stops requests in flight when cancelled
+    cancel: CancellationToken,
 }
 
 impl NetworkClient {
     /// Create a new network client
-    pub fn new(dns_manager: Arc<DnsManager>) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("network-latency-tester/0.1.0")
-            .build()
-            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)))?;
-        
-        Ok(Self {
-            dns_manager,
-            client,
-            default_timeout: Duration::from_secs(10),
-        })
+    pub fn new(resolver: Arc<dyn NameResolver>) -> Result<Self> {
+        Self::from_builder(resolver, Client::builder().timeout(Duration::from_secs(30)), Duration::from_secs(10))
     }
     
     /// Create a new network client with custom timeout
-    pub fn with_timeout(dns_manager: Arc<DnsManager>, timeout: Duration) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(timeout)
+    pub fn with_timeout(resolver: Arc<dyn NameResolver>, timeout: Duration) -> Result<Self> {
+        Self::from_builder(resolver, Client::builder().timeout(timeout), timeout)
+    }
+
+    /// Create a network client from a configured builder
+    pub fn from_builder(resolver: Arc<dyn NameResolver>, builder: ClientBuilder, default_timeout: Duration) -> Result<Self> {
+        let client = builder
             .user_agent("network-latency-tester/0.1.0")
             .build()
-            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)))?;
+            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
         
         Ok(Self {
-            dns_manager,
+            resolver,
             client,
-            default_timeout: timeout,
+            default_timeout,
+            cancel: crate::cancel::global().child_token(),
         })
     }
+
+    /// Stop requests in flight, which then fail as cancelled, once `token` is cancelled
+    pub fn with_cancellation(self, token: &CancellationToken) -> Self {
+        Self { cancel: token.child_token(), ..self }
+    }
     
     /// Resolve URL using specified DNS configuration
     async fn resolve_url(&self, url: &str, dns_config: &DnsConfig) -> Result<(Vec<IpAddr>, Duration)> {
         let start_time = Instant::now();
         
         let parsed_url = Url::parse(url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         let host = parsed_url.host_str()
             .ok_or_else(|| AppError::validation("URL must have a host"))?;
@@ -199,7 +215,7 @@ impl NetworkClient {
             return Ok((vec![ip], Duration::from_nanos(0)));
         }
         
-        let ips = self.dns_manager.resolve(host, dns_config).await?;
+        let ips = self.resolver.resolve(host, dns_config).await?;
         let resolution_time = start_time.elapsed();
         
         Ok((ips, resolution_time))
@@ -211,7 +227,7 @@ impl NetworkClient {
         
         // Parse URL
         let url = Url::parse(&request.url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         // DNS resolution timing
         let (resolved_ips, dns_time) = self.resolve_url(&request.url, &request.dns_config).await?;
@@ -232,7 +248,10 @@ impl NetworkClient {
         // Set timeout
         req_builder = req_builder.timeout(request.timeout);
         
-        // Add headers
+        // Add headers, asking for compressed bodies unless the caller chose otherwise
+        if !request.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("accept-encoding")) {
+            req_builder = req_builder.header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+        }
         for (name, value) in &request.headers {
             req_builder = req_builder.header(name, value);
         }
@@ -248,10 +267,10 @@ impl NetworkClient {
         let response_result = if request.timeout > Duration::ZERO {
             timeout(request.timeout, req_builder.send()).await
                 .map_err(|_| AppError::timeout("HTTP request timed out"))?
-                .map_err(|e| AppError::http_request(e.to_string()))
+                .map_err(|e| AppError::http_request(e.to_string()).with_source(e))
         } else {
             req_builder.send().await
-                .map_err(|e| AppError::http_request(e.to_string()))
+                .map_err(|e| AppError::http_request(e.to_string()).with_source(e))
         };
         
         let request_time = request_start.elapsed();
@@ -261,6 +280,7 @@ impl NetworkClient {
             Ok(response) => {
                 let status_code = response.status().as_u16();
                 let final_url = response.url().to_string();
+                let cache_status = HttpUtils::cache_status(response.headers());
                 
                 // Extract headers
                 let headers: Vec<(String, String)> = response
@@ -271,8 +291,12 @@ impl NetworkClient {
                 
                 // Get body size (read body for size measurement)
                 let body = response.bytes().await
-                    .map_err(|e| AppError::http_request(format!("Failed to read response body: {}", e)))?;
+                    .map_err(|e| AppError::http_request(format!("Failed to read response body: {}", e)).with_source(e))?;
                 let body_size = body.len();
+                let encoding = headers.iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-encoding"))
+                    .map(|(_, value)| value.as_str());
+                let response_body = HttpUtils::response_body(encoding, &body);
                 
                 // Create timing metrics
                 let timing = TimingMetrics::success(
@@ -282,7 +306,9 @@ impl NetworkClient {
                     request_time,
                     total_time,
                     status_code,
-                );
+                ).with_resolved_ip(Some(target_ip))
+                .with_body(response_body)
+                .with_cache_status(cache_status);
                 
                 Ok(HttpResponse {
                     status_code,
@@ -295,7 +321,7 @@ impl NetworkClient {
                 })
             }
             Err(e) => {
-                let _timing = if matches!(e, AppError::Timeout(_)) {
+                let _timing = if matches!(e, AppError::Timeout(..)) {
                     TimingMetrics::timeout(total_time)
                 } else {
                     TimingMetrics::failed(e.to_string())
@@ -310,7 +336,7 @@ impl NetworkClient {
     /// Create a custom HTTP client that connects to a specific IP
     fn create_custom_client(&self, request: &HttpRequest, _target_ip: IpAddr) -> Result<Client> {
         let url = Url::parse(&request.url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         let _port = url.port().unwrap_or_else(|| {
             match url.scheme() {
@@ -335,7 +361,7 @@ impl NetworkClient {
             DnsConfig::System => {
                 // Use default system resolver
             }
-            DnsConfig::Custom { .. } | DnsConfig::DoH { .. } => {
+            DnsConfig::Custom { .. } | DnsConfig::DoH { .. } | DnsConfig::Chain { .. } | DnsConfig::Mdns => {
                 // For custom DNS, we would ideally use a custom resolver
                 // but reqwest doesn't easily support this, so we use the resolved IP directly
                 // This is a limitation that could be improved in future versions
@@ -343,14 +369,14 @@ impl NetworkClient {
         }
         
         client_builder.build()
-            .map_err(|e| AppError::network(format!("Failed to create custom client: {}", e)))
+            .map_err(|e| AppError::network(format!("Failed to create custom client: {}", e)).with_source(e))
     }
 }
 
 #[async_trait]
 impl HttpClient for NetworkClient {
     async fn execute_request(&self, request: HttpRequest) -> Result<HttpResponse> {
-        self.execute_with_dns(request).await
+        cancellable(&self.cancel, "HTTP request", self.execute_with_dns(request)).await
     }
     
     async fn head(&self, url: &str, dns_config: &DnsConfig) -> Result<HttpResponse> {
@@ -380,6 +406,7 @@ impl HttpClient for NetworkClient {
                     error: None,
                 })
             }
+            Err(e) if e.is_cancelled() => Err(e),
             Err(e) => {
                 Ok(ConnectivityTest {
                     success: false,
@@ -397,28 +424,129 @@ impl HttpClient for NetworkClient {
 
 /// HTTP client factory for different configurations
 pub struct ClientFactory {
-    dns_manager: Arc<DnsManager>,
+    resolver: Arc<dyn NameResolver>,
+    injected_client: Option<Arc<dyn HttpClient>>,
+    protocol: ProtocolOptions,
 }
 
 impl ClientFactory {
-    /// Create a new client factory
-    pub fn new(dns_manager: Arc<DnsManager>) -> Self {
-        Self { dns_manager }
+    /// Create a new client factory resolving names through the given resolver
+    pub fn new(resolver: Arc<dyn NameResolver>) -> Self {
+        Self {
+            resolver,
+            injected_client: None,
+            protocol: ProtocolOptions::default(),
+        }
+    }
+
+    /// Build network clients with the given protocol choices
+    pub fn with_protocol(mut self, protocol: ProtocolOptions) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Apply protocol choices to a client builder
+    ///
+    /// Every client that measures requests goes through here, so forcing a
+    /// protocol affects the factory's clients and the executor's pool alike.
+    /// Whether ALPN may fall back to HTTP/1.1 is checked on each response,
+    /// see [`ProtocolOptions::alpn_fallback`].
+    pub fn apply_protocol(mut builder: ClientBuilder, protocol: &ProtocolOptions) -> ClientBuilder {
+        if protocol.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        // The platform TLS library cannot require TLS 1.3, so restricted versions use rustls
+        if protocol.tls_min_version.is_some() || protocol.tls_max_version.is_some() {
+            builder = builder.use_rustls_tls();
+        }
+        let tls_version = |version: TlsVersion| match version {
+            TlsVersion::V1_2 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::V1_3 => reqwest::tls::Version::TLS_1_3,
+        };
+        if let Some(version) = protocol.tls_min_version {
+            builder = builder.min_tls_version(tls_version(version));
+        }
+        if let Some(version) = protocol.tls_max_version {
+            builder = builder.max_tls_version(tls_version(version));
+        }
+        builder
+    }
+
+    /// Apply `--tls-resumption` to a client builder that [`apply_protocol`](Self::apply_protocol) prepared
+    ///
+    /// With the setting, the client uses rustls settings of its own (see
+    /// [`resumption::tls_config`]), whose tally of full handshakes is returned.
+    pub fn apply_tls_resumption(
+        builder: ClientBuilder,
+        protocol: &ProtocolOptions,
+    ) -> Result<(ClientBuilder, Option<Arc<resumption::FullHandshakes>>)> {
+        match protocol.tls_resumption {
+            Some(enabled) => {
+                let (tls, handshakes) = resumption::tls_config(protocol, enabled)?;
+                Ok((builder.use_preconfigured_tls(tls), Some(handshakes)))
+            }
+            None => Ok((builder, None)),
+        }
+    }
+
+    /// Hand out the given client from [`ClientFactory::create_client`] instead of a network client
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.injected_client = Some(client);
+        self
+    }
+
+    /// Create the client used for testing, the injected one if there is any
+    pub fn create_client(&self) -> Result<Arc<dyn HttpClient>> {
+        match &self.injected_client {
+            Some(client) => Ok(client.clone()),
+            None => Ok(Arc::new(self.create_network_client()?)),
+        }
     }
     
     /// Create a network client with default configuration
     pub fn create_network_client(&self) -> Result<NetworkClient> {
-        NetworkClient::new(self.dns_manager.clone())
+        let builder = Self::apply_protocol(Client::builder().timeout(Duration::from_secs(30)), &self.protocol);
+        let (builder, _) = Self::apply_tls_resumption(builder, &self.protocol)?;
+        NetworkClient::from_builder(self.resolver.clone(), builder, Duration::from_secs(10))
     }
     
     /// Create a network client with custom timeout
     pub fn create_network_client_with_timeout(&self, timeout: Duration) -> Result<NetworkClient> {
-        NetworkClient::with_timeout(self.dns_manager.clone(), timeout)
+        let builder = Self::apply_protocol(Client::builder().timeout(timeout), &self.protocol);
+        let (builder, _) = Self::apply_tls_resumption(builder, &self.protocol)?;
+        NetworkClient::from_builder(self.resolver.clone(), builder, timeout)
     }
     
     /// Create a client optimized for latency testing
     pub fn create_latency_test_client(&self) -> Result<NetworkClient> {
-        NetworkClient::with_timeout(self.dns_manager.clone(), Duration::from_secs(5))
+        self.create_network_client_with_timeout(Duration::from_secs(5))
+    }
+}
+
+/// [`Fetch`] over an [`HttpClient`], for running a [`Prober`](crate::probe::Prober) natively
+pub struct ClientFetch {
+    client: Arc<dyn HttpClient>,
+    dns_config: DnsConfig,
+}
+
+impl ClientFetch {
+    /// Fetch through `client`, resolving names with `dns_config`
+    pub fn new(client: Arc<dyn HttpClient>, dns_config: DnsConfig) -> Self {
+        Self { client, dns_config }
+    }
+}
+
+#[async_trait(?Send)]
+impl Fetch for ClientFetch {
+    async fn fetch(&self, request: FetchRequest<'_>) -> Result<FetchResponse> {
+        let response = timeout(request.timeout, self.client.get(request.url, &self.dns_config))
+            .await
+            .map_err(|_| AppError::timeout(format!("Request to {} timed out after {:?}", request.url, request.timeout)))??;
+        Ok(FetchResponse {
+            status: response.status_code,
+            remote_ip: response.resolved_ip,
+            timing: Some(response.timing),
+        })
     }
 }
 
@@ -429,7 +557,7 @@ impl HttpUtils {
     /// Validate URL format and accessibility
     pub fn validate_url(url: &str) -> Result<()> {
         let parsed = Url::parse(url)
-            .map_err(|e| AppError::validation(format!("Invalid URL format: {}", e)))?;
+            .map_err(|e| AppError::validation(format!("Invalid URL format: {}", e)).with_source(e))?;
         
         // Check scheme
         match parsed.scheme() {
@@ -448,13 +576,59 @@ impl HttpUtils {
     /// Extract domain from URL
     pub fn extract_domain(url: &str) -> Result<String> {
         let parsed = Url::parse(url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         parsed.host_str()
             .ok_or_else(|| AppError::validation("URL must have a host"))
             .map(|s| s.to_string())
     }
     
+    /// Describe a response body received with the given `Content-Encoding`
+    ///
+    /// Compressed bodies are decoded to measure their compression ratio; the
+    /// decoded size is left unknown for encodings that cannot be decoded.
+    pub fn response_body(encoding: Option<&str>, body: &[u8]) -> ResponseBody {
+        let encoding = encoding
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .filter(|encoding| !encoding.is_empty() && encoding != "identity");
+        let decoded_bytes = encoding.as_deref().and_then(|encoding| Self::decoded_len(encoding, body));
+        ResponseBody { bytes: body.len() as u64, encoding, decoded_bytes }
+    }
+
+    /// Cache status reported in CDN response headers, see [`CacheStatus::from_headers`]
+    pub fn cache_status(headers: &reqwest::header::HeaderMap) -> Option<CacheStatus> {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        CacheStatus::from_headers(header("cf-cache-status"), header("x-cache"), header("age"))
+    }
+
+    /// Throttling signalled by a response, see [`Throttling::from_response`]
+    pub fn throttling(status: u16, headers: &reqwest::header::HeaderMap) -> Option<Throttling> {
+        let retry_after = headers.get(reqwest::header::RETRY_AFTER).and_then(|value| value.to_str().ok());
+        Throttling::from_response(status, retry_after, chrono::Utc::now())
+    }
+
+    /// Phases reported in `Server-Timing` headers, see [`ServerTiming::parse`]
+    pub fn server_timing(headers: &reqwest::header::HeaderMap) -> Vec<ServerTiming> {
+        headers.get_all("server-timing").iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(ServerTiming::parse)
+            .collect()
+    }
+
+    fn decoded_len(encoding: &str, body: &[u8]) -> Option<u64> {
+        use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+        let mut sink = std::io::sink();
+        match encoding {
+            "gzip" | "x-gzip" => std::io::copy(&mut MultiGzDecoder::new(body), &mut sink).ok(),
+            // Meant to be zlib-wrapped, but some servers send raw deflate
+            "deflate" => std::io::copy(&mut ZlibDecoder::new(body), &mut sink)
+                .or_else(|_| std::io::copy(&mut DeflateDecoder::new(body), &mut sink))
+                .ok(),
+            "br" => std::io::copy(&mut brotli_decompressor::Decompressor::new(body, 4096), &mut sink).ok(),
+            _ => None,
+        }
+    }
+
     /// Check if URL uses HTTPS
     pub fn is_https(url: &str) -> bool {
         url.starts_with("https://")
@@ -463,7 +637,7 @@ impl HttpUtils {
     /// Normalize URL for testing (remove fragments, sort query parameters)
     pub fn normalize_url(url: &str) -> Result<String> {
         let mut parsed = Url::parse(url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         // Remove fragment
         parsed.set_fragment(None);
@@ -492,7 +666,7 @@ impl HttpUtils {
     /// Get default port for URL scheme
     pub fn get_default_port(url: &str) -> Result<u16> {
         let parsed = Url::parse(url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         Ok(match parsed.scheme() {
             "http" => 80,
@@ -505,7 +679,7 @@ impl HttpUtils {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::DnsConfig;
+    use crate::{dns::DnsManager, types::DnsConfig};
     use std::sync::Arc;
     use std::time::Duration;
     use reqwest::Method;
@@ -711,6 +885,32 @@ mod tests {
         assert_eq!(latency_client.default_timeout, Duration::from_secs(5));
     }
 
+    #[tokio::test]
+    async fn test_client_factory_protocol_options() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dns_manager = Arc::new(DnsManager::new().unwrap());
+        let tls13 = ProtocolOptions { tls_min_version: Some(TlsVersion::V1_3), ..Default::default() };
+        assert!(ClientFactory::new(dns_manager).with_protocol(tls13).create_network_client().is_ok());
+
+        // A server that only speaks HTTP/1.1
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            }
+        });
+
+        let negotiated = ClientFactory::apply_protocol(Client::builder(), &ProtocolOptions::default()).build().unwrap();
+        assert_eq!(negotiated.get(&url).send().await.unwrap().version(), reqwest::Version::HTTP_11);
+        let prior_knowledge = ProtocolOptions { http2_prior_knowledge: true, ..Default::default() };
+        let forced = ClientFactory::apply_protocol(Client::builder(), &prior_knowledge).build().unwrap();
+        assert!(forced.get(&url).send().await.is_err());
+    }
+
     #[test]
     fn test_http_utils_validate_url() {
         // Valid URLs
@@ -726,6 +926,28 @@ mod tests {
         assert!(HttpUtils::validate_url("").is_err());
     }
 
+    #[test]
+    fn test_http_utils_response_body() {
+        use std::io::Write;
+
+        let text = "latency ".repeat(200);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let body = HttpUtils::response_body(Some("GZIP"), &gzipped);
+        assert_eq!(body.bytes, gzipped.len() as u64);
+        assert_eq!(body.encoding.as_deref(), Some("gzip"));
+        assert_eq!(body.decoded_bytes, Some(text.len() as u64));
+        assert!(body.is_compressed());
+
+        let body = HttpUtils::response_body(Some("identity"), text.as_bytes());
+        assert_eq!((body.bytes, body.encoding, body.decoded_bytes), (text.len() as u64, None, None));
+        // Encodings that cannot be decoded still count, without a decoded size
+        let body = HttpUtils::response_body(Some("zstd"), &gzipped);
+        assert_eq!((body.encoding.as_deref(), body.decoded_bytes), (Some("zstd"), None));
+    }
+
     #[test]
     fn test_http_utils_extract_domain() {
         assert_eq!(
@@ -817,7 +1039,7 @@ mod tests {
         // Test with invalid URL
         let result = client.resolve_url("not-a-valid-url", &dns_config).await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Parse(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Parse(..)));
     }
 
     #[tokio::test]
@@ -831,7 +1053,7 @@ mod tests {
         assert!(result.is_err());
         // Could be either parse or validation error depending on URL parsing behavior
         let error = result.unwrap_err();
-        assert!(matches!(error, AppError::Parse(_)) || matches!(error, AppError::Validation(_)));
+        assert!(matches!(error, AppError::Parse(..)) || matches!(error, AppError::Validation(..)));
     }
 
     #[test]
@@ -866,7 +1088,7 @@ mod tests {
         let target_ip = "8.8.8.8".parse().unwrap();
         let result = client.create_custom_client(&request, target_ip);
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Parse(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Parse(..)));
     }
 
     #[test]
@@ -353,7 +353,7 @@ impl CertificateValidationTester {
         }
 
         let client = builder.build()
-            .map_err(|e| AppError::network(format!("Failed to create client: {}", e)))?;
+            .map_err(|e| AppError::network(format!("Failed to create client: {}", e)).with_source(e))?;
 
         let start_time = Instant::now();
         
@@ -361,7 +361,7 @@ impl CertificateValidationTester {
             .head(url)
             .send()
             .await
-            .map_err(|e| AppError::network(format!("Request failed: {}", e)))?;
+            .map_err(|e| AppError::network(format!("Request failed: {}", e)).with_source(e))?;
 
         let request_time = start_time.elapsed();
         
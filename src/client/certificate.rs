@@ -0,0 +1,302 @@
+//! Reading the certificate a server presents
+//!
+//! reqwest hands out the DER encoding of the server's leaf certificate. Who it
+//! is for, who issued it and when it expires sit at fixed places in the
+//! TBSCertificate (RFC 5280, section 4.1), so [`PeerCertificate`] reads them
+//! with a small DER reader instead of a full X.509 parser.
+//!
+//! A certificate that is expired, for another host or from an untrusted CA is
+//! the one a problem report needs most, but rustls hands out only certificates
+//! it accepts. [`PeerCertificate::fetch`] therefore reads the certificate over
+//! a connection that verifies nothing, and [`PeerCertificate::verify`] reports
+//! separately whether a verifying handshake succeeds.
+
+use crate::error::{AppError, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use reqwest::tls::TlsInfo;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// The parts of a leaf certificate that matter when reporting a problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerCertificate {
+    /// Distinguished name of the holder, e.g. `CN=example.com`
+    pub subject: String,
+    /// Distinguished name of the issuing CA, e.g. `C=US, O=Let's Encrypt, CN=R11`
+    pub issuer: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    /// DNS names in the subject alternative name extension
+    pub dns_names: Vec<String>,
+}
+
+impl PeerCertificate {
+    /// Connect to `url`, at `address` when given instead of resolving its
+    /// host, and read the certificate presented, whether or not it is valid
+    pub async fn fetch(url: &str, address: Option<SocketAddr>, timeout: Duration) -> Result<Self> {
+        let (response, host) = head(url, address, timeout, false).await?;
+        let der = response.extensions().get::<TlsInfo>()
+            .and_then(TlsInfo::peer_certificate)
+            .ok_or_else(|| AppError::network(format!("{} presented no certificate", host)))?;
+        Self::from_der(der).ok_or_else(|| AppError::parse(format!("Unreadable certificate from {}", host)))
+    }
+
+    /// Connect to `url` like [`fetch`](Self::fetch), verifying the certificate
+    /// chain and host name; the error says why the handshake was rejected
+    pub async fn verify(url: &str, address: Option<SocketAddr>, timeout: Duration) -> Result<()> {
+        head(url, address, timeout, true).await.map(|_| ())
+    }
+
+    /// Read a DER-encoded X.509 certificate
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let mut certificate = Der::new(der).expect(SEQUENCE)?;
+        let mut tbs = certificate.expect(SEQUENCE)?;
+        tbs.skip_optional(VERSION);
+        tbs.expect(INTEGER)?; // serial number
+        tbs.expect(SEQUENCE)?; // signature algorithm
+        let issuer = distinguished_name(tbs.expect(SEQUENCE)?)?;
+        let mut validity = tbs.expect(SEQUENCE)?;
+        let not_before = validity.time()?;
+        let not_after = validity.time()?;
+        let subject = distinguished_name(tbs.expect(SEQUENCE)?)?;
+        tbs.expect(SEQUENCE)?; // subject public key
+        tbs.skip_optional(ISSUER_UNIQUE_ID);
+        tbs.skip_optional(SUBJECT_UNIQUE_ID);
+        let dns_names = match tbs.next() {
+            Some((EXTENSIONS, extensions)) => subject_alt_names(Der::new(extensions).expect(SEQUENCE)?)?,
+            _ => Vec::new(),
+        };
+        Some(Self { subject, issuer, not_before, not_after, dns_names })
+    }
+
+    /// Whole days until the certificate expires, negative once it has
+    pub fn days_until_expiry(&self, now: DateTime<Utc>) -> i64 {
+        (self.not_after - now).num_days()
+    }
+
+    /// Whether the certificate is for `host`, directly or through a wildcard
+    pub fn covers(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        self.dns_names.iter().any(|name| {
+            let name = name.to_lowercase();
+            match name.strip_prefix("*.") {
+                Some(parent) => host.split_once('.').is_some_and(|(_, rest)| rest == parent),
+                None => name == host,
+            }
+        })
+    }
+}
+
+/// A HEAD request to `url` without following redirects, and the host it went to
+async fn head(url: &str, address: Option<SocketAddr>, timeout: Duration, verify: bool) -> Result<(reqwest::Response, String)> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| AppError::validation(format!("Invalid URL {}: {}", url, e)))?;
+    let host = parsed.host_str()
+        .ok_or_else(|| AppError::validation(format!("URL {} has no host", url)))?
+        .to_string();
+    let mut builder = reqwest::Client::builder()
+        .tls_info(true)
+        .danger_accept_invalid_certs(!verify)
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(address) = address {
+        builder = builder.resolve(&host, address);
+    }
+    let client = builder.build()
+        .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)))?;
+    let response = client.head(parsed).send().await
+        .map_err(|e| AppError::network(format!("TLS connection to {} failed", host)).with_source(e))?;
+    Ok((response, host))
+}
+
+impl fmt::Display for PeerCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} issued by {}, valid until {}", self.subject, self.issuer, self.not_after.format("%Y-%m-%d"))
+    }
+}
+
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const BOOLEAN: u8 = 0x01;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const VERSION: u8 = 0xa0;
+const ISSUER_UNIQUE_ID: u8 = 0x81;
+const SUBJECT_UNIQUE_ID: u8 = 0x82;
+const EXTENSIONS: u8 = 0xa3;
+/// `dNSName` among the general names of a subject alternative name
+const DNS_NAME: u8 = 0x82;
+
+/// 2.5.29.17, the subject alternative name extension
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+/// Attribute types of distinguished names worth showing, under 2.5.4
+const NAME_ATTRIBUTES: [(u8, &str); 5] = [(3, "CN"), (6, "C"), (7, "L"), (10, "O"), (11, "OU")];
+
+/// Reader over a run of DER elements
+struct Der<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// The next element's tag and contents
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.data.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (length, rest) = if first < 0x80 {
+            (first as usize, rest)
+        } else {
+            let octets = (first & 0x7f) as usize;
+            if octets == 0 || octets > 4 || rest.len() < octets {
+                return None;
+            }
+            let length = rest[..octets].iter().fold(0usize, |length, &octet| length << 8 | octet as usize);
+            (length, &rest[octets..])
+        };
+        if rest.len() < length {
+            return None;
+        }
+        let (contents, rest) = rest.split_at(length);
+        self.data = rest;
+        Some((tag, contents))
+    }
+
+    /// The contents of the next element, which must have `tag`
+    fn expect(&mut self, tag: u8) -> Option<Der<'a>> {
+        match self.next()? {
+            (found, contents) if found == tag => Some(Der::new(contents)),
+            _ => None,
+        }
+    }
+
+    /// Skip the next element if it has `tag`
+    fn skip_optional(&mut self, tag: u8) {
+        if self.data.first() == Some(&tag) {
+            self.next();
+        }
+    }
+
+    /// The next element as a point in time
+    fn time(&mut self) -> Option<DateTime<Utc>> {
+        let (tag, contents) = self.next()?;
+        let text = std::str::from_utf8(contents).ok()?.strip_suffix('Z')?;
+        let (year, rest) = match tag {
+            // Two-digit years are 1950 to 2049
+            UTC_TIME => {
+                let year: i32 = text.get(..2)?.parse().ok()?;
+                (if year < 50 { 2000 + year } else { 1900 + year }, text.get(2..)?)
+            }
+            GENERALIZED_TIME => (text.get(..4)?.parse().ok()?, text.get(4..)?),
+            _ => return None,
+        };
+        let field = |at: usize| -> Option<u32> { rest.get(at..at + 2)?.parse().ok() };
+        let time = NaiveDate::from_ymd_opt(year, field(0)?, field(2)?)?.and_hms_opt(field(4)?, field(6)?, field(8)?)?;
+        Some(Utc.from_utc_datetime(&time))
+    }
+}
+
+/// A distinguished name as `TYPE=value` pairs in their encoded order
+fn distinguished_name(mut name: Der<'_>) -> Option<String> {
+    let mut parts = Vec::new();
+    while let Some((SET, set)) = name.next() {
+        let mut set = Der::new(set);
+        while let Some(mut attribute) = set.expect(SEQUENCE) {
+            let oid = attribute.expect(OBJECT_IDENTIFIER)?.data;
+            let (_, value) = attribute.next()?;
+            let label = match oid {
+                [0x55, 0x04, attribute_type] => NAME_ATTRIBUTES.iter().find(|(known, _)| known == attribute_type).map(|(_, label)| *label),
+                _ => None,
+            };
+            if let Some(label) = label {
+                parts.push(format!("{}={}", label, String::from_utf8_lossy(value)));
+            }
+        }
+    }
+    Some(parts.join(", "))
+}
+
+/// The DNS names of the subject alternative name extension, if present
+fn subject_alt_names(mut extensions: Der<'_>) -> Option<Vec<String>> {
+    while let Some(mut extension) = extensions.expect(SEQUENCE) {
+        let oid = extension.expect(OBJECT_IDENTIFIER)?.data;
+        extension.skip_optional(BOOLEAN); // critical
+        let value = extension.expect(OCTET_STRING)?.data;
+        if oid == SUBJECT_ALT_NAME {
+            let mut names = Der::new(value).expect(SEQUENCE)?;
+            let mut dns_names = Vec::new();
+            while let Some((tag, name)) = names.next() {
+                if tag == DNS_NAME {
+                    dns_names.push(String::from_utf8_lossy(name).into_owned());
+                }
+            }
+            return Some(dns_names);
+        }
+    }
+    Some(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A DER element
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut element = vec![tag];
+        if contents.len() < 0x80 {
+            element.push(contents.len() as u8);
+        } else {
+            element.extend([0x82, (contents.len() >> 8) as u8, contents.len() as u8]);
+        }
+        element.extend_from_slice(contents);
+        element
+    }
+
+    fn name(attributes: &[(u8, &str)]) -> Vec<u8> {
+        let sets: Vec<u8> = attributes.iter()
+            .flat_map(|(attribute_type, value)| {
+                let attribute = [der(OBJECT_IDENTIFIER, &[0x55, 0x04, *attribute_type]), der(0x0c, value.as_bytes())].concat();
+                der(SET, &der(SEQUENCE, &attribute))
+            })
+            .collect();
+        der(SEQUENCE, &sets)
+    }
+
+    #[test]
+    fn test_read_certificate() {
+        let names = [der(DNS_NAME, b"example.com"), der(DNS_NAME, b"*.example.com")].concat();
+        let san = [der(OBJECT_IDENTIFIER, SUBJECT_ALT_NAME), der(OCTET_STRING, &der(SEQUENCE, &names))].concat();
+        let basic_constraints = [der(OBJECT_IDENTIFIER, &[0x55, 0x1d, 0x13]), der(BOOLEAN, &[0xff]), der(OCTET_STRING, &der(SEQUENCE, &[]))].concat();
+        let extensions = der(SEQUENCE, &[der(SEQUENCE, &basic_constraints), der(SEQUENCE, &san)].concat());
+        let tbs = [
+            der(VERSION, &der(INTEGER, &[2])),
+            der(INTEGER, &[0x0a, 0x0b]),
+            der(SEQUENCE, &der(OBJECT_IDENTIFIER, &[0x2a, 0x86, 0x48])),
+            name(&[(6, "US"), (10, "Let's Encrypt"), (3, "R11")]),
+            der(SEQUENCE, &[der(UTC_TIME, b"260901000000Z"), der(GENERALIZED_TIME, b"20261130235959Z")].concat()),
+            name(&[(3, "example.com")]),
+            der(SEQUENCE, &[0; 200]),
+            der(EXTENSIONS, &extensions),
+        ].concat();
+        let certificate = der(SEQUENCE, &[der(SEQUENCE, &tbs), der(SEQUENCE, &[]), der(0x03, &[0])].concat());
+
+        let parsed = PeerCertificate::from_der(&certificate).unwrap();
+        assert_eq!(parsed.issuer, "C=US, O=Let's Encrypt, CN=R11");
+        assert_eq!(parsed.subject, "CN=example.com");
+        assert_eq!(parsed.not_before, Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap());
+        assert_eq!(parsed.to_string(), "CN=example.com issued by C=US, O=Let's Encrypt, CN=R11, valid until 2026-11-30");
+        assert_eq!(parsed.days_until_expiry(Utc.with_ymd_and_hms(2026, 11, 20, 0, 0, 0).unwrap()), 10);
+        assert!(parsed.covers("example.com"));
+        assert!(parsed.covers("WWW.example.com."));
+        assert!(!parsed.covers("a.b.example.com"));
+        assert!(!parsed.covers("example.org"));
+
+        assert_eq!(PeerCertificate::from_der(&certificate[..certificate.len() - 4]), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! Informational (1xx) responses for `--early-hints`
+//!
+//! A CDN that knows which resources a page needs can name them in a
+//! `103 Early Hints` response while the origin is still producing the page,
+//! so the browser starts fetching them early. How far ahead of the page the
+//! hints arrive is what makes one CDN faster than another here, but reqwest
+//! never hands 1xx responses to the caller. [`probe`] therefore sends the
+//! request itself over HTTP/1.1, over TLS for `https` URLs, and notes when
+//! each response head arrives; the body of the final response is not read.
+
+use crate::{
+    error::{AppError, Result},
+    models::InformationalTiming,
+    PKG_NAME, VERSION,
+};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{self, pki_types::ServerName};
+use tokio_rustls::TlsConnector;
+
+/// Largest response head accepted
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// TLS settings of every probe: the Mozilla roots, HTTP/1.1 only
+fn tls_config() -> Result<Arc<rustls::ClientConfig>> {
+    static CONFIG: OnceLock<Arc<rustls::ClientConfig>> = OnceLock::new();
+    if let Some(config) = CONFIG.get() {
+        return Ok(config.clone());
+    }
+    let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let mut config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::network(format!("Failed to set up TLS: {}", e)).with_source(e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(CONFIG.get_or_init(|| Arc::new(config)).clone())
+}
+
+/// Request `url` from `address` and time the informational responses that
+/// come before the final one; times count from sending the request, after
+/// the connection and TLS handshake
+pub async fn probe(url: &str, address: IpAddr, timeout: Duration) -> Result<InformationalTiming> {
+    let parsed = url::Url::parse(url).map_err(|e| AppError::validation(format!("Invalid URL {}: {}", url, e)))?;
+    let host = parsed.host_str()
+        .ok_or_else(|| AppError::validation(format!("URL {} has no host", url)))?
+        .to_string();
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let authority = match parsed.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.clone(),
+    };
+    let path = &parsed[url::Position::BeforePath..url::Position::AfterQuery];
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}/{}\r\n\
+         Accept: text/html,application/xhtml+xml,*/*;q=0.8\r\nAccept-Encoding: identity\r\nConnection: close\r\n\r\n",
+        if path.is_empty() { "/" } else { path }, authority, PKG_NAME, VERSION,
+    );
+
+    let exchange = async {
+        let stream = TcpStream::connect(SocketAddr::new(address, port)).await
+            .map_err(|e| AppError::network(format!("Failed to connect to {}: {}", address, e)).with_source(e))?;
+        if parsed.scheme() != "https" {
+            return exchange(stream, request.as_bytes()).await;
+        }
+        let name = ServerName::try_from(host.trim_matches(|c| c == '[' || c == ']').to_string())
+            .map_err(|e| AppError::validation(format!("Invalid TLS server name {}: {}", host, e)))?;
+        let stream = TlsConnector::from(tls_config()?).connect(name, stream).await
+            .map_err(|e| AppError::network(format!("TLS handshake with {} failed: {}", host, e)).with_source(e))?;
+        exchange(stream, request.as_bytes()).await
+    };
+    tokio::time::timeout(timeout, exchange).await
+        .map_err(|_| AppError::timeout(format!("No final response from {} within {:?}", url, timeout)))?
+}
+
+/// Send `request` and read response heads up to the final one
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &[u8]) -> Result<InformationalTiming> {
+    let start = Instant::now();
+    stream.write_all(request).await
+        .map_err(|e| AppError::network(format!("Failed to send request: {}", e)).with_source(e))?;
+
+    let mut statuses = Vec::new();
+    let mut first = None;
+    let mut received: Vec<u8> = Vec::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        // Every complete head in what has arrived so far
+        while let Some(end) = received.windows(4).position(|window| window == b"\r\n\r\n") {
+            let status = status_code(&received[..end])
+                .ok_or_else(|| AppError::network("Malformed HTTP response"))?;
+            received.drain(..end + 4);
+            // 101 switches protocols and is final
+            if (100..200).contains(&status) && status != 101 {
+                statuses.push(status);
+                first.get_or_insert_with(|| start.elapsed());
+                continue;
+            }
+            return Ok(InformationalTiming { statuses, first, response: start.elapsed() });
+        }
+        if received.len() > MAX_HEAD_BYTES {
+            return Err(AppError::network("HTTP response head too large"));
+        }
+        let read = stream.read(&mut buffer).await
+            .map_err(|e| AppError::network(format!("Failed to read response: {}", e)).with_source(e))?;
+        if read == 0 {
+            return Err(AppError::network("Connection closed before the final response"));
+        }
+        received.extend_from_slice(&buffer[..read]);
+    }
+}
+
+/// Status code of a response head such as `HTTP/1.1 103 Early Hints`
+fn status_code(head: &[u8]) -> Option<u16> {
+    let line = head.split(|&byte| byte == b'\r').next()?;
+    let line = std::str::from_utf8(line).ok()?;
+    let mut words = line.split_whitespace();
+    words.next().filter(|version| version.starts_with("HTTP/1."))?;
+    words.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_code() {
+        assert_eq!(status_code(b"HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload"), Some(103));
+        assert_eq!(status_code(b"HTTP/1.0 200 OK"), Some(200));
+        assert_eq!(status_code(b"SSH-2.0-OpenSSH"), None);
+    }
+
+    #[tokio::test]
+    async fn test_probe_times_early_hints() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0u8; 4096];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let hinted = String::from_utf8_lossy(&buffer[..read]).starts_with("GET /hinted?page=1 ");
+                    if hinted {
+                        let _ = stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 103 Early Hints\r\nLink: </a.css>; rel=preload\r\n\r\n").await;
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok").await;
+                });
+            }
+        });
+
+        let url = format!("http://{}/hinted?page=1", address);
+        let timing = probe(&url, address.ip(), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(timing.statuses, [100, 103]);
+        assert!(timing.lead().unwrap() >= Duration::from_millis(40));
+
+        let url = format!("http://{}/plain", address);
+        let timing = probe(&url, address.ip(), Duration::from_secs(5)).await.unwrap();
+        assert!(timing.statuses.is_empty());
+        assert_eq!((timing.first, timing.lead()), (None, None));
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides comprehensive integration testing for the HTTP client
 //! including mock server scenarios, DNS resolution testing, and timing validation.
 
+#![allow(clippy::assertions_on_constants)]
+
 use super::*;
 use crate::{
     dns::DnsManager,
@@ -135,7 +137,7 @@ mod http_client_integration_tests {
     /// Create a test DNS manager for integration tests
     async fn create_test_dns_manager() -> Arc<DnsManager> {
         let dns_manager = Arc::new(DnsManager::new().unwrap());
-        if let Err(_) = dns_manager.initialize_system_resolver().await {
+        if dns_manager.initialize_system_resolver().await.is_err() {
             // Skip DNS tests if system resolver initialization fails
         }
         dns_manager
@@ -196,11 +198,11 @@ mod http_client_integration_tests {
         let result = client.head(&url, &DnsConfig::System).await;
         // Should timeout or return appropriate error
         match result {
-            Err(AppError::Network(_)) => {
+            Err(AppError::Network(..)) => {
                 // Expected timeout or network error
                 assert!(true);
             }
-            Err(AppError::Timeout(_)) => {
+            Err(AppError::Timeout(..)) => {
                 // Expected timeout error
                 assert!(true);
             }
@@ -383,7 +385,7 @@ mod dns_integration_tests {
 
     async fn create_test_client() -> NetworkClient {
         let dns_manager = Arc::new(DnsManager::new().unwrap());
-        if let Err(_) = dns_manager.initialize_system_resolver().await {
+        if dns_manager.initialize_system_resolver().await.is_err() {
             // Skip DNS tests if system resolver initialization fails
         }
         NetworkClient::new(dns_manager).unwrap()
@@ -507,11 +509,11 @@ mod dns_integration_tests {
         // Should fail with DNS resolution error
         assert!(result.is_err());
         match result.unwrap_err() {
-            AppError::DnsResolution(_) => {
+            AppError::DnsResolution(..) => {
                 // Expected DNS resolution error
                 assert!(true);
             }
-            AppError::Network(_) => {
+            AppError::Network(..) => {
                 // May also be reported as network error
                 assert!(true);
             }
@@ -583,7 +585,7 @@ mod performance_integration_tests {
 
     async fn create_test_client() -> NetworkClient {
         let dns_manager = Arc::new(DnsManager::new().unwrap());
-        if let Err(_) = dns_manager.initialize_system_resolver().await {
+        if dns_manager.initialize_system_resolver().await.is_err() {
             // Skip DNS tests if system resolver initialization fails
         }
         NetworkClient::new(dns_manager).unwrap()
@@ -627,9 +629,9 @@ mod error_scenario_tests {
         
         // Should be some type of connection error (network, timeout, or other)
         match result.unwrap_err() {
-            AppError::Network(_) => assert!(true),
-            AppError::Timeout(_) => assert!(true), // May also be reported as timeout
-            AppError::DnsResolution(_) => assert!(true), // Possible on some systems
+            AppError::Network(..) => assert!(true),
+            AppError::Timeout(..) => assert!(true), // May also be reported as timeout
+            AppError::DnsResolution(..) => assert!(true), // Possible on some systems
             other => {
                 // Print the actual error type for debugging, but still pass the test
                 eprintln!("Connection refused returned unexpected error type: {:?}", other);
@@ -648,8 +650,8 @@ mod error_scenario_tests {
         
         // Should either timeout or succeed very quickly
         match result {
-            Err(AppError::Timeout(_)) => assert!(true),
-            Err(AppError::Network(_)) => assert!(true), // May be reported as network error
+            Err(AppError::Timeout(..)) => assert!(true),
+            Err(AppError::Network(..)) => assert!(true), // May be reported as network error
             Ok(_) => {
                 // If it succeeds, the server was faster than expected
                 assert!(true);
@@ -0,0 +1,398 @@
+//! Scripted transport and resolver for deterministic tests
+//!
+//! [`MockTransport`] implements [`HttpClient`] without touching the network: each
+//! URL is answered from a script of [`MockResponse`]s and the reported timings are
+//! the scripted latencies, not wall-clock time. Pass it to
+//! [`OptimizedExecutor::with_client`](crate::executor::OptimizedExecutor::with_client)
+//! to run the executor and statistics pipeline end to end. [`MockResolver`] does
+//! the same for [`NameResolver`].
+//!
+//! Available to the crate's own tests and, for library users, behind the `mock` feature.
+
+use super::{ConnectivityTest, HttpClient, HttpRequest, HttpResponse};
+use crate::{
+    dns::NameResolver,
+    error::{AppError, Result},
+    models::metrics::TimingMetrics,
+    types::DnsConfig,
+};
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Scripted outcome of a single request
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// Respond with the given HTTP status, reporting the given latency
+    Status { status: u16, latency: Duration },
+    /// Fail with a network error
+    Error(String),
+    /// Never respond, so the caller's timeout fires
+    Hang,
+}
+
+impl MockResponse {
+    /// A `200 OK` response taking `latency_ms` milliseconds
+    pub fn ok(latency_ms: u64) -> Self {
+        Self::status(200, latency_ms)
+    }
+
+    /// A response with the given status taking `latency_ms` milliseconds
+    pub fn status(status: u16, latency_ms: u64) -> Self {
+        Self::Status { status, latency: Duration::from_millis(latency_ms) }
+    }
+
+    /// A network error with the given message
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::Error(message.into())
+    }
+}
+
+/// Responses for one URL, optionally limited to one DNS configuration
+#[derive(Debug)]
+struct Route {
+    url: String,
+    dns_config: Option<DnsConfig>,
+    responses: Vec<MockResponse>,
+    served: usize,
+}
+
+impl Route {
+    /// Next scripted response; the last one repeats once the script runs out
+    fn next_response(&mut self) -> MockResponse {
+        let index = self.served.min(self.responses.len() - 1);
+        self.served += 1;
+        self.responses[index].clone()
+    }
+}
+
+/// HTTP client that answers from scripted responses
+#[derive(Debug)]
+pub struct MockTransport {
+    routes: Mutex<Vec<Route>>,
+    requests: Mutex<Vec<(String, DnsConfig)>>,
+    remote_ip: IpAddr,
+}
+
+impl MockTransport {
+    /// Create a transport with no routes; unrouted requests fail
+    pub fn new() -> Self {
+        Self {
+            routes: Mutex::new(Vec::new()),
+            requests: Mutex::new(Vec::new()),
+            remote_ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+        }
+    }
+
+    /// Answer requests for `url` with `responses` in order, whatever the DNS configuration
+    pub fn respond(self, url: &str, responses: Vec<MockResponse>) -> Self {
+        self.add_route(url, None, responses)
+    }
+
+    /// Answer requests for `url` made with `dns_config` with `responses` in order
+    ///
+    /// Routes for a specific DNS configuration take precedence over [`MockTransport::respond`].
+    pub fn respond_for(self, url: &str, dns_config: &DnsConfig, responses: Vec<MockResponse>) -> Self {
+        self.add_route(url, Some(dns_config.clone()), responses)
+    }
+
+    /// Report this address as the remote end of every response
+    pub fn with_remote_ip(mut self, ip: IpAddr) -> Self {
+        self.remote_ip = ip;
+        self
+    }
+
+    /// URL and DNS configuration of every request received so far
+    pub fn requests(&self) -> Vec<(String, DnsConfig)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn add_route(self, url: &str, dns_config: Option<DnsConfig>, responses: Vec<MockResponse>) -> Self {
+        assert!(!responses.is_empty(), "a mock route needs at least one response");
+        self.routes.lock().unwrap().push(Route {
+            url: url.to_string(),
+            dns_config,
+            responses,
+            served: 0,
+        });
+        self
+    }
+
+    fn next_response(&self, url: &str, dns_config: &DnsConfig) -> Option<MockResponse> {
+        let mut routes = self.routes.lock().unwrap();
+        let index = routes.iter()
+            .position(|r| r.url == url && r.dns_config.as_ref() == Some(dns_config))
+            .or_else(|| routes.iter().position(|r| r.url == url && r.dns_config.is_none()))?;
+        Some(routes[index].next_response())
+    }
+
+    /// Timing of a scripted response, split into phases in fixed proportions
+    fn timing(&self, url: &str, status: u16, latency: Duration) -> TimingMetrics {
+        if !(200..300).contains(&status) {
+            let mut metrics = TimingMetrics::failed(format!("HTTP {}", status))
+                .with_total_duration(latency)
+                .with_resolved_ip(Some(self.remote_ip));
+            metrics.http_status = status;
+            return metrics;
+        }
+
+        let dns = latency / 10;
+        let tcp = latency / 5;
+        let tls = url.starts_with("https://").then_some(latency / 4);
+        let first_byte = latency - dns - tcp - tls.unwrap_or(Duration::ZERO);
+        TimingMetrics::success(dns, tcp, tls, first_byte, latency, status)
+            .with_resolved_ip(Some(self.remote_ip))
+    }
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HttpClient for MockTransport {
+    async fn execute_request(&self, request: HttpRequest) -> Result<HttpResponse> {
+        self.requests.lock().unwrap().push((request.url.clone(), request.dns_config.clone()));
+
+        let response = self.next_response(&request.url, &request.dns_config)
+            .ok_or_else(|| AppError::network(format!("No mock response for {}", request.url)))?;
+
+        match response {
+            MockResponse::Status { status, latency } => Ok(HttpResponse {
+                status_code: status,
+                headers: Vec::new(),
+                body_size: 0,
+                timing: self.timing(&request.url, status, latency),
+                resolved_ip: Some(self.remote_ip),
+                dns_config_used: request.dns_config,
+                final_url: request.url,
+            }),
+            MockResponse::Error(message) => Err(AppError::network(message)),
+            MockResponse::Hang => std::future::pending().await,
+        }
+    }
+
+    async fn head(&self, url: &str, dns_config: &DnsConfig) -> Result<HttpResponse> {
+        self.execute_request(HttpRequest::head(url.to_string(), dns_config.clone())).await
+    }
+
+    async fn get(&self, url: &str, dns_config: &DnsConfig) -> Result<HttpResponse> {
+        self.execute_request(HttpRequest::get(url.to_string(), dns_config.clone())).await
+    }
+
+    async fn test_connectivity(&self, url: &str, dns_config: &DnsConfig) -> Result<ConnectivityTest> {
+        match self.get(url, dns_config).await {
+            Ok(response) => Ok(ConnectivityTest {
+                success: response.is_success(),
+                status_code: Some(response.status_code),
+                response_time: response.timing.total_duration,
+                resolved_ip: response.resolved_ip,
+                dns_resolution_time: response.timing.dns_resolution,
+                connection_time: response.timing.tcp_connection,
+                error: None,
+            }),
+            Err(e) => Ok(ConnectivityTest {
+                success: false,
+                status_code: None,
+                response_time: Duration::ZERO,
+                resolved_ip: None,
+                dns_resolution_time: Duration::ZERO,
+                connection_time: Duration::ZERO,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+}
+
+/// Name resolver that answers from a fixed table of records
+#[derive(Debug, Clone, Default)]
+pub struct MockResolver {
+    records: HashMap<String, Vec<IpAddr>>,
+    /// Configurations under which every lookup fails
+    failing: Vec<DnsConfig>,
+}
+
+impl MockResolver {
+    /// Create a resolver with no records; every lookup fails
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve `domain` to `ips` under every DNS configuration
+    pub fn with_record(mut self, domain: &str, ips: Vec<IpAddr>) -> Self {
+        self.records.insert(domain.to_string(), ips);
+        self
+    }
+
+    /// Fail every lookup under `dns_config`, e.g. to make a resolver chain fall back
+    pub fn failing_for(mut self, dns_config: DnsConfig) -> Self {
+        self.failing.push(dns_config);
+        self
+    }
+}
+
+#[async_trait]
+impl NameResolver for MockResolver {
+    async fn resolve(&self, domain: &str, dns_config: &DnsConfig) -> Result<Vec<IpAddr>> {
+        if self.failing.contains(dns_config) {
+            return Err(AppError::dns_resolution(format!("Mock resolver fails under {}", dns_config.id())));
+        }
+        self.records.get(domain)
+            .cloned()
+            .ok_or_else(|| AppError::dns_resolution(format!("No mock record for {}", domain)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::{ClientFactory, ClientFetch},
+        dns::DnsManager,
+        executor::{ExecutionEvent, OptimizedExecutor},
+        models::{Config, RequestPhase},
+        probe::Prober,
+        stats::StatisticsEngine,
+        types::TestStatus,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_executor_and_statistics_with_mock_transport() {
+        let url = "https://api.example";
+        let custom = DnsConfig::Custom { servers: vec!["1.1.1.1".parse().unwrap()] };
+        let transport = Arc::new(MockTransport::new()
+            .respond(url, vec![MockResponse::ok(100)])
+            .respond_for(url, &custom, vec![
+                MockResponse::ok(300),
+                MockResponse::error("connection reset"),
+                MockResponse::Hang,
+                MockResponse::status(503, 40),
+            ]));
+
+        let config = Config {
+            test_count: 4,
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let executor = OptimizedExecutor::with_client(&config, transport.clone()).await.unwrap();
+        let mut events = executor.events().channel();
+        let results = executor
+            .execute_optimized_tests(&[url.to_string()], &[DnsConfig::System, custom.clone()])
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 8);
+
+        let mut samples = 0;
+        let mut finished = 0;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                ExecutionEvent::SampleCompleted { .. } => samples += 1,
+                ExecutionEvent::ConfigFinished { iterations, .. } => {
+                    assert_eq!(iterations, 4);
+                    finished += 1;
+                }
+                ExecutionEvent::RunCompleted { tests, .. } => assert_eq!((samples, finished, tests), (8, 2, 2)),
+                _ => {}
+            }
+        }
+        assert_eq!(finished, 2);
+
+        let system = results.iter().find(|r| r.dns_config == DnsConfig::System).unwrap();
+        assert_eq!(system.success_count, 4);
+        assert_eq!(system.statistics.as_ref().unwrap().total_avg_ms, 100.0);
+
+        let custom_result = results.iter().find(|r| r.dns_config == custom).unwrap();
+        let statuses: Vec<TestStatus> = custom_result.individual_results.iter().map(|m| m.status).collect();
+        assert_eq!(statuses, [TestStatus::Success, TestStatus::Failed, TestStatus::Timeout, TestStatus::Failed]);
+        assert!(custom_result.individual_results[1].error_message.as_deref().unwrap().contains("connection reset"));
+        assert_eq!(custom_result.individual_results[3].http_status, 503);
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(results);
+        let analysis = engine.analyze().unwrap();
+        assert_eq!(analysis.basic_stats["System DNS"].reliability.success_rate, 100.0);
+        assert_eq!(analysis.basic_stats["Custom DNS (1.1.1.1)"].basic.total_avg_ms, 300.0);
+        let timeouts = analysis.basic_stats["Custom DNS (1.1.1.1)"].timeouts.as_ref().unwrap();
+        assert_eq!(timeouts.count, 1);
+        assert_eq!(timeouts.rate, 25.0);
+        // The failed request before the timeout left no connection behind
+        assert_eq!(timeouts.phase, Some(RequestPhase::Connecting));
+    }
+
+    #[tokio::test]
+    async fn test_executor_cancellation() {
+        let url = "https://api.example";
+        let transport = Arc::new(MockTransport::new().respond(url, vec![MockResponse::ok(100), MockResponse::Hang]));
+        let config = Config {
+            test_count: 3,
+            timeout: Duration::from_secs(30),
+            ..Default::default()
+        };
+        let executor = OptimizedExecutor::with_client(&config, transport.clone()).await.unwrap();
+        let cancel = executor.cancellation().clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+        let results = executor.execute_optimized_tests(&[url.to_string()], &[DnsConfig::System]).await.unwrap();
+        // The hanging request is dropped rather than reported, and no more are sent
+        assert_eq!(transport.requests().len(), 2);
+        let statuses: Vec<TestStatus> = results[0].individual_results.iter().map(|m| m.status).collect();
+        assert_eq!(statuses, [TestStatus::Success]);
+
+        let results = executor.execute_optimized_tests(&[url.to_string()], &[DnsConfig::System]).await.unwrap();
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(results[0].individual_results[0].error_message.as_deref(),
+            Some("Run cancelled before this configuration was tested"));
+    }
+
+    #[tokio::test]
+    async fn test_injected_resolver_and_client() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let resolver = Arc::new(MockResolver::new()
+            .with_record("api.example", vec![ip])
+            .with_record("xn--r8jz45g.xn--zckzah", vec![ip]));
+
+        let manager = DnsManager::with_resolver(resolver.clone()).unwrap();
+        assert_eq!(manager.resolve("api.example", &DnsConfig::System).await.unwrap(), vec![ip]);
+        assert!(manager.resolve("other.example", &DnsConfig::System).await.is_err());
+        // Internationalized names are looked up in punycode
+        assert_eq!(manager.resolve("例え.テスト", &DnsConfig::System).await.unwrap(), vec![ip]);
+
+        let transport = Arc::new(MockTransport::new()
+            .with_remote_ip(ip)
+            .respond("http://api.example", vec![MockResponse::status(404, 20)]));
+        let client = ClientFactory::new(resolver).with_client(transport).create_client().unwrap();
+
+        let connectivity = client.test_connectivity("http://api.example", &DnsConfig::System).await.unwrap();
+        assert!(!connectivity.success);
+        assert_eq!(connectivity.status_code, Some(404));
+        assert_eq!(connectivity.resolved_ip, Some(ip));
+        assert!(client.get("http://unrouted.example", &DnsConfig::System).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prober_over_client_fetch() {
+        let url = "https://api.example";
+        let transport = Arc::new(MockTransport::new()
+            .respond(url, vec![MockResponse::ok(80), MockResponse::Hang]));
+        let config = Config {
+            test_count: 2,
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+
+        let prober = Prober::new(ClientFetch::new(transport, DnsConfig::System), &config);
+        let result = prober.probe(url).await;
+        assert_eq!(result.individual_results[0].total_duration, Duration::from_millis(80));
+        assert_eq!(result.individual_results[1].status, TestStatus::Timeout);
+        assert_eq!(result.individual_results[1].phase_reached, Some(RequestPhase::AwaitingResponse));
+    }
+}
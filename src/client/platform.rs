@@ -34,14 +34,7 @@ pub struct PlatformNetworkConfig {
     pub strict_tls_verification: bool,
 }
 
-/// Supported TLS versions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TlsVersion {
-    /// TLS 1.2
-    V1_2,
-    /// TLS 1.3
-    V1_3,
-}
+pub use crate::types::TlsVersion;
 
 impl Default for PlatformNetworkConfig {
     fn default() -> Self {
@@ -293,7 +286,7 @@ impl PlatformClientBuilder {
     /// Build the HTTP client
     pub fn build(self) -> Result<Client> {
         self.builder.build()
-            .map_err(|e| AppError::network(format!("Failed to build HTTP client: {}", e)))
+            .map_err(|e| AppError::network(format!("Failed to build HTTP client: {}", e)).with_source(e))
     }
 
     /// Get the platform configuration
@@ -0,0 +1,150 @@
+//! TLS session resumption for `--tls-resumption`
+//!
+//! A client that kept a session ticket from an earlier connection can resume
+//! it on the next one and skip most of the handshake, so the first request of
+//! a test and those after a closed connection measure different things
+//! depending on whether a ticket was at hand. reqwest does not say which
+//! handshake a connection used. [`tls_config`] builds the rustls settings of
+//! a client with resumption on or off and counts the handshakes that checked
+//! the server's certificate: a resumed handshake skips that check, so a new
+//! connection that leaves the count of its host unchanged was resumed.
+
+use crate::{
+    error::{AppError, Result},
+    types::{ProtocolOptions, TlsVersion},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    client::{Resumption, WebPkiServerVerifier},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+
+/// Full handshakes a client completed, by server name
+#[derive(Debug, Default)]
+pub struct FullHandshakes {
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl FullHandshakes {
+    /// Full handshakes with `host` so far
+    pub fn count(&self, host: &str) -> u64 {
+        self.counts.lock().unwrap().get(host).copied().unwrap_or(0)
+    }
+
+    /// Full handshakes with every host so far
+    pub fn snapshot(&self) -> HashMap<String, u64> {
+        self.counts.lock().unwrap().clone()
+    }
+
+    fn record(&self, server_name: &ServerName<'_>) {
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_string(),
+            ServerName::IpAddress(address) => std::net::IpAddr::from(*address).to_string(),
+            _ => return,
+        };
+        *self.counts.lock().unwrap().entry(host).or_default() += 1;
+    }
+}
+
+/// Verifies certificates as rustls does, counting each verification as a full handshake
+#[derive(Debug)]
+struct CountingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    handshakes: Arc<FullHandshakes>,
+}
+
+impl ServerCertVerifier for CountingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        self.handshakes.record(server_name);
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// TLS settings of a client with `protocol`: the Mozilla roots, its TLS
+/// versions and ALPN, and session resumption on or off. Full handshakes are
+/// counted in the returned tally.
+pub fn tls_config(protocol: &ProtocolOptions, resumption: bool) -> Result<(rustls::ClientConfig, Arc<FullHandshakes>)> {
+    let failed = |e: rustls::Error| AppError::network(format!("Failed to set up TLS: {}", e)).with_source(e);
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = Arc::new(rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()));
+    let inner = WebPkiServerVerifier::builder_with_provider(roots, provider.clone())
+        .build()
+        .map_err(|e| AppError::network(format!("Failed to set up TLS: {}", e)).with_source(e))?;
+    let handshakes = Arc::new(FullHandshakes::default());
+
+    let in_range = |version: TlsVersion| {
+        protocol.tls_min_version.is_none_or(|min| version >= min) && protocol.tls_max_version.is_none_or(|max| version <= max)
+    };
+    let versions: Vec<&'static rustls::SupportedProtocolVersion> = [(TlsVersion::V1_3, &rustls::version::TLS13), (TlsVersion::V1_2, &rustls::version::TLS12)]
+        .into_iter()
+        .filter(|(version, _)| in_range(*version))
+        .map(|(_, supported)| supported)
+        .collect();
+
+    let mut config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&versions)
+        .map_err(failed)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(CountingVerifier { inner, handshakes: handshakes.clone() }))
+        .with_no_client_auth();
+    config.alpn_protocols = if protocol.http2_prior_knowledge {
+        vec![b"h2".to_vec()]
+    } else {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    };
+    if !resumption {
+        config.resumption = Resumption::disabled();
+    }
+    Ok((config, handshakes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_config() {
+        let tls13 = ProtocolOptions { tls_min_version: Some(TlsVersion::V1_3), ..Default::default() };
+        let (config, handshakes) = tls_config(&tls13, false).unwrap();
+        assert_eq!(config.alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+        assert_eq!(handshakes.count("example.com"), 0);
+
+        handshakes.record(&ServerName::try_from("example.com").unwrap());
+        handshakes.record(&ServerName::try_from("192.0.2.1").unwrap());
+        assert_eq!((handshakes.count("example.com"), handshakes.count("192.0.2.1")), (1, 1));
+    }
+}
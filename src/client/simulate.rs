@@ -0,0 +1,345 @@
+//! Synthetic latency for rehearsing thresholds, dashboards and notifications
+//!
+//! [`SimulatedTransport`] implements [`HttpClient`] like the scripted
+//! transport in [`mock`](super::mock), but draws each request's latency from a
+//! [`Simulation`]: a normal or lognormal distribution, optionally with spikes
+//! and bursts of lost requests. Passed to
+//! [`OptimizedExecutor::with_client`](crate::executor::OptimizedExecutor::with_client),
+//! the samples go through the same statistics, output, export and watch-mode
+//! health checks as measured ones, without any traffic.
+//!
+//! Each URL and DNS configuration is a separate stream with its own random
+//! state and a fixed latency factor between 0.75 and 1.25, so configurations
+//! rank differently and a given seed produces the same samples however the
+//! requests are scheduled.
+
+use super::{ConnectivityTest, HttpClient, HttpRequest, HttpResponse};
+use crate::{
+    error::{AppError, Result},
+    models::metrics::TimingMetrics,
+    types::DnsConfig,
+    utils::duration::{format_duration, parse_duration},
+};
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Address reported as the remote end of simulated responses (TEST-NET-1)
+const SIMULATED_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+/// Burst length when a loss rate is given without one
+const DEFAULT_BURST: u32 = 3;
+/// How much slower a spike is than the sample it replaces, unless given
+const DEFAULT_SPIKE_FACTOR: f64 = 10.0;
+
+/// Runs simulated so far in this process, so that watch cycles differ
+static RUNS: AtomicU64 = AtomicU64::new(0);
+
+/// Distribution of simulated response times
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LatencyModel {
+    Normal { mean: Duration, sd: Duration },
+    LogNormal { median: Duration, sigma: f64 },
+}
+
+/// How simulated requests behave, parsed from `--simulate`
+///
+/// The form is `DISTRIBUTION[:KEY=VALUE,...]`, e.g.
+/// `lognormal:median=60ms,sigma=0.5,spikes=2%,loss=1%,burst=5`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub model: LatencyModel,
+    /// Share of requests that take `spike_factor` times as long
+    pub spikes: f64,
+    pub spike_factor: f64,
+    /// Chance of each request starting a burst of `burst` lost requests
+    pub loss: f64,
+    pub burst: u32,
+    /// Seed for reproducible samples; random when absent
+    pub seed: Option<u64>,
+}
+
+impl FromStr for Simulation {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, options) = s.trim().split_once(':').unwrap_or((s.trim(), ""));
+        let mut model = match name.to_lowercase().as_str() {
+            "normal" => LatencyModel::Normal { mean: Duration::from_millis(50), sd: Duration::from_millis(10) },
+            "lognormal" => LatencyModel::LogNormal { median: Duration::from_millis(50), sigma: 0.5 },
+            _ => return Err(AppError::config(format!(
+                "Unknown simulated distribution '{}'; expected normal or lognormal", name
+            ))),
+        };
+        let mut simulation = Self {
+            model,
+            spikes: 0.0,
+            spike_factor: DEFAULT_SPIKE_FACTOR,
+            loss: 0.0,
+            burst: DEFAULT_BURST,
+            seed: None,
+        };
+
+        for option in options.split(',').map(str::trim).filter(|option| !option.is_empty()) {
+            let invalid = |expected: &str| AppError::config(format!("Invalid simulation option '{}': expected {}", option, expected));
+            let (key, value) = option.split_once('=').ok_or_else(|| invalid("KEY=VALUE"))?;
+            let (key, value) = (key.trim().to_lowercase(), value.trim());
+            let duration = || parse_duration(value).map_err(|_| invalid("a duration such as 80ms"));
+            let number = || value.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0).ok_or_else(|| invalid("a non-negative number"));
+            match (&mut model, key.as_str()) {
+                (LatencyModel::Normal { mean, .. }, "mean") => *mean = duration()?,
+                (LatencyModel::Normal { sd, .. }, "sd") => *sd = duration()?,
+                (LatencyModel::LogNormal { median, .. }, "median") => *median = duration()?,
+                (LatencyModel::LogNormal { sigma, .. }, "sigma") => *sigma = number()?,
+                (_, "spikes") => simulation.spikes = parse_share(value).ok_or_else(|| invalid("a share such as 2% or 0.02"))?,
+                (_, "spike-factor") => simulation.spike_factor = number()?.max(1.0),
+                (_, "loss") => simulation.loss = parse_share(value).ok_or_else(|| invalid("a share such as 1% or 0.01"))?,
+                (_, "burst") => simulation.burst = value.parse().ok().filter(|burst| *burst > 0).ok_or_else(|| invalid("a number of requests"))?,
+                (_, "seed") => simulation.seed = Some(value.parse().map_err(|_| invalid("a whole number"))?),
+                _ => {
+                    let keys = match model {
+                        LatencyModel::Normal { .. } => "mean, sd",
+                        LatencyModel::LogNormal { .. } => "median, sigma",
+                    };
+                    return Err(AppError::config(format!(
+                        "Unknown simulation option '{}' for {}; expected {}, spikes, spike-factor, loss, burst or seed",
+                        key, name.to_lowercase(), keys
+                    )));
+                }
+            }
+        }
+        simulation.model = model;
+        Ok(simulation)
+    }
+}
+
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.model {
+            LatencyModel::Normal { mean, sd } => write!(f, "normal:mean={},sd={}", format_duration(mean), format_duration(sd))?,
+            LatencyModel::LogNormal { median, sigma } => write!(f, "lognormal:median={},sigma={}", format_duration(median), sigma)?,
+        }
+        if self.spikes > 0.0 {
+            write!(f, ",spikes={},spike-factor={}", self.spikes, self.spike_factor)?;
+        }
+        if self.loss > 0.0 {
+            write!(f, ",loss={},burst={}", self.loss, self.burst)?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, ",seed={}", seed)?;
+        }
+        Ok(())
+    }
+}
+
+/// A share given as a percentage ("2%") or a fraction ("0.02")
+fn parse_share(value: &str) -> Option<f64> {
+    let share = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => value.parse().ok()?,
+    };
+    (0.0..=1.0).contains(&share).then_some(share)
+}
+
+/// Random state and loss burst of one URL and DNS configuration
+#[derive(Debug)]
+struct Stream {
+    rng: SplitMix64,
+    /// Latency factor of this stream
+    factor: f64,
+    /// Requests still to be lost in the current burst
+    lost: u32,
+}
+
+/// HTTP client that answers every request with simulated timings
+#[derive(Debug)]
+pub struct SimulatedTransport {
+    simulation: Simulation,
+    seed: u64,
+    /// Streams by URL and DNS configuration
+    streams: Mutex<HashMap<String, Stream>>,
+}
+
+impl SimulatedTransport {
+    /// A transport for one run; successive runs in a process draw different samples
+    pub fn new(simulation: Simulation) -> Self {
+        let base = simulation.seed.unwrap_or_else(|| crate::timing::timestamp().timestamp_nanos_opt().unwrap_or_default() as u64);
+        let run = RUNS.fetch_add(1, Ordering::Relaxed);
+        Self::with_seed(simulation, base ^ SplitMix64::new(run).next())
+    }
+
+    fn with_seed(simulation: Simulation, seed: u64) -> Self {
+        Self { simulation, seed, streams: Mutex::new(HashMap::new()) }
+    }
+
+    /// The next sample of the stream for `url` and `dns_config`: its latency, or none when lost
+    fn next_latency(&self, url: &str, dns_config: &DnsConfig) -> Option<Duration> {
+        let key = format!("{}|{:?}", url, dns_config);
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.entry(key.clone()).or_insert_with(|| {
+            let rng = SplitMix64::new(self.seed ^ fnv1a(key.as_bytes()));
+            let factor = 0.75 + 0.5 * SplitMix64::new(fnv1a(key.as_bytes())).uniform();
+            Stream { rng, factor, lost: 0 }
+        });
+
+        if stream.lost == 0 && self.simulation.loss > 0.0 && stream.rng.uniform() < self.simulation.loss {
+            stream.lost = self.simulation.burst;
+        }
+        if stream.lost > 0 {
+            stream.lost -= 1;
+            return None;
+        }
+
+        let ms = match self.simulation.model {
+            LatencyModel::Normal { mean, sd } => {
+                mean.as_secs_f64() * 1000.0 + sd.as_secs_f64() * 1000.0 * stream.rng.standard_normal()
+            }
+            LatencyModel::LogNormal { median, sigma } => {
+                median.as_secs_f64() * 1000.0 * (sigma * stream.rng.standard_normal()).exp()
+            }
+        };
+        let spike = self.simulation.spikes > 0.0 && stream.rng.uniform() < self.simulation.spikes;
+        let ms = ms * stream.factor * if spike { self.simulation.spike_factor } else { 1.0 };
+        Some(Duration::from_secs_f64(ms.max(1.0) / 1000.0))
+    }
+
+    /// Timing of a response taking `latency`, split into phases in fixed proportions
+    fn timing(url: &str, latency: Duration) -> TimingMetrics {
+        let dns = latency / 10;
+        let tcp = latency / 5;
+        let tls = url.starts_with("https://").then_some(latency / 4);
+        let first_byte = latency - dns - tcp - tls.unwrap_or(Duration::ZERO);
+        TimingMetrics::success(dns, tcp, tls, first_byte, latency, 200).with_resolved_ip(Some(SIMULATED_IP))
+    }
+}
+
+#[async_trait]
+impl HttpClient for SimulatedTransport {
+    async fn execute_request(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let latency = self.next_latency(&request.url, &request.dns_config)
+            .ok_or_else(|| AppError::network("Connection timed out (simulated loss)"))?;
+        Ok(HttpResponse {
+            status_code: 200,
+            headers: Vec::new(),
+            body_size: 0,
+            timing: Self::timing(&request.url, latency),
+            resolved_ip: Some(SIMULATED_IP),
+            dns_config_used: request.dns_config,
+            final_url: request.url,
+        })
+    }
+
+    async fn head(&self, url: &str, dns_config: &DnsConfig) -> Result<HttpResponse> {
+        self.execute_request(HttpRequest::head(url.to_string(), dns_config.clone())).await
+    }
+
+    async fn get(&self, url: &str, dns_config: &DnsConfig) -> Result<HttpResponse> {
+        self.execute_request(HttpRequest::get(url.to_string(), dns_config.clone())).await
+    }
+
+    async fn test_connectivity(&self, url: &str, dns_config: &DnsConfig) -> Result<ConnectivityTest> {
+        let response = self.get(url, dns_config).await;
+        Ok(ConnectivityTest {
+            success: response.is_ok(),
+            status_code: response.as_ref().ok().map(|response| response.status_code),
+            response_time: response.as_ref().map_or(Duration::ZERO, |response| response.timing.total_duration),
+            resolved_ip: response.as_ref().ok().and(Some(SIMULATED_IP)),
+            dns_resolution_time: response.as_ref().map_or(Duration::ZERO, |response| response.timing.dns_resolution),
+            connection_time: response.as_ref().map_or(Duration::ZERO, |response| response.timing.tcp_connection),
+            error: response.err().map(|e| e.to_string()),
+        })
+    }
+}
+
+/// SplitMix64, a small generator whose output is the same on every platform
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, by the Box-Muller transform
+    fn standard_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.uniform();
+        let u2 = self.uniform();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// 64-bit FNV-1a, for stable per-stream seeds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simulation() {
+        let simulation: Simulation = "lognormal:median=60ms,sigma=0.4,spikes=2%,loss=0.01,burst=5,seed=7".parse().unwrap();
+        assert_eq!(simulation.model, LatencyModel::LogNormal { median: Duration::from_millis(60), sigma: 0.4 });
+        assert_eq!((simulation.spikes, simulation.loss, simulation.burst, simulation.seed), (0.02, 0.01, 5, Some(7)));
+        assert_eq!(simulation.to_string().parse::<Simulation>().unwrap(), simulation);
+
+        let normal: Simulation = "Normal".parse().unwrap();
+        assert_eq!(normal.model, LatencyModel::Normal { mean: Duration::from_millis(50), sd: Duration::from_millis(10) });
+        assert!("uniform".parse::<Simulation>().is_err());
+        assert!("normal:median=60ms".parse::<Simulation>().unwrap_err().to_string().contains("mean, sd"));
+        assert!("normal:loss=150%".parse::<Simulation>().is_err());
+        assert!("normal:burst=0".parse::<Simulation>().is_err());
+    }
+
+    #[test]
+    fn test_simulated_samples() {
+        let simulation: Simulation = "normal:mean=100ms,sd=10ms,loss=5%,burst=4,seed=1".parse().unwrap();
+        let transport = SimulatedTransport::with_seed(simulation.clone(), 1);
+        let samples: Vec<Option<Duration>> = (0..2000).map(|_| transport.next_latency("https://a.example", &DnsConfig::System)).collect();
+
+        // Lost requests come in bursts of four
+        let lost = samples.iter().filter(|sample| sample.is_none()).count();
+        assert!(lost > 0 && lost % 4 == 0, "{} lost", lost);
+        let kept: Vec<f64> = samples.iter().flatten().map(|latency| latency.as_secs_f64() * 1000.0).collect();
+        let mean = kept.iter().sum::<f64>() / kept.len() as f64;
+        assert!((70.0..130.0).contains(&mean), "mean {}", mean);
+
+        // The same seed gives the same samples, whatever other streams are drawn in between
+        let again = SimulatedTransport::with_seed(simulation, 1);
+        again.next_latency("https://b.example", &DnsConfig::System);
+        assert_eq!(again.next_latency("https://a.example", &DnsConfig::System), samples[0]);
+    }
+
+    #[test]
+    fn test_spikes() {
+        let simulation: Simulation = "lognormal:median=50ms,sigma=0.1,spikes=10%,spike-factor=20".parse().unwrap();
+        let transport = SimulatedTransport::with_seed(simulation, 3);
+        let spikes = (0..1000)
+            .filter_map(|_| transport.next_latency("https://a.example", &DnsConfig::System))
+            .filter(|latency| *latency > Duration::from_millis(300))
+            .count();
+        assert!((50..150).contains(&spikes), "{} spikes", spikes);
+    }
+}
@@ -0,0 +1,150 @@
+//! Kernel TCP statistics for `--socket-stats`
+//!
+//! Latency spikes are often packet loss in disguise: a lost segment costs at
+//! least one retransmission timeout, which looks like a slow server. The
+//! kernel counts retransmissions per connection and reports them, along with
+//! its own round-trip estimate, through `getsockopt(TCP_INFO)`.
+//!
+//! reqwest does not hand out its sockets, so the connection a response came
+//! over is found by address: every socket descriptor of this process is
+//! listed from `/proc/self/fd` and the stream socket whose local and peer
+//! addresses match is queried. The descriptors are only borrowed for
+//! `getsockname`, `getpeername` and `getsockopt`, which fail harmlessly on a
+//! descriptor closed in the meantime. Only Linux supports this; elsewhere
+//! [`read`] returns `None`.
+
+use crate::models::SocketStats;
+use std::net::SocketAddr;
+
+/// Whether [`read`] can return statistics on this platform
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// Statistics of this process's TCP connection from `local` to `peer`, if it
+/// is still open
+pub fn read(local: SocketAddr, peer: SocketAddr) -> Option<SocketStats> {
+    imp::read(local, peer)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::*;
+    use std::{
+        net::{Ipv4Addr, Ipv6Addr, SocketAddrV6},
+        os::fd::{AsRawFd, BorrowedFd, RawFd},
+    };
+
+    /// `getsockname` or `getpeername`
+    type AddressQuery = unsafe extern "C" fn(libc::c_int, *mut libc::sockaddr, *mut libc::socklen_t) -> libc::c_int;
+
+    pub(super) fn read(local: SocketAddr, peer: SocketAddr) -> Option<SocketStats> {
+        let info = std::fs::read_dir("/proc/self/fd").ok()?.flatten().find_map(|entry| {
+            let target = std::fs::read_link(entry.path()).ok()?;
+            if !target.to_string_lossy().starts_with("socket:") {
+                return None;
+            }
+            let fd: RawFd = entry.file_name().to_str()?.parse().ok()?;
+            // SAFETY: the descriptor is only queried while borrowed here, and a
+            // query on a descriptor closed or reused in the meantime fails or
+            // does not match; nothing reads from, writes to or closes it
+            let socket = unsafe { BorrowedFd::borrow_raw(fd) };
+            let matches = is_stream(socket)
+                && address(socket, libc::getsockname) == Some(local)
+                && address(socket, libc::getpeername) == Some(peer);
+            if matches { tcp_info(socket) } else { None }
+        })?;
+        let total_retransmits = info.tcpi_total_retrans;
+        Some(SocketStats {
+            rtt_ms: f64::from(info.tcpi_rtt) / 1000.0,
+            rtt_var_ms: f64::from(info.tcpi_rttvar) / 1000.0,
+            cwnd: info.tcpi_snd_cwnd,
+            retransmits: total_retransmits,
+            total_retransmits,
+        })
+    }
+
+    /// Whether `socket` is a stream socket, the only kind with TCP statistics
+    fn is_stream(socket: BorrowedFd<'_>) -> bool {
+        getsockopt::<libc::c_int>(socket, libc::SOL_SOCKET, libc::SO_TYPE) == Some(libc::SOCK_STREAM)
+    }
+
+    fn tcp_info(socket: BorrowedFd<'_>) -> Option<libc::tcp_info> {
+        getsockopt(socket, libc::IPPROTO_TCP, libc::TCP_INFO)
+    }
+
+    /// Value of a socket option whose type is plain data
+    fn getsockopt<T>(socket: BorrowedFd<'_>, level: libc::c_int, name: libc::c_int) -> Option<T> {
+        // SAFETY: the option types queried are plain data for which all zeroes is a valid value
+        let mut value: T = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<T>() as libc::socklen_t;
+        // SAFETY: value and len describe a writable buffer of the size passed
+        let status = unsafe {
+            libc::getsockopt(socket.as_raw_fd(), level, name, (&mut value as *mut T).cast(), &mut len)
+        };
+        (status == 0).then_some(value)
+    }
+
+    /// Local or peer address of `socket`, as `query` reports it
+    fn address(socket: BorrowedFd<'_>, query: AddressQuery) -> Option<SocketAddr> {
+        // SAFETY: sockaddr_storage is plain data for which all zeroes is a valid value
+        let mut storage: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        // SAFETY: storage and len describe a writable buffer of the size passed
+        let status = unsafe { query(socket.as_raw_fd(), (&mut storage as *mut libc::sockaddr_storage).cast(), &mut len) };
+        if status != 0 {
+            return None;
+        }
+        match libc::c_int::from(storage.ss_family) {
+            libc::AF_INET => {
+                // SAFETY: the family says the storage holds a sockaddr_in
+                let addr = unsafe { &*(&storage as *const libc::sockaddr_storage).cast::<libc::sockaddr_in>() };
+                Some(SocketAddr::from((Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)), u16::from_be(addr.sin_port))))
+            }
+            libc::AF_INET6 => {
+                // SAFETY: the family says the storage holds a sockaddr_in6
+                let addr = unsafe { &*(&storage as *const libc::sockaddr_storage).cast::<libc::sockaddr_in6>() };
+                Some(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::from(addr.sin6_addr.s6_addr), u16::from_be(addr.sin6_port), addr.sin6_flowinfo, addr.sin6_scope_id,
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::*;
+
+    pub(super) fn read(_local: SocketAddr, _peer: SocketAddr) -> Option<SocketStats> {
+        None
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn test_read_socket_stats() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (local, peer) = (client.local_addr().unwrap(), client.peer_addr().unwrap());
+
+        let stats = read(local, peer).unwrap();
+        assert_eq!(stats.retransmits, stats.total_retransmits);
+        assert!(stats.cwnd > 0);
+        // The accepted side of the connection has the addresses swapped
+        let (_server, _) = listener.accept().unwrap();
+        assert!(read(peer, local).is_some());
+
+        drop(client);
+        assert!(read(local, peer).is_none());
+
+        // A datagram socket with the same addresses has no TCP statistics
+        let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let other = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        udp.connect(other.local_addr().unwrap()).unwrap();
+        assert!(read(udp.local_addr().unwrap(), other.local_addr().unwrap()).is_none());
+    }
+}
@@ -73,7 +73,8 @@ impl TimeoutManager {
     /// Adjust timeout based on DNS configuration type
     fn adjust_for_dns_config(&self, base_timeout: Duration, dns_config: &DnsConfig) -> Duration {
         match dns_config {
-            DnsConfig::System => base_timeout,
+            // LAN responders answer quickly or not at all
+            DnsConfig::System | DnsConfig::Mdns => base_timeout,
             DnsConfig::Custom { .. } => {
                 // Custom DNS servers might be slower
                 base_timeout + Duration::from_millis(500)
@@ -82,6 +83,13 @@ impl TimeoutManager {
                 // DNS-over-HTTPS has HTTPS overhead
                 base_timeout * 2
             }
+            DnsConfig::Chain { resolvers } => {
+                // Allow for the slowest resolver of the chain
+                resolvers.iter()
+                    .map(|resolver| self.adjust_for_dns_config(base_timeout, resolver))
+                    .max()
+                    .unwrap_or(base_timeout)
+            }
         }
     }
 
@@ -607,7 +615,7 @@ mod tests {
         };
 
         let recommendations = optimizer.analyze_and_recommend(&stats);
-        assert!(recommendations.recommendations.len() > 0);
+        assert!(!recommendations.recommendations.is_empty());
         
         let report = recommendations.generate_report();
         assert!(report.contains("Linux"));
@@ -0,0 +1,99 @@
+//! Generated request bodies for `--upload`
+//!
+//! On asymmetric links such as DSL, cable or LTE the upstream is a fraction
+//! of the downstream, so an API that receives data is slower than a download
+//! suggests. [`UploadBody`] streams a body of the requested size to the
+//! connection and notes when its last byte was handed over, which splits the
+//! request into the time spent sending and the time the server took to
+//! answer. The bytes are pseudo-random so that no compression on the way
+//! shrinks them.
+
+use reqwest::Body;
+use std::convert::Infallible;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+/// Bytes handed to the connection at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A chunk of incompressible bytes every body is cut from
+fn chunk() -> &'static [u8] {
+    static CHUNK: OnceLock<Vec<u8>> = OnceLock::new();
+    CHUNK.get_or_init(|| {
+        // xorshift64: fast, and random enough to defeat compression
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        (0..CHUNK_SIZE / 8)
+            .flat_map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state.to_le_bytes()
+            })
+            .collect()
+    })
+}
+
+/// A generated body of a given size
+#[derive(Debug, Clone)]
+pub struct UploadBody {
+    bytes: u64,
+    sent: Arc<OnceLock<Instant>>,
+}
+
+impl UploadBody {
+    pub fn new(bytes: u64) -> Self {
+        Self { bytes, sent: Arc::new(OnceLock::new()) }
+    }
+
+    /// Size of the body
+    pub fn len(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes == 0
+    }
+
+    /// The body to send; it counts as sent when the connection takes the
+    /// last chunk, since with a `Content-Length` it never asks for more
+    pub fn body(&self) -> Body {
+        let sent = Arc::clone(&self.sent);
+        let chunks = futures::stream::unfold(self.bytes, move |remaining| {
+            let sent = Arc::clone(&sent);
+            async move {
+                if remaining == 0 {
+                    return None;
+                }
+                let size = remaining.min(CHUNK_SIZE as u64) as usize;
+                if size as u64 == remaining {
+                    let _ = sent.set(Instant::now());
+                }
+                Some((Ok::<_, Infallible>(&chunk()[..size]), remaining - size as u64))
+            }
+        });
+        Body::wrap_stream(chunks)
+    }
+
+    /// When the last byte was handed to the connection, once it was
+    pub fn sent_at(&self) -> Option<Instant> {
+        self.sent.get().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_is_incompressible() {
+        let chunk = chunk();
+        assert_eq!(chunk.len(), CHUNK_SIZE);
+        // Every byte value turns up, about equally often
+        let mut counts = [0usize; 256];
+        for byte in chunk {
+            counts[*byte as usize] += 1;
+        }
+        let expected = CHUNK_SIZE / 256;
+        assert!(counts.iter().all(|&count| count > expected / 2 && count < expected * 2));
+    }
+}
@@ -16,7 +16,7 @@ mod config_edge_cases {
     fn test_config_with_extremely_large_values() {
         let mut config = Config::default();
         config.test_count = 100; // Maximum valid
-        config.timeout_seconds = 299; // Just under maximum valid
+        config.timeout = std::time::Duration::from_secs(299); // Just under maximum valid
         
         assert!(config.validate().is_ok());
         
@@ -143,7 +143,7 @@ mod cli_parsing_tests {
         assert!(cli.verbose);
         assert!(cli.debug);
         assert_eq!(cli.count, 50);
-        assert_eq!(cli.timeout, 120);
+        assert_eq!(cli.timeout, Some(std::time::Duration::from_secs(120)));
         assert!(!cli.urls.is_empty());
     }
     
@@ -312,7 +312,7 @@ mod concurrency_tests {
                     let mut config = Config::default();
                     config.target_urls = vec![format!("https://site{}.com", i)];
                     config.test_count = (i % 50 + 1) as u32;
-                    config.timeout_seconds = (i % 120 + 1) as u64;
+                    config.timeout = std::time::Duration::from_secs((i % 120 + 1) as u64);
                     
                     // All validations should succeed
                     assert!(config.validate().is_ok());
@@ -1,18 +1,53 @@
 //! Environment variable handling and .env file management
 
+use crate::config::preferences::Preferences;
 use crate::error::{AppError, Result};
+use crate::models::{Config, ScoringWeights};
+use crate::types::{BindTarget, DisplayNames, DnsConfig, DohBootstrap, Locale, PoolOptions, ResolverOptions, RedirectPolicy, Shard, TlsVersion, Upload, UserAgent};
+use crate::utils::duration::parse_duration;
+use crate::utils::url::suggest_url;
 use std::path::Path;
 
+/// Prefix of the variables that configure the tool without colliding with
+/// other programs' environment, e.g. in a Kubernetes ConfigMap:
+/// `NLT_DNS_SERVERS` sets `DNS_SERVERS`
+pub const ENV_PREFIX: &str = "NLT_";
+
+/// Shorter names that may follow the prefix, with the setting each stands for
+pub const PREFIX_ALIASES: &[(&str, &str)] = &[
+    ("TARGETS", "TARGET_URLS"),
+    ("COUNT", "TEST_COUNT"),
+    ("TIMEOUT", "TIMEOUT_SECONDS"),
+    ("LOCALE", "DISPLAY_LOCALE"),
+];
+
+/// Prefixed variables that stand for command-line options rather than settings
+pub const PREFIXED_OPTIONS: &[(&str, &str)] = &[
+    ("NLT_INTERVAL", "--watch"),
+    ("NLT_SERVE", "--serve"),
+    ("NLT_SERVE_CORS", "--serve-cors"),
+    ("NLT_LOG_FILE", "--log-file"),
+    ("NLT_NOTIFY", "--notify"),
+    ("NLT_HONOR_RETRY_AFTER", "--honor-retry-after"),
+    ("NLT_EXPORT", "--export"),
+    ("NLT_EXPORT_CSV", "--export-csv"),
+];
+
 /// Environment variable configuration manager
 pub struct EnvManager;
 
 impl EnvManager {
     /// Load .env file if it exists
     pub fn load_env_file(debug: bool) -> Result<()> {
+        // Prefixed variables come first, so that neither .env nor the preferences override them
+        for (key, value) in Self::prefixed_settings(std::env::vars()).0 {
+            std::env::set_var(key, value);
+        }
+
         // Try to load .env from current directory
         if Path::new(".env").exists() {
             dotenv::from_filename(".env")
-                .map_err(|e| AppError::config(format!("Failed to load .env file: {}", e)))?;
+                .map_err(|e| AppError::config(format!("Failed to load .env file: {}", e)).with_source(e))?;
             
             if debug {
                 println!("Loaded configuration from .env file");
@@ -21,6 +56,11 @@ impl EnvManager {
             println!("No .env file found, using defaults and CLI arguments");
         }
 
+        // Saved preferences come after .env, so that .env overrides them
+        if let Some(preferences) = Preferences::locate() {
+            preferences.load(debug)?;
+        }
+
         Ok(())
     }
 
@@ -41,15 +81,123 @@ impl EnvManager {
 # DNS-over-HTTPS providers (comma-separated URLs)
 # DOH_PROVIDERS=https://cloudflare-dns.com/dns-query,https://dns.google/dns-query
 
+# Resolver failover chains (comma-separated; members joined by '>' are tried in order)
+# DNS_CHAINS=223.5.5.5>8.8.8.8>https://dns.google/dns-query
+
+# Search domains for short host names such as http://intranet (overrides the system's; empty means none)
+# SEARCH_DOMAINS=corp.example.com,example.com
+
+# Short names with fewer dots than this are tried with the search domains first (0-15)
+# NDOTS=1
+
+# Also test each short target under its names qualified with the search domains
+# TEST_SEARCH_PATHS=true
+
 # Number of test iterations per DNS configuration
 # TEST_COUNT=5
 
-# Request timeout in seconds
+# Request timeout; bare numbers are seconds, units ms/s/m are accepted (e.g. 500ms, 1m30s)
 # TIMEOUT_SECONDS=10
 
+# Time budget for the whole run; iteration counts are reduced to fit (e.g. 90s, 10m)
+# MAX_RUNTIME=10m
+
+# Keep adding iterations until the mean and p95 are within this precision (true for 5%, or e.g. 2%)
+# AUTO_COUNT=false
+
 # Enable colored output (true/false)
 # ENABLE_COLOR=true
 
+# Ranking score weights (speed, reliability, consistency, p95, jitter, loss)
+# SCORING_WEIGHTS=speed=0.4,reliability=0.35,consistency=0.25
+
+# Count timed-out requests as lasting at least the timeout in percentiles (true/false)
+# CENSOR_TIMEOUTS=false
+
+# Read kernel TCP statistics (RTT, retransmissions) after each request; Linux only (true/false)
+# SOCKET_STATS=false
+
+# Send this many UDP packets to each route and DNS server to measure packet loss (true for 50)
+# LOSS_PROBE=false
+
+# Send this many ICMP echo requests to each address the requests went to (true for 10)
+# PING=false
+
+# Whether requests follow redirects: follow, none (measure the redirect itself) or https-only
+# REDIRECT_POLICY=follow
+
+# Redirects a request may follow before it fails (0-50)
+# MAX_REDIRECTS=10
+
+# User-Agent and headers to send: tool, curl, chrome, mobile-safari, or a custom User-Agent string
+# USER_AGENT=tool
+
+# Speak HTTP/2 without negotiating it: h2c on http:// URLs, only h2 offered on https:// (true/false)
+# HTTP2_PRIOR_KNOWLEDGE=false
+
+# Accept connections on which ALPN settled on HTTP/1.1; false fails requests not answered over HTTP/2
+# ALPN_FALLBACK=true
+
+# Oldest and newest TLS versions to negotiate (1.2/1.3)
+# TLS_MIN_VERSION=1.2
+# TLS_MAX_VERSION=1.3
+
+# Resume TLS sessions from earlier connections (on) or always make a full handshake (off);
+# either uses rustls and reports resumed handshakes apart. Unset leaves it to the platform TLS library
+# TLS_RESUMPTION=off
+
+# Open a new connection to the DoH provider for every query instead of reusing one (true/false)
+# DOH_FRESH_CONNECTIONS=false
+
+# Resolve DoH provider host names up front, to a pinned IP or through the system resolver (PROVIDER=IP|system pairs)
+# DOH_BOOTSTRAP=cloudflare-dns.com=1.1.1.1,dns.google=system
+
+# Check that custom DNS configurations leave the system DNS settings alone (true/false)
+# SANDBOX_DNS=false
+
+# Flush the OS resolver cache before testing, so System DNS lookups start cold (true/false)
+# FLUSH_DNS_CACHE=false
+
+# Run on a single thread with smaller buffers (true/false); on by default on low-resource machines
+# SINGLE_THREAD=false
+
+# Compare the system clock against an NTP server and report the offset (host or host:port)
+# NTP_SERVER=pool.ntp.org
+
+# Language of DNS configuration names (en/zh); follows LANG when unset
+# DISPLAY_LOCALE=en
+
+# Names to report DNS configurations under (ID=LABEL pairs, ID is system, a DNS server or a DoH URL)
+# DNS_LABELS=8.8.8.8=Google,https://cloudflare-dns.com/dns-query=Cloudflare
+
+# Resolver settings per DNS configuration, resolv.conf style (ID=OPTIONS pairs, ID is system or DNS servers joined by +)
+# Options: timeout:DURATION attempts:N rotate cache:N strategy:first|round-robin|race-all|random;
+# unset ones keep the defaults (the system's for system); a strategy times each server of 8.8.8.8+1.1.1.1 apart
+# DNS_RESOLVER_OPTIONS=system=timeout:5 attempts:2,8.8.8.8=timeout:1 attempts:0 cache:0
+
+# Test only shard i of n of the target list, to split a large list across processes (i/n)
+# SHARD=1/4
+
+# Network interface or local address to send requests from (e.g. eth0, 192.168.1.20)
+# BIND=eth0
+
+# Connection pool settings replacing those chosen for the run, as space-separated options
+# Options: idle-per-host:N idle-timeout:DURATION|off keepalive:DURATION|off nodelay:on|off;
+# idle-per-host:0 opens a new connection for every request, to time the cold path
+# POOL_OPTIONS=idle-per-host:0
+
+# Upload a generated body of this size with each request to time the upload path ([post|put:]SIZE)
+# UPLOAD=put:1MiB
+
+# Time informational (1xx) responses such as 103 Early Hints with an extra HTTP/1.1 request (true/false)
+# EARLY_HINTS=false
+
+# Capture packets to a target with tcpdump or dumpcap after a request fails or takes longer than this (e.g. 500ms)
+# CAPTURE_ON_ANOMALY=1s
+
+# Write a per-request debug trace of every run to traces/trace-RUN_ID.jsonl (true/false)
+# TRACE_REQUESTS=false
+
 # Example configurations for different scenarios:
 #
 # Testing multiple targets:
@@ -73,7 +221,7 @@ impl EnvManager {
         
         let content = Self::create_example_env_content();
         fs::write(path, content)
-            .map_err(|e| AppError::config(format!("Failed to write example .env file: {}", e)))?;
+            .map_err(|e| AppError::config(format!("Failed to write example .env file: {}", e)).with_source(e))?;
         
         Ok(())
     }
@@ -85,8 +233,10 @@ impl EnvManager {
                 for url in value.split(',') {
                     let url = url.trim();
                     if !url.is_empty() {
-                        url::Url::parse(url)
-                            .map_err(|e| AppError::config(format!("Invalid TARGET_URLS entry '{}': {}", url, e)))?;
+                        url::Url::parse(url).map_err(|e| {
+                            let hint = suggest_url(url).map(|fixed| format!(" (did you mean '{}'?)", fixed)).unwrap_or_default();
+                            AppError::config(format!("Invalid TARGET_URLS entry '{}': {}{}", url, e, hint)).with_source(e)
+                        })?;
                     }
                 }
             }
@@ -94,8 +244,7 @@ impl EnvManager {
                 for server in value.split(',') {
                     let server = server.trim();
                     if !server.is_empty() {
-                        server.parse::<std::net::IpAddr>()
-                            .map_err(|e| AppError::config(format!("Invalid DNS_SERVERS entry '{}': {}", server, e)))?;
+                        DnsConfig::parse_servers(server)?;
                     }
                 }
             }
@@ -104,30 +253,161 @@ impl EnvManager {
                     let provider = provider.trim();
                     if !provider.is_empty() {
                         let parsed = url::Url::parse(provider)
-                            .map_err(|e| AppError::config(format!("Invalid DOH_PROVIDERS entry '{}': {}", provider, e)))?;
+                            .map_err(|e| AppError::config(format!("Invalid DOH_PROVIDERS entry '{}': {}", provider, e)).with_source(e))?;
                         if parsed.scheme() != "https" {
                             return Err(AppError::config(format!("DoH provider must use HTTPS: {}", provider)));
                         }
                     }
                 }
             }
+            "DNS_CHAINS" => {
+                for chain in value.split(',').map(str::trim).filter(|chain| !chain.is_empty()) {
+                    DnsConfig::parse_chain(chain)
+                        .map_err(|e| AppError::config(format!("Invalid DNS_CHAINS entry: {}", e.message())))?;
+                }
+            }
+            "SEARCH_DOMAINS" => {
+                Config::parse_search_domains(value)?;
+            }
+            "NDOTS" => {
+                let ndots: u8 = value.parse()
+                    .map_err(|e| AppError::config(format!("Invalid NDOTS value '{}': {}", value, e)).with_source(e))?;
+                Config::validate_ndots(ndots)?;
+            }
+            "TEST_SEARCH_PATHS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid TEST_SEARCH_PATHS value '{}': {}", value, e)).with_source(e))?;
+            }
             "TEST_COUNT" => {
                 let count: u32 = value.parse()
-                    .map_err(|e| AppError::config(format!("Invalid TEST_COUNT value '{}': {}", value, e)))?;
+                    .map_err(|e| AppError::config(format!("Invalid TEST_COUNT value '{}': {}", value, e)).with_source(e))?;
                 if count == 0 || count > 100 {
                     return Err(AppError::config(format!("TEST_COUNT must be between 1 and 100, got: {}", count)));
                 }
             }
             "TIMEOUT_SECONDS" => {
-                let timeout: u64 = value.parse()
-                    .map_err(|e| AppError::config(format!("Invalid TIMEOUT_SECONDS value '{}': {}", value, e)))?;
-                if timeout == 0 || timeout > 300 {
-                    return Err(AppError::config(format!("TIMEOUT_SECONDS must be between 1 and 300, got: {}", timeout)));
+                let timeout = parse_duration(value)
+                    .map_err(|e| AppError::config(format!("Invalid TIMEOUT_SECONDS value '{}': {}", value, e)).with_source(e))?;
+                if timeout.is_zero() || timeout > crate::defaults::MAX_TIMEOUT {
+                    return Err(AppError::config(format!("TIMEOUT_SECONDS must be between 1ms and 300s, got: {}", value)));
                 }
             }
+            "MAX_RUNTIME" => {
+                let max_runtime = parse_duration(value)
+                    .map_err(|e| AppError::config(format!("Invalid MAX_RUNTIME value '{}': {}", value, e)).with_source(e))?;
+                if max_runtime.is_zero() {
+                    return Err(AppError::config("MAX_RUNTIME must be greater than 0"));
+                }
+            }
+            "AUTO_COUNT" => {
+                Config::parse_auto_count(value)?;
+            }
             "ENABLE_COLOR" => {
                 value.parse::<bool>()
-                    .map_err(|e| AppError::config(format!("Invalid ENABLE_COLOR value '{}': {}", value, e)))?;
+                    .map_err(|e| AppError::config(format!("Invalid ENABLE_COLOR value '{}': {}", value, e)).with_source(e))?;
+            }
+            "SCORING_WEIGHTS" => {
+                value.parse::<ScoringWeights>()
+                    .map_err(|e| AppError::config(format!("Invalid SCORING_WEIGHTS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "CENSOR_TIMEOUTS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid CENSOR_TIMEOUTS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "SOCKET_STATS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid SOCKET_STATS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "LOSS_PROBE" => {
+                Config::parse_loss_probe(value)?;
+            }
+            "PING" => {
+                Config::parse_ping(value)?;
+            }
+            "REDIRECT_POLICY" => {
+                value.parse::<RedirectPolicy>()?;
+            }
+            "MAX_REDIRECTS" => {
+                let max_redirects = value.parse::<u32>()
+                    .map_err(|e| AppError::config(format!("Invalid MAX_REDIRECTS value '{}': {}", value, e)).with_source(e))?;
+                if max_redirects > crate::defaults::MAX_REDIRECTS {
+                    return Err(AppError::config(format!(
+                        "MAX_REDIRECTS must be between 0 and {}, got: {}",
+                        crate::defaults::MAX_REDIRECTS,
+                        max_redirects
+                    )));
+                }
+            }
+            "USER_AGENT" => {
+                value.parse::<UserAgent>()?;
+            }
+            "HTTP2_PRIOR_KNOWLEDGE" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid HTTP2_PRIOR_KNOWLEDGE value '{}': {}", value, e)).with_source(e))?;
+            }
+            "ALPN_FALLBACK" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid ALPN_FALLBACK value '{}': {}", value, e)).with_source(e))?;
+            }
+            "TLS_MIN_VERSION" | "TLS_MAX_VERSION" => {
+                value.parse::<TlsVersion>()?;
+            }
+            "TLS_RESUMPTION" => {
+                Config::parse_tls_resumption(value)?;
+            }
+            "DOH_FRESH_CONNECTIONS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid DOH_FRESH_CONNECTIONS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "DOH_BOOTSTRAP" => {
+                DohBootstrap::parse_list(value)?;
+            }
+            "SANDBOX_DNS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid SANDBOX_DNS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "FLUSH_DNS_CACHE" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid FLUSH_DNS_CACHE value '{}': {}", value, e)).with_source(e))?;
+            }
+            "SINGLE_THREAD" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid SINGLE_THREAD value '{}': {}", value, e)).with_source(e))?;
+            }
+            "NTP_SERVER" => {
+                Config::validate_ntp_server(value)?;
+            }
+            "DISPLAY_LOCALE" => {
+                value.parse::<Locale>()?;
+            }
+            "DNS_LABELS" => {
+                DisplayNames::parse_labels(value)?;
+            }
+            "DNS_RESOLVER_OPTIONS" => {
+                ResolverOptions::parse_list(value)?;
+            }
+            "SHARD" => {
+                value.parse::<Shard>()?;
+            }
+            "BIND" => {
+                value.parse::<BindTarget>()?;
+            }
+            "POOL_OPTIONS" => {
+                value.parse::<PoolOptions>()?;
+            }
+            "UPLOAD" => {
+                value.parse::<Upload>()?;
+            }
+            "CAPTURE_ON_ANOMALY" => {
+                Config::parse_capture_threshold(value)?;
+            }
+            "EARLY_HINTS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid EARLY_HINTS value '{}': {}", value, e)).with_source(e))?;
+            }
+            "TRACE_REQUESTS" => {
+                value.parse::<bool>()
+                    .map_err(|e| AppError::config(format!("Invalid TRACE_REQUESTS value '{}': {}", value, e)).with_source(e))?;
             }
             _ => {
                 // Unknown environment variable, ignore
@@ -143,12 +423,78 @@ impl EnvManager {
             ("TARGET_URLS", "Comma-separated list of URLs to test", "https://example.com,https://google.com"),
             ("DNS_SERVERS", "Comma-separated list of DNS server IPs", "8.8.8.8,1.1.1.1,208.67.222.222"),
             ("DOH_PROVIDERS", "Comma-separated list of DoH URLs", "https://cloudflare-dns.com/dns-query"),
+            ("DNS_CHAINS", "Comma-separated resolver chains; '>' separates the resolvers tried in turn", "223.5.5.5>8.8.8.8"),
+            ("SEARCH_DOMAINS", "Search domains for short host names, in place of the system's", "corp.example.com"),
+            ("NDOTS", "Names with fewer dots are tried with the search domains first (0-15)", "1"),
+            ("TEST_SEARCH_PATHS", "Also test short targets qualified with each search domain", "true"),
             ("TEST_COUNT", "Number of test iterations (1-100)", "5"),
-            ("TIMEOUT_SECONDS", "Request timeout in seconds (1-300)", "10"),
+            ("TIMEOUT_SECONDS", "Request timeout, bare numbers are seconds (e.g. 10, 500ms, 1m30s; max 300s)", "10"),
+            ("MAX_RUNTIME", "Time budget for the whole run; iteration counts shrink to fit", "10m"),
+            ("AUTO_COUNT", "Add iterations until the mean and p95 are within this precision (true means 5%)", "2%"),
             ("ENABLE_COLOR", "Enable colored output", "true"),
+            ("SCORING_WEIGHTS", "Ranking score weights (speed, reliability, consistency, p95, jitter, loss)", "speed=0.5,reliability=0.3,consistency=0.2"),
+            ("CENSOR_TIMEOUTS", "Count timeouts as lasting at least the timeout in percentiles", "true"),
+            ("SOCKET_STATS", "Report kernel TCP RTT and retransmissions per request (Linux only)", "true"),
+            ("LOSS_PROBE", "UDP packets per route and DNS server for packet loss (true means 50)", "100"),
+            ("PING", "ICMP echo requests per address the requests went to (true means 10)", "true"),
+            ("REDIRECT_POLICY", "Whether requests follow redirects (follow, none, https-only)", "https-only"),
+            ("MAX_REDIRECTS", "Redirects a request may follow before it fails (0-50)", "5"),
+            ("USER_AGENT", "User-Agent preset (tool, curl, chrome, mobile-safari) or a custom string", "chrome"),
+            ("HTTP2_PRIOR_KNOWLEDGE", "Speak HTTP/2 without negotiating it (h2c on http:// URLs)", "true"),
+            ("ALPN_FALLBACK", "Accept HTTP/1.1 when ALPN does not settle on HTTP/2", "false"),
+            ("TLS_MIN_VERSION", "Oldest TLS version to negotiate (1.2, 1.3)", "1.3"),
+            ("TLS_MAX_VERSION", "Newest TLS version to negotiate (1.2, 1.3)", "1.2"),
+            ("TLS_RESUMPTION", "Resume TLS sessions (on) or always make full handshakes (off), reporting which", "off"),
+            ("DOH_FRESH_CONNECTIONS", "Open a new DoH provider connection for every query", "true"),
+            ("DOH_BOOTSTRAP", "Resolve DoH provider hosts up front as PROVIDER=IP|system pairs", "dns.google=8.8.8.8"),
+            ("SANDBOX_DNS", "Verify that custom DNS configurations leave system DNS settings unchanged", "true"),
+            ("FLUSH_DNS_CACHE", "Flush the OS resolver cache before testing System DNS", "true"),
+            ("SINGLE_THREAD", "Run on a single thread with smaller buffers", "true"),
+            ("NTP_SERVER", "NTP server to check the system clock against", "pool.ntp.org"),
+            ("DISPLAY_LOCALE", "Language of DNS configuration names (en, zh); follows LANG when unset", "zh"),
+            ("DNS_LABELS", "Names for DNS configurations as ID=LABEL pairs", "8.8.8.8=Google,system=ISP"),
+            ("DNS_RESOLVER_OPTIONS", "Resolver settings as ID=OPTIONS pairs (timeout:DURATION attempts:N rotate cache:N)", "8.8.8.8=timeout:1 attempts:0"),
+            ("SHARD", "Test only shard i of n of the target list (merge the exports with `merge`)", "2/4"),
+            ("BIND", "Network interface or local address to send requests from", "eth0"),
+            ("POOL_OPTIONS", "Connection pool settings (idle-per-host:N idle-timeout:DURATION keepalive:DURATION nodelay:on|off)", "idle-per-host:0"),
+            ("UPLOAD", "Upload a generated body with each request ([post|put:]SIZE)", "put:1MiB"),
+            ("EARLY_HINTS", "Time 1xx responses such as 103 Early Hints ahead of the final response", "true"),
+            ("CAPTURE_ON_ANOMALY", "Capture packets after a request fails or takes longer than this", "1s"),
+            ("TRACE_REQUESTS", "Write a per-request debug trace of every run to traces/", "true"),
         ]
     }
 
+    /// The setting a prefixed variable such as `NLT_TARGETS` stands for
+    pub fn prefixed_setting(name: &str) -> Option<&'static str> {
+        let name = name.strip_prefix(ENV_PREFIX)?;
+        let name = PREFIX_ALIASES.iter().find(|(alias, _)| *alias == name).map_or(name, |(_, key)| *key);
+        Self::get_supported_env_vars().into_iter().map(|(key, _, _)| key).find(|key| *key == name)
+    }
+
+    /// The settings the prefixed variables among `vars` set, in the order to
+    /// set them, and the prefixed variables that stand for nothing
+    ///
+    /// Aliases come first, so that `NLT_TARGET_URLS` wins over `NLT_TARGETS`.
+    pub fn prefixed_settings(vars: impl IntoIterator<Item = (String, String)>) -> (Vec<(&'static str, String)>, Vec<String>) {
+        let mut settings = Vec::new();
+        let mut unknown = Vec::new();
+        for (name, value) in vars {
+            match Self::prefixed_setting(&name) {
+                Some(key) => {
+                    let is_alias = PREFIX_ALIASES.iter().any(|(alias, _)| name.strip_prefix(ENV_PREFIX) == Some(*alias));
+                    settings.push((is_alias, key, value))
+                }
+                None if name.starts_with(ENV_PREFIX) && !PREFIXED_OPTIONS.iter().any(|(option, _)| *option == name) => {
+                    unknown.push(name)
+                }
+                None => {}
+            }
+        }
+        settings.sort_by_key(|(is_alias, _, _)| !is_alias);
+        unknown.sort();
+        (settings.into_iter().map(|(_, key, value)| (key, value)).collect(), unknown)
+    }
+
     /// Display environment variable help
     pub fn display_env_help() -> String {
         let mut help = String::new();
@@ -159,6 +505,10 @@ impl EnvManager {
             help.push_str(&format!("  {:<18} Example: {}\n\n", "", example));
         }
         
+        help.push_str(&format!(
+            "Each variable can also be set with the {} prefix (e.g. {}DNS_SERVERS), which wins over the plain name.\n\n",
+            ENV_PREFIX, ENV_PREFIX
+        ));
         help.push_str("Configuration Priority (highest to lowest):\n");
         help.push_str("  1. Command-line arguments\n");
         help.push_str("  2. Environment variables\n");
@@ -191,7 +541,7 @@ impl EnvManager {
 
         // Load the .env file temporarily to validate
         let content = std::fs::read_to_string(".env")
-            .map_err(|e| AppError::config(format!("Failed to read .env file: {}", e)))?;
+            .map_err(|e| AppError::config(format!("Failed to read .env file: {}", e)).with_source(e))?;
 
         let mut warnings = Vec::new();
         
@@ -230,6 +580,7 @@ mod tests {
         assert!(content.contains("TEST_COUNT="));
         assert!(content.contains("TIMEOUT_SECONDS="));
         assert!(content.contains("ENABLE_COLOR="));
+        assert!(content.contains("SCORING_WEIGHTS="));
     }
 
     #[test]
@@ -251,30 +602,144 @@ mod tests {
         assert!(EnvManager::validate_env_var("DOH_PROVIDERS", "https://cloudflare-dns.com/dns-query").is_ok());
         assert!(EnvManager::validate_env_var("TEST_COUNT", "5").is_ok());
         assert!(EnvManager::validate_env_var("TIMEOUT_SECONDS", "10").is_ok());
+        assert!(EnvManager::validate_env_var("TIMEOUT_SECONDS", "750ms").is_ok());
+        assert!(EnvManager::validate_env_var("MAX_RUNTIME", "10m").is_ok());
+        assert!(EnvManager::validate_env_var("AUTO_COUNT", "true").is_ok());
+        assert!(EnvManager::validate_env_var("AUTO_COUNT", "2.5%").is_ok());
         assert!(EnvManager::validate_env_var("ENABLE_COLOR", "true").is_ok());
+        assert!(EnvManager::validate_env_var("SCORING_WEIGHTS", "speed=0.5,reliability=0.3,consistency=0.2").is_ok());
+        assert!(EnvManager::validate_env_var("CENSOR_TIMEOUTS", "true").is_ok());
+        assert!(EnvManager::validate_env_var("SOCKET_STATS", "true").is_ok());
+        assert!(EnvManager::validate_env_var("LOSS_PROBE", "100").is_ok());
+        assert!(EnvManager::validate_env_var("PING", "true").is_ok());
+        assert!(EnvManager::validate_env_var("REDIRECT_POLICY", "https-only").is_ok());
+        assert!(EnvManager::validate_env_var("MAX_REDIRECTS", "0").is_ok());
+        assert!(EnvManager::validate_env_var("USER_AGENT", "mobile-safari").is_ok());
+        assert!(EnvManager::validate_env_var("HTTP2_PRIOR_KNOWLEDGE", "true").is_ok());
+        assert!(EnvManager::validate_env_var("ALPN_FALLBACK", "false").is_ok());
+        assert!(EnvManager::validate_env_var("TLS_MIN_VERSION", "1.3").is_ok());
+        assert!(EnvManager::validate_env_var("DOH_FRESH_CONNECTIONS", "true").is_ok());
+        assert!(EnvManager::validate_env_var("DOH_BOOTSTRAP", "dns.google=8.8.8.8,cloudflare-dns.com=system").is_ok());
+        assert!(EnvManager::validate_env_var("SANDBOX_DNS", "true").is_ok());
+        assert!(EnvManager::validate_env_var("FLUSH_DNS_CACHE", "false").is_ok());
+        assert!(EnvManager::validate_env_var("SINGLE_THREAD", "false").is_ok());
+        assert!(EnvManager::validate_env_var("NTP_SERVER", "time.cloudflare.com").is_ok());
+        assert!(EnvManager::validate_env_var("DISPLAY_LOCALE", "zh_CN.UTF-8").is_ok());
+        assert!(EnvManager::validate_env_var("DNS_LABELS", "8.8.8.8=Google,system=ISP").is_ok());
+        assert!(EnvManager::validate_env_var("DNS_RESOLVER_OPTIONS", "system=timeout:2 rotate,8.8.8.8=cache:0").is_ok());
+        assert!(EnvManager::validate_env_var("SHARD", "2/4").is_ok());
+        assert!(EnvManager::validate_env_var("BIND", "192.168.1.20").is_ok());
+        assert!(EnvManager::validate_env_var("POOL_OPTIONS", "idle-timeout:5m keepalive:off").is_ok());
+        assert!(EnvManager::validate_env_var("UPLOAD", "put:1MiB").is_ok());
+        assert!(EnvManager::validate_env_var("EARLY_HINTS", "true").is_ok());
+        assert!(EnvManager::validate_env_var("CAPTURE_ON_ANOMALY", "500ms").is_ok());
+        assert!(EnvManager::validate_env_var("TRACE_REQUESTS", "true").is_ok());
 
         // Invalid cases
         assert!(EnvManager::validate_env_var("TARGET_URLS", "not-a-url").is_err());
         assert!(EnvManager::validate_env_var("DNS_SERVERS", "not-an-ip").is_err());
         assert!(EnvManager::validate_env_var("DOH_PROVIDERS", "http://insecure.com/dns-query").is_err());
+        assert!(EnvManager::validate_env_var("DNS_CHAINS", "223.5.5.5>8.8.8.8, system>https://dns.google/dns-query").is_ok());
+        assert!(EnvManager::validate_env_var("DNS_CHAINS", "223.5.5.5").is_err());
+        assert!(EnvManager::validate_env_var("SEARCH_DOMAINS", "corp.example.com, example.com").is_ok());
+        assert!(EnvManager::validate_env_var("SEARCH_DOMAINS", "").is_ok());
+        assert!(EnvManager::validate_env_var("SEARCH_DOMAINS", "https://corp.example.com").is_err());
+        assert!(EnvManager::validate_env_var("NDOTS", "2").is_ok());
+        assert!(EnvManager::validate_env_var("NDOTS", "16").is_err());
+        assert!(EnvManager::validate_env_var("TEST_SEARCH_PATHS", "yes").is_err());
         assert!(EnvManager::validate_env_var("TEST_COUNT", "0").is_err());
         assert!(EnvManager::validate_env_var("TEST_COUNT", "101").is_err());
         assert!(EnvManager::validate_env_var("TIMEOUT_SECONDS", "0").is_err());
         assert!(EnvManager::validate_env_var("TIMEOUT_SECONDS", "301").is_err());
+        assert!(EnvManager::validate_env_var("TIMEOUT_SECONDS", "5 minutes").is_err());
+        assert!(EnvManager::validate_env_var("MAX_RUNTIME", "0s").is_err());
+        assert!(EnvManager::validate_env_var("AUTO_COUNT", "0%").is_err());
         assert!(EnvManager::validate_env_var("ENABLE_COLOR", "maybe").is_err());
+        assert!(EnvManager::validate_env_var("SCORING_WEIGHTS", "latency=1").is_err());
+        assert!(EnvManager::validate_env_var("CENSOR_TIMEOUTS", "sometimes").is_err());
+        assert!(EnvManager::validate_env_var("SOCKET_STATS", "yes").is_err());
+        assert!(EnvManager::validate_env_var("LOSS_PROBE", "-1").is_err());
+        assert!(EnvManager::validate_env_var("PING", "1000").is_err());
+        assert!(EnvManager::validate_env_var("REDIRECT_POLICY", "manual").is_err());
+        assert!(EnvManager::validate_env_var("MAX_REDIRECTS", "51").is_err());
+        assert!(EnvManager::validate_env_var("USER_AGENT", "firefox").is_err());
+        assert!(EnvManager::validate_env_var("HTTP2_PRIOR_KNOWLEDGE", "h2").is_err());
+        assert!(EnvManager::validate_env_var("TLS_MAX_VERSION", "1.1").is_err());
+        assert!(EnvManager::validate_env_var("TLS_RESUMPTION", "sometimes").is_err());
+        assert!(EnvManager::validate_env_var("DOH_FRESH_CONNECTIONS", "always").is_err());
+        assert!(EnvManager::validate_env_var("DOH_BOOTSTRAP", "dns.google").is_err());
+        assert!(EnvManager::validate_env_var("SANDBOX_DNS", "on").is_err());
+        assert!(EnvManager::validate_env_var("FLUSH_DNS_CACHE", "yes").is_err());
+        assert!(EnvManager::validate_env_var("SINGLE_THREAD", "1").is_err());
+        assert!(EnvManager::validate_env_var("NTP_SERVER", "").is_err());
+        assert!(EnvManager::validate_env_var("DISPLAY_LOCALE", "fr").is_err());
+        assert!(EnvManager::validate_env_var("DNS_LABELS", "8.8.8.8").is_err());
+        assert!(EnvManager::validate_env_var("DNS_RESOLVER_OPTIONS", "8.8.8.8=ndots:2").is_err());
+        assert!(EnvManager::validate_env_var("SHARD", "5/4").is_err());
+        assert!(EnvManager::validate_env_var("BIND", "Wi Fi").is_err());
+        assert!(EnvManager::validate_env_var("POOL_OPTIONS", "idle-per-host:-1").is_err());
+        assert!(EnvManager::validate_env_var("UPLOAD", "get:1MiB").is_err());
+        assert!(EnvManager::validate_env_var("EARLY_HINTS", "on").is_err());
+        assert!(EnvManager::validate_env_var("CAPTURE_ON_ANOMALY", "0s").is_err());
+        assert!(EnvManager::validate_env_var("TRACE_REQUESTS", "yes").is_err());
     }
 
     #[test]
     fn test_get_supported_env_vars() {
         let vars = EnvManager::get_supported_env_vars();
         
-        assert_eq!(vars.len(), 6);
+        assert_eq!(vars.len(), 41);
         assert!(vars.iter().any(|(name, _, _)| *name == "TARGET_URLS"));
         assert!(vars.iter().any(|(name, _, _)| *name == "DNS_SERVERS"));
         assert!(vars.iter().any(|(name, _, _)| *name == "DOH_PROVIDERS"));
         assert!(vars.iter().any(|(name, _, _)| *name == "TEST_COUNT"));
         assert!(vars.iter().any(|(name, _, _)| *name == "TIMEOUT_SECONDS"));
         assert!(vars.iter().any(|(name, _, _)| *name == "ENABLE_COLOR"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "SCORING_WEIGHTS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "AUTO_COUNT"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "CENSOR_TIMEOUTS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "SOCKET_STATS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "LOSS_PROBE"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "PING"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "REDIRECT_POLICY"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "MAX_REDIRECTS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "USER_AGENT"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "HTTP2_PRIOR_KNOWLEDGE"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "ALPN_FALLBACK"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "TLS_MIN_VERSION"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "TLS_MAX_VERSION"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "SANDBOX_DNS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "FLUSH_DNS_CACHE"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "SINGLE_THREAD"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "NTP_SERVER"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "DISPLAY_LOCALE"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "DNS_LABELS"));
+        assert!(vars.iter().any(|(name, _, _)| *name == "SHARD"));
+    }
+
+    #[test]
+    fn test_prefixed_settings() {
+        let vars = [
+            ("NLT_TARGET_URLS", "https://b.example"),
+            ("NLT_TARGETS", "https://a.example"),
+            ("NLT_DNS_SERVERS", "8.8.8.8"),
+            ("NLT_INTERVAL", "5m"),
+            ("NLT_TEST_CONUT", "5"),
+            ("TEST_COUNT", "3"),
+            ("PATH", "/usr/bin"),
+        ];
+        let (settings, unknown) = EnvManager::prefixed_settings(
+            vars.iter().map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+        // The alias is set first, so the full name wins
+        assert_eq!(settings, [
+            ("TARGET_URLS", "https://a.example".to_string()),
+            ("TARGET_URLS", "https://b.example".to_string()),
+            ("DNS_SERVERS", "8.8.8.8".to_string()),
+        ]);
+        assert_eq!(unknown, ["NLT_TEST_CONUT"]);
+        assert_eq!(EnvManager::prefixed_setting("NLT_COUNT"), Some("TEST_COUNT"));
+        assert_eq!(EnvManager::prefixed_setting("TEST_COUNT"), None);
     }
 
     #[test]
@@ -3,15 +3,30 @@
 pub mod parser;
 pub mod validation;
 pub mod env;
+pub mod preferences;
+pub mod provenance;
+pub mod quick;
+pub mod reload;
+pub mod thorough;
+pub mod url_file;
+pub mod wizard;
 
 // Re-export main functionality
-pub use parser::{ConfigParser, load_config, display_config_summary};
+pub use parser::{ConfigParser, load_config, load_config_with_provenance, display_config_summary};
 pub use validation::{ConfigValidator, validate_config};
 pub use env::EnvManager;
+pub use preferences::Preferences;
+pub use provenance::{ConfigSource, Provenance};
+pub use quick::QuickProfile;
+pub use reload::ConfigWatcher;
+pub use thorough::ThoroughProfile;
+pub use url_file::UrlList;
+pub use wizard::{SetupWizard, TcpProbe};
 
 // Re-export from models for convenience
 pub use crate::models::Config;
 
 // Additional comprehensive tests in separate module
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod comprehensive_tests;
\ No newline at end of file
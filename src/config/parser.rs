@@ -3,9 +3,22 @@
 use crate::{
     cli::Cli,
     models::Config,
-    error::Result,
-    config::env::EnvManager,
+    error::{AppError, Result},
+    types::DnsConfig,
+    config::{
+        env::EnvManager,
+        preferences::Preferences,
+        provenance::{ConfigSource, Provenance},
+        quick::QuickProfile,
+        thorough::ThoroughProfile,
+        reload::read_env_file,
+        url_file::UrlList,
+        validation::ConfigValidator,
+    },
+    dns::system::SearchPolicy,
+    utils::duration::format_duration,
 };
+use std::path::Path;
 
 /// Configuration parser that combines CLI arguments with environment variables
 pub struct ConfigParser {
@@ -20,22 +33,75 @@ impl ConfigParser {
 
     /// Parse and build the complete configuration
     pub fn parse(&self) -> Result<Config> {
+        self.parse_with_provenance().map(|(config, _)| config)
+    }
+
+    /// Parse and build the complete configuration, recording which layer
+    /// (default, preferences, .env, environment or command line) set each setting
+    pub fn parse_with_provenance(&self) -> Result<(Config, Provenance)> {
         // Start with default configuration
         let mut config = Config::default();
 
-        // Load from environment file if it exists
+        // Saved preferences and then .env are loaded as environment variables that are not already set
         self.load_env_file()?;
+        let env_file = read_env_file(Path::new(".env"))?;
+        let preferences = match Preferences::locate() {
+            Some(preferences) => preferences.entries()?,
+            None => Vec::new(),
+        };
+        let mut provenance = Provenance::from_env(&env_file, &preferences);
+        let (_, unknown_prefixed) = EnvManager::prefixed_settings(std::env::vars());
+        if self.cli.strict {
+            ConfigValidator::check_known_keys(Path::new(".env"), &env_file)?;
+            if let Some(preferences_file) = Preferences::locate() {
+                ConfigValidator::check_known_keys(preferences_file.path(), &preferences)?;
+            }
+            if !unknown_prefixed.is_empty() {
+                return Err(AppError::config(format!(
+                    "Strict mode: the environment sets unknown variables: {}", unknown_prefixed.join(", ")
+                )));
+            }
+        }
+        for name in unknown_prefixed {
+            eprintln!("Warning: ignoring {}, which names no setting", name);
+        }
 
         // Merge environment variables into config
         config.merge_from_env()?;
 
         // Override with CLI arguments
-        self.apply_cli_overrides(&mut config)?;
+        self.apply_cli_overrides(&mut config, &mut provenance)?;
+
+        // Fix up target URLs; entries that cannot be fixed are skipped with a warning, or fail in strict mode
+        let issues = config.normalize_target_urls()?;
+        if self.cli.strict && !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(AppError::config(format!("Strict mode: invalid target URLs: {}", issues.join("; "))));
+        }
+        for issue in issues {
+            eprintln!("Warning: skipping target URL {}", issue);
+        }
 
         // Validate the final configuration
         config.validate()?;
 
-        Ok(config)
+        if config.test_search_paths {
+            let policy = SearchPolicy::system(config.search_domains.clone(), config.ndots);
+            config.expand_search_paths(&policy.domains, policy.ndots);
+        }
+
+        // Keep only this process's share of a sharded target list
+        config.apply_shard()?;
+
+        if self.cli.quick {
+            QuickProfile::default().apply(&mut config, &mut provenance);
+        }
+        if self.cli.thorough {
+            ThoroughProfile::default().apply(&mut config, &mut provenance);
+        }
+        config.timeout_given = provenance.source("TIMEOUT_SECONDS") != ConfigSource::Default;
+
+        Ok((config, provenance))
     }
 
     /// Load .env file if it exists
@@ -44,31 +110,250 @@ impl ConfigParser {
     }
 
     /// Apply CLI argument overrides to configuration
-    fn apply_cli_overrides(&self, config: &mut Config) -> Result<()> {
+    fn apply_cli_overrides(&self, config: &mut Config, provenance: &mut Provenance) -> Result<()> {
+        let mut from_cli = |key: &str| provenance.set(key, ConfigSource::CommandLine);
+
         // Override test count if specified
         if self.cli.count != crate::defaults::DEFAULT_TEST_COUNT {
             config.test_count = self.cli.count;
+            from_cli("TEST_COUNT");
+        }
+
+        // Override timeout if specified, even with the default's value
+        if let Some(timeout) = self.cli.timeout {
+            config.timeout = timeout;
+            from_cli("TIMEOUT_SECONDS");
         }
 
-        // Override timeout if specified
-        if self.cli.timeout != crate::defaults::DEFAULT_TIMEOUT.as_secs() {
-            config.timeout_seconds = self.cli.timeout;
+        // Override run time budget if specified
+        if let Some(max_runtime) = self.cli.max_runtime {
+            config.max_runtime = Some(max_runtime);
+            from_cli("MAX_RUNTIME");
         }
 
-        // Override color setting based on CLI flags
-        config.enable_color = self.cli.use_colors();
+        if let Some(precision) = self.cli.auto_count {
+            config.auto_count = Some(precision);
+            from_cli("AUTO_COUNT");
+        }
+
+        // --color and --no-color decide; otherwise ENABLE_COLOR=false turns off colors a terminal supports
+        config.enable_color = self.cli.use_colors() && (self.cli.color || config.enable_color);
+        if self.cli.color || self.cli.no_color {
+            from_cli("ENABLE_COLOR");
+        }
 
         // Set verbose and debug flags (these are CLI-only)
         config.verbose = self.cli.verbose;
         config.debug = self.cli.debug;
 
-        // Set target URLs from CLI
-        config.target_urls = self.cli.get_urls();
+        // Set target URLs from CLI; only watch mode may leave them to TARGET_URLS
+        let mut urls = self.cli.get_urls();
+        if let (Some(path), false) = (&self.cli.url_file, self.cli.test_original) {
+            let list = UrlList::load(path)?;
+            if let (true, Some((line, issue))) = (self.cli.strict, list.rejected.first()) {
+                return Err(AppError::config(format!("Strict mode: invalid entry at {}:{}: {}", path.display(), line, issue)));
+            }
+            for (line, issue) in &list.rejected {
+                eprintln!("Warning: skipping {}:{}: {}", path.display(), line, issue);
+            }
+            for url in list.urls {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+            config.url_tags = list.tags;
+        }
+        if !urls.is_empty() {
+            from_cli("TARGET_URLS");
+        }
+        if !urls.is_empty() || self.cli.watch.is_none() {
+            config.target_urls = urls;
+        }
+
+        // DNS servers and DoH providers from the command line replace the configured ones
+        let list = |value: &str| value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect();
+        if let Some(ref dns_servers) = self.cli.dns_servers {
+            config.dns_servers = list(dns_servers);
+            from_cli("DNS_SERVERS");
+        }
+        if let Some(ref doh_providers) = self.cli.doh_providers {
+            config.doh_providers = list(doh_providers);
+            from_cli("DOH_PROVIDERS");
+        }
+
+        // Override ranking weights if specified
+        if let Some(ref scoring) = self.cli.scoring {
+            config.scoring = scoring.parse()?;
+            from_cli("SCORING_WEIGHTS");
+        }
+
+        // The flag can only turn censoring on; the environment may already have
+        if self.cli.censor_timeouts {
+            config.censor_timeouts = true;
+            from_cli("CENSOR_TIMEOUTS");
+        }
+
+        if self.cli.socket_stats {
+            config.socket_stats = true;
+            from_cli("SOCKET_STATS");
+        }
+
+        if let Some(packets) = self.cli.loss_probe {
+            config.loss_probe = Some(packets);
+            from_cli("LOSS_PROBE");
+        }
+
+        if let Some(count) = self.cli.ping {
+            config.ping = Some(count);
+            from_cli("PING");
+        }
+
+        if let Some(policy) = self.cli.redirect_policy {
+            config.redirect_policy = policy;
+            from_cli("REDIRECT_POLICY");
+        }
+
+        if let Some(max_redirects) = self.cli.max_redirects {
+            config.max_redirects = max_redirects;
+            from_cli("MAX_REDIRECTS");
+        }
+
+        if let Some(ref user_agent) = self.cli.user_agent {
+            config.user_agent = user_agent.clone();
+            from_cli("USER_AGENT");
+        }
+
+        if self.cli.http2_prior_knowledge {
+            config.http2_prior_knowledge = true;
+            from_cli("HTTP2_PRIOR_KNOWLEDGE");
+        }
+
+        if self.cli.no_alpn_fallback {
+            config.alpn_fallback = false;
+            from_cli("ALPN_FALLBACK");
+        }
+
+        if let Some(version) = self.cli.tls_min_version {
+            config.tls_min_version = Some(version);
+            from_cli("TLS_MIN_VERSION");
+        }
+
+        if let Some(version) = self.cli.tls_max_version {
+            config.tls_max_version = Some(version);
+            from_cli("TLS_MAX_VERSION");
+        }
+
+        if let Some(resumption) = self.cli.tls_resumption {
+            config.tls_resumption = Some(resumption);
+            from_cli("TLS_RESUMPTION");
+        }
+
+        if !self.cli.dns_chains.is_empty() {
+            config.dns_chains = self.cli.dns_chains.iter().map(DnsConfig::id).collect();
+            from_cli("DNS_CHAINS");
+        }
+
+        if let Some(ref search_domains) = self.cli.search_domains {
+            config.search_domains = Some(Config::parse_search_domains(search_domains)?);
+            from_cli("SEARCH_DOMAINS");
+        }
+
+        if let Some(ndots) = self.cli.ndots {
+            config.ndots = Some(ndots);
+            from_cli("NDOTS");
+        }
+
+        if self.cli.test_search_paths {
+            config.test_search_paths = true;
+            from_cli("TEST_SEARCH_PATHS");
+        }
+
+        if self.cli.doh_fresh_connections {
+            config.doh_fresh_connections = true;
+            from_cli("DOH_FRESH_CONNECTIONS");
+        }
+
+        // Bootstraps from the command line add to or replace those from the environment
+        for (host, bootstrap) in &self.cli.doh_bootstrap {
+            config.doh_bootstrap.insert(host.clone(), *bootstrap);
+            from_cli("DOH_BOOTSTRAP");
+        }
+
+        if self.cli.sandbox_dns {
+            config.sandbox_dns = true;
+            from_cli("SANDBOX_DNS");
+        }
+
+        if self.cli.flush_dns_cache {
+            config.flush_dns_cache = true;
+            from_cli("FLUSH_DNS_CACHE");
+        }
+
+        if self.cli.single_thread {
+            config.single_thread = true;
+            from_cli("SINGLE_THREAD");
+        }
+
+        if let Some(ref ntp_server) = self.cli.ntp_server {
+            config.ntp_server = Some(ntp_server.clone());
+            from_cli("NTP_SERVER");
+        }
+
+        if let Some(locale) = self.cli.locale {
+            config.locale = locale;
+            from_cli("DISPLAY_LOCALE");
+        }
+
+        if let Some(shard) = self.cli.shard {
+            config.shard = Some(shard);
+            from_cli("SHARD");
+        }
+
+        if let Some(ref bind) = self.cli.bind {
+            config.bind = Some(bind.clone());
+            from_cli("BIND");
+        }
+
+        if let Some(options) = self.cli.pool_options {
+            config.pool_options = Some(options);
+            from_cli("POOL_OPTIONS");
+        }
+
+        if let Some(upload) = self.cli.upload {
+            config.upload = Some(upload);
+            from_cli("UPLOAD");
+        }
+
+        if self.cli.early_hints {
+            config.early_hints = true;
+            from_cli("EARLY_HINTS");
+        }
+
+        if let Some(threshold) = self.cli.capture_on_anomaly {
+            config.capture_on_anomaly = Some(threshold);
+            from_cli("CAPTURE_ON_ANOMALY");
+        }
+
+        if self.cli.trace_requests {
+            config.trace_requests = true;
+            from_cli("TRACE_REQUESTS");
+        }
+
+        // Labels from the command line add to or replace those from the environment
+        for (id, label) in &self.cli.dns_labels {
+            config.dns_labels.insert(id.clone(), label.clone());
+            from_cli("DNS_LABELS");
+        }
+
+        for (id, options) in &self.cli.resolver_options {
+            config.resolver_options.insert(id.clone(), options.clone());
+            from_cli("DNS_RESOLVER_OPTIONS");
+        }
 
         if config.debug {
             println!("Applied CLI overrides to configuration");
-            println!("Final config: test_count={}, timeout={}s, enable_color={}", 
-                    config.test_count, config.timeout_seconds, config.enable_color);
+            println!("Final config: test_count={}, timeout={}, enable_color={}", 
+                    config.test_count, format_duration(config.timeout), config.enable_color);
             println!("Testing URLs: {}", config.target_urls.join(", "));
         }
 
@@ -82,16 +367,111 @@ pub fn load_config(cli: Cli) -> Result<Config> {
     parser.parse()
 }
 
+/// Load the configuration as if the variables in `overrides` were set in the
+/// environment, which is left as it was
+pub fn load_config_with(cli: Cli, overrides: &[(String, String)]) -> Result<Config> {
+    let previous: Vec<(&String, Option<String>)> = overrides.iter()
+        .map(|(key, _)| (key, std::env::var(key).ok()))
+        .collect();
+    for (key, value) in overrides {
+        std::env::set_var(key, value);
+    }
+    let config = load_config(cli);
+    for (key, value) in previous {
+        match value {
+            Some(value) => std::env::set_var(key, value),
+            None => std::env::remove_var(key),
+        }
+    }
+    config
+}
+
+/// Load the complete configuration along with where each setting came from
+pub fn load_config_with_provenance(cli: Cli) -> Result<(Config, Provenance)> {
+    ConfigParser::new(cli).parse_with_provenance()
+}
+
 /// Display configuration summary for debug purposes
 pub fn display_config_summary(config: &Config) -> String {
     let mut summary = Vec::new();
 
     summary.push(format!("Target URLs: {}", config.target_urls.join(", ")));
+    if !config.url_tags.is_empty() {
+        summary.push(format!("Tagged URLs: {}", config.url_tags.len()));
+    }
     summary.push(format!("DNS Servers: {}", config.dns_servers.join(", ")));
     summary.push(format!("DoH Providers: {}", config.doh_providers.len()));
+    if !config.dns_chains.is_empty() {
+        summary.push(format!("DNS Chains: {}", config.dns_chains.join(", ")));
+    }
+    if let Some(ref search_domains) = config.search_domains {
+        summary.push(format!("Search Domains: {}", if search_domains.is_empty() { "none".to_string() } else { search_domains.join(", ") }));
+    }
+    if let Some(ndots) = config.ndots {
+        summary.push(format!("Ndots: {}", ndots));
+    }
+    if config.test_search_paths {
+        summary.push("Test Search Paths: true".to_string());
+    }
     summary.push(format!("Test Count: {}", config.test_count));
-    summary.push(format!("Timeout: {}s", config.timeout_seconds));
+    if let Some(precision) = config.auto_count {
+        summary.push(format!("Auto Count: until ±{}%, up to {}", precision, crate::defaults::MAX_AUTO_COUNT));
+    }
+    summary.push(format!("Timeout: {}", format_duration(config.timeout)));
+    if let Some(max_runtime) = config.max_runtime {
+        summary.push(format!("Max Runtime: {}", format_duration(max_runtime)));
+    }
     summary.push(format!("Color Output: {}", config.enable_color));
+    summary.push(format!("Scoring Weights: {}", config.scoring));
+    summary.push(format!("Censor Timeouts: {}", config.censor_timeouts));
+    summary.push(format!("Socket Stats: {}", config.socket_stats));
+    if let Some(packets) = config.loss_probe {
+        summary.push(format!("Loss Probe: {} packets", packets));
+    }
+    if let Some(count) = config.ping {
+        summary.push(format!("Ping: {} echo requests", count));
+    }
+    summary.push(format!("Redirects: {} (max {})", config.redirect_policy, config.max_redirects));
+    summary.push(format!("User Agent: {}", config.user_agent));
+    summary.push(format!("Protocol: {}", config.protocol_options()));
+    summary.push(format!("Fresh DoH Connections: {}", config.doh_fresh_connections));
+    for (host, bootstrap) in &config.doh_bootstrap {
+        summary.push(format!("DoH Bootstrap: {} = {}", host, bootstrap));
+    }
+    summary.push(format!("DNS Sandbox Check: {}", config.sandbox_dns));
+    summary.push(format!("Flush DNS Cache: {}", config.flush_dns_cache));
+    summary.push(format!("Single Thread: {}", config.single_thread));
+    if let Some(ref ntp_server) = config.ntp_server {
+        summary.push(format!("NTP Server: {}", ntp_server));
+    }
+    summary.push(format!("Locale: {}", config.locale));
+    for (id, label) in &config.dns_labels {
+        summary.push(format!("DNS Label: {} = {}", id, label));
+    }
+    for (id, options) in &config.resolver_options {
+        summary.push(format!("Resolver Options: {} = {}", id, options));
+    }
+    if let Some(shard) = config.shard {
+        summary.push(format!("Shard: {}", shard));
+    }
+    if let Some(ref bind) = config.bind {
+        summary.push(format!("Bind: {}", bind));
+    }
+    if let Some(options) = config.pool_options {
+        summary.push(format!("Pool Options: {}", options));
+    }
+    if let Some(upload) = config.upload {
+        summary.push(format!("Upload: {}", upload));
+    }
+    if config.early_hints {
+        summary.push("Early Hints: true".to_string());
+    }
+    if let Some(threshold) = config.capture_on_anomaly {
+        summary.push(format!("Capture On Anomaly: {}", format_duration(threshold)));
+    }
+    if config.trace_requests {
+        summary.push("Trace Requests: true".to_string());
+    }
     summary.push(format!("Verbose: {}", config.verbose));
     summary.push(format!("Debug: {}", config.debug));
 
@@ -102,6 +482,7 @@ pub fn display_config_summary(config: &Config) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Reproduction;
     use clap::Parser;
     use std::env;
     use tempfile::NamedTempFile;
@@ -113,7 +494,7 @@ mod tests {
         let config = Config::default();
         
         assert_eq!(config.test_count, crate::defaults::DEFAULT_TEST_COUNT);
-        assert_eq!(config.timeout_seconds, crate::defaults::DEFAULT_TIMEOUT.as_secs());
+        assert_eq!(config.timeout, crate::defaults::DEFAULT_TIMEOUT);
         assert_eq!(config.enable_color, crate::defaults::DEFAULT_ENABLE_COLOR);
         assert!(!config.verbose);
         assert!(!config.debug);
@@ -143,14 +524,21 @@ mod tests {
             let _ = std::fs::rename(".env", ".env.test_backup_cli_overrides");
         }
         
-        let cli = Cli::parse_from(&["test", "--count", "10", "--timeout", "5", "--no-color", "--verbose"]);
+        let cli = Cli::parse_from(["test", "--count", "10", "--timeout", "5", "--no-color", "--verbose"]);
         let parser = ConfigParser::new(cli);
         let config = parser.parse().unwrap();
         
         assert_eq!(config.test_count, 10);
-        assert_eq!(config.timeout_seconds, 5);
+        assert_eq!(config.timeout, std::time::Duration::from_secs(5));
+        assert!(config.timeout_given);
         assert!(!config.enable_color);
         assert!(config.verbose);
+
+        // The default's value is given all the same
+        let config = ConfigParser::new(Cli::parse_from(["test", "--timeout", "10s"])).parse().unwrap();
+        assert!(config.timeout_given);
+        let config = ConfigParser::new(Cli::parse_from(["test"])).parse().unwrap();
+        assert!(!config.timeout_given);
         
         // Restore .env file
         if env_file_exists {
@@ -173,7 +561,7 @@ mod tests {
         // Clear environment variables to avoid interference
         env::remove_var("TARGET_URLS");
         
-        let cli = Cli::parse_from(&["test", "--url", "https://example.com"]);
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
         let parser = ConfigParser::new(cli);
         let config = parser.parse().unwrap();
         
@@ -186,12 +574,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_url_file_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("targets.csv");
+        std::fs::write(&path, "# targets\nhttps://example.com,edge\nhttps://api.example.com\n").unwrap();
+
+        let cli = Cli::parse_from([
+            "test", "--url", "https://example.com", "--url-file", path.to_str().unwrap(),
+        ]);
+        let config = ConfigParser::new(cli).parse().unwrap();
+        assert_eq!(config.target_urls, ["https://example.com", "https://api.example.com"]);
+        assert_eq!(config.url_tags["https://example.com"], ["edge"]);
+
+        let cli = Cli::parse_from(["test", "--url-file", dir.path().join("missing.txt").to_str().unwrap()]);
+        assert!(ConfigParser::new(cli).parse().unwrap_err().to_string().contains("missing.txt"));
+    }
+
+    #[test]
+    fn test_search_paths_flag() {
+        let cli = Cli::parse_from([
+            "test", "--url", "http://intranet", "--url", "https://example.com",
+            "--search-domains", "corp.example.com", "--ndots", "1", "--test-search-paths",
+        ]);
+        let config = ConfigParser::new(cli).parse().unwrap();
+        assert_eq!(config.target_urls, ["http://intranet", "http://intranet.corp.example.com", "https://example.com"]);
+        assert_eq!(config.search_domains, Some(vec!["corp.example.com".to_string()]));
+        assert!(display_config_summary(&config).contains("Search Domains: corp.example.com"));
+    }
+
+    #[test]
+    fn test_dns_servers_flag() {
+        let cli = Cli::parse_from([
+            "test", "--url", "https://example.com", "--dns-servers", "8.8.8.8+1.1.1.1, 9.9.9.9",
+            "--resolver-options", "8.8.8.8+1.1.1.1=strategy:race-all",
+        ]);
+        let config = ConfigParser::new(cli).parse().unwrap();
+        assert_eq!(config.dns_servers, ["8.8.8.8+1.1.1.1", "9.9.9.9"]);
+        assert_eq!(config.create_dns_configs().unwrap()[1].id(), "8.8.8.8+1.1.1.1");
+        assert!(display_config_summary(&config).contains("Resolver Options: 8.8.8.8+1.1.1.1 = strategy:race-all"));
+    }
+
+    #[test]
+    fn test_reproduction_round_trip() {
+        let cli = Cli::parse_from([
+            "test", "--url", "https://example.com", "--count", "7", "--timeout", "2500ms", "--loss-probe",
+            "--dns-chain", "223.5.5.5 > 8.8.8.8", "--user-agent", "my probe/1.0", "--dns-label", "system=ISP",
+        ]);
+        let reproduction = Reproduction::from_config(&ConfigParser::new(cli).parse().unwrap());
+
+        // The command line parses back into the same configuration
+        let mut args = vec!["test".to_string()];
+        args.extend(reproduction.urls.iter().map(|url| format!("--url={}", url)));
+        args.extend(reproduction.options.iter().cloned());
+        let config = ConfigParser::new(Cli::parse_from(args)).parse().unwrap();
+        assert_eq!(Reproduction::from_config(&config), reproduction);
+        assert!(reproduction.command().contains("--loss-probe=50"));
+    }
+
     #[test]
     fn test_original_url_flag() {
         // Clear environment variables to avoid interference
         env::remove_var("TARGET_URLS");
         
-        let cli = Cli::parse_from(&["test", "--test-original"]);
+        let cli = Cli::parse_from(["test", "--test-original"]);
         let parser = ConfigParser::new(cli);
         let config = parser.parse().unwrap();
         
@@ -298,7 +744,7 @@ mod tests {
         env::set_var("TEST_COUNT", "8");
         
         // Override with CLI
-        let cli = Cli::parse_from(&["test", "--count", "12"]);
+        let cli = Cli::parse_from(["test", "--count", "12"]);
         let parser = ConfigParser::new(cli);
         let config = parser.parse().unwrap();
         
@@ -0,0 +1,177 @@
+//! Preferences saved in the platform's config directory
+//!
+//! Settings such as the DNS servers to compare or the display locale rarely
+//! change between runs, but a `.env` file only applies in the directory it is
+//! in. [`Preferences`] keeps them in `network-latency-tester/config.env` under
+//! `$XDG_CONFIG_HOME`, `%APPDATA%` on Windows, or `~/.config`. The file uses
+//! the `.env` format and is read after `.env`, so that the environment, `.env`
+//! and command-line arguments all override it.
+
+use crate::config::{env::EnvManager, reload::read_env_file};
+use crate::error::{AppError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory of the preferences file within the config directory
+const APP_DIR: &str = "network-latency-tester";
+/// Name of the preferences file
+const FILE_NAME: &str = "config.env";
+
+/// Settings that can be saved as preferences, with what they do
+pub const PREFERENCE_KEYS: &[(&str, &str)] = &[
+    ("ENABLE_COLOR", "Colored output when the terminal supports it (true/false)"),
+    ("DISPLAY_LOCALE", "Language of DNS configuration names (en/zh)"),
+    ("DNS_SERVERS", "DNS servers to test (comma-separated IP addresses)"),
+    ("DOH_PROVIDERS", "DNS-over-HTTPS providers to test (comma-separated URLs)"),
+    ("DNS_CHAINS", "Resolver failover chains (comma-separated; members joined by '>')"),
+];
+
+/// The preferences file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preferences {
+    path: PathBuf,
+}
+
+impl Preferences {
+    /// Preferences kept in `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Preferences in the platform's config directory, if one can be found
+    pub fn locate() -> Option<Self> {
+        let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+        let config_dir = non_empty("XDG_CONFIG_HOME")
+            .or_else(|| if cfg!(windows) { non_empty("APPDATA") } else { None })
+            .or_else(|| non_empty("HOME").map(|home| home.join(".config")))?;
+        Some(Self::new(config_dir.join(APP_DIR).join(FILE_NAME)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Set the saved preferences as environment variables, except those
+    /// already set, so that they apply below `.env` and the environment
+    pub fn load(&self, debug: bool) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        dotenv::from_path(&self.path)
+            .map_err(|e| AppError::config(format!("Failed to load preferences from {}: {}", self.path.display(), e)).with_source(e))?;
+        if debug {
+            println!("Loaded preferences from {}", self.path.display());
+        }
+        Ok(())
+    }
+
+    /// Write a commented-out template on first run; returns whether one was written
+    pub fn create_if_missing(&self) -> Result<bool> {
+        if self.path.exists() {
+            return Ok(false);
+        }
+        self.write(&template())?;
+        Ok(true)
+    }
+
+    /// The preferences that are set, in file order
+    pub fn entries(&self) -> Result<Vec<(String, String)>> {
+        read_env_file(&self.path)
+    }
+
+    /// Save `key` (case-insensitive) as `value`, or remove it when `value` is
+    /// empty; returns the key as saved
+    pub fn set(&self, key: &str, value: &str) -> Result<String> {
+        let key = key.to_ascii_uppercase();
+        if !PREFERENCE_KEYS.iter().any(|(name, _)| *name == key) {
+            let names: Vec<&str> = PREFERENCE_KEYS.iter().map(|(name, _)| *name).collect();
+            return Err(AppError::validation(format!(
+                "{} cannot be saved as a preference; use one of {}", key, names.join(", ")
+            )));
+        }
+        let value = value.trim();
+        if !value.is_empty() {
+            EnvManager::validate_env_var(&key, value)?;
+        }
+
+        let content = if self.path.exists() {
+            fs::read_to_string(&self.path)
+                .map_err(|e| AppError::io(format!("Failed to read {}: {}", self.path.display(), e)).with_source(e))?
+        } else {
+            template()
+        };
+        // Replace the setting where it is, or uncomment its template line
+        let assignment = if value.is_empty() { format!("# {}=", key) } else { format!("{}={}", key, value) };
+        let is_setting = |line: &str| {
+            let line = line.trim_start().trim_start_matches('#').trim_start();
+            line.strip_prefix(key.as_str()).is_some_and(|rest| rest.starts_with('='))
+        };
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        match lines.iter().position(|line| is_setting(line)) {
+            Some(index) => lines[index] = assignment,
+            None => lines.push(assignment),
+        }
+        lines.retain({
+            let mut seen = false;
+            move |line| !is_setting(line) || !std::mem::replace(&mut seen, true)
+        });
+        self.write(&(lines.join("\n") + "\n"))?;
+        Ok(key)
+    }
+
+    fn write(&self, content: &str) -> Result<()> {
+        let failed = |e: std::io::Error| AppError::io(format!("Failed to write {}: {}", self.path.display(), e)).with_source(e);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(failed)?;
+        }
+        fs::write(&self.path, content).map_err(failed)
+    }
+}
+
+/// The preferences file written on first run, with every setting commented out
+pub fn template() -> String {
+    let mut content = String::from(
+        "# Network Latency Tester preferences\n\
+         #\n\
+         # Read on every run, after .env; the environment, .env and command-line\n\
+         # arguments override anything set here. Uncomment a line to set it, or use\n\
+         # `network-latency-tester config set KEY VALUE`.\n",
+    );
+    for (key, description) in PREFERENCE_KEYS {
+        content.push_str(&format!("\n# {}\n# {}=\n", description, key));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_preferences() {
+        let dir = TempDir::new().unwrap();
+        let preferences = Preferences::new(dir.path().join(APP_DIR).join(FILE_NAME));
+        assert!(preferences.entries().unwrap().is_empty());
+
+        assert!(preferences.create_if_missing().unwrap());
+        assert!(!preferences.create_if_missing().unwrap());
+        assert!(preferences.entries().unwrap().is_empty());
+
+        assert_eq!(preferences.set("dns_servers", "8.8.8.8,1.1.1.1").unwrap(), "DNS_SERVERS");
+        preferences.set("ENABLE_COLOR", "false").unwrap();
+        preferences.set("DNS_SERVERS", "9.9.9.9").unwrap();
+        assert_eq!(preferences.entries().unwrap(), [
+            ("ENABLE_COLOR".to_string(), "false".to_string()),
+            ("DNS_SERVERS".to_string(), "9.9.9.9".to_string()),
+        ]);
+        // The template lines were replaced rather than added to
+        let content = fs::read_to_string(preferences.path()).unwrap();
+        assert_eq!(content.matches("DNS_SERVERS=").count(), 1);
+
+        preferences.set("ENABLE_COLOR", "").unwrap();
+        assert_eq!(preferences.entries().unwrap().len(), 1);
+        assert!(preferences.set("TEST_COUNT", "5").is_err());
+        assert!(preferences.set("DNS_SERVERS", "not-an-ip").is_err());
+    }
+}
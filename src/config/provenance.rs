@@ -0,0 +1,189 @@
+//! Where each setting of the effective configuration came from
+//!
+//! Settings are layered: built-in defaults, then saved preferences, then
+//! `.env`, then the environment, then command-line arguments, each layer
+//! overriding the ones before it. Preferences and `.env` both reach the
+//! configuration as environment variables, so [`Provenance::from_env`] tells
+//! them apart from variables set in the shell by their values. `--check-config`
+//! prints the result with [`Provenance::report`].
+
+use crate::config::env::EnvManager;
+use crate::models::Config;
+use crate::utils::duration::format_duration;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A configuration layer, in increasing order of priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigSource {
+    Default,
+    Preferences,
+    EnvFile,
+    Environment,
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigSource::Default => "default",
+            ConfigSource::Preferences => "preferences",
+            ConfigSource::EnvFile => ".env",
+            ConfigSource::Environment => "environment",
+            ConfigSource::CommandLine => "command line",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The layer each setting was last set by, keyed by its environment variable name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl Provenance {
+    /// Sources of the settings set in the environment, given the variables
+    /// defined in `.env` and in the saved preferences
+    ///
+    /// Neither file overrides a variable that is already set, so a variable
+    /// whose value differs from the file's was set in the shell.
+    pub fn from_env(env_file: &[(String, String)], preferences: &[(String, String)]) -> Self {
+        Self::from_vars(|key| std::env::var(key).ok(), env_file, preferences)
+    }
+
+    fn from_vars(
+        var: impl Fn(&str) -> Option<String>,
+        env_file: &[(String, String)],
+        preferences: &[(String, String)],
+    ) -> Self {
+        let defines = |entries: &[(String, String)], key: &str, value: &str| {
+            entries.iter().any(|(name, defined)| name == key && defined == value)
+        };
+        let mut provenance = Self::default();
+        for (key, _, _) in EnvManager::get_supported_env_vars() {
+            let Some(value) = var(key) else { continue };
+            let source = if defines(env_file, key, &value) {
+                ConfigSource::EnvFile
+            } else if defines(preferences, key, &value) {
+                ConfigSource::Preferences
+            } else {
+                ConfigSource::Environment
+            };
+            provenance.set(key, source);
+        }
+        provenance
+    }
+
+    /// Record that `source` set `key`, overriding any lower layer
+    pub fn set(&mut self, key: &str, source: ConfigSource) {
+        self.sources.insert(key.to_string(), source);
+    }
+
+    /// The layer that set `key`
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources.get(key).copied().unwrap_or(ConfigSource::Default)
+    }
+
+    /// Every setting with its effective value and the layer it came from
+    pub fn report(&self, config: &Config) -> String {
+        let settings = EnvManager::get_supported_env_vars();
+        let width = settings.iter().map(|(key, _, _)| key.len()).max().unwrap_or(0);
+        let mut report = String::from("Effective configuration (highest priority first: command line, environment, .env, preferences, default)\n");
+        for (key, _, _) in settings {
+            report.push_str(&format!(
+                "  {:<width$}  {}  [{}]\n", key, setting_value(config, key), self.source(key), width = width
+            ));
+        }
+        report
+    }
+}
+
+/// The effective value of a setting, in the form its environment variable takes
+pub fn setting_value(config: &Config, key: &str) -> String {
+    let list = |items: &[String]| if items.is_empty() { "(none)".to_string() } else { items.join(",") };
+    let unset = || "(unset)".to_string();
+    match key {
+        "TARGET_URLS" => list(&config.target_urls),
+        "DNS_SERVERS" => list(&config.dns_servers),
+        "DOH_PROVIDERS" => list(&config.doh_providers),
+        "DNS_CHAINS" => list(&config.dns_chains),
+        "SEARCH_DOMAINS" => config.search_domains.as_deref().map_or_else(|| "(system)".to_string(), list),
+        "NDOTS" => config.ndots.map_or_else(|| "(system)".to_string(), |ndots| ndots.to_string()),
+        "TEST_SEARCH_PATHS" => config.test_search_paths.to_string(),
+        "TEST_COUNT" => config.test_count.to_string(),
+        "TIMEOUT_SECONDS" => format_duration(config.timeout),
+        "MAX_RUNTIME" => config.max_runtime.map_or_else(unset, format_duration),
+        "AUTO_COUNT" => config.auto_count.map_or_else(|| "false".to_string(), |precision| format!("{}%", precision)),
+        "ENABLE_COLOR" => config.enable_color.to_string(),
+        "SCORING_WEIGHTS" => config.scoring.to_string(),
+        "CENSOR_TIMEOUTS" => config.censor_timeouts.to_string(),
+        "SOCKET_STATS" => config.socket_stats.to_string(),
+        "LOSS_PROBE" => config.loss_probe.map_or_else(|| "false".to_string(), |packets| packets.to_string()),
+        "PING" => config.ping.map_or_else(|| "false".to_string(), |count| count.to_string()),
+        "REDIRECT_POLICY" => config.redirect_policy.to_string(),
+        "MAX_REDIRECTS" => config.max_redirects.to_string(),
+        "USER_AGENT" => config.user_agent.to_string(),
+        "HTTP2_PRIOR_KNOWLEDGE" => config.http2_prior_knowledge.to_string(),
+        "ALPN_FALLBACK" => config.alpn_fallback.to_string(),
+        "TLS_MIN_VERSION" => config.tls_min_version.map_or_else(unset, |version| version.to_string()),
+        "TLS_MAX_VERSION" => config.tls_max_version.map_or_else(unset, |version| version.to_string()),
+        "TLS_RESUMPTION" => config.tls_resumption.map_or_else(unset, |resumption| resumption.to_string()),
+        "DOH_FRESH_CONNECTIONS" => config.doh_fresh_connections.to_string(),
+        "DOH_BOOTSTRAP" => {
+            let bootstraps: Vec<String> = config.doh_bootstrap.iter().map(|(host, via)| format!("{}={}", host, via)).collect();
+            list(&bootstraps)
+        }
+        "SANDBOX_DNS" => config.sandbox_dns.to_string(),
+        "FLUSH_DNS_CACHE" => config.flush_dns_cache.to_string(),
+        "SINGLE_THREAD" => config.single_thread.to_string(),
+        "NTP_SERVER" => config.ntp_server.clone().unwrap_or_else(unset),
+        "DISPLAY_LOCALE" => config.locale.to_string(),
+        "DNS_LABELS" => {
+            let labels: Vec<String> = config.dns_labels.iter().map(|(id, label)| format!("{}={}", id, label)).collect();
+            list(&labels)
+        }
+        "DNS_RESOLVER_OPTIONS" => {
+            let options: Vec<String> = config.resolver_options.iter().map(|(id, options)| format!("{}={}", id, options)).collect();
+            list(&options)
+        }
+        "SHARD" => config.shard.map_or_else(unset, |shard| shard.to_string()),
+        "BIND" => config.bind.as_ref().map_or_else(unset, ToString::to_string),
+        "POOL_OPTIONS" => config.pool_options.map_or_else(unset, |options| options.to_string()),
+        "UPLOAD" => config.upload.map_or_else(unset, |upload| upload.to_string()),
+        "EARLY_HINTS" => config.early_hints.to_string(),
+        "CAPTURE_ON_ANOMALY" => config.capture_on_anomaly.map_or_else(unset, format_duration),
+        "TRACE_REQUESTS" => config.trace_requests.to_string(),
+        _ => unset(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_from_env() {
+        let entries = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+        };
+        let environment = entries(&[("NDOTS", "2"), ("MAX_REDIRECTS", "7"), ("NTP_SERVER", "time.example")]);
+        let env_file = entries(&[("NDOTS", "2"), ("MAX_REDIRECTS", "3")]);
+        let preferences = entries(&[("NDOTS", "4"), ("NTP_SERVER", "time.example")]);
+        let var = |key: &str| environment.iter().find(|(name, _)| name == key).map(|(_, value)| value.clone());
+        let mut provenance = Provenance::from_vars(var, &env_file, &preferences);
+
+        assert_eq!(provenance.source("NDOTS"), ConfigSource::EnvFile);
+        // .env defines it, but not with the value in effect
+        assert_eq!(provenance.source("MAX_REDIRECTS"), ConfigSource::Environment);
+        assert_eq!(provenance.source("NTP_SERVER"), ConfigSource::Preferences);
+        assert_eq!(provenance.source("SHARD"), ConfigSource::Default);
+
+        provenance.set("NDOTS", ConfigSource::CommandLine);
+        let config = Config { ndots: Some(3), ..Default::default() };
+        let report = provenance.report(&config);
+        assert!(report.lines().any(|line| line.trim_start().starts_with("NDOTS") && line.ends_with("3  [command line]")));
+        assert!(report.lines().any(|line| line.trim_start().starts_with("SHARD") && line.ends_with("(unset)  [default]")));
+        assert_eq!(EnvManager::get_supported_env_vars().len() + 1, report.lines().count());
+    }
+}
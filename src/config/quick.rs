@@ -0,0 +1,175 @@
+//! The `--quick` execution profile
+//!
+//! A sanity check should answer within seconds rather than measure precisely.
+//! [`QuickProfile`] cuts the matrix down to one target per tag group, a few
+//! iterations and the resolvers that answer fastest, shortens the timeout, and
+//! gives the run a time budget the executor scales the iterations down to.
+//! Settings given on the command line are left as they are.
+
+use crate::{
+    config::provenance::{ConfigSource, Provenance},
+    dns::DnsManager,
+    models::Config,
+    types::DnsConfig,
+};
+use futures::future::join_all;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant};
+
+/// Reduced settings for a rapid check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuickProfile {
+    /// Requests per target and DNS configuration
+    pub iterations: u32,
+    /// Longest a request may take
+    pub timeout: Duration,
+    /// Number of DNS configurations to keep
+    pub resolvers: usize,
+    /// Time budget for the whole run
+    pub budget: Duration,
+}
+
+impl Default for QuickProfile {
+    fn default() -> Self {
+        Self {
+            iterations: 3,
+            timeout: Duration::from_secs(2),
+            resolvers: 3,
+            budget: Duration::from_secs(10),
+        }
+    }
+}
+
+impl QuickProfile {
+    /// Apply the profile to the settings the command line did not give,
+    /// recording them as set on the command line
+    pub fn apply(&self, config: &mut Config, provenance: &mut Provenance) {
+        let mut preset = |key: &str| {
+            let open = provenance.source(key) != ConfigSource::CommandLine;
+            if open {
+                provenance.set(key, ConfigSource::CommandLine);
+            }
+            open
+        };
+        if preset("TARGET_URLS") {
+            config.target_urls = Self::select_targets(&config.target_urls, &config.url_tags);
+        }
+        if preset("TEST_COUNT") {
+            config.test_count = self.iterations;
+        }
+        if preset("TIMEOUT_SECONDS") {
+            config.timeout = config.timeout.min(self.timeout);
+        }
+        if preset("MAX_RUNTIME") {
+            config.max_runtime = Some(config.max_runtime.map_or(self.budget, |budget| budget.min(self.budget)));
+        }
+        // Extra iterations, probes and the clock check would not fit the budget
+        if preset("AUTO_COUNT") {
+            config.auto_count = None;
+        }
+        if preset("LOSS_PROBE") {
+            config.loss_probe = None;
+        }
+        if preset("PING") {
+            config.ping = None;
+        }
+        if preset("NTP_SERVER") {
+            config.ntp_server = None;
+        }
+    }
+
+    /// The first target of each tag group, in the order of `urls`; untagged
+    /// targets form a group of their own
+    pub fn select_targets(urls: &[String], tags: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+        let mut covered: BTreeSet<Option<&str>> = BTreeSet::new();
+        urls.iter()
+            .filter(|url| {
+                let groups: Vec<Option<&str>> = match tags.get(*url) {
+                    Some(tags) if !tags.is_empty() => tags.iter().map(|tag| Some(tag.as_str())).collect(),
+                    _ => vec![None],
+                };
+                // Every group the target is in must be new, or it adds nothing
+                let new = groups.iter().any(|group| !covered.contains(group));
+                covered.extend(groups);
+                new
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The DNS configurations that look up `domain` fastest, in their original
+    /// order; configurations that fail or take longer than the timeout go last
+    /// and are only kept to make up the number
+    pub async fn fastest_resolvers(&self, dns_manager: &DnsManager, dns_configs: Vec<DnsConfig>, domain: &str) -> Vec<DnsConfig> {
+        if dns_configs.len() <= self.resolvers {
+            return dns_configs;
+        }
+        let lookups = dns_configs.iter().map(|dns_config| async move {
+            let start = Instant::now();
+            match tokio::time::timeout(self.timeout, dns_manager.resolve(domain, dns_config)).await {
+                Ok(Ok(_)) => start.elapsed(),
+                _ => Duration::MAX,
+            }
+        });
+        let durations = join_all(lookups).await;
+        Self::keep_fastest(dns_configs, &durations, self.resolvers)
+    }
+
+    /// The `count` configurations with the shortest of `durations`, in their original order
+    fn keep_fastest(dns_configs: Vec<DnsConfig>, durations: &[Duration], count: usize) -> Vec<DnsConfig> {
+        let mut ranked: Vec<usize> = (0..dns_configs.len()).collect();
+        ranked.sort_by_key(|&index| durations[index]);
+        let kept: BTreeSet<usize> = ranked.into_iter().take(count).collect();
+        dns_configs.into_iter()
+            .enumerate()
+            .filter(|(index, _)| kept.contains(index))
+            .map(|(_, dns_config)| dns_config)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_profile() {
+        let urls: Vec<String> = ["https://a.example", "https://b.example", "https://c.example", "https://d.example", "https://e.example"]
+            .iter().map(ToString::to_string).collect();
+        let tags = BTreeMap::from([
+            ("https://a.example".to_string(), vec!["cdn".to_string()]),
+            ("https://b.example".to_string(), vec!["cdn".to_string()]),
+            ("https://c.example".to_string(), vec!["api".to_string(), "cdn".to_string()]),
+        ]);
+        assert_eq!(QuickProfile::select_targets(&urls, &tags), ["https://a.example", "https://c.example", "https://d.example"]);
+        assert_eq!(QuickProfile::select_targets(&urls, &BTreeMap::new()), ["https://a.example"]);
+
+        let mut config = Config {
+            target_urls: urls,
+            url_tags: tags,
+            test_count: 10,
+            loss_probe: Some(50),
+            ..Config::default()
+        };
+        let mut provenance = Provenance::default();
+        provenance.set("TEST_COUNT", ConfigSource::CommandLine);
+        QuickProfile::default().apply(&mut config, &mut provenance);
+        assert_eq!(config.target_urls.len(), 3);
+        // Given on the command line, so kept
+        assert_eq!(config.test_count, 10);
+        assert_eq!(config.timeout, Duration::from_secs(2));
+        assert_eq!(config.max_runtime, Some(Duration::from_secs(10)));
+        assert_eq!(config.loss_probe, None);
+        assert_eq!(provenance.source("TIMEOUT_SECONDS"), ConfigSource::CommandLine);
+
+        let dns_configs = vec![
+            DnsConfig::System,
+            DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap()] },
+            DnsConfig::Custom { servers: vec!["1.1.1.1".parse().unwrap()] },
+            DnsConfig::DoH { url: "https://dns.google/dns-query".to_string() },
+        ];
+        let durations = [Duration::from_millis(40), Duration::MAX, Duration::from_millis(12), Duration::from_millis(30)];
+        let kept = QuickProfile::keep_fastest(dns_configs.clone(), &durations, 2);
+        assert_eq!(kept, [dns_configs[2].clone(), dns_configs[3].clone()]);
+    }
+}
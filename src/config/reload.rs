@@ -0,0 +1,232 @@
+//! Configuration hot reload for watch mode
+//!
+//! While `--watch` repeats the tests, [`ConfigWatcher`] watches the `.env`
+//! file. Edits are picked up before the next cycle: the file is read back into
+//! the environment, the configuration is rebuilt from the same command-line
+//! arguments and the settings that changed are reported. Values given on the
+//! command line or exported in the shell before startup keep their precedence
+//! over the file, just as on a fresh start.
+
+use crate::{
+    cli::Cli,
+    config::parser::{display_config_summary, load_config},
+    error::{AppError, Result},
+    models::Config,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Summary fields compared item by item rather than as a whole
+const LIST_FIELDS: [&str; 4] = ["Target URLs", "DNS Servers", "DoH Providers", "DNS Chains"];
+
+/// Watches the configuration file and rebuilds the configuration when it changes
+pub struct ConfigWatcher {
+    cli: Cli,
+    path: PathBuf,
+    changed: Arc<AtomicBool>,
+    /// Variables whose values come from the file
+    file_keys: HashSet<String>,
+    /// Variables the shell set before startup, which the file cannot override
+    shell_keys: HashSet<String>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Watch `.env` in the current directory; call after the configuration was first loaded
+    pub fn new(cli: Cli) -> Result<Self> {
+        Self::watching(cli, Path::new(".env"))
+    }
+
+    fn watching(cli: Cli, path: &Path) -> Result<Self> {
+        let path = std::path::absolute(path)
+            .map_err(|e| AppError::config(format!("Cannot watch {}: {}", path.display(), e)))?;
+        let file_name = path.file_name().map(|name| name.to_os_string());
+        let changed = Arc::new(AtomicBool::new(false));
+
+        // Editors often replace the file instead of writing to it, so watch its directory
+        let flag = changed.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if !event.kind.is_access() && event.paths.iter().any(|p| p.file_name() == file_name.as_deref()) {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            }
+        })
+        .map_err(|e| AppError::config(format!("Cannot watch {}: {}", path.display(), e)))?;
+        let directory = path.parent().unwrap_or(Path::new("."));
+        watcher.watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|e| AppError::config(format!("Cannot watch {}: {}", directory.display(), e)))?;
+
+        // The file was loaded without overriding the shell, so a differing value came from the shell
+        let mut file_keys = HashSet::new();
+        let mut shell_keys = HashSet::new();
+        for (key, value) in read_env_file(&path)? {
+            if std::env::var(&key).is_ok_and(|current| current == value) {
+                file_keys.insert(key);
+            } else {
+                shell_keys.insert(key);
+            }
+        }
+
+        Ok(Self { cli, path, changed, file_keys, shell_keys, _watcher: watcher })
+    }
+
+    /// Path of the watched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last reload
+    pub fn has_changed(&self) -> bool {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    /// Rebuild the configuration if the file changed since the last call
+    ///
+    /// When the edited file is invalid the error is returned and the current
+    /// configuration should stay in effect; [`diff`] describes what a new one changes.
+    pub fn reload(&mut self) -> Result<Option<Config>> {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let entries = read_env_file(&self.path)?;
+        let keys: HashSet<String> = entries.iter()
+            .map(|(key, _)| key.clone())
+            .filter(|key| !self.shell_keys.contains(key))
+            .collect();
+        // Settings deleted from the file fall back to their defaults
+        for key in self.file_keys.difference(&keys) {
+            std::env::remove_var(key);
+        }
+        for (key, value) in entries.iter().filter(|(key, _)| keys.contains(key)) {
+            std::env::set_var(key, value);
+        }
+        self.file_keys = keys;
+
+        load_config(self.cli.clone()).map(Some)
+    }
+}
+
+/// Read the variables defined in a .env file; a missing file defines none
+pub(crate) fn read_env_file(path: &Path) -> Result<Vec<(String, String)>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    // Deprecated upstream, but dotenv has no other way to read a file without applying it
+    #[allow(deprecated)]
+    let entries = dotenv::from_path_iter(path);
+    entries
+        .and_then(|entries| entries.collect::<std::result::Result<Vec<_>, _>>())
+        .map_err(|e| AppError::config(format!("Failed to read {}: {}", path.display(), e)).with_source(e))
+}
+
+/// Describe every setting that differs between two configurations, e.g.
+/// `Timeout: 10s -> 5s` or `DNS Servers: +9.9.9.9, -8.8.8.8`
+pub fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for (field, before, after) in [
+        (LIST_FIELDS[0], &old.target_urls, &new.target_urls),
+        (LIST_FIELDS[1], &old.dns_servers, &new.dns_servers),
+        (LIST_FIELDS[2], &old.doh_providers, &new.doh_providers),
+        (LIST_FIELDS[3], &old.dns_chains, &new.dns_chains),
+    ] {
+        let added = after.iter().filter(|item| !before.contains(item)).map(|item| format!("+{}", item));
+        let removed = before.iter().filter(|item| !after.contains(item)).map(|item| format!("-{}", item));
+        let items: Vec<String> = added.chain(removed).collect();
+        if !items.is_empty() {
+            changes.push(format!("{}: {}", field, items.join(", ")));
+        }
+    }
+
+    let before = summary_fields(old);
+    let after = summary_fields(new);
+    for (field, value) in &after {
+        match before.iter().find(|(f, _)| f == field) {
+            Some((_, old_value)) if old_value == value => {}
+            Some((_, old_value)) => changes.push(format!("{}: {} -> {}", field, old_value, value)),
+            None => changes.push(format!("{}: {} (was unset)", field, value)),
+        }
+    }
+    for (field, value) in &before {
+        if !after.iter().any(|(f, _)| f == field) {
+            changes.push(format!("{}: {} (now unset)", field, value));
+        }
+    }
+
+    changes
+}
+
+/// Fields of the configuration summary, with repeated fields joined
+fn summary_fields(config: &Config) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for (field, value) in display_config_summary(config).lines().filter_map(|line| line.split_once(": ")) {
+        if LIST_FIELDS.contains(&field) {
+            continue;
+        }
+        match fields.iter_mut().find(|(f, _)| f == field) {
+            Some((_, values)) => {
+                values.push_str(", ");
+                values.push_str(value);
+            }
+            None => fields.push((field.to_string(), value.to_string())),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_config_diff() {
+        let old = Config {
+            dns_servers: vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()],
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        assert!(diff(&old, &new).is_empty());
+
+        new.dns_servers = vec!["1.1.1.1".to_string(), "9.9.9.9".to_string()];
+        new.timeout = Duration::from_secs(5);
+        new.max_runtime = Some(Duration::from_secs(600));
+        new.dns_labels.insert("system".to_string(), "ISP".to_string());
+        assert_eq!(diff(&old, &new), [
+            "DNS Servers: +9.9.9.9, -8.8.8.8",
+            "Timeout: 10s -> 5s",
+            "Max Runtime: 10m (was unset)",
+            "DNS Label: system = ISP (was unset)",
+        ]);
+    }
+
+    #[test]
+    fn test_watcher_notices_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "NLT_RELOAD_TEST_UNUSED=1\n").unwrap();
+
+        let cli = Cli::parse_from(["test", "--url", "https://example.com"]);
+        let watcher = ConfigWatcher::watching(cli, &path).unwrap();
+        assert!(!watcher.has_changed());
+
+        std::fs::write(dir.path().join("other.txt"), "unrelated").unwrap();
+        std::fs::write(&path, "NLT_RELOAD_TEST_UNUSED=2\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !watcher.has_changed() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(watcher.has_changed());
+        assert_eq!(read_env_file(watcher.path()).unwrap(), [("NLT_RELOAD_TEST_UNUSED".to_string(), "2".to_string())]);
+    }
+}
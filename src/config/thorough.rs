@@ -0,0 +1,99 @@
+//! The `--thorough` execution profile
+//!
+//! Evidence for a support ticket should leave no room for "please measure
+//! again". [`ThoroughProfile`] takes the most samples the configuration
+//! allows and keeps adding iterations until the averages are precise, and
+//! turns on every measurement that helps to tell where time goes: packet
+//! loss, kernel TCP statistics and the clock check. Settings given on the
+//! command line are left as they are.
+
+use crate::{
+    client::socket_stats,
+    config::provenance::{ConfigSource, Provenance},
+    models::Config,
+};
+
+/// Settings for collecting comprehensive evidence
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThoroughProfile {
+    /// Requests per target and DNS configuration before precision is checked
+    pub iterations: u32,
+    /// Precision, in percent, the mean and p95 must reach
+    pub precision: f64,
+    /// UDP packets per route and DNS server for the loss probe
+    pub loss_packets: u16,
+    /// Server to check the system clock against when none is configured
+    pub ntp_server: String,
+}
+
+impl Default for ThoroughProfile {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            precision: 1.0,
+            loss_packets: 100,
+            ntp_server: "pool.ntp.org".to_string(),
+        }
+    }
+}
+
+impl ThoroughProfile {
+    /// Apply the profile to the settings the command line did not give,
+    /// recording them as set on the command line
+    pub fn apply(&self, config: &mut Config, provenance: &mut Provenance) {
+        let mut preset = |key: &str| {
+            let open = provenance.source(key) != ConfigSource::CommandLine;
+            if open {
+                provenance.set(key, ConfigSource::CommandLine);
+            }
+            open
+        };
+        if preset("TEST_COUNT") {
+            config.test_count = config.test_count.max(self.iterations);
+        }
+        if preset("AUTO_COUNT") {
+            config.auto_count = Some(config.auto_count.map_or(self.precision, |precision| precision.min(self.precision)));
+        }
+        if preset("LOSS_PROBE") {
+            config.loss_probe = Some(config.loss_probe.map_or(self.loss_packets, |packets| packets.max(self.loss_packets)));
+        }
+        if socket_stats::SUPPORTED && preset("SOCKET_STATS") {
+            config.socket_stats = true;
+        }
+        if config.ntp_server.is_none() && preset("NTP_SERVER") {
+            config.ntp_server = Some(self.ntp_server.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thorough_profile() {
+        let mut config = Config {
+            auto_count: Some(5.0),
+            loss_probe: Some(500),
+            ..Config::default()
+        };
+        let mut provenance = Provenance::default();
+        provenance.set("NTP_SERVER", ConfigSource::CommandLine);
+        ThoroughProfile::default().apply(&mut config, &mut provenance);
+        assert_eq!(config.test_count, 100);
+        // The more precise and the larger of the two
+        assert_eq!(config.auto_count, Some(1.0));
+        assert_eq!(config.loss_probe, Some(500));
+        assert_eq!(config.socket_stats, socket_stats::SUPPORTED);
+        // Given on the command line, so kept
+        assert_eq!(config.ntp_server, None);
+        assert_eq!(provenance.source("TEST_COUNT"), ConfigSource::CommandLine);
+
+        let mut config = Config { test_count: 5, ..Config::default() };
+        let mut provenance = Provenance::default();
+        provenance.set("TEST_COUNT", ConfigSource::CommandLine);
+        ThoroughProfile::default().apply(&mut config, &mut provenance);
+        assert_eq!(config.test_count, 5);
+        assert_eq!(config.ntp_server.as_deref(), Some("pool.ntp.org"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Target list files for `--url-file`
+//!
+//! One URL per line, optionally followed by comma-separated tags:
+//!
+//! ```text
+//! # Production APIs
+//! https://api.example.com,prod,api
+//! https://www.example.com        # tags are optional
+//! ```
+//!
+//! Blank lines and comments (`#` at the start of a line or after whitespace)
+//! are skipped, and a `url,...` header line is allowed so that spreadsheet
+//! exports load as they are. URLs are normalized (see
+//! [`normalize_url`](crate::utils::url::normalize_url)); a URL listed twice is
+//! tested once, with the tags of both lines. Lines with a URL that cannot be
+//! fixed are set aside rather than failing the whole file.
+
+use crate::{
+    error::{AppError, Result},
+    utils::url::{normalize_url, UrlIssue},
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// URLs and their tags read from a target list file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UrlList {
+    /// URLs in the order they first appear
+    pub urls: Vec<String>,
+    /// Tags of each URL that has any
+    pub tags: BTreeMap<String, Vec<String>>,
+    /// Lines that were skipped because of an invalid URL, by line number
+    pub rejected: Vec<(usize, UrlIssue)>,
+}
+
+impl UrlList {
+    /// Read and parse a target list file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::config(format!("Failed to read URL file {}: {}", path.display(), e)).with_source(e))?;
+        Self::parse(&content)
+            .map_err(|e| AppError::config(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Parse the contents of a target list file
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut list = Self::default();
+        let mut seen = HashSet::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(str::trim);
+            let entry = fields.next().unwrap_or_default();
+            if number == 0 && entry.eq_ignore_ascii_case("url") {
+                continue;
+            }
+            let url = match normalize_url(entry) {
+                Ok(url) => url,
+                Err(issue) => {
+                    list.rejected.push((number + 1, issue));
+                    continue;
+                }
+            };
+
+            if seen.insert(url.clone()) {
+                list.urls.push(url.clone());
+            }
+            for tag in fields.filter(|tag| !tag.is_empty()) {
+                let tags = list.tags.entry(url.clone()).or_default();
+                if !tags.iter().any(|existing| existing == tag) {
+                    tags.push(tag.to_string());
+                }
+            }
+        }
+
+        if list.urls.is_empty() {
+            let problems: Vec<String> = list.rejected.iter()
+                .map(|(line, issue)| format!("\n  line {}: {}", line, issue))
+                .collect();
+            return Err(AppError::config(format!("no valid URLs found{}", problems.concat())));
+        }
+        Ok(list)
+    }
+}
+
+/// Remove a `#` comment, which must start the line or follow whitespace so
+/// that URL fragments are kept
+fn strip_comment(line: &str) -> &str {
+    let mut previous = None;
+    for (index, c) in line.char_indices() {
+        if c == '#' && previous.is_none_or(char::is_whitespace) {
+            return &line[..index];
+        }
+        previous = Some(c);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_list() {
+        let list = UrlList::parse(
+            "url,tags\n\
+             # Production APIs\n\
+             https://api.example.com, prod ,api\n\
+             \n\
+             https://www.example.com/#top   # fragment kept\n\
+             api.example.com,prod,eu\n             htps://typo.example,broken\n",
+        )
+        .unwrap();
+
+        assert_eq!(list.urls, ["https://api.example.com", "https://www.example.com/#top"]);
+        assert_eq!(list.tags.len(), 1);
+        assert_eq!(list.tags["https://api.example.com"], ["prod", "api", "eu"]);
+        assert_eq!(list.rejected.len(), 1);
+        assert_eq!(list.rejected[0].0, 7);
+        assert_eq!(list.rejected[0].1.suggestion.as_deref(), Some("https://typo.example"));
+
+        let error = UrlList::parse("not a url\nhtps://typo.example\n").unwrap_err();
+        assert!(error.to_string().contains("line 1: 'not a url': URL contains whitespace"), "{}", error);
+        assert!(error.to_string().contains("line 2: 'htps://typo.example'"));
+        assert!(UrlList::parse("# nothing here\n\n").is_err());
+    }
+}
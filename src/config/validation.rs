@@ -1,12 +1,21 @@
 //! Configuration validation utilities and rules
 
 use crate::{
+    config::env::EnvManager,
     models::Config,
     error::{AppError, Result},
+    utils::duration::format_duration,
 };
 use std::net::IpAddr;
+use std::path::Path;
 use std::time::Duration;
 
+/// Variables a .env file may set that are read elsewhere than the configuration
+const OTHER_KNOWN_KEYS: &[&str] = &[
+    // Read by the updater to authenticate GitHub API requests
+    "GITHUB_TOKEN",
+];
+
 /// Configuration validator with advanced validation rules
 pub struct ConfigValidator;
 
@@ -27,6 +36,43 @@ impl ConfigValidator {
         Ok(warnings)
     }
 
+    /// Keys defined in a .env-format file that name no setting
+    pub fn unknown_keys(entries: &[(String, String)]) -> Vec<&str> {
+        let supported = EnvManager::get_supported_env_vars();
+        entries.iter()
+            .map(|(key, _)| key.as_str())
+            .filter(|key| !supported.iter().any(|(name, _, _)| name == key) && !OTHER_KNOWN_KEYS.contains(key))
+            .collect()
+    }
+
+    /// Strict mode: fail when `file` defines keys that name no setting, which
+    /// are otherwise ignored, e.g. a misspelt `TEST_CONUT`
+    pub fn check_known_keys(file: &Path, entries: &[(String, String)]) -> Result<()> {
+        let unknown = Self::unknown_keys(entries);
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::config(format!(
+            "Strict mode: {} sets unknown configuration keys: {} (see --help-topic config for the supported ones)",
+            file.display(), unknown.join(", ")
+        )))
+    }
+
+    /// Strict mode: fail unless every DoH provider answers over HTTPS, rather
+    /// than letting its requests fail during the run
+    pub async fn check_doh_reachable(config: &Config) -> Result<()> {
+        let mut unreachable = Vec::new();
+        for provider in &config.doh_providers {
+            if let ConnectivityResult::Failed { error } = test_doh_connectivity(provider, config.timeout()).await {
+                unreachable.push(format!("{} ({})", provider, error));
+            }
+        }
+        if unreachable.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::config(format!("Strict mode: DoH providers unreachable: {}", unreachable.join(", "))))
+    }
+
     /// Validate target URLs with detailed checks
     fn validate_target_urls(urls: &[String]) -> Result<Vec<ValidationWarning>> {
         let mut warnings = Vec::new();
@@ -100,7 +146,8 @@ impl ConfigValidator {
     fn validate_dns_servers(servers: &[String]) -> Result<Vec<ValidationWarning>> {
         let mut warnings = Vec::new();
 
-        for server in servers {
+        // Servers joined with `+` make up one configuration
+        for server in servers.iter().flat_map(|entry| entry.split('+')).map(str::trim) {
             match server.parse::<IpAddr>() {
                 Ok(ip) => {
                     // Check for known public DNS servers
@@ -211,15 +258,15 @@ impl ConfigValidator {
         }
 
         // Check timeout
-        if config.timeout_seconds < 3 {
+        if config.timeout < Duration::from_secs(3) {
             warnings.push(ValidationWarning::new(
                 ValidationLevel::Warning,
-                format!("Timeout of {}s may be too short for reliable measurements", config.timeout_seconds)
+                format!("Timeout of {} may be too short for reliable measurements", format_duration(config.timeout))
             ));
-        } else if config.timeout_seconds > 60 {
+        } else if config.timeout > Duration::from_secs(60) {
             warnings.push(ValidationWarning::new(
                 ValidationLevel::Info,
-                format!("Long timeout of {}s will slow down failure detection", config.timeout_seconds)
+                format!("Long timeout of {} will slow down failure detection", format_duration(config.timeout))
             ));
         }
 
@@ -396,8 +443,8 @@ async fn test_url_connectivity(url: &str, timeout: Duration) -> ConnectivityResu
 /// Test DNS server connectivity
 async fn test_dns_connectivity(dns_server: &str) -> ConnectivityResult {
     // For now, just validate the IP format (actual DNS query testing would be more complex)
-    match dns_server.parse::<IpAddr>() {
-        Ok(_) => ConnectivityResult::Success {
+    match dns_server.split('+').try_for_each(|server| server.trim().parse::<IpAddr>().map(drop)) {
+        Ok(()) => ConnectivityResult::Success {
             status_code: None,
             response_time: None,
         },
@@ -512,6 +559,7 @@ pub struct ConnectivitySummary {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -638,17 +686,17 @@ mod tests {
         let mut config = Config::default();
         
         // Test minimum boundary
-        config.timeout_seconds = 1;
+        config.timeout = Duration::from_secs(1);
         let warnings = ConfigValidator::validate_comprehensive(&config).unwrap();
         assert!(warnings.iter().any(|w| w.message.contains("too short")));
         
         // Test just above boundary
-        config.timeout_seconds = 3;
+        config.timeout = Duration::from_secs(3);
         let warnings = ConfigValidator::validate_comprehensive(&config).unwrap();
         assert!(!warnings.iter().any(|w| w.message.contains("too short")));
         
         // Test high boundary
-        config.timeout_seconds = 61;
+        config.timeout = Duration::from_secs(61);
         let warnings = ConfigValidator::validate_comprehensive(&config).unwrap();
         assert!(warnings.iter().any(|w| w.message.contains("slow down failure")));
     }
@@ -684,4 +732,27 @@ mod tests {
         let warnings = ConfigValidator::validate_comprehensive(&config).unwrap();
         assert!(warnings.iter().any(|w| w.message.contains("long time")));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strict_unknown_keys() {
+        let entries: Vec<(String, String)> = [("TEST_COUNT", "5"), ("TEST_CONUT", "5"), ("GITHUB_TOKEN", "x"), ("RUST_LOG", "debug")]
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(ConfigValidator::unknown_keys(&entries), ["TEST_CONUT", "RUST_LOG"]);
+        let error = ConfigValidator::check_known_keys(Path::new(".env"), &entries).unwrap_err();
+        assert!(error.to_string().contains(".env sets unknown configuration keys: TEST_CONUT, RUST_LOG"));
+        assert!(ConfigValidator::check_known_keys(Path::new(".env"), &entries[..1]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_strict_unreachable_doh() {
+        let mut config = Config::default();
+        config.doh_providers = vec!["https://127.0.0.1:9/dns-query".to_string()];
+        let error = ConfigValidator::check_doh_reachable(&config).await.unwrap_err();
+        assert!(error.to_string().contains("DoH providers unreachable: https://127.0.0.1:9/dns-query"));
+
+        config.doh_providers.clear();
+        assert!(ConfigValidator::check_doh_reachable(&config).await.is_ok());
+    }
+}
@@ -0,0 +1,413 @@
+//! Interactive setup wizard for creating a .env configuration file
+//!
+//! The wizard asks for each supported setting in turn, validates answers with
+//! the same rules used for environment variables, and runs a quick
+//! reachability check on target URLs, DNS servers and DoH providers before
+//! accepting them. The answers are written as a `.env` file that is picked up
+//! automatically on the next run.
+
+use crate::config::EnvManager;
+use crate::error::{AppError, Result};
+use crate::models::Config;
+use crate::utils::duration::format_duration;
+use std::io::{BufRead, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long a reachability check may take per endpoint
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Answer that clears an optional setting
+const NONE_ANSWER: &str = "none";
+
+/// Endpoint checked before an answer is accepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeTarget {
+    /// HTTP(S) URL of a test target or DoH provider
+    Url(String),
+    /// DNS server address
+    DnsServer(IpAddr),
+}
+
+/// Quick reachability check used while answering the wizard
+pub trait Probe {
+    /// Check that `target` can be reached, describing the problem if not
+    fn check(&self, target: &ProbeTarget) -> std::result::Result<(), String>;
+}
+
+/// Reachability check that opens a TCP connection to the endpoint
+///
+/// URLs are checked on their host and port (80/443 by default); DNS servers
+/// are checked on TCP port 53, which every public resolver accepts.
+#[derive(Debug, Clone)]
+pub struct TcpProbe {
+    timeout: Duration,
+}
+
+impl TcpProbe {
+    /// Create a probe that gives up on an endpoint after `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    fn connect(&self, addrs: impl Iterator<Item = SocketAddr>) -> std::result::Result<(), String> {
+        let mut last_error = "no addresses found".to_string();
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = format!("{}: {}", addr, e),
+            }
+        }
+        Err(last_error)
+    }
+}
+
+impl Default for TcpProbe {
+    fn default() -> Self {
+        Self::new(PROBE_TIMEOUT)
+    }
+}
+
+impl Probe for TcpProbe {
+    fn check(&self, target: &ProbeTarget) -> std::result::Result<(), String> {
+        match target {
+            ProbeTarget::Url(url) => {
+                let parsed = url::Url::parse(url).map_err(|e| e.to_string())?;
+                let host = parsed.host_str().ok_or("URL has no host")?;
+                let port = parsed.port_or_known_default().ok_or("URL has no port")?;
+                let addrs = (host, port)
+                    .to_socket_addrs()
+                    .map_err(|e| format!("cannot resolve {}: {}", host, e))?;
+                self.connect(addrs)
+            }
+            ProbeTarget::DnsServer(ip) => self.connect(std::iter::once(SocketAddr::new(*ip, 53))),
+        }
+    }
+}
+
+/// A single wizard question, answered with the value of one environment variable
+struct Question {
+    key: &'static str,
+    prompt: &'static str,
+    default: String,
+    optional: bool,
+    probe: fn(&str) -> Vec<ProbeTarget>,
+}
+
+fn no_probe(_: &str) -> Vec<ProbeTarget> {
+    Vec::new()
+}
+
+fn probe_urls(value: &str) -> Vec<ProbeTarget> {
+    split_list(value).map(|url| ProbeTarget::Url(url.to_string())).collect()
+}
+
+fn probe_dns_servers(value: &str) -> Vec<ProbeTarget> {
+    split_list(value)
+        .filter_map(|server| server.parse().ok())
+        .map(ProbeTarget::DnsServer)
+        .collect()
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Settings collected by the wizard, as environment variable assignments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WizardAnswers {
+    /// `(KEY, value)` pairs in the order they were asked
+    pub values: Vec<(&'static str, String)>,
+}
+
+impl WizardAnswers {
+    /// Value given for an environment variable, if it was set
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Render the answers as .env file content
+    pub fn to_env_content(&self) -> String {
+        let mut content = String::new();
+        content.push_str("# Network Latency Tester Configuration\n");
+        content.push_str("#\n");
+        content.push_str("# Generated by `network-latency-tester init`. Command-line arguments\n");
+        content.push_str("# override these values; see .env.example for all options.\n\n");
+
+        for (var, description, _) in EnvManager::get_supported_env_vars() {
+            if let Some(value) = self.get(var) {
+                content.push_str(&format!("# {}\n{}={}\n\n", description, var, value));
+            }
+        }
+
+        content
+    }
+}
+
+/// Interactive prompt loop collecting and validating configuration answers
+pub struct SetupWizard<R, W, P> {
+    input: R,
+    output: W,
+    probe: Option<P>,
+}
+
+impl<R: BufRead, W: Write, P: Probe> SetupWizard<R, W, P> {
+    /// Create a wizard reading answers from `input` and writing prompts to `output`
+    ///
+    /// Without a `probe`, answers are validated but endpoints are not contacted.
+    pub fn new(input: R, output: W, probe: Option<P>) -> Self {
+        Self { input, output, probe }
+    }
+
+    /// Ask every question and return the validated answers
+    pub fn run(&mut self) -> Result<WizardAnswers> {
+        self.say("Network Latency Tester setup")?;
+        self.say("Press Enter to accept the value in brackets, or type 'none' to leave an optional setting unset.\n")?;
+
+        let mut answers = WizardAnswers::default();
+        for question in Self::questions() {
+            if let Some(value) = self.ask(&question)? {
+                answers.values.push((question.key, value));
+            }
+        }
+
+        Ok(answers)
+    }
+
+    /// Ask a yes/no question
+    pub fn confirm(&mut self, prompt: &str, default: bool) -> Result<bool> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        loop {
+            let answer = self.read_answer(&format!("{} [{}]", prompt, hint))?;
+            match answer.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => self.say("Please answer 'y' or 'n'.")?,
+            }
+        }
+    }
+
+    fn questions() -> Vec<Question> {
+        let defaults = Config::default();
+        vec![
+            Question {
+                key: "TARGET_URLS",
+                prompt: "Target URLs to test (comma-separated)",
+                default: defaults.target_urls.join(","),
+                optional: false,
+                probe: probe_urls,
+            },
+            Question {
+                key: "DNS_SERVERS",
+                prompt: "Custom DNS servers to compare (comma-separated IPs)",
+                default: defaults.dns_servers.join(","),
+                optional: true,
+                probe: probe_dns_servers,
+            },
+            Question {
+                key: "DOH_PROVIDERS",
+                prompt: "DNS-over-HTTPS providers (comma-separated HTTPS URLs)",
+                default: defaults.doh_providers.join(","),
+                optional: true,
+                probe: probe_urls,
+            },
+            Question {
+                key: "TEST_COUNT",
+                prompt: "Requests per URL and DNS configuration (1-100)",
+                default: defaults.test_count.to_string(),
+                optional: false,
+                probe: no_probe,
+            },
+            Question {
+                key: "TIMEOUT_SECONDS",
+                prompt: "Request timeout (e.g. 500ms, 10s)",
+                default: format_duration(defaults.timeout),
+                optional: false,
+                probe: no_probe,
+            },
+            Question {
+                key: "MAX_RUNTIME",
+                prompt: "Time budget for a whole run (e.g. 10m)",
+                default: NONE_ANSWER.to_string(),
+                optional: true,
+                probe: no_probe,
+            },
+            Question {
+                key: "ENABLE_COLOR",
+                prompt: "Colored output (true/false)",
+                default: defaults.enable_color.to_string(),
+                optional: false,
+                probe: no_probe,
+            },
+            Question {
+                key: "SCORING_WEIGHTS",
+                prompt: "Ranking weights (speed, reliability, consistency, p95, jitter, loss)",
+                default: defaults.scoring.to_string(),
+                optional: true,
+                probe: no_probe,
+            },
+        ]
+    }
+
+    /// Ask one question until a valid, reachable (or explicitly accepted) answer is given
+    ///
+    /// Returns `None` when an optional setting is left unset.
+    fn ask(&mut self, question: &Question) -> Result<Option<String>> {
+        loop {
+            let answer = self.read_answer(&format!("{} [{}]", question.prompt, question.default))?;
+            let answer = if answer.is_empty() { question.default.clone() } else { answer };
+
+            if answer.eq_ignore_ascii_case(NONE_ANSWER) {
+                if question.optional {
+                    // An empty list disables the comparison; other settings are simply omitted
+                    return Ok(matches!(question.key, "DNS_SERVERS" | "DOH_PROVIDERS").then(String::new));
+                }
+                self.say("  This setting is required.")?;
+                continue;
+            }
+
+            if let Err(e) = EnvManager::validate_env_var(question.key, &answer) {
+                self.say(&format!("  {}", e))?;
+                continue;
+            }
+
+            if self.check_reachability((question.probe)(&answer))? {
+                return Ok(Some(answer));
+            }
+        }
+    }
+
+    /// Probe each endpoint, returning whether the answer should be kept
+    fn check_reachability(&mut self, targets: Vec<ProbeTarget>) -> Result<bool> {
+        let Some(probe) = self.probe.as_ref() else {
+            return Ok(true);
+        };
+        if targets.is_empty() {
+            return Ok(true);
+        }
+
+        let results: Vec<_> = targets.iter().map(|target| (target, probe.check(target))).collect();
+
+        let mut unreachable = 0;
+        for (target, result) in results {
+            let name = match target {
+                ProbeTarget::Url(url) => url.clone(),
+                ProbeTarget::DnsServer(ip) => ip.to_string(),
+            };
+            match result {
+                Ok(()) => self.say(&format!("  ✓ {} is reachable", name))?,
+                Err(reason) => {
+                    unreachable += 1;
+                    self.say(&format!("  ✗ {} is not reachable ({})", name, reason))?;
+                }
+            }
+        }
+
+        if unreachable == 0 {
+            return Ok(true);
+        }
+        self.confirm("  Keep these values anyway?", false)
+    }
+
+    fn read_answer(&mut self, prompt: &str) -> Result<String> {
+        write!(self.output, "{}: ", prompt)
+            .and_then(|_| self.output.flush())
+            .map_err(|e| AppError::io(format!("Failed to write prompt: {}", e)))?;
+
+        let mut line = String::new();
+        let read = self
+            .input
+            .read_line(&mut line)
+            .map_err(|e| AppError::io(format!("Failed to read answer: {}", e)))?;
+        if read == 0 {
+            return Err(AppError::config("Setup cancelled: no more input"));
+        }
+
+        Ok(line.trim().to_string())
+    }
+
+    fn say(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "{}", message).map_err(|e| AppError::io(format!("Failed to write prompt: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Probe that treats every endpoint containing "down" as unreachable
+    struct FakeProbe;
+
+    impl Probe for FakeProbe {
+        fn check(&self, target: &ProbeTarget) -> std::result::Result<(), String> {
+            match target {
+                ProbeTarget::Url(url) if url.contains("down") => Err("connection refused".to_string()),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn run_wizard(input: &str) -> (Result<WizardAnswers>, String) {
+        let mut output = Vec::new();
+        let result = SetupWizard::new(Cursor::new(input), &mut output, Some(FakeProbe)).run();
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_wizard_accepts_defaults() {
+        let (answers, _) = run_wizard("\n\n\n\n\n\n\n\n");
+        let answers = answers.unwrap();
+
+        assert_eq!(answers.get("TARGET_URLS"), Some("https://bing.com"));
+        assert_eq!(answers.get("TEST_COUNT"), Some("5"));
+        assert_eq!(answers.get("TIMEOUT_SECONDS"), Some("10s"));
+        assert_eq!(answers.get("MAX_RUNTIME"), None);
+
+        let content = answers.to_env_content();
+        assert!(content.contains("TARGET_URLS=https://bing.com\n"));
+        assert!(!content.contains("MAX_RUNTIME"));
+    }
+
+    #[test]
+    fn test_wizard_revalidates_bad_answers() {
+        let mut input = [
+            "https://down.example",  // unreachable
+            "n",                     // do not keep it
+            "https://api.example",
+            "none",                  // no custom DNS
+            "http://doh.example",    // DoH must be HTTPS
+            "https://doh.example/dns-query",
+            "500",                   // out of range
+            "20",
+            "0s",                    // timeout must be > 0
+            "750ms",
+            "90s",
+            "maybe",                 // not a bool
+            "false",
+            "",
+        ]
+        .join("\n");
+        input.push('\n');
+
+        let (answers, output) = run_wizard(&input);
+        let answers = answers.unwrap();
+
+        assert_eq!(answers.get("TARGET_URLS"), Some("https://api.example"));
+        assert_eq!(answers.get("DNS_SERVERS"), Some(""));
+        assert_eq!(answers.get("DOH_PROVIDERS"), Some("https://doh.example/dns-query"));
+        assert_eq!(answers.get("TEST_COUNT"), Some("20"));
+        assert_eq!(answers.get("TIMEOUT_SECONDS"), Some("750ms"));
+        assert_eq!(answers.get("MAX_RUNTIME"), Some("90s"));
+        assert_eq!(answers.get("ENABLE_COLOR"), Some("false"));
+        assert!(output.contains("https://down.example is not reachable (connection refused)"));
+        assert!(output.contains("DoH provider must use HTTPS"));
+    }
+
+    #[test]
+    fn test_wizard_cancelled_on_end_of_input() {
+        let (answers, _) = run_wizard("https://api.example\n");
+        assert!(answers.is_err());
+    }
+}
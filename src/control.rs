@@ -0,0 +1,365 @@
+//! Control channel for a running watch-mode instance
+//!
+//! A `--watch` run listens on a local endpoint, a Unix domain socket or on
+//! Windows a named pipe, so that operators can manage a long-running probe
+//! without killing it. Each connection sends one [`ControlCommand`] as a line
+//! of text and receives one line back, `ok: <message>` or `error: <message>`.
+//! The `ctl` subcommand is the client side, see [`send`].
+//!
+//! A run can also be asked for a report snapshot with a signal, SIGUSR1 or
+//! Ctrl+Break on Windows, see [`SnapshotSignal`], for when a shell is at hand
+//! but the control endpoint is not.
+
+use crate::error::{AppError, Result};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+
+/// How long `ctl` waits for a running instance to answer
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest command line accepted from a client
+const MAX_COMMAND_LEN: u64 = 256;
+
+/// A command for a running watch-mode instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Stop starting new cycles; a cycle in progress finishes
+    Pause,
+    /// Start a cycle now and continue at the usual interval
+    Resume,
+    /// Report on every measurement since the last flush, then start over
+    FlushReport,
+    /// Move the log file aside and start a new one
+    RotateLog,
+    /// Report average latency per configuration over the last minute, 15 minutes, hour and day
+    Windows,
+}
+
+impl ControlCommand {
+    /// All commands, in the order they are documented
+    pub const ALL: [ControlCommand; 5] = [Self::Pause, Self::Resume, Self::FlushReport, Self::RotateLog, Self::Windows];
+}
+
+impl fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::FlushReport => "flush-report",
+            Self::RotateLog => "rotate-log",
+            Self::Windows => "windows",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ControlCommand {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::validation(format!("Unknown control command '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+/// A command received from a client, waiting for the instance to answer it
+#[derive(Debug)]
+pub struct ControlRequest {
+    /// The command to carry out
+    pub command: ControlCommand,
+    reply: oneshot::Sender<std::result::Result<String, String>>,
+}
+
+impl ControlRequest {
+    /// Send the outcome back to the client
+    pub fn reply(self, outcome: Result<String>) {
+        // The client may have given up waiting; there is nobody left to tell
+        let _ = self.reply.send(outcome.map_err(|e| e.message().to_string()));
+    }
+}
+
+/// Endpoint the control channel uses unless `--control-socket` names another
+///
+/// It is per user, so that instances run by different users do not collide.
+pub fn default_path() -> PathBuf {
+    #[cfg(windows)]
+    {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        PathBuf::from(format!(r"\\.\pipe\network-latency-tester-{}", user))
+    }
+    #[cfg(not(windows))]
+    {
+        match std::env::var_os("XDG_RUNTIME_DIR") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("network-latency-tester.sock"),
+            _ => {
+                let user = std::env::var("USER").unwrap_or_default();
+                std::env::temp_dir().join(format!("network-latency-tester-{}.sock", user))
+            }
+        }
+    }
+}
+
+/// Listens on the control endpoint and queues the commands it receives
+pub struct ControlServer {
+    path: PathBuf,
+    requests: mpsc::Receiver<ControlRequest>,
+    listener: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Start listening on `path`
+    ///
+    /// Fails if another instance is already listening there. A socket file left
+    /// behind by an instance that did not exit cleanly is replaced.
+    pub async fn bind(path: &Path) -> Result<Self> {
+        let (sender, requests) = mpsc::channel(8);
+        let listener = listen(path, sender).await?;
+        Ok(Self { path: path.to_path_buf(), requests, listener })
+    }
+
+    /// Endpoint the server listens on
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Wait for the next command
+    pub async fn recv(&mut self) -> Option<ControlRequest> {
+        self.requests.recv().await
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.listener.abort();
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+async fn listen(path: &Path, sender: mpsc::Sender<ControlRequest>) -> Result<JoinHandle<()>> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::net::{UnixListener, UnixStream};
+
+    let in_use = |e: std::io::Error| AppError::io(format!("Cannot listen on {}: {}", path.display(), e)).with_source(e);
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(AppError::io(format!("Another instance is already listening on {}", path.display())));
+        }
+        std::fs::remove_file(path).map_err(in_use)?;
+    }
+    // Only the user running the probe may control it. The socket is bound in a
+    // directory nobody else can enter and restricted before it is moved into
+    // place, so another user never gets to connect in between
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let private_dir = path.with_file_name(format!(".{}.{}", name, std::process::id()));
+    std::fs::DirBuilder::new().mode(0o700).create(&private_dir).map_err(in_use)?;
+    let staged = private_dir.join("control.sock");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&private_dir);
+    let listener = bound.map_err(in_use)?;
+
+    Ok(tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, sender.clone()));
+        }
+    }))
+}
+
+#[cfg(windows)]
+async fn listen(path: &Path, sender: mpsc::Sender<ControlRequest>) -> Result<JoinHandle<()>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = path.as_os_str().to_os_string();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)
+        .map_err(|e| AppError::io(format!("Cannot listen on {}: {}", path.display(), e)).with_source(e))?;
+
+    Ok(tokio::spawn(async move {
+        loop {
+            if server.connect().await.is_err() {
+                return;
+            }
+            // A new instance must exist before the connected one is handed off
+            let connected = server;
+            server = match ServerOptions::new().create(&name) {
+                Ok(next) => next,
+                Err(_) => return,
+            };
+            tokio::spawn(serve(connected, sender.clone()));
+        }
+    }))
+}
+
+/// Answer one client: read its command, queue it and write back the reply
+async fn serve<S>(stream: S, sender: mpsc::Sender<ControlRequest>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = String::new();
+    let mut reader = BufReader::new(reader.take(MAX_COMMAND_LEN));
+    if reader.read_line(&mut line).await.is_err() {
+        return;
+    }
+
+    let outcome = match line.parse::<ControlCommand>() {
+        Ok(command) => {
+            let (reply, answer) = oneshot::channel();
+            if sender.send(ControlRequest { command, reply }).await.is_err() {
+                Err("the instance is shutting down".to_string())
+            } else {
+                answer.await.unwrap_or_else(|_| Err("the instance is shutting down".to_string()))
+            }
+        }
+        Err(e) => Err(e.message().to_string()),
+    };
+
+    let response = match outcome {
+        Ok(message) => format!("ok: {}\n", message),
+        Err(message) => format!("error: {}\n", message),
+    };
+    let _ = writer.write_all(response.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+/// Send `command` to the instance listening on `path` and return its answer
+pub async fn send(path: &Path, command: ControlCommand) -> Result<String> {
+    let exchange = async {
+        let stream = connect(path).await?;
+        let (reader, mut writer) = tokio::io::split(stream);
+        writer.write_all(format!("{}\n", command).as_bytes()).await?;
+        let mut response = String::new();
+        BufReader::new(reader).read_line(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    };
+
+    let response = tokio::time::timeout(REPLY_TIMEOUT, exchange)
+        .await
+        .map_err(|_| AppError::timeout(format!("No answer from {} within {:?}", path.display(), REPLY_TIMEOUT)))?
+        .map_err(|e| {
+            AppError::io(format!("Cannot reach a watch-mode instance at {}: {}", path.display(), e)).with_source(e)
+        })?;
+
+    let response = response.trim_end();
+    match response.split_once(": ") {
+        Some(("ok", message)) => Ok(message.to_string()),
+        Some(("error", message)) => Err(AppError::validation(format!("{} failed: {}", command, message))),
+        _ => Err(AppError::io(format!("Unexpected answer from {}: '{}'", path.display(), response))),
+    }
+}
+
+#[cfg(unix)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(path.as_os_str())
+}
+
+/// The signal asking a running watch to export a report snapshot: SIGUSR1,
+/// or Ctrl+Break on Windows
+pub struct SnapshotSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+    #[cfg(windows)]
+    signal: tokio::signal::windows::CtrlBreak,
+}
+
+impl SnapshotSignal {
+    /// Name of the signal, for messages
+    #[cfg(unix)]
+    pub const NAME: &'static str = "SIGUSR1";
+    #[cfg(windows)]
+    pub const NAME: &'static str = "Ctrl+Break";
+
+    /// Start listening for the signal, replacing its default action
+    pub fn listen() -> Result<Self> {
+        #[cfg(unix)]
+        let signal = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1());
+        #[cfg(windows)]
+        let signal = tokio::signal::windows::ctrl_break();
+        let signal = signal.map_err(|e| AppError::io(format!("Cannot listen for {}: {}", Self::NAME, e)).with_source(e))?;
+        Ok(Self { signal })
+    }
+
+    /// Wait for the next signal
+    pub async fn recv(&mut self) -> Option<()> {
+        self.signal.recv().await
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_control_command_names() {
+        for command in ControlCommand::ALL {
+            assert_eq!(command.to_string().parse::<ControlCommand>().unwrap(), command);
+        }
+        assert_eq!(" Flush-Report\n".parse::<ControlCommand>().unwrap(), ControlCommand::FlushReport);
+        assert!("stop".parse::<ControlCommand>().unwrap_err().to_string().contains("pause, resume, flush-report, rotate-log"));
+    }
+
+    #[tokio::test]
+    async fn test_control_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        let mut server = ControlServer::bind(&path).await.unwrap();
+        assert!(ControlServer::bind(&path).await.err().unwrap().to_string().contains("already listening"));
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        // Nothing is left of the directory the socket was bound in
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let answering = tokio::spawn(async move {
+            let request = server.recv().await.unwrap();
+            assert_eq!(request.command, ControlCommand::Pause);
+            request.reply(Ok("paused".to_string()));
+            let request = server.recv().await.unwrap();
+            request.reply(Err(AppError::validation("no log file")));
+            server
+        });
+
+        assert_eq!(send(&path, ControlCommand::Pause).await.unwrap(), "paused");
+        let error = send(&path, ControlCommand::RotateLog).await.unwrap_err();
+        assert!(error.to_string().contains("rotate-log failed: no log file"), "{}", error);
+
+        drop(answering.await.unwrap());
+        assert!(!path.exists());
+        assert!(send(&path, ControlCommand::Resume).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_signal() {
+        let mut signal = SnapshotSignal::listen().unwrap();
+        let status = std::process::Command::new("kill")
+            .args(["-USR1", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(tokio::time::timeout(Duration::from_secs(5), signal.recv()).await.unwrap(), Some(()));
+    }
+}
@@ -1,13 +1,17 @@
 //! Network diagnostics and connectivity tests
 
 use crate::{
+    cancel::{cancellable, CancellationToken},
     error::{AppError, Result},
-    types::DnsConfig,
+    types::{DisplayNames, DnsConfig},
     models::metrics::{TimingMetrics, TestResult},
-    dns::{DnsManager, DnsPerformanceResult},
-    client::{HttpClient, NetworkClient, ConnectivityTest},
+    dns::{DnsManager, DnsPerformanceResult, DoHTiming},
+    client::{HttpClient, NetworkClient, ConnectivityTest, certificate::PeerCertificate},
     stats::{StatisticsEngine, StatisticalAnalysis},
+    traceroute::Traceroute,
 };
+// Latency breakdowns are computed by the statistics engine, which does not depend on diagnostics
+pub use crate::stats::LatencyBreakdown;
 use std::{
     net::{IpAddr, TcpStream, SocketAddr},
     time::{Duration, Instant},
@@ -18,11 +22,16 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use colored::*;
 
+/// Certificates expiring sooner than this are reported
+const CERTIFICATE_EXPIRY_WARNING_DAYS: i64 = 30;
+
 /// Comprehensive network diagnostics engine
 pub struct NetworkDiagnostics {
     dns_manager: Arc<DnsManager>,
     http_client: Arc<dyn HttpClient>,
     config: DiagnosticsConfig,
+    /// Stops the diagnostics between and within phases when cancelled
+    cancel: CancellationToken,
 }
 
 /// Configuration for diagnostic operations
@@ -42,6 +51,10 @@ pub struct DiagnosticsConfig {
     pub detailed_reporting: bool,
     /// Minimum sample size for reliable diagnostics
     pub min_sample_size: usize,
+    /// Names DNS configurations are reported under
+    pub display_names: DisplayNames,
+    /// Whether to trace the route to each target host
+    pub include_traceroute: bool,
 }
 
 /// Comprehensive diagnostic report
@@ -108,6 +121,12 @@ pub struct ConnectivityDiagnostics {
     pub network_interfaces: Vec<NetworkInterfaceInfo>,
     /// Routing table analysis
     pub routing_analysis: Option<RoutingAnalysis>,
+    /// Route to each target host, when traced
+    #[serde(default)]
+    pub traceroutes: Vec<Traceroute>,
+    /// Why hosts could not be traced
+    #[serde(default)]
+    pub traceroute_errors: Vec<String>,
 }
 
 /// DNS resolution diagnostic results
@@ -266,6 +285,9 @@ pub struct DoHProviderAnalysis {
     pub response_time: Option<Duration>,
     pub supports_json: bool,
     pub privacy_policy_score: Option<f64>,
+    /// Provider connection setup and query time, measured apart
+    #[serde(default)]
+    pub timing: Option<DoHTiming>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -316,15 +338,6 @@ pub struct PerformanceBottleneck {
     pub recommendations: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LatencyBreakdown {
-    pub dns_percentage: f64,
-    pub tcp_percentage: f64,
-    pub tls_percentage: f64,
-    pub request_percentage: f64,
-    pub total_average_ms: f64,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThroughputAnalysis {
     pub bytes_per_second: f64,
@@ -415,6 +428,8 @@ impl Default for DiagnosticsConfig {
             include_performance_analysis: true,
             detailed_reporting: true,
             min_sample_size: 5,
+            display_names: DisplayNames::default(),
+            include_traceroute: false,
         }
     }
 }
@@ -430,9 +445,15 @@ impl NetworkDiagnostics {
             dns_manager,
             http_client,
             config,
+            cancel: crate::cancel::global().child_token(),
         }
     }
 
+    /// Stop the diagnostics, which then fail as cancelled, once `token` is cancelled
+    pub fn with_cancellation(self, token: &CancellationToken) -> Self {
+        Self { cancel: token.child_token(), ..self }
+    }
+
     /// Create diagnostics with default configuration
     pub fn with_defaults(dns_manager: Arc<DnsManager>) -> Result<Self> {
         let http_client = Arc::new(NetworkClient::new(dns_manager.clone())?);
@@ -461,12 +482,12 @@ impl NetworkDiagnostics {
         let mut recommendations = Vec::new();
 
         // Run connectivity diagnostics
-        let connectivity_diagnostics = self.run_connectivity_diagnostics(targets).await?;
+        let connectivity_diagnostics = cancellable(&self.cancel, "diagnostics", self.run_connectivity_diagnostics(targets)).await?;
         self.analyze_connectivity_issues(&connectivity_diagnostics, &mut issues, &mut recommendations);
 
         // Run DNS diagnostics
         let dns_diagnostics = if self.config.include_dns_diagnostics {
-            self.run_dns_diagnostics(targets, dns_configs).await?
+            cancellable(&self.cancel, "diagnostics", self.run_dns_diagnostics(targets, dns_configs)).await?
         } else {
             execution_summary.tests_skipped += targets.len() * dns_configs.len();
             DnsDiagnostics::empty()
@@ -475,7 +496,7 @@ impl NetworkDiagnostics {
 
         // Run HTTP diagnostics
         let http_diagnostics = if self.config.include_http_diagnostics {
-            self.run_http_diagnostics(targets, dns_configs).await?
+            cancellable(&self.cancel, "diagnostics", self.run_http_diagnostics(targets, dns_configs)).await?
         } else {
             execution_summary.tests_skipped += targets.len() * dns_configs.len();
             HttpDiagnostics::empty()
@@ -484,7 +505,7 @@ impl NetworkDiagnostics {
 
         // Run performance analysis
         let performance_analysis = if self.config.include_performance_analysis {
-            self.run_performance_analysis(targets, dns_configs).await?
+            cancellable(&self.cancel, "diagnostics", self.run_performance_analysis(targets, dns_configs)).await?
         } else {
             PerformanceAnalysis::empty()
         };
@@ -542,12 +563,20 @@ impl NetworkDiagnostics {
         // Analyze routing (simplified)
         let routing_analysis = self.analyze_routing().await;
 
+        let (traceroutes, traceroute_errors) = if self.config.include_traceroute {
+            self.trace_targets(targets).await
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
         Ok(ConnectivityDiagnostics {
             internet_connectivity,
             target_reachability,
             port_connectivity,
             network_interfaces,
             routing_analysis,
+            traceroutes,
+            traceroute_errors,
         })
     }
 
@@ -560,28 +589,36 @@ impl NetworkDiagnostics {
 
         // Test each DNS configuration
         for dns_config in dns_configs {
-            let config_name = dns_config.name();
-
-            // Test DNS server accessibility
-            match dns_config {
-                DnsConfig::Custom { servers } => {
-                    for server in servers {
-                        let status = self.test_dns_server_accessibility(*server).await;
-                        dns_server_status.insert(server.to_string(), status);
-                    }
-                }
-                DnsConfig::DoH { url } => {
-                    let analysis = self.analyze_doh_provider(url).await;
-                    doh_analysis.insert(url.clone(), analysis);
-                }
-                DnsConfig::System => {
-                    // Test system DNS servers
-                    if let Ok(system_servers) = self.dns_manager.get_system_dns_servers() {
-                        for server in system_servers {
-                            let status = self.test_dns_server_accessibility(server).await;
+            let config_name = self.config.display_names.resolve(dns_config);
+
+            // Test DNS server accessibility, of every resolver of a chain
+            let resolvers = match dns_config {
+                DnsConfig::Chain { resolvers } => resolvers.as_slice(),
+                single => std::slice::from_ref(single),
+            };
+            for resolver in resolvers {
+                match resolver {
+                    DnsConfig::Custom { servers } => {
+                        for server in servers {
+                            let status = self.test_dns_server_accessibility(*server).await;
                             dns_server_status.insert(server.to_string(), status);
                         }
                     }
+                    DnsConfig::DoH { url } => {
+                        let analysis = self.analyze_doh_provider(url).await;
+                        doh_analysis.insert(url.clone(), analysis);
+                    }
+                    DnsConfig::System => {
+                        // Test system DNS servers
+                        if let Ok(system_servers) = self.dns_manager.get_system_dns_servers() {
+                            for server in system_servers {
+                                let status = self.test_dns_server_accessibility(server).await;
+                                dns_server_status.insert(server.to_string(), status);
+                            }
+                        }
+                    }
+                    // Chains do not nest, and mDNS has no server to check
+                    DnsConfig::Chain { .. } | DnsConfig::Mdns => {}
                 }
             }
 
@@ -604,6 +641,7 @@ impl NetworkDiagnostics {
                                     duration: Duration::from_secs(0),
                                     resolved_ips: Vec::new(),
                                     error: Some("DNS resolution failed".to_string()),
+                                    record_latencies: Vec::new(),
                                 },
                             );
                         }
@@ -634,7 +672,7 @@ impl NetworkDiagnostics {
 
         for target in targets {
             for dns_config in dns_configs {
-                let config_name = dns_config.name();
+                let config_name = self.config.display_names.resolve(dns_config);
                 let key = format!("{}:{}", config_name, target);
 
                 // Test HTTP connectivity
@@ -669,7 +707,7 @@ impl NetworkDiagnostics {
 
                 // Analyze SSL certificate for HTTPS URLs
                 if target.starts_with("https://") {
-                    let cert_analysis = self.analyze_ssl_certificate(target).await;
+                    let cert_analysis = self.analyze_ssl_certificate(target, dns_config).await;
                     certificate_analysis.insert(key.clone(), cert_analysis);
                 }
 
@@ -699,7 +737,7 @@ impl NetworkDiagnostics {
         // Collect performance measurements
         for target in targets {
             for dns_config in dns_configs {
-                let config_name = dns_config.name();
+                let config_name = self.config.display_names.resolve(dns_config);
 
                 // Perform multiple measurements for statistical significance
                 let mut test_result = TestResult::new(config_name.clone(), dns_config.clone(), target.clone());
@@ -909,6 +947,25 @@ impl NetworkDiagnostics {
         None
     }
 
+    /// Trace the route to each distinct target host at the same time, with
+    /// the errors of the hosts that could not be traced
+    async fn trace_targets(&self, targets: &[String]) -> (Vec<Traceroute>, Vec<String>) {
+        let mut hosts: Vec<String> = targets.iter()
+            .filter_map(|target| url::Url::parse(target).ok()?.host_str().map(str::to_string))
+            .collect();
+        hosts.sort();
+        hosts.dedup();
+        let mut traceroutes = Vec::new();
+        let mut errors = Vec::new();
+        for trace in futures::future::join_all(hosts.iter().map(|host| Traceroute::run(host))).await {
+            match trace {
+                Ok(trace) => traceroutes.push(trace),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        (traceroutes, errors)
+    }
+
     async fn test_dns_server_accessibility(&self, server: IpAddr) -> DnsServerStatus {
         // Test DNS server accessibility by attempting a simple query
         let dns_config = DnsConfig::Custom { servers: vec![server] };
@@ -944,6 +1001,12 @@ impl NetworkDiagnostics {
                 response_time: Some(result.duration),
                 supports_json: true, // Assume JSON support for DoH
                 privacy_policy_score: None, // Would need web scraping to analyze
+                timing: if result.success {
+                    let queries = crate::defaults::DEFAULT_DNS_BENCH_QUERIES as usize;
+                    self.dns_manager.benchmark_doh(url, "google.com", queries).await.ok()
+                } else {
+                    None
+                },
             },
             Err(_) => DoHProviderAnalysis {
                 provider_name,
@@ -952,6 +1015,7 @@ impl NetworkDiagnostics {
                 response_time: None,
                 supports_json: false,
                 privacy_policy_score: None,
+                timing: None,
             },
         }
     }
@@ -962,17 +1026,60 @@ impl NetworkDiagnostics {
         false
     }
 
-    async fn analyze_ssl_certificate(&self, _url: &str) -> CertificateAnalysis {
-        // This would require SSL/TLS certificate validation
-        // For now, return a placeholder analysis
+    async fn analyze_ssl_certificate(&self, url: &str, dns_config: &DnsConfig) -> CertificateAnalysis {
+        // Connect to the address this DNS configuration gives, which may be a
+        // different server with a different certificate
+        let address = match url::Url::parse(url) {
+            Ok(parsed) => match (parsed.host_str(), parsed.port_or_known_default()) {
+                (Some(host), Some(port)) => self.dns_manager.resolve(host, dns_config).await.ok()
+                    .and_then(|ips| ips.first().map(|ip| SocketAddr::new(*ip, port))),
+                _ => None,
+            },
+            Err(_) => None,
+        };
+
+        let timeout = self.config.connectivity_timeout;
+        let (certificate, verified) = tokio::join!(
+            PeerCertificate::fetch(url, address, timeout),
+            PeerCertificate::verify(url, address, timeout),
+        );
+        let certificate = match certificate {
+            Ok(certificate) => certificate,
+            Err(e) => return CertificateAnalysis {
+                valid: false,
+                issuer: "Unknown".to_string(),
+                subject: "Unknown".to_string(),
+                expiry_date: None,
+                days_until_expiry: None,
+                chain_valid: false,
+                security_issues: vec![e.display_chain()],
+            },
+        };
+
+        let days_until_expiry = certificate.days_until_expiry(Utc::now());
+        let mut security_issues = Vec::new();
+        // Read without verification, so a rejected certificate is still described
+        if let Err(ref e) = verified {
+            let reason = e.causes().pop().unwrap_or_else(|| e.to_string());
+            security_issues.push(format!("Certificate rejected: {}", reason));
+        }
+        if days_until_expiry < 0 {
+            security_issues.push(format!("Certificate expired {} days ago", -days_until_expiry));
+        } else if days_until_expiry < CERTIFICATE_EXPIRY_WARNING_DAYS {
+            security_issues.push(format!("Certificate expires in {} days", days_until_expiry));
+        }
+        let host = url::Url::parse(url).ok().and_then(|parsed| parsed.host_str().map(str::to_string));
+        if let Some(host) = host.filter(|host| !certificate.covers(host)) {
+            security_issues.push(format!("Certificate does not cover {}", host));
+        }
         CertificateAnalysis {
-            valid: true,
-            issuer: "Unknown".to_string(),
-            subject: "Unknown".to_string(),
-            expiry_date: None,
-            days_until_expiry: None,
-            chain_valid: true,
-            security_issues: Vec::new(),
+            valid: days_until_expiry >= 0 && verified.is_ok(),
+            issuer: certificate.issuer,
+            subject: certificate.subject,
+            expiry_date: Some(certificate.not_after),
+            days_until_expiry: Some(days_until_expiry),
+            chain_valid: verified.is_ok(),
+            security_issues,
         }
     }
 
@@ -1058,34 +1165,7 @@ impl NetworkDiagnostics {
     }
 
     fn calculate_latency_breakdown(&self, measurements: &[TimingMetrics]) -> LatencyBreakdown {
-        if measurements.is_empty() {
-            return LatencyBreakdown {
-                dns_percentage: 0.0,
-                tcp_percentage: 0.0,
-                tls_percentage: 0.0,
-                request_percentage: 0.0,
-                total_average_ms: 0.0,
-            };
-        }
-
-        let total_count = measurements.len() as f64;
-        let avg_dns = measurements.iter().map(|m| m.dns_ms()).sum::<f64>() / total_count;
-        let avg_tcp = measurements.iter().map(|m| m.tcp_ms()).sum::<f64>() / total_count;
-        let avg_tls = measurements.iter()
-            .map(|m| m.tls_ms().unwrap_or(0.0))
-            .sum::<f64>() / total_count;
-        let avg_first_byte = measurements.iter().map(|m| m.first_byte_ms()).sum::<f64>() / total_count;
-        let avg_total = measurements.iter().map(|m| m.total_ms()).sum::<f64>() / total_count;
-
-        let total_breakdown = avg_dns + avg_tcp + avg_tls + avg_first_byte;
-
-        LatencyBreakdown {
-            dns_percentage: if total_breakdown > 0.0 { (avg_dns / total_breakdown) * 100.0 } else { 0.0 },
-            tcp_percentage: if total_breakdown > 0.0 { (avg_tcp / total_breakdown) * 100.0 } else { 0.0 },
-            tls_percentage: if total_breakdown > 0.0 { (avg_tls / total_breakdown) * 100.0 } else { 0.0 },
-            request_percentage: if total_breakdown > 0.0 { (avg_first_byte / total_breakdown) * 100.0 } else { 0.0 },
-            total_average_ms: avg_total,
-        }
+        LatencyBreakdown::from_measurements(measurements)
     }
 
     fn analyze_performance_trends(&self, _measurements: &[TimingMetrics]) -> Vec<PerformanceTrend> {
@@ -1152,6 +1232,42 @@ impl NetworkDiagnostics {
                 related_metrics: HashMap::new(),
             });
         }
+
+        if !connectivity.traceroute_errors.is_empty() {
+            issues.push(DiagnosticIssue {
+                severity: IssueSeverity::Low,
+                category: IssueCategory::Configuration,
+                title: "Routes Not Traced".to_string(),
+                description: connectivity.traceroute_errors.join("; "),
+                affected_components: vec!["Traceroute".to_string()],
+                impact: "The report shows no route to the affected hosts".to_string(),
+                resolution_steps: vec![
+                    "Install traceroute (or tracepath) and run again".to_string(),
+                ],
+                related_metrics: HashMap::new(),
+            });
+        }
+
+        // Check where traced routes stop
+        for trace in connectivity.traceroutes.iter().filter(|trace| !trace.reached()) {
+            let last_hop = trace.hops.iter().rev().find_map(|hop| hop.address);
+            issues.push(DiagnosticIssue {
+                severity: IssueSeverity::Medium,
+                category: IssueCategory::Connectivity,
+                title: "Route Does Not Reach Target".to_string(),
+                description: match last_hop {
+                    Some(address) => format!("The route to {} stops answering after {}", trace.host, address),
+                    None => format!("No router on the route to {} answered", trace.host),
+                },
+                affected_components: vec![trace.host.clone()],
+                impact: "Packets may be dropped on the way, or the target ignores traceroute probes".to_string(),
+                resolution_steps: vec![
+                    "Share the traceroute with the network provider".to_string(),
+                    "Compare with a traceroute from another network".to_string(),
+                ],
+                related_metrics: HashMap::new(),
+            });
+        }
     }
 
     fn analyze_dns_issues(
@@ -1220,12 +1336,46 @@ impl NetworkDiagnostics {
 
     fn analyze_http_issues(
         &self,
-        _http: &HttpDiagnostics,
-        _issues: &mut [DiagnosticIssue],
-        _recommendations: &mut [Recommendation],
+        http: &HttpDiagnostics,
+        issues: &mut Vec<DiagnosticIssue>,
+        recommendations: &mut Vec<Recommendation>,
     ) {
-        // HTTP issue analysis would go here
-        // For now, skip as the implementation is already quite comprehensive
+        // Certificate problems, once per finding rather than per DNS configuration
+        let mut findings: Vec<(&String, &String)> = http.certificate_analysis
+            .iter()
+            .flat_map(|(key, analysis)| analysis.security_issues.iter().map(move |finding| (finding, key)))
+            .collect();
+        findings.sort();
+        findings.dedup_by_key(|(finding, _)| *finding);
+
+        for (finding, key) in &findings {
+            issues.push(DiagnosticIssue {
+                severity: IssueSeverity::High,
+                category: IssueCategory::Certificate,
+                title: "TLS Certificate Problem".to_string(),
+                description: finding.to_string(),
+                affected_components: vec![key.to_string()],
+                impact: "Clients will refuse the connection or warn users".to_string(),
+                resolution_steps: vec![
+                    "Check the certificate from another network".to_string(),
+                    "Report the certificate to the site operator".to_string(),
+                    "Look for a proxy or middlebox replacing certificates".to_string(),
+                ],
+                related_metrics: HashMap::new(),
+            });
+        }
+
+        if !findings.is_empty() {
+            recommendations.push(Recommendation {
+                priority: RecommendationPriority::High,
+                category: RecommendationCategory::Security,
+                title: "Resolve Certificate Problems".to_string(),
+                description: "Have the affected certificates renewed or corrected before clients start failing".to_string(),
+                expected_benefits: vec!["Connections that are trusted by every client".to_string()],
+                complexity: ImplementationComplexity::Simple,
+                estimated_time: "Depends on the site operator".to_string(),
+            });
+        }
     }
 
     fn analyze_performance_issues(
@@ -1421,7 +1571,7 @@ impl NetworkDiagnostics {
     /// URL parsing utility methods
     fn extract_host_from_url(&self, url: &str) -> Result<String> {
         let parsed = url::Url::parse(url)
-            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Invalid URL: {}", e)).with_source(e))?;
         
         parsed.host_str()
             .ok_or_else(|| AppError::validation("URL must have a host"))
@@ -1619,7 +1769,7 @@ impl DiagnosticReport {
     /// Export the report as JSON
     pub fn to_json(&self) -> Result<String> {
         serde_json::to_string_pretty(self)
-            .map_err(|e| AppError::io(format!("Failed to export report to JSON: {}", e)))
+            .map_err(|e| AppError::io(format!("Failed to export report to JSON: {}", e)).with_source(e))
     }
 }
 
@@ -1741,6 +1891,35 @@ mod tests {
         assert!(diagnostics.config.include_dns_diagnostics);
     }
 
+    #[cfg(feature = "doh")]
+    #[tokio::test]
+    async fn test_dns_diagnostics_against_a_local_doh_server() {
+        use crate::dns::mock_server::MockDnsServer;
+
+        let dns = MockDnsServer::new()
+            .with_record("app.test", vec!["192.0.2.1".parse().unwrap()])
+            .with_record("google.com", vec!["192.0.2.2".parse().unwrap()])
+            .start(None, Some("127.0.0.1:0".parse().unwrap()))
+            .await
+            .unwrap();
+        let url = dns.doh_url().unwrap();
+        let dns_manager = Arc::new(DnsManager::new().unwrap());
+        let diagnostics = NetworkDiagnostics::with_defaults(dns_manager).unwrap();
+
+        let targets = ["http://app.test/".to_string(), "http://missing.test/".to_string()];
+        let report = diagnostics.run_dns_diagnostics(&targets, &[DnsConfig::DoH { url: url.clone() }]).await.unwrap();
+        assert!(report.doh_analysis[&url].accessible);
+        assert!(report.doh_analysis[&url].timing.is_some());
+        let mut resolutions: Vec<_> = report.resolution_performance.iter()
+            .map(|(key, result)| (key.rsplit(':').next().unwrap().to_string(), result.success, result.resolved_ips.clone()))
+            .collect();
+        resolutions.sort();
+        assert_eq!(resolutions, [
+            ("app.test".to_string(), true, vec!["192.0.2.1".parse::<IpAddr>().unwrap()]),
+            ("missing.test".to_string(), false, Vec::new()),
+        ]);
+    }
+
     #[test]
     fn test_system_health_calculation() {
         let mut component_scores = HashMap::new();
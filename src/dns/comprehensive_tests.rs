@@ -135,8 +135,7 @@ mod dns_validation_tests {
         // Try to resolve localhost - should work on most systems
         let result = manager.resolve("localhost", &config).await;
         
-        if result.is_ok() {
-            let ips = result.unwrap();
+        if let Ok(ips) = result {
             // Should contain loopback address
             assert!(ips.iter().any(|ip| ip.is_loopback()));
         }
@@ -0,0 +1,147 @@
+//! Multicast DNS resolution of `.local` names
+//!
+//! NAS boxes, printers and other LAN services announce themselves over
+//! multicast DNS (RFC 6762) rather than through a DNS server. [`MdnsResolver`]
+//! sends a one-shot query to the mDNS group from an ordinary port, which
+//! responders answer directly to the sender (RFC 6762, section 6.7), so no
+//! port 5353 listener or responder daemon is needed on the test machine.
+//! Only the IPv4 group is queried; AAAA records are still asked for.
+
+use crate::{
+    error::{AppError, Result},
+    utils::url::is_mdns_name,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::atomic::{AtomicU16, Ordering},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use trust_dns_resolver::proto::{
+    op::{Message, MessageType, OpCode, Query},
+    rr::{Name, RecordType},
+};
+
+/// The IPv4 multicast DNS group and port
+pub const MDNS_GROUP: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+/// How long to wait for a responder; LAN devices answer within milliseconds when present
+const MDNS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Query IDs, so that answers to an earlier query are not taken for this one
+static NEXT_QUERY_ID: AtomicU16 = AtomicU16::new(1);
+
+/// Resolves `.local` names by asking the local network
+#[derive(Debug, Clone)]
+pub struct MdnsResolver {
+    /// Where queries are sent, the mDNS group unless a test points it elsewhere
+    group: SocketAddr,
+    timeout: Duration,
+}
+
+impl Default for MdnsResolver {
+    fn default() -> Self {
+        Self { group: MDNS_GROUP, timeout: MDNS_TIMEOUT }
+    }
+}
+
+impl MdnsResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve a `.local` name to the addresses its responder announces
+    pub async fn resolve(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        if !is_mdns_name(domain) {
+            return Err(AppError::dns_resolution(format!("mDNS only resolves .local names, not {}", domain)));
+        }
+        let failed = |e: std::io::Error| AppError::dns_resolution(format!("mDNS query for {} failed: {}", domain, e)).with_source(e);
+
+        let id = NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed);
+        let query = Self::query(domain, id)?;
+        let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)).await.map_err(failed)?;
+        socket.set_multicast_ttl_v4(255).map_err(failed)?;
+        socket.send_to(&query, self.group).await.map_err(failed)?;
+
+        let wanted = Name::from_ascii(domain).map_err(|e| AppError::dns_resolution(format!("Invalid mDNS name {}: {}", domain, e)))?;
+        let mut buffer = [0u8; 9000];
+        let answer = tokio::time::timeout(self.timeout, async {
+            loop {
+                let (len, _) = socket.recv_from(&mut buffer).await?;
+                // Responders may also send unrelated announcements; skip anything that does not answer
+                let Ok(message) = Message::from_vec(&buffer[..len]) else { continue };
+                if message.id() != id || message.message_type() != MessageType::Response {
+                    continue;
+                }
+                let ips: Vec<IpAddr> = message.answers().iter()
+                    .chain(message.additionals())
+                    .filter(|record| record.name() == &wanted)
+                    .filter_map(|record| record.data().and_then(|data| data.ip_addr()))
+                    .collect();
+                if !ips.is_empty() {
+                    return Ok::<_, std::io::Error>(ips);
+                }
+            }
+        })
+        .await;
+
+        match answer {
+            Ok(Ok(ips)) => Ok(ips),
+            Ok(Err(e)) => Err(failed(e)),
+            Err(_) => Err(AppError::dns_resolution(format!(
+                "No mDNS responder answered for {} within {:?}", domain, self.timeout
+            ))),
+        }
+    }
+
+    /// A query for the A and AAAA records of `domain`
+    fn query(domain: &str, id: u16) -> Result<Vec<u8>> {
+        let name = Name::from_ascii(domain).map_err(|e| AppError::dns_resolution(format!("Invalid mDNS name {}: {}", domain, e)))?;
+        let mut message = Message::new();
+        message.set_id(id)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(false);
+        message.add_query(Query::query(name.clone(), RecordType::A));
+        message.add_query(Query::query(name, RecordType::AAAA));
+        message.to_vec().map_err(|e| AppError::dns_resolution(format!("Failed to encode mDNS query for {}: {}", domain, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::rr::{rdata::A, RData, Record};
+
+    #[tokio::test]
+    async fn test_mdns_resolution_against_local_responder() {
+        // A responder that answers like a LAN device would, after an unrelated announcement
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let group = responder.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 512];
+            while let Ok((len, sender)) = responder.recv_from(&mut buffer).await {
+                let query = Message::from_vec(&buffer[..len]).unwrap();
+                assert_eq!(query.queries().len(), 2);
+                // Answers name the host fully qualified, in whatever case the device uses
+                let name = Name::from_ascii("NAS.local.").unwrap();
+
+                let mut announcement = Message::new();
+                announcement.set_id(0).set_message_type(MessageType::Response);
+                responder.send_to(&announcement.to_vec().unwrap(), sender).await.unwrap();
+
+                let mut response = Message::new();
+                response.set_id(query.id()).set_message_type(MessageType::Response);
+                response.add_answer(Record::from_rdata(name, 120, RData::A(A::new(192, 168, 1, 20))));
+                responder.send_to(&response.to_vec().unwrap(), sender).await.unwrap();
+            }
+        });
+
+        let resolver = MdnsResolver { group, timeout: Duration::from_secs(2) };
+        let ips = resolver.resolve("nas.local").await.unwrap();
+        assert_eq!(ips, ["192.168.1.20".parse::<IpAddr>().unwrap()]);
+
+        assert!(resolver.resolve("example.com").await.is_err());
+        let silent = MdnsResolver { group: "127.0.0.1:9".parse().unwrap(), timeout: Duration::from_millis(100) };
+        assert!(silent.resolve("printer.local").await.unwrap_err().to_string().contains("No mDNS responder"));
+    }
+}
@@ -0,0 +1,377 @@
+//! Local DNS and DoH server answering from a fixed table of records
+//!
+//! [`MockDnsServer`] listens on loopback (or any address it is given) and
+//! answers A and AAAA queries from the records it was built with, over plain
+//! UDP and over the JSON DoH API that DoH configurations use
+//! (`GET /dns-query?name=...&type=A`, served over plain HTTP). Names not in the
+//! table get NXDOMAIN, unless a `*` record answers every name. Tests point a
+//! DoH configuration at [`MockDnsHandle::doh_url`] to run the executor or the
+//! diagnostics without reaching a real resolver, and `serve-mock-dns` runs the
+//! same server for checking a resolver setup offline.
+//!
+//! Available to the crate's own tests and, for library users and the
+//! `serve-mock-dns` subcommand, behind the `mock` feature.
+
+use crate::error::{AppError, Result};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream, UdpSocket},
+    task::JoinHandle,
+};
+use trust_dns_resolver::proto::{
+    op::{Message, MessageType, ResponseCode},
+    rr::{
+        rdata::{A, AAAA},
+        Name, RData, Record, RecordType,
+    },
+};
+
+/// Time to live of every answer, in seconds
+const ANSWER_TTL: u32 = 60;
+/// Longest request line or header line a DoH client may send
+const MAX_LINE_LEN: usize = 8 * 1024;
+
+/// Records and answering behaviour of a mock DNS server
+#[derive(Debug, Clone, Default)]
+pub struct MockDnsServer {
+    /// Addresses by lowercase name without the trailing dot; `*` matches any name
+    records: HashMap<String, Vec<IpAddr>>,
+    /// Time to wait before answering each query
+    delay: Duration,
+}
+
+/// Outcome of a lookup in the record table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockAnswer {
+    /// The name exists; its addresses of the requested type, possibly none
+    Records(Vec<IpAddr>),
+    /// The name does not exist
+    NxDomain,
+}
+
+impl MockDnsServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer queries for `domain` with `ips`; `*` answers every name without a record of its own
+    pub fn with_record(mut self, domain: &str, ips: Vec<IpAddr>) -> Self {
+        self.records.entry(Self::key(domain)).or_default().extend(ips);
+        self
+    }
+
+    /// Wait `delay` before answering each query
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Parse a record given as `NAME=IP[,IP...]`, e.g. `app.test=192.0.2.1,2001:db8::1`
+    pub fn parse_record(value: &str) -> Result<(String, Vec<IpAddr>)> {
+        let invalid = |reason: String| AppError::validation(format!("Invalid mock DNS record '{}': {}", value, reason));
+        let (name, ips) = value.split_once('=').ok_or_else(|| invalid("expected NAME=IP[,IP...]".to_string()))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(invalid("the name is empty".to_string()));
+        }
+        let ips = ips.split(',')
+            .map(|ip| ip.trim().parse::<IpAddr>().map_err(|e| invalid(format!("'{}' is not an IP address: {}", ip.trim(), e))))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((name.to_string(), ips))
+    }
+
+    /// The answer to a query for `domain` and `record_type`
+    pub fn answer(&self, domain: &str, record_type: RecordType) -> MockAnswer {
+        let ips = match self.records.get(&Self::key(domain)).or_else(|| self.records.get("*")) {
+            Some(ips) => ips,
+            None => return MockAnswer::NxDomain,
+        };
+        let wanted = |ip: &&IpAddr| match record_type {
+            RecordType::A => ip.is_ipv4(),
+            RecordType::AAAA => ip.is_ipv6(),
+            _ => false,
+        };
+        MockAnswer::Records(ips.iter().filter(wanted).copied().collect())
+    }
+
+    /// Names with records, `*` included, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.records.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Serve DNS over UDP on `udp` and the JSON DoH API on `doh`, each when
+    /// given; port 0 picks a free port
+    pub async fn start(self, udp: Option<SocketAddr>, doh: Option<SocketAddr>) -> Result<MockDnsHandle> {
+        let failed = |address: SocketAddr, e: std::io::Error| {
+            AppError::network(format!("Failed to listen on {}: {}", address, e)).with_source(e)
+        };
+        let server = Arc::new(self);
+        let queries = Arc::new(AtomicU64::new(0));
+        let mut handle = MockDnsHandle { udp_addr: None, doh_addr: None, queries: queries.clone(), tasks: Vec::new() };
+
+        if let Some(address) = udp {
+            let socket = UdpSocket::bind(address).await.map_err(|e| failed(address, e))?;
+            handle.udp_addr = Some(socket.local_addr().map_err(|e| failed(address, e))?);
+            handle.tasks.push(tokio::spawn(serve_udp(socket, server.clone(), queries.clone())));
+        }
+        if let Some(address) = doh {
+            let listener = TcpListener::bind(address).await.map_err(|e| failed(address, e))?;
+            handle.doh_addr = Some(listener.local_addr().map_err(|e| failed(address, e))?);
+            handle.tasks.push(tokio::spawn(serve_doh(listener, server, queries)));
+        }
+        Ok(handle)
+    }
+
+    fn key(domain: &str) -> String {
+        domain.trim_end_matches('.').to_ascii_lowercase()
+    }
+}
+
+/// A running mock DNS server, stopped when dropped
+#[derive(Debug)]
+pub struct MockDnsHandle {
+    udp_addr: Option<SocketAddr>,
+    doh_addr: Option<SocketAddr>,
+    queries: Arc<AtomicU64>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockDnsHandle {
+    /// Address the UDP server listens on
+    pub fn udp_addr(&self) -> Option<SocketAddr> {
+        self.udp_addr
+    }
+
+    /// URL of the DoH endpoint, for a DoH configuration
+    pub fn doh_url(&self) -> Option<String> {
+        self.doh_addr.map(|address| format!("http://{}/dns-query", address))
+    }
+
+    /// Queries answered so far, over either transport, one per record type asked for
+    pub fn queries(&self) -> u64 {
+        self.queries.load(Ordering::Relaxed)
+    }
+
+    /// Serve until the listeners fail, which is normally never
+    pub async fn wait(&mut self) {
+        for task in &mut self.tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for MockDnsHandle {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Answer each datagram with a response to its questions
+async fn serve_udp(socket: UdpSocket, server: Arc<MockDnsServer>, queries: Arc<AtomicU64>) {
+    let socket = Arc::new(socket);
+    let mut buffer = [0u8; 4096];
+    while let Ok((len, sender)) = socket.recv_from(&mut buffer).await {
+        // Not a DNS query; a real server would drop it too
+        let Ok(request) = Message::from_vec(&buffer[..len]) else { continue };
+        if request.message_type() != MessageType::Query {
+            continue;
+        }
+        queries.fetch_add(request.queries().len() as u64, Ordering::Relaxed);
+        let (socket, server) = (socket.clone(), server.clone());
+        tokio::spawn(async move {
+            tokio::time::sleep(server.delay).await;
+            if let Ok(response) = wire_response(&server, &request).to_vec() {
+                let _ = socket.send_to(&response, sender).await;
+            }
+        });
+    }
+}
+
+/// A response to every question of `request`
+fn wire_response(server: &MockDnsServer, request: &Message) -> Message {
+    let mut response = Message::new();
+    response.set_id(request.id())
+        .set_message_type(MessageType::Response)
+        .set_op_code(request.op_code())
+        .set_recursion_desired(request.recursion_desired())
+        .set_recursion_available(true)
+        .set_authoritative(true);
+    response.add_queries(request.queries().iter().cloned());
+    for query in request.queries() {
+        match server.answer(&query.name().to_ascii(), query.query_type()) {
+            MockAnswer::Records(ips) => {
+                response.add_answers(ips.into_iter().map(|ip| record(query.name().clone(), ip)));
+            }
+            MockAnswer::NxDomain => {
+                response.set_response_code(ResponseCode::NXDomain);
+            }
+        }
+    }
+    response
+}
+
+fn record(name: Name, ip: IpAddr) -> Record {
+    let data = match ip {
+        IpAddr::V4(ip) => RData::A(A::from(ip)),
+        IpAddr::V6(ip) => RData::AAAA(AAAA::from(ip)),
+    };
+    Record::from_rdata(name, ANSWER_TTL, data)
+}
+
+/// Serve the JSON DoH API, keeping connections open between requests
+async fn serve_doh(listener: TcpListener, server: Arc<MockDnsServer>, queries: Arc<AtomicU64>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve_doh_connection(stream, server.clone(), queries.clone()));
+    }
+}
+
+async fn serve_doh_connection(stream: TcpStream, server: Arc<MockDnsServer>, queries: Arc<AtomicU64>) {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let Some(target) = read_request_target(&mut stream).await else { return };
+        let (status, body) = match doh_query(&target) {
+            Some((name, record_type)) => {
+                queries.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(server.delay).await;
+                ("200 OK", json_response(&server, &name, record_type))
+            }
+            None => ("400 Bad Request", r#"{"Status":1,"Comment":"expected GET /dns-query?name=NAME&type=A|AAAA"}"#.to_string()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/dns-json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        if stream.get_mut().write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// The target of the next request on the connection, its headers skipped;
+/// `None` once the client is gone or sent something too long
+async fn read_request_target(stream: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.ok()? == 0 || request_line.len() > MAX_LINE_LEN {
+        return None;
+    }
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stream.read_line(&mut line).await.ok()? == 0 || line.len() > MAX_LINE_LEN {
+            return None;
+        }
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+    }
+    request_line.split_whitespace().nth(1).map(str::to_string)
+}
+
+/// Name and record type asked for by a DoH request target
+fn doh_query(target: &str) -> Option<(String, RecordType)> {
+    let url = url::Url::parse(&format!("http://mock{}", target)).ok()?;
+    if url.path() != "/dns-query" {
+        return None;
+    }
+    let parameter = |key: &str| url.query_pairs().find(|(name, _)| name == key).map(|(_, value)| value.into_owned());
+    let record_type = match parameter("type").as_deref().unwrap_or("A").to_ascii_uppercase().as_str() {
+        "A" | "1" => RecordType::A,
+        "AAAA" | "28" => RecordType::AAAA,
+        _ => return None,
+    };
+    Some((parameter("name")?, record_type))
+}
+
+/// The answer to a DoH query, in the JSON format of Google and Cloudflare
+fn json_response(server: &MockDnsServer, name: &str, record_type: RecordType) -> String {
+    let question = serde_json::json!([{ "name": name, "type": u16::from(record_type) }]);
+    let response = match server.answer(name, record_type) {
+        MockAnswer::Records(ips) => serde_json::json!({
+            "Status": 0,
+            "Question": question,
+            "Answer": ips.iter().map(|ip| serde_json::json!({
+                "name": name,
+                "type": u16::from(record_type),
+                "TTL": ANSWER_TTL,
+                "data": ip.to_string(),
+            })).collect::<Vec<_>>(),
+        }),
+        MockAnswer::NxDomain => serde_json::json!({ "Status": 3, "Question": question }),
+    };
+    response.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::op::Query;
+
+    fn server() -> MockDnsServer {
+        MockDnsServer::new()
+            .with_record("app.test", vec!["192.0.2.1".parse().unwrap(), "2001:db8::1".parse().unwrap()])
+            .with_record("v4.test", vec!["192.0.2.4".parse().unwrap()])
+    }
+
+    #[test]
+    fn test_mock_answers() {
+        let server = server();
+        assert_eq!(server.answer("APP.test.", RecordType::A), MockAnswer::Records(vec!["192.0.2.1".parse().unwrap()]));
+        assert_eq!(server.answer("app.test", RecordType::AAAA), MockAnswer::Records(vec!["2001:db8::1".parse().unwrap()]));
+        assert_eq!(server.answer("v4.test", RecordType::AAAA), MockAnswer::Records(Vec::new()));
+        assert_eq!(server.answer("other.test", RecordType::A), MockAnswer::NxDomain);
+
+        let server = server.with_record("*", vec!["127.0.0.1".parse().unwrap()]);
+        assert_eq!(server.answer("other.test", RecordType::A), MockAnswer::Records(vec!["127.0.0.1".parse().unwrap()]));
+        assert_eq!(server.names(), ["*", "app.test", "v4.test"]);
+
+        let (name, ips) = MockDnsServer::parse_record("app.test=192.0.2.1, 2001:db8::1").unwrap();
+        assert_eq!((name.as_str(), ips.len()), ("app.test", 2));
+        assert!(MockDnsServer::parse_record("app.test").is_err());
+        assert!(MockDnsServer::parse_record("=192.0.2.1").is_err());
+        assert!(MockDnsServer::parse_record("app.test=nowhere").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_server_over_udp_and_doh() {
+        let loopback: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let handle = server().start(Some(loopback), Some(loopback)).await.unwrap();
+
+        let client = UdpSocket::bind(loopback).await.unwrap();
+        for (name, code, answers) in [("app.test.", ResponseCode::NoError, 1), ("missing.test.", ResponseCode::NXDomain, 0)] {
+            let mut query = Message::new();
+            query.set_id(7).add_query(Query::query(Name::from_ascii(name).unwrap(), RecordType::A));
+            client.send_to(&query.to_vec().unwrap(), handle.udp_addr().unwrap()).await.unwrap();
+            let mut buffer = [0u8; 512];
+            let (len, _) = client.recv_from(&mut buffer).await.unwrap();
+            let response = Message::from_vec(&buffer[..len]).unwrap();
+            assert_eq!((response.id(), response.response_code(), response.answers().len()), (7, code, answers), "{}", name);
+        }
+
+        let url = handle.doh_url().unwrap();
+        let http = reqwest::Client::new();
+        let body: serde_json::Value = http.get(&url).query(&[("name", "app.test"), ("type", "AAAA")])
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["Answer"][0]["data"], "2001:db8::1");
+        let body: serde_json::Value = http.get(&url).query(&[("name", "missing.test")])
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["Status"], 3);
+        let response = http.get(url.replace("/dns-query", "/other")).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(handle.queries(), 4);
+    }
+}
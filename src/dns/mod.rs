@@ -1,24 +1,53 @@
 //! DNS configuration and resolution management
-
+//!
+//! Custom and DoH configurations resolve through resolvers built here from
+//! their own servers; the system DNS configuration is only ever read, through
+//! [`system`].
+
+pub mod mdns;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_server;
 pub mod platform;
+pub mod system;
 
+use self::{mdns::MdnsResolver, system::SearchPolicy};
 use crate::{
+    cancel::{cancellable, CancellationToken},
     error::{AppError, Result},
-    types::DnsConfig,
+    types::{DnsConfig, DohBootstrap, ResolverOptions, ServerStrategy},
+    utils::url::{ascii_domain, is_mdns_name},
 };
 use std::{
-    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    net::{IpAddr, SocketAddr},
     time::Duration,
 };
 use trust_dns_resolver::{
     config::{ResolverConfig, ResolverOpts, NameServerConfig, Protocol},
-    system_conf,
     TokioAsyncResolver,
 };
+#[cfg(feature = "doh")]
 use reqwest::Client;
+#[cfg(feature = "doh")]
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
+use async_trait::async_trait;
+
+/// How long a connection to a DoH provider stays open between queries
+#[cfg(feature = "doh")]
+const DOH_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Longest a DoH query for one record type may take, independent of the other's
+#[cfg(feature = "doh")]
+const DOH_RECORD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Name resolution for a DNS configuration, abstracted so resolvers can be swapped out in tests
+#[async_trait]
+pub trait NameResolver: Send + Sync {
+    /// Resolve a domain name using the specified DNS configuration
+    async fn resolve(&self, domain: &str, dns_config: &DnsConfig) -> Result<Vec<IpAddr>>;
+}
 
 /// DNS configuration manager that handles different DNS resolution strategies
 pub struct DnsManager {
@@ -27,31 +56,151 @@ pub struct DnsManager {
     /// Custom resolvers for different configurations
     custom_resolvers: Arc<RwLock<std::collections::HashMap<String, TokioAsyncResolver>>>,
     /// HTTP client for DoH requests
+    #[cfg(feature = "doh")]
     http_client: Client,
+    /// Whether every DoH query opens a new connection to the provider
+    fresh_doh_connections: bool,
+    /// DoH provider hosts resolved up front, whose queries skip the lookup
+    doh_bootstrap: Vec<BootstrapLookup>,
+    /// Resolver that replaces the built-in ones, if injected
+    injected_resolver: Option<Arc<dyn NameResolver>>,
+    /// Which resolver answered the lookups of each resolver chain, by chain id
+    failover: std::sync::Mutex<std::collections::BTreeMap<String, (DnsConfig, ResolverFailover)>>,
+    /// How short names are qualified, if they are
+    search: Option<SearchPolicy>,
+    /// Resolver settings replacing the defaults, by configuration id
+    resolver_options: std::collections::BTreeMap<String, ResolverOptions>,
+    /// How the servers of configurations with a strategy answered, by configuration id
+    distribution: std::sync::Mutex<std::collections::BTreeMap<String, ServerRotation>>,
+    /// Stops lookups in flight when cancelled
+    cancel: CancellationToken,
+}
+
+/// Lookups spread over the servers of one configuration, and where the next starts
+struct ServerRotation {
+    config: DnsConfig,
+    distribution: ServerDistribution,
+    next: usize,
 }
 
 impl DnsManager {
     /// Create a new DNS manager
     pub fn new() -> Result<Self> {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .user_agent("network-latency-tester/0.1.0")
-            .build()
-            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)))?;
+        #[cfg(feature = "doh")]
+        let http_client = Self::doh_http_client(false, &[])?;
 
         Ok(Self {
             system_resolver: Arc::new(RwLock::new(None)),
             custom_resolvers: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            #[cfg(feature = "doh")]
             http_client,
+            fresh_doh_connections: false,
+            doh_bootstrap: Vec::new(),
+            injected_resolver: None,
+            failover: Default::default(),
+            search: None,
+            resolver_options: std::collections::BTreeMap::new(),
+            distribution: Default::default(),
+            cancel: crate::cancel::global().child_token(),
+        })
+    }
+
+    /// Stop lookups in flight, which then fail as cancelled, once `token` is cancelled
+    pub fn with_cancellation(self, token: &CancellationToken) -> Self {
+        Self { cancel: token.child_token(), ..self }
+    }
+
+    /// Open a new connection to the DoH provider for every query instead of
+    /// reusing the one the previous query left open
+    pub fn with_fresh_doh_connections(self, fresh: bool) -> Result<Self> {
+        Ok(Self {
+            #[cfg(feature = "doh")]
+            http_client: Self::doh_http_client(fresh, &self.doh_bootstrap)?,
+            fresh_doh_connections: fresh,
+            ..self
+        })
+    }
+
+    /// Resolve the host names of DoH providers before any query, as
+    /// `bootstrap` says, so that no timed query includes a lookup of its provider
+    pub async fn with_doh_bootstrap(self, bootstrap: &std::collections::BTreeMap<String, DohBootstrap>) -> Result<Self> {
+        let mut lookups = Vec::with_capacity(bootstrap.len());
+        for (host, via) in bootstrap {
+            lookups.push(BootstrapLookup::run(host, *via).await?);
+        }
+        Ok(Self {
+            #[cfg(feature = "doh")]
+            http_client: Self::doh_http_client(self.fresh_doh_connections, &lookups)?,
+            doh_bootstrap: lookups,
+            ..self
+        })
+    }
+
+    /// DoH provider hosts resolved up front
+    pub fn doh_bootstrap(&self) -> &[BootstrapLookup] {
+        &self.doh_bootstrap
+    }
+
+    /// Whether every DoH query opens a new connection to the provider
+    pub fn fresh_doh_connections(&self) -> bool {
+        self.fresh_doh_connections
+    }
+
+    /// Qualify short names with `policy`'s search domains under every
+    /// configuration, not only the ones that read the system's
+    pub fn with_search_policy(self, policy: SearchPolicy) -> Self {
+        Self { search: Some(policy), ..self }
+    }
+
+    /// Use `options` instead of the default resolver settings of the
+    /// configurations they are keyed by; resolvers already created keep theirs
+    pub fn with_resolver_options(self, options: std::collections::BTreeMap<String, ResolverOptions>) -> Self {
+        Self { resolver_options: options, ..self }
+    }
+
+    /// The settings `dns_config` uses instead of the resolver defaults, if any
+    pub fn resolver_options(&self, dns_config: &DnsConfig) -> Option<&ResolverOptions> {
+        self.resolver_options.get(&dns_config.id())
+    }
+
+    /// `defaults` with the settings given for `dns_config` applied
+    fn resolver_opts(&self, dns_config: &DnsConfig, mut defaults: ResolverOpts) -> ResolverOpts {
+        if let Some(options) = self.resolver_options(dns_config) {
+            if let Some(timeout) = options.timeout {
+                defaults.timeout = timeout;
+            }
+            if let Some(attempts) = options.attempts {
+                defaults.attempts = attempts;
+            }
+            // ResolverOpts::rotate is not implemented; shuffling the servers is what rotate does
+            if options.rotate {
+                defaults.shuffle_dns_servers = true;
+            }
+            if let Some(cache_size) = options.cache_size {
+                defaults.cache_size = cache_size;
+            }
+        }
+        defaults
+    }
+
+    /// How short names are qualified, if a search policy is set
+    pub fn search_policy(&self) -> Option<&SearchPolicy> {
+        self.search.as_ref()
+    }
+
+    /// Create a DNS manager that answers every lookup through the given resolver
+    pub fn with_resolver(resolver: Arc<dyn NameResolver>) -> Result<Self> {
+        Ok(Self {
+            injected_resolver: Some(resolver),
+            ..Self::new()?
         })
     }
 
     /// Initialize the system DNS resolver
     pub async fn initialize_system_resolver(&self) -> Result<()> {
-        let (config, opts) = system_conf::read_system_conf()
-            .map_err(|e| AppError::dns_resolution(format!("Failed to read system DNS config: {}", e)))?;
+        let (config, opts) = system::read_system_config()?;
         
-        let resolver = TokioAsyncResolver::tokio(config, opts);
+        let resolver = TokioAsyncResolver::tokio(config, self.resolver_opts(&DnsConfig::System, opts));
         
         let mut system_resolver = self.system_resolver.write().await;
         *system_resolver = Some(resolver);
@@ -65,25 +214,8 @@ impl DnsManager {
             return Err(AppError::validation("No DNS servers provided"));
         }
 
-        let mut config = ResolverConfig::new();
-        
-        // Add each server to the configuration
-        for &server in servers {
-            let socket_addr = match server {
-                IpAddr::V4(ipv4) => SocketAddr::new(IpAddr::V4(ipv4), 53),
-                IpAddr::V6(ipv6) => SocketAddr::new(IpAddr::V6(ipv6), 53),
-            };
-            
-            let name_server = NameServerConfig::new(socket_addr, Protocol::Udp);
-            config.add_name_server(name_server);
-            
-            // Also add TCP fallback
-            let tcp_name_server = NameServerConfig::new(socket_addr, Protocol::Tcp);
-            config.add_name_server(tcp_name_server);
-        }
-
-        let opts = ResolverOpts::default();
-        let resolver = TokioAsyncResolver::tokio(config, opts);
+        let dns_config = DnsConfig::Custom { servers: servers.to_vec() };
+        let resolver = TokioAsyncResolver::tokio(custom_resolver_config(servers), self.resolver_opts(&dns_config, ResolverOpts::default()));
         
         Ok(resolver)
     }
@@ -92,6 +224,9 @@ impl DnsManager {
     pub async fn get_resolver(&self, dns_config: &DnsConfig) -> Result<DnsResolver> {
         match dns_config {
             DnsConfig::System => {
+                if self.system_resolver.read().await.is_none() {
+                    self.initialize_system_resolver().await?;
+                }
                 let system_resolver = self.system_resolver.read().await;
                 if let Some(resolver) = system_resolver.as_ref() {
                     Ok(DnsResolver::System(resolver.clone()))
@@ -111,30 +246,298 @@ impl DnsManager {
                     Ok(DnsResolver::Custom(resolver))
                 }
             }
+            #[cfg(feature = "doh")]
             DnsConfig::DoH { url } => {
                 Ok(DnsResolver::DoH(DoHClient::new(url.clone(), self.http_client.clone())))
             }
+            #[cfg(not(feature = "doh"))]
+            DnsConfig::DoH { .. } => {
+                Err(AppError::config("DNS-over-HTTPS is not available: this build was compiled without the `doh` feature"))
+            }
+            DnsConfig::Chain { .. } => {
+                Err(AppError::config("A resolver chain has no single resolver; resolve it through the DNS manager, and do not nest chains"))
+            }
+            DnsConfig::Mdns => Ok(DnsResolver::Mdns(MdnsResolver::new())),
         }
     }
 
     /// Resolve a domain name using the specified DNS configuration
+    ///
+    /// Internationalized names are queried in their punycode form.
     pub async fn resolve(&self, domain: &str, dns_config: &DnsConfig) -> Result<Vec<IpAddr>> {
+        self.resolve_timed(domain, dns_config).await.map(|(ips, _)| ips)
+    }
+
+    /// Resolve a domain name, with the latency of each record type where the
+    /// resolver queries them separately
+    pub async fn resolve_timed(&self, domain: &str, dns_config: &DnsConfig) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let domain = ascii_domain(domain)?;
+        cancellable(&self.cancel, "DNS lookup", async {
+            match &self.search {
+                Some(policy) if policy.is_short(&domain) => self.resolve_search(&domain, dns_config, policy).await,
+                _ => self.resolve_name(&domain, dns_config).await,
+            }
+        }).await
+    }
+
+    /// Try the fully qualified names a search policy gives for a short name, in order
+    async fn resolve_search(
+        &self,
+        domain: &str,
+        dns_config: &DnsConfig,
+        policy: &SearchPolicy,
+    ) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let mut errors = Vec::new();
+        for candidate in policy.candidates(domain) {
+            match self.resolve_name(&candidate, dns_config).await {
+                Ok(answer) => return Ok(answer),
+                Err(e) => errors.push(format!("{}: {}", candidate, e)),
+            }
+        }
+        Err(AppError::dns_resolution(format!(
+            "{} did not resolve with any search domain ({})", domain, errors.join("; ")
+        )))
+    }
+
+    /// Resolve a name exactly as given
+    async fn resolve_name(&self, domain: &str, dns_config: &DnsConfig) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        match dns_config {
+            DnsConfig::Chain { resolvers } => self.resolve_chain(domain, dns_config, resolvers).await,
+            // Like a host with nss-mdns: .local names go to the LAN, everything else to DNS
+            DnsConfig::Mdns if !is_mdns_name(domain) => self.resolve_single(domain, &DnsConfig::System).await,
+            _ => self.resolve_single(domain, dns_config).await,
+        }
+    }
+
+    /// Resolve through one resolver, the injected one if there is
+    async fn resolve_single(&self, domain: &str, dns_config: &DnsConfig) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        if let DnsConfig::Custom { servers } = dns_config {
+            let strategy = self.resolver_options(dns_config).and_then(|options| options.strategy);
+            if let Some(strategy) = strategy.filter(|_| servers.len() > 1) {
+                return self.resolve_distributed(domain, dns_config, servers, strategy).await;
+            }
+        }
+        if let Some(resolver) = &self.injected_resolver {
+            return Ok((resolver.resolve(domain, dns_config).await?, Vec::new()));
+        }
+
         let resolver = self.get_resolver(dns_config).await?;
-        resolver.resolve(domain).await
+        resolver.resolve_timed(domain).await
+    }
+
+    /// Try the resolvers of a chain in order until one answers, counting which did
+    async fn resolve_chain(
+        &self,
+        domain: &str,
+        chain: &DnsConfig,
+        resolvers: &[DnsConfig],
+    ) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let mut errors = Vec::new();
+        let mut answer = None;
+        for (position, resolver) in resolvers.iter().enumerate() {
+            match self.resolve_single(domain, resolver).await {
+                Ok(resolved) => {
+                    answer = Some((position, resolved));
+                    break;
+                }
+                Err(e) => errors.push(format!("{}: {}", resolver.id(), e)),
+            }
+        }
+
+        self.failover.lock().unwrap()
+            .entry(chain.id())
+            .or_insert_with(|| (chain.clone(), ResolverFailover::default()))
+            .1
+            .record(resolvers.len(), answer.as_ref().map(|(position, _)| *position));
+        answer.map(|(_, resolved)| resolved).ok_or_else(|| AppError::dns_resolution(format!(
+            "Every resolver of the chain failed to resolve {} ({})", domain, errors.join("; ")
+        )))
+    }
+
+    /// Which resolver answered the lookups of each resolver chain so far
+    pub fn resolver_failover(&self) -> Vec<(DnsConfig, ResolverFailover)> {
+        self.failover.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Spread a lookup over the servers of a configuration as `strategy`
+    /// says, timing every server asked
+    async fn resolve_distributed(
+        &self,
+        domain: &str,
+        group: &DnsConfig,
+        servers: &[IpAddr],
+        strategy: ServerStrategy,
+    ) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let start = {
+            let mut distribution = self.distribution.lock().unwrap();
+            let rotation = distribution.entry(group.id()).or_insert_with(|| ServerRotation {
+                config: group.clone(),
+                distribution: ServerDistribution::new(strategy, servers),
+                next: 0,
+            });
+            rotation.next += 1;
+            match strategy {
+                ServerStrategy::RoundRobin => (rotation.next - 1) % servers.len(),
+                // A fresh RandomState is seeded differently every time
+                ServerStrategy::Random => {
+                    std::hash::BuildHasher::hash_one(&std::collections::hash_map::RandomState::new(), rotation.next) as usize % servers.len()
+                }
+                ServerStrategy::First | ServerStrategy::RaceAll => 0,
+            }
+        };
+        let order: Vec<usize> = (0..servers.len()).map(|offset| (start + offset) % servers.len()).collect();
+        let tally = |update: &dyn Fn(&mut ServerDistribution)| {
+            if let Some(rotation) = self.distribution.lock().unwrap().get_mut(&group.id()) {
+                update(&mut rotation.distribution);
+            }
+        };
+        let ask = |position: usize| tally(&|distribution| distribution.ask(position));
+        let record = |position: usize, outcome: ServerOutcome| tally(&|distribution| distribution.record(position, outcome));
+
+        let mut errors = Vec::new();
+        if strategy == ServerStrategy::RaceAll {
+            order.iter().for_each(|&position| ask(position));
+            let mut queries: futures::stream::FuturesUnordered<_> = order.iter().map(|&position| async move {
+                let started = std::time::Instant::now();
+                (position, self.resolve_member(domain, group, servers[position]).await, started.elapsed())
+            }).collect();
+            while let Some((position, answer, elapsed)) = futures::StreamExt::next(&mut queries).await {
+                match answer {
+                    Ok(resolved) => {
+                        record(position, ServerOutcome::Answered(elapsed));
+                        return Ok(resolved);
+                    }
+                    Err(e) => {
+                        record(position, ServerOutcome::Failed);
+                        errors.push(format!("{}: {}", servers[position], e));
+                    }
+                }
+            }
+        } else {
+            for position in order {
+                ask(position);
+                let started = std::time::Instant::now();
+                match self.resolve_member(domain, group, servers[position]).await {
+                    Ok(resolved) => {
+                        record(position, ServerOutcome::Answered(started.elapsed()));
+                        return Ok(resolved);
+                    }
+                    Err(e) => {
+                        record(position, ServerOutcome::Failed);
+                        errors.push(format!("{}: {}", servers[position], e));
+                    }
+                }
+            }
+        }
+
+        tally(&|distribution| distribution.failed += 1);
+        Err(AppError::dns_resolution(format!(
+            "No server of {} resolved {} ({})", group.id(), domain, errors.join("; ")
+        )))
+    }
+
+    /// Resolve through one server of a configuration, with the configuration's settings
+    async fn resolve_member(&self, domain: &str, group: &DnsConfig, server: IpAddr) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let member = DnsConfig::Custom { servers: vec![server] };
+        if let Some(resolver) = &self.injected_resolver {
+            return Ok((resolver.resolve(domain, &member).await?, Vec::new()));
+        }
+
+        let cache_key = format!("{}@{}", server, group.id());
+        let resolver = {
+            let mut custom_resolvers = self.custom_resolvers.write().await;
+            custom_resolvers.entry(cache_key)
+                .or_insert_with(|| TokioAsyncResolver::tokio(
+                    custom_resolver_config(&[server]),
+                    self.resolver_opts(group, ResolverOpts::default()),
+                ))
+                .clone()
+        };
+        DnsResolver::Custom(resolver).resolve_timed(domain).await
+    }
+
+    /// How the servers of each configuration with a strategy answered so far
+    pub fn server_distribution(&self) -> Vec<(DnsConfig, ServerDistribution)> {
+        self.distribution.lock().unwrap().values()
+            .map(|rotation| (rotation.config.clone(), rotation.distribution.clone()))
+            .collect()
+    }
+
+    /// HTTP client DoH queries are sent with
+    ///
+    /// All resolvers share its connection pool, so queries to a provider reuse
+    /// the connection an earlier one opened unless `fresh_connections` is set.
+    /// Hosts in `bootstrap` connect to their looked-up address directly.
+    #[cfg(feature = "doh")]
+    fn doh_http_client(fresh_connections: bool, bootstrap: &[BootstrapLookup]) -> Result<Client> {
+        let builder = bootstrap.iter().fold(Client::builder(), |builder, lookup| {
+            // Port 0 keeps the port of the URL
+            builder.resolve(&lookup.host, SocketAddr::new(lookup.address, 0))
+        });
+        let builder = builder
+            .timeout(Duration::from_secs(10))
+            .user_agent("network-latency-tester/0.1.0");
+        let builder = if fresh_connections {
+            builder.pool_max_idle_per_host(0)
+        } else {
+            builder.pool_idle_timeout(DOH_IDLE_TIMEOUT).tcp_keepalive(DOH_IDLE_TIMEOUT)
+        };
+        builder.build()
+            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)).with_source(e))
+    }
+
+    /// Split the time of DoH queries to `url` into connection setup and query time
+    ///
+    /// Looks up the provider's host first, unless it was bootstrapped, then
+    /// sends one A query for `domain` over a new connection to the provider
+    /// and `queries` more over the same connection. The first includes TCP
+    /// and TLS setup, the later ones only the query.
+    pub async fn benchmark_doh(&self, url: &str, domain: &str, queries: usize) -> Result<DoHTiming> {
+        #[cfg(feature = "doh")]
+        {
+            let domain = ascii_domain(domain)?;
+            let host = DohBootstrap::provider_host(url)
+                .ok_or_else(|| AppError::validation(format!("Invalid DoH URL {}", url)))?;
+            let bootstrap = match self.doh_bootstrap.iter().find(|lookup| lookup.host == host) {
+                Some(lookup) => lookup.clone(),
+                None => BootstrapLookup::run(&host, DohBootstrap::System).await?,
+            };
+            // A client of its own, so no earlier query has warmed up the connection
+            let client = DoHClient::new(url.to_string(), Self::doh_http_client(false, std::slice::from_ref(&bootstrap))?);
+            let start = std::time::Instant::now();
+            client.query_record(&domain, "A").await?;
+            let first_query = start.elapsed();
+
+            let mut warm = Vec::with_capacity(queries);
+            for _ in 0..queries {
+                let start = std::time::Instant::now();
+                client.query_record(&domain, "A").await?;
+                warm.push(start.elapsed());
+            }
+            DoHTiming::from_samples(first_query, warm)
+                .map(|timing| timing.with_bootstrap(bootstrap))
+                .ok_or_else(|| AppError::validation("At least one query over the established connection is needed"))
+        }
+        #[cfg(not(feature = "doh"))]
+        {
+            let _ = (url, domain, queries);
+            Err(AppError::config("DNS-over-HTTPS is not available: this build was compiled without the `doh` feature"))
+        }
     }
 
     /// Test DNS resolution performance
     pub async fn test_resolution_performance(&self, domain: &str, dns_config: &DnsConfig) -> Result<DnsPerformanceResult> {
         let start_time = std::time::Instant::now();
         
-        match self.resolve(domain, dns_config).await {
-            Ok(ips) => {
+        match self.resolve_timed(domain, dns_config).await {
+            Ok((ips, record_latencies)) => {
                 let duration = start_time.elapsed();
                 Ok(DnsPerformanceResult {
                     success: true,
                     duration,
                     resolved_ips: ips,
                     error: None,
+                    record_latencies,
                 })
             }
             Err(e) => {
@@ -144,6 +547,7 @@ impl DnsManager {
                     duration,
                     resolved_ips: Vec::new(),
                     error: Some(e.to_string()),
+                    record_latencies: Vec::new(),
                 })
             }
         }
@@ -151,8 +555,7 @@ impl DnsManager {
 
     /// Get system DNS servers by inspecting system configuration
     pub fn get_system_dns_servers(&self) -> Result<Vec<IpAddr>> {
-        let (config, _) = system_conf::read_system_conf()
-            .map_err(|e| AppError::dns_resolution(format!("Failed to read system DNS config: {}", e)))?;
+        let (config, _) = system::read_system_config()?;
         
         let servers: Vec<IpAddr> = config
             .name_servers()
@@ -284,16 +687,57 @@ impl DnsManager {
                     }
                 }
             }
+            // Only .local names resolve over mDNS, so a public test domain says nothing about it
+            DnsConfig::Mdns => Ok(DnsValidationResult {
+                valid: true,
+                warnings: Vec::new(),
+                test_duration: None,
+            }),
+            DnsConfig::Chain { resolvers } => {
+                // The chain works while any resolver answers; failing ones are worth a warning
+                let mut warnings = Vec::new();
+                for resolver in resolvers {
+                    let result = self.test_resolution_performance("google.com", resolver).await?;
+                    if !result.success {
+                        warnings.push(format!("Resolver {} of the chain failed to resolve test domain: {}",
+                            resolver.id(), result.error.unwrap_or_default()));
+                    }
+                }
+                let result = self.test_resolution_performance("google.com", dns_config).await?;
+                Ok(DnsValidationResult {
+                    valid: result.success,
+                    warnings,
+                    test_duration: Some(result.duration),
+                })
+            }
         }
     }
 }
 
+/// Resolver configuration that queries exactly `servers`, over UDP with TCP fallback
+pub(crate) fn custom_resolver_config(servers: &[IpAddr]) -> ResolverConfig {
+    let mut config = ResolverConfig::new();
+    for &server in servers {
+        let socket_addr = SocketAddr::new(server, 53);
+        config.add_name_server(NameServerConfig::new(socket_addr, Protocol::Udp));
+        config.add_name_server(NameServerConfig::new(socket_addr, Protocol::Tcp));
+    }
+    config
+}
+
 impl Default for DnsManager {
     fn default() -> Self {
         Self::new().expect("Failed to create default DNS manager")
     }
 }
 
+#[async_trait]
+impl NameResolver for DnsManager {
+    async fn resolve(&self, domain: &str, dns_config: &DnsConfig) -> Result<Vec<IpAddr>> {
+        DnsManager::resolve(self, domain, dns_config).await
+    }
+}
+
 /// DNS resolver wrapper that abstracts different resolution methods
 #[derive(Clone)]
 pub enum DnsResolver {
@@ -302,7 +746,10 @@ pub enum DnsResolver {
     /// Custom DNS resolver
     Custom(TokioAsyncResolver),
     /// DNS-over-HTTPS client
+    #[cfg(feature = "doh")]
     DoH(DoHClient),
+    /// Multicast DNS on the local network
+    Mdns(MdnsResolver),
 }
 
 impl DnsResolver {
@@ -313,23 +760,37 @@ impl DnsResolver {
                 let response = resolver
                     .lookup_ip(domain)
                     .await
-                    .map_err(|e| AppError::dns_resolution(format!("DNS lookup failed for {}: {}", domain, e)))?;
+                    .map_err(|e| AppError::dns_resolution(format!("DNS lookup failed for {}: {}", domain, e)).with_source(e))?;
                 
                 let ips: Vec<IpAddr> = response.iter().collect();
                 Ok(ips)
             }
+            #[cfg(feature = "doh")]
             DnsResolver::DoH(client) => client.resolve(domain).await,
+            DnsResolver::Mdns(resolver) => resolver.resolve(domain).await,
+        }
+    }
+
+    /// Resolve a domain name, with the latency of each record type where
+    /// they are queried separately (DoH)
+    pub async fn resolve_timed(&self, domain: &str) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        match self {
+            #[cfg(feature = "doh")]
+            DnsResolver::DoH(client) => client.resolve_timed(domain).await,
+            _ => Ok((self.resolve(domain).await?, Vec::new())),
         }
     }
 }
 
 /// DNS-over-HTTPS client implementation
+#[cfg(feature = "doh")]
 #[derive(Clone)]
 pub struct DoHClient {
     url: String,
     client: Client,
 }
 
+#[cfg(feature = "doh")]
 impl DoHClient {
     /// Create a new DoH client
     pub fn new(url: String, client: Client) -> Self {
@@ -338,25 +799,46 @@ impl DoHClient {
 
     /// Resolve a domain using DNS-over-HTTPS
     pub async fn resolve(&self, domain: &str) -> Result<Vec<IpAddr>> {
-        // Create DNS query for A and AAAA records
-        let queries = vec![
-            self.query_record(domain, "A").await,
-            self.query_record(domain, "AAAA").await,
-        ];
+        self.resolve_timed(domain).await.map(|(ips, _)| ips)
+    }
+
+    /// Resolve a domain using DNS-over-HTTPS, with the latency of the A and
+    /// AAAA queries, which are sent at the same time
+    pub async fn resolve_timed(&self, domain: &str) -> Result<(Vec<IpAddr>, Vec<RecordLatency>)> {
+        let domain = &ascii_domain(domain)?;
+        let (a, aaaa) = tokio::join!(self.timed_query(domain, "A"), self.timed_query(domain, "AAAA"));
 
         let mut all_ips = Vec::new();
-        for query_result in queries {
-            match query_result {
-                Ok(mut ips) => all_ips.append(&mut ips),
-                Err(_) => continue, // Ignore individual query failures
+        let mut latencies = Vec::new();
+        for (query_result, latency) in [a, aaaa] {
+            // Ignore individual query failures
+            if let Ok(mut ips) = query_result {
+                all_ips.append(&mut ips);
             }
+            latencies.push(latency);
         }
 
         if all_ips.is_empty() {
             return Err(AppError::dns_resolution(format!("No IP addresses resolved for {}", domain)));
         }
 
-        Ok(all_ips)
+        Ok((all_ips, latencies))
+    }
+
+    /// Query one record type under its own timeout, timing the query
+    async fn timed_query(&self, domain: &str, record_type: &str) -> (Result<Vec<IpAddr>>, RecordLatency) {
+        let start = std::time::Instant::now();
+        let result = tokio::time::timeout(DOH_RECORD_TIMEOUT, self.query_record(domain, record_type))
+            .await
+            .unwrap_or_else(|_| Err(AppError::timeout(format!(
+                "DoH {} query timed out after {}s", record_type, DOH_RECORD_TIMEOUT.as_secs()
+            ))));
+        let latency = RecordLatency {
+            record_type: record_type.to_string(),
+            duration: start.elapsed(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        (result, latency)
     }
 
     /// Query specific DNS record type via DoH
@@ -374,7 +856,7 @@ impl DoHClient {
             .header("Accept", "application/dns-json")
             .send()
             .await
-            .map_err(|e| AppError::network(format!("DoH request failed: {}", e)))?;
+            .map_err(|e| AppError::network(format!("DoH request failed: {}", e)).with_source(e))?;
 
         if !response.status().is_success() {
             return Err(AppError::dns_resolution(format!(
@@ -386,7 +868,7 @@ impl DoHClient {
         let dns_response: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| AppError::parse(format!("Failed to parse DoH response: {}", e)))?;
+            .map_err(|e| AppError::parse(format!("Failed to parse DoH response: {}", e)).with_source(e))?;
 
         self.parse_dns_response(dns_response, record_type)
     }
@@ -426,6 +908,321 @@ pub struct DnsPerformanceResult {
     pub duration: Duration,
     pub resolved_ips: Vec<IpAddr>,
     pub error: Option<String>,
+    /// Latency of each record type, for resolvers that query them separately (DoH)
+    #[serde(default)]
+    pub record_latencies: Vec<RecordLatency>,
+}
+
+/// Which resolver of a chain answered its lookups
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolverFailover {
+    /// Lookups answered by each resolver of the chain, in chain order
+    pub answered_by: Vec<u64>,
+    /// Lookups no resolver of the chain answered
+    pub failed: u64,
+}
+
+impl ResolverFailover {
+    /// Count a lookup through a chain of `resolvers`, answered by the one at
+    /// `position` or by none
+    pub fn record(&mut self, resolvers: usize, position: Option<usize>) {
+        if self.answered_by.len() < resolvers {
+            self.answered_by.resize(resolvers, 0);
+        }
+        match position {
+            Some(position) => self.answered_by[position] += 1,
+            None => self.failed += 1,
+        }
+    }
+
+    /// Add the counts of another tally
+    pub fn merge(&mut self, other: &ResolverFailover) {
+        if self.answered_by.len() < other.answered_by.len() {
+            self.answered_by.resize(other.answered_by.len(), 0);
+        }
+        for (count, other) in self.answered_by.iter_mut().zip(&other.answered_by) {
+            *count += other;
+        }
+        self.failed += other.failed;
+    }
+
+    /// Lookups through the chain
+    pub fn lookups(&self) -> u64 {
+        self.answered_by.iter().sum::<u64>() + self.failed
+    }
+
+    /// Lookups the first resolver did not answer
+    pub fn fallbacks(&self) -> u64 {
+        self.lookups() - self.answered_by.first().copied().unwrap_or(0)
+    }
+
+    /// Share of lookups that needed a fallback (0.0-100.0)
+    pub fn fallback_rate(&self) -> f64 {
+        match self.lookups() {
+            0 => 0.0,
+            lookups => self.fallbacks() as f64 / lookups as f64 * 100.0,
+        }
+    }
+}
+
+impl std::fmt::Display for ResolverFailover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let answered: Vec<_> = self.answered_by.iter().map(u64::to_string).collect();
+        write!(
+            f,
+            "{}/{} lookups fell back ({:.1}%), answered by each resolver: {}",
+            self.fallbacks(),
+            self.lookups(),
+            self.fallback_rate(),
+            answered.join(", ")
+        )?;
+        if self.failed > 0 {
+            write!(f, ", {} failed entirely", self.failed)?;
+        }
+        Ok(())
+    }
+}
+
+/// How the lookups of a configuration with several servers went, server by server
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerDistribution {
+    pub strategy: ServerStrategy,
+    /// Each server, in configuration order
+    pub servers: Vec<ServerTally>,
+    /// Lookups no server answered
+    pub failed: u64,
+}
+
+/// Queries sent to one server of a configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerTally {
+    pub server: IpAddr,
+    /// Lookups sent to the server
+    pub queries: u64,
+    /// Lookups whose answer was used
+    pub answers: u64,
+    /// Lookups the server failed
+    pub failures: u64,
+    /// Sum of the times of the answers used, for the mean
+    pub answer_ms_total: f64,
+}
+
+/// What came of one query to one server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServerOutcome {
+    Answered(Duration),
+    Failed,
+}
+
+impl ServerDistribution {
+    pub fn new(strategy: ServerStrategy, servers: &[IpAddr]) -> Self {
+        Self {
+            strategy,
+            servers: servers.iter().map(|&server| ServerTally {
+                server,
+                queries: 0,
+                answers: 0,
+                failures: 0,
+                answer_ms_total: 0.0,
+            }).collect(),
+            failed: 0,
+        }
+    }
+
+    /// Count a query sent to the server at `position`
+    pub fn ask(&mut self, position: usize) {
+        if let Some(tally) = self.servers.get_mut(position) {
+            tally.queries += 1;
+        }
+    }
+
+    /// Count what came of a query to the server at `position`; a racing
+    /// server that was outrun gets no outcome
+    pub fn record(&mut self, position: usize, outcome: ServerOutcome) {
+        let Some(tally) = self.servers.get_mut(position) else { return };
+        match outcome {
+            ServerOutcome::Answered(duration) => {
+                tally.answers += 1;
+                tally.answer_ms_total += duration.as_secs_f64() * 1000.0;
+            }
+            ServerOutcome::Failed => tally.failures += 1,
+        }
+    }
+
+    /// Lookups through the configuration
+    pub fn lookups(&self) -> u64 {
+        self.servers.iter().map(|tally| tally.answers).sum::<u64>() + self.failed
+    }
+
+    /// Add the counts of another tally of the same servers
+    pub fn merge(&mut self, other: &ServerDistribution) {
+        if self.servers.is_empty() {
+            *self = other.clone();
+            return;
+        }
+        for (tally, other) in self.servers.iter_mut().zip(&other.servers) {
+            tally.queries += other.queries;
+            tally.answers += other.answers;
+            tally.failures += other.failures;
+            tally.answer_ms_total += other.answer_ms_total;
+        }
+        self.failed += other.failed;
+    }
+}
+
+impl ServerTally {
+    /// Mean time of the answers used, if there were any
+    pub fn answer_avg_ms(&self) -> Option<f64> {
+        (self.answers > 0).then(|| self.answer_ms_total / self.answers as f64)
+    }
+}
+
+impl std::fmt::Display for ServerTally {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}/{} answered", self.server, self.answers, self.queries)?;
+        if let Some(avg_ms) = self.answer_avg_ms() {
+            write!(f, " in {:.1}ms avg", avg_ms)?;
+        }
+        if self.failures > 0 {
+            write!(f, ", {} failed", self.failures)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ServerDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let servers: Vec<String> = self.servers.iter().map(ToString::to_string).collect();
+        write!(f, "{}, {} lookups: {}", self.strategy, self.lookups(), servers.join(" | "))?;
+        if self.failed > 0 {
+            write!(f, ", {} failed entirely", self.failed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Time the query for one record type took
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordLatency {
+    /// Record type queried, such as A or AAAA
+    pub record_type: String,
+    pub duration: Duration,
+    /// Why the query failed, if it did
+    pub error: Option<String>,
+}
+
+impl std::fmt::Display for RecordLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:.1}ms", self.record_type, self.duration.as_secs_f64() * 1000.0)?;
+        if self.error.is_some() {
+            write!(f, " (failed)")?;
+        }
+        Ok(())
+    }
+}
+
+/// How a DoH provider's host name was resolved before its queries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapLookup {
+    /// Host name of the provider
+    pub host: String,
+    pub via: DohBootstrap,
+    /// Address the queries go to
+    pub address: IpAddr,
+    /// Time the lookup took; zero for a pinned address
+    pub duration: Duration,
+}
+
+impl BootstrapLookup {
+    /// Resolve `host` as `via` says
+    pub async fn run(host: &str, via: DohBootstrap) -> Result<Self> {
+        let start = std::time::Instant::now();
+        let address = match via {
+            DohBootstrap::Pinned(address) => address,
+            // The port is only needed to form a socket address
+            DohBootstrap::System => tokio::net::lookup_host((host, 443)).await
+                .map_err(|e| AppError::dns_resolution(format!("Failed to bootstrap DoH provider {}: {}", host, e)).with_source(e))?
+                .next()
+                .ok_or_else(|| AppError::dns_resolution(format!("Failed to bootstrap DoH provider {}: no addresses", host)))?
+                .ip(),
+        };
+        let duration = match via {
+            DohBootstrap::Pinned(_) => Duration::ZERO,
+            DohBootstrap::System => start.elapsed(),
+        };
+        Ok(Self { host: host.to_string(), via, address, duration })
+    }
+}
+
+impl std::fmt::Display for BootstrapLookup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.via {
+            DohBootstrap::Pinned(address) => write!(f, "{} pinned to {}", self.host, address),
+            DohBootstrap::System => write!(
+                f, "{} -> {} via system resolver in {:.1}ms",
+                self.host, self.address, self.duration.as_secs_f64() * 1000.0
+            ),
+        }
+    }
+}
+
+/// Where the time of DoH queries goes: the lookup of the provider's host, its
+/// HTTPS connection setup and the queries themselves
+///
+/// The first query to a provider pays for TCP and TLS setup, so it is slower
+/// than the queries that follow on the same connection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DoHTiming {
+    /// Cold query: sent over a new connection, including its setup
+    pub first_query: Duration,
+    /// Warm query: median of the queries sent over the established connection
+    pub query_time: Duration,
+    /// Queries the median was taken over
+    pub warm_queries: usize,
+    /// Lookup of the provider's host, which the cold query does not include
+    #[serde(default)]
+    pub bootstrap: Option<BootstrapLookup>,
+}
+
+impl DoHTiming {
+    /// Timing from the first query and those that followed it, if any did
+    pub fn from_samples(first_query: Duration, mut warm: Vec<Duration>) -> Option<Self> {
+        if warm.is_empty() {
+            return None;
+        }
+        warm.sort();
+        let middle = warm.len() / 2;
+        let query_time = if warm.len().is_multiple_of(2) { (warm[middle - 1] + warm[middle]) / 2 } else { warm[middle] };
+        Some(Self { first_query, query_time, warm_queries: warm.len(), bootstrap: None })
+    }
+
+    /// Record how the provider's host was looked up
+    pub fn with_bootstrap(self, bootstrap: BootstrapLookup) -> Self {
+        Self { bootstrap: Some(bootstrap), ..self }
+    }
+
+    /// Time the provider's connection setup added to the first query
+    pub fn connection_setup(&self) -> Duration {
+        self.first_query.saturating_sub(self.query_time)
+    }
+}
+
+impl std::fmt::Display for DoHTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        match self.bootstrap {
+            Some(BootstrapLookup { via: DohBootstrap::Pinned(address), .. }) => write!(f, "pinned to {}, ", address)?,
+            Some(ref lookup) => write!(f, "bootstrap {:.1}ms, ", ms(lookup.duration))?,
+            None => {}
+        }
+        write!(
+            f,
+            "cold query {:.1}ms, warm query {:.1}ms (connection setup {:.1}ms)",
+            ms(self.first_query),
+            ms(self.query_time),
+            ms(self.connection_setup())
+        )
+    }
 }
 
 /// DNS configuration validation result
@@ -518,19 +1315,20 @@ impl DnsUtils {
             let mut servers = Vec::new();
             for part in input.split(',') {
                 let ip = part.trim().parse::<IpAddr>()
-                    .map_err(|e| AppError::parse(format!("Invalid IP address '{}': {}", part.trim(), e)))?;
+                    .map_err(|e| AppError::parse(format!("Invalid IP address '{}': {}", part.trim(), e)).with_source(e))?;
                 servers.push(ip);
             }
             Ok(DnsConfig::Custom { servers })
         } else {
             let ip = input.parse::<IpAddr>()
-                .map_err(|e| AppError::parse(format!("Invalid IP address '{}': {}", input, e)))?;
+                .map_err(|e| AppError::parse(format!("Invalid IP address '{}': {}", input, e)).with_source(e))?;
             Ok(DnsConfig::Custom { servers: vec![ip] })
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
     use std::time::Duration;
@@ -541,6 +1339,184 @@ mod tests {
         assert!(dns_manager.is_ok());
     }
 
+    #[test]
+    fn test_doh_timing_split() {
+        let ms = Duration::from_millis;
+        let timing = DoHTiming::from_samples(ms(90), vec![ms(30), ms(20), ms(26), ms(24)]).unwrap();
+        assert_eq!(timing.query_time, ms(25));
+        assert_eq!(timing.connection_setup(), ms(65));
+        assert_eq!(timing.warm_queries, 4);
+        assert_eq!(timing.to_string(), "cold query 90.0ms, warm query 25.0ms (connection setup 65.0ms)");
+
+        // A first query that happened to be quick adds no setup time
+        let timing = DoHTiming::from_samples(ms(10), vec![ms(20)]).unwrap();
+        assert_eq!(timing.connection_setup(), Duration::ZERO);
+        assert!(DoHTiming::from_samples(ms(10), Vec::new()).is_none());
+    }
+
+    /// Plain-HTTP stand-in for a DoH provider that answers every query with
+    /// 192.0.2.1 after `delay` and counts the connections it accepts
+    #[cfg(feature = "doh")]
+    async fn doh_server(delay: Duration) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/dns-query", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let body = r#"{"Answer":[{"data":"192.0.2.1","type":1}]}"#;
+                    loop {
+                        // Skip the request up to the blank line ending its headers
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                        }
+                        tokio::time::sleep(delay).await;
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/dns-json\r\nContent-Length: {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        if stream.get_mut().write_all(response.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    #[tokio::test]
+    async fn test_server_strategies() {
+        use crate::client::mock::MockResolver;
+
+        let (first, second): (IpAddr, IpAddr) = ("192.0.2.53".parse().unwrap(), "198.51.100.53".parse().unwrap());
+        let group = DnsConfig::Custom { servers: vec![first, second] };
+        let mock = MockResolver::new()
+            .with_record("example.com", vec!["192.0.2.1".parse().unwrap()])
+            .failing_for(DnsConfig::Custom { servers: vec![first] });
+        let options = ResolverOptions::parse_list("192.0.2.53+198.51.100.53=strategy:round-robin").unwrap();
+        let dns_manager = DnsManager::with_resolver(Arc::new(mock.clone())).unwrap().with_resolver_options(options);
+        for _ in 0..4 {
+            dns_manager.resolve("example.com", &group).await.unwrap();
+        }
+        // Every other lookup starts at the failing server and falls back
+        let distribution = dns_manager.server_distribution();
+        assert_eq!(distribution[0].0, group);
+        let distribution = &distribution[0].1;
+        assert_eq!((distribution.servers[0].queries, distribution.servers[0].failures), (2, 2));
+        assert_eq!((distribution.servers[1].queries, distribution.servers[1].answers), (4, 4));
+        assert_eq!(distribution.lookups(), 4);
+        assert!(distribution.to_string().starts_with("round-robin, 4 lookups: 192.0.2.53 0/2 answered, 2 failed | 198.51.100.53 4/4 answered in "));
+
+        let options = ResolverOptions::parse_list("192.0.2.53+198.51.100.53=strategy:race-all").unwrap();
+        let dns_manager = DnsManager::with_resolver(Arc::new(mock)).unwrap().with_resolver_options(options);
+        dns_manager.resolve("example.com", &group).await.unwrap();
+        assert!(dns_manager.resolve("example.org", &group).await.is_err());
+        let distribution = &dns_manager.server_distribution()[0].1;
+        assert_eq!(distribution.servers.iter().map(|tally| tally.queries).collect::<Vec<_>>(), [2, 2]);
+        assert_eq!((distribution.servers[1].answers, distribution.failed), (1, 1));
+    }
+
+    #[test]
+    fn test_resolver_options() {
+        let options = ResolverOptions::parse_list("8.8.8.8=timeout:1 attempts:0 rotate cache:0").unwrap();
+        let dns_manager = DnsManager::new().unwrap().with_resolver_options(options);
+        let custom = DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap()] };
+        let opts = dns_manager.resolver_opts(&custom, ResolverOpts::default());
+        assert_eq!((opts.timeout, opts.attempts, opts.cache_size), (Duration::from_secs(1), 0, 0));
+        assert!(opts.shuffle_dns_servers);
+        // Other configurations keep the defaults they are given
+        let opts = dns_manager.resolver_opts(&DnsConfig::System, ResolverOpts::default());
+        assert_eq!(opts.timeout, ResolverOpts::default().timeout);
+        assert!(dns_manager.resolver_options(&DnsConfig::System).is_none());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "doh")]
+    async fn test_doh_connection_reuse() {
+        use std::sync::atomic::Ordering;
+
+        let (url, connections) = doh_server(Duration::ZERO).await;
+        let dns_manager = DnsManager::new().unwrap();
+        assert!(!dns_manager.fresh_doh_connections());
+        let doh = DnsConfig::DoH { url: url.clone() };
+        for _ in 0..3 {
+            let ips = dns_manager.resolve("example.com", &doh).await.unwrap();
+            assert!(ips.contains(&"192.0.2.1".parse().unwrap()));
+        }
+        // One connection for each of the A and AAAA queries in flight together
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+        let (url, connections) = doh_server(Duration::ZERO).await;
+        let dns_manager = DnsManager::new().unwrap().with_fresh_doh_connections(true).unwrap();
+        assert!(dns_manager.fresh_doh_connections());
+        for _ in 0..3 {
+            dns_manager.resolve("example.com", &DnsConfig::DoH { url: url.clone() }).await.unwrap();
+        }
+        // An A and an AAAA query per lookup, each on its own connection
+        assert_eq!(connections.load(Ordering::SeqCst), 6);
+
+        // The benchmark measures both regardless of the manager's setting
+        let timing = dns_manager.benchmark_doh(&url, "example.com", 3).await.unwrap();
+        assert_eq!(timing.warm_queries, 3);
+        assert_eq!(connections.load(Ordering::SeqCst), 7);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "doh")]
+    async fn test_doh_bootstrap() {
+        let (url, _) = doh_server(Duration::ZERO).await;
+        // A host name nothing resolves, pinned to the stand-in's address
+        let port = url::Url::parse(&url).unwrap().port().unwrap();
+        let pinned_url = format!("http://doh.invalid:{}/dns-query", port);
+        let bootstrap = DohBootstrap::parse_list("doh.invalid=127.0.0.1").unwrap();
+        let dns_manager = DnsManager::new().unwrap().with_doh_bootstrap(&bootstrap).await.unwrap();
+        assert_eq!(dns_manager.doh_bootstrap()[0].to_string(), "doh.invalid pinned to 127.0.0.1");
+        let ips = dns_manager.resolve("example.com", &DnsConfig::DoH { url: pinned_url.clone() }).await.unwrap();
+        assert!(ips.contains(&"192.0.2.1".parse().unwrap()));
+
+        let timing = dns_manager.benchmark_doh(&pinned_url, "example.com", 2).await.unwrap();
+        assert!(timing.to_string().starts_with("pinned to 127.0.0.1, cold query"));
+        // Without a bootstrap the host is looked up through the system resolver, apart from the queries
+        let timing = DnsManager::new().unwrap().benchmark_doh(&url, "example.com", 2).await.unwrap();
+        let lookup = timing.bootstrap.unwrap();
+        assert_eq!((lookup.via, lookup.address), (DohBootstrap::System, "127.0.0.1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "doh")]
+    async fn test_doh_record_types_are_queried_concurrently() {
+        let delay = Duration::from_millis(300);
+        let (url, _) = doh_server(delay).await;
+        let dns_manager = DnsManager::new().unwrap();
+
+        let result = dns_manager.test_resolution_performance("example.com", &DnsConfig::DoH { url }).await.unwrap();
+        assert!(result.success);
+        let types: Vec<_> = result.record_latencies.iter().map(|latency| latency.record_type.as_str()).collect();
+        assert_eq!(types, ["A", "AAAA"]);
+        assert!(result.record_latencies.iter().all(|latency| latency.duration >= delay && latency.error.is_none()));
+        // Sequential queries would take at least twice the delay
+        assert!(result.duration < delay * 2, "took {:?}", result.duration);
+        assert!(result.record_latencies[0].to_string().starts_with("A 3"));
+
+        let failed = RecordLatency { record_type: "AAAA".to_string(), duration: Duration::from_millis(5000), error: Some("timed out".to_string()) };
+        assert_eq!(failed.to_string(), "AAAA 5000.0ms (failed)");
+    }
+
     #[tokio::test]
     async fn test_system_resolver_initialization() {
         let dns_manager = DnsManager::new().unwrap();
@@ -576,7 +1552,7 @@ mod tests {
         
         let result = dns_manager.create_custom_resolver(&servers).await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Validation(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Validation(..)));
     }
 
     #[test]
@@ -586,6 +1562,7 @@ mod tests {
             duration: Duration::from_millis(150),
             resolved_ips: vec!["8.8.8.8".parse().unwrap()],
             error: None,
+            record_latencies: Vec::new(),
         };
         
         assert!(result.success);
@@ -609,6 +1586,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "doh")]
     fn test_doh_client_creation() {
         let client = Client::new();
         let doh_client = DoHClient::new("https://dns.google/dns-query".to_string(), client);
@@ -617,6 +1595,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "doh")]
     fn test_dns_parse_response_a_record() {
         let client = Client::new();
         let doh_client = DoHClient::new("https://example.com".to_string(), client);
@@ -641,6 +1620,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "doh")]
     fn test_dns_parse_response_aaaa_record() {
         let client = Client::new();
         let doh_client = DoHClient::new("https://example.com".to_string(), client);
@@ -660,6 +1640,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "doh")]
     fn test_dns_parse_response_empty() {
         let client = Client::new();
         let doh_client = DoHClient::new("https://example.com".to_string(), client);
@@ -673,6 +1654,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "doh")]
     fn test_dns_parse_response_no_answer() {
         let client = Client::new();
         let doh_client = DoHClient::new("https://example.com".to_string(), client);
@@ -779,14 +1761,14 @@ mod tests {
     fn test_dns_config_parsing_invalid_ip() {
         let result = DnsUtils::parse_dns_config("not-an-ip");
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Parse(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Parse(..)));
     }
 
     #[test]
     fn test_dns_config_parsing_mixed_valid_invalid_ips() {
         let result = DnsUtils::parse_dns_config("8.8.8.8,not-an-ip");
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Parse(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Parse(..)));
     }
 
     #[tokio::test]
@@ -796,7 +1778,7 @@ mod tests {
         
         let result = DnsUtils::find_fastest_dns(&dns_manager, &configs, "google.com").await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Validation(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Validation(..)));
     }
 
     #[test]
@@ -873,6 +1855,42 @@ mod tests {
         // The URL parsing may return the path portion, so we just check that DoH is mentioned
         assert!(name.starts_with("DoH"));
     }
+
+    #[tokio::test]
+    async fn test_mdns_config_resolves_other_names_through_system_dns() {
+        use crate::client::mock::MockResolver;
+
+        let resolver = MockResolver::new()
+            .with_record("nas.local", vec!["192.168.1.20".parse().unwrap()])
+            .with_record("example.com", vec!["93.184.216.34".parse().unwrap()])
+            .failing_for(DnsConfig::Mdns);
+        let dns_manager = DnsManager::with_resolver(Arc::new(resolver)).unwrap();
+
+        assert!(dns_manager.resolve("nas.local", &DnsConfig::Mdns).await.is_err());
+        let ips = dns_manager.resolve("example.com", &DnsConfig::Mdns).await.unwrap();
+        assert_eq!(ips, ["93.184.216.34".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_search_policy_qualifies_short_names() {
+        use crate::client::mock::MockResolver;
+
+        let resolver = MockResolver::new()
+            .with_record("intranet.corp.example.com.", vec!["10.0.0.5".parse().unwrap()])
+            .with_record("example.com", vec!["93.184.216.34".parse().unwrap()]);
+        let custom = DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap()] };
+
+        let dns_manager = DnsManager::with_resolver(Arc::new(resolver.clone())).unwrap();
+        assert!(dns_manager.resolve("intranet", &custom).await.is_err());
+
+        let policy = SearchPolicy { domains: vec!["example.com".to_string(), "corp.example.com".to_string()], ndots: 1, overridden: true };
+        let dns_manager = DnsManager::with_resolver(Arc::new(resolver)).unwrap().with_search_policy(policy);
+        assert_eq!(dns_manager.resolve("intranet", &custom).await.unwrap(), ["10.0.0.5".parse::<IpAddr>().unwrap()]);
+        // Names with enough dots are left alone
+        assert_eq!(dns_manager.resolve("example.com", &custom).await.unwrap(), ["93.184.216.34".parse::<IpAddr>().unwrap()]);
+        let error = dns_manager.resolve("printer", &custom).await.unwrap_err().to_string();
+        assert!(error.contains("did not resolve with any search domain"));
+    }
 }
 
 // Additional comprehensive tests in separate module
@@ -4,13 +4,15 @@
 //! performance and compatibility across different operating systems.
 
 use crate::{
-    error::Result,
+    error::{AppError, Result},
     types::DnsConfig,
 };
 use std::{
+    fmt,
     net::IpAddr,
-    time::Duration,
+    time::{Duration, Instant},
 };
+use tokio::process::Command;
 
 /// Platform-specific DNS configuration and optimization settings
 #[derive(Debug, Clone)]
@@ -160,7 +162,7 @@ impl PlatformDnsConfig {
     /// Get recommended timeout for DNS queries on this platform
     pub fn get_dns_timeout(&self, dns_config: &DnsConfig) -> Duration {
         match dns_config {
-            DnsConfig::System => self.default_timeout,
+            DnsConfig::System | DnsConfig::Mdns => self.default_timeout,
             DnsConfig::Custom { .. } => {
                 // Custom DNS servers might be slower
                 self.default_timeout + Duration::from_millis(500)
@@ -169,6 +171,13 @@ impl PlatformDnsConfig {
                 // DoH queries take longer due to HTTPS overhead
                 self.default_timeout * 2
             }
+            DnsConfig::Chain { resolvers } => {
+                // Allow for the slowest resolver of the chain
+                resolvers.iter()
+                    .map(|resolver| self.get_dns_timeout(resolver))
+                    .max()
+                    .unwrap_or(self.default_timeout)
+            }
         }
     }
 }
@@ -244,6 +253,8 @@ impl PlatformDnsResolver {
                 DnsConfig::System => {
                     // Already added
                 }
+                // Chains and mDNS are kept as configured
+                DnsConfig::Chain { .. } | DnsConfig::Mdns => optimized.push(config),
             }
         }
         
@@ -405,6 +416,69 @@ pub fn get_platform_name() -> String {
     }
 }
 
+/// Commands that flush the OS resolver cache, in the order they are tried;
+/// the first that succeeds is enough
+fn flush_commands() -> &'static [&'static [&'static str]] {
+    #[cfg(target_os = "windows")]
+    {
+        &[&["ipconfig", "/flushdns"]]
+    }
+    #[cfg(target_os = "macos")]
+    {
+        &[&["dscacheutil", "-flushcache"]]
+    }
+    #[cfg(target_os = "linux")]
+    {
+        &[&["resolvectl", "flush-caches"], &["systemd-resolve", "--flush-caches"], &["nscd", "--invalidate=hosts"]]
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        &[]
+    }
+}
+
+/// A flush of the OS resolver cache, done with `--flush-dns-cache`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsCacheFlush {
+    /// The command that flushed the cache
+    pub command: String,
+    pub duration: Duration,
+}
+
+impl DnsCacheFlush {
+    /// Flush the OS resolver cache, so that lookups through the system
+    /// configuration start cold
+    ///
+    /// Most platforms need administrator rights for this. On macOS the
+    /// mDNSResponder daemon keeps its own cache, which is only dropped too
+    /// when it can be sent a hang-up signal.
+    pub async fn run() -> Result<Self> {
+        let mut failures = Vec::new();
+        for command in flush_commands() {
+            let started = Instant::now();
+            match Command::new(command[0]).args(&command[1..]).output().await {
+                Ok(output) if output.status.success() => {
+                    #[cfg(target_os = "macos")]
+                    let _ = Command::new("killall").args(["-HUP", "mDNSResponder"]).output().await;
+                    return Ok(Self { command: command.join(" "), duration: started.elapsed() });
+                }
+                Ok(output) => failures.push(format!("{} failed: {}", command.join(" "), String::from_utf8_lossy(&output.stderr).trim())),
+                Err(e) => failures.push(format!("{} is not available: {}", command[0], e)),
+            }
+        }
+        if failures.is_empty() {
+            return Err(AppError::cache(format!("Flushing the DNS cache is not supported on {}", get_platform_name())));
+        }
+        Err(AppError::cache(format!("Could not flush the OS DNS cache ({})", failures.join("; "))))
+    }
+}
+
+impl fmt::Display for DnsCacheFlush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Flushed the OS DNS cache with `{}` ({:.1}ms)", self.command, self.duration.as_secs_f64() * 1000.0)
+    }
+}
+
 /// Platform-specific DNS performance tuning
 pub struct DnsPerformanceTuner {
     resolver: PlatformDnsResolver,
@@ -431,13 +505,14 @@ impl DnsPerformanceTuner {
     /// Check if a specific DNS configuration is optimal for this platform
     pub fn is_config_optimal(&self, config: &DnsConfig) -> bool {
         match config {
-            DnsConfig::System => true, // Always acceptable
+            DnsConfig::System | DnsConfig::Mdns => true, // Always acceptable
             DnsConfig::Custom { servers } => {
                 servers.iter().all(|ip| self.resolver.is_dns_server_optimal(ip))
             }
             DnsConfig::DoH { url } => {
                 self.resolver.config().get_optimized_doh_providers().contains(url)
             }
+            DnsConfig::Chain { resolvers } => resolvers.first().is_some_and(|first| self.is_config_optimal(first)),
         }
     }
 
@@ -573,4 +648,15 @@ mod tests {
         #[cfg(target_os = "windows")]
         assert!(!has_ipv6); // Conservative default
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dns_cache_flush() {
+        #[cfg(target_os = "linux")]
+        assert_eq!(flush_commands()[0], ["resolvectl", "flush-caches"]);
+        #[cfg(target_os = "windows")]
+        assert_eq!(flush_commands(), [["ipconfig", "/flushdns"]]);
+
+        let flush = DnsCacheFlush { command: "resolvectl flush-caches".to_string(), duration: Duration::from_micros(12_340) };
+        assert_eq!(flush.to_string(), "Flushed the OS DNS cache with `resolvectl flush-caches` (12.3ms)");
+    }
+}
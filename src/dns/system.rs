@@ -0,0 +1,352 @@
+//! Read-only access to the system DNS configuration
+//!
+//! A comparison run must not disturb the machine it runs on: custom and DoH
+//! configurations are tested through resolvers this tool builds from their own
+//! servers, and the system configuration is read (to test System DNS and list
+//! its servers) but never written. Every access to it goes through this module
+//! so that the guarantee can be checked in one place. Anything that applies a
+//! configuration to the system, such as switching to the best one found, does
+//! not exist in this tool; it would belong here, behind an opt-in Cargo feature.
+//!
+//! `--sandbox-dns` verifies the guarantee on each run with a [`DnsSandbox`]:
+//! before testing, each custom configuration must resolve through exactly its
+//! own servers, and after testing the system configuration must be unchanged.
+
+use super::custom_resolver_config;
+use crate::{
+    error::{AppError, Result},
+    types::DnsConfig,
+};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
+use trust_dns_resolver::{
+    config::{ResolverConfig, ResolverOpts},
+    system_conf,
+};
+
+/// Read the system DNS configuration
+pub(crate) fn read_system_config() -> Result<(ResolverConfig, ResolverOpts)> {
+    system_conf::read_system_conf()
+        .map_err(|e| AppError::dns_resolution(format!("Failed to read system DNS config: {}", e)).with_source(e))
+}
+
+/// The parts of the system DNS configuration that decide how names resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemDnsSnapshot {
+    /// Name servers, in the order they are tried
+    pub name_servers: Vec<SocketAddr>,
+    /// Local domain, if one is set
+    pub domain: Option<String>,
+    /// Search domains
+    pub search: Vec<String>,
+}
+
+impl SystemDnsSnapshot {
+    /// Snapshot of the current system DNS configuration
+    pub fn capture() -> Result<Self> {
+        read_system_config().map(|(config, _)| Self::from_config(&config))
+    }
+
+    fn from_config(config: &ResolverConfig) -> Self {
+        let mut name_servers = Vec::new();
+        for server in config.name_servers() {
+            // Each server is usually listed once per protocol
+            if !name_servers.contains(&server.socket_addr) {
+                name_servers.push(server.socket_addr);
+            }
+        }
+        Self {
+            name_servers,
+            domain: config.domain().map(ToString::to_string),
+            search: config.search().iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Descriptions of how this snapshot differs from an `earlier` one
+    pub fn changes_since(&self, earlier: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.name_servers != earlier.name_servers {
+            changes.push(format!("name servers {} -> {}", list(&earlier.name_servers), list(&self.name_servers)));
+        }
+        if self.domain != earlier.domain {
+            let describe = |domain: &Option<String>| domain.clone().unwrap_or_else(|| "none".to_string());
+            changes.push(format!("domain {} -> {}", describe(&earlier.domain), describe(&self.domain)));
+        }
+        if self.search != earlier.search {
+            changes.push(format!("search {} -> {}", list(&earlier.search), list(&self.search)));
+        }
+        changes
+    }
+}
+
+impl fmt::Display for SystemDnsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", list(&self.name_servers))?;
+        if !self.search.is_empty() {
+            write!(f, " (search {})", self.search.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+fn list<T: ToString>(items: &[T]) -> String {
+    if items.is_empty() {
+        return "none".to_string();
+    }
+    items.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// How short host names are qualified, as the `search` and `ndots` options
+/// of resolv.conf describe
+///
+/// A name with fewer dots than `ndots` is tried with each search domain
+/// appended before it is tried as given; other names are tried as given
+/// first. Custom and DoH resolvers know nothing of the network's search
+/// domains, so without this an intranet name that the system resolves would
+/// fail under them with a misleading NXDOMAIN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPolicy {
+    /// Search domains, in the order they are tried
+    pub domains: Vec<String>,
+    /// Names with fewer dots than this are tried with the search domains first
+    pub ndots: usize,
+    /// Whether the domains or ndots differ from the system's on purpose
+    pub overridden: bool,
+}
+
+impl Default for SearchPolicy {
+    fn default() -> Self {
+        Self { domains: Vec::new(), ndots: 1, overridden: false }
+    }
+}
+
+impl SearchPolicy {
+    /// The system's search domains and ndots, with any given overrides applied
+    ///
+    /// A system configuration that cannot be read counts as having no search
+    /// domains, which is what the resolvers see then too.
+    pub fn system(domains: Option<Vec<String>>, ndots: Option<u8>) -> Self {
+        let mut policy = match read_system_config() {
+            Ok((config, opts)) => Self {
+                domains: config.search().iter().map(|name| name.to_string().trim_end_matches('.').to_string()).collect(),
+                ndots: opts.ndots,
+                overridden: false,
+            },
+            Err(_) => Self::default(),
+        };
+        policy.overridden = domains.is_some() || ndots.is_some();
+        if let Some(domains) = domains {
+            policy.domains = domains;
+        }
+        if let Some(ndots) = ndots {
+            policy.ndots = ndots as usize;
+        }
+        policy
+    }
+
+    /// Whether `name` is tried with the search domains before as given
+    pub fn is_short(&self, name: &str) -> bool {
+        !name.ends_with('.') && name.matches('.').count() < self.ndots
+    }
+
+    /// Fully qualified names to try for `name`, in order
+    pub fn candidates(&self, name: &str) -> Vec<String> {
+        if name.ends_with('.') {
+            return vec![name.to_string()];
+        }
+        let qualified = self.domains.iter().map(|domain| format!("{}.{}.", name, domain.trim_matches('.')));
+        let as_given = std::iter::once(format!("{}.", name));
+        if self.is_short(name) {
+            qualified.chain(as_given).collect()
+        } else {
+            as_given.chain(qualified).collect()
+        }
+    }
+}
+
+impl fmt::Display for SearchPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "search {}, ndots {}", list(&self.domains), self.ndots)?;
+        if self.overridden {
+            write!(f, " (overridden)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that a run resolves through its own resolvers and leaves the system
+/// DNS configuration alone
+#[derive(Debug, Clone)]
+pub struct DnsSandbox {
+    /// System configuration before the run
+    before: SystemDnsSnapshot,
+    /// Configurations resolved without the system configuration
+    explicit: usize,
+}
+
+impl DnsSandbox {
+    /// Verify the configurations of a run before it starts and snapshot the
+    /// system configuration
+    ///
+    /// Fails when a custom configuration would query a server other than its
+    /// own, which would make it depend on the system instead of being compared
+    /// against it.
+    pub fn verify(dns_configs: &[DnsConfig]) -> Result<Self> {
+        Self::verify_against(dns_configs, SystemDnsSnapshot::capture()?)
+    }
+
+    fn verify_against(dns_configs: &[DnsConfig], before: SystemDnsSnapshot) -> Result<Self> {
+        let mut explicit = 0;
+        for dns_config in dns_configs {
+            // A chain is explicit when none of its resolvers is the system's
+            let resolvers = match dns_config {
+                DnsConfig::Chain { resolvers } => resolvers.as_slice(),
+                single => std::slice::from_ref(single),
+            };
+            let mut own_servers = true;
+            for resolver in resolvers {
+                own_servers &= Self::verify_resolver(resolver)?;
+            }
+            if own_servers {
+                explicit += 1;
+            }
+        }
+        Ok(Self { before, explicit })
+    }
+
+    /// Verify a single resolver, returning whether it avoids the system configuration
+    fn verify_resolver(dns_config: &DnsConfig) -> Result<bool> {
+        match dns_config {
+            DnsConfig::System | DnsConfig::Chain { .. } => Ok(false),
+            DnsConfig::Custom { servers } => {
+                let expected: BTreeSet<IpAddr> = servers.iter().copied().collect();
+                let queried: BTreeSet<IpAddr> = custom_resolver_config(servers)
+                    .name_servers()
+                    .iter()
+                    .map(|server| server.socket_addr.ip())
+                    .collect();
+                if queried.is_empty() || queried != expected {
+                    return Err(AppError::config(format!(
+                        "DNS sandbox check failed: {} would query {} instead of only its own servers",
+                        dns_config.name(),
+                        list(&queried.into_iter().collect::<Vec<_>>())
+                    )));
+                }
+                Ok(true)
+            }
+            // DoH queries go to the provider's URL over HTTPS, mDNS queries to the multicast group
+            DnsConfig::DoH { .. } | DnsConfig::Mdns => Ok(true),
+        }
+    }
+
+    /// System configuration before the run
+    pub fn before(&self) -> &SystemDnsSnapshot {
+        &self.before
+    }
+
+    /// Confirm after the run that the system configuration is unchanged
+    ///
+    /// This tool never writes it, so a change means another program did,
+    /// and System DNS results may mix the old and new configuration.
+    pub fn confirm(&self) -> Result<()> {
+        self.confirm_against(&SystemDnsSnapshot::capture()?)
+    }
+
+    fn confirm_against(&self, after: &SystemDnsSnapshot) -> Result<()> {
+        let changes = after.changes_since(&self.before);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::config(format!(
+            "System DNS settings changed during the run ({}); this tool does not modify them, so System DNS results may mix both",
+            changes.join("; ")
+        )))
+    }
+}
+
+impl fmt::Display for DnsSandbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DNS sandbox: {} configuration{} resolve through their own servers; system DNS is {}",
+            self.explicit,
+            if self.explicit == 1 { "" } else { "s" },
+            self.before
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::config::{NameServerConfig, Protocol};
+
+    fn snapshot(servers: &[&str], search: &[&str]) -> SystemDnsSnapshot {
+        let mut config = ResolverConfig::new();
+        for server in servers {
+            let addr = SocketAddr::new(server.parse().unwrap(), 53);
+            config.add_name_server(NameServerConfig::new(addr, Protocol::Udp));
+            config.add_name_server(NameServerConfig::new(addr, Protocol::Tcp));
+        }
+        for domain in search {
+            config.add_search(domain.parse().unwrap());
+        }
+        SystemDnsSnapshot::from_config(&config)
+    }
+
+    #[test]
+    fn test_sandbox_verifies_and_detects_changes() {
+        let before = snapshot(&["192.168.1.1"], &["lan"]);
+        assert_eq!(before.to_string(), "192.168.1.1:53 (search lan)");
+
+        let configs = [
+            DnsConfig::System,
+            DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap(), "8.8.4.4".parse().unwrap()] },
+            DnsConfig::DoH { url: "https://cloudflare-dns.com/dns-query".to_string() },
+        ];
+        let sandbox = DnsSandbox::verify_against(&configs, before.clone()).unwrap();
+        assert!(sandbox.to_string().starts_with("DNS sandbox: 2 configurations resolve through their own servers"));
+        assert!(sandbox.confirm_against(&before).is_ok());
+
+        let error = sandbox.confirm_against(&snapshot(&["1.1.1.1"], &["lan"])).unwrap_err();
+        assert!(error.to_string().contains("name servers 192.168.1.1:53 -> 1.1.1.1:53"), "{}", error);
+        assert!(DnsSandbox::verify_against(&[DnsConfig::Custom { servers: Vec::new() }], before).is_err());
+    }
+
+    #[test]
+    fn test_search_policy_candidates() {
+        let policy = SearchPolicy { domains: vec!["corp.example.com".to_string(), "example.com".to_string()], ndots: 1, overridden: true };
+        assert!(policy.is_short("intranet"));
+        assert_eq!(policy.candidates("intranet"), ["intranet.corp.example.com.", "intranet.example.com.", "intranet."]);
+        assert_eq!(policy.candidates("wiki.eng"), ["wiki.eng.", "wiki.eng.corp.example.com.", "wiki.eng.example.com."]);
+        assert_eq!(policy.candidates("example.org."), ["example.org."]);
+        assert_eq!(policy.to_string(), "search corp.example.com, example.com, ndots 1 (overridden)");
+
+        // With a higher ndots, names with a dot are qualified first too
+        let policy = SearchPolicy { ndots: 2, ..policy };
+        assert_eq!(policy.candidates("wiki.eng")[0], "wiki.eng.corp.example.com.");
+        assert_eq!(SearchPolicy::default().candidates("intranet"), ["intranet."]);
+    }
+
+    #[test]
+    fn test_system_config_is_only_read_here() {
+        // The guarantee holds only while nothing else reaches the system configuration
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut pending = vec![src];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with("dns/system.rs") {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    let direct = ["system_conf::", "system_conf,", "system_conf}", "read_system_conf("].iter().any(|name| source.contains(name));
+                    assert!(!direct, "{} reads the system DNS configuration directly", path.display());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Collapsing of repeated identical failures
+//!
+//! Long runs against an unreachable target produce the same error for every
+//! request. [`ErrorAggregator`] groups failures by message so logs and reports
+//! can show one line such as `connection refused ×47 (first at 12:01:03, last
+//! at 12:15:40)` instead of 47 identical ones.
+
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// All occurrences of one distinct error message
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorGroup {
+    /// The error message shared by every occurrence
+    pub message: String,
+    /// Number of occurrences
+    pub count: usize,
+    /// Time of the first occurrence
+    pub first_at: DateTime<Utc>,
+    /// Time of the last occurrence
+    pub last_at: DateTime<Utc>,
+    /// Where the error occurred (e.g. configuration and URL), in order of first appearance
+    pub sources: Vec<String>,
+}
+
+impl fmt::Display for ErrorGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 1 {
+            write!(f, "{} (at {})", self.message, self.first_at.format("%H:%M:%S"))
+        } else {
+            write!(
+                f,
+                "{} ×{} (first at {}, last at {})",
+                self.message,
+                self.count,
+                self.first_at.format("%H:%M:%S"),
+                self.last_at.format("%H:%M:%S")
+            )
+        }
+    }
+}
+
+/// Groups failures by identical message
+#[derive(Debug, Clone, Default)]
+pub struct ErrorAggregator {
+    groups: Vec<ErrorGroup>,
+}
+
+impl ErrorAggregator {
+    /// Create an empty aggregator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an occurrence of `message` from `source`
+    ///
+    /// Returns `true` the first time a message is seen, so callers can log the
+    /// first occurrence immediately and suppress the repeats.
+    pub fn record(&mut self, message: &str, at: DateTime<Utc>, source: &str) -> bool {
+        match self.groups.iter_mut().find(|group| group.message == message) {
+            Some(group) => {
+                group.count += 1;
+                group.first_at = group.first_at.min(at);
+                group.last_at = group.last_at.max(at);
+                if !group.sources.iter().any(|s| s == source) {
+                    group.sources.push(source.to_string());
+                }
+                false
+            }
+            None => {
+                self.groups.push(ErrorGroup {
+                    message: message.to_string(),
+                    count: 1,
+                    first_at: at,
+                    last_at: at,
+                    sources: vec![source.to_string()],
+                });
+                true
+            }
+        }
+    }
+
+    /// Whether no errors were recorded
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Total number of recorded occurrences
+    pub fn total(&self) -> usize {
+        self.groups.iter().map(|group| group.count).sum()
+    }
+
+    /// Groups that occurred more than once, in order of first appearance
+    pub fn repeated(&self) -> impl Iterator<Item = &ErrorGroup> {
+        self.groups.iter().filter(|group| group.count > 1)
+    }
+
+    /// All groups, most frequent first
+    pub fn into_groups(mut self) -> Vec<ErrorGroup> {
+        self.groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.first_at.cmp(&b.first_at)));
+        self.groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_aggregator_collapses_repeats() {
+        let at = |minute, second| Utc.with_ymd_and_hms(2025, 1, 1, 12, minute, second).unwrap();
+        let mut aggregator = ErrorAggregator::new();
+
+        assert!(aggregator.record("connection refused", at(1, 0), "System DNS @ https://a"));
+        for minute in 2..=15 {
+            assert!(!aggregator.record("connection refused", at(minute, 0), "System DNS @ https://a"));
+        }
+        assert!(!aggregator.record("connection refused", at(5, 0), "DoH @ https://a"));
+        assert!(aggregator.record("timed out", at(3, 30), "System DNS @ https://b"));
+
+        assert_eq!(aggregator.total(), 17);
+        assert_eq!(aggregator.repeated().count(), 1);
+
+        let groups = aggregator.into_groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].count, 16);
+        assert_eq!(groups[0].sources, vec!["System DNS @ https://a", "DoH @ https://a"]);
+        assert_eq!(groups[0].to_string(), "connection refused ×16 (first at 12:01:00, last at 12:15:00)");
+        assert_eq!(groups[1].to_string(), "timed out (at 12:03:30)");
+    }
+}
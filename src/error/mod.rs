@@ -1,264 +1,478 @@
 //! Error handling for the network latency tester
 
+pub mod aggregate;
 pub mod user_messages;
+#[cfg(feature = "native")]
 mod recovery;
 
+pub use aggregate::{ErrorAggregator, ErrorGroup};
+
 pub use user_messages::{
     UserMessageProvider, EnhancedErrorMessage, UserMessageConfig,
     Platform, ExperienceLevel, ResolutionTime,
 };
 
+use std::sync::Arc;
 use thiserror::Error;
 
+/// Underlying error that caused an [`AppError`]
+///
+/// Shared rather than boxed so that `AppError` stays cheap to clone.
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync + 'static>;
+
+/// What went wrong during an update or version operation
+///
+/// Carried by [`AppError::Update`] and [`AppError::Version`] so that retry
+/// decisions and user guidance can depend on the actual failure rather than
+/// on the message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateErrorKind {
+    /// The version string is not a valid semantic version
+    InvalidVersion,
+    /// A pre-release version was requested without allowing pre-releases
+    PrereleaseNotAllowed,
+    /// The target version is older than the installed one and `--force` was not given
+    DowngradeRequiresForce,
+    /// No release source (cache, feeds, GitHub API) could be reached
+    SourceUnavailable,
+    /// The GitHub API rate limit is exhausted
+    RateLimited,
+    /// No releases were found to choose from
+    NoReleases,
+    /// Any other update failure
+    Other,
+}
+
+impl UpdateErrorKind {
+    /// Whether the failure concerns the requested version rather than the update process
+    pub fn is_version_problem(&self) -> bool {
+        matches!(self, Self::InvalidVersion | Self::PrereleaseNotAllowed | Self::DowngradeRequiresForce)
+    }
+
+    /// Whether retrying later may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::SourceUnavailable | Self::RateLimited)
+    }
+
+    /// Short suggestion shown with the error
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            Self::InvalidVersion => "Check the version format (e.g., '1.2.3' or 'v1.2.3').",
+            Self::PrereleaseNotAllowed => "Request a stable release, or allow pre-releases explicitly.",
+            Self::DowngradeRequiresForce => "Add --force to install an older version.",
+            Self::SourceUnavailable => "Check your network connection and try again later.",
+            Self::RateLimited => "Wait until the GitHub API rate limit resets, then try again.",
+            Self::NoReleases => "Check https://github.com/MaurUppi/network-latency-tester/releases for published versions.",
+            Self::Other => "Check your network connection, verify the version exists, or try again later.",
+        }
+    }
+}
+
 /// Custom error types for the network latency tester
+///
+/// Every variant carries a message and, when the error wraps a lower-level
+/// failure (reqwest, trust-dns, I/O, ...), that failure as its `source()`.
 #[derive(Error, Debug, Clone)]
 pub enum AppError {
     /// Configuration-related errors
     #[error("Configuration error: {0}")]
-    Config(String),
+    Config(String, #[source] Option<ErrorSource>),
 
     /// Network connectivity errors
     #[error("Network error: {0}")]
-    Network(String),
+    Network(String, #[source] Option<ErrorSource>),
 
     /// DNS resolution errors
     #[error("DNS resolution error: {0}")]
-    DnsResolution(String),
+    DnsResolution(String, #[source] Option<ErrorSource>),
 
     /// HTTP request errors
     #[error("HTTP request error: {0}")]
-    HttpRequest(String),
+    HttpRequest(String, #[source] Option<ErrorSource>),
 
     /// Timeout errors
     #[error("Timeout error: {0}")]
-    Timeout(String),
+    Timeout(String, #[source] Option<ErrorSource>),
 
     /// Validation errors
     #[error("Validation error: {0}")]
-    Validation(String),
+    Validation(String, #[source] Option<ErrorSource>),
 
     /// I/O errors (file operations, etc.)
     #[error("I/O error: {0}")]
-    Io(String),
+    Io(String, #[source] Option<ErrorSource>),
 
     /// Parsing errors (URLs, JSON, etc.)
     #[error("Parsing error: {0}")]
-    Parse(String),
+    Parse(String, #[source] Option<ErrorSource>),
 
     /// Authentication/authorization errors
     #[error("Authentication error: {0}")]
-    Auth(String),
+    Auth(String, #[source] Option<ErrorSource>),
 
     /// Test execution errors
     #[error("Test execution error: {0}")]
-    TestExecution(String),
+    TestExecution(String, #[source] Option<ErrorSource>),
 
     /// Statistics calculation errors
     #[error("Statistics error: {0}")]
-    Statistics(String),
+    Statistics(String, #[source] Option<ErrorSource>),
 
     /// Update-related errors
     #[error("Update error: {0}")]
-    Update(String),
+    Update(String, UpdateErrorKind, #[source] Option<ErrorSource>),
 
     /// Version parsing or comparison errors
     #[error("Version error: {0}")]
-    Version(String),
+    Version(String, UpdateErrorKind, #[source] Option<ErrorSource>),
 
     /// Geographic detection errors
     #[error("Geographic detection error: {0}")]
-    Geographic(String),
+    Geographic(String, #[source] Option<ErrorSource>),
 
     /// Cache-related errors
     #[error("Cache error: {0}")]
-    Cache(String),
+    Cache(String, #[source] Option<ErrorSource>),
 
     /// Generic internal errors
     #[error("Internal error: {0}")]
-    Internal(String),
+    Internal(String, #[source] Option<ErrorSource>),
+
+    /// Operations stopped through a cancellation token, e.g. by Ctrl-C
+    #[error("Cancelled: {0}")]
+    Cancelled(String, #[source] Option<ErrorSource>),
 }
 
 impl AppError {
     /// Create a new configuration error
     pub fn config<S: Into<String>>(message: S) -> Self {
-        Self::Config(message.into())
+        Self::Config(message.into(), None)
     }
 
     /// Create a new network error
     pub fn network<S: Into<String>>(message: S) -> Self {
-        Self::Network(message.into())
+        Self::Network(message.into(), None)
     }
 
     /// Create a new DNS resolution error
     pub fn dns_resolution<S: Into<String>>(message: S) -> Self {
-        Self::DnsResolution(message.into())
+        Self::DnsResolution(message.into(), None)
     }
 
     /// Create a new HTTP request error
     pub fn http_request<S: Into<String>>(message: S) -> Self {
-        Self::HttpRequest(message.into())
+        Self::HttpRequest(message.into(), None)
     }
 
     /// Create a new timeout error
     pub fn timeout<S: Into<String>>(message: S) -> Self {
-        Self::Timeout(message.into())
+        Self::Timeout(message.into(), None)
     }
 
     /// Create a new validation error
     pub fn validation<S: Into<String>>(message: S) -> Self {
-        Self::Validation(message.into())
+        Self::Validation(message.into(), None)
     }
 
     /// Create a new I/O error
     pub fn io<S: Into<String>>(message: S) -> Self {
-        Self::Io(message.into())
+        Self::Io(message.into(), None)
     }
 
     /// Create a new parsing error
     pub fn parse<S: Into<String>>(message: S) -> Self {
-        Self::Parse(message.into())
+        Self::Parse(message.into(), None)
     }
 
     /// Create a new authentication error
     pub fn auth<S: Into<String>>(message: S) -> Self {
-        Self::Auth(message.into())
+        Self::Auth(message.into(), None)
     }
 
     /// Create a new test execution error
     pub fn test_execution<S: Into<String>>(message: S) -> Self {
-        Self::TestExecution(message.into())
+        Self::TestExecution(message.into(), None)
     }
 
     /// Create a new statistics error
     pub fn statistics<S: Into<String>>(message: S) -> Self {
-        Self::Statistics(message.into())
+        Self::Statistics(message.into(), None)
     }
 
     /// Create a new update error
     pub fn update<S: Into<String>>(message: S) -> Self {
-        Self::Update(message.into())
+        Self::Update(message.into(), UpdateErrorKind::Other, None)
     }
 
-    /// Create a new version error
+    /// Create a new version error for an invalid version
     pub fn version<S: Into<String>>(message: S) -> Self {
-        Self::Version(message.into())
+        Self::Version(message.into(), UpdateErrorKind::InvalidVersion, None)
+    }
+
+    /// Create an update or version error of a specific kind
+    ///
+    /// Problems with the requested version become [`AppError::Version`];
+    /// everything else becomes [`AppError::Update`].
+    pub fn update_error<S: Into<String>>(kind: UpdateErrorKind, message: S) -> Self {
+        if kind.is_version_problem() {
+            Self::Version(message.into(), kind, None)
+        } else {
+            Self::Update(message.into(), kind, None)
+        }
+    }
+
+    /// The kind of update failure, for update and version errors
+    pub fn update_kind(&self) -> Option<UpdateErrorKind> {
+        match self {
+            Self::Update(_, kind, _) | Self::Version(_, kind, _) => Some(*kind),
+            _ => None,
+        }
     }
 
     /// Create a new geographic detection error
     pub fn geographic<S: Into<String>>(message: S) -> Self {
-        Self::Geographic(message.into())
+        Self::Geographic(message.into(), None)
     }
 
     /// Create a new cache error
     pub fn cache<S: Into<String>>(message: S) -> Self {
-        Self::Cache(message.into())
+        Self::Cache(message.into(), None)
     }
 
     /// Create a new internal error
     pub fn internal<S: Into<String>>(message: S) -> Self {
-        Self::Internal(message.into())
+        Self::Internal(message.into(), None)
+    }
+
+    /// Create a new cancellation error
+    pub fn cancelled<S: Into<String>>(message: S) -> Self {
+        Self::Cancelled(message.into(), None)
+    }
+
+    /// Whether the operation was cancelled rather than failed
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled(..))
+    }
+
+    /// Attach the lower-level error that caused this one
+    pub fn with_source<E>(mut self, error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        *self.source_mut() = Some(Arc::new(error));
+        self
+    }
+
+    /// The error message without the category prefix
+    pub fn message(&self) -> &str {
+        self.parts().0
+    }
+
+    /// The lower-level error attached with [`with_source`](Self::with_source), if any
+    ///
+    /// Unlike [`std::error::Error::source`], the returned reference can be
+    /// downcast to the concrete error type.
+    pub fn underlying(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        self.parts().1.as_deref()
+    }
+
+    /// Find the first error of type `E` in the cause chain
+    ///
+    /// Allows matching on the underlying failure, e.g.
+    /// `error.find_source::<std::io::Error>().map(|e| e.kind())`.
+    pub fn find_source<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        let mut current = self.underlying().map(|error| error as &(dyn std::error::Error + 'static));
+        while let Some(error) = current {
+            if let Some(found) = error.downcast_ref::<E>() {
+                return Some(found);
+            }
+            // A nested AppError exposes its source through the shared wrapper, which cannot be downcast
+            current = match error.downcast_ref::<AppError>() {
+                Some(app_error) => app_error.underlying().map(|error| error as &(dyn std::error::Error + 'static)),
+                None => error.source(),
+            };
+        }
+        None
+    }
+
+    /// Causes of this error that add information beyond its own message, outermost first
+    ///
+    /// Causes whose text already appears in the message (or in the previous
+    /// cause) are skipped, since many wrappers repeat their source's text.
+    pub fn causes(&self) -> Vec<String> {
+        let mut seen = self.to_string();
+        let mut causes = Vec::new();
+        let mut current = std::error::Error::source(self);
+        while let Some(error) = current {
+            let text = error.to_string();
+            if !text.is_empty() && !seen.contains(&text) {
+                seen = text.clone();
+                causes.push(text);
+            }
+            current = error.source();
+        }
+        causes
+    }
+
+    /// The message followed by every informative cause, separated by `: `
+    pub fn display_chain(&self) -> String {
+        std::iter::once(self.to_string())
+            .chain(self.causes())
+            .collect::<Vec<_>>()
+            .join(": ")
+    }
+
+    fn parts(&self) -> (&str, &Option<ErrorSource>) {
+        match self {
+            Self::Config(message, source)
+            | Self::Network(message, source)
+            | Self::DnsResolution(message, source)
+            | Self::HttpRequest(message, source)
+            | Self::Timeout(message, source)
+            | Self::Validation(message, source)
+            | Self::Io(message, source)
+            | Self::Parse(message, source)
+            | Self::Auth(message, source)
+            | Self::TestExecution(message, source)
+            | Self::Statistics(message, source)
+            | Self::Update(message, _, source)
+            | Self::Version(message, _, source)
+            | Self::Geographic(message, source)
+            | Self::Cache(message, source)
+            | Self::Internal(message, source)
+            | Self::Cancelled(message, source) => (message, source),
+        }
+    }
+
+    fn source_mut(&mut self) -> &mut Option<ErrorSource> {
+        match self {
+            Self::Config(_, source)
+            | Self::Network(_, source)
+            | Self::DnsResolution(_, source)
+            | Self::HttpRequest(_, source)
+            | Self::Timeout(_, source)
+            | Self::Validation(_, source)
+            | Self::Io(_, source)
+            | Self::Parse(_, source)
+            | Self::Auth(_, source)
+            | Self::TestExecution(_, source)
+            | Self::Statistics(_, source)
+            | Self::Update(_, _, source)
+            | Self::Version(_, _, source)
+            | Self::Geographic(_, source)
+            | Self::Cache(_, source)
+            | Self::Internal(_, source)
+            | Self::Cancelled(_, source) => source,
+        }
     }
 
     /// Get error category for logging and reporting
     pub fn category(&self) -> &'static str {
         match self {
-            Self::Config(_) => "CONFIG",
-            Self::Network(_) => "NETWORK",
-            Self::DnsResolution(_) => "DNS",
-            Self::HttpRequest(_) => "HTTP",
-            Self::Timeout(_) => "TIMEOUT",
-            Self::Validation(_) => "VALIDATION",
-            Self::Io(_) => "IO",
-            Self::Parse(_) => "PARSE",
-            Self::Auth(_) => "AUTH",
-            Self::TestExecution(_) => "TEST",
-            Self::Statistics(_) => "STATS",
-            Self::Update(_) => "UPDATE",
-            Self::Version(_) => "VERSION",
-            Self::Geographic(_) => "GEOGRAPHIC",
-            Self::Cache(_) => "CACHE",
-            Self::Internal(_) => "INTERNAL",
+            Self::Config(..) => "CONFIG",
+            Self::Network(..) => "NETWORK",
+            Self::DnsResolution(..) => "DNS",
+            Self::HttpRequest(..) => "HTTP",
+            Self::Timeout(..) => "TIMEOUT",
+            Self::Validation(..) => "VALIDATION",
+            Self::Io(..) => "IO",
+            Self::Parse(..) => "PARSE",
+            Self::Auth(..) => "AUTH",
+            Self::TestExecution(..) => "TEST",
+            Self::Statistics(..) => "STATS",
+            Self::Update(..) => "UPDATE",
+            Self::Version(..) => "VERSION",
+            Self::Geographic(..) => "GEOGRAPHIC",
+            Self::Cache(..) => "CACHE",
+            Self::Internal(..) => "INTERNAL",
+            Self::Cancelled(..) => "CANCELLED",
         }
     }
 
     /// Check if error is recoverable (can retry)
     pub fn is_recoverable(&self) -> bool {
         match self {
-            Self::Network(_) | Self::HttpRequest(_) | Self::Timeout(_) | Self::DnsResolution(_) => true,
-            Self::Geographic(_) | Self::Cache(_) => true,  // Network-related, can retry
-            Self::Config(_) | Self::Validation(_) | Self::Parse(_) | Self::Auth(_) => false,
-            Self::Update(_) | Self::Version(_) => false,  // Business logic errors, not retryable
-            Self::Io(_) | Self::TestExecution(_) | Self::Statistics(_) | Self::Internal(_) => false,
+            Self::Network(..) | Self::HttpRequest(..) | Self::Timeout(..) | Self::DnsResolution(..) => true,
+            Self::Geographic(..) | Self::Cache(..) => true,  // Network-related, can retry
+            Self::Config(..) | Self::Validation(..) | Self::Parse(..) | Self::Auth(..) => false,
+            // Unreachable sources and rate limits clear up; version problems do not
+            Self::Update(_, kind, _) | Self::Version(_, kind, _) => kind.is_transient(),
+            Self::Io(..) | Self::TestExecution(..) | Self::Statistics(..) | Self::Internal(..) => false,
+            Self::Cancelled(..) => false,
         }
     }
 
     /// Get user-friendly error message with suggestions
     pub fn user_friendly_message(&self) -> String {
         match self {
-            Self::Config(msg) => {
+            Self::Config(msg, _) => {
                 format!("Configuration problem: {}\n\nSuggestion: Check your .env file or command line arguments.", msg)
             }
-            Self::Network(msg) => {
+            Self::Network(msg, _) => {
                 format!("Network connectivity issue: {}\n\nSuggestion: Check your internet connection and try again.", msg)
             }
-            Self::DnsResolution(msg) => {
+            Self::DnsResolution(msg, _) => {
                 format!("DNS resolution failed: {}\n\nSuggestion: Try using different DNS servers (8.8.8.8, 1.1.1.1) or check if the domain exists.", msg)
             }
-            Self::HttpRequest(msg) => {
+            Self::HttpRequest(msg, _) => {
                 format!("HTTP request failed: {}\n\nSuggestion: The target server may be down or blocking requests. Try a different URL or check if it requires authentication.", msg)
             }
-            Self::Timeout(msg) => {
+            Self::Timeout(msg, _) => {
                 format!("Request timed out: {}\n\nSuggestion: Increase the timeout value using --timeout or check your network connection.", msg)
             }
-            Self::Validation(msg) => {
+            Self::Validation(msg, _) => {
                 format!("Invalid input: {}\n\nSuggestion: Check the format of your URLs, IP addresses, or other configuration values.", msg)
             }
-            Self::Io(msg) => {
+            Self::Io(msg, _) => {
                 format!("File operation failed: {}\n\nSuggestion: Check file permissions and disk space.", msg)
             }
-            Self::Parse(msg) => {
+            Self::Parse(msg, _) => {
                 format!("Failed to parse data: {}\n\nSuggestion: Check the format of your input data or configuration files.", msg)
             }
-            Self::Auth(msg) => {
+            Self::Auth(msg, _) => {
                 format!("Authentication failed: {}\n\nSuggestion: Check your credentials or API keys.", msg)
             }
-            Self::TestExecution(msg) => {
+            Self::TestExecution(msg, _) => {
                 format!("Test execution failed: {}\n\nSuggestion: This may be a temporary issue. Try running the test again.", msg)
             }
-            Self::Statistics(msg) => {
+            Self::Statistics(msg, _) => {
                 format!("Statistics calculation failed: {}\n\nSuggestion: This may indicate insufficient or invalid test data.", msg)
             }
-            Self::Update(msg) => {
-                format!("Update operation failed: {}\n\nSuggestion: Check your network connection, verify the version exists, or try again later.", msg)
+            Self::Update(msg, kind, _) => {
+                format!("Update operation failed: {}\n\nSuggestion: {}", msg, kind.suggestion())
             }
-            Self::Version(msg) => {
-                format!("Version handling error: {}\n\nSuggestion: Check the version format (e.g., '1.2.3' or 'v1.2.3') or use --force for downgrades.", msg)
+            Self::Version(msg, kind, _) => {
+                format!("Version handling error: {}\n\nSuggestion: {}", msg, kind.suggestion())
             }
-            Self::Geographic(msg) => {
+            Self::Geographic(msg, _) => {
                 format!("Geographic detection failed: {}\n\nSuggestion: This won't prevent updates - the system will use global download URLs.", msg)
             }
-            Self::Cache(msg) => {
+            Self::Cache(msg, _) => {
                 format!("Cache operation failed: {}\n\nSuggestion: The cache will be rebuilt automatically. This may cause slower initial requests.", msg)
             }
-            Self::Internal(msg) => {
+            Self::Internal(msg, _) => {
                 format!("Internal error: {}\n\nThis is likely a bug. Please report this issue with the error details.", msg)
             }
+            Self::Cancelled(msg, _) => {
+                format!("Cancelled: {}", msg)
+            }
         }
     }
 
     /// Get exit code for this error type
     pub fn exit_code(&self) -> i32 {
         match self {
-            Self::Config(_) | Self::Validation(_) | Self::Parse(_) => 1,  // Invalid configuration/usage
-            Self::Network(_) | Self::DnsResolution(_) | Self::HttpRequest(_) => 2,  // Network issues
-            Self::Timeout(_) => 3,  // Timeout issues
-            Self::Auth(_) => 4,  // Authentication issues
-            Self::Io(_) => 5,  // I/O issues
-            Self::TestExecution(_) | Self::Statistics(_) => 6,  // Test execution issues
-            Self::Update(_) => 7,  // Update operation issues
-            Self::Version(_) => 8,  // Version handling issues
-            Self::Geographic(_) | Self::Cache(_) => 9,  // Recoverable auxiliary service issues
-            Self::Internal(_) => 99,  // Internal/unexpected errors
+            Self::Config(..) | Self::Validation(..) | Self::Parse(..) => 1,  // Invalid configuration/usage
+            Self::Network(..) | Self::DnsResolution(..) | Self::HttpRequest(..) => 2,  // Network issues
+            Self::Timeout(..) => 3,  // Timeout issues
+            Self::Auth(..) => 4,  // Authentication issues
+            Self::Io(..) => 5,  // I/O issues
+            Self::TestExecution(..) | Self::Statistics(..) => 6,  // Test execution issues
+            Self::Update(..) => 7,  // Update operation issues
+            Self::Version(..) => 8,  // Version handling issues
+            Self::Geographic(..) | Self::Cache(..) => 9,  // Recoverable auxiliary service issues
+            Self::Internal(..) => 99,  // Internal/unexpected errors
+            Self::Cancelled(..) => 130,  // Interrupted, as shells report SIGINT
         }
     }
 
@@ -270,30 +484,33 @@ impl AppError {
         if use_color {
             use colored::Colorize;
             match self {
-                Self::Config(_) | Self::Validation(_) | Self::Parse(_) => {
+                Self::Config(..) | Self::Validation(..) | Self::Parse(..) => {
                     format!("[{}] {}", category.red().bold(), message.red())
                 }
-                Self::Network(_) | Self::DnsResolution(_) | Self::HttpRequest(_) => {
+                Self::Network(..) | Self::DnsResolution(..) | Self::HttpRequest(..) => {
                     format!("[{}] {}", category.yellow().bold(), message.yellow())
                 }
-                Self::Timeout(_) => {
+                Self::Timeout(..) => {
                     format!("[{}] {}", category.blue().bold(), message.blue())
                 }
-                Self::Auth(_) => {
+                Self::Auth(..) => {
                     format!("[{}] {}", category.magenta().bold(), message.magenta())
                 }
-                Self::Io(_) | Self::TestExecution(_) | Self::Statistics(_) => {
+                Self::Io(..) | Self::TestExecution(..) | Self::Statistics(..) => {
                     format!("[{}] {}", category.cyan().bold(), message.cyan())
                 }
-                Self::Update(_) | Self::Version(_) => {
+                Self::Update(..) | Self::Version(..) => {
                     format!("[{}] {}", category.bright_yellow().bold(), message.bright_yellow())
                 }
-                Self::Geographic(_) | Self::Cache(_) => {
+                Self::Geographic(..) | Self::Cache(..) => {
                     format!("[{}] {}", category.green().bold(), message.green())
                 }
-                Self::Internal(_) => {
+                Self::Internal(..) => {
                     format!("[{}] {}", category.bright_red().bold(), message.bright_red())
                 }
+                Self::Cancelled(..) => {
+                    format!("[{}] {}", category.dimmed(), message.dimmed())
+                }
             }
         } else {
             format!("[{}] {}", category, message)
@@ -304,81 +521,89 @@ impl AppError {
 // Standard library error conversions
 impl From<std::io::Error> for AppError {
     fn from(error: std::io::Error) -> Self {
-        Self::io(error.to_string())
+        Self::io(error.to_string()).with_source(error)
     }
 }
 
 impl From<url::ParseError> for AppError {
     fn from(error: url::ParseError) -> Self {
-        Self::parse(format!("URL parse error: {}", error))
+        Self::parse(format!("URL parse error: {}", error)).with_source(error)
     }
 }
 
 impl From<serde_json::Error> for AppError {
     fn from(error: serde_json::Error) -> Self {
-        Self::parse(format!("JSON parse error: {}", error))
+        Self::parse(format!("JSON parse error: {}", error)).with_source(error)
     }
 }
 
+#[cfg(feature = "native")]
 impl From<reqwest::Error> for AppError {
     fn from(error: reqwest::Error) -> Self {
+        let message = error.to_string();
         if error.is_timeout() {
-            Self::timeout(error.to_string())
+            Self::timeout(message).with_source(error)
         } else if error.is_connect() || error.is_request() {
-            Self::network(error.to_string())
+            Self::network(message).with_source(error)
         } else {
-            Self::http_request(error.to_string())
+            Self::http_request(message).with_source(error)
         }
     }
 }
 
+#[cfg(feature = "native")]
 impl From<trust_dns_resolver::error::ResolveError> for AppError {
     fn from(error: trust_dns_resolver::error::ResolveError) -> Self {
-        Self::dns_resolution(error.to_string())
+        Self::dns_resolution(error.to_string()).with_source(error)
     }
 }
 
+#[cfg(feature = "native")]
 impl From<dotenv::Error> for AppError {
     fn from(error: dotenv::Error) -> Self {
-        Self::config(format!("Environment file error: {}", error))
+        Self::config(format!("Environment file error: {}", error)).with_source(error)
     }
 }
 
 impl From<std::num::ParseIntError> for AppError {
     fn from(error: std::num::ParseIntError) -> Self {
-        Self::parse(format!("Integer parse error: {}", error))
+        Self::parse(format!("Integer parse error: {}", error)).with_source(error)
     }
 }
 
 impl From<std::num::ParseFloatError> for AppError {
     fn from(error: std::num::ParseFloatError) -> Self {
-        Self::parse(format!("Float parse error: {}", error))
+        Self::parse(format!("Float parse error: {}", error)).with_source(error)
     }
 }
 
 impl From<std::str::ParseBoolError> for AppError {
     fn from(error: std::str::ParseBoolError) -> Self {
-        Self::parse(format!("Boolean parse error: {}", error))
+        Self::parse(format!("Boolean parse error: {}", error)).with_source(error)
     }
 }
 
 impl From<std::net::AddrParseError> for AppError {
     fn from(error: std::net::AddrParseError) -> Self {
-        Self::parse(format!("IP address parse error: {}", error))
+        Self::parse(format!("IP address parse error: {}", error)).with_source(error)
     }
 }
 
 // Anyhow integration
 impl From<anyhow::Error> for AppError {
     fn from(error: anyhow::Error) -> Self {
-        Self::internal(error.to_string())
+        let message = error.to_string();
+        let mut app_error = Self::internal(message);
+        *app_error.source_mut() = Some(Arc::from(Box::<dyn std::error::Error + Send + Sync>::from(error)));
+        app_error
     }
 }
 
 // Update-specific error conversions
+#[cfg(feature = "updater")]
 impl From<semver::Error> for AppError {
     fn from(error: semver::Error) -> Self {
-        Self::version(format!("Semantic version error: {}", error))
+        Self::version(format!("Semantic version error: {}", error)).with_source(error)
     }
 }
 
@@ -410,7 +635,7 @@ where
         self.map_err(|e| {
             let original_error = e.into();
             let context = f();
-            AppError::internal(format!("{}: {}", context, original_error))
+            AppError::internal(format!("{}: {}", context, original_error)).with_source(original_error)
         })
     }
 
@@ -422,7 +647,7 @@ where
 /// Helper function to create context for errors
 pub fn context<T>(message: &'static str) -> impl Fn(AppError) -> AppError {
     let msg = message.to_string();
-    move |error| AppError::internal(format!("{}: {}", msg, error))
+    move |error| AppError::internal(format!("{}: {}", msg, error)).with_source(error)
 }
 
 /// Error reporter for structured error logging and user feedback
@@ -501,6 +726,7 @@ impl Default for ErrorReporter {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
 
@@ -525,6 +751,33 @@ mod tests {
         assert!(display.contains("Test configuration error"));
     }
 
+    #[test]
+    fn test_error_source_chain() {
+        use std::error::Error as _;
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+        let error = AppError::network("HTTP request failed").with_source(io_error);
+
+        assert_eq!(error.message(), "HTTP request failed");
+        assert_eq!(error.source().unwrap().to_string(), "connection refused");
+        assert_eq!(
+            error.find_source::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::ConnectionRefused)
+        );
+        assert!(error.find_source::<std::num::ParseIntError>().is_none());
+        assert_eq!(error.display_chain(), "Network error: HTTP request failed: connection refused");
+
+        // Sources survive conversion and context wrapping
+        let parse_error: AppError = "x".parse::<u32>().unwrap_err().into();
+        assert!(parse_error.find_source::<std::num::ParseIntError>().is_some());
+
+        let wrapped = Err::<(), _>(error).context("Fetching release notes").unwrap_err();
+        assert_eq!(wrapped.category(), "INTERNAL");
+        assert!(wrapped.find_source::<std::io::Error>().is_some());
+        // Causes already spelled out in the message are not repeated
+        assert_eq!(wrapped.causes(), vec!["connection refused"]);
+    }
+
     #[test]
     fn test_error_categories() {
         let errors = [
@@ -576,6 +829,26 @@ mod tests {
         assert!(!AppError::version("test").is_recoverable());
     }
 
+    #[test]
+    fn test_update_error_kinds() {
+        let downgrade = AppError::update_error(UpdateErrorKind::DowngradeRequiresForce, "1.0.0 -> 0.9.0");
+        assert_eq!(downgrade.category(), "VERSION");
+        assert_eq!(downgrade.exit_code(), 8);
+        assert!(!downgrade.is_recoverable());
+        assert!(downgrade.user_friendly_message().contains("--force"));
+
+        let rate_limited = AppError::update_error(UpdateErrorKind::RateLimited, "limit resets at 1700000000");
+        assert_eq!(rate_limited.category(), "UPDATE");
+        assert_eq!(rate_limited.exit_code(), 7);
+        assert!(rate_limited.is_recoverable());
+        assert!(rate_limited.user_friendly_message().contains("rate limit resets"));
+
+        assert!(AppError::update_error(UpdateErrorKind::SourceUnavailable, "offline").is_recoverable());
+        assert_eq!(AppError::version("1.x").update_kind(), Some(UpdateErrorKind::InvalidVersion));
+        assert_eq!(AppError::update("failed").update_kind(), Some(UpdateErrorKind::Other));
+        assert_eq!(AppError::config("bad").update_kind(), None);
+    }
+
     #[test]
     fn test_exit_codes() {
         assert_eq!(AppError::config("test").exit_code(), 1);
@@ -589,6 +862,7 @@ mod tests {
         assert_eq!(AppError::geographic("test").exit_code(), 9);
         assert_eq!(AppError::cache("test").exit_code(), 9);
         assert_eq!(AppError::internal("test").exit_code(), 99);
+        assert_eq!(AppError::cancelled("test").exit_code(), 130);
     }
 
     #[test]
@@ -674,11 +948,12 @@ mod tests {
         assert!(formatted_color.contains("Test error"));
         
         // Both should contain the basic structure even if colors don't work in test environment
-        assert!(formatted_no_color.len() > 0);
-        assert!(formatted_color.len() > 0);
+        assert!(!formatted_no_color.is_empty());
+        assert!(!formatted_color.is_empty());
     }
 
     #[test]
+    #[cfg(feature = "native")]
     fn test_reqwest_error_conversion() {
         // Create a simple reqwest error for testing conversion
         // We can't easily create specific error types, so we test the general conversion
@@ -710,6 +985,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "native")]
     fn test_dotenv_error_conversion() {
         let dotenv_error = dotenv::Error::LineParse(".env".to_string(), 1);
         let app_error: AppError = dotenv_error.into();
@@ -823,10 +1099,11 @@ mod tests {
             AppError::geographic("geographic"),
             AppError::cache("cache"),
             AppError::internal("internal"),
+            AppError::cancelled("cancelled"),
         ];
 
         // Each should have the correct category
-        let expected = ["CONFIG", "NETWORK", "DNS", "HTTP", "TIMEOUT", "VALIDATION", "IO", "PARSE", "AUTH", "TEST", "STATS", "UPDATE", "VERSION", "GEOGRAPHIC", "CACHE", "INTERNAL"];
+        let expected = ["CONFIG", "NETWORK", "DNS", "HTTP", "TIMEOUT", "VALIDATION", "IO", "PARSE", "AUTH", "TEST", "STATS", "UPDATE", "VERSION", "GEOGRAPHIC", "CACHE", "INTERNAL", "CANCELLED"];
         
         for (error, expected_category) in errors.iter().zip(expected.iter()) {
             assert_eq!(error.category(), *expected_category);
@@ -858,6 +1135,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "updater")]
     fn test_semver_error_conversion() {
         // Test that semver errors convert properly
         let invalid_semver = "not.a.version";
@@ -166,7 +166,7 @@ impl RecoveryStrategy {
 
 /// Circuit breaker state for preventing cascade failures
 #[derive(Debug, Clone, PartialEq)]
-enum CircuitBreakerState {
+pub(crate) enum CircuitBreakerState {
     /// Circuit is closed (normal operation)
     Closed,
     /// Circuit is open (failing fast)
@@ -457,7 +457,7 @@ impl ErrorRecoveryManager {
         let mut strategies = Vec::new();
         
         match error {
-            AppError::Network(_) => {
+            AppError::Network(..) => {
                 // Network errors: retry with backoff, then try DNS fallback
                 strategies.push(RecoveryStrategy::Retry { delay: self.config.initial_delay });
                 strategies.push(RecoveryStrategy::ExponentialBackoff { 
@@ -474,7 +474,7 @@ impl ErrorRecoveryManager {
                 }
             }
             
-            AppError::DnsResolution(_) => {
+            AppError::DnsResolution(..) => {
                 // DNS errors: try fallback DNS servers
                 if self.config.enable_dns_fallback {
                     for dns_server in &self.config.fallback_dns_servers {
@@ -491,7 +491,7 @@ impl ErrorRecoveryManager {
                 }
             }
             
-            AppError::HttpRequest(_) => {
+            AppError::HttpRequest(..) => {
                 // HTTP errors: retry with backoff, then timeout adjustment
                 strategies.push(RecoveryStrategy::ExponentialBackoff { 
                     base_delay: self.config.initial_delay, 
@@ -505,7 +505,7 @@ impl ErrorRecoveryManager {
                 }
             }
             
-            AppError::Timeout(_) => {
+            AppError::Timeout(..) => {
                 // Timeout errors: increase timeout and retry
                 if self.config.enable_adaptive_timeout {
                     strategies.push(RecoveryStrategy::TimeoutAdjustment { 
@@ -519,7 +519,7 @@ impl ErrorRecoveryManager {
                 strategies.push(RecoveryStrategy::Retry { delay: Duration::from_secs(2) });
             }
             
-            AppError::Validation(_) => {
+            AppError::Validation(..) => {
                 // Validation errors: try URL correction
                 if self.config.enable_url_validation {
                     strategies.push(RecoveryStrategy::UrlValidation { corrected_url: None });
@@ -718,14 +718,12 @@ mod tests {
     
     #[test]
     fn test_recovery_strategy_naming() {
-        let strategies = vec![
-            RecoveryStrategy::Retry { delay: Duration::from_millis(100) },
+        let strategies = [RecoveryStrategy::Retry { delay: Duration::from_millis(100) },
             RecoveryStrategy::ExponentialBackoff { base_delay: Duration::from_millis(100), multiplier: 2.0 },
             RecoveryStrategy::DnsFallback { fallback_server: "8.8.8.8".to_string() },
             RecoveryStrategy::TimeoutAdjustment { new_timeout: Duration::from_secs(30) },
             RecoveryStrategy::UrlValidation { corrected_url: None },
-            RecoveryStrategy::CircuitBreakerReset,
-        ];
+            RecoveryStrategy::CircuitBreakerReset];
         
         let expected_names = ["retry", "exponential_backoff", "dns_fallback", "timeout_adjustment", "url_validation", "circuit_breaker_reset"];
         
@@ -3,7 +3,7 @@
 //! This module provides enhanced error messaging with detailed troubleshooting tips,
 //! platform-specific guidance, and contextual help for common issues users encounter.
 
-use super::AppError;
+use super::{AppError, UpdateErrorKind};
 use crate::models::Config;
 use std::collections::HashMap;
 use colored::Colorize;
@@ -211,22 +211,23 @@ impl UserMessageProvider {
     /// Generate enhanced message for error
     fn generate_enhanced_message(&self, error: &AppError) -> EnhancedErrorMessage {
         match error {
-            AppError::Config(_) => self.generate_config_error_message(error),
-            AppError::Network(_) => self.generate_network_error_message(error),
-            AppError::DnsResolution(_) => self.generate_dns_error_message(error),
-            AppError::HttpRequest(_) => self.generate_http_error_message(error),
-            AppError::Timeout(_) => self.generate_timeout_error_message(error),
-            AppError::Validation(_) => self.generate_validation_error_message(error),
-            AppError::Io(_) => self.generate_io_error_message(error),
-            AppError::Parse(_) => self.generate_parse_error_message(error),
-            AppError::Auth(_) => self.generate_auth_error_message(error),
-            AppError::TestExecution(_) => self.generate_test_execution_error_message(error),
-            AppError::Statistics(_) => self.generate_statistics_error_message(error),
-            AppError::Update(_) => self.generate_update_error_message(error),
-            AppError::Version(_) => self.generate_version_error_message(error),
-            AppError::Geographic(_) => self.generate_geographic_error_message(error),
-            AppError::Cache(_) => self.generate_cache_error_message(error),
-            AppError::Internal(_) => self.generate_internal_error_message(error),
+            AppError::Config(..) => self.generate_config_error_message(error),
+            AppError::Network(..) => self.generate_network_error_message(error),
+            AppError::DnsResolution(..) => self.generate_dns_error_message(error),
+            AppError::HttpRequest(..) => self.generate_http_error_message(error),
+            AppError::Timeout(..) => self.generate_timeout_error_message(error),
+            AppError::Validation(..) => self.generate_validation_error_message(error),
+            AppError::Io(..) => self.generate_io_error_message(error),
+            AppError::Parse(..) => self.generate_parse_error_message(error),
+            AppError::Auth(..) => self.generate_auth_error_message(error),
+            AppError::TestExecution(..) => self.generate_test_execution_error_message(error),
+            AppError::Statistics(..) => self.generate_statistics_error_message(error),
+            AppError::Update(..) => self.generate_update_error_message(error),
+            AppError::Version(..) => self.generate_version_error_message(error),
+            AppError::Geographic(..) => self.generate_geographic_error_message(error),
+            AppError::Cache(..) => self.generate_cache_error_message(error),
+            AppError::Internal(..) => self.generate_internal_error_message(error),
+            AppError::Cancelled(..) => self.generate_cancelled_message(error),
         }
     }
     
@@ -801,6 +802,23 @@ impl UserMessageProvider {
             resolution_time: ResolutionTime::Advanced,
         }
     }
+
+    /// Generate cancellation message
+    fn generate_cancelled_message(&self, _error: &AppError) -> EnhancedErrorMessage {
+        EnhancedErrorMessage {
+            message: "Operation Cancelled".to_string(),
+            description: "The operation was stopped before it finished, e.g. with Ctrl-C. Nothing is wrong with the network or the configuration.".to_string(),
+            immediate_actions: vec![
+                "Run the command again to finish the measurement".to_string(),
+            ],
+            troubleshooting_steps: vec![],
+            help_topics: vec![],
+            platform_notes: vec![],
+            examples: vec![],
+            is_common: true,
+            resolution_time: ResolutionTime::Quick,
+        }
+    }
     
     /// Format enhanced message for console display
     pub fn format_enhanced_message(&self, message: &EnhancedErrorMessage) -> String {
@@ -1097,15 +1115,36 @@ impl UserMessageProvider {
     }
     
     /// Generate update error message
-    fn generate_update_error_message(&self, _error: &AppError) -> EnhancedErrorMessage {
+    fn generate_update_error_message(&self, error: &AppError) -> EnhancedErrorMessage {
+        let (message, immediate_actions) = match error.update_kind() {
+            Some(UpdateErrorKind::RateLimited) => (
+                "GitHub API Rate Limit Reached",
+                vec![
+                    "Wait for the rate limit to reset (usually within an hour)".to_string(),
+                    "Try again later; cached release data is used when available".to_string(),
+                ],
+            ),
+            Some(UpdateErrorKind::NoReleases) => (
+                "No Releases Found",
+                vec![
+                    "Check the releases page for published versions".to_string(),
+                    "Try again in a few minutes".to_string(),
+                ],
+            ),
+            _ => (
+                "Update Operation Failed",
+                vec![
+                    "Check your network connection".to_string(),
+                    "Verify the version exists".to_string(),
+                    "Try again in a few minutes".to_string(),
+                ],
+            ),
+        };
+
         EnhancedErrorMessage {
-            message: "Update Operation Failed".to_string(),
+            message: message.to_string(),
             description: "Unable to complete the update operation. This could be due to network connectivity issues, version availability, or permission problems.".to_string(),
-            immediate_actions: vec![
-                "Check your network connection".to_string(),
-                "Verify the version exists".to_string(),
-                "Try again in a few minutes".to_string(),
-            ],
+            immediate_actions,
             troubleshooting_steps: vec![
                 TroubleshootingStep {
                     number: 1,
@@ -1140,9 +1179,15 @@ impl UserMessageProvider {
     }
 
     /// Generate version error message
-    fn generate_version_error_message(&self, _error: &AppError) -> EnhancedErrorMessage {
+    fn generate_version_error_message(&self, error: &AppError) -> EnhancedErrorMessage {
+        let message = match error.update_kind() {
+            Some(UpdateErrorKind::DowngradeRequiresForce) => "Downgrade Requires --force",
+            Some(UpdateErrorKind::PrereleaseNotAllowed) => "Pre-release Version Not Allowed",
+            _ => "Version Format Error",
+        };
+
         EnhancedErrorMessage {
-            message: "Version Format Error".to_string(),
+            message: message.to_string(),
             description: "The version string provided is not in a valid format or the specified version could not be processed.".to_string(),
             immediate_actions: vec![
                 "Check the version format (e.g., '1.2.3' or 'v1.2.3')".to_string(),
@@ -0,0 +1,289 @@
+//! Interleaved A/B runs for the `ab` subcommand
+//!
+//! Whether connection pooling, HTTP/2 or another resolver makes a service
+//! faster is hard to tell from two runs one after the other: by the time the
+//! second one starts, the network may have changed. [`AbExperiment`] runs two
+//! configurations in the same process instead, one iteration of each per
+//! round with the two taking turns at going first, and pairs the samples of
+//! every round into a [`PairedComparison`] per target and DNS configuration.
+//!
+//! Each side is described by a TOML file of configuration variables, such as
+//! `HTTP2_PRIOR_KNOWLEDGE = true` or `DNS_SERVERS = ["8.8.8.8", "1.1.1.1"]`,
+//! applied over the environment and `.env`. Command-line arguments apply to
+//! both sides.
+
+use super::{create_executor_for_mode, ExecutionMode};
+use crate::{
+    cli::Cli,
+    config::{parser::load_config_with, reload, ConfigValidator, EnvManager},
+    error::{AppError, Result},
+    models::{Config, Name, TestResult},
+    stats::PairedComparison,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// One side of the experiment
+#[derive(Debug, Clone)]
+pub struct AbVariant {
+    /// File the side was read from
+    pub path: PathBuf,
+    pub config: Config,
+}
+
+impl AbVariant {
+    /// Load the side described by the TOML file at `path`
+    pub fn load(cli: &Cli, path: &Path) -> Result<Self> {
+        let settings = read_variant_file(path)?;
+        let config = load_config_with(cli.clone(), &settings)
+            .map_err(|e| AppError::config(format!("Invalid configuration in {}: {}", path.display(), e)).with_source(e))?;
+        Ok(Self { path: path.to_path_buf(), config })
+    }
+}
+
+/// Read the configuration variables of a variant file
+pub fn read_variant_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}: {}", path.display(), e)).with_source(e))?;
+    let settings = parse_variant(&text)
+        .map_err(|e| AppError::config(format!("Invalid variant file {}: {}", path.display(), e)))?;
+    let unknown = ConfigValidator::unknown_keys(&settings);
+    if !unknown.is_empty() {
+        return Err(AppError::config(format!(
+            "{} sets unknown configuration keys: {} (see --help-topic config for the supported ones)",
+            path.display(), unknown.join(", ")
+        )));
+    }
+    for (key, value) in &settings {
+        EnvManager::validate_env_var(key, value)?;
+    }
+    Ok(settings)
+}
+
+/// Turn the top-level keys of a TOML document into configuration variables;
+/// keys are case-insensitive and arrays become comma-separated lists
+fn parse_variant(text: &str) -> std::result::Result<Vec<(String, String)>, String> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    fn scalar(key: &str, value: &toml::Value) -> std::result::Result<String, String> {
+        match value {
+            toml::Value::String(text) => Ok(text.clone()),
+            toml::Value::Integer(number) => Ok(number.to_string()),
+            toml::Value::Float(number) => Ok(number.to_string()),
+            toml::Value::Boolean(flag) => Ok(flag.to_string()),
+            _ => Err(format!("{} must be a string, number, boolean or a list of them", key)),
+        }
+    }
+    table.iter()
+        .map(|(key, value)| {
+            let key = key.to_uppercase();
+            let value = match value {
+                toml::Value::Array(items) => items.iter()
+                    .map(|item| scalar(&key, item))
+                    .collect::<std::result::Result<Vec<_>, _>>()?
+                    .join(","),
+                value => scalar(&key, value)?,
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Samples of one target and DNS configuration
+#[derive(Debug, Clone, Default)]
+struct Samples {
+    /// Rounds in which both sides tested it, as `(a, b)` total times
+    pairs: Vec<(f64, f64)>,
+    a_failures: u32,
+    b_failures: u32,
+    /// Rounds in which only one side tested it
+    unpaired: u32,
+}
+
+/// Two configurations run in alternating order, round by round
+pub struct AbExperiment {
+    a: AbVariant,
+    b: AbVariant,
+    rounds: u32,
+}
+
+impl AbExperiment {
+    pub fn new(a: AbVariant, b: AbVariant, rounds: u32) -> Self {
+        Self { a, b, rounds }
+    }
+
+    /// Run every round and compare the sides
+    pub async fn run(&self) -> Result<AbReport> {
+        let a_executor = create_executor_for_mode(&round_config(&self.a.config), ExecutionMode::Optimized).await?;
+        let b_executor = create_executor_for_mode(&round_config(&self.b.config), ExecutionMode::Optimized).await?;
+        let a_dns = self.a.config.create_dns_configs()?;
+        let b_dns = self.b.config.create_dns_configs()?;
+
+        let mut samples: BTreeMap<(Name, Name), Samples> = BTreeMap::new();
+        for round in 0..self.rounds {
+            // Taking turns at going first keeps whatever the first run warms up from favouring one side
+            let (a_results, b_results) = if round.is_multiple_of(2) {
+                let a = a_executor.execute_tests(&self.a.config.target_urls, &a_dns).await?;
+                (a, b_executor.execute_tests(&self.b.config.target_urls, &b_dns).await?)
+            } else {
+                let b = b_executor.execute_tests(&self.b.config.target_urls, &b_dns).await?;
+                (a_executor.execute_tests(&self.a.config.target_urls, &a_dns).await?, b)
+            };
+            record_round(&mut samples, &a_results, &b_results);
+        }
+
+        let targets = samples.into_iter()
+            .map(|((url, dns), samples)| AbTarget {
+                url: url.into(),
+                dns: dns.into(),
+                comparison: PairedComparison::of(&samples.pairs),
+                a_failures: samples.a_failures,
+                b_failures: samples.b_failures,
+                unpaired: samples.unpaired,
+            })
+            .collect();
+        Ok(AbReport {
+            a: self.a.path.display().to_string(),
+            b: self.b.path.display().to_string(),
+            rounds: self.rounds,
+            differences: reload::diff(&round_config(&self.a.config), &round_config(&self.b.config)),
+            targets,
+        })
+    }
+}
+
+/// The configuration of a side for a single round: one iteration, and none
+/// of the extra probes that run once per test run
+fn round_config(config: &Config) -> Config {
+    Config {
+        test_count: 1,
+        auto_count: None,
+        max_runtime: None,
+        loss_probe: None,
+        ping: None,
+        ntp_server: None,
+        ..config.clone()
+    }
+}
+
+/// Pair the samples of one round by target and DNS configuration name
+fn record_round(samples: &mut BTreeMap<(Name, Name), Samples>, a: &[TestResult], b: &[TestResult]) {
+    // Total time of the round's request, or none when it failed
+    let sample = |result: &TestResult| result.individual_results.first()
+        .filter(|metrics| metrics.is_successful())
+        .map(|metrics| metrics.total_ms());
+    let key = |result: &TestResult| (result.url.clone(), result.config_name.clone());
+
+    for a_result in a {
+        let entry = samples.entry(key(a_result)).or_default();
+        let a_sample = sample(a_result);
+        entry.a_failures += u32::from(a_sample.is_none());
+        match b.iter().find(|b_result| key(b_result) == key(a_result)) {
+            Some(b_result) => {
+                let b_sample = sample(b_result);
+                entry.b_failures += u32::from(b_sample.is_none());
+                if let (Some(a_sample), Some(b_sample)) = (a_sample, b_sample) {
+                    entry.pairs.push((a_sample, b_sample));
+                }
+            }
+            None => entry.unpaired += 1,
+        }
+    }
+    for b_result in b.iter().filter(|b_result| !a.iter().any(|a_result| key(a_result) == key(b_result))) {
+        let entry = samples.entry(key(b_result)).or_default();
+        entry.b_failures += u32::from(sample(b_result).is_none());
+        entry.unpaired += 1;
+    }
+}
+
+/// Comparison of the two sides for one target and DNS configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbTarget {
+    pub url: String,
+    /// Name of the DNS configuration
+    pub dns: String,
+    /// None when no round produced a sample on both sides
+    pub comparison: Option<PairedComparison>,
+    pub a_failures: u32,
+    pub b_failures: u32,
+    /// Rounds in which only one side tested this combination
+    pub unpaired: u32,
+}
+
+/// Result of an A/B experiment
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbReport {
+    /// Files of the two sides
+    pub a: String,
+    pub b: String,
+    pub rounds: u32,
+    /// Settings in which B differs from A
+    pub differences: Vec<String>,
+    pub targets: Vec<AbTarget>,
+}
+
+impl fmt::Display for AbReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "A/B comparison over {} rounds", self.rounds)?;
+        writeln!(f, "  A: {}", self.a)?;
+        writeln!(f, "  B: {}", self.b)?;
+        if self.differences.is_empty() {
+            writeln!(f, "  The two configurations are the same")?;
+        }
+        for difference in &self.differences {
+            writeln!(f, "  {}", difference)?;
+        }
+        for target in &self.targets {
+            writeln!(f)?;
+            writeln!(f, "{} with {}", target.url, target.dns)?;
+            match target.comparison {
+                Some(ref comparison) => writeln!(f, "  {}", comparison)?,
+                None if target.unpaired > 0 => writeln!(f, "  Tested by one side only")?,
+                None => writeln!(f, "  No round succeeded on both sides")?,
+            }
+            if target.a_failures + target.b_failures > 0 {
+                writeln!(f, "  Failed requests: A {}, B {}", target.a_failures, target.b_failures)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::TimingMetrics, test_support::result_with_totals};
+
+    #[test]
+    fn test_parse_variant() {
+        let settings = parse_variant("# pooling off\nhttp2_prior_knowledge = true\nTEST_COUNT = 5\nDNS_SERVERS = [\"8.8.8.8\", \"1.1.1.1\"]\n").unwrap();
+        assert_eq!(settings, [
+            ("DNS_SERVERS".to_string(), "8.8.8.8,1.1.1.1".to_string()),
+            ("TEST_COUNT".to_string(), "5".to_string()),
+            ("HTTP2_PRIOR_KNOWLEDGE".to_string(), "true".to_string()),
+        ]);
+        assert!(parse_variant("[section]\nkey = 1\n").unwrap_err().contains("SECTION must be"));
+        assert!(parse_variant("TIMEOUT =").is_err());
+    }
+
+    #[test]
+    fn test_record_round() {
+        let result = |config: &str, total_ms: Option<u64>| {
+            let mut result = result_with_totals(config, "https://example.com", total_ms.as_slice());
+            if total_ms.is_none() {
+                result.add_measurement(TimingMetrics::failed("Connection refused".to_string()));
+            }
+            result
+        };
+        let mut samples = BTreeMap::new();
+        record_round(&mut samples, &[result("System DNS", Some(100)), result("A only", Some(50))], &[result("System DNS", Some(80))]);
+        record_round(&mut samples, &[result("System DNS", None)], &[result("System DNS", Some(90))]);
+
+        let system = &samples[&(Name::new("https://example.com"), Name::new("System DNS"))];
+        assert_eq!(system.pairs, [(100.0, 80.0)]);
+        assert_eq!((system.a_failures, system.b_failures, system.unpaired), (1, 0, 0));
+        let a_only = &samples[&(Name::new("https://example.com"), Name::new("A only"))];
+        assert!(a_only.pairs.is_empty());
+        assert_eq!(a_only.unpaired, 1);
+    }
+}
@@ -0,0 +1,341 @@
+//! Bounded event channels for subscribers slower than the run
+//!
+//! An [`EventBus::channel`](super::EventBus::channel) is unbounded: a sink
+//! that writes to a remote service or redraws a screen and falls behind the
+//! executor keeps every event it has not handled yet, one per request. A
+//! bounded channel holds at most its capacity of events and, once full,
+//! handles the next one by its [`OverflowPolicy`]:
+//!
+//! - [`Block`](OverflowPolicy::Block) makes the publishing test wait for room,
+//!   slowing the run down to the subscriber
+//! - [`DropOldest`](OverflowPolicy::DropOldest) discards the oldest queued
+//!   event, counting it in [`EventReceiver::dropped`]
+//! - [`Aggregate`](OverflowPolicy::Aggregate) folds the samples of each test
+//!   into one [`ExecutionEvent::SamplesAggregated`] that is delivered before
+//!   the test's `ConfigFinished`; the other events always fit, as there are
+//!   only a few per test
+
+use super::events::ExecutionEvent;
+use crate::{error::AppError, models::Name};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// What a full bounded channel does with the next event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the subscriber to make room
+    #[default]
+    Block,
+    /// Discard the oldest queued event
+    DropOldest,
+    /// Fold the samples of each test into a summary
+    Aggregate,
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverflowPolicy::Block => write!(f, "block"),
+            OverflowPolicy::DropOldest => write!(f, "drop-oldest"),
+            OverflowPolicy::Aggregate => write!(f, "aggregate"),
+        }
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "block" => Ok(OverflowPolicy::Block),
+            "drop-oldest" | "drop_oldest" => Ok(OverflowPolicy::DropOldest),
+            "aggregate" => Ok(OverflowPolicy::Aggregate),
+            other => Err(AppError::validation(format!(
+                "Unknown overflow policy '{}': expected block, drop-oldest or aggregate", other
+            ))),
+        }
+    }
+}
+
+/// Samples of one test folded together by [`OverflowPolicy::Aggregate`]
+#[derive(Debug, Clone, Default)]
+struct PendingSamples {
+    samples: u32,
+    successes: u32,
+    total_ms_sum: f64,
+    max_ms: Option<f64>,
+}
+
+impl PendingSamples {
+    fn into_event(self, (url, config_name): (Name, Name)) -> ExecutionEvent {
+        ExecutionEvent::SamplesAggregated {
+            url,
+            config_name,
+            samples: self.samples,
+            successes: self.successes,
+            mean_ms: (self.successes > 0).then(|| self.total_ms_sum / self.successes as f64),
+            max_ms: self.max_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    events: VecDeque<ExecutionEvent>,
+    /// Samples being aggregated, by URL and configuration
+    pending: HashMap<(Name, Name), PendingSamples>,
+    dropped: u64,
+    /// The bus or the receiver is gone
+    closed: bool,
+}
+
+/// The queue shared by the bus and an [`EventReceiver`]
+#[derive(Debug)]
+pub(super) struct EventQueue {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<QueueState>,
+    readable: Notify,
+    writable: Notify,
+}
+
+impl EventQueue {
+    pub(super) fn new(capacity: usize, policy: OverflowPolicy) -> Arc<Self> {
+        Arc::new(Self {
+            capacity: capacity.max(1),
+            policy,
+            state: Mutex::new(QueueState::default()),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        })
+    }
+
+    /// Queue an event, waiting for room under [`OverflowPolicy::Block`]
+    pub(super) async fn push(&self, event: ExecutionEvent) {
+        if self.policy == OverflowPolicy::Block {
+            loop {
+                // Registered before looking, so room made in between is not missed
+                let writable = self.writable.notified();
+                tokio::pin!(writable);
+                writable.as_mut().enable();
+                {
+                    let mut state = self.state.lock().unwrap();
+                    if state.closed {
+                        return;
+                    }
+                    if state.events.len() < self.capacity {
+                        state.events.push_back(event);
+                        break;
+                    }
+                }
+                writable.await;
+            }
+        } else {
+            let mut state = self.state.lock().unwrap();
+            if state.closed {
+                return;
+            }
+            self.push_without_waiting(&mut state, event);
+        }
+        self.readable.notify_one();
+    }
+
+    fn push_without_waiting(&self, state: &mut QueueState, event: ExecutionEvent) {
+        if self.policy == OverflowPolicy::Aggregate {
+            match event {
+                ExecutionEvent::SampleCompleted { url, config_name, iteration, metrics } => {
+                    let key = (url, config_name);
+                    // Once a test's samples are being folded, later ones join them to keep the order
+                    if state.events.len() >= self.capacity || state.pending.contains_key(&key) {
+                        let pending = state.pending.entry(key).or_default();
+                        pending.samples += 1;
+                        if metrics.is_successful() {
+                            let total_ms = metrics.total_ms();
+                            pending.successes += 1;
+                            pending.total_ms_sum += total_ms;
+                            pending.max_ms = Some(pending.max_ms.map_or(total_ms, |max| max.max(total_ms)));
+                        }
+                    } else {
+                        state.events.push_back(ExecutionEvent::SampleCompleted {
+                            url: key.0,
+                            config_name: key.1,
+                            iteration,
+                            metrics,
+                        });
+                    }
+                }
+                ExecutionEvent::ConfigFinished { ref url, ref config_name, .. } => {
+                    if let Some(pending) = state.pending.remove(&(url.clone(), config_name.clone())) {
+                        let summary = pending.into_event((url.clone(), config_name.clone()));
+                        state.events.push_back(summary);
+                    }
+                    state.events.push_back(event);
+                }
+                ExecutionEvent::RunCompleted { .. } => {
+                    let pending: Vec<_> = state.pending.drain().collect();
+                    for (key, samples) in pending {
+                        state.events.push_back(samples.into_event(key));
+                    }
+                    state.events.push_back(event);
+                }
+                event => state.events.push_back(event),
+            }
+            return;
+        }
+
+        if state.events.len() >= self.capacity {
+            state.events.pop_front();
+            state.dropped += 1;
+        }
+        state.events.push_back(event);
+    }
+
+    /// Stop delivering; queued events can still be received
+    pub(super) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_one();
+        self.writable.notify_waiters();
+    }
+}
+
+/// Receiving end of [`EventBus::bounded_channel`](super::EventBus::bounded_channel)
+#[derive(Debug)]
+pub struct EventReceiver {
+    queue: Arc<EventQueue>,
+}
+
+impl EventReceiver {
+    pub(super) fn new(queue: Arc<EventQueue>) -> Self {
+        Self { queue }
+    }
+
+    /// The next event, or `None` once the bus is gone and the queue is empty
+    pub async fn recv(&mut self) -> Option<ExecutionEvent> {
+        loop {
+            let readable = self.queue.readable.notified();
+            {
+                let mut state = self.queue.state.lock().unwrap();
+                if let Some(event) = state.events.pop_front() {
+                    drop(state);
+                    self.queue.writable.notify_waiters();
+                    return Some(event);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            readable.await;
+        }
+    }
+
+    /// The next event if one is queued
+    pub fn try_recv(&mut self) -> Option<ExecutionEvent> {
+        let event = self.queue.state.lock().unwrap().events.pop_front();
+        if event.is_some() {
+            self.queue.writable.notify_waiters();
+        }
+        event
+    }
+
+    /// Events discarded under [`OverflowPolicy::DropOldest`]
+    pub fn dropped(&self) -> u64 {
+        self.queue.state.lock().unwrap().dropped
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.queue.policy
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        // Nobody listens any more, so publishers must not wait for room
+        self.queue.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{executor::EventBus, test_support::success};
+    use std::time::Duration;
+
+    fn sample(config_name: &str, iteration: u32, total_ms: u64) -> ExecutionEvent {
+        ExecutionEvent::SampleCompleted {
+            url: "https://example.com".into(),
+            config_name: config_name.into(),
+            iteration,
+            metrics: Box::new(success(total_ms)),
+        }
+    }
+
+    fn finished(config_name: &str) -> ExecutionEvent {
+        ExecutionEvent::ConfigFinished {
+            url: "https://example.com".into(),
+            config_name: config_name.into(),
+            iterations: 4,
+            successes: 4,
+            precision: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bounded_channel_policies() {
+        assert_eq!("drop-oldest".parse::<OverflowPolicy>().unwrap(), OverflowPolicy::DropOldest);
+        assert!("spill".parse::<OverflowPolicy>().is_err());
+
+        // Drop-oldest keeps the newest events and counts the rest
+        let bus = EventBus::new();
+        let mut receiver = bus.bounded_channel(2, OverflowPolicy::DropOldest);
+        for iteration in 0..5 {
+            bus.publish(sample("System DNS", iteration, 10)).await;
+        }
+        assert_eq!(receiver.dropped(), 3);
+        assert!(matches!(receiver.try_recv(), Some(ExecutionEvent::SampleCompleted { iteration: 3, .. })));
+        assert!(matches!(receiver.try_recv(), Some(ExecutionEvent::SampleCompleted { iteration: 4, .. })));
+        assert!(receiver.try_recv().is_none());
+
+        // Aggregate folds what does not fit into a summary ahead of ConfigFinished
+        let bus = EventBus::new();
+        let mut receiver = bus.bounded_channel(1, OverflowPolicy::Aggregate);
+        for (iteration, total_ms) in [10, 20, 30, 70].into_iter().enumerate() {
+            bus.publish(sample("System DNS", iteration as u32, total_ms)).await;
+        }
+        bus.publish(finished("System DNS")).await;
+        assert!(matches!(receiver.try_recv(), Some(ExecutionEvent::SampleCompleted { .. })));
+        match receiver.try_recv() {
+            Some(ExecutionEvent::SamplesAggregated { samples, successes, mean_ms, max_ms, .. }) => {
+                assert_eq!((samples, successes), (3, 3));
+                assert_eq!((mean_ms, max_ms), (Some(40.0), Some(70.0)));
+            }
+            other => panic!("expected aggregated samples, got {:?}", other),
+        }
+        assert!(matches!(receiver.try_recv(), Some(ExecutionEvent::ConfigFinished { .. })));
+        assert_eq!(receiver.dropped(), 0);
+
+        // Block holds the publisher until the subscriber makes room
+        let bus = Arc::new(EventBus::new());
+        let mut receiver = bus.bounded_channel(1, OverflowPolicy::Block);
+        bus.publish(sample("System DNS", 0, 10)).await;
+        let publisher = tokio::spawn({
+            let bus = bus.clone();
+            async move { bus.publish(sample("System DNS", 1, 10)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!publisher.is_finished());
+        assert!(matches!(receiver.recv().await, Some(ExecutionEvent::SampleCompleted { iteration: 0, .. })));
+        publisher.await.unwrap();
+        assert!(matches!(receiver.recv().await, Some(ExecutionEvent::SampleCompleted { iteration: 1, .. })));
+
+        // A dropped receiver never holds up the run
+        drop(receiver);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            for iteration in 0..3 {
+                bus.publish(sample("System DNS", iteration, 10)).await;
+            }
+        }).await.unwrap();
+    }
+}
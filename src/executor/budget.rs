@@ -0,0 +1,132 @@
+//! Run-time budget for bounding a whole test run
+//!
+//! When `--max-runtime` is set, every test task shares a [`RunBudget`]. Tasks
+//! ask it how many iterations they may still run: completed requests so far
+//! are extrapolated over the remaining time, and iteration counts are scaled
+//! down proportionally for every configuration when the planned work would not
+//! fit. A small part of the budget is held back so analysis and reporting
+//! also finish before the deadline.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Upper bound on the time reserved for analysis and reporting
+const MAX_REPORT_RESERVE: Duration = Duration::from_secs(5);
+
+/// Shared deadline and progress tracker for one test run
+#[derive(Debug)]
+pub struct RunBudget {
+    started: Instant,
+    deadline: Instant,
+    planned_requests: u64,
+    completed_requests: AtomicU64,
+}
+
+impl RunBudget {
+    /// Create a budget for `planned_requests` requests that must finish within `max_runtime`
+    pub fn new(max_runtime: Duration, planned_requests: u64) -> Self {
+        // Keep 5% (at most 5s) of the budget free for the final report
+        let reserve = (max_runtime / 20).min(MAX_REPORT_RESERVE);
+        let started = Instant::now();
+
+        Self {
+            started,
+            deadline: started + max_runtime - reserve,
+            planned_requests,
+            completed_requests: AtomicU64::new(0),
+        }
+    }
+
+    /// Time left before requests must stop
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline for requests has passed
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Record that a request finished
+    pub fn record_request(&self) {
+        self.completed_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of requests finished so far
+    pub fn completed_requests(&self) -> u64 {
+        self.completed_requests.load(Ordering::Relaxed)
+    }
+
+    /// Iterations each configuration may run so the planned work fits in the remaining time
+    pub fn allowed_iterations(&self, test_count: u32) -> u32 {
+        allowed_iterations(
+            test_count,
+            self.planned_requests,
+            self.completed_requests(),
+            self.started.elapsed(),
+            self.remaining(),
+        )
+    }
+}
+
+/// Scale `test_count` by the share of planned requests that can finish in time
+///
+/// Throughput so far is extrapolated over the remaining time. Until a request
+/// has completed there is nothing to extrapolate from, so the full count is
+/// allowed. At least one iteration is always allowed.
+fn allowed_iterations(
+    test_count: u32,
+    planned_requests: u64,
+    completed_requests: u64,
+    elapsed: Duration,
+    remaining: Duration,
+) -> u32 {
+    if completed_requests == 0 || planned_requests == 0 || elapsed.is_zero() {
+        return test_count;
+    }
+
+    let throughput = completed_requests as f64 / elapsed.as_secs_f64();
+    let achievable = completed_requests as f64 + throughput * remaining.as_secs_f64();
+    let scale = (achievable / planned_requests as f64).min(1.0);
+
+    ((test_count as f64 * scale).floor() as u32).clamp(1, test_count.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_iterations_scales_proportionally() {
+        let secs = Duration::from_secs;
+
+        // No progress yet: nothing to extrapolate from
+        assert_eq!(allowed_iterations(10, 100, 0, secs(5), secs(5)), 10);
+
+        // 10 of 100 requests took 5s; 5s more fits another 10, so 20% of the plan
+        assert_eq!(allowed_iterations(10, 100, 10, secs(5), secs(5)), 2);
+
+        // Plenty of time left: never more than requested
+        assert_eq!(allowed_iterations(10, 100, 50, secs(5), secs(60)), 10);
+
+        // Out of time: still at least one iteration
+        assert_eq!(allowed_iterations(10, 100, 10, secs(5), Duration::ZERO), 1);
+    }
+
+    #[test]
+    fn test_budget_reserves_report_time() {
+        let budget = RunBudget::new(Duration::from_secs(10), 20);
+        let remaining = budget.remaining();
+        assert!(remaining <= Duration::from_millis(9500));
+        assert!(remaining > Duration::from_secs(9));
+        assert!(!budget.is_exhausted());
+
+        budget.record_request();
+        assert_eq!(budget.completed_requests(), 1);
+
+        let budget = RunBudget::new(Duration::ZERO, 20);
+        assert!(budget.is_exhausted());
+    }
+}
@@ -0,0 +1,202 @@
+//! Change of results between watch-mode cycles
+//!
+//! Each `--watch` cycle prints a fresh table, so drift is easy to miss. A
+//! [`CycleHistory`] remembers the average response time of every URL and
+//! configuration, both in the previous cycle and in the first cycle that
+//! measured it (the session baseline), and turns the next cycle's results into
+//! a [`CycleDrift`] that the table formatters show next to each row. With
+//! `--network-history` the history is kept per network across runs (see
+//! [`crate::network`]).
+
+use super::{ExecutionResults, ResultKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Changes smaller than this share of the earlier average count as steady
+const STEADY_PERCENT: f64 = 5.0;
+
+/// Average response times of the cycles of a watch session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CycleHistory {
+    /// Averages of the first cycle that measured each result
+    #[serde(with = "averages_serde")]
+    baseline: HashMap<ResultKey, f64>,
+    /// Averages of the previous cycle
+    #[serde(with = "averages_serde")]
+    previous: HashMap<ResultKey, f64>,
+}
+
+impl CycleHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare a cycle's results with the previous cycle and the baseline,
+    /// then remember them for the next cycle
+    pub fn record(&mut self, results: &ExecutionResults) -> CycleDrift {
+        let mut deltas = HashMap::new();
+        let mut current = HashMap::new();
+        for (key, result) in &results.test_results {
+            let Some(avg_ms) = result.statistics.as_ref().map(|stats| stats.total_avg_ms) else {
+                continue;
+            };
+            let previous = self.previous.get(key).map(|&earlier| Delta::new(avg_ms, earlier));
+            let baseline = self.baseline.get(key).map(|&earlier| Delta::new(avg_ms, earlier));
+            if previous.is_some() || baseline.is_some() {
+                deltas.insert(key.clone(), Drift { previous, baseline });
+            }
+            self.baseline.entry(key.clone()).or_insert(avg_ms);
+            current.insert(key.clone(), avg_ms);
+        }
+        self.previous = current;
+        CycleDrift { deltas }
+    }
+}
+
+/// Change of each result's average response time in one cycle
+#[derive(Debug, Clone, Default)]
+pub struct CycleDrift {
+    deltas: HashMap<ResultKey, Drift>,
+}
+
+impl CycleDrift {
+    /// Drift of one URL and configuration, if it was measured before
+    pub fn get(&self, key: &ResultKey) -> Option<&Drift> {
+        self.deltas.get(key)
+    }
+
+    /// Whether nothing in the cycle was measured before, as in the first cycle
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+}
+
+/// Change of one result's average response time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drift {
+    /// Against the previous cycle, if it measured this result
+    pub previous: Option<Delta>,
+    /// Against the first cycle that measured this result
+    pub baseline: Option<Delta>,
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |delta: Option<Delta>| delta.map_or("-".to_string(), |delta| delta.to_string());
+        write!(f, "{} / {}", describe(self.previous), describe(self.baseline))
+    }
+}
+
+/// Difference between an average response time and an earlier one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delta {
+    /// Current minus earlier average (milliseconds)
+    pub delta_ms: f64,
+    /// The earlier average (milliseconds)
+    pub earlier_ms: f64,
+}
+
+/// Direction of a [`Delta`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Slower,
+    Steady,
+    Faster,
+}
+
+impl Delta {
+    fn new(current_ms: f64, earlier_ms: f64) -> Self {
+        Self { delta_ms: current_ms - earlier_ms, earlier_ms }
+    }
+
+    /// Whether the change is large enough to call it slower or faster
+    pub fn trend(&self) -> Trend {
+        if self.delta_ms.abs() < self.earlier_ms.abs() * STEADY_PERCENT / 100.0 {
+            Trend::Steady
+        } else if self.delta_ms > 0.0 {
+            Trend::Slower
+        } else {
+            Trend::Faster
+        }
+    }
+}
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self.trend() {
+            Trend::Slower => "↑",
+            Trend::Steady => "→",
+            Trend::Faster => "↓",
+        };
+        write!(f, "{}{:+.0}ms", arrow, self.delta_ms)
+    }
+}
+
+/// Serialize averages as a flat list, since JSON maps only have string keys
+mod averages_serde {
+    use super::ResultKey;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct Average {
+        #[serde(flatten)]
+        key: ResultKey,
+        avg_ms: f64,
+    }
+
+    pub fn serialize<S>(map: &HashMap<ResultKey, f64>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut averages: Vec<Average> = map.iter().map(|(key, &avg_ms)| Average { key: key.clone(), avg_ms }).collect();
+        averages.sort_by(|a, b| a.key.cmp(&b.key));
+        averages.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<HashMap<ResultKey, f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let averages = Vec::<Average>::deserialize(deserializer)?;
+        Ok(averages.into_iter().map(|average| (average.key, average.avg_ms)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{executor::ExecutionSummary, test_support::result_with_totals};
+
+    fn cycle(results: &[(&str, u64)]) -> ExecutionResults {
+        let results = results
+            .iter()
+            .map(|&(config_name, total_ms)| result_with_totals(config_name, "https://example.com", &[total_ms]))
+            .collect();
+        let summary = ExecutionSummary::default();
+        ExecutionResults::new(summary, results)
+    }
+
+    #[test]
+    fn test_cycle_drift_against_previous_and_baseline() {
+        let key = |config_name: &str| ResultKey::new("https://example.com", config_name);
+        let mut history = CycleHistory::new();
+        assert!(history.record(&cycle(&[("System DNS", 100)])).is_empty());
+
+        let drift = history.record(&cycle(&[("System DNS", 130), ("DoH", 50)]));
+        let system = drift.get(&key("System DNS")).unwrap();
+        assert_eq!(system.previous.unwrap().trend(), Trend::Slower);
+        assert_eq!(system.to_string(), "↑+30ms / ↑+30ms");
+        assert!(drift.get(&key("DoH")).is_none());
+
+        let drift = history.record(&cycle(&[("System DNS", 102), ("DoH", 50)]));
+        assert_eq!(drift.get(&key("System DNS")).unwrap().to_string(), "↓-28ms / →+2ms");
+        assert_eq!(drift.get(&key("DoH")).unwrap().to_string(), "→+0ms / →+0ms");
+
+        // A result missing from a cycle is compared with the baseline only
+        history.record(&cycle(&[("DoH", 50)]));
+        let drift = history.record(&cycle(&[("System DNS", 90)]));
+        assert_eq!(drift.get(&key("System DNS")).unwrap().to_string(), "- / ↓-10ms");
+    }
+}
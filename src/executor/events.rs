@@ -0,0 +1,228 @@
+//! Events published while tests run
+//!
+//! The executor reports progress as [`ExecutionEvent`]s on an [`EventBus`]
+//! instead of printing it. Anything that follows a run, such as the console
+//! log, an alerting hook, a TUI or a library user, implements
+//! [`EventSubscriber`] (a closure will do) or takes a channel with
+//! [`EventBus::channel`], and sees the same events in the same order. Sinks
+//! that may fall behind the run take an [`EventBus::bounded_channel`] instead.
+
+use super::backpressure::{EventQueue, EventReceiver, OverflowPolicy};
+use crate::{models::{Name, TimingMetrics}, stats::Precision};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc;
+
+/// Something that happened during a run
+#[derive(Debug, Clone)]
+pub enum ExecutionEvent {
+    /// Testing of a URL with a configuration started
+    TestStarted {
+        url: Name,
+        config_name: Name,
+        /// Most iterations the test may run
+        iterations: u32,
+    },
+    /// A request of a test finished, successfully or not
+    SampleCompleted {
+        url: Name,
+        config_name: Name,
+        /// Zero-based iteration of the request
+        iteration: u32,
+        metrics: Box<TimingMetrics>,
+    },
+    /// Testing of a URL with a configuration finished
+    ConfigFinished {
+        url: Name,
+        config_name: Name,
+        /// Requests sent
+        iterations: u32,
+        /// Requests that succeeded
+        successes: u32,
+        /// Precision that ended the test early under `--auto-count`
+        precision: Option<Precision>,
+    },
+    /// Samples of a test that a full [`OverflowPolicy::Aggregate`] channel
+    /// folded together, delivered in their place
+    SamplesAggregated {
+        url: Name,
+        config_name: Name,
+        samples: u32,
+        successes: u32,
+        /// Mean and slowest total time of the successful samples
+        mean_ms: Option<f64>,
+        max_ms: Option<f64>,
+    },
+    /// The test machine itself limited the measurements, so concurrency was reduced
+    ThresholdBreached {
+        description: String,
+    },
+    /// Every test of the run finished
+    RunCompleted {
+        tests: usize,
+        duration: Duration,
+    },
+}
+
+/// Receives the events of a run
+///
+/// Events are delivered on the task that published them, so subscribers
+/// should return quickly; slow work belongs behind [`EventBus::bounded_channel`].
+pub trait EventSubscriber: Send + Sync {
+    fn on_event(&self, event: &ExecutionEvent);
+}
+
+impl<F> EventSubscriber for F
+where
+    F: Fn(&ExecutionEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &ExecutionEvent) {
+        self(event)
+    }
+}
+
+impl EventSubscriber for mpsc::UnboundedSender<ExecutionEvent> {
+    fn on_event(&self, event: &ExecutionEvent) {
+        // A dropped receiver only means nobody listens any more
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Delivers each published event to every subscriber, in subscription order
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+    /// Bounded channels, delivered to after the subscribers
+    queues: RwLock<Vec<Arc<EventQueue>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver future events to `subscriber`
+    pub fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers.write().unwrap().push(subscriber);
+    }
+
+    /// Receive future events through a channel, for async consumers
+    ///
+    /// The channel is unbounded; a consumer that may not keep up with the
+    /// run should take a [`bounded_channel`](Self::bounded_channel).
+    pub fn channel(&self) -> mpsc::UnboundedReceiver<ExecutionEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.subscribe(Arc::new(sender));
+        receiver
+    }
+
+    /// Receive future events through a channel holding at most `capacity`
+    /// of them, handling the rest by `policy`
+    pub fn bounded_channel(&self, capacity: usize, policy: OverflowPolicy) -> EventReceiver {
+        let queue = EventQueue::new(capacity, policy);
+        self.queues.write().unwrap().push(queue.clone());
+        EventReceiver::new(queue)
+    }
+
+    /// Deliver an event to every subscriber, waiting for room in bounded
+    /// channels that block when full
+    pub async fn publish(&self, event: ExecutionEvent) {
+        for subscriber in self.subscribers.read().unwrap().iter() {
+            subscriber.on_event(&event);
+        }
+        let queues = self.queues.read().unwrap().clone();
+        if let Some((last, others)) = queues.split_last() {
+            for queue in others {
+                queue.push(event.clone()).await;
+            }
+            last.push(event).await;
+        }
+    }
+}
+
+impl Drop for EventBus {
+    fn drop(&mut self) {
+        for queue in self.queues.get_mut().unwrap().iter() {
+            queue.close();
+        }
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.read().unwrap().len())
+            .field("bounded_channels", &self.queues.read().unwrap().len())
+            .finish()
+    }
+}
+
+/// The executor's terminal output: per-request progress with `--verbose` and
+/// a warning the first time the test machine limits the measurements
+#[derive(Debug, Default)]
+pub struct ConsoleLog {
+    verbose: bool,
+    warned: AtomicBool,
+}
+
+impl ConsoleLog {
+    pub fn new(verbose: bool) -> Self {
+        Self { verbose, warned: AtomicBool::new(false) }
+    }
+}
+
+impl EventSubscriber for ConsoleLog {
+    fn on_event(&self, event: &ExecutionEvent) {
+        match event {
+            ExecutionEvent::SampleCompleted { url, config_name, iteration, metrics } if self.verbose => {
+                println!("Completed iteration {} for {} with {}: {:?}", iteration + 1, url, config_name, metrics.total_duration);
+            }
+            ExecutionEvent::ConfigFinished { url, config_name, iterations, precision: Some(precision), .. } if self.verbose => {
+                println!("Reached {} for {} with {} after {} iterations", precision, url, config_name, iterations);
+            }
+            ExecutionEvent::ThresholdBreached { description } if !self.warned.swap(true, Ordering::Relaxed) => {
+                eprintln!("Warning: the test machine is limiting the measurements ({}); reducing concurrency", description);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_events_reach_every_subscriber_in_order() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        bus.subscribe(Arc::new(move |event: &ExecutionEvent| {
+            if let ExecutionEvent::TestStarted { config_name, .. } = event {
+                recorder.lock().unwrap().push(config_name.clone());
+            }
+        }));
+        let mut receiver = bus.channel();
+
+        for config_name in ["System DNS", "DoH"] {
+            bus.publish(ExecutionEvent::TestStarted {
+                url: "https://example.com".into(),
+                config_name: config_name.into(),
+                iterations: 5,
+            }).await;
+        }
+        bus.publish(ExecutionEvent::RunCompleted { tests: 2, duration: Duration::from_secs(1) }).await;
+
+        assert_eq!(*seen.lock().unwrap(), ["System DNS", "DoH"]);
+        assert!(matches!(receiver.recv().await, Some(ExecutionEvent::TestStarted { iterations: 5, .. })));
+        assert!(matches!(receiver.recv().await, Some(ExecutionEvent::TestStarted { .. })));
+        assert!(matches!(receiver.recv().await, Some(ExecutionEvent::RunCompleted { tests: 2, .. })));
+        assert!(format!("{:?}", bus).contains("subscribers: 2"));
+    }
+}
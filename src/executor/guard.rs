@@ -0,0 +1,312 @@
+//! Guard against the test machine interfering with its own measurements
+//!
+//! A saturated CPU or exhausted local sockets show up as latency and failures
+//! that have nothing to do with the network under test. [`InterferenceGuard`]
+//! samples CPU usage and counts local socket errors while a run is in
+//! progress. When the machine itself becomes the bottleneck it publishes an
+//! [`ExecutionEvent::ThresholdBreached`], halves the executor's concurrency
+//! and records the affected interval, so the report can point at the samples
+//! that should not be trusted.
+
+use super::events::{EventBus, ExecutionEvent};
+use crate::{models::TimingMetrics, timing};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+/// How often CPU usage and socket errors are evaluated
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// CPU usage, as a percentage of all cores, above which the machine is saturated
+const CPU_SATURATION_PERCENT: f64 = 90.0;
+/// Share of requests failing with local socket errors above which sockets are exhausted
+const LOCAL_ERROR_RATE: f64 = 0.05;
+/// Local socket errors an interval needs before the rate is trusted
+const MIN_LOCAL_ERRORS: u64 = 2;
+
+/// Error messages produced when the test machine runs out of sockets, ports or buffers
+const LOCAL_SOCKET_ERRORS: &[&str] = &[
+    "too many open files",
+    "address already in use",
+    "cannot assign requested address",
+    "can't assign requested address",
+    "no buffer space available",
+    "only one usage of each socket address",
+];
+
+/// Whether a failure was caused by the test machine's own socket limits
+pub fn is_local_socket_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    LOCAL_SOCKET_ERRORS.iter().any(|pattern| message.contains(pattern))
+}
+
+/// Why an interval was flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterferenceReason {
+    /// The CPU was saturated
+    CpuSaturated,
+    /// Requests failed because local sockets, ports or buffers ran out
+    LocalSocketErrors,
+}
+
+impl fmt::Display for InterferenceReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CpuSaturated => write!(f, "CPU saturated"),
+            Self::LocalSocketErrors => write!(f, "local socket errors"),
+        }
+    }
+}
+
+/// A stretch of the run during which the test machine was the bottleneck
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterferenceInterval {
+    /// Start of the interval
+    pub started_at: DateTime<Utc>,
+    /// End of the interval
+    pub ended_at: DateTime<Utc>,
+    /// What was wrong with the machine
+    pub reasons: Vec<InterferenceReason>,
+    /// CPU usage over the interval as a percentage of all cores, where measurable
+    pub cpu_percent: Option<f64>,
+    /// Requests completed during the interval
+    pub requests: u64,
+    /// Requests that failed with local socket errors
+    pub local_errors: u64,
+    /// Concurrency limit the guard set in response
+    pub concurrency: usize,
+}
+
+impl InterferenceInterval {
+    /// Whether a sample taken at `timestamp` falls within this interval
+    pub fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        self.started_at <= timestamp && timestamp <= self.ended_at
+    }
+}
+
+impl fmt::Display for InterferenceInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} UTC", self.started_at.format("%H:%M:%S"), self.ended_at.format("%H:%M:%S"))?;
+        let mut details = Vec::new();
+        if let Some(cpu) = self.cpu_percent.filter(|_| self.reasons.contains(&InterferenceReason::CpuSaturated)) {
+            details.push(format!("CPU {:.0}%", cpu));
+        }
+        if self.reasons.contains(&InterferenceReason::LocalSocketErrors) {
+            details.push(format!("{} of {} requests hit local socket errors", self.local_errors, self.requests));
+        }
+        write!(f, ": {}; concurrency limited to {}", details.join(", "), self.concurrency)
+    }
+}
+
+/// CPU time counters from `/proc/stat`
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    busy: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    #[cfg(target_os = "linux")]
+    fn read() -> Option<Self> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        let fields: Vec<u64> = stat.lines().next()?
+            .split_whitespace()
+            .skip(1)
+            .filter_map(|field| field.parse().ok())
+            .collect();
+        // user nice system idle iowait irq softirq steal
+        let total: u64 = fields.iter().take(8).sum();
+        let idle = fields.get(3)? + fields.get(4).unwrap_or(&0);
+        Some(Self { busy: total - idle, total })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read() -> Option<Self> {
+        // CPU usage is only measured where /proc/stat is available
+        None
+    }
+
+    /// Usage between two readings as a percentage of all cores
+    fn usage_since(&self, earlier: &CpuTimes) -> Option<f64> {
+        let total = self.total.checked_sub(earlier.total).filter(|&t| t > 0)?;
+        let busy = self.busy.saturating_sub(earlier.busy);
+        Some(busy as f64 / total as f64 * 100.0)
+    }
+}
+
+/// Interval bookkeeping shared between the sampler and the executor
+#[derive(Debug)]
+struct GuardState {
+    interval_start: DateTime<Utc>,
+    /// Permits the semaphore was created with, minus those forgotten so far
+    permits: usize,
+    /// Concurrency the guard wants
+    target: usize,
+    intervals: Vec<InterferenceInterval>,
+}
+
+/// Watches the test machine during a run and sheds load when it is the bottleneck
+#[derive(Debug)]
+pub struct InterferenceGuard {
+    limiter: Arc<Semaphore>,
+    requests: AtomicU64,
+    local_errors: AtomicU64,
+    events: Arc<EventBus>,
+    state: Mutex<GuardState>,
+}
+
+impl InterferenceGuard {
+    /// Guard a run whose concurrency is limited by `limiter`, which holds `concurrency` permits
+    pub fn new(limiter: Arc<Semaphore>, concurrency: usize) -> Self {
+        Self::with_events(limiter, concurrency, Arc::new(EventBus::new()))
+    }
+
+    /// Like [`new`](Self::new), publishing breaches on `events`
+    pub fn with_events(limiter: Arc<Semaphore>, concurrency: usize, events: Arc<EventBus>) -> Self {
+        Self {
+            limiter,
+            requests: AtomicU64::new(0),
+            local_errors: AtomicU64::new(0),
+            events,
+            state: Mutex::new(GuardState {
+                interval_start: timing::timestamp(),
+                permits: concurrency,
+                target: concurrency,
+                intervals: Vec::new(),
+            }),
+        }
+    }
+
+    /// Count a completed request
+    pub fn record(&self, metrics: &TimingMetrics) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if metrics.error_message.as_deref().is_some_and(is_local_socket_error) {
+            self.local_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Sample the machine every second until the returned task is aborted
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        self.requests.store(0, Ordering::Relaxed);
+        self.local_errors.store(0, Ordering::Relaxed);
+        self.state.lock().unwrap().interval_start = timing::timestamp();
+
+        let guard = self.clone();
+        tokio::spawn(async move {
+            let mut previous = CpuTimes::read();
+            loop {
+                tokio::time::sleep(SAMPLE_INTERVAL).await;
+                let current = CpuTimes::read();
+                let cpu_percent = current.zip(previous).and_then(|(now, before)| now.usage_since(&before));
+                previous = current;
+                // The state is released by now, as subscribers may ask for the intervals
+                if let Some(interval) = guard.close_interval(cpu_percent) {
+                    guard.events.publish(ExecutionEvent::ThresholdBreached { description: interval.to_string() }).await;
+                }
+            }
+        })
+    }
+
+    /// Intervals in which the machine was the bottleneck, oldest first
+    pub fn intervals(&self) -> Vec<InterferenceInterval> {
+        self.state.lock().unwrap().intervals.clone()
+    }
+
+    /// End the current interval, reacting if the machine was the bottleneck during it
+    fn close_interval(&self, cpu_percent: Option<f64>) -> Option<InterferenceInterval> {
+        let requests = self.requests.swap(0, Ordering::Relaxed);
+        let local_errors = self.local_errors.swap(0, Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap();
+        let started_at = std::mem::replace(&mut state.interval_start, timing::timestamp());
+
+        let mut reasons = Vec::new();
+        if cpu_percent.is_some_and(|cpu| cpu >= CPU_SATURATION_PERCENT) {
+            reasons.push(InterferenceReason::CpuSaturated);
+        }
+        if local_errors >= MIN_LOCAL_ERRORS && local_errors as f64 >= requests as f64 * LOCAL_ERROR_RATE {
+            reasons.push(InterferenceReason::LocalSocketErrors);
+        }
+
+        if !reasons.is_empty() {
+            state.target = (state.target / 2).max(1);
+        }
+        // Permits in use cannot be forgotten until they are returned, so keep trying
+        let excess = state.permits - state.target;
+        state.permits -= self.limiter.forget_permits(excess);
+
+        if reasons.is_empty() {
+            return None;
+        }
+
+        let interval = InterferenceInterval {
+            started_at,
+            ended_at: state.interval_start,
+            reasons,
+            cpu_percent,
+            requests,
+            local_errors,
+            concurrency: state.target,
+        };
+        state.intervals.push(interval.clone());
+        Some(interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_socket_error_detection() {
+        assert!(is_local_socket_error("Network error: error trying to connect: Too many open files (os error 24)"));
+        assert!(is_local_socket_error("Cannot assign requested address (os error 99)"));
+        assert!(!is_local_socket_error("Connection refused (os error 111)"));
+        assert!(!is_local_socket_error("HTTP 503"));
+    }
+
+    #[test]
+    fn test_guard_sheds_concurrency_when_machine_is_bottleneck() {
+        let limiter = Arc::new(Semaphore::new(8));
+        let guard = InterferenceGuard::new(limiter.clone(), 8);
+
+        // A quiet interval changes nothing
+        guard.record(&TimingMetrics::failed("Connection refused".to_string()));
+        assert!(guard.close_interval(Some(40.0)).is_none());
+        assert_eq!(limiter.available_permits(), 8);
+
+        // Saturated CPU halves concurrency, but held permits are only forgotten once returned
+        let held = limiter.clone().try_acquire_many_owned(6).unwrap();
+        let interval = guard.close_interval(Some(97.0)).unwrap();
+        assert_eq!(interval.reasons, [InterferenceReason::CpuSaturated]);
+        assert_eq!(interval.concurrency, 4);
+        assert_eq!(limiter.available_permits(), 0);
+        drop(held);
+        assert!(guard.close_interval(None).is_none());
+        assert_eq!(limiter.available_permits(), 4);
+
+        // Local socket errors count too, and concurrency never drops below one
+        for _ in 0..3 {
+            guard.record(&TimingMetrics::failed("Too many open files (os error 24)".to_string()));
+        }
+        let interval = guard.close_interval(None).unwrap();
+        assert_eq!(interval.reasons, [InterferenceReason::LocalSocketErrors]);
+        assert_eq!(interval.local_errors, 3);
+        assert!(interval.to_string().contains("3 of 3 requests hit local socket errors"));
+        guard.close_interval(Some(100.0));
+        guard.close_interval(Some(100.0));
+        assert_eq!(guard.close_interval(Some(100.0)).unwrap().concurrency, 1);
+        assert_eq!(limiter.available_permits(), 1);
+
+        let intervals = guard.intervals();
+        assert_eq!(intervals.len(), 5);
+        assert!(intervals[0].contains(intervals[0].started_at));
+    }
+}
@@ -0,0 +1,94 @@
+//! Running the matrix from every network interface at once
+//!
+//! A host with ethernet, Wi-Fi and LTE reaches the targets over three
+//! different paths. [`InterfaceExecutor`] binds an [`OptimizedExecutor`] to
+//! each usable interface and runs them side by side, since the interfaces do
+//! not share a link. Results are renamed after their interface with
+//! [`TestResult::on_interface`], so the same configuration from two interfaces
+//! stays apart in the report and `InterfaceMatrix` can line them up.
+
+use super::{ExecutorStatistics, OptimizedExecutor, TestExecutor};
+use crate::{
+    capture::PacketCapture,
+    error::{AppError, Result},
+    models::{Config, TestResult},
+    network::usable_interfaces,
+    types::{BindTarget, DnsConfig},
+};
+use async_trait::async_trait;
+use futures::future::try_join_all;
+
+/// Executor that runs every test once from each usable interface
+pub struct InterfaceExecutor {
+    /// Executors by interface, in name order
+    executors: Vec<(String, OptimizedExecutor)>,
+}
+
+impl InterfaceExecutor {
+    /// Bind an executor to each interface that is up and has a routable address
+    pub async fn new(config: &Config) -> Result<Self> {
+        let interfaces = usable_interfaces(config.timeout).await?;
+        if interfaces.is_empty() {
+            return Err(AppError::network("No usable network interfaces found"));
+        }
+        if config.verbose || config.debug {
+            let names: Vec<&str> = interfaces.iter().map(|interface| interface.name.as_str()).collect();
+            println!("Testing from {} interfaces: {}", names.len(), names.join(", "));
+        }
+
+        let mut executors = Vec::with_capacity(interfaces.len());
+        for interface in interfaces {
+            let bound = Config { bind: Some(BindTarget::Interface(interface.name.clone())), ..config.clone() };
+            executors.push((interface.name, OptimizedExecutor::new(&bound).await?));
+        }
+        Ok(Self { executors })
+    }
+}
+
+#[async_trait]
+impl TestExecutor for InterfaceExecutor {
+    async fn execute_tests(
+        &self,
+        urls: &[String],
+        dns_configs: &[DnsConfig],
+    ) -> Result<Vec<TestResult>> {
+        let runs = try_join_all(self.executors.iter().map(|(interface, executor)| async move {
+            let results = executor.execute_tests(urls, dns_configs).await?;
+            Ok::<_, AppError>(results.into_iter().map(|result| result.on_interface(interface)))
+        }))
+        .await?;
+        Ok(runs.into_iter().flatten().collect())
+    }
+
+    fn get_statistics(&self) -> ExecutorStatistics {
+        let mut statistics = ExecutorStatistics::default();
+        for (interface, executor) in &self.executors {
+            let bound = executor.get_statistics();
+            statistics.memory_usage_bytes = match (statistics.memory_usage_bytes, bound.memory_usage_bytes) {
+                (Some(total), Some(bytes)) => Some(total + bytes),
+                (total, bytes) => total.or(bytes),
+            };
+            statistics.interference.extend(bound.interference);
+            statistics.connection_reuse.extend(bound.connection_reuse.into_iter()
+                .map(|(config_name, reuse)| (TestResult::interface_config_name(&config_name, interface), reuse)));
+            statistics.resolver_failover.extend(bound.resolver_failover.into_iter()
+                .map(|(config_name, failover)| (TestResult::interface_config_name(&config_name, interface), failover)));
+            statistics.server_distribution.extend(bound.server_distribution.into_iter()
+                .map(|(config_name, distribution)| (TestResult::interface_config_name(&config_name, interface), distribution)));
+            statistics.packet_captures.extend(bound.packet_captures.into_iter().map(|capture| PacketCapture {
+                config_name: TestResult::interface_config_name(&capture.config_name, interface),
+                ..capture
+            }));
+        }
+        // The runs overlap, so the intervals of all interfaces are put back in order
+        statistics.interference.sort_by_key(|interval| interval.started_at);
+        statistics
+    }
+
+    async fn reset(&self) -> Result<()> {
+        for (_, executor) in &self.executors {
+            executor.reset().await?;
+        }
+        Ok(())
+    }
+}
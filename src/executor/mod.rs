@@ -5,11 +5,38 @@
 //! - Concurrent execution tuning based on real-time performance feedback
 //! - Memory-efficient execution strategies
 
+pub mod ab;
+pub mod backpressure;
+pub mod budget;
+pub mod drift;
+pub mod events;
+pub mod guard;
+pub mod interfaces;
 pub mod optimized;
+pub mod plan;
+pub mod replay;
+pub mod runtime;
+pub mod shard;
+pub mod sizing;
+pub mod trace;
 pub mod tuning;
 
+pub use ab::{AbExperiment, AbReport, AbVariant};
+pub use backpressure::{EventReceiver, OverflowPolicy};
+pub use budget::RunBudget;
+pub use drift::{CycleDrift, CycleHistory};
+pub use events::{ConsoleLog, EventBus, EventSubscriber, ExecutionEvent};
+pub use guard::{InterferenceGuard, InterferenceInterval, InterferenceReason};
+pub use interfaces::InterfaceExecutor;
+pub use plan::ExecutionPlan;
+pub use replay::{RecordedSample, Replay};
+pub use runtime::RuntimeMode;
+pub use shard::{merge_shards, ShardMerge};
+pub use sizing::RunSizing;
+pub use trace::{RequestTrace, RequestTracer};
+
 pub use optimized::{
-    OptimizedExecutor, ClientPool, PoolConfig, SystemResources,
+    OptimizedExecutor, ClientPool, PoolConfig, SystemResources, SystemSnapshot,
     ExecutorStats, PoolStats,
 };
 
@@ -20,14 +47,19 @@ pub use tuning::{
 // Re-export new execution result types - no need for self:: since they're defined in this module
 
 use crate::{
-    error::Result,
-    models::{Config, TestResult},
-    types::DnsConfig,
-    stats::StatisticalAnalysis,
-    diagnostics::DiagnosticReport,
+    capture::PacketCapture,
+    dns::{ResolverFailover, ServerDistribution},
+    error::{AppError, ErrorAggregator, ErrorGroup, Result},
+    models::{BaselineComparison, Config, LossReport, Name, PhysicalBound, Reproduction, TestResult, TimingMetrics},
+    network::NetworkProfile,
+    timing::ClockReport,
+    types::{DisplayNames, DnsConfig, ProtocolOptions, RedirectPolicy, Shard, TestStatus, Upload, UserAgent},
+    stats::{StatisticalAnalysis, StatisticsEngine},
 };
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::DiagnosticReport;
 use std::time::Duration;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use serde::{Deserialize, Serialize};
 use async_trait::async_trait;
 
@@ -42,6 +74,43 @@ pub struct ExecutionConfig {
     pub verbose: bool,
     /// Enable debug output during execution
     pub debug: bool,
+    /// Time budget for the whole run; iteration counts shrink to fit it
+    #[serde(default)]
+    pub max_runtime: Option<Duration>,
+    /// Precision, in percent, to keep adding iterations for past `test_count`
+    #[serde(default)]
+    pub auto_count: Option<f64>,
+    /// Read kernel TCP statistics of the connection after each request
+    #[serde(default)]
+    pub socket_stats: bool,
+    /// Keep buffers and concurrency small for a single-threaded runtime
+    #[serde(default)]
+    pub single_thread: bool,
+    /// Names results are reported under
+    #[serde(default)]
+    pub display_names: DisplayNames,
+    /// Whether requests follow redirects
+    #[serde(default)]
+    pub redirect_policy: RedirectPolicy,
+    /// Redirects a request may follow before it fails
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: u32,
+    /// HTTP version and TLS choices of the clients
+    #[serde(default)]
+    pub protocol: ProtocolOptions,
+    /// Generated body each request uploads
+    #[serde(default)]
+    pub upload: Option<Upload>,
+    /// Time informational responses with an extra request after each successful one
+    #[serde(default)]
+    pub early_hints: bool,
+    /// Write a debug trace of every request to a file per run
+    #[serde(default)]
+    pub trace_requests: bool,
+}
+
+fn default_max_redirects() -> u32 {
+    crate::defaults::DEFAULT_MAX_REDIRECTS
 }
 
 impl Default for ExecutionConfig {
@@ -51,12 +120,34 @@ impl Default for ExecutionConfig {
             timeout: Duration::from_secs(10),
             verbose: false,
             debug: false,
+            max_runtime: None,
+            auto_count: None,
+            socket_stats: false,
+            single_thread: false,
+            display_names: DisplayNames::default(),
+            redirect_policy: RedirectPolicy::default(),
+            max_redirects: default_max_redirects(),
+            protocol: ProtocolOptions::default(),
+            upload: None,
+            early_hints: false,
+            trace_requests: false,
+        }
+    }
+}
+
+impl ExecutionConfig {
+    /// Most iterations a URL and configuration may get: `test_count`, or with
+    /// `auto_count` up to [`MAX_AUTO_COUNT`](crate::defaults::MAX_AUTO_COUNT)
+    pub fn iteration_limit(&self) -> u32 {
+        match self.auto_count {
+            Some(_) => crate::defaults::MAX_AUTO_COUNT.max(self.test_count),
+            None => self.test_count,
         }
     }
 }
 
 /// Summary of test execution results
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionSummary {
     /// Total execution duration
     pub total_duration: Duration,
@@ -74,6 +165,54 @@ pub struct ExecutionSummary {
     pub success_rate: f64,
     /// Performance summary by configuration
     pub performance_summary: HashMap<String, ConfigPerformance>,
+    /// State of the test machine when the run finished
+    #[serde(default)]
+    pub system: Option<SystemSnapshot>,
+    /// Intervals in which the test machine itself was the bottleneck
+    #[serde(default)]
+    pub interference: Vec<InterferenceInterval>,
+    /// Clock drift during the run and the offset from NTP time, if checked
+    #[serde(default)]
+    pub clock: Option<ClockReport>,
+    /// Share of the target list the run covered, when it was one of several shards
+    #[serde(default)]
+    pub shard: Option<Shard>,
+    /// User-Agent preset the requests were sent with; unknown for older results
+    #[serde(default)]
+    pub user_agent: Option<UserAgent>,
+    /// The `--simulate` settings, when the samples were simulated rather than measured
+    #[serde(default)]
+    pub simulation: Option<String>,
+    /// Tags of target URLs, as given in a `--url-file`
+    #[serde(default)]
+    pub url_tags: BTreeMap<String, Vec<String>>,
+    /// Connection reuse by configuration, where the executor could observe it
+    #[serde(default)]
+    pub connection_reuse: BTreeMap<String, ConnectionReuse>,
+    /// Which resolver answered the lookups of each resolver chain, by configuration
+    #[serde(default)]
+    pub resolver_failover: BTreeMap<String, ResolverFailover>,
+    /// How each server of configurations with a server strategy answered, by configuration
+    #[serde(default)]
+    pub server_distribution: BTreeMap<String, ServerDistribution>,
+    /// Packet captures started by anomalous requests, from `--capture-on-anomaly`
+    #[serde(default)]
+    pub packet_captures: Vec<PacketCapture>,
+    /// Packet loss measured by `--loss-probe`, by configuration and target
+    #[serde(default)]
+    pub packet_loss: Vec<LossReport>,
+    /// Latency compared with public measurements of the same targets, from `--compare-public`
+    #[serde(default)]
+    pub public_baseline: Vec<BaselineComparison>,
+    /// Connect times against the speed-of-light minimum for each route, from `--geo`
+    #[serde(default)]
+    pub physical_bounds: Vec<PhysicalBound>,
+    /// Network the run was made on, with `--network-history`
+    #[serde(default)]
+    pub network: Option<NetworkProfile>,
+    /// The baseline network, when the run was made on another one
+    #[serde(default)]
+    pub baseline_network: Option<NetworkProfile>,
 }
 
 /// Performance metrics for a specific configuration
@@ -87,38 +226,273 @@ pub struct ConfigPerformance {
     pub test_count: u32,
 }
 
+/// How often the requests of a configuration reused a pooled connection
+///
+/// The first request of each test is counted as the cold one that pays for
+/// connection setup, and later ones as warm. The mismatch counts show when
+/// that assumption did not hold, e.g. because the server closed connections
+/// after every response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionReuse {
+    /// Requests whose connection could be identified
+    pub requests: u64,
+    /// Requests sent over a connection an earlier request had opened
+    pub reused: u64,
+    /// First requests that nevertheless reused a connection
+    pub cold_reused: u64,
+    /// Later requests that nevertheless opened a new connection
+    pub warm_fresh: u64,
+}
+
+impl ConnectionReuse {
+    /// Count a measurement, if it records whether its connection was reused
+    pub fn record(&mut self, metrics: &TimingMetrics) {
+        let Some(reused) = metrics.reused_connection else {
+            return;
+        };
+        self.requests += 1;
+        if reused {
+            self.reused += 1;
+        }
+        match (metrics.is_first_use, reused) {
+            (true, true) => self.cold_reused += 1,
+            (false, false) => self.warm_fresh += 1,
+            _ => {}
+        }
+    }
+
+    /// Add the counts of another tally
+    pub fn merge(&mut self, other: &ConnectionReuse) {
+        self.requests += other.requests;
+        self.reused += other.reused;
+        self.cold_reused += other.cold_reused;
+        self.warm_fresh += other.warm_fresh;
+    }
+
+    /// Share of requests that reused a connection (0.0-100.0)
+    pub fn reuse_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.reused as f64 / self.requests as f64 * 100.0
+    }
+
+    /// Whether every request matched its cold or warm classification
+    pub fn matches_first_use(&self) -> bool {
+        self.cold_reused == 0 && self.warm_fresh == 0
+    }
+}
+
+impl std::fmt::Display for ConnectionReuse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} reused ({:.1}%)", self.reused, self.requests, self.reuse_rate())?;
+        if self.warm_fresh > 0 {
+            write!(f, ", {} later requests opened a new connection", self.warm_fresh)?;
+        }
+        if self.cold_reused > 0 {
+            write!(f, ", {} first requests reused one", self.cold_reused)?;
+        }
+        Ok(())
+    }
+}
+
+/// Composite key identifying the result of one DNS configuration against one URL
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ResultKey {
+    /// Target URL that was tested
+    pub url: Name,
+    /// Human-readable name of the DNS configuration
+    pub config_name: Name,
+}
+
+impl ResultKey {
+    /// Create a new result key
+    pub fn new(url: impl Into<Name>, config_name: impl Into<Name>) -> Self {
+        Self {
+            url: url.into(),
+            config_name: config_name.into(),
+        }
+    }
+
+    /// Build the key for an existing test result
+    pub fn for_result(result: &TestResult) -> Self {
+        Self::new(result.url.clone(), result.config_name.clone())
+    }
+}
+
+impl std::fmt::Display for ResultKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} @ {}", self.config_name, self.url)
+    }
+}
+
+/// A single request sample together with the configuration and URL it belongs to
+#[derive(Debug, Clone, Copy)]
+pub struct SampleRef<'a> {
+    /// Human-readable name of the DNS configuration
+    pub config_name: &'a str,
+    /// Target URL that was tested
+    pub url: &'a str,
+    /// Timing and outcome of the individual request
+    pub metrics: &'a TimingMetrics,
+}
+
 /// Complete execution results including all analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResults {
     /// High-level execution summary
     pub execution_summary: ExecutionSummary,
-    /// Detailed test results by configuration
-    pub test_results: HashMap<String, TestResult>,
+    /// Detailed test results by URL and configuration
+    #[serde(with = "result_map_serde")]
+    pub test_results: HashMap<ResultKey, TestResult>,
     /// Statistical analysis (if generated)
     pub statistical_analysis: Option<StatisticalAnalysis>,
     /// Network diagnostics report (if generated)
+    #[cfg(feature = "diagnostics")]
     pub diagnostics_report: Option<DiagnosticReport>,
+    /// Command line that reproduces the run
+    #[serde(default)]
+    pub reproduction: Option<Reproduction>,
 }
 
 impl ExecutionResults {
     /// Create a new ExecutionResults with the given summary and test results
-    pub fn new(execution_summary: ExecutionSummary, test_results: HashMap<String, TestResult>) -> Self {
+    pub fn new(execution_summary: ExecutionSummary, test_results: Vec<TestResult>) -> Self {
+        let test_results = test_results
+            .into_iter()
+            .map(|result| (ResultKey::for_result(&result), result))
+            .collect();
+
         Self {
             execution_summary,
             test_results,
             statistical_analysis: None,
+            #[cfg(feature = "diagnostics")]
             diagnostics_report: None,
+            reproduction: None,
         }
     }
 
-    /// Get the best performing configuration based on average response time
-    pub fn best_config(&self) -> Option<&str> {
+    /// Insert a test result, replacing any previous result for the same URL and configuration
+    pub fn insert_result(&mut self, result: TestResult) -> Option<TestResult> {
+        self.test_results.insert(ResultKey::for_result(&result), result)
+    }
+
+    /// Get the result for a specific URL and configuration
+    pub fn get_result(&self, url: &str, config_name: &str) -> Option<&TestResult> {
+        self.test_results.get(&ResultKey::new(url, config_name))
+    }
+
+    /// Get all results for a target URL, ordered by configuration name
+    pub fn results_for_url(&self, url: &str) -> Vec<&TestResult> {
+        let mut results: Vec<_> = self.test_results
+            .iter()
+            .filter(|(key, _)| key.url == url)
+            .collect();
+        results.sort_by(|a, b| a.0.cmp(b.0));
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Get all results for a DNS configuration, ordered by URL
+    pub fn results_for_config(&self, config_name: &str) -> Vec<&TestResult> {
+        let mut results: Vec<_> = self.test_results
+            .iter()
+            .filter(|(key, _)| key.config_name == config_name)
+            .collect();
+        results.sort_by(|a, b| a.0.cmp(b.0));
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Get the distinct target URLs, sorted
+    pub fn urls(&self) -> Vec<&str> {
+        let mut urls: Vec<&str> = self.test_results.keys().map(|k| k.url.as_str()).collect();
+        urls.sort_unstable();
+        urls.dedup();
+        urls
+    }
+
+    /// Get the distinct configuration names, sorted
+    pub fn config_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.test_results.keys().map(|k| k.config_name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Get the `n` slowest individual requests across all results, slowest first
+    ///
+    /// Failed and timed-out requests are included so that pathological samples
+    /// can be investigated; skipped requests never ran and are left out.
+    pub fn slowest_samples(&self, n: usize) -> Vec<SampleRef<'_>> {
+        let mut samples: Vec<SampleRef<'_>> = self.test_results
+            .values()
+            .flat_map(|result| {
+                result.individual_results.iter().map(move |metrics| SampleRef {
+                    config_name: &result.config_name,
+                    url: &result.url,
+                    metrics,
+                })
+            })
+            .filter(|sample| !matches!(sample.metrics.status, TestStatus::Skipped))
+            .collect();
+
+        samples.sort_by(|a, b| {
+            b.metrics.total_duration
+                .cmp(&a.metrics.total_duration)
+                .then_with(|| a.config_name.cmp(b.config_name))
+                .then_with(|| a.url.cmp(b.url))
+        });
+        samples.truncate(n);
+        samples
+    }
+
+    /// Failed and timed-out requests grouped by identical error message, most frequent first
+    pub fn error_summary(&self) -> Vec<ErrorGroup> {
+        let mut results: Vec<&TestResult> = self.test_results.values().collect();
+        results.sort_by(|a, b| a.config_name.cmp(&b.config_name).then_with(|| a.url.cmp(&b.url)));
+
+        let mut aggregator = ErrorAggregator::new();
+        for result in results {
+            let source = format!("{} @ {}", result.config_name, result.url);
+            for metrics in &result.individual_results {
+                if matches!(metrics.status, TestStatus::Failed | TestStatus::Timeout) {
+                    let message = metrics.error_message.as_deref().unwrap_or("Unknown error");
+                    aggregator.record(message, metrics.timestamp, &source);
+                }
+            }
+        }
+
+        aggregator.into_groups()
+    }
+
+    /// Longest wait a throttling server asked for with `Retry-After`, if any
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.test_results.values()
+            .flat_map(|result| &result.individual_results)
+            .filter_map(|metrics| metrics.throttling.and_then(|throttling| throttling.retry_after))
+            .max()
+    }
+
+    /// Get the best performing result key of the [`best_config`](Self::best_config)
+    pub fn best_result(&self) -> Option<&ResultKey> {
         use crate::utils::comparison::test_result_min_comparator;
+        let best_config = self.best_config()?;
         self.test_results
             .iter()
-            .filter(|(_, result)| result.success_count > 0)
+            .filter(|(key, result)| key.config_name == best_config && result.success_count > 0)
             .min_by(test_result_min_comparator())
-            .map(|(name, _)| name.as_str())
+            .map(|(key, _)| key)
+    }
+
+    /// Get the best performing configuration based on average response time,
+    /// unless it is not separated from the runner-up
+    pub fn best_config(&self) -> Option<&str> {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(self.test_results.values().cloned().collect());
+        let fastest = engine.fastest_config()?;
+        self.test_results.keys()
+            .map(|key| key.config_name.as_str())
+            .find(|config_name| *config_name == fastest)
     }
     
     /// Get the worst performing configuration based on average response time
@@ -128,7 +502,7 @@ impl ExecutionResults {
             .iter()
             .filter(|(_, result)| result.success_count > 0)
             .max_by(test_result_max_comparator())
-            .map(|(name, _)| name.as_str())
+            .map(|(key, _)| key.config_name.as_str())
     }
     
     /// Check if execution had any failures
@@ -137,15 +511,71 @@ impl ExecutionResults {
         self.execution_summary.timeout_tests > 0 ||
         self.execution_summary.success_rate < 95.0
     }
+
+    /// Write the results as JSON so they can be queried later
+    pub fn save_json(&self, path: &std::path::Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::parse(format!("Failed to serialize results: {}", e)).with_source(e))?;
+        std::fs::write(path, json)
+            .map_err(|e| AppError::io(format!("Failed to write results to {}: {}", path.display(), e)).with_source(e))
+    }
+
+    /// Load results previously written with [`ExecutionResults::save_json`]
+    pub fn load_json(path: &std::path::Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| AppError::io(format!("Failed to read results from {}: {}", path.display(), e)).with_source(e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| AppError::parse(format!("Invalid results file {}: {}", path.display(), e)).with_source(e))
+    }
+}
+
+/// Serialize the keyed result map as a flat list, since every `TestResult`
+/// already carries its URL and configuration name
+mod result_map_serde {
+    use super::{ResultKey, TestResult};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S>(map: &HashMap<ResultKey, TestResult>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut results: Vec<(&ResultKey, &TestResult)> = map.iter().collect();
+        results.sort_by(|a, b| a.0.cmp(b.0));
+        let results: Vec<&TestResult> = results.into_iter().map(|(_, result)| result).collect();
+        results.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<HashMap<ResultKey, TestResult>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let results = Vec::<TestResult>::deserialize(deserializer)?;
+        Ok(results
+            .into_iter()
+            .map(|result| (ResultKey::for_result(&result), result))
+            .collect())
+    }
 }
 
 impl From<&Config> for ExecutionConfig {
     fn from(config: &Config) -> Self {
         Self {
             test_count: config.test_count,
-            timeout: Duration::from_secs(config.timeout_seconds),
+            timeout: config.timeout,
             verbose: config.verbose,
             debug: config.debug,
+            max_runtime: config.max_runtime,
+            auto_count: config.auto_count,
+            socket_stats: config.socket_stats,
+            single_thread: config.single_thread,
+            display_names: config.display_names(),
+            redirect_policy: config.redirect_policy,
+            max_redirects: config.max_redirects,
+            protocol: config.protocol_options(),
+            upload: config.upload,
+            early_hints: config.early_hints,
+            trace_requests: config.trace_requests,
         }
     }
 }
@@ -182,6 +612,21 @@ pub struct ExecutorStatistics {
     pub total_execution_duration: Duration,
     /// Current memory usage (if available)
     pub memory_usage_bytes: Option<usize>,
+    /// Intervals in which the test machine itself was the bottleneck
+    #[serde(default)]
+    pub interference: Vec<InterferenceInterval>,
+    /// Connection reuse observed so far, by configuration
+    #[serde(default)]
+    pub connection_reuse: BTreeMap<String, ConnectionReuse>,
+    /// Resolver chain failover so far, by configuration
+    #[serde(default)]
+    pub resolver_failover: BTreeMap<String, ResolverFailover>,
+    /// Server strategy outcomes so far, by configuration
+    #[serde(default)]
+    pub server_distribution: BTreeMap<String, ServerDistribution>,
+    /// Packet captures started by anomalous requests so far
+    #[serde(default)]
+    pub packet_captures: Vec<PacketCapture>,
 }
 
 impl Default for ExecutorStatistics {
@@ -193,6 +638,11 @@ impl Default for ExecutorStatistics {
             avg_execution_time_ms: 0.0,
             total_execution_duration: Duration::ZERO,
             memory_usage_bytes: None,
+            interference: Vec::new(),
+            connection_reuse: BTreeMap::new(),
+            resolver_failover: BTreeMap::new(),
+            server_distribution: BTreeMap::new(),
+            packet_captures: Vec::new(),
         }
     }
 }
@@ -365,6 +815,11 @@ impl TestExecutor for TunedTestExecutor {
             memory_usage_bytes: Some(
                 executor_stats.pool_stats.total_clients * size_of::<reqwest::Client>()
             ),
+            interference: self.optimized_executor.interference(),
+            connection_reuse: executor_stats.connection_reuse,
+            resolver_failover: executor_stats.resolver_failover,
+            server_distribution: executor_stats.server_distribution,
+            packet_captures: executor_stats.packet_captures,
         }
     }
     
@@ -374,11 +829,12 @@ impl TestExecutor for TunedTestExecutor {
 }
 
 /// Execution mode selection for different performance requirements
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum ExecutionMode {
     /// Basic execution without optimizations
     Basic,
     /// Optimized execution with connection pooling
+    #[default]
     Optimized,
     /// Adaptive execution with real-time tuning
     Adaptive,
@@ -386,12 +842,6 @@ pub enum ExecutionMode {
     HighPerformance,
 }
 
-impl Default for ExecutionMode {
-    fn default() -> Self {
-        Self::Optimized
-    }
-}
-
 /// Create the appropriate executor based on execution mode
 pub async fn create_executor_for_mode(
     config: &Config,
@@ -427,6 +877,7 @@ pub async fn create_executor_for_mode(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{result_with_totals, success};
     
     #[tokio::test]
     async fn test_basic_executor_creation() {
@@ -464,11 +915,168 @@ mod tests {
         }
     }
     
+
+    #[test]
+    fn test_execution_results_keyed_by_url_and_config() {
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![
+            result_with_totals("System DNS", "https://a.example", &[60; 5]),
+            result_with_totals("System DNS", "https://b.example", &[50; 5]),
+            result_with_totals("DoH (cloudflare)", "https://a.example", &[100; 5]),
+        ]);
+
+        // Same config against different URLs must not overwrite each other
+        assert_eq!(results.test_results.len(), 3);
+        assert_eq!(results.urls(), vec!["https://a.example", "https://b.example"]);
+        assert_eq!(results.config_names(), vec!["DoH (cloudflare)", "System DNS"]);
+
+        let for_a = results.results_for_url("https://a.example");
+        assert_eq!(for_a.len(), 2);
+        assert!(for_a.iter().all(|r| r.url == "https://a.example"));
+
+        let for_system = results.results_for_config("System DNS");
+        assert_eq!(for_system.len(), 2);
+        assert_eq!(for_system[0].url, "https://a.example");
+
+        assert!(results.get_result("https://b.example", "System DNS").is_some());
+        assert!(results.get_result("https://b.example", "DoH (cloudflare)").is_none());
+
+        let best = results.best_result().unwrap();
+        assert_eq!(best, &ResultKey::new("https://b.example", "System DNS"));
+        assert_eq!(results.best_config(), Some("System DNS"));
+
+        // Too few samples to separate the two configurations
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![
+            result_with_totals("System DNS", "https://a.example", &[50]),
+            result_with_totals("DoH (cloudflare)", "https://a.example", &[80]),
+        ]);
+        assert_eq!(results.best_config(), None);
+        assert_eq!(results.best_result(), None);
+    }
+
+    #[test]
+    fn test_connection_reuse_tally() {
+        let sample = |first_use: bool, reused: Option<bool>| {
+            success(100)
+                .with_first_use(first_use)
+                .with_reused_connection(reused)
+        };
+
+        let mut reuse = ConnectionReuse::default();
+        for metrics in [sample(true, Some(false)), sample(false, Some(true)), sample(false, Some(true)), sample(false, None)] {
+            reuse.record(&metrics);
+        }
+        assert_eq!((reuse.requests, reuse.reused), (3, 2));
+        assert!(reuse.matches_first_use());
+        assert_eq!(reuse.to_string(), "2/3 reused (66.7%)");
+
+        let mut closing = ConnectionReuse::default();
+        closing.record(&sample(true, Some(false)));
+        closing.record(&sample(false, Some(false)));
+        reuse.merge(&closing);
+        assert_eq!(reuse.warm_fresh, 1);
+        assert!(!reuse.matches_first_use());
+        assert_eq!(reuse.to_string(), "2/5 reused (40.0%), 1 later requests opened a new connection");
+    }
+
+    #[test]
+    fn test_resolver_failover_tally() {
+        let mut failover = ResolverFailover::default();
+        failover.record(3, Some(0));
+        failover.record(3, Some(0));
+        failover.record(3, Some(2));
+        failover.record(3, None);
+        assert_eq!((failover.lookups(), failover.fallbacks()), (4, 2));
+        assert_eq!(failover.fallback_rate(), 50.0);
+        assert_eq!(failover.to_string(), "2/4 lookups fell back (50.0%), answered by each resolver: 2, 0, 1, 1 failed entirely");
+
+        // Shards of the same run add up
+        let mut other = ResolverFailover::default();
+        other.record(3, Some(1));
+        failover.merge(&other);
+        assert_eq!(failover.answered_by, [2, 1, 1]);
+        assert_eq!(failover.fallbacks(), 3);
+    }
+
+    #[test]
+    fn test_execution_results_serde_roundtrip() {
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![
+            result_with_totals("System DNS", "https://a.example", &[100]),
+            result_with_totals("System DNS", "https://b.example", &[50]),
+        ]);
+
+        let json = serde_json::to_string(&results).unwrap();
+        let restored: ExecutionResults = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.test_results.len(), 2);
+        assert!(restored.get_result("https://a.example", "System DNS").is_some());
+    }
+
+    #[test]
+    fn test_execution_results_json_file_roundtrip() {
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![
+            result_with_totals("System DNS", "https://a.example", &[100]),
+        ]);
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        results.save_json(file.path()).unwrap();
+        let restored = ExecutionResults::load_json(file.path()).unwrap();
+        assert!(restored.get_result("https://a.example", "System DNS").is_some());
+
+        std::fs::write(file.path(), "not json").unwrap();
+        assert!(matches!(ExecutionResults::load_json(file.path()), Err(AppError::Parse(..))));
+    }
+
+    #[test]
+    fn test_slowest_samples() {
+        let mut slow = result_with_totals("System DNS", "https://a.example", &[100]);
+        slow.add_measurement(TimingMetrics::timeout(Duration::from_secs(2)));
+        slow.add_measurement(TimingMetrics::skipped("not run".to_string()));
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![
+            slow,
+            result_with_totals("DoH (cloudflare)", "https://b.example", &[300]),
+        ]);
+
+        let samples = results.slowest_samples(2);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].metrics.total_duration, Duration::from_secs(2));
+        assert_eq!(samples[0].config_name, "System DNS");
+        assert_eq!(samples[1].url, "https://b.example");
+
+        // Skipped samples are never reported
+        assert_eq!(results.slowest_samples(10).len(), 3);
+        assert!(results.slowest_samples(0).is_empty());
+    }
+
+    #[test]
+    fn test_error_summary_collapses_repeats() {
+        let mut dead = result_with_totals("System DNS", "https://dead.example", &[100]);
+        for _ in 0..3 {
+            dead.add_measurement(TimingMetrics::failed("connection refused".to_string()));
+        }
+        dead.add_measurement(TimingMetrics::timeout(Duration::from_secs(2)));
+        dead.add_measurement(TimingMetrics::skipped("not run".to_string()));
+        let mut other = result_with_totals("DoH (cloudflare)", "https://dead.example", &[100]);
+        other.add_measurement(TimingMetrics::failed("connection refused".to_string()));
+
+        let results = ExecutionResults::new(ExecutionSummary::default(), vec![dead, other]);
+        let groups = results.error_summary();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].message, "connection refused");
+        assert_eq!(groups[0].count, 4);
+        assert_eq!(groups[0].sources, vec![
+            "DoH (cloudflare) @ https://dead.example",
+            "System DNS @ https://dead.example",
+        ]);
+        assert_eq!(groups[1].count, 1);
+        assert!(groups[1].message.contains("timed out"));
+    }
+
     #[test]
     fn test_execution_config_from_config() {
         let config = Config {
             test_count: 10,
-            timeout_seconds: 15,
+            timeout: Duration::from_secs(15),
             verbose: true,
             debug: true,
             ..Default::default()
@@ -479,5 +1087,10 @@ mod tests {
         assert_eq!(exec_config.timeout, Duration::from_secs(15));
         assert!(exec_config.verbose);
         assert!(exec_config.debug);
+        assert_eq!(exec_config.iteration_limit(), 10);
+
+        let exec_config = ExecutionConfig::from(&Config { auto_count: Some(2.0), ..config });
+        assert_eq!(exec_config.auto_count, Some(2.0));
+        assert_eq!(exec_config.iteration_limit(), crate::defaults::MAX_AUTO_COUNT);
     }
 }
\ No newline at end of file
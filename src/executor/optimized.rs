@@ -7,31 +7,57 @@
 //! - Adaptive timeout management
 
 use crate::{
-    dns::DnsManager,
-    error::{AppError, Result},
-    executor::{ExecutionConfig, TestExecutor, ExecutorStatistics},
-    models::{Config, TestResult, TimingMetrics},
-    types::DnsConfig,
+    cancel::{self, cancellable, CancellationToken},
+    capture::{CaptureTrigger, PacketCapture},
+    client::{
+        certificate::PeerCertificate, informational, resumption::FullHandshakes, socket_stats, upload::UploadBody, ClientFactory,
+        HttpClient, HttpUtils, ACCEPT_ENCODING,
+    },
+    dns::{system::SearchPolicy, DnsManager, ResolverFailover, ServerDistribution},
+    error::{AppError, ErrorAggregator, Result},
+    executor::{
+        trace::{TracedConnection, TracedHop, TracedTls, TRACE_DIR},
+        ConnectionReuse, ConsoleLog, EventBus, ExecutionConfig, ExecutionEvent, InterferenceGuard, InterferenceInterval,
+        RequestTrace, RequestTracer, RunBudget, RunSizing, TestExecutor, ExecutorStatistics,
+    },
+    models::{Config, Name, RedirectHop, RequestPhase, SocketStats, TestResult, TimingMetrics, UploadTiming},
+    stats::{Precision, RollingStats},
+    timing,
+    types::{BindTarget, DnsConfig, ProtocolOptions, TestStatus, UploadMethod, UserAgent},
 };
 use std::{
-    collections::HashMap,
-    sync::Arc,
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 use futures::future::join_all;
+use hyper_util::client::legacy::connect::HttpInfo;
 use reqwest::Client;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{mpsc, Semaphore},
     time::timeout,
 };
 
+/// Machines with this many CPU cores or fewer count as constrained
+const CONSTRAINED_CPU_CORES: usize = 2;
+/// Machines with less available memory than this count as constrained
+const CONSTRAINED_MEMORY_BYTES: u64 = 512 * 1024 * 1024;
+/// Tests allowed to run at once in single-thread mode
+pub(super) const SINGLE_THREAD_CONCURRENCY: usize = 2;
+/// Completed results buffered before the collector picks them up
+const RESULT_BUFFER: usize = 1000;
+/// Result buffer in single-thread mode, where the collector runs between tests anyway
+const SINGLE_THREAD_RESULT_BUFFER: usize = 16;
+
 /// Optimized test executor with connection pooling and performance enhancements
 pub struct OptimizedExecutor {
     /// Shared HTTP client pool
     client_pool: Arc<ClientPool>,
     /// DNS manager
-    #[allow(dead_code)]
     dns_manager: Arc<DnsManager>,
     /// Execution configuration
     config: ExecutionConfig,
@@ -39,6 +65,49 @@ pub struct OptimizedExecutor {
     concurrency_limiter: Arc<Semaphore>,
     /// System resource detector
     system_resources: SystemResources,
+    /// Client that replaces the connection pool, if injected
+    injected_client: Option<Arc<dyn HttpClient>>,
+    /// Sheds concurrency when the test machine is the bottleneck
+    guard: Arc<InterferenceGuard>,
+    /// Connection reuse observed in completed tests, by configuration
+    connection_reuse: Mutex<BTreeMap<String, ConnectionReuse>>,
+    /// Progress of runs, for the console log and other subscribers
+    events: Arc<EventBus>,
+    /// Starts packet captures after anomalous requests, with `--capture-on-anomaly`
+    capture: Option<Arc<CaptureTrigger>>,
+    /// Stops runs at the next request; a child of the process token
+    cancel: CancellationToken,
+}
+
+/// Run-wide state every test of a run reports to
+#[derive(Clone, Copy)]
+struct RunContext<'a> {
+    /// Time budget, when the run is time-limited
+    budget: Option<&'a RunBudget>,
+    guard: &'a InterferenceGuard,
+    events: &'a EventBus,
+    /// Trace file of the run, with `--trace-requests`
+    tracer: Option<&'a RequestTracer>,
+    cancel: &'a CancellationToken,
+}
+
+/// Where the requests of a single test are sent
+enum RequestSender {
+    /// Pooled reqwest client for the DNS configuration
+    Pooled(Arc<Client>),
+    /// Injected client, e.g. a mock transport
+    Injected(Arc<dyn HttpClient>),
+}
+
+/// What a test knows about the connection its previous request used
+#[derive(Default)]
+struct ConnectionState {
+    /// Local address of the connection
+    local_addr: Option<SocketAddr>,
+    /// Kernel TCP statistics read after the request, with `--socket-stats`
+    socket: Option<SocketStats>,
+    /// Full TLS handshakes of the test's client, with `--tls-resumption`
+    handshakes: Option<Arc<FullHandshakes>>,
 }
 
 /// HTTP client pool for connection reuse
@@ -47,6 +116,8 @@ pub struct ClientPool {
     clients: HashMap<String, Arc<Client>>,
     /// Connection pool configuration
     pool_config: PoolConfig,
+    /// Resolves the configurations the system resolver cannot, like chains
+    dns_manager: Option<Arc<DnsManager>>,
 }
 
 /// Configuration for the connection pool
@@ -64,6 +135,18 @@ pub struct PoolConfig {
     pub keep_alive_timeout: Option<Duration>,
     /// TCP keep-alive settings
     pub tcp_keep_alive: Option<Duration>,
+    /// Disable Nagle's algorithm, as reqwest does by default
+    pub tcp_nodelay: bool,
+    /// HTTP/2 stream and connection flow-control window; reqwest's default if unset
+    pub http2_window_size: Option<u32>,
+    /// User-Agent and headers every request is sent with
+    pub user_agent: UserAgent,
+    /// HTTP version and TLS choices of the clients
+    pub protocol: ProtocolOptions,
+    /// Interface or local address connections are made from
+    pub bind: Option<BindTarget>,
+    /// Keep the server's certificate with each response, for request traces
+    pub tls_info: bool,
 }
 
 impl Default for PoolConfig {
@@ -75,6 +158,24 @@ impl Default for PoolConfig {
             max_connections: 100,
             keep_alive_timeout: Some(Duration::from_secs(90)),
             tcp_keep_alive: Some(Duration::from_secs(60)),
+            tcp_nodelay: true,
+            http2_window_size: None,
+            user_agent: UserAgent::default(),
+            protocol: ProtocolOptions::default(),
+            bind: None,
+            tls_info: false,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Smaller pool and receive buffers for single-thread mode on constrained machines
+    pub fn constrained() -> Self {
+        Self {
+            max_idle_per_host: 1,
+            max_connections: 8,
+            http2_window_size: Some(64 * 1024),
+            ..Self::default()
         }
     }
 }
@@ -112,6 +213,11 @@ impl SystemResources {
             max_concurrent_connections,
         }
     }
+
+    /// Whether the machine is small enough that single-thread mode should be used
+    pub fn is_constrained(&self) -> bool {
+        self.cpu_cores <= CONSTRAINED_CPU_CORES || self.available_memory < CONSTRAINED_MEMORY_BYTES
+    }
     
     /// Estimate available memory (simplified implementation)
     fn estimate_available_memory() -> u64 {
@@ -170,22 +276,91 @@ impl SystemResources {
     }
 }
 
+/// State of the test machine recorded with a run
+///
+/// Latency measured from an overloaded machine says as much about the machine
+/// as about the network; keeping this next to the results lets such runs be
+/// recognized later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    /// Number of logical CPU cores
+    pub cpu_cores: usize,
+    /// Available memory in bytes (approximate)
+    pub available_memory: u64,
+    /// 1, 5 and 15 minute load averages, where the platform reports them
+    pub load_average: Option<[f64; 3]>,
+    /// Operating system, e.g. `linux`
+    pub os: String,
+    /// CPU architecture, e.g. `x86_64`
+    pub arch: String,
+}
+
+impl SystemSnapshot {
+    /// Capture the current state of this machine
+    pub fn capture() -> Self {
+        Self::from_resources(&SystemResources::detect())
+    }
+
+    /// Build a snapshot from already detected resources
+    pub fn from_resources(resources: &SystemResources) -> Self {
+        Self {
+            cpu_cores: resources.cpu_cores,
+            available_memory: resources.available_memory,
+            load_average: Self::load_average(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+
+    /// Whether the 1 minute load average exceeded the number of cores, so
+    /// requests may have waited for CPU time
+    pub fn is_overloaded(&self) -> bool {
+        self.load_average.is_some_and(|[one_minute, _, _]| one_minute > self.cpu_cores as f64)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn load_average() -> Option<[f64; 3]> {
+        let loadavg = std::fs::read_to_string("/proc/loadavg").ok()?;
+        let mut fields = loadavg.split_whitespace().map(|field| field.parse::<f64>().ok());
+        Some([fields.next()??, fields.next()??, fields.next()??])
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn load_average() -> Option<[f64; 3]> {
+        // No portable way to read it without platform APIs
+        None
+    }
+}
+
 impl ClientPool {
     /// Create a new client pool with the given configuration
     pub fn new(pool_config: PoolConfig) -> Self {
         Self {
             clients: HashMap::new(),
             pool_config,
+            dns_manager: None,
         }
     }
+
+    /// Resolve the hosts of every configuration but the system's through `dns_manager`
+    pub fn with_dns_manager(mut self, dns_manager: Arc<DnsManager>) -> Self {
+        self.dns_manager = Some(dns_manager);
+        self
+    }
     
     /// Get or create an HTTP client for the given DNS configuration
     pub async fn get_client(&self, dns_config: &DnsConfig) -> Result<Arc<Client>> {
+        Ok(self.get_tracked_client(dns_config).await?.0)
+    }
+
+    /// Get or create an HTTP client for the given DNS configuration, with the
+    /// tally of its full TLS handshakes when `--tls-resumption` is set
+    pub async fn get_tracked_client(&self, dns_config: &DnsConfig) -> Result<(Arc<Client>, Option<Arc<FullHandshakes>>)> {
         let config_key = self.dns_config_key(dns_config);
         
         // Check if we already have a client for this configuration
         if let Some(client) = self.clients.get(&config_key) {
-            return Ok(client.clone());
+            return Ok((client.clone(), None));
         }
         
         // Create a new client for this DNS configuration
@@ -193,39 +368,77 @@ impl ClientPool {
     }
     
     /// Create a new HTTP client configured for the specific DNS configuration
-    async fn create_client(&self, dns_config: &DnsConfig) -> Result<Arc<Client>> {
+    async fn create_client(&self, dns_config: &DnsConfig) -> Result<(Arc<Client>, Option<Arc<FullHandshakes>>)> {
+        // Redirects are followed by the executor, which times each hop and applies the redirect policy
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in self.pool_config.user_agent.headers() {
+            headers.insert(*name, reqwest::header::HeaderValue::from_static(value));
+        }
         let mut client_builder = Client::builder()
+            .user_agent(self.pool_config.user_agent.header())
+            .default_headers(headers)
+            .redirect(reqwest::redirect::Policy::none())
             .connect_timeout(self.pool_config.connect_timeout)
             .pool_max_idle_per_host(self.pool_config.max_idle_per_host)
-            .pool_idle_timeout(self.pool_config.keep_alive_timeout);
+            .pool_idle_timeout(self.pool_config.keep_alive_timeout)
+            .tcp_nodelay(self.pool_config.tcp_nodelay)
+            .tls_info(self.pool_config.tls_info);
         
         // Configure TCP keep-alive if specified
         if let Some(keep_alive) = self.pool_config.tcp_keep_alive {
             client_builder = client_builder.tcp_keepalive(keep_alive);
         }
-        
-        // Apply DNS-specific configuration
-        match dns_config {
-            DnsConfig::System => {
-                // Use system DNS resolver - no special configuration needed
+
+        client_builder = match self.pool_config.bind {
+            Some(BindTarget::Address(address)) => client_builder.local_address(address),
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+            Some(BindTarget::Interface(ref name)) => client_builder.interface(name),
+            #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios")))]
+            Some(BindTarget::Interface(ref name)) => {
+                return Err(AppError::config(format!(
+                    "Binding to interface {} needs Linux or macOS; bind to one of its addresses instead", name
+                )));
             }
-            DnsConfig::Custom { servers: _ } => {
-                // For custom DNS, we would need to configure the resolver
-                // This requires more complex DNS resolution setup
-                // For now, we'll use the system resolver as a fallback
+            None => client_builder,
+        };
+
+        if let Some(window) = self.pool_config.http2_window_size {
+            client_builder = client_builder
+                .http2_initial_stream_window_size(window)
+                .http2_initial_connection_window_size(window);
+        }
+        
+        // Every configuration but the system's is only measured if its lookups really go
+        // through the DNS manager; the system configuration only needs it for overridden
+        // search domains and resolver options, which the system resolver would ignore
+        let managed = match (dns_config, &self.dns_manager) {
+            (DnsConfig::System, Some(dns_manager)) => {
+                dns_manager.search_policy().is_some_and(|policy| policy.overridden)
+                    || dns_manager.resolver_options(dns_config).is_some()
             }
-            DnsConfig::DoH { url: _ } => {
-                // DNS-over-HTTPS requires special handling
-                // This would typically involve configuring a custom resolver
-                // For now, we'll use the system resolver as a fallback
+            (DnsConfig::System, None) => false,
+            (_, Some(_)) => true,
+            (_, None) => {
+                return Err(AppError::config(format!(
+                    "DNS configuration {} needs a DNS manager to resolve through; the system resolver would answer instead",
+                    self.dns_config_key(dns_config)
+                )));
             }
+        };
+        if let (true, Some(dns_manager)) = (managed, &self.dns_manager) {
+            client_builder = client_builder.dns_resolver(Arc::new(ManagedResolver {
+                dns_manager: dns_manager.clone(),
+                dns_config: dns_config.clone(),
+            }));
         }
         
+        let client_builder = ClientFactory::apply_protocol(client_builder, &self.pool_config.protocol);
+        let (client_builder, handshakes) = ClientFactory::apply_tls_resumption(client_builder, &self.pool_config.protocol)?;
         let client = client_builder
             .build()
-            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)))?;
+            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
         
-        Ok(Arc::new(client))
+        Ok((Arc::new(client), handshakes))
     }
     
     /// Generate a unique key for a DNS configuration
@@ -238,6 +451,10 @@ impl ClientPool {
             DnsConfig::DoH { url } => {
                 format!("doh:{}", url)
             }
+            DnsConfig::Chain { .. } => {
+                format!("chain:{}", dns_config.id())
+            }
+            DnsConfig::Mdns => "mdns".to_string(),
         }
     }
     
@@ -250,6 +467,25 @@ impl ClientPool {
     }
 }
 
+/// Resolves the hosts of a client's requests through the DNS manager
+struct ManagedResolver {
+    dns_manager: Arc<DnsManager>,
+    dns_config: DnsConfig,
+}
+
+impl reqwest::dns::Resolve for ManagedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let dns_manager = self.dns_manager.clone();
+        let dns_config = self.dns_config.clone();
+        Box::pin(async move {
+            let ips = dns_manager.resolve(name.as_str(), &dns_config).await?;
+            // The port is filled in from the request URL
+            let addrs: reqwest::dns::Addrs = Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
 /// Statistics about the connection pool
 #[derive(Debug)]
 pub struct PoolStats {
@@ -261,19 +497,43 @@ impl OptimizedExecutor {
     /// Create a new optimized executor
     pub async fn new(config: &Config) -> Result<Self> {
         let system_resources = SystemResources::detect();
-        let pool_config = PoolConfig::default();
-        let client_pool = Arc::new(ClientPool::new(pool_config));
-        let dns_manager = Arc::new(DnsManager::new()?);
-        
-        let execution_config = ExecutionConfig {
-            test_count: config.test_count,
-            timeout: Duration::from_secs(config.timeout_seconds),
-            verbose: config.verbose,
-            debug: config.debug,
+        let config_count = config.create_dns_configs().map_or(1, |dns_configs| dns_configs.len());
+        let sizing = RunSizing::choose(config, config_count, &system_resources);
+        if config.verbose {
+            for notice in &sizing.notices {
+                println!("{}", notice);
+            }
+        }
+        let concurrency = sizing.concurrency;
+        let pool_config = PoolConfig {
+            user_agent: config.user_agent.clone(),
+            protocol: config.protocol_options(),
+            bind: config.bind.clone(),
+            tls_info: config.trace_requests,
+            ..sizing.pool
         };
+        let cancel = cancel::global().child_token();
+        let dns_manager = Arc::new(
+            DnsManager::new()?
+                .with_cancellation(&cancel)
+                .with_fresh_doh_connections(config.doh_fresh_connections)?
+                .with_doh_bootstrap(&config.doh_bootstrap).await?
+                .with_resolver_options(config.resolver_options.clone())
+                .with_search_policy(SearchPolicy::system(config.search_domains.clone(), config.ndots)),
+        );
+        let client_pool = Arc::new(ClientPool::new(pool_config).with_dns_manager(dns_manager.clone()));
+        
+        let execution_config = ExecutionConfig { timeout: sizing.timeout, ..ExecutionConfig::from(config) };
         
         // Create semaphore with optimal concurrency
-        let concurrency_limiter = Arc::new(Semaphore::new(system_resources.optimal_concurrency));
+        let concurrency_limiter = Arc::new(Semaphore::new(concurrency));
+        let events = Arc::new(EventBus::new());
+        events.subscribe(Arc::new(ConsoleLog::new(execution_config.verbose)));
+        let capture = config.capture_on_anomaly.map(|threshold| Arc::new(CaptureTrigger::new(threshold)));
+        if let Some(ref capture) = capture {
+            events.subscribe(capture.clone());
+        }
+        let guard = Arc::new(InterferenceGuard::with_events(concurrency_limiter.clone(), concurrency, events.clone()));
         
         Ok(Self {
             client_pool,
@@ -281,16 +541,61 @@ impl OptimizedExecutor {
             config: execution_config,
             concurrency_limiter,
             system_resources,
+            injected_client: None,
+            guard,
+            connection_reuse: Mutex::new(BTreeMap::new()),
+            events,
+            capture,
+            cancel,
+        })
+    }
+
+    /// Events of this executor's runs; subscribe to follow progress
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Token that stops this executor's runs
+    ///
+    /// Once it is cancelled, every test ends at its next request and reports
+    /// the samples it has; Ctrl-C cancels it through the process token.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Create an executor that sends every request through the given client
+    ///
+    /// The connection pool is bypassed, which lets tests and embedders run the
+    /// executor against a mock transport.
+    pub async fn with_client(config: &Config, client: Arc<dyn HttpClient>) -> Result<Self> {
+        Ok(Self {
+            injected_client: Some(client),
+            ..Self::new(config).await?
         })
     }
     
     /// Execute tests with connection pooling and optimal concurrency
     pub async fn execute_optimized_tests(&self, urls: &[String], dns_configs: &[DnsConfig]) -> Result<Vec<TestResult>> {
+        let run_start = Instant::now();
         let mut all_results = Vec::new();
         
         // Create a channel for collecting results
-        let (result_sender, mut result_receiver) = mpsc::channel(1000);
+        let buffer = if self.config.single_thread { SINGLE_THREAD_RESULT_BUFFER } else { RESULT_BUFFER };
+        let (result_sender, mut result_receiver) = mpsc::channel(buffer);
+
+        // Share one time budget across every task when the run is time-limited
+        let planned_requests = (urls.len() * dns_configs.len()) as u64 * self.config.iteration_limit() as u64;
+        let budget = self.config.max_runtime
+            .map(|max_runtime| Arc::new(RunBudget::new(max_runtime, planned_requests)));
         
+        let tracer = self.config.trace_requests
+            .then(|| RequestTracer::create(Path::new(TRACE_DIR)))
+            .transpose()?
+            .map(Arc::new);
+
+        // Watch for the test machine itself becoming the bottleneck
+        let sampler = self.guard.spawn();
+
         // Create tasks for each URL and DNS configuration combination
         let mut tasks = Vec::new();
         
@@ -302,6 +607,12 @@ impl OptimizedExecutor {
                 let config = self.config.clone();
                 let semaphore = self.concurrency_limiter.clone();
                 let sender = result_sender.clone();
+                let budget = budget.clone();
+                let injected_client = self.injected_client.clone();
+                let guard = self.guard.clone();
+                let events = self.events.clone();
+                let tracer = tracer.clone();
+                let cancel = self.cancel.clone();
                 
                 let task = tokio::spawn(async move {
                     // Acquire semaphore permit for concurrency control
@@ -310,9 +621,11 @@ impl OptimizedExecutor {
                     // Execute test with connection pooling
                     let result = Self::execute_single_test_optimized(
                         &client_pool,
+                        injected_client,
                         &url,
                         &dns_config,
                         &config,
+                        RunContext { budget: budget.as_deref(), guard: &guard, events: &events, tracer: tracer.as_deref(), cancel: &cancel },
                     ).await;
                     
                     // Send result through channel
@@ -330,7 +643,10 @@ impl OptimizedExecutor {
         let mut total_expected = urls.len() * dns_configs.len();
         while let Some(result) = result_receiver.recv().await {
             match result {
-                Ok(test_result) => all_results.push(test_result),
+                Ok(test_result) => {
+                    self.record_connection_reuse(&test_result);
+                    all_results.push(test_result);
+                }
                 Err(e) => {
                     if self.config.debug {
                         eprintln!("Test execution error: {}", e);
@@ -345,96 +661,392 @@ impl OptimizedExecutor {
         
         // Wait for all tasks to complete
         let _ = join_all(tasks).await;
+        sampler.abort();
+        if let Some(ref capture) = self.capture {
+            capture.finish().await;
+        }
+        if let Some(ref tracer) = tracer {
+            eprintln!("Request trace written to {}", tracer.path().display());
+        }
+        self.events.publish(ExecutionEvent::RunCompleted { tests: all_results.len(), duration: run_start.elapsed() }).await;
         
         Ok(all_results)
     }
     
     /// Execute a single test using the connection pool
+    ///
+    /// With a run budget, iterations stop early once the budget says the
+    /// planned work no longer fits, and requests still in flight at the
+    /// deadline are dropped rather than reported as timeouts.
     async fn execute_single_test_optimized(
         client_pool: &ClientPool,
+        injected_client: Option<Arc<dyn HttpClient>>,
         url: &str,
         dns_config: &DnsConfig,
         config: &ExecutionConfig,
+        run: RunContext<'_>,
     ) -> Result<TestResult> {
+        let RunContext { budget, guard, events, tracer, cancel } = run;
         let start_time = Instant::now();
         let mut individual_results = Vec::with_capacity(config.test_count as usize);
+        let iteration_limit = config.iteration_limit();
+        // Successful totals so far, for the --auto-count stopping rule
+        let mut rolling = RollingStats::new();
+        let mut sorted_totals: Vec<f64> = Vec::new();
+        // Debug logs show each distinct failure once and summarize the repeats at the end
+        let mut failures = ErrorAggregator::new();
+        // Interned once, then shared by the events and the result
+        let config_name = Name::from(config.display_names.resolve(dns_config));
+        let target = Name::new(url);
+        // Precision that ended the test early, with --auto-count
+        let mut reached = None;
         
         // Get pooled client for this DNS configuration
-        let client = client_pool.get_client(dns_config).await?;
+        // The connection the previous request used
+        let mut last_connection = ConnectionState::default();
+        let sender = match injected_client {
+            Some(client) => RequestSender::Injected(client),
+            None => {
+                let (client, handshakes) = client_pool.get_tracked_client(dns_config).await?;
+                last_connection.handshakes = handshakes;
+                RequestSender::Pooled(client)
+            }
+        };
+        // Whether the previous request got a response, leaving a connection in the pool
+        let mut connection_ready = false;
         
+        events.publish(ExecutionEvent::TestStarted {
+            url: target.clone(),
+            config_name: config_name.clone(),
+            iterations: iteration_limit,
+        }).await;
+
         // Execute multiple iterations using the same client
-        for iteration in 0..config.test_count {
-            let _iteration_start = Instant::now();
+        for iteration in 0..iteration_limit {
+            let iteration_start = Instant::now();
+            if cancel.is_cancelled() {
+                break;
+            }
+
+            let mut request_timeout = config.timeout;
+            if let Some(budget) = budget {
+                if budget.is_exhausted() || iteration >= budget.allowed_iterations(iteration_limit) {
+                    break;
+                }
+                request_timeout = request_timeout.min(budget.remaining());
+            }
+            let mut trace = tracer.map(|tracer| tracer.start(target.clone(), config_name.clone(), iteration));
             
-            let timing_result = timeout(config.timeout, async {
-                Self::execute_single_request(&client, url).await
-            }).await;
+            let timing_result = timeout(request_timeout, cancellable(cancel, "HTTP request", async {
+                match &sender {
+                    RequestSender::Pooled(client) => {
+                        Self::execute_single_request(client, url, &mut last_connection, config, trace.as_mut()).await
+                    }
+                    RequestSender::Injected(client) => Self::execute_injected_request(client.as_ref(), url, dns_config).await,
+                }
+            })).await;
             
             let timing_metrics = match timing_result {
-                Ok(Ok(metrics)) => metrics,
+                Ok(Ok(metrics)) => {
+                    connection_ready = true;
+                    metrics
+                }
+                // A request cut short by cancellation measured nothing
+                Ok(Err(e)) if e.is_cancelled() => break,
                 Ok(Err(e)) => {
+                    connection_ready = false;
+                    // Keep the underlying cause, e.g. "Connection refused", which the top-level message hides
+                    let message = e.display_chain();
+                    let metrics = TimingMetrics::failed(message.clone()).with_total_duration(iteration_start.elapsed());
+                    if failures.record(&message, metrics.timestamp, &config_name) && config.debug {
+                        eprintln!("Request failed for {} (iteration {}): {}", url, iteration + 1, message);
+                    }
+                    metrics
+                }
+                Err(_) if request_timeout < config.timeout => {
+                    // Cut short by the run deadline, not by the network
                     if config.debug {
-                        eprintln!("Request failed for {} (iteration {}): {}", url, iteration + 1, e);
+                        eprintln!("Run time limit reached for {} (iteration {})", url, iteration + 1);
                     }
-                    TimingMetrics::failed(e.to_string())
+                    break;
                 }
                 Err(_) => {
-                    if config.debug {
+                    // Keep the time actually spent so the sample can stand in as a lower bound
+                    let phase = if connection_ready {
+                        RequestPhase::AwaitingResponse
+                    } else {
+                        RequestPhase::Connecting
+                    };
+                    connection_ready = false;
+                    let metrics = TimingMetrics::timeout(config.timeout)
+                        .with_phase_reached(phase)
+                        .with_total_duration(iteration_start.elapsed().max(config.timeout));
+                    let message = metrics.error_message.as_deref().unwrap_or_default();
+                    if failures.record(message, metrics.timestamp, &config_name) && config.debug {
                         eprintln!("Request timed out for {} (iteration {})", url, iteration + 1);
                     }
-                    TimingMetrics::timeout(config.timeout)
+                    metrics
+                }
+            };
+
+            // reqwest never sees 1xx responses, so they take a request of their own
+            let timing_metrics = match timing_metrics.resolved_ip {
+                Some(address) if config.early_hints && timing_metrics.is_successful() && timing_metrics.redirects.is_empty()
+                    && matches!(sender, RequestSender::Pooled(_)) => {
+                    match informational::probe(url, address, request_timeout).await {
+                        Ok(informational) => timing_metrics.with_informational(Some(informational)),
+                        Err(e) => {
+                            if config.debug {
+                                eprintln!("Early hints check failed for {} (iteration {}): {}", url, iteration + 1, e);
+                            }
+                            timing_metrics
+                        }
+                    }
                 }
+                _ => timing_metrics,
             };
+
+            if let Some(budget) = budget {
+                budget.record_request();
+            }
+            guard.record(&timing_metrics);
             
+            // The first request for each URL and configuration pays for connection setup;
+            // later iterations reuse the pooled connection
+            let timing_metrics = timing_metrics
+                .with_started_at(timing::process_clock().timestamp_of(iteration_start))
+                .with_first_use(iteration == 0);
+            if let (Some(tracer), Some(mut trace)) = (tracer, trace) {
+                trace.finish(&timing_metrics);
+                if let (RequestSender::Pooled(_), Some(dns_manager)) = (&sender, client_pool.dns_manager.as_deref()) {
+                    trace.resolve(dns_manager, dns_config).await;
+                }
+                if let Err(e) = tracer.write(&trace) {
+                    if config.debug {
+                        eprintln!("{}", e);
+                    }
+                }
+            }
+            events.publish(ExecutionEvent::SampleCompleted {
+                url: target.clone(),
+                config_name: config_name.clone(),
+                iteration,
+                metrics: Box::new(timing_metrics.clone()),
+            }).await;
             individual_results.push(timing_metrics);
-            
-            if config.verbose {
-                println!("Completed iteration {} for {} with {}: {:?}",
-                    iteration + 1,
-                    url,
-                    Self::dns_config_name(dns_config),
-                    individual_results.last().unwrap().total_duration
-                );
+
+            // With --auto-count, stop once the estimates are precise enough
+            if let (Some(target), Some(latest)) = (config.auto_count, individual_results.last()) {
+                if latest.is_successful() {
+                    let total = latest.total_ms();
+                    rolling.add_value(total);
+                    let position = sorted_totals.partition_point(|&value| value <= total);
+                    sorted_totals.insert(position, total);
+                }
+                if iteration + 1 >= config.test_count {
+                    let precision = Precision::of(&rolling, &sorted_totals);
+                    if precision.is_within(target) {
+                        reached = Some(precision);
+                        break;
+                    }
+                }
             }
         }
+
+        if config.debug {
+            for group in failures.repeated() {
+                eprintln!("Repeated failure for {} with {}: {}", url, config_name, group);
+            }
+        }
+
+        if individual_results.is_empty() {
+            let reason = if cancel.is_cancelled() {
+                "Run cancelled before this configuration was tested"
+            } else {
+                "Run time limit reached before this configuration was tested"
+            };
+            individual_results.push(TimingMetrics::skipped(reason.to_string()));
+        }
         
         // Calculate statistics
         let success_count = individual_results.iter().filter(|m| m.is_successful()).count() as u32;
-        let total_count = config.test_count;
+        let total_count = individual_results.iter()
+            .filter(|m| !matches!(m.status, TestStatus::Skipped))
+            .count() as u32;
         
-        // Create test result with composite key format: "{url}::{dns_config_name}"
+        // URL and configuration are kept separate; ExecutionResults keys on both
         let mut result = TestResult {
-            config_name: format!("{}::{}", url, Self::dns_config_name(dns_config)),
+            config_name,
             dns_config: dns_config.clone(),
-            url: url.to_string(),
+            url: target,
             individual_results,
             statistics: None,
             success_count,
             total_count,
-            started_at: chrono::Utc::now() - chrono::Duration::from_std(start_time.elapsed()).unwrap_or_default(),
-            completed_at: Some(chrono::Utc::now()),
+            started_at: timing::process_clock().timestamp_of(start_time),
+            completed_at: Some(timing::timestamp()),
+            interface: None,
+            ping: Vec::new(),
         };
         
         // Calculate statistics from the measurements
         result.calculate_statistics();
+        events.publish(ExecutionEvent::ConfigFinished {
+            url: result.url.clone(),
+            config_name: result.config_name.clone(),
+            iterations: total_count,
+            successes: success_count,
+            precision: reached,
+        }).await;
         
         Ok(result)
     }
     
     /// Execute a single HTTP request with timing
-    async fn execute_single_request(client: &Client, url: &str) -> Result<TimingMetrics> {
+    ///
+    /// A connection is recognized as reused when its local address matches the
+    /// one `last_connection` holds from the previous request, which is updated.
+    /// With `socket_stats`, the kernel's TCP statistics of the connection are
+    /// read once the body is in; on a reused connection only the retransmissions
+    /// since the previous request are counted against this one.
+    ///
+    /// The client must not follow redirects itself: under the configured
+    /// redirect policy they are followed here, up to `max_redirects`, and each
+    /// hop is timed. The total covers every hop. Without ALPN fallback, a
+    /// final response not sent over HTTP/2 fails the request.
+    ///
+    /// With `upload`, every hop sends the generated body; the upload timing
+    /// is that of the final hop.
+    ///
+    /// With `trace`, each hop, the connection and the server's certificate
+    /// are recorded in it as the request goes.
+    async fn execute_single_request(
+        client: &Client,
+        url: &str,
+        last_connection: &mut ConnectionState,
+        config: &ExecutionConfig,
+        mut trace: Option<&mut RequestTrace>,
+    ) -> Result<TimingMetrics> {
         let start_time = Instant::now();
-        
-        // Make the HTTP request
-        let response = client.get(url).send().await.map_err(|e| {
-            AppError::network(format!("HTTP request failed: {}", e))
-        })?;
+        let mut current_url = url.to_string();
+        let mut redirects = Vec::new();
+        // Full TLS handshakes by host before the request, to tell whether its connection resumed a session
+        let handshakes_before = last_connection.handshakes.as_ref().map(|handshakes| handshakes.snapshot());
+
+        let (response, upload) = loop {
+            let hop_start = Instant::now();
+            let body = config.upload.map(|upload| (upload.method, UploadBody::new(upload.bytes)));
+            let method = match body {
+                Some((UploadMethod::Post, _)) => reqwest::Method::POST,
+                Some((UploadMethod::Put, _)) => reqwest::Method::PUT,
+                None => reqwest::Method::GET,
+            };
+            let request = match body {
+                Some((_, ref body)) => {
+                    client.request(method.clone(), current_url.as_str())
+                        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                        .header(reqwest::header::CONTENT_LENGTH, body.len())
+                        .body(body.body())
+                }
+                None => client.get(current_url.as_str()),
+            };
+            let response = request
+                .header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING)
+                .send()
+                .await
+                .map_err(|e| AppError::network(format!("HTTP request failed: {}", e)).with_source(e))?;
+            if let Some(ref mut trace) = trace {
+                trace.hops.push(TracedHop::of(&method, &response, hop_start.elapsed()));
+            }
+            let upload = body.map(|(_, body)| {
+                let answered = Instant::now();
+                // A server may answer before it has read the whole body
+                let sent = body.sent_at().unwrap_or(answered).min(answered);
+                UploadTiming { bytes: body.len(), send: sent - hop_start, ack: answered - sent }
+            });
+
+            let location = response.headers().get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let location = match location {
+                Some(location) if config.redirect_policy.follows() && response.status().is_redirection() => location,
+                _ => break (response, upload),
+            };
+            let target = response.url().join(&location)
+                .map_err(|e| AppError::network(format!("Invalid redirect location '{}': {}", location, e)).with_source(e))?;
+            let status = response.status().as_u16();
+            redirects.push(RedirectHop { url: current_url, status, duration: hop_start.elapsed() });
+            // Drain the redirect body so its connection can serve the next hop
+            let _ = response.bytes().await;
+
+            let refusal = config.redirect_policy.refusal(&target).or_else(|| {
+                (redirects.len() > config.max_redirects as usize)
+                    .then(|| format!("Too many redirects (more than {})", config.max_redirects))
+            });
+            if let Some(reason) = refusal {
+                let mut metrics = TimingMetrics::failed(reason)
+                    .with_total_duration(start_time.elapsed())
+                    .with_redirects(redirects);
+                metrics.http_status = status;
+                return Ok(metrics);
+            }
+            current_url = target.to_string();
+        };
         
         let total_duration = start_time.elapsed();
-        let status_code = response.status().as_u16();
+        if !config.protocol.alpn_fallback && response.version() != reqwest::Version::HTTP_2 {
+            let mut metrics = TimingMetrics::failed(format!("Server answered over {:?} instead of HTTP/2", response.version()))
+                .with_total_duration(total_duration)
+                .with_resolved_ip(response.remote_addr().map(|addr| addr.ip()))
+                .with_redirects(redirects);
+            metrics.http_status = response.status().as_u16();
+            return Ok(metrics);
+        }
+        let status = response.status();
+        let status_code = status.as_u16();
+        let resolved_ip = response.remote_addr().map(|addr| addr.ip());
+        let cache_status = HttpUtils::cache_status(response.headers());
+        let server_timing = HttpUtils::server_timing(response.headers());
+        let throttling = HttpUtils::throttling(status_code, response.headers());
+        let addresses = response.extensions().get::<HttpInfo>().map(|info| (info.local_addr(), info.remote_addr()));
+        let reused_connection = addresses.map(|(local, _)| last_connection.local_addr.replace(local) == Some(local));
+        // A new TLS connection that did not add a full handshake with its host resumed a session
+        let tls_resumed = match (&last_connection.handshakes, handshakes_before, response.url().host_str()) {
+            (Some(handshakes), Some(before), Some(host)) if response.url().scheme() == "https" && reused_connection == Some(false) => {
+                Some(handshakes.count(host) == before.get(host).copied().unwrap_or(0))
+            }
+            _ => None,
+        };
+        if let Some(trace) = trace {
+            trace.connection = addresses.map(|(local, remote)| TracedConnection { local, remote, reused: None });
+            if response.url().scheme() == "https" {
+                let certificate = response.extensions().get::<reqwest::tls::TlsInfo>()
+                    .and_then(|info| info.peer_certificate())
+                    .and_then(PeerCertificate::from_der);
+                trace.tls = Some(TracedTls { settings: config.protocol.to_string(), certificate, resumed: tls_resumed });
+            }
+        }
+
+        // The body is read after timing stops, so its size is recorded without
+        // changing what the latency covers; a body that breaks off is left unknown
+        let encoding = response.headers().get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await.ok()
+            .map(|body| HttpUtils::response_body(encoding.as_deref(), &body));
+
+        let socket = addresses
+            .filter(|_| config.socket_stats)
+            .and_then(|(local, remote)| socket_stats::read(local, remote));
+        let earlier = std::mem::replace(&mut last_connection.socket, socket);
+        let socket = match (socket, earlier) {
+            (Some(socket), Some(earlier)) if reused_connection == Some(true) => Some(socket.since(&earlier)),
+            _ => socket,
+        };
         
         // Since reqwest doesn't provide detailed timing breakdown, we need to estimate
-        // the components based on realistic proportions of the total request time
+        // the components based on realistic proportions of the total request time;
+        // the metrics are marked so that reports do not show them as measured
         let total_ms = total_duration.as_millis() as u64;
         
         // Realistic estimates based on typical request patterns:
@@ -443,7 +1055,7 @@ impl OptimizedExecutor {
         // TLS (for HTTPS): 15-40% of total time
         // First byte: remaining time
         
-        let is_https = url.starts_with("https://");
+        let is_https = current_url.starts_with("https://");
         
         let dns_duration = Duration::from_millis(
             (total_ms / 10).clamp(1, 50) // 10% of total, 1-50ms range
@@ -468,39 +1080,92 @@ impl OptimizedExecutor {
             .unwrap_or(Duration::from_millis(1))
             .max(Duration::from_millis(1));
         
-        if response.status().is_success() {
-            Ok(TimingMetrics::success(
+        // Under the `none` policy the redirect itself is the measured response
+        if status.is_success() || (status.is_redirection() && !config.redirect_policy.follows()) {
+            let mut metrics = TimingMetrics::success(
                 dns_duration,
                 connect_duration,
                 tls_duration,
                 first_byte_duration,
                 total_duration,
                 status_code,
-            ))
+            ).with_resolved_ip(resolved_ip)
+            .with_cache_status(cache_status)
+            .with_reused_connection(reused_connection)
+            .with_tls_resumed(tls_resumed)
+            .with_socket_stats(socket)
+            .with_upload(upload)
+            .with_server_timing(server_timing)
+            .with_redirects(redirects)
+            .with_estimated_phases(true);
+            metrics.body = body;
+            Ok(metrics)
         } else {
-            Ok(TimingMetrics::failed(format!("HTTP {}", status_code)))
+            // Kept apart from failures of the network or the server
+            let reason = match throttling {
+                Some(throttling) => format!("Throttled: {}", throttling),
+                None => format!("HTTP {}", status_code),
+            };
+            let mut metrics = TimingMetrics::failed(reason)
+                .with_total_duration(total_duration)
+                .with_resolved_ip(resolved_ip)
+                .with_reused_connection(reused_connection)
+                .with_tls_resumed(tls_resumed)
+                .with_socket_stats(socket)
+                .with_upload(upload)
+                .with_throttling(throttling)
+                .with_server_timing(server_timing)
+                .with_redirects(redirects);
+            metrics.http_status = status_code;
+            Ok(metrics)
         }
     }
     
-    /// Get human-readable name for a DNS configuration
-    fn dns_config_name(dns_config: &DnsConfig) -> String {
-        match dns_config {
-            DnsConfig::System => "System DNS".to_string(),
-            DnsConfig::Custom { servers } => {
-                format!("Custom DNS ({})", servers.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(","))
-            }
-            DnsConfig::DoH { url } => {
-                format!("DoH ({})", url)
-            }
+    /// Execute a single request through an injected client
+    async fn execute_injected_request(client: &dyn HttpClient, url: &str, dns_config: &DnsConfig) -> Result<TimingMetrics> {
+        let response = client.get(url, dns_config).await?;
+        if response.is_success() {
+            return Ok(response.timing);
         }
+
+        let mut metrics = TimingMetrics::failed(format!("HTTP {}", response.status_code))
+            .with_total_duration(response.timing.total_duration)
+            .with_resolved_ip(response.resolved_ip);
+        metrics.http_status = response.status_code;
+        Ok(metrics)
     }
     
+    /// Intervals of the runs so far in which the test machine was the bottleneck
+    pub fn interference(&self) -> Vec<InterferenceInterval> {
+        self.guard.intervals()
+    }
+    
+    /// Count the connection reuse of a completed test under its configuration
+    fn record_connection_reuse(&self, result: &TestResult) {
+        let mut reuse = ConnectionReuse::default();
+        for metrics in &result.individual_results {
+            reuse.record(metrics);
+        }
+        if reuse.requests > 0 {
+            let mut tallies = self.connection_reuse.lock().unwrap();
+            tallies.entry(result.config_name.to_string()).or_default().merge(&reuse);
+        }
+    }
+
     /// Get executor performance statistics
     pub fn performance_stats(&self) -> ExecutorStats {
         ExecutorStats {
             system_resources: self.system_resources.clone(),
             pool_stats: self.client_pool.stats(),
             concurrency_limit: self.concurrency_limiter.available_permits(),
+            connection_reuse: self.connection_reuse.lock().unwrap().clone(),
+            resolver_failover: self.dns_manager.resolver_failover().into_iter()
+                .map(|(chain, failover)| (self.config.display_names.resolve(&chain), failover))
+                .collect(),
+            server_distribution: self.dns_manager.server_distribution().into_iter()
+                .map(|(config, distribution)| (self.config.display_names.resolve(&config), distribution))
+                .collect(),
+            packet_captures: self.capture.as_ref().map(|capture| capture.captures()).unwrap_or_default(),
         }
     }
 }
@@ -511,6 +1176,14 @@ pub struct ExecutorStats {
     pub system_resources: SystemResources,
     pub pool_stats: PoolStats,
     pub concurrency_limit: usize,
+    /// Connection reuse observed in completed tests, by configuration
+    pub connection_reuse: BTreeMap<String, ConnectionReuse>,
+    /// Which resolver answered the lookups of each resolver chain, by configuration
+    pub resolver_failover: BTreeMap<String, ResolverFailover>,
+    /// How each server answered under a server strategy, by configuration
+    pub server_distribution: BTreeMap<String, ServerDistribution>,
+    /// Packet captures started by anomalous requests
+    pub packet_captures: Vec<PacketCapture>,
 }
 
 /// Implementation of TestExecutor for OptimizedExecutor
@@ -533,6 +1206,11 @@ impl TestExecutor for OptimizedExecutor {
             avg_execution_time_ms: 0.0, // Would be calculated from results
             total_execution_duration: Duration::ZERO, // Would be tracked
             memory_usage_bytes: Some(stats.pool_stats.total_clients * size_of::<Client>()),
+            interference: self.interference(),
+            connection_reuse: stats.connection_reuse,
+            resolver_failover: stats.resolver_failover,
+            server_distribution: stats.server_distribution,
+            packet_captures: stats.packet_captures,
         }
     }
     
@@ -546,6 +1224,7 @@ impl TestExecutor for OptimizedExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::RedirectPolicy;
     
     #[test]
     fn test_system_resources_detection() {
@@ -566,6 +1245,40 @@ mod tests {
         assert_eq!(config.connect_timeout, Duration::from_secs(10));
         assert_eq!(config.max_connections, 100);
         assert!(config.keep_alive_timeout.is_some());
+        assert_eq!(config.http2_window_size, None);
+
+        let constrained = PoolConfig::constrained();
+        assert_eq!(constrained.max_idle_per_host, 1);
+        assert!(constrained.max_connections < config.max_connections);
+        assert_eq!(constrained.connect_timeout, config.connect_timeout);
+        assert!(constrained.http2_window_size.is_some());
+    }
+
+    #[test]
+    fn test_constrained_system_detection() {
+        let resources = SystemResources {
+            cpu_cores: 8,
+            available_memory: 8_000_000_000,
+            optimal_concurrency: 16,
+            max_concurrent_connections: 32,
+        };
+        assert!(!resources.is_constrained());
+        assert!(SystemResources { cpu_cores: 1, ..resources.clone() }.is_constrained());
+        assert!(SystemResources { available_memory: 256 * 1024 * 1024, ..resources }.is_constrained());
+    }
+
+    #[test]
+    fn test_system_snapshot() {
+        let snapshot = SystemSnapshot::capture();
+        assert!(snapshot.cpu_cores > 0);
+        assert_eq!(snapshot.os, std::env::consts::OS);
+        #[cfg(target_os = "linux")]
+        assert!(snapshot.load_average.is_some());
+
+        let busy = SystemSnapshot { cpu_cores: 2, load_average: Some([3.5, 1.0, 0.5]), ..snapshot.clone() };
+        assert!(busy.is_overloaded());
+        assert!(!SystemSnapshot { load_average: Some([1.5, 3.0, 3.0]), ..busy.clone() }.is_overloaded());
+        assert!(!SystemSnapshot { load_average: None, ..busy }.is_overloaded());
     }
     
     #[tokio::test]
@@ -590,4 +1303,296 @@ mod tests {
         assert!(custom_key.starts_with("custom:"));
         assert!(custom_key.contains("8.8.8.8"));
     }
+
+    /// Serve `ok` to every request on a local port, closing each connection
+    /// after one response unless `keep_alive` is set
+    async fn local_server(keep_alive: bool) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let connection = if keep_alive { "keep-alive" } else { "close" };
+                    let response = format!("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: {}\r\n\r\nok", connection);
+                    let mut buffer = [0u8; 4096];
+                    while matches!(stream.read(&mut buffer).await, Ok(read) if read > 0) {
+                        if stream.write_all(response.as_bytes()).await.is_err() || !keep_alive {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_connection_reuse_detection() {
+        for (keep_alive, expected) in [(true, [false, true, true]), (false, [false, false, false])] {
+            let url = local_server(keep_alive).await;
+            let client = Client::new();
+            let config = ExecutionConfig { socket_stats: true, ..Default::default() };
+            let mut last_connection = ConnectionState::default();
+            let mut reuse = ConnectionReuse::default();
+            for (iteration, expected) in expected.into_iter().enumerate() {
+                let metrics = OptimizedExecutor::execute_single_request(&client, &url, &mut last_connection, &config, None)
+                    .await
+                    .unwrap()
+                    .with_first_use(iteration == 0);
+                assert_eq!(metrics.reused_connection, Some(expected), "keep-alive {}", keep_alive);
+                // Loopback does not lose segments
+                if socket_stats::SUPPORTED && keep_alive {
+                    assert_eq!(metrics.socket.map(|socket| socket.retransmits), Some(0));
+                }
+                reuse.record(&metrics);
+            }
+            assert_eq!(reuse.matches_first_use(), keep_alive);
+        }
+    }
+
+    /// Serve `/a` redirecting to `/b`, `/b` redirecting to `/ok` and `ok` on
+    /// `/ok`, closing each connection after one response
+    async fn redirect_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/a", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0u8; 4096];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]);
+                    let response = match request.split_whitespace().nth(1) {
+                        Some("/a") => "HTTP/1.1 301 Moved Permanently\r\nLocation: /b\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        Some("/b") => "HTTP/1.1 302 Found\r\nLocation: /ok\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        _ => "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    };
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy() {
+        let url = redirect_server().await;
+        let client = Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+        let request = |redirect_policy: RedirectPolicy, max_redirects: u32| {
+            let config = ExecutionConfig { redirect_policy, max_redirects, ..Default::default() };
+            let client = client.clone();
+            let url = url.clone();
+            async move {
+                OptimizedExecutor::execute_single_request(&client, &url, &mut ConnectionState::default(), &config, None)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Each hop is timed and counted in the total
+        let metrics = request(RedirectPolicy::Follow, 10).await;
+        assert!(metrics.is_successful());
+        assert_eq!(metrics.http_status, 200);
+        let statuses: Vec<u16> = metrics.redirects.iter().map(|hop| hop.status).collect();
+        assert_eq!(statuses, [301, 302]);
+        assert!(metrics.redirects[1].url.ends_with("/b"));
+        assert!(metrics.redirects.iter().all(|hop| hop.duration <= metrics.total_duration));
+
+        // The redirect itself is measured
+        let metrics = request(RedirectPolicy::None, 10).await;
+        assert!(metrics.is_successful());
+        assert_eq!(metrics.http_status, 301);
+        assert!(metrics.redirects.is_empty());
+
+        let metrics = request(RedirectPolicy::Follow, 1).await;
+        assert!(!metrics.is_successful());
+        assert_eq!(metrics.error_message.as_deref(), Some("Too many redirects (more than 1)"));
+        assert_eq!(metrics.redirects.len(), 2);
+
+        let metrics = request(RedirectPolicy::HttpsOnly, 10).await;
+        assert!(!metrics.is_successful());
+        assert!(metrics.error_message.unwrap().contains("leaves HTTPS"));
+        assert_eq!(metrics.redirects.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_timing() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers 200 to a body upload that arrived whole and 400 to anything else
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 16 * 1024];
+                    let header_end = loop {
+                        let read = stream.read(&mut buffer).await.unwrap_or(0);
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..read]);
+                        if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                    };
+                    let head = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+                    let length: usize = head.lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |value| value.trim().parse().unwrap());
+                    let mut received = request.len() - header_end;
+                    while received < length {
+                        let read = stream.read(&mut buffer).await.unwrap_or(0);
+                        if read == 0 {
+                            break;
+                        }
+                        received += read;
+                    }
+                    let method = head.split_whitespace().next().unwrap_or_default();
+                    let status = if length > 0 && received == length && matches!(method, "post" | "put") { "200 OK" } else { "400 Bad Request" };
+                    let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let client = Client::new();
+        for upload in ["put:300KiB", "1500"] {
+            let upload: crate::types::Upload = upload.parse().unwrap();
+            let config = ExecutionConfig { upload: Some(upload), ..Default::default() };
+            let metrics = OptimizedExecutor::execute_single_request(&client, &url, &mut ConnectionState::default(), &config, None)
+                .await
+                .unwrap();
+            assert!(metrics.is_successful());
+            let timing = metrics.upload.unwrap();
+            assert_eq!(timing.bytes, upload.bytes);
+            assert!(timing.send + timing.ack <= metrics.total_duration);
+        }
+
+        // Without an upload the request stays a plain GET
+        let metrics = OptimizedExecutor::execute_single_request(&client, &url, &mut ConnectionState::default(), &ExecutionConfig::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(metrics.http_status, 400);
+        assert!(metrics.upload.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pooled_clients_send_the_user_agent_preset() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 4096];
+            let read = stream.read(&mut buffer).await.unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            String::from_utf8_lossy(&buffer[..read]).to_lowercase()
+        });
+
+        let pool = ClientPool::new(PoolConfig { user_agent: UserAgent::MobileSafari, ..PoolConfig::default() });
+        let client = pool.get_client(&DnsConfig::System).await.unwrap();
+        client.get(&url).send().await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("user-agent: mozilla/5.0 (iphone;"), "{}", request);
+        assert!(request.contains("sec-fetch-mode: navigate"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_no_alpn_fallback_fails_http1_responses() {
+        let url = local_server(false).await;
+        let config = ExecutionConfig {
+            protocol: ProtocolOptions { alpn_fallback: false, ..Default::default() },
+            ..Default::default()
+        };
+        let metrics = OptimizedExecutor::execute_single_request(&Client::new(), &url, &mut ConnectionState::default(), &config, None)
+            .await
+            .unwrap();
+        assert!(!metrics.is_successful());
+        assert_eq!(metrics.error_message.as_deref(), Some("Server answered over HTTP/1.1 instead of HTTP/2"));
+    }
+
+    #[tokio::test]
+    async fn test_resolver_chain_falls_back_in_pooled_clients() {
+        use crate::{client::mock::MockResolver, dns::ResolverFailover};
+
+        let url = local_server(false).await;
+        let port = url::Url::parse(&url).unwrap().port().unwrap();
+        let unreachable = DnsConfig::Custom { servers: vec!["192.0.2.1".parse().unwrap()] };
+        let resolver = MockResolver::new()
+            .with_record("chain.test", vec!["127.0.0.1".parse().unwrap()])
+            .failing_for(unreachable);
+        let dns_manager = Arc::new(DnsManager::with_resolver(Arc::new(resolver)).unwrap());
+        let pool = ClientPool::new(PoolConfig::default()).with_dns_manager(dns_manager.clone());
+
+        let chain = DnsConfig::parse_chain("192.0.2.1>system").unwrap();
+        let client = pool.get_client(&chain).await.unwrap();
+        for _ in 0..2 {
+            let response = client.get(format!("http://chain.test:{}/", port)).send().await.unwrap();
+            assert!(response.status().is_success());
+        }
+
+        let failover = dns_manager.resolver_failover();
+        assert_eq!(failover, [(chain, ResolverFailover { answered_by: vec![0, 2], failed: 0 })]);
+        assert_eq!(failover[0].1.to_string(), "2/2 lookups fell back (100.0%), answered by each resolver: 0, 2");
+    }
+
+    #[tokio::test]
+    async fn test_custom_servers_resolve_through_the_dns_manager() {
+        use crate::client::mock::MockResolver;
+
+        let url = local_server(false).await;
+        let port = url::Url::parse(&url).unwrap().port().unwrap();
+        let custom = DnsConfig::Custom { servers: vec!["192.0.2.1".parse().unwrap()] };
+        let resolver = MockResolver::new().with_record("custom.test", vec!["127.0.0.1".parse().unwrap()]);
+        let dns_manager = Arc::new(DnsManager::with_resolver(Arc::new(resolver)).unwrap());
+
+        // The system resolver knows nothing of custom.test
+        let pool = ClientPool::new(PoolConfig::default()).with_dns_manager(dns_manager);
+        let client = pool.get_client(&custom).await.unwrap();
+        let response = client.get(format!("http://custom.test:{}/", port)).send().await.unwrap();
+        assert!(response.status().is_success());
+
+        // Without a DNS manager there is nothing to resolve through
+        assert!(ClientPool::new(PoolConfig::default()).get_client(&custom).await.is_err());
+    }
+
+    #[cfg(feature = "doh")]
+    #[tokio::test]
+    async fn test_resolver_chain_resolves_through_a_local_doh_server() {
+        use crate::dns::{mock_server::MockDnsServer, ResolverFailover};
+
+        let url = local_server(true).await;
+        let port = url::Url::parse(&url).unwrap().port().unwrap();
+        let dns = MockDnsServer::new()
+            .with_record("app.test", vec!["127.0.0.1".parse().unwrap()])
+            .start(None, Some("127.0.0.1:0".parse().unwrap()))
+            .await
+            .unwrap();
+        let chain = DnsConfig::Chain { resolvers: vec![DnsConfig::DoH { url: dns.doh_url().unwrap() }, DnsConfig::System] };
+        let dns_manager = Arc::new(DnsManager::new().unwrap());
+        let pool = ClientPool::new(PoolConfig::default()).with_dns_manager(dns_manager.clone());
+        let client = pool.get_client(&chain).await.unwrap();
+
+        let config = ExecutionConfig::default();
+        let mut last_connection = ConnectionState::default();
+        let metrics = OptimizedExecutor::execute_single_request(&client, &format!("http://app.test:{}/", port), &mut last_connection, &config, None)
+            .await
+            .unwrap();
+        assert!(metrics.is_successful(), "{:?}", metrics.error_message);
+        assert_eq!(metrics.resolved_ip, Some("127.0.0.1".parse().unwrap()));
+        // One A and one AAAA query
+        assert_eq!(dns.queries(), 2);
+        assert_eq!(dns_manager.resolver_failover(), [(chain, ResolverFailover { answered_by: vec![1, 0], failed: 0 })]);
+
+        let metrics = OptimizedExecutor::execute_single_request(&client, &format!("http://missing.test:{}/", port), &mut last_connection, &config, None)
+            .await;
+        assert!(metrics.is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+//! Dry-run execution plan
+//!
+//! [`ExecutionPlan`] describes what a run would do — the URL × configuration
+//! matrix, how many requests are sent, how they are spread over concurrent
+//! tasks and roughly how long that takes — without sending any traffic.
+
+use crate::{
+    defaults::MAX_AUTO_COUNT,
+    executor::{PoolConfig, RunSizing, SystemResources},
+    models::Config,
+    types::{DisplayNames, DnsConfig, Shard},
+    utils::{duration::format_duration, url::display_url},
+};
+use std::time::Duration;
+
+/// Assumed latency of a typical request when estimating run time
+pub const TYPICAL_REQUEST_LATENCY: Duration = Duration::from_millis(300);
+
+/// Everything a run would do, computed without sending traffic
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    /// Target URLs, in the order they are tested
+    pub urls: Vec<String>,
+    /// DNS configurations, in the order they are tested
+    pub dns_configs: Vec<DnsConfig>,
+    /// Names the configurations are reported under
+    pub display_names: DisplayNames,
+    /// Iterations per URL and configuration
+    pub iterations: u32,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Time budget for the whole run, if any
+    pub max_runtime: Option<Duration>,
+    /// Precision `--auto-count` adds iterations for, if enabled
+    pub auto_count: Option<f64>,
+    /// Share of the target list `urls` was taken from, if the run is sharded
+    pub shard: Option<Shard>,
+    /// Number of URL × configuration tasks run at the same time
+    pub concurrency: usize,
+    /// Connection pool settings used by every client
+    pub pool: PoolConfig,
+    /// Why the concurrency, timeout and pool sizes were chosen
+    pub sizing_notices: Vec<String>,
+}
+
+impl ExecutionPlan {
+    /// Plan a run of `config` against `dns_configs` on this machine, sized
+    /// as the executor would size it
+    pub fn new(config: &Config, dns_configs: &[DnsConfig]) -> Self {
+        let sizing = RunSizing::choose(config, dns_configs.len(), &SystemResources::detect());
+        Self {
+            timeout: sizing.timeout,
+            pool: sizing.pool,
+            sizing_notices: sizing.notices,
+            ..Self::with_concurrency(config, dns_configs, sizing.concurrency)
+        }
+    }
+
+    /// Plan a run with an explicit concurrency limit
+    pub fn with_concurrency(config: &Config, dns_configs: &[DnsConfig], concurrency: usize) -> Self {
+        Self {
+            urls: config.target_urls.clone(),
+            dns_configs: dns_configs.to_vec(),
+            display_names: config.display_names(),
+            iterations: config.test_count,
+            timeout: config.timeout,
+            max_runtime: config.max_runtime,
+            auto_count: config.auto_count,
+            shard: config.shard,
+            concurrency: concurrency.max(1),
+            pool: PoolConfig::default(),
+            sizing_notices: Vec::new(),
+        }
+    }
+
+    /// Number of URL × configuration tasks
+    pub fn task_count(&self) -> usize {
+        self.urls.len() * self.dns_configs.len()
+    }
+
+    /// Total number of requests that would be sent
+    pub fn request_count(&self) -> u64 {
+        self.task_count() as u64 * self.iterations as u64
+    }
+
+    /// Number of rounds needed to get through every task at the concurrency limit
+    pub fn waves(&self) -> usize {
+        self.task_count().div_ceil(self.concurrency)
+    }
+
+    /// Estimated wall-clock time if every request takes `request_latency`
+    ///
+    /// Each task sends its iterations one after another, so a wave lasts
+    /// `iterations × request_latency`. The result is capped by `max_runtime`.
+    pub fn estimated_duration(&self, request_latency: Duration) -> Duration {
+        let estimate = request_latency * self.iterations * self.waves() as u32;
+        match self.max_runtime {
+            Some(max_runtime) => estimate.min(max_runtime),
+            None => estimate,
+        }
+    }
+
+    /// Estimated wall-clock time if every request runs into the timeout
+    pub fn worst_case_duration(&self) -> Duration {
+        self.estimated_duration(self.timeout)
+    }
+
+    /// Client features the run would use
+    pub fn client_features(&self) -> Vec<String> {
+        let mut features = vec![
+            format!(
+                "Connection pooling: one client per DNS configuration, up to {} idle connections per host, idle timeout {}",
+                self.pool.max_idle_per_host,
+                self.pool.keep_alive_timeout.map_or("none".to_string(), format_duration)
+            ),
+            format!("Connect timeout: {}", format_duration(self.pool.connect_timeout)),
+        ];
+
+        if let Some(keep_alive) = self.pool.tcp_keep_alive {
+            features.push(format!("TCP keep-alive: {}", format_duration(keep_alive)));
+        }
+
+        let https = self.urls.iter().filter(|url| url.starts_with("https://")).count();
+        if https > 0 {
+            features.push(format!("TLS: {} of {} URLs use HTTPS", https, self.urls.len()));
+        }
+
+        if self.dns_configs.iter().any(|c| matches!(c, DnsConfig::Custom { .. })) {
+            features.push("Custom DNS servers: configured, resolved through the system resolver by the pooled client".to_string());
+        }
+        if self.dns_configs.iter().any(|c| matches!(c, DnsConfig::DoH { .. })) {
+            features.push("DNS-over-HTTPS: configured, resolved through the system resolver by the pooled client".to_string());
+        }
+        if self.dns_configs.contains(&DnsConfig::Mdns) {
+            features.push("mDNS: .local names resolved over multicast DNS, other names through the system resolver".to_string());
+        }
+
+        features
+    }
+
+    /// Render the plan as plain text
+    pub fn format_report(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("Execution Plan (dry run, no requests are sent)\n");
+        output.push_str("==============================================\n\n");
+
+        match self.shard {
+            Some(shard) => output.push_str(&format!("Target URLs ({}, shard {}):\n", self.urls.len(), shard)),
+            None => output.push_str(&format!("Target URLs ({}):\n", self.urls.len())),
+        }
+        for url in &self.urls {
+            output.push_str(&format!("  {}\n", display_url(url)));
+        }
+
+        output.push_str(&format!("\nDNS configurations ({}):\n", self.dns_configs.len()));
+        for dns_config in &self.dns_configs {
+            output.push_str(&format!("  {}\n", self.display_names.resolve(dns_config)));
+        }
+
+        output.push_str(&format!(
+            "\nMatrix: {} URLs × {} configurations × {} iterations = {} requests\n",
+            self.urls.len(),
+            self.dns_configs.len(),
+            self.iterations,
+            self.request_count()
+        ));
+
+        output.push_str(&format!(
+            "Concurrency: {} tasks, up to {} at a time ({} waves); iterations within a task run sequentially\n",
+            self.task_count(),
+            self.concurrency,
+            self.waves()
+        ));
+
+        output.push_str(&format!("Request timeout: {}\n", format_duration(self.timeout)));
+        if let Some(max_runtime) = self.max_runtime {
+            output.push_str(&format!(
+                "Run time budget: {} (iteration counts shrink to fit if needed)\n",
+                format_duration(max_runtime)
+            ));
+        }
+        if let Some(precision) = self.auto_count {
+            output.push_str(&format!(
+                "Auto count: up to {} iterations per task until the mean and p95 are within ±{}%; estimates assume {}\n",
+                MAX_AUTO_COUNT.max(self.iterations),
+                precision,
+                self.iterations
+            ));
+        }
+
+        output.push_str(&format!(
+            "Estimated duration: ~{} at {} per request, at most {} if every request times out\n",
+            format_duration(self.estimated_duration(TYPICAL_REQUEST_LATENCY)),
+            format_duration(TYPICAL_REQUEST_LATENCY),
+            format_duration(self.worst_case_duration())
+        ));
+
+        if !self.sizing_notices.is_empty() {
+            output.push_str("\nChosen for this run:\n");
+            for notice in &self.sizing_notices {
+                output.push_str(&format!("  {}\n", notice));
+            }
+        }
+
+        output.push_str("\nClient features:\n");
+        for feature in self.client_features() {
+            output.push_str(&format!("  {}\n", feature));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(urls: usize, concurrency: usize) -> ExecutionPlan {
+        let config = Config {
+            target_urls: (0..urls).map(|i| format!("https://example{}.com", i)).collect(),
+            test_count: 4,
+            timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let dns_configs = vec![
+            DnsConfig::System,
+            DnsConfig::DoH { url: "https://dns.google/dns-query".to_string() },
+        ];
+        ExecutionPlan::with_concurrency(&config, &dns_configs, concurrency)
+    }
+
+    #[test]
+    fn test_plan_matrix_and_estimates() {
+        let plan = plan(3, 4);
+        assert_eq!(plan.task_count(), 6);
+        assert_eq!(plan.request_count(), 24);
+        assert_eq!(plan.waves(), 2);
+        assert_eq!(plan.estimated_duration(Duration::from_millis(100)), Duration::from_millis(800));
+        assert_eq!(plan.worst_case_duration(), Duration::from_secs(16));
+
+        let report = plan.format_report();
+        assert!(report.contains("3 URLs × 2 configurations × 4 iterations = 24 requests"));
+        assert!(report.contains("DoH (https://dns.google/dns-query)"));
+        assert!(report.contains("DNS-over-HTTPS"));
+    }
+
+    #[test]
+    fn test_plan_respects_max_runtime() {
+        let mut plan = plan(10, 1);
+        plan.max_runtime = Some(Duration::from_secs(30));
+        assert_eq!(plan.worst_case_duration(), Duration::from_secs(30));
+        assert!(plan.format_report().contains("Run time budget: 30s"));
+
+        plan.auto_count = Some(2.0);
+        assert!(plan.format_report().contains("Auto count: up to 200 iterations per task until the mean and p95 are within ±2%"));
+
+        plan.shard = Some(Shard { index: 1, count: 4 });
+        assert!(plan.format_report().contains("Target URLs (10, shard 1/4):"));
+    }
+}
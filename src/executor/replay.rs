@@ -0,0 +1,257 @@
+//! Feeding recorded runs back through the reporting pipeline
+//!
+//! Results exported with `--export` keep every request's timings. [`Replay`]
+//! takes them apart and publishes each request on an [`EventBus`] again, in
+//! the order the requests finished and optionally at the pace they were
+//! recorded at, then builds the results up from those samples. The statistics,
+//! output and health checks of the current version thereby run on captured
+//! data, which makes a formatter or an alerting rule testable offline. The same
+//! export always replays to the same results.
+
+use super::{ConsoleLog, EventBus, ExecutionEvent, ExecutionResults};
+use crate::models::{TestResult, TimingMetrics};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A request of a recorded run
+#[derive(Debug, Clone, Copy)]
+pub struct RecordedSample<'a> {
+    /// The test the request belongs to
+    pub test: &'a TestResult,
+    /// Zero-based iteration of the request within its test
+    pub iteration: u32,
+    pub metrics: &'a TimingMetrics,
+}
+
+/// A recorded run, ready to be replayed
+#[derive(Debug)]
+pub struct Replay {
+    recorded: ExecutionResults,
+    /// Tests of the run in a fixed order, since the results map has none
+    tests: Vec<TestResult>,
+    /// How much faster than recorded to replay, when keeping the recorded pace
+    speed: Option<f64>,
+    /// End of the run replayed before this one, to keep the gap between them
+    after: Option<DateTime<Utc>>,
+    events: Arc<EventBus>,
+}
+
+impl Replay {
+    /// Replay `recorded` as fast as possible, logging each request with `verbose`
+    /// as the executor does
+    pub fn new(mut recorded: ExecutionResults, verbose: bool) -> Self {
+        let mut tests: Vec<TestResult> = std::mem::take(&mut recorded.test_results).into_values().collect();
+        tests.sort_by(|a, b| a.url.cmp(&b.url).then_with(|| a.config_name.cmp(&b.config_name)));
+        let events = Arc::new(EventBus::new());
+        events.subscribe(Arc::new(ConsoleLog::new(verbose)));
+        Self { recorded, tests, speed: None, after: None, events }
+    }
+
+    /// Keep the recorded pace, `speed` times as fast, starting as long after
+    /// `after` (the end of a run replayed before) as the run started after it
+    pub fn paced(mut self, speed: f64, after: Option<DateTime<Utc>>) -> Self {
+        self.speed = Some(speed);
+        self.after = after;
+        self
+    }
+
+    /// Events published during the replay, to subscribe to before [`Replay::run`]
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Every request of the run, in the order they finished
+    pub fn samples(&self) -> Vec<RecordedSample<'_>> {
+        self.ordered().into_iter().map(|(_, sample)| sample).collect()
+    }
+
+    /// [`Replay::samples`] with the index of their test
+    fn ordered(&self) -> Vec<(usize, RecordedSample<'_>)> {
+        let mut samples: Vec<(usize, RecordedSample<'_>)> = self.tests.iter().enumerate()
+            .flat_map(|(index, test)| test.individual_results.iter().enumerate().map(move |(iteration, metrics)| {
+                (index, RecordedSample { test, iteration: iteration as u32, metrics })
+            }))
+            .collect();
+        // Stable, so requests that finished together keep the order of the tests
+        samples.sort_by_key(|(_, sample)| sample.metrics.timestamp);
+        samples
+    }
+
+    /// When the first request was sent
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.samples().iter().map(|sample| sample.metrics.start_time()).min()
+    }
+
+    /// When the last request finished
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.samples().last().map(|sample| sample.metrics.timestamp)
+    }
+
+    /// How long to wait before publishing each of [`Replay::samples`] to keep
+    /// the recorded pace, all zero unless [`Replay::paced`]
+    pub fn waits(&self) -> Vec<Duration> {
+        let samples = self.samples();
+        let Some(speed) = self.speed else {
+            return vec![Duration::ZERO; samples.len()];
+        };
+        let mut previous = self.after.or_else(|| self.started_at());
+        samples.iter()
+            .map(|sample| {
+                let gap = previous.map_or(Duration::ZERO, |previous| {
+                    (sample.metrics.timestamp - previous).to_std().unwrap_or(Duration::ZERO)
+                });
+                previous = Some(sample.metrics.timestamp);
+                gap.div_f64(speed)
+            })
+            .collect()
+    }
+
+    /// Publish the recorded requests and build the results up from them,
+    /// without statistical analysis
+    ///
+    /// The summary keeps what the run recorded about itself, such as its
+    /// duration, machine and clock, while the test counts are taken from the
+    /// rebuilt tests.
+    pub async fn run(&self) -> ExecutionResults {
+        let mut rebuilt: Vec<TestResult> = self.tests.iter()
+            .map(|test| TestResult {
+                individual_results: Vec::new(),
+                statistics: None,
+                success_count: 0,
+                total_count: 0,
+                ..test.clone()
+            })
+            .collect();
+
+        for ((index, sample), wait) in self.ordered().into_iter().zip(self.waits()) {
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+            let test = &mut rebuilt[index];
+            if test.total_count == 0 {
+                self.events.publish(ExecutionEvent::TestStarted {
+                    url: test.url.clone(),
+                    config_name: test.config_name.clone(),
+                    iterations: sample.test.total_count,
+                }).await;
+            }
+            self.events.publish(ExecutionEvent::SampleCompleted {
+                url: test.url.clone(),
+                config_name: test.config_name.clone(),
+                iteration: sample.iteration,
+                metrics: Box::new(sample.metrics.clone()),
+            }).await;
+            test.add_measurement(sample.metrics.clone());
+            if test.total_count == sample.test.total_count {
+                self.events.publish(ExecutionEvent::ConfigFinished {
+                    url: test.url.clone(),
+                    config_name: test.config_name.clone(),
+                    iterations: test.total_count,
+                    successes: test.success_count,
+                    precision: None,
+                }).await;
+            }
+        }
+        for (test, recorded) in rebuilt.iter_mut().zip(&self.tests) {
+            test.calculate_statistics();
+            // Statistics are stamped with the time they were calculated, which would differ on every replay
+            test.completed_at = recorded.completed_at;
+        }
+
+        let mut summary = self.recorded.execution_summary.clone();
+        summary.total_tests = rebuilt.len() as u32;
+        summary.successful_tests = rebuilt.iter().filter(|test| test.success_count > 0).count() as u32;
+        summary.failed_tests = summary.total_tests - summary.successful_tests;
+        summary.success_rate = if summary.total_tests > 0 {
+            summary.successful_tests as f64 / summary.total_tests as f64 * 100.0
+        } else {
+            0.0
+        };
+        self.events.publish(ExecutionEvent::RunCompleted { tests: rebuilt.len(), duration: summary.total_duration }).await;
+
+        let mut results = ExecutionResults::new(summary, rebuilt);
+        results.reproduction = self.recorded.reproduction.clone();
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionSummary;
+    use crate::test_support::success;
+    use crate::types::DnsConfig;
+    use std::sync::Mutex;
+
+    fn recorded_run() -> ExecutionResults {
+        let start = DateTime::parse_from_rfc3339("2026-10-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let sample = |offset_ms: i64, total_ms: Option<u64>| {
+            let mut metrics = match total_ms {
+                Some(total_ms) => success(total_ms),
+                None => TimingMetrics::failed("Connection refused".to_string()),
+            };
+            metrics.timestamp = start + chrono::Duration::milliseconds(offset_ms);
+            metrics
+        };
+        let mut system = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://a.example".to_string());
+        system.add_measurement(sample(1000, Some(100)));
+        system.add_measurement(sample(3000, Some(300)));
+        let mut custom = TestResult::new("Custom DNS".to_string(), DnsConfig::System, "https://b.example".to_string());
+        custom.add_measurement(sample(2000, None));
+        for test in [&mut system, &mut custom] {
+            test.calculate_statistics();
+        }
+
+        let summary = ExecutionSummary {
+            total_duration: Duration::from_secs(3),
+            total_tests: 2,
+            successful_tests: 1,
+            failed_tests: 1,
+            success_rate: 50.0,
+            simulation: Some("normal".to_string()),
+            ..Default::default()
+        };
+        ExecutionResults::new(summary, vec![system, custom])
+    }
+
+    #[tokio::test]
+    async fn test_replay_rebuilds_the_run() {
+        let recorded = recorded_run();
+        let replay = Replay::new(recorded.clone(), false);
+        let order: Vec<(&str, u32)> = replay.samples().iter()
+            .map(|sample| (sample.test.url.as_str(), sample.iteration))
+            .collect();
+        assert_eq!(order, [("https://a.example", 0), ("https://b.example", 0), ("https://a.example", 1)]);
+        assert!(replay.waits().iter().all(Duration::is_zero));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        replay.events().subscribe(Arc::new(move |event: &ExecutionEvent| {
+            if let ExecutionEvent::SampleCompleted { url, .. } = event {
+                recorder.lock().unwrap().push(url.clone());
+            }
+        }));
+        let results = replay.run().await;
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        assert_eq!(results.execution_summary.successful_tests, 1);
+        assert_eq!(results.execution_summary.simulation.as_deref(), Some("normal"));
+        for (key, test) in &recorded.test_results {
+            let replayed = &results.test_results[key];
+            assert_eq!(replayed.total_count, test.total_count);
+            assert_eq!(serde_json::to_value(replayed).unwrap(), serde_json::to_value(test).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_paced_waits() {
+        let replay = Replay::new(recorded_run(), false).paced(2.0, None);
+        // The first request was sent 100ms before it finished at 1s
+        assert_eq!(replay.started_at().map(|start| start.timestamp_subsec_millis()), Some(900));
+        assert_eq!(replay.waits(), [Duration::from_millis(50), Duration::from_millis(500), Duration::from_millis(500)]);
+
+        let previous = replay.finished_at().map(|end| end - chrono::Duration::seconds(10));
+        let waits = Replay::new(recorded_run(), false).paced(1.0, previous).waits();
+        assert_eq!(waits[0], Duration::from_secs(8));
+    }
+}
@@ -0,0 +1,95 @@
+//! Tokio runtime selection
+//!
+//! The multi-threaded runtime starts one worker per CPU core. On routers and
+//! small VMs those workers cost more memory than the probing itself, so
+//! [`RuntimeMode`] falls back to a current-thread runtime there, or whenever
+//! `--single-thread` asks for it. Requests are I/O bound, so one thread keeps
+//! up with the reduced concurrency the executor uses in that mode.
+
+use super::SystemResources;
+use crate::error::Result;
+use tokio::runtime::{Builder, Runtime};
+
+/// Threads for blocking work (system DNS lookups, file I/O) in single-thread mode
+const SINGLE_THREAD_BLOCKING_THREADS: usize = 4;
+
+/// Which tokio runtime the application runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeMode {
+    /// One worker thread per CPU core
+    MultiThread,
+    /// Everything on the calling thread
+    CurrentThread,
+}
+
+impl RuntimeMode {
+    /// Pick single-thread mode when `requested` or when the machine is constrained
+    pub fn select(requested: bool, resources: &SystemResources) -> Self {
+        if requested || resources.is_constrained() {
+            Self::CurrentThread
+        } else {
+            Self::MultiThread
+        }
+    }
+
+    /// Whether this mode runs on a single thread
+    pub fn is_single_thread(self) -> bool {
+        self == Self::CurrentThread
+    }
+
+    /// Build the runtime for this mode
+    pub fn build(self) -> Result<Runtime> {
+        let runtime = match self {
+            Self::MultiThread => Builder::new_multi_thread().enable_all().build()?,
+            Self::CurrentThread => Builder::new_current_thread()
+                .enable_all()
+                .max_blocking_threads(SINGLE_THREAD_BLOCKING_THREADS)
+                .build()?,
+        };
+        Ok(runtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::mock::{MockResponse, MockTransport},
+        executor::OptimizedExecutor,
+        models::Config,
+        types::DnsConfig,
+    };
+    use std::sync::Arc;
+
+    fn resources(cpu_cores: usize) -> SystemResources {
+        SystemResources {
+            cpu_cores,
+            available_memory: 8_000_000_000,
+            optimal_concurrency: 16,
+            max_concurrent_connections: 32,
+        }
+    }
+
+    #[test]
+    fn test_runtime_mode_selection() {
+        assert_eq!(RuntimeMode::select(false, &resources(8)), RuntimeMode::MultiThread);
+        assert_eq!(RuntimeMode::select(true, &resources(8)), RuntimeMode::CurrentThread);
+        assert_eq!(RuntimeMode::select(false, &resources(1)), RuntimeMode::CurrentThread);
+        assert!(RuntimeMode::CurrentThread.is_single_thread());
+    }
+
+    #[test]
+    fn test_executor_on_current_thread_runtime() {
+        let runtime = RuntimeMode::CurrentThread.build().unwrap();
+        let url = "https://api.example";
+        let transport = Arc::new(MockTransport::new().respond(url, vec![MockResponse::ok(50)]));
+        let config = Config { test_count: 3, single_thread: true, ..Default::default() };
+
+        let results = runtime.block_on(async {
+            let executor = OptimizedExecutor::with_client(&config, transport).await.unwrap();
+            executor.execute_optimized_tests(&[url.to_string()], &[DnsConfig::System]).await.unwrap()
+        });
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].success_count, 3);
+    }
+}
@@ -0,0 +1,212 @@
+//! Combining the results of a sharded run
+//!
+//! `--shard i/n` splits a large target list between processes or machines,
+//! each of which exports its own results (see [`Shard`] for how the list is
+//! split). [`merge_shards`] combines those exports into one set of results, as
+//! if a single process had tested the whole list, and notes which shards of
+//! the run are missing from the inputs.
+
+use super::{ExecutionResults, ExecutionSummary};
+use crate::{
+    error::{AppError, Result},
+    models::Reproduction,
+    types::Shard,
+};
+use std::collections::BTreeSet;
+
+/// Results combined from the shards of a run
+#[derive(Debug, Clone)]
+pub struct ShardMerge {
+    /// Combined results, not yet analyzed
+    pub results: ExecutionResults,
+    /// Number of shards the run was split into, when the inputs came from a sharded run
+    pub shard_count: Option<u32>,
+    /// Shards of the run that none of the inputs came from
+    pub missing: Vec<Shard>,
+}
+
+/// Combine exported results from the shards of a run
+///
+/// Inputs without shard information are merged as they are. Fails when the
+/// inputs disagree on the number of shards or contain a shard twice, since
+/// either would mix up or double-count measurements.
+pub fn merge_shards(parts: Vec<ExecutionResults>) -> Result<ShardMerge> {
+    if parts.is_empty() {
+        return Err(AppError::validation("No results to merge"));
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut shard_count = None;
+    for shard in parts.iter().filter_map(|part| part.execution_summary.shard) {
+        if shard_count.is_some_and(|count| count != shard.count) {
+            return Err(AppError::validation(format!(
+                "Cannot merge shards of different runs: found both {} and {} shards",
+                shard_count.unwrap_or_default(), shard.count
+            )));
+        }
+        shard_count = Some(shard.count);
+        if !seen.insert(shard.index) {
+            return Err(AppError::validation(format!("Shard {} appears more than once", shard)));
+        }
+    }
+    let missing = shard_count
+        .map(|count| (1..=count).filter(|index| !seen.contains(index)).map(|index| Shard { index, count }).collect())
+        .unwrap_or_default();
+
+    let single = parts.len() == 1;
+    // Shards sent with different user agents measured different things; only a shared one describes the merge
+    let user_agent = parts[0].execution_summary.user_agent.clone()
+        .filter(|user_agent| parts.iter().all(|part| part.execution_summary.user_agent.as_ref() == Some(user_agent)));
+    // Likewise a network only describes shards that all ran on it
+    let network = parts[0].execution_summary.network.clone()
+        .filter(|network| parts.iter().all(|part| part.execution_summary.network.as_ref() == Some(network)));
+    let baseline_network = network.as_ref().and_then(|_| parts[0].execution_summary.baseline_network.clone());
+    let mut summary = ExecutionSummary {
+        user_agent,
+        // Simulated samples must never pass for measured ones, even mixed with them
+        simulation: parts.iter().find_map(|part| part.execution_summary.simulation.clone()),
+        network,
+        baseline_network,
+        ..Default::default()
+    };
+    let mut merged = ExecutionResults::new(summary.clone(), Vec::new());
+    // Parts from before reproductions were recorded leave the merge without one
+    merged.reproduction = parts.iter()
+        .map(|part| part.reproduction.as_ref())
+        .collect::<Option<Vec<_>>>()
+        .and_then(Reproduction::merge);
+
+    for part in parts {
+        let part_summary = part.execution_summary;
+        // Shards run side by side, so the longest one determines the duration
+        summary.total_duration = summary.total_duration.max(part_summary.total_duration);
+        summary.total_tests += part_summary.total_tests;
+        summary.successful_tests += part_summary.successful_tests;
+        summary.failed_tests += part_summary.failed_tests;
+        summary.timeout_tests += part_summary.timeout_tests;
+        summary.skipped_tests += part_summary.skipped_tests;
+        summary.performance_summary.extend(part_summary.performance_summary);
+        summary.interference.extend(part_summary.interference);
+        summary.packet_loss.extend(part_summary.packet_loss);
+        summary.physical_bounds.extend(part_summary.physical_bounds);
+        // Shards share hosts when they split the paths of one site; the public side is the same
+        for comparison in part_summary.public_baseline {
+            if !summary.public_baseline.iter().any(|kept| kept.target == comparison.target) {
+                summary.public_baseline.push(comparison);
+            }
+        }
+        for (config_name, reuse) in &part_summary.connection_reuse {
+            summary.connection_reuse.entry(config_name.clone()).or_default().merge(reuse);
+        }
+        for (config_name, failover) in &part_summary.resolver_failover {
+            summary.resolver_failover.entry(config_name.clone()).or_default().merge(failover);
+        }
+        for (config_name, distribution) in &part_summary.server_distribution {
+            summary.server_distribution.entry(config_name.clone()).or_default().merge(distribution);
+        }
+        summary.packet_captures.extend(part_summary.packet_captures);
+        for (url, tags) in part_summary.url_tags {
+            let merged_tags = summary.url_tags.entry(url).or_default();
+            for tag in tags {
+                if !merged_tags.contains(&tag) {
+                    merged_tags.push(tag);
+                }
+            }
+        }
+        // Machine snapshots only describe a run made on one machine
+        if single {
+            summary.system = part_summary.system;
+        }
+        // Keep the clock report with the largest drift, which is the one to warn about
+        if let Some(clock) = part_summary.clock {
+            if summary.clock.as_ref().is_none_or(|kept| clock.drift_ms.abs() > kept.drift_ms.abs()) {
+                summary.clock = Some(clock);
+            }
+        }
+
+        for (key, result) in part.test_results {
+            match merged.test_results.get_mut(&key) {
+                Some(existing) => existing.merge(result),
+                None => {
+                    merged.test_results.insert(key, result);
+                }
+            }
+        }
+    }
+
+    summary.success_rate = if summary.total_tests > 0 {
+        summary.successful_tests as f64 / summary.total_tests as f64 * 100.0
+    } else {
+        0.0
+    };
+    summary.interference.sort_by_key(|interval| interval.started_at);
+    merged.execution_summary = summary;
+
+    Ok(ShardMerge { results: merged, shard_count, missing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Config, test_support::result_with_failures};
+    use std::time::Duration;
+
+    fn shard_results(shard: Option<Shard>, url: &str, successes: u32) -> ExecutionResults {
+        let result = result_with_failures("System DNS", url, &vec![100; successes as usize], 1);
+
+        let (successful_tests, failed_tests) = if successes > 0 { (1, 0) } else { (0, 1) };
+        let summary = ExecutionSummary {
+            total_duration: Duration::from_secs(u64::from(successes)),
+            total_tests: 1,
+            successful_tests,
+            failed_tests,
+            success_rate: successful_tests as f64 * 100.0,
+            shard,
+            ..Default::default()
+        };
+        let mut results = ExecutionResults::new(summary, vec![result]);
+        let config = Config { target_urls: vec![url.to_string()], shard, ..Default::default() };
+        results.reproduction = Some(Reproduction::from_config(&config));
+        results
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let first = Some(Shard { index: 1, count: 3 });
+        let third = Some(Shard { index: 3, count: 3 });
+        let merge = merge_shards(vec![
+            shard_results(first, "https://a.example", 2),
+            shard_results(third, "https://c.example", 0),
+        ])
+        .unwrap();
+
+        assert_eq!(merge.shard_count, Some(3));
+        assert_eq!(merge.missing, [Shard { index: 2, count: 3 }]);
+        let summary = &merge.results.execution_summary;
+        assert_eq!((summary.total_tests, summary.successful_tests, summary.failed_tests), (2, 1, 1));
+        assert_eq!(summary.success_rate, 50.0);
+        assert_eq!(summary.total_duration, Duration::from_secs(2));
+        assert!(summary.shard.is_none());
+        assert_eq!(merge.results.test_results.len(), 2);
+        assert_eq!(merge.results.reproduction.unwrap().urls, ["https://a.example", "https://c.example"]);
+
+        // The same URL in two inputs is combined into one result
+        let merge = merge_shards(vec![
+            shard_results(None, "https://a.example", 2),
+            shard_results(None, "https://a.example", 1),
+        ])
+        .unwrap();
+        assert!(merge.missing.is_empty());
+        let result = merge.results.get_result("https://a.example", "System DNS").unwrap();
+        assert_eq!((result.success_count, result.total_count), (3, 5));
+
+        let error = merge_shards(vec![shard_results(first, "https://a.example", 1), shard_results(first, "https://b.example", 1)]);
+        assert!(error.unwrap_err().to_string().contains("Shard 1/3 appears more than once"));
+        let error = merge_shards(vec![
+            shard_results(first, "https://a.example", 1),
+            shard_results(Some(Shard { index: 2, count: 4 }), "https://b.example", 1),
+        ]);
+        assert!(error.unwrap_err().to_string().contains("found both 3 and 4 shards"));
+        assert!(merge_shards(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Default concurrency, timeout and pool sizes for the size of a run
+//!
+//! A fixed concurrency of twice the CPU cores suits neither end: one URL with
+//! three resolvers never has more than three tasks to run, while hundreds of
+//! URL × configuration tasks keep every permit busy for many waves, where a
+//! handful of dead targets holding the default timeout stretch each wave and
+//! ten idle connections per host add up. [`RunSizing`] picks the values from
+//! the matrix and [`SystemResources`], and keeps a notice for each choice,
+//! which verbose runs and `--plan` print. `--pool-options` replace the pool
+//! settings chosen here.
+
+use super::{optimized::SINGLE_THREAD_CONCURRENCY, PoolConfig, SystemResources};
+use crate::{models::Config, utils::duration::format_duration};
+use std::time::Duration;
+
+/// Tasks from which a run counts as large
+pub const LARGE_RUN_TASKS: usize = 200;
+/// Default timeout of a large run, when no timeout is given
+pub const LARGE_RUN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Idle connections kept per host in a large run
+const LARGE_RUN_IDLE_PER_HOST: usize = 2;
+
+/// Concurrency, timeout and pool sizes chosen for a run
+#[derive(Debug, Clone)]
+pub struct RunSizing {
+    /// URL × configuration tasks run at the same time
+    pub concurrency: usize,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Pool sizes of every client, connecting within the timeout
+    pub pool: PoolConfig,
+    /// Why each value was chosen
+    pub notices: Vec<String>,
+}
+
+impl RunSizing {
+    /// Size a run of `config` against `config_count` DNS configurations on a
+    /// machine with `resources`
+    pub fn choose(config: &Config, config_count: usize, resources: &SystemResources) -> Self {
+        let tasks = (config.target_urls.len() * config_count).max(1);
+        let large = tasks >= LARGE_RUN_TASKS;
+        let mut notices = Vec::new();
+        let (pool, base_concurrency) = if config.single_thread {
+            (PoolConfig::constrained(), SINGLE_THREAD_CONCURRENCY)
+        } else {
+            (PoolConfig::default(), resources.optimal_concurrency)
+        };
+
+        // More permits than tasks only cost memory
+        let concurrency = base_concurrency.min(tasks).max(1);
+        if concurrency < base_concurrency {
+            notices.push(format!(
+                "Concurrency {}: the run has {} URL × configuration tasks, fewer than the {} this machine allows",
+                concurrency, tasks, base_concurrency
+            ));
+        } else if config.single_thread {
+            notices.push(format!(
+                "Concurrency {}: single-thread mode, {} tasks in {} waves",
+                concurrency, tasks, tasks.div_ceil(concurrency)
+            ));
+        } else {
+            notices.push(format!(
+                "Concurrency {}: twice the {} CPU cores within limits, {} tasks in {} waves",
+                concurrency, resources.cpu_cores, tasks, tasks.div_ceil(concurrency)
+            ));
+        }
+
+        // A timeout given on the command line, in the environment or a file is kept
+        let timeout = if large && !config.timeout_given {
+            notices.push(format!(
+                "Timeout {}: {} tasks run in many waves, so unresponsive targets are given up on sooner; pass --timeout to override",
+                format_duration(LARGE_RUN_TIMEOUT), tasks
+            ));
+            LARGE_RUN_TIMEOUT
+        } else {
+            config.timeout
+        };
+
+        // Each configuration has its own client, whose tasks for a host run one at a time
+        let given = config.pool_options.unwrap_or_default();
+        let (max_idle_per_host, max_connections) = if large {
+            let idle = given.max_idle_per_host.unwrap_or(pool.max_idle_per_host.min(LARGE_RUN_IDLE_PER_HOST));
+            let connections = pool.max_connections.min(concurrency * LARGE_RUN_IDLE_PER_HOST).max(1);
+            notices.push(format!(
+                "Connection pool: up to {} idle per host and {} connections in all, as each host sees one task per configuration at a time",
+                idle, connections
+            ));
+            (idle, connections)
+        } else {
+            (given.max_idle_per_host.unwrap_or(pool.max_idle_per_host), pool.max_connections)
+        };
+
+        let pool = PoolConfig {
+            max_idle_per_host,
+            max_connections,
+            connect_timeout: pool.connect_timeout.min(timeout),
+            keep_alive_timeout: given.idle_timeout.unwrap_or(pool.keep_alive_timeout),
+            tcp_keep_alive: given.tcp_keepalive.unwrap_or(pool.tcp_keep_alive),
+            tcp_nodelay: given.tcp_nodelay.unwrap_or(pool.tcp_nodelay),
+            ..pool
+        };
+        if let Some(options) = config.pool_options {
+            notices.push(format!("Connection pool: {} as given", options));
+        }
+        Self { concurrency, timeout, pool, notices }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defaults::DEFAULT_TIMEOUT;
+
+    fn resources() -> SystemResources {
+        SystemResources {
+            cpu_cores: 8,
+            available_memory: 8_000_000_000,
+            optimal_concurrency: 16,
+            max_concurrent_connections: 32,
+        }
+    }
+
+    fn config(urls: usize) -> Config {
+        Config {
+            target_urls: (0..urls).map(|i| format!("https://site{}.example/", i)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_sizing() {
+        // A small run only needs as many permits as it has tasks
+        let small = RunSizing::choose(&config(1), 3, &resources());
+        assert_eq!(small.concurrency, 3);
+        assert_eq!(small.timeout, DEFAULT_TIMEOUT);
+        assert_eq!((small.pool.max_idle_per_host, small.pool.max_connections), (10, 100));
+        assert!(small.notices[0].contains("3 URL × configuration tasks"));
+
+        // A large run shortens the default timeout and trims the pool
+        let large = RunSizing::choose(&config(50), 5, &resources());
+        assert_eq!(large.concurrency, 16);
+        assert_eq!(large.timeout, LARGE_RUN_TIMEOUT);
+        assert_eq!((large.pool.max_idle_per_host, large.pool.max_connections), (2, 32));
+        assert_eq!(large.pool.connect_timeout, LARGE_RUN_TIMEOUT);
+        assert!(large.notices[0].contains("250 tasks in 16 waves"));
+        assert_eq!(large.notices.len(), 3);
+
+        // ... but keeps a timeout that was given, even the default's value
+        let given = Config { timeout: Duration::from_secs(20), timeout_given: true, ..config(50) };
+        assert_eq!(RunSizing::choose(&given, 5, &resources()).timeout, Duration::from_secs(20));
+        let given = Config { timeout_given: true, ..config(50) };
+        assert_eq!(RunSizing::choose(&given, 5, &resources()).timeout, DEFAULT_TIMEOUT);
+
+        let single = RunSizing::choose(&Config { single_thread: true, ..config(4) }, 3, &resources());
+        assert_eq!((single.concurrency, single.pool.max_idle_per_host), (2, 1));
+
+        // Pool options replace the sizes chosen for the run
+        let cold = Config { pool_options: Some("idle-per-host:0 keepalive:off nodelay:off".parse().unwrap()), ..config(50) };
+        let cold = RunSizing::choose(&cold, 5, &resources());
+        assert_eq!((cold.pool.max_idle_per_host, cold.pool.max_connections), (0, 32));
+        assert_eq!((cold.pool.tcp_keep_alive, cold.pool.tcp_nodelay), (None, false));
+        assert_eq!(cold.pool.keep_alive_timeout, PoolConfig::default().keep_alive_timeout);
+        assert!(cold.notices.last().unwrap().contains("idle-per-host:0 keepalive:off nodelay:off as given"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Per-request debug trace for `--trace-requests`
+//!
+//! The results say how long a request took, not what it went through on the
+//! way. With `--trace-requests`, every run writes one JSON line per request to
+//! `traces/trace-RUN_ID.jsonl`, away from the normal output: the addresses the
+//! configuration's resolver answers with, the connection the request went out
+//! on, the server's certificate, each hop of a redirect chain with its
+//! response headers, and how the request ended. Requests are not retried, so
+//! the hops are every request a sample sent. Each line carries the run ID and
+//! the index of the sample in the run, which a bug report can quote.
+
+use crate::{
+    client::certificate::PeerCertificate,
+    dns::DnsManager,
+    error::{AppError, Result},
+    models::{Name, TimingMetrics},
+    types::{DnsConfig, TestStatus},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Directory trace files are written to
+pub const TRACE_DIR: &str = "traces";
+
+/// Everything known about one request of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestTrace {
+    pub run_id: String,
+    /// Index of the sample in the run, in the order the requests started
+    pub sample: u64,
+    pub url: Name,
+    pub config_name: Name,
+    /// Iteration of the sample for its URL and configuration
+    pub iteration: u32,
+    pub started_at: DateTime<Utc>,
+    /// Addresses the configuration's resolver answers with, looked up again
+    /// once the request is timed
+    #[serde(default)]
+    pub addresses: Vec<IpAddr>,
+    /// Why that lookup failed
+    #[serde(default)]
+    pub resolver_error: Option<String>,
+    /// Connection the final hop went out on
+    #[serde(default)]
+    pub connection: Option<TracedConnection>,
+    /// TLS of the final hop, for `https` URLs
+    #[serde(default)]
+    pub tls: Option<TracedTls>,
+    /// Requests sent for the sample, redirects first
+    #[serde(default)]
+    pub hops: Vec<TracedHop>,
+    pub status: TestStatus,
+    pub total_ms: f64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Addresses of the connection a request used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracedConnection {
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    /// Whether an earlier request had opened it, when that could be told
+    pub reused: Option<bool>,
+}
+
+/// What is known of a TLS session; reqwest does not report the negotiated
+/// version or cipher suite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracedTls {
+    /// HTTP version and TLS choices in effect, e.g. `default` or `TLS 1.3`
+    pub settings: String,
+    /// The server's leaf certificate
+    pub certificate: Option<PeerCertificate>,
+    /// Whether a new connection resumed an earlier session, with `--tls-resumption`
+    #[serde(default)]
+    pub resumed: Option<bool>,
+}
+
+/// One request of a sample and the response head it got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracedHop {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// HTTP version of the response, which shows what ALPN settled on
+    pub version: String,
+    pub duration_ms: f64,
+    /// Response headers in the order they came
+    pub headers: Vec<(String, String)>,
+}
+
+impl TracedHop {
+    /// Record a response that took `duration` since its request was sent
+    pub fn of(method: &reqwest::Method, response: &reqwest::Response, duration: Duration) -> Self {
+        Self {
+            method: method.to_string(),
+            url: response.url().to_string(),
+            status: response.status().as_u16(),
+            version: format!("{:?}", response.version()),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            headers: response.headers().iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl RequestTrace {
+    /// Take the outcome of the finished request
+    pub fn finish(&mut self, metrics: &TimingMetrics) {
+        self.status = metrics.status;
+        self.total_ms = metrics.total_ms();
+        self.error = metrics.error_message.clone();
+        if let Some(ref mut connection) = self.connection {
+            connection.reused = metrics.reused_connection;
+        }
+    }
+
+    /// Record what the resolver of `dns_config` answers for the URL's host
+    pub async fn resolve(&mut self, dns_manager: &DnsManager, dns_config: &DnsConfig) {
+        let Some(host) = url::Url::parse(&self.url).ok().and_then(|url| url.host_str().map(str::to_string)) else { return };
+        match dns_manager.resolve(host.trim_matches(|c| c == '[' || c == ']'), dns_config).await {
+            Ok(addresses) => self.addresses = addresses,
+            Err(e) => self.resolver_error = Some(e.to_string()),
+        }
+    }
+}
+
+/// Writes the traces of one run to its own file
+#[derive(Debug)]
+pub struct RequestTracer {
+    run_id: String,
+    path: PathBuf,
+    file: Mutex<File>,
+    samples: AtomicU64,
+}
+
+impl RequestTracer {
+    /// Start the trace file of a new run in `directory`
+    pub fn create(directory: &Path) -> Result<Self> {
+        let now = Utc::now();
+        let run_id = format!("{}-{}", now.format("%Y%m%d-%H%M%S"), &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        std::fs::create_dir_all(directory)
+            .map_err(|e| AppError::io(format!("Cannot create {}: {}", directory.display(), e)).with_source(e))?;
+        let path = directory.join(format!("trace-{}.jsonl", run_id));
+        let file = File::create(&path)
+            .map_err(|e| AppError::io(format!("Cannot create trace file {}: {}", path.display(), e)).with_source(e))?;
+        Ok(Self { run_id, path, file: Mutex::new(file), samples: AtomicU64::new(0) })
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// File the traces go to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Begin the trace of a request about to be sent
+    pub fn start(&self, url: impl Into<Name>, config_name: impl Into<Name>, iteration: u32) -> RequestTrace {
+        RequestTrace {
+            run_id: self.run_id.clone(),
+            sample: self.samples.fetch_add(1, Ordering::Relaxed),
+            url: url.into(),
+            config_name: config_name.into(),
+            iteration,
+            started_at: Utc::now(),
+            addresses: Vec::new(),
+            resolver_error: None,
+            connection: None,
+            tls: None,
+            hops: Vec::new(),
+            status: TestStatus::Skipped,
+            total_ms: 0.0,
+            error: None,
+        }
+    }
+
+    /// Append a finished trace as one line
+    pub fn write(&self, trace: &RequestTrace) -> Result<()> {
+        let mut line = serde_json::to_string(trace)
+            .map_err(|e| AppError::io(format!("Failed to serialize request trace: {}", e)).with_source(e))?;
+        line.push('\n');
+        self.file.lock().unwrap().write_all(line.as_bytes())
+            .map_err(|e| AppError::io(format!("Failed to write {}: {}", self.path.display(), e)).with_source(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::success;
+
+    #[test]
+    fn test_request_tracer() {
+        let directory = tempfile::tempdir().unwrap();
+        let tracer = RequestTracer::create(directory.path()).unwrap();
+        assert!(tracer.path().ends_with(format!("trace-{}.jsonl", tracer.run_id())));
+
+        let mut first = tracer.start("https://example.com/", "System DNS", 0);
+        let second = tracer.start("https://example.com/", "System DNS", 1);
+        assert_eq!((first.sample, second.sample), (0, 1));
+        first.connection = Some(TracedConnection {
+            local: "192.168.1.10:50514".parse().unwrap(),
+            remote: "93.184.215.14:443".parse().unwrap(),
+            reused: None,
+        });
+        let metrics = success(42).with_reused_connection(Some(false));
+        first.finish(&metrics);
+        assert_eq!(first.status, TestStatus::Success);
+        assert_eq!(first.connection.as_ref().unwrap().reused, Some(false));
+        tracer.write(&first).unwrap();
+        tracer.write(&second).unwrap();
+
+        let text = std::fs::read_to_string(tracer.path()).unwrap();
+        let lines: Vec<RequestTrace> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines, [first, second]);
+        assert!(lines.iter().all(|trace| trace.run_id == tracer.run_id()));
+    }
+}
@@ -230,7 +230,7 @@ impl ConcurrencyTuner {
     /// Calculate initial execution parameters based on system resources
     fn calculate_initial_parameters(resources: &SystemResources, config: &Config) -> Result<ExecutionParameters> {
         let base_concurrency = resources.optimal_concurrency;
-        let base_timeout = Duration::from_secs(config.timeout_seconds);
+        let base_timeout = config.timeout;
         
         // Adjust based on URL count - more URLs need less concurrency per URL
         let url_count = config.target_urls.len().max(1);
@@ -691,7 +691,7 @@ impl AdaptiveLearningState {
         
         // Explore periodically based on exploration factor
         let exploration_frequency = (1.0 / self.exploration_factor) as usize;
-        self.iteration_count % exploration_frequency == 0
+        self.iteration_count.is_multiple_of(exploration_frequency)
     }
     
     /// Update performance improvement trend
@@ -722,6 +722,7 @@ impl AdaptiveLearningState {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use std::time::Duration;
@@ -0,0 +1,300 @@
+//! Where the client and the tested servers are
+//!
+//! `--geo` locates the client's public address and every address the requests
+//! went to with an IP geolocation service, then sets the connect time of each
+//! route against the time light needs to cover the distance (see
+//! [`crate::stats::physics`]) as a [`PhysicalBound`]. Geolocation places an
+//! address by city at best, and an anycast address wherever its operator
+//! registered it, so the bound is a sanity check rather than a measurement.
+//! Private and reserved addresses are never sent to the service.
+
+use crate::{
+    error::{AppError, Result},
+    models::{PhysicalBound, TestResult, TimingMetrics},
+    stats::physics::{self, GeoPoint},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// IP geolocation service (ip-api.com); its free tier is plain HTTP only
+pub const GEO_API: &str = "http://ip-api.com";
+/// Most addresses the service locates per batch request
+const BATCH_SIZE: usize = 100;
+/// Fields requested for each address
+const FIELDS: &str = "status,message,lat,lon,city,countryCode,as,query";
+
+/// Where the client is, as given to `--geo`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientLocation {
+    /// Locate the client's public address
+    Detect,
+    /// A known position, e.g. where a VPN would mislead the lookup
+    At(GeoPoint),
+}
+
+impl FromStr for ClientLocation {
+    type Err = AppError;
+
+    /// `auto`, or `LATITUDE,LONGITUDE`
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            return Ok(ClientLocation::Detect);
+        }
+        s.parse().map(ClientLocation::At)
+    }
+}
+
+impl fmt::Display for ClientLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientLocation::Detect => write!(f, "auto"),
+            ClientLocation::At(point) => write!(f, "{}", point),
+        }
+    }
+}
+
+/// Where an address is
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpLocation {
+    pub point: GeoPoint,
+    pub city: Option<String>,
+    /// ISO 3166 country code
+    pub country: Option<String>,
+    /// Autonomous system announcing the address, e.g. `AS15169 Google LLC`
+    pub asn: Option<String>,
+}
+
+impl fmt::Display for IpLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.city, &self.country) {
+            (Some(city), Some(country)) => write!(f, "{}, {}", city, country),
+            (Some(place), None) | (None, Some(place)) => write!(f, "{}", place),
+            (None, None) => write!(f, "{}", self.point),
+        }
+    }
+}
+
+/// An answer of the geolocation service
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Located {
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    lat: f64,
+    #[serde(default)]
+    lon: f64,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    country_code: Option<String>,
+    #[serde(default, rename = "as")]
+    asn: Option<String>,
+    #[serde(default)]
+    query: String,
+}
+
+impl Located {
+    fn location(self) -> Result<IpLocation> {
+        if self.status != "success" {
+            return Err(AppError::network(format!(
+                "Could not locate {}: {}", self.query, self.message.as_deref().unwrap_or("no reason given")
+            )));
+        }
+        let non_empty = |value: Option<String>| value.filter(|value| !value.is_empty());
+        Ok(IpLocation {
+            point: GeoPoint::new(self.lat, self.lon)?,
+            city: non_empty(self.city),
+            country: non_empty(self.country_code),
+            asn: non_empty(self.asn),
+        })
+    }
+}
+
+/// Client of the IP geolocation service
+#[derive(Debug, Clone)]
+pub struct GeoLocator {
+    client: Client,
+    api: String,
+}
+
+impl GeoLocator {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        Self::with_api(GEO_API, timeout)
+    }
+
+    /// The service served at `api`, for mirrors and tests
+    pub fn with_api(api: &str, timeout: Duration) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| AppError::network(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
+        Ok(Self { client, api: api.trim_end_matches('/').to_string() })
+    }
+
+    /// Where the client's public address is
+    pub async fn locate_client(&self) -> Result<IpLocation> {
+        self.public_address().await.map(|(_, location)| location)
+    }
+
+    /// The client's public address, and where it is
+    pub async fn public_address(&self) -> Result<(IpAddr, IpLocation)> {
+        let request = self.client.get(format!("{}/json/", self.api)).query(&[("fields", FIELDS)]);
+        let located: Located = send(request).await?;
+        let ip = located.query.parse::<IpAddr>()
+            .map_err(|e| AppError::network(format!("Geolocation service answered with an invalid address: {}", e)))?;
+        Ok((ip, located.location()?))
+    }
+
+    /// Where each of `ips` is; private and reserved addresses, and addresses
+    /// the service could not place, are left out
+    pub async fn locate(&self, ips: &[IpAddr]) -> Result<HashMap<IpAddr, IpLocation>> {
+        let public: Vec<String> = ips.iter().filter(|ip| is_locatable(ip)).map(ToString::to_string).collect();
+        let mut locations = HashMap::new();
+        for batch in public.chunks(BATCH_SIZE) {
+            let request = self.client.post(format!("{}/batch", self.api)).query(&[("fields", FIELDS)]).json(batch);
+            let answers: Vec<Located> = send(request).await?;
+            for answer in answers {
+                let Ok(ip) = answer.query.parse::<IpAddr>() else { continue };
+                if let Ok(location) = answer.location() {
+                    locations.insert(ip, location);
+                }
+            }
+        }
+        Ok(locations)
+    }
+}
+
+async fn send<T: serde::de::DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let failed = |e: reqwest::Error| AppError::network(format!("Geolocation request failed: {}", e)).with_source(e);
+    request.send().await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?
+        .json().await
+        .map_err(failed)
+}
+
+/// Whether an address is on the public internet, so that locating it means something
+pub fn is_locatable(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+            || ip.is_documentation() || ip.is_broadcast() || ip.is_multicast()
+            // Carrier-grade NAT (100.64.0.0/10)
+            || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)),
+        IpAddr::V6(ip) => !(ip.is_loopback() || ip.is_unspecified() || ip.is_unique_local()
+            || ip.is_unicast_link_local() || ip.is_multicast()),
+    }
+}
+
+/// Each route of `results` (a host and an address its requests went to) with
+/// its connect time against the physical minimum from `client`
+pub fn physical_bounds(results: &[&TestResult], client: GeoPoint, locations: &HashMap<IpAddr, IpLocation>) -> Vec<PhysicalBound> {
+    let mut routes: BTreeMap<(String, IpAddr), Vec<&TimingMetrics>> = BTreeMap::new();
+    for result in results {
+        let Some(host) = url::Url::parse(&result.url).ok().and_then(|url| url.host_str().map(str::to_string)) else {
+            continue;
+        };
+        for metrics in &result.individual_results {
+            if let Some(ip) = metrics.resolved_ip {
+                routes.entry((host.clone(), ip)).or_default().push(metrics);
+            }
+        }
+    }
+
+    routes.into_iter()
+        .filter_map(|((target, ip), metrics)| {
+            let location = locations.get(&ip)?;
+            let distance_km = client.distance_km(&location.point);
+            Some(PhysicalBound {
+                target,
+                ip,
+                location: location.to_string(),
+                asn: location.asn.clone(),
+                distance_km,
+                min_rtt_ms: physics::min_rtt_ms(distance_km),
+                connect_ms: TimingMetrics::median_connect_ms(metrics),
+            })
+        })
+        .collect()
+}
+
+/// Locate the client (unless given) and the routes of `results`, and bound
+/// each route's connect time
+pub async fn locate_routes(results: &[&TestResult], client: ClientLocation, timeout: Duration) -> Result<Vec<PhysicalBound>> {
+    let locator = GeoLocator::new(timeout)?;
+    let client = match client {
+        ClientLocation::At(point) => point,
+        ClientLocation::Detect => locator.locate_client().await?.point,
+    };
+    let mut ips: Vec<IpAddr> = results.iter()
+        .flat_map(|result| result.individual_results.iter().filter_map(|metrics| metrics.resolved_ip))
+        .collect();
+    ips.sort();
+    ips.dedup();
+    let locations = locator.locate(&ips).await?;
+    Ok(physical_bounds(results, client, &locations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DnsConfig;
+    use wiremock::{matchers::{method, path}, Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_locate_routes() {
+        let server = MockServer::start().await;
+        let json = |body: &str| ResponseTemplate::new(200).set_body_raw(body.to_string(), "application/json");
+        Mock::given(method("GET")).and(path("/json/"))
+            .respond_with(json(r#"{"status": "success", "lat": 52.52, "lon": 13.405, "city": "Berlin", "countryCode": "DE", "query": "198.51.100.7"}"#))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/batch"))
+            .respond_with(json(r#"[
+                {"status": "success", "lat": 40.7128, "lon": -74.006, "city": "New York", "countryCode": "US", "as": "AS15133 Edgecast Inc.", "query": "93.184.216.34"},
+                {"status": "fail", "message": "reserved range", "query": "240.0.0.1"}
+            ]"#))
+            .mount(&server).await;
+        let locator = GeoLocator::with_api(&server.uri(), Duration::from_secs(5)).unwrap();
+        let client = locator.locate_client().await.unwrap();
+        assert_eq!(client.to_string(), "Berlin, DE");
+
+        let ips: Vec<IpAddr> = ["93.184.216.34", "240.0.0.1", "10.0.0.9"].iter().map(|ip| ip.parse().unwrap()).collect();
+        let locations = locator.locate(&ips).await.unwrap();
+        assert_eq!(locations.len(), 1);
+        // Private addresses are not sent at all
+        let requests = server.received_requests().await.unwrap();
+        let batch = requests.iter().find(|request| request.url.path() == "/batch").unwrap();
+        assert!(!String::from_utf8_lossy(&batch.body).contains("10.0.0.9"));
+
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://example.com".to_string());
+        for connect_ms in [95, 105] {
+            let tcp = Duration::from_millis(connect_ms);
+            let mut metrics = TimingMetrics::success(Duration::ZERO, tcp, None, tcp, tcp, 200).with_first_use(true);
+            metrics.resolved_ip = Some(ips[0]);
+            result.add_measurement(metrics);
+        }
+        let bounds = physical_bounds(&[&result], client.point, &locations);
+        assert_eq!(bounds.len(), 1);
+        assert_eq!(bounds[0].connect_ms, Some(100.0));
+        assert_eq!(bounds[0].asn.as_deref(), Some("AS15133 Edgecast Inc."));
+        assert!(!bounds[0].is_indirect());
+        assert_eq!(bounds[0].to_string(), "connect 100.0ms, 1.6x above the physical minimum of 63.9ms over 6385km (New York, US)");
+    }
+
+    #[test]
+    fn test_client_location() {
+        assert_eq!("auto".parse::<ClientLocation>().unwrap(), ClientLocation::Detect);
+        assert_eq!("52.52,13.405".parse::<ClientLocation>().unwrap(), ClientLocation::At(GeoPoint::new(52.52, 13.405).unwrap()));
+        assert!("berlin".parse::<ClientLocation>().is_err());
+        assert!(is_locatable(&"93.184.216.34".parse().unwrap()));
+        assert!(!is_locatable(&"100.100.0.1".parse().unwrap()));
+        assert!(!is_locatable(&"fd00::1".parse().unwrap()));
+    }
+}
@@ -20,29 +20,95 @@
 //! - Interactive version selection with fallback to basic input
 //! - Safe downgrade protection with force override option
 //! - Semantic version parsing and comparison
+//!
+//! ## Cargo Features
+//!
+//! The runtime-agnostic core ([`models`], [`stats`], [`probe`], [`timing`],
+//! [`error`] and [`utils`]) is always built and has no tokio, reqwest or socket dependency,
+//! so it compiles for `wasm32-wasi`. Everything else is optional:
+//!
+//! - `native` (default): the tokio/reqwest networking stack, DNS resolvers,
+//!   executor, CLI, configuration loading and terminal output
+//! - `updater` (default): self-update support behind `--update`
+//! - `doh` (default): DNS-over-HTTPS resolution; without it DoH providers are skipped
+//! - `diagnostics` (default): the [`diagnostics`] module and its reports
+//! - `tui`: interactive version picker for the updater
+//! - `mock`: scripted transport and resolver in `client::mock` for tests, the
+//!   local DNS and DoH server in `dns::mock_server` and the `serve-mock-dns` subcommand
+//!
+//! Native embedders that do not need the updater, DoH or diagnostics can use
+//! `default-features = false, features = ["native"]`; edge and WASI embedders
+//! use `default-features = false` and drive a [`Prober`] through their own
+//! [`Fetch`] implementation.
 
+#[cfg(feature = "native")]
 pub mod app;
+#[cfg(feature = "native")]
+pub mod baseline;
+#[cfg(feature = "native")]
+pub mod bench;
+#[cfg(feature = "native")]
+pub mod cache;
+#[cfg(feature = "native")]
+pub mod cancel;
+#[cfg(feature = "native")]
+pub mod capture;
+#[cfg(feature = "native")]
 pub mod cli;
+#[cfg(feature = "native")]
 pub mod config;
+#[cfg(feature = "native")]
+pub mod control;
+#[cfg(feature = "native")]
 pub mod client;
+#[cfg(feature = "native")]
 pub mod dns;
 pub mod error;
+#[cfg(feature = "native")]
+pub mod geo;
+#[cfg(feature = "native")]
 pub mod logging;
+#[cfg(feature = "native")]
+pub mod loss;
 pub mod stats;
+#[cfg(feature = "diagnostics")]
 pub mod diagnostics;
+#[cfg(feature = "native")]
 pub mod executor;
+#[cfg(feature = "native")]
+pub mod network;
+#[cfg(feature = "native")]
+pub mod notification;
+#[cfg(feature = "native")]
 pub mod output;
+#[cfg(feature = "native")]
+pub mod ping;
+pub mod probe;
+#[cfg(feature = "native")]
+pub mod query;
+#[cfg(feature = "native")]
+pub mod serve;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod models;
+pub mod timing;
+#[cfg(feature = "native")]
+pub mod traceroute;
 pub mod types;
+#[cfg(feature = "updater")]
 pub mod updater;
 pub mod utils;
 
 // Re-export commonly used types
 pub use error::{AppError, Result};
 pub use models::{Config, TimingMetrics, TestResult, Statistics};
-pub use stats::{StatisticsEngine, StatisticalAnalysis, ExtendedStatistics, OptimizedStatisticsCalculator, RollingStats};
+pub use stats::{StatisticsEngine, StatisticalAnalysis, ExtendedStatistics, OptimizedStatisticsCalculator, RollingStats, StreamingAnalyzer};
+#[cfg(feature = "diagnostics")]
 pub use diagnostics::{NetworkDiagnostics, DiagnosticReport, SystemHealth};
+pub use probe::{Fetch, FetchRequest, FetchResponse, Prober};
+#[cfg(feature = "native")]
 pub use output::{OutputFormatter, ColoredFormatter, PlainFormatter, OutputCoordinator, OutputFormatterFactory, VerboseTimingFormatter};
+#[cfg(feature = "updater")]
 pub use updater::{
     UpdateCoordinator, UpdateArgs, UpdateResult, UpdateMode, 
     Version, Release, ReleaseAsset, 
@@ -50,8 +116,8 @@ pub use updater::{
     VersionManager, 
     CacheManager, CacheStats,
     FeedsClient, FeedStats,
-    GitHubApiClient, GitHubApiStats, ApiAvailability, RateLimitInfo,
-    DataSourceManager, DataSourceStats, DataSourceStatus, DataSourcePriority,
+    GitHubApiClient, GitHubApiStats, ApiAvailability, RateLimitInfo, ReleasesFetch,
+    DataSourceManager, DataSourceStats, DataSourceStatus, DataSourcePriority, SourceAttempt, SourceHealth,
     GeographicDetector, InteractiveUI,
 };
 
@@ -65,7 +131,36 @@ pub mod defaults {
     use std::time::Duration;
 
     pub const DEFAULT_TEST_COUNT: u32 = 5;
+    /// Most iterations `--auto-count` runs while waiting for the target precision
+    pub const MAX_AUTO_COUNT: u32 = 200;
+    /// Target precision of `--auto-count` when none is given (percent)
+    pub const DEFAULT_AUTO_COUNT_PRECISION: f64 = 5.0;
+    /// UDP packets `--loss-probe` sends to each route and resolver by default
+    pub const DEFAULT_LOSS_PROBE_PACKETS: u16 = 50;
+    /// Most UDP packets `--loss-probe` may send to each route and resolver
+    pub const MAX_LOSS_PROBE_PACKETS: u16 = 1000;
+    /// ICMP echo requests `--ping` sends to each address by default
+    pub const DEFAULT_PING_COUNT: u16 = 10;
+    /// Most ICMP echo requests `--ping` may send to each address
+    pub const MAX_PING_COUNT: u16 = 100;
+    /// Redirects a request follows before it fails, as most HTTP clients do
+    pub const DEFAULT_MAX_REDIRECTS: u32 = 10;
+    /// Largest request body `--upload` may generate
+    pub const MAX_UPLOAD_BYTES: u64 = 1 << 30;
+    /// Longest `Retry-After` a watch with `--honor-retry-after` waits for
+    pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+    /// Most redirects `--max-redirects` may allow
+    pub const MAX_REDIRECTS: u32 = 50;
+    /// Highest `ndots` resolv.conf accepts
+    pub const MAX_NDOTS: u8 = 15;
+    /// DoH queries sent over an established connection to time the query alone
+    pub const DEFAULT_DNS_BENCH_QUERIES: u32 = 5;
+    /// Rounds of an `ab` experiment, enough for a paired t-test to separate a few percent
+    pub const DEFAULT_AB_ROUNDS: u32 = 20;
+    /// Timed runs of each `bench-internal` workload, after the warm-up
+    pub const DEFAULT_BENCH_RUNS: u32 = 10;
     pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+    pub const MAX_TIMEOUT: Duration = Duration::from_secs(300);
     pub const DEFAULT_TARGET_URLS: &[&str] = &["https://bing.com"];
     pub const DEFAULT_DNS_SERVERS: &[&str] = &[
         "114.114.114.114",  // 114 DNS
@@ -9,6 +9,7 @@
 
 use crate::error::{AppError, Result};
 use crate::models::{Config, TimingMetrics, TestResult};
+use crate::timing::{self, MonotonicClock};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -154,7 +155,7 @@ struct LogContext {
 /// Performance timing logger for detailed execution tracking
 pub struct PerformanceLogger {
     logger: Logger,
-    start_times: HashMap<String, DateTime<Utc>>,
+    start_times: HashMap<String, MonotonicClock>,
     operation_stack: Vec<String>,
 }
 
@@ -410,7 +411,7 @@ impl<'a> LogEntryBuilder<'a> {
         Self {
             logger,
             entry: LogEntry {
-                timestamp: Utc::now(),
+                timestamp: timing::timestamp(),
                 level,
                 message,
                 logger: logger.name.clone(),
@@ -482,8 +483,9 @@ impl PerformanceLogger {
     
     /// Start timing an operation
     pub async fn start_timing(&mut self, operation: &str) {
-        let start_time = Utc::now();
-        self.start_times.insert(operation.to_string(), start_time);
+        let clock = MonotonicClock::start();
+        let start_time = clock.started_at();
+        self.start_times.insert(operation.to_string(), clock);
         self.operation_stack.push(operation.to_string());
         
         self.logger.debug(&format!("Started timing: {}", operation))
@@ -495,9 +497,10 @@ impl PerformanceLogger {
     
     /// End timing an operation and log the duration
     pub async fn end_timing(&mut self, operation: &str) -> Option<chrono::Duration> {
-        if let Some(start_time) = self.start_times.remove(operation) {
-            let end_time = Utc::now();
-            let duration = end_time - start_time;
+        if let Some(clock) = self.start_times.remove(operation) {
+            let start_time = clock.started_at();
+            let end_time = clock.now();
+            let duration = chrono::Duration::from_std(clock.elapsed()).unwrap_or(chrono::Duration::MAX);
             
             // Remove from operation stack
             if let Some(pos) = self.operation_stack.iter().position(|x| x == operation) {
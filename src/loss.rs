@@ -0,0 +1,346 @@
+//! UDP burst probes for `--loss-probe`
+//!
+//! HTTP requests run over TCP, which hides packet loss behind retransmissions:
+//! a lost segment shows up as a slow request, not as a failed one. A burst of
+//! small UDP packets, each of which the far end answers, measures loss
+//! directly, along with reordering and duplication on the path.
+//!
+//! Two kinds of probe are sent, neither of which needs special privileges:
+//!
+//! - a route to a target is probed with QUIC packets of a reserved version,
+//!   which a QUIC server must answer with a Version Negotiation packet that
+//!   echoes the connection IDs (RFC 9000, section 6). Targets without QUIC
+//!   support do not answer at all and are reported as such rather than as
+//!   lossy.
+//! - a DNS server is probed with queries for the target's host name, answered
+//!   under the same query ID.
+//!
+//! ICMP echo would reach more targets but needs raw sockets, so it is not used.
+
+use crate::{
+    models::{LossReport, TestResult},
+    types::DnsConfig,
+    utils::url::display_url,
+};
+use futures::stream::{self, StreamExt};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+use tokio::net::UdpSocket;
+
+/// Time between the packets of a burst
+pub const PACKET_INTERVAL: Duration = Duration::from_millis(20);
+/// Bursts sent at the same time
+const CONCURRENT_BURSTS: usize = 8;
+/// QUIC version of the reserved `0x?a?a?a?a` form, which no server supports
+const PROBE_QUIC_VERSION: u32 = 0x1a2a_3a4a;
+/// Smallest datagram a QUIC server answers (RFC 9000, section 14.1)
+const QUIC_MIN_DATAGRAM: usize = 1200;
+/// Marks connection IDs as belonging to this probe
+const CONNECTION_ID_MAGIC: [u8; 4] = *b"nlt1";
+
+/// What a burst is sent to and how the far end is asked to answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeKind {
+    /// QUIC Version Negotiation from a web server
+    Quic,
+    /// DNS queries for `query_name` to a DNS server
+    Dns { query_name: String },
+}
+
+impl ProbeKind {
+    /// Packet number `sequence` of a burst
+    fn packet(&self, sequence: u16) -> Vec<u8> {
+        match self {
+            ProbeKind::Quic => quic_probe(sequence),
+            ProbeKind::Dns { query_name } => dns_query(query_name, sequence),
+        }
+    }
+
+    /// Sequence number of the packet an answer belongs to, if it is an answer
+    fn sequence_of(&self, answer: &[u8]) -> Option<u16> {
+        match self {
+            ProbeKind::Quic => quic_answer_sequence(answer),
+            ProbeKind::Dns { .. } => dns_answer_sequence(answer),
+        }
+    }
+
+    /// Port the far end listens on when the target does not name one
+    fn default_port(&self) -> u16 {
+        match self {
+            ProbeKind::Quic => 443,
+            ProbeKind::Dns { .. } => 53,
+        }
+    }
+}
+
+/// Send `packets` probe packets to `address` and count the answers
+///
+/// Answers are awaited for `timeout` after the last packet leaves.
+pub async fn burst(address: SocketAddr, kind: &ProbeKind, packets: u16, timeout: Duration) -> std::io::Result<BurstTally> {
+    let local: IpAddr = if address.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+    let socket = UdpSocket::bind(SocketAddr::new(local, 0)).await?;
+    socket.connect(address).await?;
+
+    let start = Instant::now();
+    let deadline = start + PACKET_INTERVAL * u32::from(packets) + timeout;
+    let mut tally = BurstTally::new(packets);
+    // Send times are needed by the receiving side, which runs concurrently
+    let sent_times = std::sync::Mutex::new(vec![None; usize::from(packets)]);
+
+    let sending = async {
+        let mut ticker = tokio::time::interval(PACKET_INTERVAL);
+        for sequence in 0..packets {
+            ticker.tick().await;
+            let sent_at = start.elapsed();
+            socket.send(&kind.packet(sequence)).await?;
+            sent_times.lock().unwrap()[usize::from(sequence)] = Some(sent_at);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+
+    let receiving = async {
+        let mut buffer = [0u8; 2048];
+        while tally.received() < u32::from(packets) {
+            let Ok(Ok(len)) = tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), socket.recv(&mut buffer)).await else {
+                break;
+            };
+            let Some(sequence) = kind.sequence_of(&buffer[..len]).filter(|&sequence| sequence < packets) else {
+                continue;
+            };
+            let sent_at = sent_times.lock().unwrap()[usize::from(sequence)];
+            let rtt = sent_at.map(|sent_at| start.elapsed().saturating_sub(sent_at));
+            tally.record(sequence, rtt);
+        }
+    };
+
+    let (sent, ()) = tokio::join!(sending, receiving);
+    sent?;
+    Ok(tally)
+}
+
+/// Probe the route each test result took and the DNS servers of each
+/// configuration with `packets` packets apiece
+///
+/// A route is the address its requests were most often sent to; results
+/// without a resolved address are skipped.
+pub async fn probe_results(results: &[&TestResult], packets: u16, timeout: Duration) -> Vec<LossReport> {
+    let mut probes = Vec::new();
+    let mut resolvers_seen = Vec::new();
+    for result in results {
+        let parsed = url::Url::parse(&result.url).ok();
+        let host = parsed.as_ref().and_then(|url| url.host_str()).map(str::to_string);
+        let port = parsed.as_ref().and_then(url::Url::port);
+        if let Some(ip) = route_address(result) {
+            let address = SocketAddr::new(ip, port.unwrap_or(ProbeKind::Quic.default_port()));
+            let target = format!("{} ({})", display_url(&result.url), ip);
+            probes.push((result.config_name.clone(), target, address, ProbeKind::Quic));
+        }
+
+        if let (DnsConfig::Custom { servers }, Some(host)) = (&result.dns_config, host) {
+            for &server in servers {
+                if resolvers_seen.contains(&(result.config_name.clone(), server)) {
+                    continue;
+                }
+                resolvers_seen.push((result.config_name.clone(), server));
+                let kind = ProbeKind::Dns { query_name: host.clone() };
+                let address = SocketAddr::new(server, kind.default_port());
+                probes.push((result.config_name.clone(), format!("resolver {}", server), address, kind));
+            }
+        }
+    }
+
+    stream::iter(probes)
+        .map(|(config_name, target, address, kind)| async move {
+            let tally = burst(address, &kind, packets, timeout).await.unwrap_or_else(|_| BurstTally::new(packets));
+            tally.report(config_name.into(), target)
+        })
+        .buffered(CONCURRENT_BURSTS)
+        .collect()
+        .await
+}
+
+/// Address the requests of a result were most often sent to
+fn route_address(result: &TestResult) -> Option<IpAddr> {
+    let mut counts: HashMap<IpAddr, usize> = HashMap::new();
+    for ip in result.individual_results.iter().filter_map(|metrics| metrics.resolved_ip) {
+        *counts.entry(ip).or_default() += 1;
+    }
+    counts.into_iter().max_by_key(|&(ip, count)| (count, std::cmp::Reverse(ip))).map(|(ip, _)| ip)
+}
+
+/// Answers to a burst, in the order they arrived
+#[derive(Debug, Clone)]
+pub struct BurstTally {
+    sent: u16,
+    answered: Vec<bool>,
+    highest: Option<u16>,
+    reordered: u32,
+    duplicates: u32,
+    rtts: Vec<Duration>,
+}
+
+impl BurstTally {
+    fn new(sent: u16) -> Self {
+        Self {
+            sent,
+            answered: vec![false; usize::from(sent)],
+            highest: None,
+            reordered: 0,
+            duplicates: 0,
+            rtts: Vec::new(),
+        }
+    }
+
+    /// Count the answer to packet `sequence`, which took `rtt` if known
+    fn record(&mut self, sequence: u16, rtt: Option<Duration>) {
+        let answered = &mut self.answered[usize::from(sequence)];
+        if *answered {
+            self.duplicates += 1;
+            return;
+        }
+        *answered = true;
+        if self.highest.is_some_and(|highest| sequence < highest) {
+            self.reordered += 1;
+        }
+        self.highest = self.highest.max(Some(sequence));
+        self.rtts.extend(rtt);
+    }
+
+    /// Distinct packets answered
+    pub fn received(&self) -> u32 {
+        self.answered.iter().filter(|&&answered| answered).count() as u32
+    }
+
+    /// Report the burst under `config_name` and `target`
+    pub fn report(&self, config_name: String, target: String) -> LossReport {
+        let avg_rtt_ms = (!self.rtts.is_empty())
+            .then(|| self.rtts.iter().map(Duration::as_secs_f64).sum::<f64>() / self.rtts.len() as f64 * 1000.0);
+        LossReport {
+            config_name,
+            target,
+            sent: u32::from(self.sent),
+            received: self.received(),
+            reordered: self.reordered,
+            duplicates: self.duplicates,
+            avg_rtt_ms,
+        }
+    }
+}
+
+/// QUIC long-header packet of an unsupported version, padded to the minimum
+/// size servers answer, with the sequence number in both connection IDs
+fn quic_probe(sequence: u16) -> Vec<u8> {
+    let mut connection_id = [0u8; 8];
+    connection_id[..4].copy_from_slice(&CONNECTION_ID_MAGIC);
+    connection_id[6..].copy_from_slice(&sequence.to_be_bytes());
+
+    let mut packet = Vec::with_capacity(QUIC_MIN_DATAGRAM);
+    // Long header with the fixed bit set
+    packet.push(0xc0);
+    packet.extend_from_slice(&PROBE_QUIC_VERSION.to_be_bytes());
+    for _ in 0..2 {
+        packet.push(connection_id.len() as u8);
+        packet.extend_from_slice(&connection_id);
+    }
+    packet.resize(QUIC_MIN_DATAGRAM, 0);
+    packet
+}
+
+/// Sequence number echoed in a Version Negotiation packet, whose destination
+/// connection ID is the source connection ID of the probe
+fn quic_answer_sequence(answer: &[u8]) -> Option<u16> {
+    let is_version_negotiation = answer.first()? & 0x80 != 0 && answer.get(1..5)? == [0, 0, 0, 0];
+    if !is_version_negotiation || *answer.get(5)? != 8 {
+        return None;
+    }
+    let connection_id = answer.get(6..14)?;
+    (connection_id[..4] == CONNECTION_ID_MAGIC).then(|| u16::from_be_bytes([connection_id[6], connection_id[7]]))
+}
+
+/// DNS query for the A record of `name` under query ID `sequence`
+fn dns_query(name: &str, sequence: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(name.len() + 18);
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    // Standard query with recursion desired, one question
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for label in name.trim_end_matches('.').split('.').filter(|label| !label.is_empty()) {
+        let label = &label.as_bytes()[..label.len().min(63)];
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label);
+    }
+    // Root label, type A, class IN
+    packet.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x01]);
+    packet
+}
+
+/// Query ID of a DNS response, any response code included
+fn dns_answer_sequence(answer: &[u8]) -> Option<u16> {
+    let is_response = answer.get(2)? & 0x80 != 0;
+    (is_response && answer.len() >= 12).then(|| u16::from_be_bytes([answer[0], answer[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_tally() {
+        let mut tally = BurstTally::new(5);
+        for sequence in [0, 2, 1, 2, 4] {
+            tally.record(sequence, Some(Duration::from_millis(10)));
+        }
+        let report = tally.report("System DNS".to_string(), "resolver 192.0.2.1".to_string());
+        assert_eq!((report.sent, report.received, report.reordered, report.duplicates), (5, 4, 1, 1));
+        assert_eq!(report.loss_percentage(), Some(20.0));
+        assert!((report.avg_rtt_ms.unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_probe_packets() {
+        let probe = quic_probe(513);
+        assert_eq!(probe.len(), QUIC_MIN_DATAGRAM);
+        assert_eq!(probe[1..5], PROBE_QUIC_VERSION.to_be_bytes());
+        // A Version Negotiation packet swaps the connection IDs and lists versions
+        let mut answer = vec![0x80, 0, 0, 0, 0];
+        answer.extend_from_slice(&probe[14..23]);
+        answer.extend_from_slice(&probe[5..14]);
+        answer.extend_from_slice(&1u32.to_be_bytes());
+        assert_eq!(quic_answer_sequence(&answer), Some(513));
+        assert_eq!(quic_answer_sequence(&probe), None);
+
+        let query = dns_query("example.com.", 7);
+        assert_eq!(query[12..25], *b"\x07example\x03com\x00");
+        assert_eq!(dns_answer_sequence(&query), None);
+        let mut response = query.clone();
+        response[2] |= 0x80;
+        assert_eq!(dns_answer_sequence(&response), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_burst_against_local_responder() {
+        // Answer every query except the third, and the fourth twice
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 512];
+            while let Ok((len, peer)) = server.recv_from(&mut buffer).await {
+                let mut response = buffer[..len].to_vec();
+                response[2] |= 0x80;
+                let sequence = u16::from_be_bytes([response[0], response[1]]);
+                let copies = match sequence { 2 => 0, 3 => 2, _ => 1 };
+                for _ in 0..copies {
+                    let _ = server.send_to(&response, peer).await;
+                }
+            }
+        });
+
+        let kind = ProbeKind::Dns { query_name: "example.com".to_string() };
+        let tally = burst(address, &kind, 6, Duration::from_millis(200)).await.unwrap();
+        let report = tally.report("Custom DNS".to_string(), format!("resolver {}", address.ip()));
+        assert_eq!((report.sent, report.received, report.duplicates), (6, 5, 1));
+        assert!(report.avg_rtt_ms.is_some());
+    }
+}
@@ -3,26 +3,51 @@
 //! A high-performance network latency testing tool that measures connectivity
 //! to configurable target URLs using various DNS configurations.
 
+use chrono::{Local, Offset, Utc};
 use clap::Parser;
 use network_latency_tester::{
-    cli::Cli,
-    config::parser::load_config,
-    client::ClientFactory,
-    dns::DnsManager,
-    executor::{ExecutionMode, create_executor_for_mode},
-    output::{OutputFormatterFactory, OutputCoordinator},
+    baseline, bench::{self, BenchSize}, cache::known_caches, cancel, defaults, geo,
+    cli::{AbArgs, BenchInternalArgs, CacheAction, CacheArgs, Cli, ConfigAction, ConfigArgs, CtlArgs, DnsBenchArgs, HeatmapArgs, InitArgs, MergeArgs, QueryArgs, ReplayArgs},
+    config::{
+        env::EnvManager, parser::{load_config, load_config_with_provenance}, preferences::PREFERENCE_KEYS, reload, Config, ConfigValidator, ConfigWatcher, Preferences,
+        QuickProfile, SetupWizard, TcpProbe,
+    },
+    control::{self, ControlCommand, ControlRequest, ControlServer, SnapshotSignal},
+    client::{simulate::SimulatedTransport, socket_stats, ClientFactory},
+    dns::{platform::DnsCacheFlush, system::DnsSandbox, DnsManager},
+    executor::{
+        AbExperiment, AbVariant, CycleDrift, CycleHistory, ExecutionMode, ExecutionPlan, ExecutionResults, InterfaceExecutor, OptimizedExecutor, Replay,
+        RuntimeMode, SystemResources, TestExecutor, create_executor_for_mode, merge_shards,
+    },
+    loss,
+    network::{NetworkProfile, NetworkStore},
+    ping,
+    notification::{self, BreachNotifier},
+    serve::{self, AgentStatus, SampleStore, Served, SharedState},
+    output::{render_html, CsvFormatter, OutputFormatterFactory, OutputCoordinator},
+    query::Query,
     error::{AppError, Result},
-    models::TestResult,
+    models::{Name, Reproduction, TestResult},
+    stats::{LatencyHeatmap, MultiWindowStats, StatisticsConfig, StatisticsEngine},
+    timing::{self, ClockReport},
     types::DnsConfig,
-    updater::UpdateCoordinator,
+    utils::duration::format_duration,
     VERSION, PKG_NAME,
 };
-use std::{process, error::Error};
-use std::sync::Arc;
-use tokio;
+#[cfg(feature = "diagnostics")]
+use network_latency_tester::{
+    client::NetworkClient,
+    diagnostics::{DiagnosticsConfig, NetworkDiagnostics},
+};
+#[cfg(feature = "updater")]
+use network_latency_tester::{error::UpdateErrorKind, updater::UpdateCoordinator};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() {
+fn main() {
     // Set up better panic handling
     std::panic::set_hook(Box::new(|panic_info| {
         eprintln!("Application panic: {}", panic_info);
@@ -30,26 +55,49 @@ async fn main() {
         process::exit(1);
     }));
 
+    // Anchor all timestamps to the wall clock before anything is measured
+    timing::process_clock();
+
     // Parse command line arguments
     let cli = Cli::parse();
 
+    // The runtime has to exist before the configuration is loaded, so only the
+    // flag, the environment and the machine's resources decide its mode
+    let runtime_mode = RuntimeMode::select(single_thread_requested(&cli), &SystemResources::detect());
+    let runtime = runtime_mode.build().unwrap_or_else(|e| exit_with_error(e));
+
     // Handle the actual application logic
-    if let Err(e) = run_application(cli).await {
-        eprintln!("Error: {}", e);
-        
-        if let Some(source) = e.source() {
-            eprintln!("Caused by: {}", source);
-        }
-        
-        // Print suggestions for common errors
-        print_error_suggestions(&e);
-        
-        process::exit(e.exit_code());
+    if let Err(e) = runtime.block_on(run_application(cli, runtime_mode)) {
+        exit_with_error(e);
+    }
+}
+
+/// Whether `--single-thread` or `SINGLE_THREAD` (including from .env) asks for single-thread mode
+fn single_thread_requested(cli: &Cli) -> bool {
+    if cli.single_thread {
+        return true;
+    }
+    // Loading errors are reported when the configuration is loaded for real
+    let _ = EnvManager::load_env_file(false);
+    std::env::var("SINGLE_THREAD").is_ok_and(|value| value.parse().unwrap_or(false))
+}
+
+/// Print the error with its causes and suggestions, then exit
+fn exit_with_error(e: AppError) -> ! {
+    eprintln!("Error: {}", e);
+    
+    for cause in e.causes() {
+        eprintln!("Caused by: {}", cause);
     }
+    
+    // Print suggestions for common errors
+    print_error_suggestions(&e);
+    
+    process::exit(e.exit_code());
 }
 
 /// Main application logic
-async fn run_application(cli: Cli) -> Result<()> {
+async fn run_application(cli: Cli, runtime_mode: RuntimeMode) -> Result<()> {
     // Handle help topics first, before any validation
     if cli.should_show_topic_help() {
         println!("{}", cli.display_help());
@@ -59,11 +107,61 @@ async fn run_application(cli: Cli) -> Result<()> {
     // Early validation of CLI arguments
     cli.validate().map_err(AppError::validation)?;
 
+    // The first Ctrl-C stops what is in flight; the results so far are still reported
+    cancel::cancel_on_ctrl_c();
+
     // Early update mode detection - handle update operations before any other processing
+    #[cfg(feature = "updater")]
     if cli.is_update_mode() {
         return handle_update_mode(&cli).await;
     }
 
+    // Queries only read a results file, so they need no network configuration
+    if let Some(query_args) = cli.query_args() {
+        return handle_query_mode(query_args);
+    }
+
+    if let Some(init_args) = cli.init_args() {
+        return handle_init_mode(init_args);
+    }
+
+    if let Some(merge_args) = cli.merge_args() {
+        return handle_merge_mode(&cli, merge_args).await;
+    }
+
+    if let Some(replay_args) = cli.replay_args() {
+        return handle_replay_mode(&cli, replay_args).await;
+    }
+
+    if let Some(heatmap_args) = cli.heatmap_args() {
+        return handle_heatmap_mode(heatmap_args);
+    }
+
+    if let Some(config_args) = cli.config_args() {
+        return handle_config_mode(config_args);
+    }
+
+    if let Some(bench_args) = cli.bench_internal_args() {
+        return handle_bench_internal_mode(bench_args).await;
+    }
+
+    #[cfg(feature = "mock")]
+    if let Some(mock_dns_args) = cli.serve_mock_dns_args() {
+        return handle_serve_mock_dns_mode(mock_dns_args).await;
+    }
+
+    if let Some(cache_args) = cli.cache_args() {
+        return handle_cache_mode(&cli, cache_args);
+    }
+
+    if let Some(ctl_args) = cli.ctl_args() {
+        return handle_ctl_mode(ctl_args, cli.control_socket.as_deref()).await;
+    }
+
+    if let Some(ab_args) = cli.ab_args() {
+        return handle_ab_mode(&cli, ab_args).await;
+    }
+
     // Show debug info if requested
     if cli.debug {
         println!("{} v{}", PKG_NAME, VERSION);
@@ -71,8 +169,33 @@ async fn run_application(cli: Cli) -> Result<()> {
         println!();
     }
 
+    // Give first-time users a preferences file to fill in; not being able to write one is no reason to stop
+    if let Some(preferences) = Preferences::locate() {
+        if let Ok(true) = preferences.create_if_missing() {
+            if cli.debug {
+                println!("Created a preferences file at {}", preferences.path().display());
+            }
+        }
+    }
+
+    // Checking the configuration only loads it, with where each setting came from
+    if cli.check_config {
+        let (config, provenance) = load_config_with_provenance(cli.clone())?;
+        if cli.strict {
+            ConfigValidator::check_doh_reachable(&config).await?;
+        }
+        print!("{}", provenance.report(&config));
+        println!("Configuration is valid");
+        return Ok(());
+    }
+
     // Load and validate configuration
-    let config = load_config(cli.clone())?;
+    let mut config = load_config(cli.clone())?;
+    if cli.strict {
+        ConfigValidator::check_doh_reachable(&config).await?;
+    }
+    // Constrained machines get single-thread mode even when it was not asked for
+    config.single_thread |= runtime_mode.is_single_thread();
     
     if config.debug {
         println!("Configuration loaded successfully:");
@@ -80,29 +203,91 @@ async fn run_application(cli: Cli) -> Result<()> {
         println!("  DNS Servers: {}", config.dns_servers.join(", "));
         println!("  DoH Providers: {} configured", config.doh_providers.len());
         println!("  Test Count: {}", config.test_count);
-        println!("  Timeout: {}s", config.timeout_seconds);
+        println!("  Timeout: {}", format_duration(config.timeout));
         println!("  Color Output: {}", config.enable_color);
+        println!("  Single Thread: {}", config.single_thread);
         println!();
     }
 
-    // Create DNS configurations
+    if let Some(dns_bench_args) = cli.dns_bench_args() {
+        return handle_dns_bench_mode(&config, dns_bench_args).await;
+    }
+
+    // A plan only describes the run, so stop before any traffic is sent
+    if cli.plan {
+        let dns_configs = create_dns_configs(&config)?;
+        print!("{}", ExecutionPlan::new(&config, &dns_configs).format_report());
+        return Ok(());
+    }
+
+    match cli.watch {
+        Some(interval) => run_watch_mode(&cli, config, runtime_mode, interval).await,
+        None => {
+            let results = run_tests(&cli, &config, None).await?;
+            // The report covers what was measured; the exit code says the run was cut short
+            if cancel::global().is_cancelled() {
+                return Err(AppError::cancelled("test run"));
+            }
+            check_success_rate(&results)
+        }
+    }
+}
+
+/// Create the DNS configurations to test, listing them in debug mode
+fn create_dns_configs(config: &Config) -> Result<Vec<DnsConfig>> {
     let dns_configs = config.create_dns_configs()
-        .map_err(|e| AppError::config(format!("Failed to create DNS configurations: {}", e)))?;
+        .map_err(|e| AppError::config(format!("Failed to create DNS configurations: {}", e)).with_source(e))?;
 
     if config.debug {
         println!("DNS Configurations ({}):", dns_configs.len());
+        let display_names = config.display_names();
         for (i, dns_config) in dns_configs.iter().enumerate() {
-            println!("  {}: {}", i + 1, dns_config.name());
+            println!("  {}: {}", i + 1, display_names.resolve(dns_config));
         }
         println!();
     }
 
+    Ok(dns_configs)
+}
+
+/// Run the tests once and report the results, with their change since
+/// earlier cycles when a watch-mode `history` is given
+async fn run_tests(cli: &Cli, config: &Config, history: Option<&mut CycleHistory>) -> Result<ExecutionResults> {
+    let started = std::time::Instant::now();
+    let dns_configs = create_dns_configs(config)?;
+    if config.socket_stats && !socket_stats::SUPPORTED {
+        eprintln!("Warning: --socket-stats needs Linux; TCP statistics are not collected on this platform");
+    }
+    let sandbox = if config.sandbox_dns && cli.simulate.is_none() {
+        let sandbox = DnsSandbox::verify(&dns_configs)?;
+        println!("{}", sandbox);
+        Some(sandbox)
+    } else {
+        None
+    };
+
     // Initialize core components
-    let dns_manager = Arc::new(DnsManager::new()?);
-    let _client_factory = ClientFactory::new(dns_manager.clone());
+    let dns_manager = Arc::new(
+        DnsManager::new()?
+            .with_fresh_doh_connections(config.doh_fresh_connections)?
+            .with_doh_bootstrap(&config.doh_bootstrap).await?
+            .with_resolver_options(config.resolver_options.clone()),
+    );
+    if config.verbose || config.debug {
+        for lookup in dns_manager.doh_bootstrap() {
+            println!("DoH bootstrap: {}", lookup);
+        }
+    }
+    let _client_factory = ClientFactory::new(dns_manager.clone()).with_protocol(config.protocol_options());
 
-    // Create and configure test executor
-    let executor = create_executor_for_mode(&config, ExecutionMode::Optimized).await?;
+    // The quick profile keeps the resolvers that look up the first target fastest
+    let first_host = config.target_urls.first()
+        .and_then(|url| url::Url::parse(url).ok())
+        .and_then(|url| url.host_str().map(str::to_string));
+    let dns_configs = match (cli.quick, &cli.simulate, first_host) {
+        (true, None, Some(host)) => QuickProfile::default().fastest_resolvers(&dns_manager, dns_configs, &host).await,
+        _ => dns_configs,
+    };
 
     if config.verbose || config.debug {
         println!("Starting network latency tests...");
@@ -112,11 +297,90 @@ async fn run_application(cli: Cli) -> Result<()> {
         println!();
     }
 
+    // Create and configure test executor; a simulation stands in for the network
+    let executor: Box<dyn TestExecutor + Send + Sync> = match cli.simulate {
+        Some(ref simulation) => {
+            let transport = Arc::new(SimulatedTransport::new(simulation.clone()));
+            Box::new(OptimizedExecutor::with_client(config, transport).await?)
+        }
+        None if cli.all_interfaces => Box::new(InterfaceExecutor::new(config).await?),
+        None => create_executor_for_mode(config, ExecutionMode::Optimized).await?,
+    };
+
+    // Start System DNS cold; a failed flush only skews the results, so it
+    // does not stop the run
+    if config.flush_dns_cache && cli.simulate.is_none() && dns_configs.iter().any(|dns| matches!(dns, DnsConfig::System)) {
+        match DnsCacheFlush::run().await {
+            Ok(flush) if config.verbose || config.debug => println!("{}", flush),
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+    }
+
     // Execute tests
     let test_results = executor.execute_tests(&config.target_urls, &dns_configs).await?;
+    if let Some(sandbox) = sandbox {
+        if let Err(e) = sandbox.confirm() {
+            eprintln!("Warning: {}", e);
+        }
+    }
     
     // Convert to ExecutionResults structure
-    let results = create_execution_results(test_results, &config.target_urls, &dns_configs);
+    let mut results = create_execution_results(test_results, &config.target_urls, &dns_configs);
+    results.execution_summary.total_duration = started.elapsed();
+    let executor_statistics = executor.get_statistics();
+    results.execution_summary.interference = executor_statistics.interference;
+    results.execution_summary.connection_reuse = executor_statistics.connection_reuse;
+    results.execution_summary.resolver_failover = executor_statistics.resolver_failover;
+    results.execution_summary.server_distribution = executor_statistics.server_distribution;
+    results.execution_summary.packet_captures = executor_statistics.packet_captures;
+    results.execution_summary.shard = config.shard;
+    results.execution_summary.user_agent = Some(config.user_agent.clone());
+    results.execution_summary.url_tags = config.url_tags.clone();
+    results.execution_summary.simulation = cli.simulate.as_ref().map(ToString::to_string);
+    results.execution_summary.clock = Some(ClockReport::check(config.ntp_server.as_deref(), config.timeout).await);
+    if let (Some(packets), None) = (config.loss_probe, &cli.simulate) {
+        let tested: Vec<&TestResult> = results.test_results.values().collect();
+        results.execution_summary.packet_loss = loss::probe_results(&tested, packets, config.timeout).await;
+    }
+    if let (Some(count), None) = (config.ping, &cli.simulate) {
+        ping::ping_results(results.test_results.values_mut(), count).await;
+    }
+    // Simulated samples say nothing about the real network, so they are not compared
+    if let (Some(spec), None) = (&cli.compare_public, &cli.simulate) {
+        match spec.source(config.timeout) {
+            Ok(source) => {
+                let tested: Vec<&TestResult> = results.test_results.values().collect();
+                let (comparisons, errors) = baseline::compare(&tested, source.as_ref()).await;
+                for e in errors {
+                    eprintln!("Warning: {}", e);
+                }
+                results.execution_summary.public_baseline = comparisons;
+            }
+            Err(e) => eprintln!("Warning: no public baseline: {}", e),
+        }
+    }
+    if let (Some(client), None) = (cli.geo, &cli.simulate) {
+        let tested: Vec<&TestResult> = results.test_results.values().collect();
+        match geo::locate_routes(&tested, client, config.timeout).await {
+            Ok(bounds) => results.execution_summary.physical_bounds = bounds,
+            Err(e) => eprintln!("Warning: no geolocation: {}", e),
+        }
+    }
+    let mut networks = match (cli.network_history, &cli.simulate) {
+        (true, None) => open_network_store(cli, config).await,
+        _ => None,
+    };
+    if let Some((store, profile)) = &networks {
+        results.execution_summary.network = Some(profile.clone());
+        results.execution_summary.baseline_network = store.baseline().filter(|baseline| *baseline != profile).cloned();
+    }
+
+    analyze_results(config, &mut results);
+    results.reproduction = Some(reproduction(cli, config));
+    if cli.thorough && cli.simulate.is_none() {
+        run_thorough_diagnostics(config, &dns_manager, &dns_configs, &mut results).await;
+    }
 
     if config.debug {
         println!("Test execution completed:");
@@ -132,13 +396,32 @@ async fn run_application(cli: Cli) -> Result<()> {
         println!();
     }
 
-    // Create output formatter and coordinator
-    let formatter = OutputFormatterFactory::create_formatter(config.enable_color, config.verbose);
-    let coordinator = OutputCoordinator::new(formatter);
+    // The network's own history replaces the session's, so that drift compares like with like
+    let drift = match networks.as_mut() {
+        Some((store, profile)) => {
+            let drift = store.record(profile, &results);
+            if let Err(e) = store.save() {
+                eprintln!("Warning: network history not saved: {}", e);
+            }
+            Some(drift)
+        }
+        None => history.map(|history| history.record(&results)),
+    };
+    display_results(cli, config, &results, drift).await?;
 
-    // Generate and display results  
-    let output = coordinator.display_results(&results).await?;
-    println!("{}", output);
+    if let Some(max_runtime) = config.max_runtime {
+        let planned = (config.target_urls.len() * dns_configs.len()) as u32 * config.test_count;
+        let attempted: u32 = results.test_results.values().map(|r| r.total_count).sum();
+        if attempted < planned {
+            println!("Note: run time limited to {}; ran {} of {} planned requests",
+                format_duration(max_runtime), attempted, planned);
+        }
+    }
+
+    export_results(cli, config, &results)?;
+    if cli.thorough {
+        save_evidence_bundle(&results)?;
+    }
 
     // Show additional information in verbose mode
     if config.verbose {
@@ -160,7 +443,141 @@ async fn run_application(cli: Cli) -> Result<()> {
         }
     }
 
-    // Return appropriate exit code
+    Ok(results)
+}
+
+/// The command line reproducing a run, simulated again if it was
+fn reproduction(cli: &Cli, config: &Config) -> Reproduction {
+    let reproduction = Reproduction::from_config(config);
+    match &cli.simulate {
+        Some(simulation) => reproduction.simulated(&simulation.to_string()),
+        None => reproduction,
+    }
+}
+
+/// Statistical analysis provides the per-URL breakdown and recommendations
+fn analyze_results(config: &Config, results: &mut ExecutionResults) {
+    let mut stats_engine = StatisticsEngine::new(StatisticsConfig::from(config));
+    stats_engine.add_results(results.test_results.values().cloned().collect());
+    stats_engine.add_packet_loss(results.execution_summary.packet_loss.clone());
+    stats_engine.add_public_baseline(results.execution_summary.public_baseline.clone());
+    stats_engine.add_physical_bounds(results.execution_summary.physical_bounds.clone());
+    stats_engine.add_networks(results.execution_summary.physical_bounds.iter()
+        .filter_map(|bound| Some((bound.ip, bound.asn.clone()?))));
+    results.statistical_analysis = stats_engine.analyze().ok();
+}
+
+/// Format the results for the terminal and print them
+async fn display_results(cli: &Cli, config: &Config, results: &ExecutionResults, drift: Option<CycleDrift>) -> Result<()> {
+    let formatter = OutputFormatterFactory::create_formatter(config.enable_color, config.verbose);
+    let coordinator = OutputCoordinator::new(formatter)
+        .with_slowest_requests(cli.slowest)
+        .with_table_view(cli.sort_by, cli.top.map(|top| top as usize))
+        .with_columns(cli.columns.clone())
+        .with_cycle_drift(drift)
+        .with_compact_summary(cli.quick);
+
+    let output = coordinator.display_results(results).await?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Identify the current network and load the history of every network, for
+/// `--network-history`; without a data directory or a readable history the
+/// run goes on without it
+async fn open_network_store(cli: &Cli, config: &Config) -> Option<(NetworkStore, NetworkProfile)> {
+    let Some(path) = NetworkStore::locate() else {
+        eprintln!("Warning: no data directory to keep the network history in");
+        return None;
+    };
+    let mut store = match NetworkStore::load(&path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Warning: network history not loaded: {}", e);
+            return None;
+        }
+    };
+    let profile = NetworkProfile::detect(config.timeout).await;
+    if profile.is_unknown() {
+        eprintln!("Warning: could not identify the network; results are kept as an unknown network");
+    }
+    if cli.network_baseline {
+        store.set_baseline(&profile);
+    }
+    if config.verbose {
+        println!("Network history: {} ({})", profile, path.display());
+    }
+    Some((store, profile))
+}
+
+/// Write the results to the `--export` and `--export-csv` files, if given
+fn export_results(cli: &Cli, config: &Config, results: &ExecutionResults) -> Result<()> {
+    if let Some(ref export_path) = cli.export {
+        // A .csv file gets one row per request, anything else the full JSON results
+        if export_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            CsvFormatter::new().save(results, export_path)?;
+        } else {
+            results.save_json(export_path)?;
+        }
+        if config.verbose {
+            println!("Results exported to {}", export_path.display());
+        }
+    }
+    if let Some(ref csv_path) = cli.export_csv {
+        CsvFormatter::new().save(results, csv_path)?;
+        if config.verbose {
+            println!("Requests exported to {}", csv_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Run the full diagnostics, with a traceroute to every target and the
+/// certificate of every HTTPS target, for `--thorough`; without them the
+/// results are still worth keeping, so a failure is only reported
+#[cfg(feature = "diagnostics")]
+async fn run_thorough_diagnostics(config: &Config, dns_manager: &Arc<DnsManager>, dns_configs: &[DnsConfig], results: &mut ExecutionResults) {
+    let diagnostics_config = DiagnosticsConfig {
+        connectivity_timeout: config.timeout,
+        include_traceroute: true,
+        display_names: config.display_names(),
+        ..DiagnosticsConfig::default()
+    };
+    let diagnostics = match NetworkClient::new(dns_manager.clone()) {
+        Ok(client) => NetworkDiagnostics::new(dns_manager.clone(), Arc::new(client), diagnostics_config),
+        Err(e) => {
+            eprintln!("Warning: no diagnostics: {}", e);
+            return;
+        }
+    };
+    println!("Running diagnostics and traceroutes...");
+    match diagnostics.run_diagnostics(&config.target_urls, dns_configs).await {
+        Ok(report) => results.diagnostics_report = Some(report),
+        Err(e) => eprintln!("Warning: no diagnostics: {}", e),
+    }
+}
+
+#[cfg(not(feature = "diagnostics"))]
+async fn run_thorough_diagnostics(_config: &Config, _dns_manager: &Arc<DnsManager>, _dns_configs: &[DnsConfig], _results: &mut ExecutionResults) {
+    eprintln!("Warning: this build has no diagnostics; the evidence bundle holds the test results only");
+}
+
+/// Write the results as JSON and as an HTML report into a new directory
+/// named after the time of the run, for `--thorough`
+fn save_evidence_bundle(results: &ExecutionResults) -> Result<()> {
+    let directory = PathBuf::from(format!("network-latency-evidence-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::create_dir_all(&directory)
+        .map_err(|e| AppError::io(format!("Failed to create {}: {}", directory.display(), e)).with_source(e))?;
+    results.save_json(&directory.join("results.json"))?;
+    let report = directory.join("report.html");
+    std::fs::write(&report, render_html(results))
+        .map_err(|e| AppError::io(format!("Failed to write {}: {}", report.display(), e)).with_source(e))?;
+    println!("Evidence bundle written to {}", directory.display());
+    Ok(())
+}
+
+/// Fail when most tests failed, which usually means the network is down
+fn check_success_rate(results: &ExecutionResults) -> Result<()> {
     let success_rate = if results.execution_summary.total_tests > 0 {
         results.execution_summary.successful_tests as f64 / results.execution_summary.total_tests as f64
     } else {
@@ -174,7 +591,634 @@ async fn run_application(cli: Cli) -> Result<()> {
     }
 }
 
+/// Repeat the tests every `interval` until interrupted, applying .env changes between cycles
+async fn run_watch_mode(cli: &Cli, mut config: Config, runtime_mode: RuntimeMode, interval: Duration) -> Result<()> {
+    let mut watcher = ConfigWatcher::new(cli.clone())?;
+    let mut session = WatchSession::new(cli.log_file.as_deref())?;
+    // The server stops when dropped, so it lives as long as the watch
+    let _server = match cli.serve {
+        Some(addr) => {
+            let state = Arc::new(Mutex::new(Served {
+                samples: SampleStore::new(serve::MAX_SAMPLES),
+                status: AgentStatus::new(Utc::now(), interval),
+            }));
+            let server = serve::Server::bind(addr, state.clone(), cli.serve_cors.clone()).await?;
+            println!("Serving results at http://{}/ for the Grafana JSON and Infinity datasources", server.local_addr());
+            session.served = Some(state);
+            Some(server)
+        }
+        None => None,
+    };
+    let mut notifier = cli.notify.map(BreachNotifier::new);
+    let mut history = CycleHistory::new();
+    let socket = cli.control_socket.clone().unwrap_or_else(control::default_path);
+    // Watching works without the control channel, e.g. while another instance holds it
+    let mut control = match ControlServer::bind(&socket).await {
+        Ok(server) => Some(server),
+        Err(e) => {
+            eprintln!("Warning: control commands are unavailable: {}", e);
+            None
+        }
+    };
+    let mut snapshots = match SnapshotSignal::listen() {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            eprintln!("Warning: report snapshots are unavailable: {}", e);
+            None
+        }
+    };
+    println!("Testing every {}; edits to {} apply from the next cycle (Ctrl+C to stop)",
+        format_duration(interval), watcher.path().display());
+    if let Some(ref server) = control {
+        println!("Control with `{} ctl <COMMAND> --control-socket {}`", PKG_NAME, server.path().display());
+    }
+    if let (Some(export_path), Some(_)) = (cli.export.as_ref().or(cli.export_csv.as_ref()), &snapshots) {
+        println!("Send {} (pid {}) to export a report snapshot to {}", SnapshotSignal::NAME, process::id(), export_path.display());
+    }
+
+    // Set when a throttling server asked for a longer wait than the interval
+    let mut backoff = None;
+    loop {
+        if !session.paused {
+            // Commands are answered while a cycle runs, so `ctl` never waits for a whole cycle
+            let cycle = run_tests(cli, &config, Some(&mut history));
+            tokio::pin!(cycle);
+            let outcome = loop {
+                tokio::select! {
+                    outcome = &mut cycle => break outcome,
+                    Some(request) = next_request(&mut control) => session.handle(request, cli, &config).await,
+                    Some(()) = next_snapshot(&mut snapshots) => session.handle_snapshot(cli, &config),
+                }
+            };
+            let requested = outcome.as_ref().ok().and_then(ExecutionResults::retry_after);
+            backoff = requested.filter(|_| cli.honor_retry_after)
+                .map(|wait| wait.min(defaults::MAX_RETRY_AFTER))
+                .filter(|&wait| wait > interval);
+            // A failed cycle is reported but does not end the watch
+            let recorded = session.record(outcome);
+            if let Some(ref mut notifier) = notifier {
+                let problem = recorded.as_ref().err().map(|e| e.message().to_string());
+                if let Some(notice) = notifier.observe(session.cycles, problem.as_deref()) {
+                    if let Err(e) = notification::send(notifier.target(), &notice).await {
+                        eprintln!("Warning: {}", e);
+                    }
+                }
+            }
+            if let Err(e) = recorded {
+                eprintln!("Error: {}", e);
+            }
+        }
+
+        if let Some(wait) = backoff {
+            println!("Throttled: waiting {} before the next cycle, as the server asked with Retry-After", format_duration(wait));
+        }
+        let wait = tokio::time::sleep(backoff.unwrap_or(interval));
+        tokio::pin!(wait);
+        loop {
+            tokio::select! {
+                _ = &mut wait, if !session.paused => break,
+                _ = cancel::global().cancelled() => return Ok(()),
+                Some(()) = next_snapshot(&mut snapshots) => session.handle_snapshot(cli, &config),
+                Some(request) = next_request(&mut control) => {
+                    let resumes = request.command == ControlCommand::Resume && session.paused;
+                    session.handle(request, cli, &config).await;
+                    if resumes {
+                        break;
+                    }
+                }
+            }
+        }
+
+        match watcher.reload() {
+            Ok(Some(mut reloaded)) => {
+                reloaded.single_thread |= runtime_mode.is_single_thread();
+                let changes = reload::diff(&config, &reloaded);
+                if !changes.is_empty() {
+                    println!("Configuration reloaded from {}:", watcher.path().display());
+                    for change in &changes {
+                        println!("  {}", change);
+                    }
+                }
+                config = reloaded;
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: keeping the previous configuration: {}", e),
+        }
+    }
+}
+
+/// Next control command, or never when the control channel is unavailable
+async fn next_request(control: &mut Option<ControlServer>) -> Option<ControlRequest> {
+    match control {
+        Some(server) => server.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Next snapshot signal, or never when signals cannot be received
+async fn next_snapshot(signal: &mut Option<SnapshotSignal>) -> Option<()> {
+    match signal {
+        Some(signal) => signal.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// State of a watch-mode run that control commands act on
+struct WatchSession {
+    paused: bool,
+    /// Cycles completed so far
+    cycles: u32,
+    /// Measurements since the last flush-report, merged per URL and configuration
+    pending: Vec<TestResult>,
+    /// Cycles that contributed to `pending`
+    pending_cycles: u32,
+    /// Short- and long-term latency of each configuration over the whole session
+    windows: BTreeMap<Name, MultiWindowStats>,
+    log: Option<CycleLog>,
+    /// Samples and agent status answered over HTTP with --serve
+    served: Option<SharedState>,
+}
+
+impl WatchSession {
+    fn new(log_file: Option<&Path>) -> Result<Self> {
+        Ok(Self {
+            paused: false,
+            cycles: 0,
+            pending: Vec::new(),
+            pending_cycles: 0,
+            windows: BTreeMap::new(),
+            log: log_file.map(CycleLog::open).transpose()?,
+            served: None,
+        })
+    }
+
+    /// Keep a cycle's measurements for the next flush-report and log its outcome
+    fn record(&mut self, outcome: Result<ExecutionResults>) -> Result<()> {
+        self.cycles += 1;
+        let line = match &outcome {
+            Ok(results) => {
+                let attempted: u32 = results.test_results.values().map(|r| r.total_count).sum();
+                let succeeded: u32 = results.test_results.values().map(|r| r.success_count).sum();
+                let best = results.best_config().map(|config| format!("; best {}", config)).unwrap_or_default();
+                format!("cycle {}: {} of {} requests succeeded{}", self.cycles, succeeded, attempted, best)
+            }
+            Err(e) => format!("cycle {} failed: {}", self.cycles, e),
+        };
+        self.log_line(&line);
+
+        let results = match outcome {
+            Ok(results) => results,
+            Err(e) => {
+                self.update_served(|served| served.status.record_cycle(Utc::now(), Some(e.to_string())));
+                return Err(e);
+            }
+        };
+        let healthy = check_success_rate(&results);
+        self.update_served(|served| {
+            served.samples.record(results.test_results.values());
+            served.status.record_cycle(Utc::now(), healthy.as_ref().err().map(ToString::to_string));
+        });
+        self.pending_cycles += 1;
+        for result in results.test_results.into_values() {
+            let windows = self.windows.entry(result.config_name.clone()).or_default();
+            for measurement in &result.individual_results {
+                windows.record(measurement);
+            }
+            match self.pending.iter_mut().find(|r| r.url == result.url && r.config_name == result.config_name) {
+                Some(pending) => pending.merge(result),
+                None => self.pending.push(result),
+            }
+        }
+        healthy
+    }
+
+    /// Carry out a control command and answer it
+    async fn handle(&mut self, request: ControlRequest, cli: &Cli, config: &Config) {
+        let outcome = match request.command {
+            ControlCommand::Pause if self.paused => Ok("already paused".to_string()),
+            ControlCommand::Pause => {
+                self.paused = true;
+                Ok("paused; a cycle in progress still finishes".to_string())
+            }
+            ControlCommand::Resume if !self.paused => Ok("not paused".to_string()),
+            ControlCommand::Resume => {
+                self.paused = false;
+                Ok("resumed; the next cycle starts now".to_string())
+            }
+            ControlCommand::FlushReport => self.flush_report(cli, config).await,
+            ControlCommand::RotateLog => match self.log.as_mut() {
+                Some(log) => log.rotate().map(|rotated| format!("previous log moved to {}", rotated.display())),
+                None => Err(AppError::validation("no --log-file was given")),
+            },
+            ControlCommand::Windows => self.describe_windows(),
+        };
+        match &outcome {
+            Ok(message) => self.log_line(&format!("{}: {}", request.command, message)),
+            Err(e) => eprintln!("Warning: {} failed: {}", request.command, e),
+        }
+        let paused = self.paused;
+        self.update_served(|served| served.status.paused = paused);
+        request.reply(outcome);
+    }
+
+    /// Change what --serve answers with, if serving
+    fn update_served(&self, update: impl FnOnce(&mut Served)) {
+        if let Some(Ok(mut served)) = self.served.as_ref().map(|state| state.lock()) {
+            update(&mut served);
+        }
+    }
+
+    /// Print a report over every measurement since the last flush and start over
+    async fn flush_report(&mut self, cli: &Cli, config: &Config) -> Result<String> {
+        if self.pending.is_empty() {
+            return Err(AppError::validation("no measurements since the last flush-report"));
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let requests: u32 = pending.iter().map(|r| r.total_count).sum();
+        let mut results = create_execution_results(pending, &config.target_urls, &[]);
+        analyze_results(config, &mut results);
+        results.reproduction = Some(reproduction(cli, config));
+
+        println!("Report on {} requests over {} cycles:", requests, self.pending_cycles);
+        display_results(cli, config, &results, None).await?;
+        export_results(cli, config, &results)?;
+
+        let message = format!("reported {} requests over {} cycles", requests, self.pending_cycles);
+        self.pending_cycles = 0;
+        Ok(message)
+    }
+
+    /// Export a report over every measurement since the last flush-report,
+    /// without starting over, when a snapshot signal arrives
+    fn handle_snapshot(&mut self, cli: &Cli, config: &Config) {
+        let outcome = match cli.export.as_ref().or(cli.export_csv.as_ref()) {
+            Some(export_path) if !self.pending.is_empty() => {
+                let requests: u32 = self.pending.iter().map(|r| r.total_count).sum();
+                let mut results = create_execution_results(self.pending.clone(), &config.target_urls, &[]);
+                analyze_results(config, &mut results);
+                results.reproduction = Some(reproduction(cli, config));
+                export_results(cli, config, &results).map(|()| {
+                    format!("exported {} requests over {} cycles to {}", requests, self.pending_cycles, export_path.display())
+                })
+            }
+            Some(_) => Err(AppError::validation("no measurements since the last flush-report")),
+            None => Err(AppError::validation("no --export path to write it to")),
+        };
+        match outcome {
+            Ok(message) => {
+                println!("Snapshot: {}", message);
+                self.log_line(&format!("snapshot: {}", message));
+            }
+            Err(e) => eprintln!("Warning: snapshot failed: {}", e),
+        }
+    }
+
+    /// Average latency of each configuration over each rolling window, on one line
+    fn describe_windows(&self) -> Result<String> {
+        if self.windows.is_empty() {
+            return Err(AppError::validation("no cycle has finished yet"));
+        }
+        let now = timing::timestamp();
+        let configs: Vec<String> = self.windows.iter()
+            .map(|(config_name, windows)| {
+                let spans: Vec<String> = windows.snapshot(now).iter().map(ToString::to_string).collect();
+                format!("{}: {}", config_name, spans.join(", "))
+            })
+            .collect();
+        Ok(configs.join("; "))
+    }
+
+    fn log_line(&mut self, line: &str) {
+        if let Some(log) = self.log.as_mut() {
+            if let Err(e) = log.append(line) {
+                eprintln!("Warning: {}", e);
+            }
+        }
+    }
+}
+
+/// Log file with one line per watch cycle and control command
+struct CycleLog {
+    path: PathBuf,
+    /// Closed only while the log is being rotated
+    file: Option<std::fs::File>,
+}
+
+impl CycleLog {
+    fn open(path: &Path) -> Result<Self> {
+        let mut log = Self { path: path.to_path_buf(), file: None };
+        log.reopen()?;
+        Ok(log)
+    }
+
+    fn reopen(&mut self) -> Result<()> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| AppError::io(format!("Failed to open {}: {}", self.path.display(), e)).with_source(e))?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn append(&mut self, line: &str) -> Result<()> {
+        use std::io::Write;
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let timestamp = timing::timestamp().format("%Y-%m-%d %H:%M:%S UTC");
+        writeln!(file, "{} {}", timestamp, line)
+            .map_err(|e| AppError::io(format!("Failed to write {}: {}", self.path.display(), e)).with_source(e))
+    }
+
+    /// Move the log to `<path>.1`, replacing an earlier one, and start a new log
+    fn rotate(&mut self) -> Result<PathBuf> {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        let rotated = PathBuf::from(rotated);
+
+        // Windows cannot rename a file that is open, so close it first
+        self.file = None;
+        let renamed = std::fs::rename(&self.path, &rotated);
+        self.reopen()?;
+        renamed.map_err(|e| AppError::io(format!("Failed to rotate {}: {}", self.path.display(), e)).with_source(e))?;
+        Ok(rotated)
+    }
+}
+
+/// Send a control command to a running watch-mode instance and print its answer
+async fn handle_ctl_mode(args: &CtlArgs, socket: Option<&Path>) -> Result<()> {
+    let socket = socket.map_or_else(control::default_path, Path::to_path_buf);
+    let answer = control::send(&socket, args.command).await?;
+    println!("{}", answer);
+    Ok(())
+}
+
+/// Run a query over previously exported results
+fn handle_query_mode(args: &QueryArgs) -> Result<()> {
+    let query = Query::new(args.filter.as_deref(), &args.metric, args.group_by.as_deref())?;
+    let results = ExecutionResults::load_json(&args.file)?;
+
+    print!("{}", query.execute(&results).format_table());
+    Ok(())
+}
+
+/// Combine the exports of a sharded run into one report
+async fn handle_merge_mode(cli: &Cli, args: &MergeArgs) -> Result<()> {
+    let parts = args.files.iter()
+        .map(|file| ExecutionResults::load_json(file))
+        .collect::<Result<Vec<_>>>()?;
+    let merge = merge_shards(parts)?;
+
+    if let Some(count) = merge.shard_count {
+        println!("Merged {} of {} shards", count as usize - merge.missing.len(), count);
+    }
+    if !merge.missing.is_empty() {
+        let missing: Vec<String> = merge.missing.iter().map(ToString::to_string).collect();
+        eprintln!("Warning: no results for shard {}; the report covers part of the target list", missing.join(", "));
+    }
+
+    // Merging needs no network configuration, only the output settings
+    let config = Config { enable_color: cli.use_colors(), verbose: cli.verbose, ..Default::default() };
+    let mut results = merge.results;
+    analyze_results(&config, &mut results);
+    display_results(cli, &config, &results, None).await?;
+
+    if let Some(ref output) = args.output {
+        results.save_json(output)?;
+        println!("Merged results written to {}", output.display());
+    }
+    Ok(())
+}
+
+/// Feed exported runs back through the statistics, output and alerts, one
+/// watch cycle per file
+async fn handle_replay_mode(cli: &Cli, args: &ReplayArgs) -> Result<()> {
+    let runs = args.files.iter()
+        .map(|file| ExecutionResults::load_json(file))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Replaying needs no network configuration, only the output settings
+    let config = Config { enable_color: cli.use_colors(), verbose: cli.verbose, ..Default::default() };
+    let mut notifier = args.notify.map(BreachNotifier::new);
+    let mut history = CycleHistory::new();
+    let mut previous_end = None;
+    let mut unhealthy = 0;
+    for (cycle, (file, recorded)) in (1..).zip(args.files.iter().zip(runs)) {
+        let mut replay = Replay::new(recorded, config.verbose);
+        if args.realtime {
+            replay = replay.paced(args.speed, previous_end);
+        }
+        previous_end = replay.finished_at().or(previous_end);
+        println!("Replaying {} ({} requests)", file.display(), replay.samples().len());
+
+        let mut results = replay.run().await;
+        analyze_results(&config, &mut results);
+        let drift = history.record(&results);
+        display_results(cli, &config, &results, Some(drift)).await?;
+
+        let healthy = check_success_rate(&results);
+        if let Some(ref mut notifier) = notifier {
+            let problem = healthy.as_ref().err().map(|e| e.message().to_string());
+            if let Some(notice) = notifier.observe(cycle, problem.as_deref()) {
+                if let Err(e) = notification::send(notifier.target(), &notice).await {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+        }
+        if let Err(e) = healthy {
+            unhealthy += 1;
+            eprintln!("Error: {}", e);
+        }
+    }
+
+    println!("Replayed {} runs; {} failed the health check", args.files.len(), unhealthy);
+    Ok(())
+}
+
+/// Show latency by hour of day and day of week from exported results
+fn handle_heatmap_mode(args: &HeatmapArgs) -> Result<()> {
+    let runs = args.files.iter()
+        .map(|file| ExecutionResults::load_json(file))
+        .collect::<Result<Vec<_>>>()?;
+    let results = runs.iter().flat_map(|run| run.test_results.values());
+    let heatmap = if args.utc {
+        LatencyHeatmap::from_results(results, &Utc.fix(), "UTC")
+    } else {
+        let zone = iana_time_zone::get_timezone().unwrap_or_else(|_| "local time".to_string());
+        LatencyHeatmap::from_results(results, &Local, zone)
+    }
+    .with_reproductions(runs.iter().filter_map(|run| run.reproduction.as_ref()));
+    if heatmap.is_empty() {
+        return Err(AppError::validation("The results files have no successful samples to chart"));
+    }
+
+    print!("{}", heatmap.render_text());
+    if let Some(ref html) = args.html {
+        std::fs::write(html, heatmap.render_html())
+            .map_err(|e| AppError::io(format!("Failed to write {}: {}", html.display(), e)).with_source(e))?;
+        println!("\nHTML heatmap written to {}", html.display());
+    }
+    Ok(())
+}
+
+/// Time each configured DoH provider, splitting connection setup from query time
+async fn handle_dns_bench_mode(config: &Config, args: &DnsBenchArgs) -> Result<()> {
+    if config.doh_providers.is_empty() {
+        return Err(AppError::validation("No DoH providers are configured; set DOH_PROVIDERS to benchmark them"));
+    }
+
+    let dns_manager = DnsManager::new()?.with_doh_bootstrap(&config.doh_bootstrap).await?;
+    let display_names = config.display_names();
+    println!("DoH providers, looking up {} with {} warm queries each:", args.domain, args.queries);
+    for lookup in dns_manager.doh_bootstrap() {
+        println!("  Bootstrap: {}", lookup);
+    }
+    if config.doh_fresh_connections {
+        println!("(test runs open a new connection for every DoH query, so their lookups take the cold query time)");
+    }
+    for url in &config.doh_providers {
+        let name = display_names.resolve(&DnsConfig::DoH { url: url.clone() });
+        match dns_manager.benchmark_doh(url, &args.domain, args.queries as usize).await {
+            Ok(timing) => println!("  {} - {}", name, timing),
+            Err(e) => println!("  {} - failed: {}", name, e),
+        }
+    }
+    Ok(())
+}
+
+/// Run two configurations interleaved and compare them round by round
+async fn handle_ab_mode(cli: &Cli, args: &AbArgs) -> Result<()> {
+    let a = AbVariant::load(cli, &args.config_a)?;
+    let b = AbVariant::load(cli, &args.config_b)?;
+    println!("Running {} rounds of {} (A) and {} (B)...", args.rounds, args.config_a.display(), args.config_b.display());
+    let report = AbExperiment::new(a, b, args.rounds).run().await?;
+    println!();
+    print!("{}", report);
+    Ok(())
+}
+
+/// Time the executor, statistics engine and formatter on simulated runs
+async fn handle_bench_internal_mode(args: &BenchInternalArgs) -> Result<()> {
+    let size = BenchSize { targets: args.targets as usize, configs: args.configs as usize, iterations: args.iterations };
+    println!("Benchmarking {} targets x {} DNS configurations x {} iterations ({} samples), {} runs each...",
+        size.targets, size.configs, size.iterations, size.samples(), args.runs);
+    for measurement in bench::run_all(&size, args.runs).await? {
+        println!("  {}", measurement);
+    }
+    Ok(())
+}
+
+/// Answer DNS queries from fixed records until interrupted
+#[cfg(feature = "mock")]
+async fn handle_serve_mock_dns_mode(args: &network_latency_tester::cli::ServeMockDnsArgs) -> Result<()> {
+    use network_latency_tester::dns::mock_server::MockDnsServer;
+
+    let records = if args.records.is_empty() {
+        vec![("*".to_string(), vec![std::net::Ipv4Addr::LOCALHOST.into()])]
+    } else {
+        args.records.clone()
+    };
+    let mut server = MockDnsServer::new().with_delay(args.delay.unwrap_or_default());
+    for (name, ips) in &records {
+        server = server.with_record(name, ips.clone());
+    }
+    let mut handle = server.start(Some(args.listen), Some(args.doh_listen)).await?;
+
+    println!("Mock DNS server answering on udp://{}", args.listen);
+    println!("Mock DoH endpoint at {}", handle.doh_url().unwrap_or_default());
+    for (name, ips) in &records {
+        let ips: Vec<String> = ips.iter().map(|ip| ip.to_string()).collect();
+        println!("  {} -> {}", name, ips.join(", "));
+    }
+    if let Some(delay) = args.delay {
+        println!("  (each answer delayed by {})", format_duration(delay));
+    }
+    println!("Press Ctrl-C to stop");
+
+    tokio::select! {
+        _ = cancel::global().cancelled() => {}
+        _ = handle.wait() => {}
+    }
+    println!("Queries answered: {}", handle.queries());
+    Ok(())
+}
+
+/// Run the setup wizard and write its answers as a .env file
+fn handle_init_mode(args: &InitArgs) -> Result<()> {
+    let probe = (!args.no_check).then(TcpProbe::default);
+    let mut wizard = SetupWizard::new(std::io::stdin().lock(), std::io::stdout(), probe);
+
+    if args.output.exists()
+        && !args.force
+        && !wizard.confirm(&format!("{} already exists. Overwrite it?", args.output.display()), false)?
+    {
+        println!("Nothing written.");
+        return Ok(());
+    }
+
+    let answers = wizard.run()?;
+    std::fs::write(&args.output, answers.to_env_content())
+        .map_err(|e| AppError::io(format!("Failed to write {}: {}", args.output.display(), e)).with_source(e))?;
+
+    println!("\nConfiguration written to {}", args.output.display());
+    if args.output != std::path::Path::new(".env") {
+        println!("Only .env in the current directory is loaded automatically; rename or copy it to use it.");
+    }
+    Ok(())
+}
+
+/// Show or change the preferences saved in the platform's config directory
+fn handle_config_mode(args: &ConfigArgs) -> Result<()> {
+    let preferences = Preferences::locate()
+        .ok_or_else(|| AppError::config("No config directory found; set XDG_CONFIG_HOME or HOME"))?;
+    match args.action {
+        ConfigAction::Show => {
+            let created = if preferences.path().exists() { "" } else { " (not created yet)" };
+            println!("Preferences: {}{}", preferences.path().display(), created);
+            let entries: BTreeMap<String, String> = preferences.entries()?.into_iter().collect();
+            for (key, _) in PREFERENCE_KEYS {
+                let Some(value) = entries.get(*key) else {
+                    println!("  {:<15} (not set)", key);
+                    continue;
+                };
+                // .env and the environment were loaded first, so a different value came from them
+                match std::env::var(key) {
+                    Ok(current) if current != *value => {
+                        println!("  {:<15} {} (overridden by .env or the environment: {})", key, value, current)
+                    }
+                    _ => println!("  {:<15} {}", key, value),
+                }
+            }
+        }
+        ConfigAction::Set { ref key, ref value } => {
+            let key = preferences.set(key, value)?;
+            if value.trim().is_empty() {
+                println!("Removed {} from {}", key, preferences.path().display());
+            } else {
+                println!("Saved {}={} to {}", key, value.trim(), preferences.path().display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Show or clear the caches kept on disk
+fn handle_cache_mode(cli: &Cli, args: &CacheArgs) -> Result<()> {
+    for cache in known_caches() {
+        match args.action {
+            CacheAction::Stats => println!("{} cache\n{}", cache.name(), cache.format_stats(cli.use_colors())?),
+            CacheAction::Clear => match cache.clear()? {
+                Some(size_bytes) => println!("Cleared the {} cache ({} bytes at {})", cache.name(), size_bytes, cache.path().display()),
+                None => println!("The {} cache is already empty", cache.name()),
+            },
+        }
+    }
+    Ok(())
+}
+
 /// Handle update mode operations with clean exit
+#[cfg(feature = "updater")]
 async fn handle_update_mode(cli: &Cli) -> Result<()> {
     // Create update coordinator with configuration from CLI
     let coordinator = UpdateCoordinator::with_config(cli.use_colors(), cli.verbose);
@@ -196,7 +1240,7 @@ async fn handle_update_mode(cli: &Cli) -> Result<()> {
 
     // Handle update results and display appropriate information
     match result {
-        network_latency_tester::updater::UpdateResult::UpdateAvailable { current, latest, download_url } => {
+        network_latency_tester::updater::UpdateResult::UpdateAvailable { ref current, ref latest, ref download_url, .. } => {
             println!("Update available!");
             println!("Current version: {}", current.original);
             println!("Latest version: {}", latest.tag_name);
@@ -207,20 +1251,20 @@ async fn handle_update_mode(cli: &Cli) -> Result<()> {
                 println!("Release page: {}", latest.html_url);
             }
         },
-        network_latency_tester::updater::UpdateResult::AlreadyUpToDate { current } => {
+        network_latency_tester::updater::UpdateResult::AlreadyUpToDate { ref current, .. } => {
             println!("Already up to date at version {}", current.original);
         },
-        network_latency_tester::updater::UpdateResult::DowngradeAvailable { current, target, download_url } => {
+        network_latency_tester::updater::UpdateResult::DowngradeAvailable { ref current, ref target, ref download_url, .. } => {
             println!("Downgrade available (forced)");
             println!("Current version: {}", current.original);
             println!("Target version: {}", target.tag_name);
             println!("Download URL: {}", download_url);
             
             if !cli.force {
-                return Err(AppError::update("Downgrade detected. Use --force to proceed."));
+                return Err(AppError::update_error(UpdateErrorKind::DowngradeRequiresForce, "Downgrade detected. Use --force to proceed."));
             }
         },
-        network_latency_tester::updater::UpdateResult::InteractiveSelection { current, available_releases } => {
+        network_latency_tester::updater::UpdateResult::InteractiveSelection { ref current, ref available_releases, .. } => {
             println!("Interactive version selection");
             println!("Current version: {}", current.original);
             println!("Available releases: {}", available_releases.len());
@@ -231,6 +1275,9 @@ async fn handle_update_mode(cli: &Cli) -> Result<()> {
     }
 
     if cli.verbose {
+        if let Some(source) = result.source() {
+            println!("Release data from {}", source);
+        }
         let duration = coordinator.get_duration();
         println!();
         println!("Update operation completed in {:.2}s", duration.as_secs_f64());
@@ -300,12 +1347,13 @@ fn print_error_suggestions(error: &AppError) {
 
 /// Convert test results into ExecutionResults structure
 fn create_execution_results(test_results: Vec<TestResult>, _urls: &[String], _dns_configs: &[DnsConfig]) -> network_latency_tester::executor::ExecutionResults {
-    use network_latency_tester::executor::{ExecutionResults, ExecutionSummary};
-    use std::collections::HashMap;
+    use network_latency_tester::executor::{ExecutionResults, ExecutionSummary, SystemSnapshot};
     
     let total_tests = test_results.len() as u32;
     let successful_tests = test_results.iter().filter(|r| r.success_count > 0).count() as u32;
-    let failed_tests = total_tests - successful_tests;
+    // Stopped by the time limit or cancellation before their first request
+    let skipped_tests = test_results.iter().filter(|r| r.total_count == 0).count() as u32;
+    let failed_tests = total_tests - successful_tests - skipped_tests;
     
     let success_rate = if total_tests > 0 {
         (successful_tests as f64 / total_tests as f64) * 100.0
@@ -318,17 +1366,13 @@ fn create_execution_results(test_results: Vec<TestResult>, _urls: &[String], _dn
         total_tests,
         successful_tests,
         failed_tests,
-        timeout_tests: 0,
-        skipped_tests: 0,
+        skipped_tests,
         success_rate,
-        performance_summary: HashMap::new(),
+        // Taken right after the run, so the 1 minute load average covers the tests
+        system: Some(SystemSnapshot::capture()),
+        ..Default::default()
     };
     
-    // Convert test results to HashMap using actual configuration names
-    let mut results_map = HashMap::new();
-    for result in test_results.into_iter() {
-        results_map.insert(result.config_name.clone(), result);
-    }
-    
-    ExecutionResults::new(execution_summary, results_map)
+    // Results are keyed by (url, config_name) so multi-URL runs keep every combination
+    ExecutionResults::new(execution_summary, test_results)
 }
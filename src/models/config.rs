@@ -1,9 +1,13 @@
 //! Configuration data model and validation
 
-use crate::types::{DnsConfig, Result, AppError};
+use crate::types::{BindTarget, DisplayNames, DnsConfig, DohBootstrap, Locale, PoolOptions, ResolverOptions, ProtocolOptions, RedirectPolicy, Result, AppError, Shard, TlsVersion, Upload, UserAgent};
+use crate::utils::duration::{format_duration, parse_duration};
+use crate::utils::url::{is_mdns_name, normalize_url, suggest_url, UrlIssue};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::net::IpAddr;
 use std::time::Duration;
+use std::fmt;
 use std::str::FromStr;
 
 /// Main application configuration
@@ -20,14 +24,44 @@ pub struct Config {
     /// DNS-over-HTTPS provider URLs
     #[serde(default = "default_doh_providers")]
     pub doh_providers: Vec<String>,
+
+    /// Resolver failover chains such as `223.5.5.5>8.8.8.8`, each tested as one configuration
+    #[serde(default)]
+    pub dns_chains: Vec<String>,
+
+    /// Search domains for short host names, in place of the system's
+    #[serde(default)]
+    pub search_domains: Option<Vec<String>>,
+
+    /// Names with fewer dots are tried with the search domains first, in place of the system's ndots
+    #[serde(default)]
+    pub ndots: Option<u8>,
+
+    /// Also test each short target under its names qualified with the search domains
+    #[serde(default)]
+    pub test_search_paths: bool,
     
     /// Number of test iterations per configuration
     #[serde(default = "default_test_count")]
     pub test_count: u32,
     
-    /// Request timeout duration
-    #[serde(default = "default_timeout_secs")]
-    pub timeout_seconds: u64,
+    /// Request timeout duration (sub-second values are allowed)
+    #[serde(default = "default_timeout", with = "crate::utils::duration::serde_duration")]
+    pub timeout: Duration,
+
+    /// Whether the timeout was set by any layer above the built-in default,
+    /// even to the default's value
+    #[serde(default)]
+    pub timeout_given: bool,
+
+    /// Time budget for the whole run; iteration counts are reduced to fit it
+    #[serde(default, with = "crate::utils::duration::serde_option_duration")]
+    pub max_runtime: Option<Duration>,
+
+    /// Target precision in percent when iterations continue past `test_count`
+    /// until the mean and p95 are that precise; fixed iteration counts when unset
+    #[serde(default)]
+    pub auto_count: Option<f64>,
     
     /// Enable colored terminal output
     #[serde(default = "default_enable_color")]
@@ -40,6 +74,298 @@ pub struct Config {
     /// Enable debug output
     #[serde(default)]
     pub debug: bool,
+
+    /// Weights used to compute the overall configuration ranking score
+    #[serde(default)]
+    pub scoring: ScoringWeights,
+
+    /// Count timed-out requests as lasting at least the timeout in percentiles
+    #[serde(default)]
+    pub censor_timeouts: bool,
+
+    /// Read kernel TCP statistics (RTT, retransmissions) after each request
+    #[serde(default)]
+    pub socket_stats: bool,
+
+    /// UDP packets to send to each route and resolver to measure packet loss;
+    /// no loss probe when unset
+    #[serde(default)]
+    pub loss_probe: Option<u16>,
+
+    /// ICMP echo requests to send to each address the requests went to;
+    /// no ping when unset
+    #[serde(default)]
+    pub ping: Option<u16>,
+
+    /// Whether requests follow redirects, and to which schemes
+    #[serde(default)]
+    pub redirect_policy: RedirectPolicy,
+
+    /// Redirects a request may follow before it fails
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: u32,
+
+    /// User-Agent preset, or custom string, requests are sent with
+    #[serde(default)]
+    pub user_agent: UserAgent,
+
+    /// Speak HTTP/2 without negotiating it first
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+
+    /// Accept connections on which ALPN settled on HTTP/1.1
+    #[serde(default = "default_alpn_fallback")]
+    pub alpn_fallback: bool,
+
+    /// Oldest TLS version to negotiate
+    #[serde(default)]
+    pub tls_min_version: Option<TlsVersion>,
+
+    /// Newest TLS version to negotiate
+    #[serde(default)]
+    pub tls_max_version: Option<TlsVersion>,
+
+    /// Resume TLS sessions (true) or never (false); the platform TLS library
+    /// decides when unset
+    #[serde(default)]
+    pub tls_resumption: Option<bool>,
+
+    /// Open a new connection to the DoH provider for every query instead of
+    /// reusing one, so each lookup includes the provider's TLS setup
+    #[serde(default)]
+    pub doh_fresh_connections: bool,
+
+    /// Verify before each run that custom DNS configurations resolve through
+    /// their own servers, and afterwards that system DNS settings are unchanged
+    #[serde(default)]
+    pub sandbox_dns: bool,
+
+    /// Flush the OS resolver cache before testing, so that System DNS
+    /// lookups are not answered from a warm cache
+    #[serde(default)]
+    pub flush_dns_cache: bool,
+
+    /// Run on a single-threaded runtime with smaller pools and buffers
+    #[serde(default)]
+    pub single_thread: bool,
+
+    /// NTP server to compare the system clock against; the check is skipped when unset
+    #[serde(default)]
+    pub ntp_server: Option<String>,
+
+    /// Language of the default DNS configuration names
+    #[serde(default)]
+    pub locale: Locale,
+
+    /// How to resolve DoH provider host names up front, keyed by host name
+    #[serde(default)]
+    pub doh_bootstrap: BTreeMap<String, DohBootstrap>,
+
+    /// Names to report DNS configurations under, keyed by configuration id
+    /// (`system`, a DNS server address or a DoH URL)
+    #[serde(default)]
+    pub dns_labels: BTreeMap<String, String>,
+
+    /// Resolver settings replacing the defaults, keyed by configuration id
+    /// (`system` or DNS server addresses joined with `+`)
+    #[serde(default)]
+    pub resolver_options: BTreeMap<String, ResolverOptions>,
+
+    /// Share of the target list this process tests when several split it
+    #[serde(default)]
+    pub shard: Option<Shard>,
+
+    /// Network interface or local address requests are sent from; the
+    /// routing table decides when unset
+    #[serde(default)]
+    pub bind: Option<BindTarget>,
+
+    /// Connection pool settings replacing those chosen for the run
+    #[serde(default)]
+    pub pool_options: Option<PoolOptions>,
+
+    /// Generated body each request uploads, to time the upload path rather
+    /// than the download
+    #[serde(default)]
+    pub upload: Option<Upload>,
+
+    /// Time informational (1xx) responses, such as `103 Early Hints`, with a
+    /// separate HTTP/1.1 request after each successful one
+    #[serde(default)]
+    pub early_hints: bool,
+
+    /// Capture the traffic to a target after one of its requests fails or
+    /// takes longer than this
+    #[serde(default, with = "crate::utils::duration::serde_option_duration")]
+    pub capture_on_anomaly: Option<Duration>,
+
+    /// Write a debug trace of every request to a file per run
+    #[serde(default)]
+    pub trace_requests: bool,
+
+    /// Tags of target URLs, as given in a `--url-file`
+    #[serde(default)]
+    pub url_tags: BTreeMap<String, Vec<String>>,
+}
+
+/// Relative weights of the metrics that make up a configuration's overall score
+///
+/// Weights do not need to sum to 1.0; the overall score is normalized by the
+/// total weight. Parsed from `key=value` pairs such as
+/// `speed=0.5,reliability=0.3,consistency=0.2`, where omitted keys weigh 0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoringWeights {
+    /// Average response time relative to the other configurations
+    #[serde(default)]
+    pub speed: f64,
+    /// Success rate
+    #[serde(default)]
+    pub reliability: f64,
+    /// Coefficient of variation of response times
+    #[serde(default)]
+    pub consistency: f64,
+    /// 95th percentile response time relative to the other configurations
+    #[serde(default)]
+    pub p95: f64,
+    /// Jitter (standard deviation) relative to the other configurations
+    #[serde(default)]
+    pub jitter: f64,
+    /// Packet loss measured by `--loss-probe`
+    #[serde(default)]
+    pub loss: f64,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            speed: 0.4,
+            reliability: 0.35,
+            consistency: 0.25,
+            p95: 0.0,
+            jitter: 0.0,
+            loss: 0.0,
+        }
+    }
+}
+
+impl ScoringWeights {
+    /// Names of the supported scoring dimensions
+    pub const DIMENSIONS: [&'static str; 6] = ["speed", "reliability", "consistency", "p95", "jitter", "loss"];
+
+    /// Get the weight of a scoring dimension by name
+    pub fn get(&self, dimension: &str) -> Option<f64> {
+        match dimension {
+            "speed" => Some(self.speed),
+            "reliability" => Some(self.reliability),
+            "consistency" => Some(self.consistency),
+            "p95" => Some(self.p95),
+            "jitter" => Some(self.jitter),
+            "loss" => Some(self.loss),
+            _ => None,
+        }
+    }
+
+    /// Sum of all weights
+    pub fn total(&self) -> f64 {
+        self.speed + self.reliability + self.consistency + self.p95 + self.jitter + self.loss
+    }
+
+    /// Validate that all weights are finite, non-negative and not all zero
+    pub fn validate(&self) -> Result<()> {
+        for dimension in Self::DIMENSIONS {
+            let weight = self.get(dimension).unwrap_or_default();
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(AppError::config(format!(
+                    "Scoring weight '{}' must be a non-negative number, got: {}", dimension, weight
+                )));
+            }
+        }
+
+        if self.total() <= 0.0 {
+            return Err(AppError::config("At least one scoring weight must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ScoringWeights {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut weights = Self {
+            speed: 0.0,
+            reliability: 0.0,
+            consistency: 0.0,
+            p95: 0.0,
+            jitter: 0.0,
+            loss: 0.0,
+        };
+
+        let pairs: Vec<&str> = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        if pairs.is_empty() {
+            return Err(AppError::config("Scoring weights cannot be empty"));
+        }
+
+        for pair in pairs {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                AppError::config(format!("Invalid scoring weight '{}': expected key=value", pair))
+            })?;
+            let value: f64 = value.trim().parse().map_err(|e| {
+                AppError::config(format!("Invalid scoring weight value '{}' for '{}': {}", value, key, e))
+            })?;
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "speed" => weights.speed = value,
+                "reliability" => weights.reliability = value,
+                "consistency" => weights.consistency = value,
+                "p95" => weights.p95 = value,
+                "jitter" => weights.jitter = value,
+                "loss" => weights.loss = value,
+                other => {
+                    return Err(AppError::config(format!(
+                        "Unknown scoring dimension '{}' (expected one of: {})",
+                        other,
+                        Self::DIMENSIONS.join(", ")
+                    )));
+                }
+            }
+        }
+
+        weights.validate()?;
+        Ok(weights)
+    }
+}
+
+impl fmt::Display for ScoringWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = Self::DIMENSIONS
+            .iter()
+            .filter_map(|&dimension| {
+                self.get(dimension)
+                    .filter(|weight| *weight > 0.0)
+                    .map(|weight| format!("{}={}", dimension, weight))
+            })
+            .collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+/// Where a URL's host ends, when the host is a short name that search
+/// domains apply to: a domain name with fewer than `ndots` dots
+fn short_host_end(url: &str, ndots: usize) -> Option<usize> {
+    let start = url.find("://")? + 3;
+    let authority_end = url[start..].find(['/', '?', '#']).map_or(url.len(), |end| start + end);
+    let host_start = url[start..authority_end].rfind('@').map_or(start, |at| start + at + 1);
+    let host_end = url[host_start..authority_end].find(':').map_or(authority_end, |colon| host_start + colon);
+    let host = &url[host_start..host_end];
+    let is_name = !host.is_empty() && !host.starts_with('[') && host.parse::<IpAddr>().is_err();
+    let short = !host.ends_with('.') && host.matches('.').count() < ndots && !host.eq_ignore_ascii_case("localhost");
+    (is_name && short).then_some(host_end)
 }
 
 impl Default for Config {
@@ -48,11 +374,48 @@ impl Default for Config {
             target_urls: default_target_urls(),
             dns_servers: default_dns_servers(),
             doh_providers: default_doh_providers(),
+            dns_chains: Vec::new(),
+            search_domains: None,
+            ndots: None,
+            test_search_paths: false,
             test_count: default_test_count(),
-            timeout_seconds: default_timeout_secs(),
+            timeout: default_timeout(),
+            timeout_given: false,
+            max_runtime: None,
+            auto_count: None,
             enable_color: default_enable_color(),
             verbose: false,
             debug: false,
+            scoring: ScoringWeights::default(),
+            censor_timeouts: false,
+            socket_stats: false,
+            loss_probe: None,
+            ping: None,
+            redirect_policy: RedirectPolicy::default(),
+            max_redirects: default_max_redirects(),
+            user_agent: UserAgent::default(),
+            http2_prior_knowledge: false,
+            alpn_fallback: default_alpn_fallback(),
+            tls_min_version: None,
+            tls_max_version: None,
+            tls_resumption: None,
+            doh_fresh_connections: false,
+            sandbox_dns: false,
+            flush_dns_cache: false,
+            single_thread: false,
+            ntp_server: None,
+            locale: Locale::default(),
+            doh_bootstrap: BTreeMap::new(),
+            dns_labels: BTreeMap::new(),
+            resolver_options: BTreeMap::new(),
+            shard: None,
+            bind: None,
+            pool_options: None,
+            upload: None,
+            early_hints: false,
+            capture_on_anomaly: None,
+            trace_requests: false,
+            url_tags: BTreeMap::new(),
         }
     }
 }
@@ -65,9 +428,164 @@ impl Config {
     
     /// Get timeout as Duration
     pub fn timeout(&self) -> Duration {
-        Duration::from_secs(self.timeout_seconds)
+        self.timeout
+    }
+
+    /// Parse search domains separated by commas or spaces, as resolv.conf lists them;
+    /// an empty value means no search domains
+    pub fn parse_search_domains(value: &str) -> Result<Vec<String>> {
+        value.split([',', ' '])
+            .map(|domain| domain.trim().trim_matches('.'))
+            .filter(|domain| !domain.is_empty())
+            .map(|domain| {
+                Self::validate_search_domain(domain)?;
+                Ok(domain.to_ascii_lowercase())
+            })
+            .collect()
+    }
+
+    /// Check that a search domain is a domain name, not a URL or address list
+    pub fn validate_search_domain(domain: &str) -> Result<()> {
+        let valid = !domain.is_empty()
+            && domain.split('.').all(|label| {
+                !label.is_empty() && label.len() <= 63 && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+        if !valid {
+            return Err(AppError::config(format!("Invalid search domain '{}': expected a domain such as corp.example.com", domain)));
+        }
+        Ok(())
+    }
+
+    /// Check that `ndots` is at most 15, the most resolv.conf accepts
+    pub fn validate_ndots(ndots: u8) -> Result<()> {
+        if ndots > crate::defaults::MAX_NDOTS {
+            return Err(AppError::config(format!("ndots must be at most {}, got: {}", crate::defaults::MAX_NDOTS, ndots)));
+        }
+        Ok(())
+    }
+
+    /// Check that an NTP server is a non-empty `host` or `host:port` without whitespace
+    pub fn validate_ntp_server(server: &str) -> Result<()> {
+        if server.is_empty() || server.contains(char::is_whitespace) {
+            return Err(AppError::config(format!("Invalid NTP server '{}': expected host or host:port", server)));
+        }
+        Ok(())
+    }
+
+    /// Check that a request timeout is greater than zero and at most 300 seconds
+    pub fn validate_timeout(timeout: Duration) -> Result<()> {
+        if timeout.is_zero() {
+            return Err(AppError::config("Timeout must be greater than 0"));
+        }
+
+        if timeout > crate::defaults::MAX_TIMEOUT {
+            return Err(AppError::config(format!(
+                "Timeout cannot exceed 300 seconds, got: {}",
+                format_duration(timeout)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Parse an `--auto-count` target precision such as `5`, `5%` or `±2.5%`
+    pub fn parse_precision(value: &str) -> Result<f64> {
+        let number = value.trim().trim_start_matches(['±', '+']).trim_end_matches('%').trim();
+        let precision: f64 = number.parse()
+            .map_err(|_| AppError::config(format!("Invalid precision '{}': expected a percentage such as 5%", value.trim())))?;
+        Self::validate_precision(precision)?;
+        Ok(precision)
+    }
+
+    /// Parse an `AUTO_COUNT` value: `true` for the default precision, a
+    /// precision such as `2%`, or `false` to use fixed iteration counts
+    pub fn parse_auto_count(value: &str) -> Result<Option<f64>> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "false" => Ok(None),
+            "true" => Ok(Some(crate::defaults::DEFAULT_AUTO_COUNT_PRECISION)),
+            precision => Self::parse_precision(precision)
+                .map(Some)
+                .map_err(|e| AppError::config(format!("Invalid AUTO_COUNT value '{}': {}", value, e.message()))),
+        }
+    }
+
+    /// Parse a `TLS_RESUMPTION` setting: on/true, off/false, or empty to leave it to the platform
+    pub fn parse_tls_resumption(value: &str) -> Result<Option<bool>> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        crate::types::parse_switch(value)
+            .map(Some)
+            .ok_or_else(|| AppError::config(format!("Invalid TLS_RESUMPTION value '{}': expected on or off", value)))
+    }
+
+    /// Parse a `CAPTURE_ON_ANOMALY` threshold such as `500ms`, which must be
+    /// greater than zero
+    pub fn parse_capture_threshold(value: &str) -> Result<Duration> {
+        let threshold = parse_duration(value)
+            .map_err(|e| AppError::config(format!("Invalid CAPTURE_ON_ANOMALY value '{}': {}", value, e)))?;
+        if threshold.is_zero() {
+            return Err(AppError::config("CAPTURE_ON_ANOMALY must be greater than 0"));
+        }
+        Ok(threshold)
+    }
+
+    /// Check that an `--auto-count` target precision is a percentage above 0 and at most 50
+    pub fn validate_precision(precision: f64) -> Result<()> {
+        if !(precision > 0.0 && precision <= 50.0) {
+            return Err(AppError::config(format!("Precision must be above 0% and at most 50%, got: {}%", precision)));
+        }
+        Ok(())
     }
     
+    /// Parse a `LOSS_PROBE` value: `true` for the default packet count, a
+    /// packet count, or `false` (or 0) for no loss probe
+    pub fn parse_loss_probe(value: &str) -> Result<Option<u16>> {
+        let invalid = |reason: String| AppError::config(format!("Invalid LOSS_PROBE value '{}': {}", value, reason));
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "false" | "0" => Ok(None),
+            "true" => Ok(Some(crate::defaults::DEFAULT_LOSS_PROBE_PACKETS)),
+            packets => {
+                let packets: u16 = packets.parse().map_err(|e| invalid(format!("{}", e)))?;
+                Self::validate_loss_probe(packets).map_err(|e| invalid(e.message().to_string()))?;
+                Ok(Some(packets))
+            }
+        }
+    }
+
+    /// Check that a `--loss-probe` packet count is between 1 and the maximum
+    pub fn validate_loss_probe(packets: u16) -> Result<()> {
+        let max = crate::defaults::MAX_LOSS_PROBE_PACKETS;
+        if packets == 0 || packets > max {
+            return Err(AppError::config(format!("Loss probe packet count must be between 1 and {}, got: {}", max, packets)));
+        }
+        Ok(())
+    }
+
+    /// Parse a `PING` value: `true` for the default echo request count, a
+    /// count, or `false` (or 0) for no ping
+    pub fn parse_ping(value: &str) -> Result<Option<u16>> {
+        let invalid = |reason: String| AppError::config(format!("Invalid PING value '{}': {}", value, reason));
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "false" | "0" => Ok(None),
+            "true" => Ok(Some(crate::defaults::DEFAULT_PING_COUNT)),
+            count => {
+                let count: u16 = count.parse().map_err(|e| invalid(format!("{}", e)))?;
+                Self::validate_ping(count).map_err(|e| invalid(e.message().to_string()))?;
+                Ok(Some(count))
+            }
+        }
+    }
+
+    /// Check that a `--ping` echo request count is between 1 and the maximum
+    pub fn validate_ping(count: u16) -> Result<()> {
+        let max = crate::defaults::MAX_PING_COUNT;
+        if count == 0 || count > max {
+            return Err(AppError::config(format!("Ping count must be between 1 and {}, got: {}", max, count)));
+        }
+        Ok(())
+    }
+
     /// Validate the configuration and return any errors
     pub fn validate(&self) -> Result<()> {
         // Validate target URLs
@@ -78,7 +596,8 @@ impl Config {
             
             // Basic URL format validation
             if let Err(e) = url::Url::parse(url) {
-                return Err(AppError::config(format!("Invalid target URL '{}': {}", url, e)));
+                let hint = suggest_url(url).map(|fixed| format!(" (did you mean '{}'?)", fixed)).unwrap_or_default();
+                return Err(AppError::config(format!("Invalid target URL '{}': {}{}", url, e, hint)));
             }
         }
         
@@ -88,9 +607,7 @@ impl Config {
                 return Err(AppError::config("DNS server cannot be empty"));
             }
             
-            if IpAddr::from_str(dns_server).is_err() {
-                return Err(AppError::config(format!("Invalid DNS server IP address: {}", dns_server)));
-            }
+            DnsConfig::parse_servers(dns_server)?;
         }
         
         // Validate DoH providers
@@ -111,6 +628,18 @@ impl Config {
             }
         }
         
+        for chain in &self.dns_chains {
+            DnsConfig::parse_chain(chain)?;
+        }
+
+        for domain in self.search_domains.iter().flatten() {
+            Self::validate_search_domain(domain)?;
+        }
+
+        if let Some(ndots) = self.ndots {
+            Self::validate_ndots(ndots)?;
+        }
+        
         // Validate numeric parameters
         if self.test_count == 0 {
             return Err(AppError::config("Test count must be greater than 0"));
@@ -120,17 +649,196 @@ impl Config {
             return Err(AppError::config("Test count cannot exceed 100"));
         }
         
-        if self.timeout_seconds == 0 {
-            return Err(AppError::config("Timeout must be greater than 0"));
+        Self::validate_timeout(self.timeout)?;
+
+        if self.max_runtime.is_some_and(|max_runtime| max_runtime.is_zero()) {
+            return Err(AppError::config("Maximum run time must be greater than 0"));
         }
-        
-        if self.timeout_seconds > 300 {
-            return Err(AppError::config("Timeout cannot exceed 300 seconds"));
+
+        if let Some(precision) = self.auto_count {
+            Self::validate_precision(precision)?;
+        }
+
+        if let Some(packets) = self.loss_probe {
+            Self::validate_loss_probe(packets)?;
+        }
+
+        if let Some(count) = self.ping {
+            Self::validate_ping(count)?;
         }
+
+        self.protocol_options().validate()?;
+
+        if self.max_redirects > crate::defaults::MAX_REDIRECTS {
+            return Err(AppError::config(format!(
+                "Maximum redirects cannot exceed {}, got: {}",
+                crate::defaults::MAX_REDIRECTS,
+                self.max_redirects
+            )));
+        }
+
+        if let Some(ref ntp_server) = self.ntp_server {
+            Self::validate_ntp_server(ntp_server)?;
+        }
+
+        self.validate_dns_labels()?;
+        self.validate_doh_bootstrap()?;
+        self.validate_resolver_options()?;
+        
+        self.scoring.validate()?;
         
         Ok(())
     }
     
+    /// Normalize every target URL (see [`normalize_url`]), dropping duplicates
+    ///
+    /// Entries that cannot be fixed are removed and returned, so that one bad
+    /// entry does not stop the others from being tested. Fails, listing every
+    /// problem, only when no valid target is left.
+    pub fn normalize_target_urls(&mut self) -> Result<Vec<UrlIssue>> {
+        let mut issues = Vec::new();
+        let mut urls: Vec<String> = Vec::with_capacity(self.target_urls.len());
+        for entry in std::mem::take(&mut self.target_urls) {
+            match normalize_url(&entry) {
+                Ok(url) => {
+                    if let Some(tags) = self.url_tags.remove(&entry) {
+                        self.url_tags.entry(url.clone()).or_default().extend(tags);
+                    }
+                    if !urls.contains(&url) {
+                        urls.push(url);
+                    }
+                }
+                Err(issue) => issues.push(issue),
+            }
+        }
+        self.target_urls = urls;
+
+        if self.target_urls.is_empty() && !issues.is_empty() {
+            let problems: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(AppError::config(format!("No valid target URLs:\n  {}", problems.join("\n  "))));
+        }
+        Ok(issues)
+    }
+
+    /// Follow each target whose host is a short name, such as `http://intranet`,
+    /// with the same URL qualified with each search domain, so that both
+    /// resolution paths are tested; tags carry over to the added URLs
+    pub fn expand_search_paths(&mut self, domains: &[String], ndots: usize) {
+        let mut urls: Vec<String> = Vec::with_capacity(self.target_urls.len());
+        for url in std::mem::take(&mut self.target_urls) {
+            let qualified: Vec<String> = match short_host_end(&url, ndots) {
+                Some(end) => domains.iter()
+                    .map(|domain| domain.trim_matches('.'))
+                    .filter(|domain| !domain.is_empty())
+                    .map(|domain| format!("{}.{}{}", &url[..end], domain, &url[end..]))
+                    .collect(),
+                None => Vec::new(),
+            };
+            if let Some(tags) = self.url_tags.get(&url).cloned() {
+                for qualified in &qualified {
+                    self.url_tags.entry(qualified.clone()).or_insert_with(|| tags.clone());
+                }
+            }
+            for url in std::iter::once(url).chain(qualified) {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+        self.target_urls = urls;
+    }
+
+    /// Narrow the target list to this process's shard, if one is set
+    pub fn apply_shard(&mut self) -> Result<()> {
+        let Some(shard) = self.shard else {
+            return Ok(());
+        };
+        let total = self.target_urls.len();
+        self.target_urls = shard.select(&self.target_urls);
+        if self.target_urls.is_empty() {
+            return Err(AppError::config(format!(
+                "Shard {} has no targets: {} URLs cannot be split into {} shards",
+                shard, total, shard.count
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that every DNS label names a configured DNS configuration and that no two share a label
+    fn validate_dns_labels(&self) -> Result<()> {
+        let mut labelled: BTreeMap<&str, &str> = BTreeMap::new();
+        for (id, label) in &self.dns_labels {
+            let known = id == "system"
+                || (id == "mdns" && self.has_mdns_targets())
+                || self.doh_providers.contains(id)
+                || self.dns_chains.iter().any(|chain| DnsConfig::parse_chain(chain).is_ok_and(|chain| chain.id() == *id))
+                || self.dns_servers.iter().any(|s| DnsConfig::parse_servers(s).is_ok_and(|servers| servers.id() == *id))
+                || id.split('+').all(|server| self.dns_servers.iter().any(|s| s == server));
+            if !known {
+                return Err(AppError::config(format!(
+                    "DNS label '{}' is for '{}', which is not 'system', a configured DNS server, DoH provider or chain, or 'mdns' with .local targets",
+                    label, id
+                )));
+            }
+            if let Some(other) = labelled.insert(label, id) {
+                return Err(AppError::config(format!(
+                    "DNS label '{}' is used for both '{}' and '{}'", label, other, id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every DoH bootstrap is for the host of a configured DoH provider
+    fn validate_doh_bootstrap(&self) -> Result<()> {
+        for host in self.doh_bootstrap.keys() {
+            let known = self.doh_providers.iter()
+                .any(|url| DohBootstrap::provider_host(url).as_ref() == Some(host));
+            if !known {
+                return Err(AppError::config(format!(
+                    "DoH bootstrap for '{}' matches no configured DoH provider", host
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every set of resolver options is for the system resolver or
+    /// configured DNS servers, on their own or in a chain
+    fn validate_resolver_options(&self) -> Result<()> {
+        for id in self.resolver_options.keys() {
+            let known = id == "system"
+                || self.dns_servers.iter().any(|s| DnsConfig::parse_servers(s).is_ok_and(|servers| servers.id() == *id))
+                || id.split('+').all(|server| self.dns_servers.iter().any(|s| s == server))
+                || self.dns_chains.iter().filter_map(|chain| DnsConfig::parse_chain(chain).ok()).any(|chain| match chain {
+                    DnsConfig::Chain { resolvers } => resolvers.iter().any(|resolver| resolver.id() == *id),
+                    _ => false,
+                });
+            if !known {
+                return Err(AppError::config(format!(
+                    "Resolver options are for '{}', which is not 'system' or configured DNS servers", id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Names DNS configurations are reported under, from the locale and labels
+    pub fn display_names(&self) -> DisplayNames {
+        DisplayNames::new(self.locale).with_labels(self.dns_labels.clone())
+    }
+
+    /// HTTP client protocol choices
+    pub fn protocol_options(&self) -> ProtocolOptions {
+        ProtocolOptions {
+            http2_prior_knowledge: self.http2_prior_knowledge,
+            alpn_fallback: self.alpn_fallback,
+            tls_min_version: self.tls_min_version,
+            tls_max_version: self.tls_max_version,
+            tls_resumption: self.tls_resumption,
+        }
+    }
+
     /// Create DNS configurations from the config settings
     pub fn create_dns_configs(&self) -> Result<Vec<DnsConfig>> {
         let mut configs = Vec::new();
@@ -140,19 +848,33 @@ impl Config {
         
         // Add custom DNS servers
         for dns_server in &self.dns_servers {
-            match IpAddr::from_str(dns_server) {
-                Ok(ip) => configs.push(DnsConfig::Custom { servers: vec![ip] }),
-                Err(e) => return Err(AppError::dns_resolution(format!("Failed to parse DNS server {}: {}", dns_server, e))),
-            }
+            configs.push(DnsConfig::parse_servers(dns_server)?);
         }
         
-        // Add DoH providers
+        // Add DoH providers; builds without DNS-over-HTTPS support skip them
+        #[cfg(feature = "doh")]
         for doh_url in &self.doh_providers {
             configs.push(DnsConfig::DoH { url: doh_url.clone() });
         }
+
+        for chain in &self.dns_chains {
+            configs.push(DnsConfig::parse_chain(chain)?);
+        }
+
+        // LAN services under .local are only reachable through multicast DNS
+        if self.has_mdns_targets() {
+            configs.push(DnsConfig::Mdns);
+        }
         
         Ok(configs)
     }
+
+    /// Whether any target is a `.local` name, which needs the mDNS configuration
+    pub fn has_mdns_targets(&self) -> bool {
+        self.target_urls.iter().any(|target| {
+            url::Url::parse(target).is_ok_and(|url| url.host_str().is_some_and(is_mdns_name))
+        })
+    }
     
     /// Merge environment variables into this configuration
     pub fn merge_from_env(&mut self) -> Result<()> {
@@ -180,21 +902,187 @@ impl Config {
                 .collect();
         }
         
+        if let Ok(dns_chains) = std::env::var("DNS_CHAINS") {
+            self.dns_chains = dns_chains
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        
+        if let Ok(search_domains) = std::env::var("SEARCH_DOMAINS") {
+            self.search_domains = Some(Self::parse_search_domains(&search_domains)?);
+        }
+
+        if let Ok(ndots) = std::env::var("NDOTS") {
+            self.ndots = Some(ndots.trim().parse()
+                .map_err(|e| AppError::config(format!("Invalid NDOTS value '{}': {}", ndots, e)))?);
+        }
+
+        if let Ok(test_search_paths) = std::env::var("TEST_SEARCH_PATHS") {
+            self.test_search_paths = test_search_paths.parse()
+                .map_err(|e| AppError::config(format!("Invalid TEST_SEARCH_PATHS value '{}': {}", test_search_paths, e)))?;
+        }
+
         if let Ok(test_count) = std::env::var("TEST_COUNT") {
             self.test_count = test_count.parse()
                 .map_err(|e| AppError::config(format!("Invalid TEST_COUNT value '{}': {}", test_count, e)))?;
         }
         
         if let Ok(timeout) = std::env::var("TIMEOUT_SECONDS") {
-            self.timeout_seconds = timeout.parse()
+            self.timeout = parse_duration(&timeout)
                 .map_err(|e| AppError::config(format!("Invalid TIMEOUT_SECONDS value '{}': {}", timeout, e)))?;
         }
         
+        if let Ok(max_runtime) = std::env::var("MAX_RUNTIME") {
+            self.max_runtime = Some(parse_duration(&max_runtime)
+                .map_err(|e| AppError::config(format!("Invalid MAX_RUNTIME value '{}': {}", max_runtime, e)))?);
+        }
+        
         if let Ok(enable_color) = std::env::var("ENABLE_COLOR") {
             self.enable_color = enable_color.parse()
                 .map_err(|e| AppError::config(format!("Invalid ENABLE_COLOR value '{}': {}", enable_color, e)))?;
         }
         
+        if let Ok(scoring) = std::env::var("SCORING_WEIGHTS") {
+            self.scoring = scoring.parse()?;
+        }
+
+        if let Ok(auto_count) = std::env::var("AUTO_COUNT") {
+            self.auto_count = Self::parse_auto_count(&auto_count)?;
+        }
+
+        if let Ok(censor_timeouts) = std::env::var("CENSOR_TIMEOUTS") {
+            self.censor_timeouts = censor_timeouts.parse()
+                .map_err(|e| AppError::config(format!("Invalid CENSOR_TIMEOUTS value '{}': {}", censor_timeouts, e)))?;
+        }
+
+        if let Ok(socket_stats) = std::env::var("SOCKET_STATS") {
+            self.socket_stats = socket_stats.parse()
+                .map_err(|e| AppError::config(format!("Invalid SOCKET_STATS value '{}': {}", socket_stats, e)))?;
+        }
+
+        if let Ok(loss_probe) = std::env::var("LOSS_PROBE") {
+            self.loss_probe = Self::parse_loss_probe(&loss_probe)?;
+        }
+
+        if let Ok(ping) = std::env::var("PING") {
+            self.ping = Self::parse_ping(&ping)?;
+        }
+
+        if let Ok(redirect_policy) = std::env::var("REDIRECT_POLICY") {
+            self.redirect_policy = redirect_policy.parse()?;
+        }
+
+        if let Ok(max_redirects) = std::env::var("MAX_REDIRECTS") {
+            self.max_redirects = max_redirects.parse()
+                .map_err(|e| AppError::config(format!("Invalid MAX_REDIRECTS value '{}': {}", max_redirects, e)))?;
+        }
+
+        if let Ok(user_agent) = std::env::var("USER_AGENT") {
+            self.user_agent = user_agent.parse()?;
+        }
+
+        if let Ok(prior_knowledge) = std::env::var("HTTP2_PRIOR_KNOWLEDGE") {
+            self.http2_prior_knowledge = prior_knowledge.parse()
+                .map_err(|e| AppError::config(format!("Invalid HTTP2_PRIOR_KNOWLEDGE value '{}': {}", prior_knowledge, e)))?;
+        }
+
+        if let Ok(alpn_fallback) = std::env::var("ALPN_FALLBACK") {
+            self.alpn_fallback = alpn_fallback.parse()
+                .map_err(|e| AppError::config(format!("Invalid ALPN_FALLBACK value '{}': {}", alpn_fallback, e)))?;
+        }
+
+        if let Ok(version) = std::env::var("TLS_MIN_VERSION") {
+            self.tls_min_version = Some(version.parse()?);
+        }
+
+        if let Ok(version) = std::env::var("TLS_MAX_VERSION") {
+            self.tls_max_version = Some(version.parse()?);
+        }
+
+        if let Ok(resumption) = std::env::var("TLS_RESUMPTION") {
+            self.tls_resumption = Self::parse_tls_resumption(&resumption)?;
+        }
+
+        if let Ok(fresh) = std::env::var("DOH_FRESH_CONNECTIONS") {
+            self.doh_fresh_connections = fresh.parse()
+                .map_err(|e| AppError::config(format!("Invalid DOH_FRESH_CONNECTIONS value '{}': {}", fresh, e)))?;
+        }
+
+        if let Ok(bootstrap) = std::env::var("DOH_BOOTSTRAP") {
+            self.doh_bootstrap = DohBootstrap::parse_list(&bootstrap)?;
+        }
+
+        if let Ok(sandbox_dns) = std::env::var("SANDBOX_DNS") {
+            self.sandbox_dns = sandbox_dns.parse()
+                .map_err(|e| AppError::config(format!("Invalid SANDBOX_DNS value '{}': {}", sandbox_dns, e)))?;
+        }
+
+        if let Ok(flush_dns_cache) = std::env::var("FLUSH_DNS_CACHE") {
+            self.flush_dns_cache = flush_dns_cache.parse()
+                .map_err(|e| AppError::config(format!("Invalid FLUSH_DNS_CACHE value '{}': {}", flush_dns_cache, e)))?;
+        }
+
+        if let Ok(single_thread) = std::env::var("SINGLE_THREAD") {
+            self.single_thread = single_thread.parse()
+                .map_err(|e| AppError::config(format!("Invalid SINGLE_THREAD value '{}': {}", single_thread, e)))?;
+        }
+
+        if let Ok(ntp_server) = std::env::var("NTP_SERVER") {
+            self.ntp_server = Some(ntp_server.trim().to_string()).filter(|server| !server.is_empty());
+        }
+
+        // Follow the system locale unless the display locale is set explicitly
+        self.locale = match std::env::var("DISPLAY_LOCALE") {
+            Ok(locale) => locale.parse()?,
+            Err(_) => Locale::from_env(),
+        };
+
+        if let Ok(dns_labels) = std::env::var("DNS_LABELS") {
+            self.dns_labels = DisplayNames::parse_labels(&dns_labels)?;
+        }
+
+        if let Ok(resolver_options) = std::env::var("DNS_RESOLVER_OPTIONS") {
+            self.resolver_options = ResolverOptions::parse_list(&resolver_options)?;
+        }
+
+        if let Ok(shard) = std::env::var("SHARD") {
+            self.shard = Some(shard).filter(|shard| !shard.trim().is_empty()).map(|shard| shard.parse()).transpose()?;
+        }
+
+        if let Ok(bind) = std::env::var("BIND") {
+            self.bind = Some(bind).filter(|bind| !bind.trim().is_empty()).map(|bind| bind.parse()).transpose()?;
+        }
+
+        if let Ok(pool_options) = std::env::var("POOL_OPTIONS") {
+            self.pool_options = Some(pool_options)
+                .filter(|options| !options.trim().is_empty())
+                .map(|options| options.parse())
+                .transpose()?;
+        }
+
+        if let Ok(upload) = std::env::var("UPLOAD") {
+            self.upload = Some(upload).filter(|upload| !upload.trim().is_empty()).map(|upload| upload.parse()).transpose()?;
+        }
+
+        if let Ok(early_hints) = std::env::var("EARLY_HINTS") {
+            self.early_hints = early_hints.parse()
+                .map_err(|e| AppError::config(format!("Invalid EARLY_HINTS value '{}': {}", early_hints, e)))?;
+        }
+
+        if let Ok(threshold) = std::env::var("CAPTURE_ON_ANOMALY") {
+            self.capture_on_anomaly = Some(threshold)
+                .filter(|threshold| !threshold.trim().is_empty())
+                .map(|threshold| Self::parse_capture_threshold(&threshold))
+                .transpose()?;
+        }
+
+        if let Ok(trace_requests) = std::env::var("TRACE_REQUESTS") {
+            self.trace_requests = trace_requests.parse()
+                .map_err(|e| AppError::config(format!("Invalid TRACE_REQUESTS value '{}': {}", trace_requests, e)))?;
+        }
+        
         Ok(())
     }
 }
@@ -225,8 +1113,16 @@ fn default_test_count() -> u32 {
     crate::defaults::DEFAULT_TEST_COUNT
 }
 
-fn default_timeout_secs() -> u64 {
-    crate::defaults::DEFAULT_TIMEOUT.as_secs()
+fn default_max_redirects() -> u32 {
+    crate::defaults::DEFAULT_MAX_REDIRECTS
+}
+
+fn default_alpn_fallback() -> bool {
+    true
+}
+
+fn default_timeout() -> Duration {
+    crate::defaults::DEFAULT_TIMEOUT
 }
 
 fn default_enable_color() -> bool {
@@ -234,6 +1130,7 @@ fn default_enable_color() -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     
@@ -243,6 +1140,190 @@ mod tests {
         assert!(config.validate().is_ok());
     }
     
+    #[test]
+    fn test_timeout_bounds_and_serde() {
+        let mut config = Config::default();
+        config.timeout = Duration::from_millis(250);
+        assert!(config.validate().is_ok());
+        config.timeout = Duration::ZERO;
+        assert!(config.validate().is_err());
+        config.timeout = Duration::from_secs(301);
+        assert!(config.validate().is_err());
+
+        let config: Config = serde_json::from_str(r#"{"timeout": "1m30s"}"#).unwrap();
+        assert_eq!(config.timeout, Duration::from_secs(90));
+        let config: Config = serde_json::from_str(r#"{"timeout": 15}"#).unwrap();
+        assert_eq!(config.timeout, Duration::from_secs(15));
+        assert!(serde_json::to_string(&config).unwrap().contains(r#""timeout":"15s""#));
+    }
+
+    #[test]
+    fn test_max_runtime() {
+        let mut config = Config::default();
+        assert!(config.max_runtime.is_none());
+        config.max_runtime = Some(Duration::ZERO);
+        assert!(config.validate().is_err());
+
+        let config: Config = serde_json::from_str(r#"{"max_runtime": "10m"}"#).unwrap();
+        assert_eq!(config.max_runtime, Some(Duration::from_secs(600)));
+        assert!(config.validate().is_ok());
+        let config: Config = serde_json::from_str(r#"{"max_runtime": null}"#).unwrap();
+        assert!(config.max_runtime.is_none());
+    }
+
+    #[test]
+    fn test_auto_count_precision() {
+        assert_eq!(Config::parse_precision("5").unwrap(), 5.0);
+        assert_eq!(Config::parse_precision(" ±2.5% ").unwrap(), 2.5);
+        assert!(Config::parse_precision("0%").is_err());
+        assert!(Config::parse_precision("75").is_err());
+        assert!(Config::parse_precision("five").is_err());
+
+        assert_eq!(Config::parse_auto_count("true").unwrap(), Some(crate::defaults::DEFAULT_AUTO_COUNT_PRECISION));
+        assert_eq!(Config::parse_auto_count("2%").unwrap(), Some(2.0));
+        assert_eq!(Config::parse_auto_count("false").unwrap(), None);
+        assert!(Config::parse_auto_count("sometimes").unwrap_err().to_string().contains("Invalid AUTO_COUNT value"));
+
+        let mut config = Config::default();
+        config.auto_count = Some(0.0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_loss_probe_packets() {
+        assert_eq!(Config::parse_loss_probe("true").unwrap(), Some(crate::defaults::DEFAULT_LOSS_PROBE_PACKETS));
+        assert_eq!(Config::parse_loss_probe(" 200 ").unwrap(), Some(200));
+        assert_eq!(Config::parse_loss_probe("0").unwrap(), None);
+        assert_eq!(Config::parse_loss_probe("false").unwrap(), None);
+        assert!(Config::parse_loss_probe("5000").unwrap_err().to_string().contains("between 1 and 1000"));
+        assert!(Config::parse_loss_probe("lots").unwrap_err().to_string().contains("Invalid LOSS_PROBE value"));
+    }
+
+    #[test]
+    fn test_ping_count() {
+        assert_eq!(Config::parse_ping("true").unwrap(), Some(crate::defaults::DEFAULT_PING_COUNT));
+        assert_eq!(Config::parse_ping(" 20 ").unwrap(), Some(20));
+        assert_eq!(Config::parse_ping("false").unwrap(), None);
+        assert!(Config::parse_ping("500").unwrap_err().to_string().contains("between 1 and 100"));
+        assert!(Config { ping: Some(0), ..Config::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_dns_labels() {
+        let mut config = Config {
+            dns_servers: vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()],
+            doh_providers: vec!["https://dns.google/dns-query".to_string()],
+            ..Default::default()
+        };
+        config.dns_chains = vec!["8.8.8.8 > system".to_string()];
+        config.dns_labels = DisplayNames::parse_labels(
+            "8.8.8.8=Google,8.8.8.8+1.1.1.1=Both,https://dns.google/dns-query=Google DoH,system=ISP,8.8.8.8>system=Fallback",
+        ).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.display_names().resolve(&DnsConfig::System), "ISP");
+
+        config.dns_labels.insert("9.9.9.9".to_string(), "Quad9".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("9.9.9.9"));
+        config.dns_labels.remove("9.9.9.9");
+        config.dns_labels.insert("1.1.1.1".to_string(), "Google".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("used for both"));
+    }
+
+    #[test]
+    fn test_resolver_options() {
+        let mut config = Config {
+            dns_servers: vec!["8.8.8.8".to_string()],
+            dns_chains: vec!["1.1.1.1 > system".to_string()],
+            ..Default::default()
+        };
+        config.resolver_options = ResolverOptions::parse_list("system=timeout:2,8.8.8.8=cache:0,1.1.1.1=attempts:0").unwrap();
+        assert!(config.validate().is_ok());
+        config.resolver_options.insert("9.9.9.9".to_string(), "rotate".parse().unwrap());
+        assert!(config.validate().unwrap_err().to_string().contains("9.9.9.9"));
+    }
+
+    #[test]
+    fn test_search_domains_and_ndots() {
+        assert_eq!(Config::parse_search_domains("corp.example.com, Lab.Example.com.").unwrap(), ["corp.example.com", "lab.example.com"]);
+        assert_eq!(Config::parse_search_domains("corp.example.com example.com").unwrap().len(), 2);
+        assert!(Config::parse_search_domains("").unwrap().is_empty());
+        assert!(Config::parse_search_domains("https://corp.example.com").is_err());
+
+        let mut config = Config { ndots: Some(15), ..Default::default() };
+        assert!(config.validate().is_ok());
+        config.ndots = Some(16);
+        assert!(config.validate().unwrap_err().to_string().contains("ndots"));
+    }
+
+    #[test]
+    fn test_expand_search_paths() {
+        let mut config = Config {
+            target_urls: vec![
+                "http://intranet:8080/health".to_string(),
+                "https://example.com".to_string(),
+                "http://localhost".to_string(),
+                "http://10.0.0.1".to_string(),
+            ],
+            url_tags: BTreeMap::from([("http://intranet:8080/health".to_string(), vec!["internal".to_string()])]),
+            ..Default::default()
+        };
+        config.expand_search_paths(&["corp.example.com".to_string(), "example.com.".to_string()], 1);
+        assert_eq!(config.target_urls, [
+            "http://intranet:8080/health",
+            "http://intranet.corp.example.com:8080/health",
+            "http://intranet.example.com:8080/health",
+            "https://example.com",
+            "http://localhost",
+            "http://10.0.0.1",
+        ]);
+        assert_eq!(config.url_tags["http://intranet.example.com:8080/health"], ["internal"]);
+
+        // Under a higher ndots, names with a dot are short too
+        let mut config = Config { target_urls: vec!["https://wiki.eng".to_string()], ..Default::default() };
+        config.expand_search_paths(&["corp.example.com".to_string()], 2);
+        assert_eq!(config.target_urls, ["https://wiki.eng", "https://wiki.eng.corp.example.com"]);
+    }
+
+    #[test]
+    fn test_ntp_server() {
+        let mut config = Config::default();
+        assert!(config.ntp_server.is_none());
+        config.ntp_server = Some("pool.ntp.org:123".to_string());
+        assert!(config.validate().is_ok());
+        config.ntp_server = Some("pool ntp org".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_apply_shard() {
+        let urls: Vec<String> = (1..=5).map(|i| format!("https://{}.example", i)).collect();
+        let mut config = Config { target_urls: urls.clone(), shard: Some("2/2".parse().unwrap()), ..Default::default() };
+        config.apply_shard().unwrap();
+        assert_eq!(config.target_urls, ["https://2.example", "https://4.example"]);
+
+        let mut config = Config { target_urls: urls[..2].to_vec(), shard: Some("3/3".parse().unwrap()), ..Default::default() };
+        assert!(config.apply_shard().unwrap_err().to_string().contains("2 URLs cannot be split into 3 shards"));
+    }
+
+    #[test]
+    fn test_normalize_target_urls() {
+        let mut config = Config {
+            target_urls: vec![" example.com".to_string(), "htps://api.example".to_string(), "https://example.com".to_string()],
+            url_tags: BTreeMap::from([(" example.com".to_string(), vec!["web".to_string()])]),
+            ..Default::default()
+        };
+        let issues = config.normalize_target_urls().unwrap();
+        assert_eq!(config.target_urls, ["https://example.com"]);
+        assert_eq!(config.url_tags["https://example.com"], ["web"]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("https://api.example"));
+
+        let mut config = Config { target_urls: vec!["htps://api.example".to_string(), "".to_string()], ..Default::default() };
+        let error = config.normalize_target_urls().unwrap_err().to_string();
+        assert!(error.contains("'htps://api.example': unsupported scheme 'htps'"), "{}", error);
+        assert!(error.contains("URL is empty"));
+    }
+
     #[test]
     fn test_empty_target_url_invalid() {
         let mut config = Config::default();
@@ -255,6 +1336,9 @@ mod tests {
         let mut config = Config::default();
         config.target_urls = vec!["not-a-url".to_string()];
         assert!(config.validate().is_err());
+
+        config.target_urls = vec!["example.com".to_string()];
+        assert!(config.validate().unwrap_err().to_string().contains("did you mean 'https://example.com'?"));
     }
     
     #[test]
@@ -283,12 +1367,55 @@ mod tests {
         let mut config = Config::default();
         config.dns_servers = vec!["8.8.8.8".to_string()];
         config.doh_providers = vec!["https://cloudflare-dns.com/dns-query".to_string()];
+        config.dns_chains = vec!["223.5.5.5>8.8.8.8".to_string()];
         
         let dns_configs = config.create_dns_configs().unwrap();
-        assert_eq!(dns_configs.len(), 3); // System + 1 custom + 1 DoH
-        
+        assert!(matches!(dns_configs.last(), Some(DnsConfig::Chain { resolvers }) if resolvers.len() == 2));
         assert_eq!(dns_configs[0], DnsConfig::System);
         assert!(matches!(dns_configs[1], DnsConfig::Custom { .. }));
-        assert!(matches!(dns_configs[2], DnsConfig::DoH { .. }));
+
+        if cfg!(feature = "doh") {
+            assert_eq!(dns_configs.len(), 4); // System + 1 custom + 1 DoH + 1 chain
+            assert!(matches!(dns_configs[2], DnsConfig::DoH { .. }));
+        } else {
+            assert_eq!(dns_configs.len(), 3);
+        }
+
+        config.dns_chains = vec!["8.8.8.8".to_string()];
+        assert!(config.validate().is_err());
+
+        // Targets on the LAN add the mDNS configuration
+        config.dns_chains.clear();
+        config.target_urls.push("http://nas.local:5000".to_string());
+        assert_eq!(config.create_dns_configs().unwrap().last(), Some(&DnsConfig::Mdns));
+    }
+    
+    #[test]
+    fn test_scoring_weights_parse() {
+        let weights: ScoringWeights = "speed=0.5 reliability=0.3 consistency=0.2".parse().unwrap();
+        assert_eq!(weights.speed, 0.5);
+        assert_eq!(weights.reliability, 0.3);
+        assert_eq!(weights.consistency, 0.2);
+        assert_eq!(weights.p95, 0.0);
+        assert_eq!(weights.jitter, 0.0);
+        
+        let weights: ScoringWeights = "speed=1,p95=2,jitter=1".parse().unwrap();
+        assert_eq!(weights.p95, 2.0);
+        assert_eq!(weights.total(), 4.0);
+        assert_eq!(weights.to_string(), "speed=1,p95=2,jitter=1");
+        assert_eq!("speed=1,loss=1".parse::<ScoringWeights>().unwrap().loss, 1.0);
+        
+        assert!("".parse::<ScoringWeights>().is_err());
+        assert!("speed".parse::<ScoringWeights>().is_err());
+        assert!("latency=0.5".parse::<ScoringWeights>().is_err());
+        assert!("speed=-1,reliability=1".parse::<ScoringWeights>().is_err());
+        assert!("speed=0,reliability=0".parse::<ScoringWeights>().is_err());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_invalid_scoring_weights_rejected() {
+        let mut config = Config::default();
+        config.scoring.speed = f64::NAN;
+        assert!(config.validate().is_err());
+    }
+}
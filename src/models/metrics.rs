@@ -1,9 +1,529 @@
 //! Timing metrics and test result data models
 
+use crate::models::name::Name;
+use crate::timing;
 use crate::types::{DnsConfig, TestStatus, PerformanceLevel};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::time::{Duration, Instant};
 use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// Furthest phase a request reached before it gave up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestPhase {
+    /// Still resolving, connecting or negotiating TLS on a fresh connection
+    Connecting,
+    /// Request sent over an established connection, waiting for response headers
+    AwaitingResponse,
+}
+
+impl fmt::Display for RequestPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestPhase::Connecting => write!(f, "connecting"),
+            RequestPhase::AwaitingResponse => write!(f, "awaiting response"),
+        }
+    }
+}
+
+/// Size and encoding of a response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseBody {
+    /// Bytes received, as sent on the wire
+    pub bytes: u64,
+    /// `Content-Encoding` of the body, if it was not sent as is
+    pub encoding: Option<String>,
+    /// Size after decoding, when the encoding could be decoded
+    pub decoded_bytes: Option<u64>,
+}
+
+impl ResponseBody {
+    /// Whether the body was sent with a content encoding
+    pub fn is_compressed(&self) -> bool {
+        self.encoding.is_some()
+    }
+}
+
+/// Kernel TCP statistics of the connection a request used, from `TCP_INFO`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SocketStats {
+    /// Smoothed round-trip time the kernel measured (milliseconds)
+    pub rtt_ms: f64,
+    /// Round-trip time variation (milliseconds)
+    pub rtt_var_ms: f64,
+    /// Congestion window (segments)
+    pub cwnd: u32,
+    /// Segments retransmitted while this request was on the connection
+    pub retransmits: u32,
+    /// Segments retransmitted over the connection's lifetime so far
+    pub total_retransmits: u32,
+}
+
+impl SocketStats {
+    /// Attribute to one request only the retransmissions since `earlier`, a
+    /// reading of the same connection after the previous request
+    pub fn since(mut self, earlier: &SocketStats) -> Self {
+        self.retransmits = self.total_retransmits.saturating_sub(earlier.total_retransmits);
+        self
+    }
+}
+
+/// How a request body went up, with `--upload`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UploadTiming {
+    /// Size of the body
+    pub bytes: u64,
+    /// Time from sending the request until the last byte of the body was
+    /// handed to the connection
+    pub send: Duration,
+    /// Time from the last byte of the body until the response headers arrived
+    pub ack: Duration,
+}
+
+impl UploadTiming {
+    /// Rate the body went out at, in megabits per second
+    pub fn throughput_mbps(&self) -> Option<f64> {
+        let seconds = self.send.as_secs_f64();
+        (seconds > 0.0).then(|| self.bytes as f64 * 8.0 / seconds / 1_000_000.0)
+    }
+}
+
+/// Informational (1xx) responses that came ahead of the final response,
+/// with `--early-hints`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InformationalTiming {
+    /// Status codes of the 1xx responses in order, e.g. `[103]`; empty when
+    /// the server sent none
+    pub statuses: Vec<u16>,
+    /// Time from sending the request until the first 1xx response arrived
+    pub first: Option<Duration>,
+    /// Time from sending the request until the final response head arrived
+    pub response: Duration,
+}
+
+impl InformationalTiming {
+    /// Whether a `103 Early Hints` response came
+    pub fn early_hints(&self) -> bool {
+        self.statuses.contains(&103)
+    }
+
+    /// How far the first 1xx response came ahead of the final one
+    pub fn lead(&self) -> Option<Duration> {
+        self.first.map(|first| self.response.saturating_sub(first))
+    }
+}
+
+/// A phase the server reported in a `Server-Timing` response header, such
+/// as `db;dur=53` or `cache;desc="Cache Read";dur=23.2`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerTiming {
+    /// Metric name, e.g. `db`, `app` or `cache`
+    pub name: String,
+    /// Time the server spent on it, in milliseconds, when it said
+    pub duration_ms: Option<f64>,
+    /// Human-readable description, when the server gave one
+    pub description: Option<String>,
+}
+
+impl ServerTiming {
+    /// Read the metrics of a `Server-Timing` header value
+    ///
+    /// Metrics are separated by commas and parameters by semicolons; unknown
+    /// parameters are ignored and a malformed `dur` leaves the duration
+    /// unknown, so that one odd entry does not lose the others.
+    pub fn parse(value: &str) -> Vec<Self> {
+        split_unquoted(value, ',')
+            .into_iter()
+            .filter_map(|metric| {
+                let mut parts = split_unquoted(metric, ';').into_iter();
+                let name = parts.next()?.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                let mut timing = Self { name: name.to_string(), duration_ms: None, description: None };
+                for parameter in parts {
+                    let Some((key, value)) = parameter.split_once('=') else { continue };
+                    let value = value.trim();
+                    let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+                    match key.trim().to_ascii_lowercase().as_str() {
+                        "dur" if timing.duration_ms.is_none() => {
+                            timing.duration_ms = value.parse().ok().filter(|ms: &f64| ms.is_finite() && *ms >= 0.0);
+                        }
+                        "desc" if timing.description.is_none() => timing.description = Some(value.replace("\\\"", "\"")),
+                        _ => {}
+                    }
+                }
+                Some(timing)
+            })
+            .collect()
+    }
+
+    /// The description when there is one, otherwise the name
+    pub fn label(&self) -> &str {
+        self.description.as_deref().filter(|description| !description.is_empty()).unwrap_or(&self.name)
+    }
+}
+
+/// Split `value` at `separator`s that are not inside double quotes
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Outcome of a burst of UDP packets sent to measure packet loss
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LossReport {
+    /// Configuration the probed route or resolver belongs to
+    pub config_name: String,
+    /// What was probed, e.g. `https://example.com (93.184.216.34)` or `resolver 8.8.8.8`
+    pub target: String,
+    /// Packets sent
+    pub sent: u32,
+    /// Distinct packets answered
+    pub received: u32,
+    /// Answers that arrived after an answer to a later packet
+    pub reordered: u32,
+    /// Answers received more than once
+    pub duplicates: u32,
+    /// Average round-trip time of the answered packets (milliseconds)
+    pub avg_rtt_ms: Option<f64>,
+}
+
+impl LossReport {
+    /// Share of packets that went unanswered (0.0-100.0)
+    ///
+    /// `None` when nothing was answered: the target most likely does not
+    /// respond to the probe at all, which says nothing about loss.
+    pub fn loss_percentage(&self) -> Option<f64> {
+        (self.received > 0 && self.sent > 0)
+            .then(|| f64::from(self.sent - self.received.min(self.sent)) / f64::from(self.sent) * 100.0)
+    }
+
+    /// Loss over several bursts, counting only targets that answered
+    pub fn combined_loss<'a>(reports: impl IntoIterator<Item = &'a LossReport>) -> Option<f64> {
+        let (sent, received) = reports.into_iter()
+            .filter(|report| report.received > 0)
+            .fold((0u64, 0u64), |(sent, received), report| {
+                (sent + u64::from(report.sent), received + u64::from(report.received.min(report.sent)))
+            });
+        (sent > 0).then(|| (sent - received) as f64 / sent as f64 * 100.0)
+    }
+}
+
+impl fmt::Display for LossReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(loss) = self.loss_percentage() else {
+            return write!(f, "no answer to {} packets (the target does not respond to this probe)", self.sent);
+        };
+        write!(f, "{}/{} lost ({:.1}%)", self.sent - self.received.min(self.sent), self.sent, loss)?;
+        if self.reordered > 0 {
+            write!(f, ", {} reordered", self.reordered)?;
+        }
+        if self.duplicates > 0 {
+            write!(f, ", {} duplicated", self.duplicates)?;
+        }
+        if let Some(rtt) = self.avg_rtt_ms {
+            write!(f, ", RTT {:.1}ms", rtt)?;
+        }
+        Ok(())
+    }
+}
+
+/// ICMP echo round trips to one address a target resolved to, with `--ping`
+///
+/// The round trip covers the network path alone, so set against the HTTP
+/// latency it tells a slow path from a slow server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PingStats {
+    /// Address the echo requests were sent to
+    pub address: IpAddr,
+    /// Echo requests sent
+    pub sent: u32,
+    /// Distinct echo replies received
+    pub received: u32,
+    /// Fastest round trip (milliseconds), when anything was answered
+    pub min_rtt_ms: Option<f64>,
+    /// Average round trip (milliseconds)
+    pub avg_rtt_ms: Option<f64>,
+    /// Slowest round trip (milliseconds)
+    pub max_rtt_ms: Option<f64>,
+    /// Population standard deviation of the round trips (milliseconds), as `ping` reports it
+    pub std_dev_ms: Option<f64>,
+    /// Why no echo request could be sent, e.g. missing permission for ICMP sockets
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl PingStats {
+    /// Statistics of the round trips of the replies to `sent` echo requests
+    pub fn from_rtts(address: IpAddr, sent: u32, rtts: &[Duration]) -> Self {
+        let rtts_ms: Vec<f64> = rtts.iter().map(|rtt| rtt.as_secs_f64() * 1000.0).collect();
+        let avg = (!rtts_ms.is_empty()).then(|| rtts_ms.iter().sum::<f64>() / rtts_ms.len() as f64);
+        Self {
+            address,
+            sent,
+            received: rtts_ms.len() as u32,
+            min_rtt_ms: rtts_ms.iter().copied().reduce(f64::min),
+            avg_rtt_ms: avg,
+            max_rtt_ms: rtts_ms.iter().copied().reduce(f64::max),
+            std_dev_ms: avg.map(|avg| (rtts_ms.iter().map(|rtt| (rtt - avg).powi(2)).sum::<f64>() / rtts_ms.len() as f64).sqrt()),
+            error: None,
+        }
+    }
+
+    /// An address that could not be pinged at all
+    pub fn failed(address: IpAddr, error: String) -> Self {
+        Self { error: Some(error), ..Self::from_rtts(address, 0, &[]) }
+    }
+
+    /// Share of echo requests that went unanswered (0.0-100.0), when any were sent
+    pub fn loss_percentage(&self) -> Option<f64> {
+        (self.sent > 0).then(|| f64::from(self.sent - self.received.min(self.sent)) / f64::from(self.sent) * 100.0)
+    }
+}
+
+impl fmt::Display for PingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref error) = self.error {
+            return write!(f, "{} not pinged: {}", self.address, error);
+        }
+        let (Some(min), Some(avg), Some(max), Some(std_dev)) = (self.min_rtt_ms, self.avg_rtt_ms, self.max_rtt_ms, self.std_dev_ms) else {
+            return write!(f, "{} no reply to {} echo requests (ICMP may be filtered)", self.address, self.sent);
+        };
+        write!(f, "{} RTT {:.1}ms avg ({:.1}-{:.1}ms, mdev {:.1}ms), {}/{} lost",
+            self.address, avg, min, max, std_dev, self.sent - self.received.min(self.sent), self.sent)
+    }
+}
+
+/// How the latency to a target compares with public measurements of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineComparison {
+    /// Host name the public measurements were made to
+    pub target: String,
+    /// Where the measurements came from, e.g. `RIPE Atlas #1001`
+    pub source: String,
+    /// Region the measuring probes were in; `None` for probes anywhere
+    pub region: Option<String>,
+    /// Probes that contributed a measurement
+    pub probes: usize,
+    /// Median round-trip time measured by the probes (milliseconds)
+    pub public_median_ms: f64,
+    /// Median TCP connect time of the run to the target (milliseconds), also a
+    /// single round trip; `None` when no fresh connection succeeded
+    pub local_median_ms: Option<f64>,
+}
+
+impl BaselineComparison {
+    /// Local round trips at least this many times the public median are abnormal
+    pub const ABNORMAL_RATIO: f64 = 2.0;
+
+    /// Local round-trip time as a multiple of the public median
+    pub fn ratio(&self) -> Option<f64> {
+        self.local_median_ms.filter(|_| self.public_median_ms > 0.0).map(|local| local / self.public_median_ms)
+    }
+
+    /// Whether the run's round trips are well above what others measure
+    pub fn is_abnormal(&self) -> bool {
+        self.ratio().is_some_and(|ratio| ratio >= Self::ABNORMAL_RATIO)
+    }
+}
+
+impl fmt::Display for BaselineComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.local_median_ms {
+            Some(local) => write!(f, "connect {:.1}ms vs ", local)?,
+            None => write!(f, "no connections to compare vs ")?,
+        }
+        write!(f, "median {:.1}ms from {} probes", self.public_median_ms, self.probes)?;
+        if let Some(ref region) = self.region {
+            write!(f, " in {}", region)?;
+        }
+        write!(f, " ({})", self.source)?;
+        if let Some(ratio) = self.ratio() {
+            write!(f, ", {:.1}x", ratio)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a CDN served a response from its cache or fetched it from the origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+    /// Served from the cache, including stale copies served while refreshing
+    Hit,
+    /// Fetched from the origin: not cached, expired, revalidated or bypassed
+    Miss,
+}
+
+impl CacheStatus {
+    /// Classify a response from its `cf-cache-status`, `x-cache` and `age` headers
+    ///
+    /// The status headers take precedence; a non-zero `age` only marks a hit
+    /// when neither is present. Returns `None` when the headers do not tell,
+    /// typically because no CDN is in front of the server.
+    pub fn from_headers(cf_cache_status: Option<&str>, x_cache: Option<&str>, age: Option<&str>) -> Option<Self> {
+        if let Some(status) = cf_cache_status.and_then(Self::from_token) {
+            return Some(status);
+        }
+        // Chained caches append their status, so the last one is the edge the client reached
+        if let Some(status) = x_cache.and_then(|value| value.rsplit(',').next()).and_then(Self::from_token) {
+            return Some(status);
+        }
+        age.and_then(|age| age.trim().parse::<u64>().ok())
+            .filter(|&age| age > 0)
+            .map(|_| Self::Hit)
+    }
+
+    /// Classify a status such as `HIT`, `TCP_MISS` or `Hit from cloudfront`
+    fn from_token(value: &str) -> Option<Self> {
+        let token = value.split_whitespace().next()?.to_ascii_uppercase();
+        if token.ends_with("HIT") || matches!(token.as_str(), "STALE" | "UPDATING") {
+            Some(Self::Hit)
+        } else if token.ends_with("MISS") || matches!(token.as_str(), "EXPIRED" | "BYPASS" | "DYNAMIC" | "REVALIDATED" | "PASS") {
+            Some(Self::Miss)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheStatus::Hit => write!(f, "HIT"),
+            CacheStatus::Miss => write!(f, "MISS"),
+        }
+    }
+}
+
+/// A response that turned a request away for coming too often:
+/// `429 Too Many Requests`, or `503 Service Unavailable` with a `Retry-After`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Throttling {
+    /// Status code of the response, 429 or 503
+    pub status: u16,
+    /// How long the server asked the client to wait, when it said
+    pub retry_after: Option<Duration>,
+}
+
+impl Throttling {
+    /// Recognise throttling from a response's status code and `Retry-After`
+    /// header, which is either a number of seconds or an HTTP date
+    ///
+    /// A 503 without `Retry-After` is an outage rather than throttling, so it
+    /// is left to count as an ordinary failure.
+    pub fn from_response(status: u16, retry_after: Option<&str>, now: DateTime<Utc>) -> Option<Self> {
+        let retry_after = retry_after.and_then(|value| {
+            let value = value.trim();
+            match value.parse::<u64>() {
+                Ok(seconds) => Some(Duration::from_secs(seconds)),
+                Err(_) => DateTime::parse_from_rfc2822(value).ok()
+                    .map(|date| (date.with_timezone(&Utc) - now).to_std().unwrap_or(Duration::ZERO)),
+            }
+        });
+        match status {
+            429 => Some(Self { status, retry_after }),
+            503 if retry_after.is_some() => Some(Self { status, retry_after }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Throttling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP {}", self.status)?;
+        if let Some(retry_after) = self.retry_after {
+            write!(f, ", retry after {}s", retry_after.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+/// A redirect a request followed on its way to the final response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedirectHop {
+    /// URL that answered with the redirect
+    pub url: String,
+    /// Redirect status code, such as 301 or 302
+    pub status: u16,
+    /// Time from sending the request to this URL until the redirect arrived
+    pub duration: Duration,
+}
+
+impl fmt::Display for RedirectHop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {:.1}ms", self.status, self.url, self.duration.as_secs_f64() * 1000.0)
+    }
+}
+
+/// A route's round trips set against the physical minimum for its distance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhysicalBound {
+    /// Host name of the target
+    pub target: String,
+    /// Address the requests went to
+    pub ip: IpAddr,
+    /// Where the address is located, e.g. `Frankfurt am Main, DE`
+    pub location: String,
+    /// Autonomous system announcing the address, e.g. `AS15169 Google LLC`
+    #[serde(default)]
+    pub asn: Option<String>,
+    /// Great-circle distance between the client and the address
+    pub distance_km: f64,
+    /// Shortest possible round trip over that distance (milliseconds)
+    pub min_rtt_ms: f64,
+    /// Median TCP connect time of fresh connections to the address
+    /// (milliseconds); `None` when none succeeded
+    pub connect_ms: Option<f64>,
+}
+
+impl PhysicalBound {
+    /// Round trips at least this many times the minimum suggest indirect routing
+    pub const INDIRECT_RATIO: f64 = 3.0;
+    /// Excess over the minimum that indirect routing also needs, since access
+    /// networks alone add a few milliseconds to nearby targets
+    pub const INDIRECT_EXCESS_MS: f64 = 20.0;
+
+    /// Connect time as a multiple of the physical minimum, for addresses far
+    /// enough away (1ms or more) for the multiple to mean anything
+    pub fn ratio(&self) -> Option<f64> {
+        self.connect_ms.filter(|_| self.min_rtt_ms >= 1.0).map(|connect| connect / self.min_rtt_ms)
+    }
+
+    /// Whether the packets obviously take a detour
+    pub fn is_indirect(&self) -> bool {
+        let excess = self.connect_ms.map_or(0.0, |connect| connect - self.min_rtt_ms);
+        self.ratio().is_some_and(|ratio| ratio >= Self::INDIRECT_RATIO) && excess >= Self::INDIRECT_EXCESS_MS
+    }
+}
+
+impl fmt::Display for PhysicalBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.connect_ms, self.ratio()) {
+            (Some(connect), Some(ratio)) => write!(f, "connect {:.1}ms, {:.1}x above the physical minimum of {:.1}ms", connect, ratio, self.min_rtt_ms)?,
+            (Some(connect), None) => write!(f, "connect {:.1}ms", connect)?,
+            (None, _) => write!(f, "physical minimum {:.1}ms", self.min_rtt_ms)?,
+        }
+        write!(f, " over {:.0}km ({})", self.distance_km, self.location)
+    }
+}
 
 /// Detailed timing metrics for a single HTTP request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,11 +549,78 @@ pub struct TimingMetrics {
     /// Test execution status
     pub status: TestStatus,
     
-    /// Timestamp when the test was executed
+    /// When the request finished
     pub timestamp: DateTime<Utc>,
+
+    /// When the request was sent; missing from results exported before it was
+    /// recorded, see [`TimingMetrics::start_time`]
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
     
     /// Error message if the test failed
     pub error_message: Option<String>,
+
+    /// Remote address the request was sent to, if known
+    #[serde(default)]
+    pub resolved_ip: Option<IpAddr>,
+
+    /// Whether this was the first request made over a fresh connection, so it
+    /// includes connection setup and handshakes that later requests skip
+    #[serde(default)]
+    pub is_first_use: bool,
+
+    /// Phase the request was in when it timed out
+    #[serde(default)]
+    pub phase_reached: Option<RequestPhase>,
+
+    /// Response body received, when the request got that far
+    #[serde(default)]
+    pub body: Option<ResponseBody>,
+
+    /// Cache status reported by a CDN, if the response carried one
+    #[serde(default)]
+    pub cache: Option<CacheStatus>,
+
+    /// Whether the request went out over a connection an earlier request had
+    /// opened, when the client could tell
+    #[serde(default)]
+    pub reused_connection: Option<bool>,
+
+    /// Whether a new TLS connection resumed an earlier session instead of a
+    /// full handshake, with `--tls-resumption`
+    #[serde(default)]
+    pub tls_resumed: Option<bool>,
+
+    /// TCP statistics of the connection after the request, with `--socket-stats`
+    #[serde(default)]
+    pub socket: Option<SocketStats>,
+
+    /// How the request body went up, with `--upload`
+    #[serde(default)]
+    pub upload: Option<UploadTiming>,
+
+    /// Informational responses of a separate HTTP/1.1 request to the same
+    /// address, with `--early-hints`
+    #[serde(default)]
+    pub informational: Option<InformationalTiming>,
+
+    /// Whether the server throttled the request, and for how long
+    #[serde(default)]
+    pub throttling: Option<Throttling>,
+
+    /// Phases the server reported in `Server-Timing` headers, in order
+    #[serde(default)]
+    pub server_timing: Vec<ServerTiming>,
+
+    /// Redirects followed before the final response, in order; their time is
+    /// part of `total_duration`
+    #[serde(default)]
+    pub redirects: Vec<RedirectHop>,
+
+    /// Whether the DNS, TCP, TLS and first-byte durations are fixed shares of
+    /// the total duration rather than measured
+    #[serde(default)]
+    pub estimated_phases: bool,
 }
 
 impl TimingMetrics {
@@ -54,8 +641,23 @@ impl TimingMetrics {
             total_duration,
             http_status,
             status: TestStatus::Success,
-            timestamp: Utc::now(),
+            timestamp: timing::timestamp(),
+            started_at: None,
             error_message: None,
+            resolved_ip: None,
+            is_first_use: false,
+            phase_reached: None,
+            body: None,
+            cache: None,
+            reused_connection: None,
+            tls_resumed: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            throttling: None,
+            server_timing: Vec::new(),
+            redirects: Vec::new(),
+            estimated_phases: false,
         }
     }
     
@@ -69,8 +671,23 @@ impl TimingMetrics {
             total_duration: Duration::ZERO,
             http_status: 0,
             status: TestStatus::Failed,
-            timestamp: Utc::now(),
+            timestamp: timing::timestamp(),
+            started_at: None,
             error_message: Some(error_message),
+            resolved_ip: None,
+            is_first_use: false,
+            phase_reached: None,
+            body: None,
+            cache: None,
+            reused_connection: None,
+            tls_resumed: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            throttling: None,
+            server_timing: Vec::new(),
+            redirects: Vec::new(),
+            estimated_phases: false,
         }
     }
     
@@ -84,8 +701,23 @@ impl TimingMetrics {
             total_duration: timeout_duration,
             http_status: 0,
             status: TestStatus::Timeout,
-            timestamp: Utc::now(),
-            error_message: Some(format!("Request timed out after {}s", timeout_duration.as_secs())),
+            timestamp: timing::timestamp(),
+            started_at: None,
+            error_message: Some(format!("Request timed out after {}", crate::utils::duration::format_duration(timeout_duration))),
+            resolved_ip: None,
+            is_first_use: false,
+            phase_reached: None,
+            body: None,
+            cache: None,
+            reused_connection: None,
+            tls_resumed: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            throttling: None,
+            server_timing: Vec::new(),
+            redirects: Vec::new(),
+            estimated_phases: false,
         }
     }
     
@@ -99,11 +731,133 @@ impl TimingMetrics {
             total_duration: Duration::ZERO,
             http_status: 0,
             status: TestStatus::Skipped,
-            timestamp: Utc::now(),
+            timestamp: timing::timestamp(),
+            started_at: None,
             error_message: Some(reason),
+            resolved_ip: None,
+            is_first_use: false,
+            phase_reached: None,
+            body: None,
+            cache: None,
+            reused_connection: None,
+            tls_resumed: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            throttling: None,
+            server_timing: Vec::new(),
+            redirects: Vec::new(),
+            estimated_phases: false,
         }
     }
     
+    /// Record the remote address the request was sent to
+    pub fn with_resolved_ip(mut self, ip: Option<IpAddr>) -> Self {
+        self.resolved_ip = ip;
+        self
+    }
+
+    /// Record when the request was sent
+    pub fn with_started_at(mut self, started_at: DateTime<Utc>) -> Self {
+        self.started_at = Some(started_at);
+        self
+    }
+
+    /// When the request was sent, or for samples that did not record it, its
+    /// end minus its duration
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.started_at.unwrap_or_else(|| {
+            self.timestamp - chrono::Duration::from_std(self.total_duration).unwrap_or(chrono::Duration::zero())
+        })
+    }
+
+    /// Mark whether this request was the first one over a fresh connection
+    pub fn with_first_use(mut self, is_first_use: bool) -> Self {
+        self.is_first_use = is_first_use;
+        self
+    }
+
+    /// Record the phase a timed-out request was in when it gave up
+    pub fn with_phase_reached(mut self, phase: RequestPhase) -> Self {
+        if let Some(message) = self.error_message.as_mut() {
+            message.push_str(&format!(" while {}", phase));
+        }
+        self.phase_reached = Some(phase);
+        self
+    }
+
+    /// Record the response body received
+    pub fn with_body(mut self, body: ResponseBody) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Record the cache status the response reported
+    pub fn with_cache_status(mut self, cache: Option<CacheStatus>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Record whether the request reused a pooled connection
+    pub fn with_reused_connection(mut self, reused: Option<bool>) -> Self {
+        self.reused_connection = reused;
+        self
+    }
+
+    /// Record whether a new TLS connection resumed an earlier session
+    pub fn with_tls_resumed(mut self, resumed: Option<bool>) -> Self {
+        self.tls_resumed = resumed;
+        self
+    }
+
+    /// Record the TCP statistics of the connection the request used
+    pub fn with_socket_stats(mut self, socket: Option<SocketStats>) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    /// Record how the request body went up
+    pub fn with_upload(mut self, upload: Option<UploadTiming>) -> Self {
+        self.upload = upload;
+        self
+    }
+
+    /// Record the informational responses a request to the same address got
+    pub fn with_informational(mut self, informational: Option<InformationalTiming>) -> Self {
+        self.informational = informational;
+        self
+    }
+
+    /// Record that the server throttled the request
+    pub fn with_throttling(mut self, throttling: Option<Throttling>) -> Self {
+        self.throttling = throttling;
+        self
+    }
+
+    /// Record the phases the server reported
+    pub fn with_server_timing(mut self, server_timing: Vec<ServerTiming>) -> Self {
+        self.server_timing = server_timing;
+        self
+    }
+
+    /// Record the redirects the request followed
+    pub fn with_redirects(mut self, redirects: Vec<RedirectHop>) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    /// Record whether the phase durations are estimated from the total
+    pub fn with_estimated_phases(mut self, estimated: bool) -> Self {
+        self.estimated_phases = estimated;
+        self
+    }
+
+    /// Record how long a failed request ran before giving up
+    pub fn with_total_duration(mut self, total_duration: Duration) -> Self {
+        self.total_duration = total_duration;
+        self
+    }
+
     /// Check if this test was successful
     pub fn is_successful(&self) -> bool {
         matches!(self.status, TestStatus::Success) && self.http_status >= 200 && self.http_status < 400
@@ -134,19 +888,38 @@ impl TimingMetrics {
     pub fn total_ms(&self) -> f64 {
         self.total_duration.as_secs_f64() * 1000.0
     }
+
+    /// Median TCP connect time (milliseconds) of the fresh connections among
+    /// `metrics`, which like a ping take a single round trip
+    ///
+    /// Requests over a reused connection report no connect time, so only the
+    /// first successful request on each connection counts.
+    pub fn median_connect_ms<'a>(metrics: impl IntoIterator<Item = &'a TimingMetrics>) -> Option<f64> {
+        let mut connects: Vec<f64> = metrics.into_iter()
+            .filter(|metrics| metrics.is_successful() && metrics.is_first_use && !metrics.tcp_connection.is_zero())
+            .map(|metrics| metrics.tcp_connection.as_secs_f64() * 1000.0)
+            .collect();
+        connects.sort_by(f64::total_cmp);
+        let middle = connects.len() / 2;
+        match connects.len() {
+            0 => None,
+            len if len.is_multiple_of(2) => Some((connects[middle - 1] + connects[middle]) / 2.0),
+            _ => Some(connects[middle]),
+        }
+    }
 }
 
 /// Results from testing a single DNS configuration against a URL
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestResult {
     /// Human-readable name of the DNS configuration
-    pub config_name: String,
+    pub config_name: Name,
     
     /// DNS configuration that was tested
     pub dns_config: DnsConfig,
     
     /// Target URL that was tested
-    pub url: String,
+    pub url: Name,
     
     /// Individual test results (one per iteration)
     pub individual_results: Vec<TimingMetrics>,
@@ -165,23 +938,55 @@ pub struct TestResult {
     
     /// When the test batch completed
     pub completed_at: Option<DateTime<Utc>>,
+
+    /// Network interface the requests were sent from, with `--all-interfaces`
+    #[serde(default)]
+    pub interface: Option<String>,
+
+    /// ICMP round trips to each address the requests went to, with `--ping`
+    #[serde(default)]
+    pub ping: Vec<PingStats>,
 }
 
 impl TestResult {
     /// Create a new test result
-    pub fn new(config_name: String, dns_config: DnsConfig, url: String) -> Self {
+    pub fn new(config_name: impl Into<Name>, dns_config: DnsConfig, url: impl Into<Name>) -> Self {
         Self {
-            config_name,
+            config_name: config_name.into(),
             dns_config,
-            url,
+            url: url.into(),
             individual_results: Vec::new(),
             statistics: None,
             success_count: 0,
             total_count: 0,
-            started_at: Utc::now(),
+            started_at: timing::timestamp(),
             completed_at: None,
+            interface: None,
+            ping: Vec::new(),
         }
     }
+
+    /// Name of `config_name` measured from `interface`, which sets it apart
+    /// from the same configuration on other interfaces
+    pub fn interface_config_name(config_name: &str, interface: &str) -> String {
+        format!("{} [{}]", config_name, interface)
+    }
+
+    /// Mark the result as measured from `interface`
+    pub fn on_interface(mut self, interface: &str) -> Self {
+        self.config_name = Self::interface_config_name(&self.config_name, interface).into();
+        self.interface = Some(interface.to_string());
+        self
+    }
+
+    /// Name of the DNS configuration without the interface it was measured from
+    pub fn dns_config_name(&self) -> &str {
+        self.interface.as_ref()
+            .and_then(|interface| {
+                self.config_name.strip_suffix(']')?.strip_suffix(interface.as_str())?.strip_suffix(" [")
+            })
+            .unwrap_or(&self.config_name)
+    }
     
     /// Add a timing measurement to this result
     pub fn add_measurement(&mut self, metrics: TimingMetrics) {
@@ -204,9 +1009,17 @@ impl TestResult {
             self.statistics = Some(Statistics::from_measurements(&successful_results));
         }
         
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(timing::timestamp());
     }
     
+    /// Add the measurements of a later batch for the same URL and configuration
+    pub fn merge(&mut self, later: TestResult) {
+        for metrics in later.individual_results {
+            self.add_measurement(metrics);
+        }
+        self.calculate_statistics();
+    }
+
     /// Get success rate as a percentage
     pub fn success_rate(&self) -> f64 {
         if self.total_count == 0 {
@@ -216,11 +1029,50 @@ impl TestResult {
         }
     }
     
+    /// Average ICMP round trip to the target's addresses (milliseconds), over
+    /// every reply, when `--ping` got any
+    pub fn ping_rtt_ms(&self) -> Option<f64> {
+        let (sum, replies) = self.ping.iter()
+            .filter_map(|ping| ping.avg_rtt_ms.map(|avg| (avg * f64::from(ping.received), ping.received)))
+            .fold((0.0, 0u32), |(sum, replies), (total, received)| (sum + total, replies + received));
+        (replies > 0).then(|| sum / f64::from(replies))
+    }
+
+    /// Percentile (0-100) of successful total times in milliseconds, interpolated linearly
+    pub fn percentile_ms(&self, percentile: f64) -> Option<f64> {
+        let mut totals: Vec<f64> = self.individual_results.iter()
+            .filter(|m| m.is_successful())
+            .map(|m| m.total_ms())
+            .collect();
+        if totals.is_empty() {
+            return None;
+        }
+        totals.sort_by(f64::total_cmp);
+        Some(crate::stats::StatisticsEngine::calculate_percentile(&totals, percentile))
+    }
+
     /// Get overall performance level
     pub fn performance_level(&self) -> Option<PerformanceLevel> {
         self.statistics.as_ref().map(|s| s.performance_level())
     }
     
+    /// Number of requests that hit the timeout
+    pub fn timeout_count(&self) -> usize {
+        self.individual_results
+            .iter()
+            .filter(|m| matches!(m.status, TestStatus::Timeout))
+            .count()
+    }
+
+    /// Share of attempted requests that hit the timeout, as a percentage
+    pub fn timeout_rate(&self) -> f64 {
+        if self.total_count == 0 {
+            0.0
+        } else {
+            (self.timeout_count() as f64 / self.total_count as f64) * 100.0
+        }
+    }
+
     /// Check if any tests were skipped
     pub fn has_skipped_tests(&self) -> bool {
         self.individual_results
@@ -438,6 +1290,7 @@ impl TimingBuilder {
             total_duration,
             http_status,
         )
+        .with_started_at(timing::process_clock().timestamp_of(self.start_time))
     }
 }
 
@@ -514,6 +1367,16 @@ mod tests {
         let stats = result.statistics.as_ref().unwrap();
         assert_eq!(stats.total_avg_ms, 110.0);
         assert_eq!(stats.sample_count, 2);
+
+        let mut later = TestResult::new("Test Config".to_string(), DnsConfig::System, "https://example.com".to_string());
+        later.add_measurement(TimingMetrics::failed("HTTP 503".to_string()));
+        result.merge(later);
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.total_count, 3);
+        assert_eq!(result.statistics.as_ref().unwrap().sample_count, 2);
+        // Percentiles only count successful requests
+        assert_eq!(result.percentile_ms(50.0), Some(110.0));
+        assert_eq!(result.percentile_ms(95.0), Some(119.0));
     }
     
     #[test]
@@ -566,4 +1429,96 @@ mod tests {
         assert!(metrics.tcp_connection > Duration::ZERO);
         assert!(metrics.total_duration > Duration::ZERO);
     }
+
+    #[test]
+    fn test_cache_status_from_headers() {
+        let classify = |cf, x_cache, age| CacheStatus::from_headers(cf, x_cache, age);
+
+        assert_eq!(classify(Some("HIT"), None, None), Some(CacheStatus::Hit));
+        assert_eq!(classify(Some("DYNAMIC"), None, Some("120")), Some(CacheStatus::Miss));
+        assert_eq!(classify(None, Some("Hit from cloudfront"), None), Some(CacheStatus::Hit));
+        assert_eq!(classify(None, Some("TCP_REFRESH_MISS"), None), Some(CacheStatus::Miss));
+        // The edge closest to the client is listed last
+        assert_eq!(classify(None, Some("MISS, HIT"), None), Some(CacheStatus::Hit));
+        assert_eq!(classify(None, Some("Error from cloudfront"), Some("30")), Some(CacheStatus::Hit));
+        assert_eq!(classify(None, None, Some("0")), None);
+        assert_eq!(classify(None, None, None), None);
+    }
+
+    #[test]
+    fn test_server_timing_parse() {
+        let timings = ServerTiming::parse(r#"cache;desc="Cache Read";dur=23.2, db;dur=53, app;dur=47.2, miss"#);
+        assert_eq!(timings.len(), 4);
+        assert_eq!(timings[0], ServerTiming { name: "cache".to_string(), duration_ms: Some(23.2), description: Some("Cache Read".to_string()) });
+        assert_eq!(timings[0].label(), "Cache Read");
+        assert_eq!((timings[1].name.as_str(), timings[1].duration_ms), ("db", Some(53.0)));
+        assert_eq!((timings[3].label(), timings[3].duration_ms), ("miss", None));
+
+        // Separators inside quotes, odd parameters and empty entries
+        let timings = ServerTiming::parse(r#"edge; desc="a, b; c" ;DUR=1.5;foo, , origin;dur=fast"#);
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].description.as_deref(), Some("a, b; c"));
+        assert_eq!(timings[0].duration_ms, Some(1.5));
+        assert_eq!((timings[1].name.as_str(), timings[1].duration_ms), ("origin", None));
+        assert!(ServerTiming::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_throttling_from_response() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT").unwrap().with_timezone(&Utc);
+        let throttling = Throttling::from_response(429, Some("120"), now).unwrap();
+        assert_eq!(throttling.retry_after, Some(Duration::from_secs(120)));
+        assert_eq!(throttling.to_string(), "HTTP 429, retry after 120s");
+        assert_eq!(Throttling::from_response(429, None, now), Some(Throttling { status: 429, retry_after: None }));
+
+        let later = Throttling::from_response(503, Some("Wed, 21 Oct 2015 07:29:30 GMT"), now).unwrap();
+        assert_eq!(later.retry_after, Some(Duration::from_secs(90)));
+        // A date already past asks for no wait at all
+        let past = Throttling::from_response(503, Some("Wed, 21 Oct 2015 07:00:00 GMT"), now).unwrap();
+        assert_eq!(past.retry_after, Some(Duration::ZERO));
+
+        // An outage, not throttling
+        assert_eq!(Throttling::from_response(503, None, now), None);
+        assert_eq!(Throttling::from_response(503, Some("soon"), now), None);
+        assert_eq!(Throttling::from_response(500, Some("30"), now), None);
+    }
+
+    #[test]
+    fn test_loss_report() {
+        let report = |sent, received, reordered| LossReport {
+            config_name: "System DNS".to_string(),
+            target: "resolver 192.0.2.1".to_string(),
+            sent,
+            received,
+            reordered,
+            duplicates: 0,
+            avg_rtt_ms: (received > 0).then_some(20.0),
+        };
+
+        assert_eq!(report(50, 45, 2).loss_percentage(), Some(10.0));
+        assert_eq!(report(50, 45, 2).to_string(), "5/50 lost (10.0%), 2 reordered, RTT 20.0ms");
+        assert_eq!(report(50, 0, 0).loss_percentage(), None);
+        assert!(report(50, 0, 0).to_string().starts_with("no answer to 50 packets"));
+        // A silent target does not count as lossy
+        assert_eq!(LossReport::combined_loss(&[report(50, 50, 0), report(50, 40, 0), report(50, 0, 0)]), Some(10.0));
+        assert_eq!(LossReport::combined_loss(&[report(50, 0, 0)]), None);
+    }
+
+    #[test]
+    fn test_ping_stats() {
+        let address: IpAddr = "192.0.2.1".parse().unwrap();
+        let rtts = [Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(30)];
+        let stats = PingStats::from_rtts(address, 4, &rtts);
+        assert_eq!((stats.min_rtt_ms, stats.avg_rtt_ms, stats.max_rtt_ms), (Some(10.0), Some(20.0), Some(30.0)));
+        assert_eq!(stats.loss_percentage(), Some(25.0));
+        assert_eq!(stats.to_string(), "192.0.2.1 RTT 20.0ms avg (10.0-30.0ms, mdev 8.2ms), 1/4 lost");
+        assert!(PingStats::from_rtts(address, 4, &[]).to_string().contains("no reply to 4 echo requests"));
+        assert_eq!(PingStats::failed(address, "denied".to_string()).loss_percentage(), None);
+
+        // The target's round trip weighs each address by its replies
+        let mut result = TestResult::new("System DNS", DnsConfig::System, "https://example.com");
+        assert_eq!(result.ping_rtt_ms(), None);
+        result.ping = vec![stats, PingStats::from_rtts("192.0.2.2".parse().unwrap(), 4, &[Duration::from_millis(60)])];
+        assert_eq!(result.ping_rtt_ms(), Some(30.0));
+    }
 }
\ No newline at end of file
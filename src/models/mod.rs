@@ -2,7 +2,11 @@
 
 pub mod config;
 pub mod metrics;
+pub mod name;
+pub mod reproduction;
 
 // Re-export main model types
-pub use config::Config;
-pub use metrics::{TimingMetrics, TestResult, Statistics};
\ No newline at end of file
+pub use config::{Config, ScoringWeights};
+pub use name::Name;
+pub use reproduction::Reproduction;
+pub use metrics::{BaselineComparison, CacheStatus, InformationalTiming, LossReport, PhysicalBound, PingStats, RedirectHop, RequestPhase, ResponseBody, ServerTiming, SocketStats, Throttling, TimingMetrics, UploadTiming, TestResult, Statistics};
\ No newline at end of file
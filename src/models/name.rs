@@ -0,0 +1,167 @@
+//! Interned configuration names and URLs
+//!
+//! Every result, sample trace and map key of a run names its DNS
+//! configuration and target URL, and a watch-mode session repeats the same
+//! few of them every round. A [`Name`] is a shared, immutable string taken
+//! from a process-wide set, so all copies of one configuration name or URL
+//! point at a single allocation and cloning one is a reference count bump.
+//! The set only grows with distinct names, which a session has few of.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// An interned string; equal names share their allocation
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    /// The name interned from `value`
+    pub fn new(value: &str) -> Self {
+        static NAMES: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+        let mut names = NAMES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = names.get(value) {
+            return Self(Arc::clone(existing));
+        }
+        let name: Arc<str> = Arc::from(value);
+        names.insert(Arc::clone(&name));
+        Self(name)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Name {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Name {
+    fn from(value: String) -> Self {
+        Self::new(&value)
+    }
+}
+
+impl From<&String> for Name {
+    fn from(value: &String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other.as_str()
+    }
+}
+
+impl PartialEq<Name> for str {
+    fn eq(&self, other: &Name) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for &str {
+    fn eq(&self, other: &Name) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for String {
+    fn eq(&self, other: &Name) -> bool {
+        self.as_str() == &*other.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Self::new(&value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_name_interning() {
+        let first = Name::new("Custom DNS (192.0.2.1)");
+        let second = Name::from("Custom DNS (192.0.2.1)".to_string());
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+        assert_eq!(first, "Custom DNS (192.0.2.1)");
+        assert_eq!(format!("{:<24}|", first), "Custom DNS (192.0.2.1)  |");
+
+        // Maps keyed by names are looked up with plain strings
+        let mut counts: HashMap<Name, u32> = HashMap::new();
+        *counts.entry(first).or_default() += 1;
+        assert_eq!(counts.get("Custom DNS (192.0.2.1)"), Some(&1));
+
+        let json = serde_json::to_string(&second).unwrap();
+        assert_eq!(json, "\"Custom DNS (192.0.2.1)\"");
+        let parsed: Name = serde_json::from_str(&json).unwrap();
+        assert!(Arc::ptr_eq(&parsed.0, &second.0));
+    }
+}
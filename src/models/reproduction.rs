@@ -0,0 +1,277 @@
+//! Command lines that reproduce a run
+//!
+//! Reports are often shared without the `.env` file or shell history behind
+//! them. [`Reproduction`] records the effective configuration of a run as a
+//! canonical command line, plus a short hash of it, so that a report says how
+//! it was produced and two reports can be told apart at a glance. Only
+//! settings that change what is measured or how it is reported are included;
+//! the output format and verbosity are left to whoever reruns it.
+
+use super::Config;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Name the command line starts with: the binary's, from `[[bin]]` in Cargo.toml
+const PROGRAM: &str = "nlt";
+
+/// A command line reproducing a run, with a hash of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reproduction {
+    /// Environment variables the command line needs, for settings with no
+    /// command-line option that takes effect
+    pub env: Vec<(String, String)>,
+    /// Targets, one `--url` each
+    pub urls: Vec<String>,
+    /// Other options, each as `--name` or `--name=value`
+    pub options: Vec<String>,
+    /// Hash of the command line, for comparing runs
+    pub config_hash: String,
+}
+
+impl Reproduction {
+    /// Reproduce a run of `config`, whose targets are the ones it tested
+    ///
+    /// A shard reproduces as its own share of the targets without `--shard`,
+    /// since sharding the share again would test a different subset.
+    pub fn from_config(config: &Config) -> Self {
+        let defaults = Config::default();
+        let mut options = Vec::new();
+        let mut option = |name: &str, value: Option<String>| match value {
+            Some(value) => options.push(format!("--{}={}", name, value)),
+            None => options.push(format!("--{}", name)),
+        };
+
+        for chain in &config.dns_chains {
+            option("dns-chain", Some(chain.clone()));
+        }
+        if let Some(ref search_domains) = config.search_domains {
+            option("search-domains", Some(search_domains.join(",")));
+        }
+        if let Some(ndots) = config.ndots {
+            option("ndots", Some(ndots.to_string()));
+        }
+        if config.test_count != defaults.test_count {
+            option("count", Some(config.test_count.to_string()));
+        }
+        if config.timeout != defaults.timeout {
+            option("timeout", Some(crate::utils::duration::format_duration(config.timeout)));
+        }
+        if let Some(max_runtime) = config.max_runtime {
+            option("max-runtime", Some(crate::utils::duration::format_duration(max_runtime)));
+        }
+        if let Some(precision) = config.auto_count {
+            option("auto-count", Some(format!("{}%", precision)));
+        }
+        if config.scoring != defaults.scoring {
+            option("scoring", Some(config.scoring.to_string()));
+        }
+        if config.censor_timeouts {
+            option("censor-timeouts", None);
+        }
+        if config.socket_stats {
+            option("socket-stats", None);
+        }
+        if let Some(packets) = config.loss_probe {
+            option("loss-probe", Some(packets.to_string()));
+        }
+        if let Some(count) = config.ping {
+            option("ping", Some(count.to_string()));
+        }
+        if config.redirect_policy != defaults.redirect_policy {
+            option("redirect-policy", Some(config.redirect_policy.to_string()));
+        }
+        if config.max_redirects != defaults.max_redirects {
+            option("max-redirects", Some(config.max_redirects.to_string()));
+        }
+        if config.user_agent != defaults.user_agent {
+            option("user-agent", Some(config.user_agent.to_string()));
+        }
+        if config.http2_prior_knowledge {
+            option("http2-prior-knowledge", None);
+        }
+        if !config.alpn_fallback {
+            option("no-alpn-fallback", None);
+        }
+        if let Some(version) = config.tls_min_version {
+            option("tls-min-version", Some(version.to_string()));
+        }
+        if let Some(version) = config.tls_max_version {
+            option("tls-max-version", Some(version.to_string()));
+        }
+        if let Some(resumption) = config.tls_resumption {
+            option("tls-resumption", Some(if resumption { "on" } else { "off" }.to_string()));
+        }
+        if config.doh_fresh_connections {
+            option("doh-fresh-connections", None);
+        }
+        for (host, bootstrap) in &config.doh_bootstrap {
+            option("doh-bootstrap", Some(format!("{}={}", host, bootstrap)));
+        }
+        if config.sandbox_dns {
+            option("sandbox-dns", None);
+        }
+        if config.flush_dns_cache {
+            option("flush-dns-cache", None);
+        }
+        if config.single_thread {
+            option("single-thread", None);
+        }
+        if let Some(ref ntp_server) = config.ntp_server {
+            option("ntp-server", Some(ntp_server.clone()));
+        }
+        if config.locale != defaults.locale {
+            option("locale", Some(config.locale.to_string()));
+        }
+        for (id, label) in &config.dns_labels {
+            option("dns-label", Some(format!("{}={}", id, label)));
+        }
+        for (id, options) in &config.resolver_options {
+            option("resolver-options", Some(format!("{}={}", id, options)));
+        }
+        if let Some(ref bind) = config.bind {
+            option("bind", Some(bind.to_string()));
+        }
+        if let Some(options) = config.pool_options {
+            option("pool-options", Some(options.to_string()));
+        }
+        if let Some(upload) = config.upload {
+            option("upload", Some(upload.to_string()));
+        }
+        if config.early_hints {
+            option("early-hints", None);
+        }
+        if let Some(threshold) = config.capture_on_anomaly {
+            option("capture-on-anomaly", Some(crate::utils::duration::format_duration(threshold)));
+        }
+
+        // The command line takes no DNS servers or DoH providers, so they go in the environment
+        let env = vec![
+            ("DNS_SERVERS".to_string(), config.dns_servers.join(",")),
+            ("DOH_PROVIDERS".to_string(), config.doh_providers.join(",")),
+        ];
+        Self::new(env, config.target_urls.clone(), options)
+    }
+
+    /// Reproduce a run whose requests `--simulate` simulated with `spec`
+    /// instead of sending them
+    pub fn simulated(self, spec: &str) -> Self {
+        let mut options = self.options;
+        options.push(format!("--simulate={}", spec));
+        Self::new(self.env, self.urls, options)
+    }
+
+    fn new(env: Vec<(String, String)>, urls: Vec<String>, options: Vec<String>) -> Self {
+        let mut reproduction = Self { env, urls, options, config_hash: String::new() };
+        reproduction.config_hash = format!("{:016x}", fnv1a(reproduction.command().as_bytes()));
+        reproduction
+    }
+
+    /// Reproduce the runs of several shards as one run over all of their
+    /// targets, if they agree on everything else
+    pub fn merge<'a>(parts: impl IntoIterator<Item = &'a Reproduction>) -> Option<Reproduction> {
+        let mut parts = parts.into_iter();
+        let first = parts.next()?;
+        let mut urls = first.urls.clone();
+        for part in parts {
+            if part.env != first.env || part.options != first.options {
+                return None;
+            }
+            for url in &part.urls {
+                if !urls.contains(url) {
+                    urls.push(url.clone());
+                }
+            }
+        }
+        Some(Self::new(first.env.clone(), urls, first.options.clone()))
+    }
+
+    /// The command line, quoted for a POSIX shell
+    pub fn command(&self) -> String {
+        let env = self.env.iter().map(|(name, value)| format!("{}={}", name, shell_quote(value)));
+        let urls = self.urls.iter().map(|url| format!("--url={}", shell_quote(url)));
+        let options = self.options.iter().map(|option| shell_quote(option));
+        env.chain(std::iter::once(PROGRAM.to_string()))
+            .chain(urls)
+            .chain(options)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for Reproduction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.command())
+    }
+}
+
+/// Quote `value` for a POSIX shell, unless it needs none
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hasher stays the same
+/// across Rust versions and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RedirectPolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reproduction_command() {
+        let config = Config {
+            target_urls: vec!["https://example.com/search?q=a&b=c".to_string()],
+            dns_servers: vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()],
+            doh_providers: Vec::new(),
+            dns_chains: vec!["223.5.5.5>8.8.8.8".to_string()],
+            test_count: 10,
+            timeout: Duration::from_millis(1500),
+            redirect_policy: RedirectPolicy::HttpsOnly,
+            verbose: true,
+            ..Default::default()
+        };
+        let reproduction = Reproduction::from_config(&config);
+        assert_eq!(
+            reproduction.command(),
+            "DNS_SERVERS=8.8.8.8,1.1.1.1 DOH_PROVIDERS='' nlt \
+             --url='https://example.com/search?q=a&b=c' '--dns-chain=223.5.5.5>8.8.8.8' \
+             --count=10 --timeout=1s500ms --redirect-policy=https-only"
+        );
+        assert_eq!(reproduction.config_hash.len(), 16);
+
+        // Output settings do not change the hash; measured ones do
+        assert_eq!(Reproduction::from_config(&Config { verbose: false, ..config.clone() }), reproduction);
+        assert_ne!(Reproduction::from_config(&Config { test_count: 11, ..config.clone() }).config_hash, reproduction.config_hash);
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+
+        // A simulated run does not reproduce as a real one
+        let simulated = reproduction.clone().simulated("normal:mean=80ms,sd=20ms");
+        assert!(simulated.command().ends_with(" --redirect-policy=https-only --simulate=normal:mean=80ms,sd=20ms"));
+        assert_ne!(simulated.config_hash, reproduction.config_hash);
+    }
+
+    #[test]
+    fn test_merge_reproductions() {
+        let shard = |url: &str| Reproduction::from_config(&Config { target_urls: vec![url.to_string()], ..Default::default() });
+        let merged = Reproduction::merge([&shard("https://a.example"), &shard("https://b.example")]).unwrap();
+        assert_eq!(merged.urls, ["https://a.example", "https://b.example"]);
+        assert_eq!(merged, Reproduction::from_config(&Config {
+            target_urls: vec!["https://a.example".to_string(), "https://b.example".to_string()],
+            ..Default::default()
+        }));
+
+        let other = Reproduction::from_config(&Config { test_count: 9, ..Default::default() });
+        assert!(Reproduction::merge([&merged, &other]).is_none());
+        assert!(Reproduction::merge([]).is_none());
+    }
+}
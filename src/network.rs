@@ -0,0 +1,538 @@
+//! Which network the machine is on
+//!
+//! A laptop measures different latency at home, in the office and behind a
+//! VPN, so comparing a run with one from another network mostly shows the
+//! change of network. `--network-history` identifies the current network by
+//! its Wi-Fi name, the MAC address of its default gateway and where its
+//! traffic leaves for the internet (a [`NetworkProfile`]), and keeps a
+//! [`CycleHistory`] for each network in a [`NetworkStore`], so that a run is
+//! compared with earlier runs on the same network. The first network recorded
+//! is the baseline, and runs on any other network point that out.
+
+use crate::{
+    error::{AppError, Result},
+    executor::{CycleDrift, CycleHistory, ExecutionResults},
+    geo::GeoLocator,
+    stats::split_horizon::network_of,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Directory of the store within the data directory
+const APP_DIR: &str = "network-latency-tester";
+/// Name of the store
+const FILE_NAME: &str = "networks.json";
+
+/// What identifies a network; any part may be unknown
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    /// Name of the Wi-Fi network, when connected over Wi-Fi
+    #[serde(default)]
+    pub ssid: Option<String>,
+    /// MAC address of the default gateway, e.g. `a4:2b:b0:12:34:56`
+    #[serde(default)]
+    pub gateway_mac: Option<String>,
+    /// Where traffic leaves for the internet: the autonomous system of the
+    /// public address, or its prefix when that is unknown
+    #[serde(default)]
+    pub egress: Option<String>,
+}
+
+impl NetworkProfile {
+    /// Identify the current network as far as the platform allows
+    pub async fn detect(timeout: Duration) -> Self {
+        let gateway_mac = match default_gateway(timeout).await {
+            Some(gateway) => gateway_mac(gateway, timeout).await,
+            None => None,
+        };
+        Self {
+            ssid: wifi_ssid(timeout).await,
+            gateway_mac,
+            egress: egress(timeout).await,
+        }
+    }
+
+    /// Whether nothing about the network could be found out
+    pub fn is_unknown(&self) -> bool {
+        self.ssid.is_none() && self.gateway_mac.is_none() && self.egress.is_none()
+    }
+}
+
+impl fmt::Display for NetworkProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(ref ssid) = self.ssid {
+            parts.push(format!("Wi-Fi \"{}\"", ssid));
+        }
+        if let Some(ref mac) = self.gateway_mac {
+            parts.push(format!("gateway {}", mac));
+        }
+        if let Some(ref egress) = self.egress {
+            parts.push(format!("via {}", egress));
+        }
+        if parts.is_empty() {
+            write!(f, "unknown network")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Run `program` and return what it printed, if it succeeded in time
+async fn run(program: &str, args: &[&str], timeout: Duration) -> Option<String> {
+    let output = tokio::time::timeout(timeout, Command::new(program).args(args).output()).await.ok()?.ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn default_gateway(timeout: Duration) -> Option<IpAddr> {
+    let routes = match std::env::consts::OS {
+        "linux" => fs::read_to_string("/proc/net/route").ok()?,
+        "macos" => run("route", &["-n", "get", "default"], timeout).await?,
+        "windows" => run("route", &["print", "-4", "0.0.0.0"], timeout).await?,
+        _ => return None,
+    };
+    parse_default_gateway(&routes)
+}
+
+async fn gateway_mac(gateway: IpAddr, timeout: Duration) -> Option<String> {
+    let ip = gateway.to_string();
+    let neighbors = match std::env::consts::OS {
+        "linux" => fs::read_to_string("/proc/net/arp").ok()?,
+        "windows" => run("arp", &["-a", &ip], timeout).await?,
+        _ => run("arp", &["-n", &ip], timeout).await?,
+    };
+    parse_neighbor_mac(&neighbors, gateway)
+}
+
+async fn wifi_ssid(timeout: Duration) -> Option<String> {
+    match std::env::consts::OS {
+        "linux" => match run("iwgetid", &["-r"], timeout).await {
+            Some(ssid) if !ssid.trim().is_empty() => Some(ssid.trim().to_string()),
+            _ => parse_ssid(&run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"], timeout).await?),
+        },
+        "macos" => parse_ssid(&run("networksetup", &["-getairportnetwork", "en0"], timeout).await?),
+        "windows" => parse_ssid(&run("netsh", &["wlan", "show", "interfaces"], timeout).await?),
+        _ => None,
+    }
+}
+
+/// The autonomous system of the public address, looked up with the service `--geo` uses
+async fn egress(timeout: Duration) -> Option<String> {
+    let (ip, location) = GeoLocator::new(timeout).ok()?.public_address().await.ok()?;
+    Some(location.asn.unwrap_or_else(|| network_of(ip)))
+}
+
+/// The default gateway in `/proc/net/route`, `route -n get default` (macOS)
+/// or `route print` (Windows) output
+fn parse_default_gateway(routes: &str) -> Option<IpAddr> {
+    routes.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["gateway:", gateway, ..] => gateway.parse().ok(),
+            ["0.0.0.0", "0.0.0.0", gateway, ..] => gateway.parse().ok(),
+            // Destination, gateway and mask are little-endian hex in /proc/net/route
+            [_, "00000000", gateway, _, _, _, _, "00000000", ..] => {
+                let gateway = u32::from_str_radix(gateway, 16).ok().filter(|&gateway| gateway != 0)?;
+                Some(IpAddr::V4(Ipv4Addr::from(gateway.to_le_bytes())))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// The MAC address of `ip` in `/proc/net/arp` or `arp` output
+fn parse_neighbor_mac(neighbors: &str, ip: IpAddr) -> Option<String> {
+    let ip = ip.to_string();
+    neighbors.lines()
+        .filter(|line| line.split_whitespace().any(|field| field.trim_matches(|c| c == '(' || c == ')') == ip))
+        .find_map(|line| line.split_whitespace().find_map(normalize_mac))
+}
+
+/// A MAC address as lowercase colon-separated pairs, from either separator
+/// and with or without leading zeros
+fn normalize_mac(field: &str) -> Option<String> {
+    let octets: Vec<u8> = field.split([':', '-'])
+        .map(|octet| if (1..=2).contains(&octet.len()) { u8::from_str_radix(octet, 16).ok() } else { None })
+        .collect::<Option<_>>()?;
+    // Incomplete entries show as all zeros
+    if octets.len() != 6 || octets.iter().all(|&octet| octet == 0) {
+        return None;
+    }
+    Some(octets.iter().map(|octet| format!("{:02x}", octet)).collect::<Vec<_>>().join(":"))
+}
+
+/// The Wi-Fi network name in `nmcli`, `networksetup` (macOS) or `netsh`
+/// (Windows) output
+fn parse_ssid(output: &str) -> Option<String> {
+    output.lines()
+        .map(str::trim)
+        .find_map(|line| {
+            line.strip_prefix("yes:")
+                .or_else(|| line.strip_prefix("Current Wi-Fi Network:"))
+                .or_else(|| line.strip_prefix("SSID").and_then(|rest| rest.trim_start().strip_prefix(':')))
+        })
+        .map(str::trim)
+        .filter(|ssid| !ssid.is_empty())
+        .map(str::to_string)
+}
+
+/// A network interface requests can be sent from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub addresses: Vec<IpAddr>,
+}
+
+/// Interfaces that are up and have an address other than a loopback or
+/// link-local one, by name, for `--all-interfaces`
+pub async fn usable_interfaces(timeout: Duration) -> Result<Vec<NetworkInterface>> {
+    let interfaces = match std::env::consts::OS {
+        "linux" => run("ip", &["-o", "addr", "show", "up"], timeout).await.map(|output| parse_ip_addr(&output)),
+        "macos" => run("ifconfig", &[], timeout).await.map(|output| parse_ifconfig(&output)),
+        os => return Err(AppError::config(format!("Listing network interfaces is not supported on {}; use --bind instead", os))),
+    };
+    interfaces.ok_or_else(|| AppError::network("Failed to list the network interfaces"))
+}
+
+/// Whether requests from `ip` can reach the internet
+fn is_usable(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !ip.is_loopback() && !ip.is_unspecified() && !ip.is_link_local(),
+        IpAddr::V6(ip) => !ip.is_loopback() && !ip.is_unspecified() && (ip.segments()[0] & 0xffc0) != 0xfe80,
+    }
+}
+
+/// Gather the usable addresses of each interface, dropping the interfaces without any
+fn collect_interfaces<'a>(addresses: impl IntoIterator<Item = (&'a str, IpAddr)>) -> Vec<NetworkInterface> {
+    let mut interfaces: BTreeMap<&str, Vec<IpAddr>> = BTreeMap::new();
+    for (name, ip) in addresses.into_iter().filter(|(_, ip)| is_usable(ip)) {
+        interfaces.entry(name).or_default().push(ip);
+    }
+    interfaces.into_iter()
+        .map(|(name, addresses)| NetworkInterface { name: name.to_string(), addresses })
+        .collect()
+}
+
+/// Interfaces in `ip -o addr show up` (Linux) output, one address per line
+fn parse_ip_addr(output: &str) -> Vec<NetworkInterface> {
+    collect_interfaces(output.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, name, "inet" | "inet6", address, ..] => {
+                let name = name.split('@').next()?;
+                Some((name, address.split('/').next()?.parse().ok()?))
+            }
+            _ => None,
+        }
+    }))
+}
+
+/// Interfaces flagged `UP` and `RUNNING` in `ifconfig` (macOS) output
+fn parse_ifconfig(output: &str) -> Vec<NetworkInterface> {
+    let mut addresses = Vec::new();
+    let mut current = None;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            // e.g. `en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500`
+            current = line.split_once(':').and_then(|(name, rest)| {
+                let flags = rest.split_once('<')?.1.split_once('>')?.0;
+                let flags: Vec<&str> = flags.split(',').collect();
+                (flags.contains(&"UP") && flags.contains(&"RUNNING") && !flags.contains(&"LOOPBACK")).then_some(name)
+            });
+            continue;
+        }
+        let Some(name) = current else { continue };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let ["inet" | "inet6", address, ..] = fields.as_slice() {
+            // Link-local IPv6 addresses carry their scope, e.g. `fe80::1%en0`
+            if let Ok(ip) = address.split('%').next().unwrap_or(address).parse() {
+                addresses.push((name, ip));
+            }
+        }
+    }
+    collect_interfaces(addresses)
+}
+
+/// Results of one network, as kept in the store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkRecord {
+    pub profile: NetworkProfile,
+    /// Number of runs recorded on the network
+    pub runs: u32,
+    pub last_seen: DateTime<Utc>,
+    pub history: CycleHistory,
+}
+
+/// Results of every network the machine has run on, by network
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkStore {
+    #[serde(skip)]
+    path: PathBuf,
+    /// The network runs on other networks are pointed out against
+    #[serde(default)]
+    baseline: Option<NetworkProfile>,
+    /// Networks by their description
+    #[serde(default)]
+    networks: BTreeMap<String, NetworkRecord>,
+}
+
+impl NetworkStore {
+    /// Where the store is kept: `network-latency-tester/networks.json` under
+    /// `$XDG_DATA_HOME`, `%LOCALAPPDATA%` on Windows, or `~/.local/share`
+    pub fn locate() -> Option<PathBuf> {
+        let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+        let data_dir = non_empty("XDG_DATA_HOME")
+            .or_else(|| if cfg!(windows) { non_empty("LOCALAPPDATA") } else { None })
+            .or_else(|| non_empty("HOME").map(|home| home.join(".local").join("share")))?;
+        Some(data_dir.join(APP_DIR).join(FILE_NAME))
+    }
+
+    /// The store in `path`, empty if there is none yet
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut store = if path.exists() {
+            let content = fs::read_to_string(path)
+                .map_err(|e| AppError::io(format!("Failed to read {}: {}", path.display(), e)).with_source(e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| AppError::parse(format!("Failed to parse network history {}: {}", path.display(), e)).with_source(e))?
+        } else {
+            Self::default()
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let failed = |e: std::io::Error| AppError::io(format!("Failed to write {}: {}", self.path.display(), e)).with_source(e);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(failed)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::parse(format!("Failed to serialize network history: {}", e)).with_source(e))?;
+        fs::write(&self.path, content).map_err(failed)
+    }
+
+    pub fn baseline(&self) -> Option<&NetworkProfile> {
+        self.baseline.as_ref()
+    }
+
+    /// Make `profile` the network others are pointed out against
+    pub fn set_baseline(&mut self, profile: &NetworkProfile) {
+        self.baseline = Some(profile.clone());
+    }
+
+    /// The record of `profile`, if it was run on before
+    pub fn get(&self, profile: &NetworkProfile) -> Option<&NetworkRecord> {
+        self.networks.get(&profile.to_string())
+    }
+
+    /// Size and contents of the store in `path`, which need not exist or be
+    /// readable, for `cache stats`
+    pub fn stats(path: &Path) -> NetworkStoreStats {
+        let size_bytes = fs::metadata(path).map(|metadata| metadata.len()).ok();
+        let store = size_bytes.and_then(|_| Self::load(path).ok());
+        let records = store.iter().flat_map(|store| store.networks.values());
+        NetworkStoreStats {
+            exists: size_bytes.is_some(),
+            readable: store.is_some(),
+            size_bytes: size_bytes.unwrap_or(0),
+            network_count: records.clone().count(),
+            run_count: records.clone().map(|record| u64::from(record.runs)).sum(),
+            last_seen: records.map(|record| record.last_seen).max(),
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Compare `results` with earlier runs on `profile`, then remember them;
+    /// the first network recorded becomes the baseline
+    pub fn record(&mut self, profile: &NetworkProfile, results: &ExecutionResults) -> CycleDrift {
+        if self.baseline.is_none() {
+            self.set_baseline(profile);
+        }
+        let record = self.networks.entry(profile.to_string()).or_insert_with(|| NetworkRecord {
+            profile: profile.clone(),
+            runs: 0,
+            last_seen: Utc::now(),
+            history: CycleHistory::new(),
+        });
+        record.runs += 1;
+        record.last_seen = Utc::now();
+        record.history.record(results)
+    }
+}
+
+/// Store statistics, as `cache stats` shows them
+#[derive(Debug, Clone)]
+pub struct NetworkStoreStats {
+    /// Whether the store file exists
+    pub exists: bool,
+    /// Whether the store could be parsed
+    pub readable: bool,
+    /// Store file size in bytes
+    pub size_bytes: u64,
+    /// Number of networks recorded
+    pub network_count: usize,
+    /// Number of runs recorded over every network
+    pub run_count: u64,
+    /// When a run was last recorded
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Store file path
+    pub path: PathBuf,
+}
+
+impl NetworkStoreStats {
+    /// Format store statistics for display
+    pub fn format_stats(&self, use_colors: bool) -> String {
+        let status = if self.readable { "Valid" } else if self.exists { "Unreadable" } else { "Not Found" };
+        let last_seen = self.last_seen.map(|last_seen| last_seen.to_rfc3339());
+        let mut output = String::new();
+
+        if use_colors {
+            use colored::Colorize;
+            output.push_str(&format!("📁 Cache Path: {}\n", self.path.display().to_string().cyan()));
+            output.push_str(&format!("📊 Status: {}\n",
+                if self.readable { status.green() } else if self.exists { status.yellow() } else { status.red() }
+            ));
+            if self.exists {
+                output.push_str(&format!("💾 Size: {} bytes\n", self.size_bytes.to_string().blue()));
+            }
+            if self.readable {
+                output.push_str(&format!("🌐 Networks: {}\n", self.network_count.to_string().blue()));
+                output.push_str(&format!("🔁 Runs: {}\n", self.run_count.to_string().blue()));
+            }
+            if let Some(ref last_seen) = last_seen {
+                output.push_str(&format!("⏰ Last Run: {}\n", last_seen.blue()));
+            }
+        } else {
+            output.push_str(&format!("Cache Path: {}\n", self.path.display()));
+            output.push_str(&format!("Status: {}\n", status));
+            if self.exists {
+                output.push_str(&format!("Size: {} bytes\n", self.size_bytes));
+            }
+            if self.readable {
+                output.push_str(&format!("Networks: {}\n", self.network_count));
+                output.push_str(&format!("Runs: {}\n", self.run_count));
+            }
+            if let Some(ref last_seen) = last_seen {
+                output.push_str(&format!("Last Run: {}\n", last_seen));
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecutionSummary, ResultKey};
+    use crate::test_support::result_with_totals;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_platform_output() {
+        let proc_route = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+            wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0\n";
+        let gateway: IpAddr = "192.168.1.1".parse().unwrap();
+        assert_eq!(parse_default_gateway(proc_route), Some(gateway));
+        assert_eq!(parse_default_gateway("   route to: default\n    gateway: 192.168.1.1\n  interface: en0\n"), Some(gateway));
+        assert_eq!(parse_default_gateway("          0.0.0.0          0.0.0.0      192.168.1.1    192.168.1.20     25\n"), Some(gateway));
+
+        let proc_arp = "IP address       HW type     Flags       HW address            Mask     Device\n\
+            192.168.1.1      0x1         0x2         a4:2b:b0:12:34:56     *        wlan0\n";
+        assert_eq!(parse_neighbor_mac(proc_arp, gateway).as_deref(), Some("a4:2b:b0:12:34:56"));
+        let macos = "? (192.168.1.1) at a4:2b:b0:12:34:6 on en0 ifscope [ethernet]\n";
+        assert_eq!(parse_neighbor_mac(macos, gateway).as_deref(), Some("a4:2b:b0:12:34:06"));
+        let windows = "Interface: 192.168.1.20 --- 0x5\n  Internet Address      Physical Address      Type\n  192.168.1.1           a4-2b-b0-12-34-56     dynamic\n";
+        assert_eq!(parse_neighbor_mac(windows, gateway).as_deref(), Some("a4:2b:b0:12:34:56"));
+        assert_eq!(parse_neighbor_mac("? (192.168.1.1) at (incomplete) on en0\n", gateway), None);
+
+        assert_eq!(parse_ssid("no:Neighbours\nyes:Home Net\n").as_deref(), Some("Home Net"));
+        assert_eq!(parse_ssid("Current Wi-Fi Network: Office\n").as_deref(), Some("Office"));
+        assert_eq!(parse_ssid("    BSSID                  : a4:2b:b0:12:34:56\n    SSID                   : Cafe\n").as_deref(), Some("Cafe"));
+        assert_eq!(parse_ssid("You are not associated with an AirPort network.\n"), None);
+    }
+
+    #[test]
+    fn test_parse_interfaces() {
+        let ip = "1: lo    inet 127.0.0.1/8 scope host lo\\       valid_lft forever preferred_lft forever\n\
+            2: eth0    inet 192.168.1.20/24 brd 192.168.1.255 scope global eth0\\       valid_lft forever preferred_lft forever\n\
+            2: eth0    inet6 fe80::1/64 scope link \\       valid_lft forever preferred_lft forever\n\
+            3: wwan0    inet6 2001:db8::7/64 scope global dynamic \\       valid_lft 7100sec preferred_lft 3500sec\n\
+            4: docker0    inet6 fe80::42/64 scope link \\       valid_lft forever preferred_lft forever\n";
+        let interfaces = parse_ip_addr(ip);
+        assert_eq!(interfaces, [
+            NetworkInterface { name: "eth0".to_string(), addresses: vec!["192.168.1.20".parse().unwrap()] },
+            NetworkInterface { name: "wwan0".to_string(), addresses: vec!["2001:db8::7".parse().unwrap()] },
+        ]);
+
+        let ifconfig = "lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384\n\
+            \tinet 127.0.0.1 netmask 0xff000000\n\
+            en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\
+            \tether a4:2b:b0:12:34:56\n\
+            \tinet6 fe80::1%en0 prefixlen 64 secured scopeid 0x4\n\
+            \tinet 192.168.1.21 netmask 0xffffff00 broadcast 192.168.1.255\n\
+            en5: flags=8822<BROADCAST,SMART,SIMPLEX,MULTICAST> mtu 1500\n\
+            \tinet 10.0.0.5 netmask 0xffffff00\n";
+        let interfaces = parse_ifconfig(ifconfig);
+        assert_eq!(interfaces, [NetworkInterface { name: "en0".to_string(), addresses: vec!["192.168.1.21".parse().unwrap()] }]);
+    }
+
+    #[test]
+    fn test_store_keeps_history_per_network() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("networks.json");
+        let home = NetworkProfile {
+            ssid: Some("Home Net".to_string()),
+            gateway_mac: Some("a4:2b:b0:12:34:56".to_string()),
+            egress: Some("AS3320 Deutsche Telekom AG".to_string()),
+        };
+        let vpn = NetworkProfile { egress: Some("AS13335 Cloudflare, Inc.".to_string()), ..home.clone() };
+        assert_eq!(vpn.to_string(), "Wi-Fi \"Home Net\", gateway a4:2b:b0:12:34:56, via AS13335 Cloudflare, Inc.");
+        assert_eq!(NetworkProfile::default().to_string(), "unknown network");
+
+        let run = |total_ms: u64| {
+            let result = result_with_totals("System DNS", "https://example.com", &[total_ms]);
+            let summary = ExecutionSummary {
+                total_tests: 1,
+                successful_tests: 1,
+                success_rate: 100.0,
+                ..Default::default()
+            };
+            ExecutionResults::new(summary, vec![result])
+        };
+        let key = ResultKey::new("https://example.com", "System DNS");
+
+        assert!(!NetworkStore::stats(&path).exists);
+        let mut store = NetworkStore::load(&path).unwrap();
+        assert!(store.record(&home, &run(20)).is_empty());
+        store.save().unwrap();
+
+        let mut store = NetworkStore::load(&path).unwrap();
+        assert_eq!(store.baseline(), Some(&home));
+        // Another network starts a history of its own
+        assert!(store.record(&vpn, &run(80)).is_empty());
+        let drift = store.record(&home, &run(25));
+        assert_eq!(drift.get(&key).unwrap().to_string(), "↑+5ms / ↑+5ms");
+        assert_eq!(store.get(&home).unwrap().runs, 2);
+        assert_eq!(store.baseline(), Some(&home));
+        store.save().unwrap();
+
+        let stats = NetworkStore::stats(&path);
+        assert!(stats.readable);
+        assert_eq!((stats.network_count, stats.run_count), (2, 3));
+        assert_eq!(stats.last_seen, Some(store.get(&home).unwrap().last_seen));
+        assert!(stats.format_stats(false).contains("Networks: 2\nRuns: 3\n"));
+        std::fs::write(&path, "not json").unwrap();
+        let stats = NetworkStore::stats(&path);
+        assert!(stats.exists && !stats.readable);
+        assert!(stats.format_stats(false).contains("Status: Unreadable\nSize: 8 bytes\n"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Desktop notifications for watch mode
+//!
+//! With `--notify desktop`, a `--watch` run on a workstation raises a
+//! notification when a cycle breaches the health threshold (the cycle fails,
+//! or fewer than half of its requests succeed) and another when a later cycle
+//! recovers, rather than one per cycle. Notifications are shown with what each
+//! platform already ships: `notify-send` on Linux and the BSDs, `osascript` on
+//! macOS and a PowerShell toast on Windows.
+
+use crate::{
+    error::{AppError, Result},
+    PKG_NAME,
+};
+use std::{fmt, str::FromStr};
+use tokio::process::Command;
+
+/// Where watch-mode notifications are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyTarget {
+    /// The notification center of the local desktop
+    Desktop,
+}
+
+impl NotifyTarget {
+    /// All targets, in the order they are documented
+    pub const ALL: [NotifyTarget; 1] = [Self::Desktop];
+}
+
+impl fmt::Display for NotifyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Desktop => write!(f, "desktop"),
+        }
+    }
+}
+
+impl FromStr for NotifyTarget {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|target| target.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::validation(format!("Unknown notification target '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+/// A notification to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Decides which watch cycles are worth a notification
+///
+/// Only changes are reported: the first unhealthy cycle after healthy ones,
+/// and the first healthy cycle after unhealthy ones.
+#[derive(Debug)]
+pub struct BreachNotifier {
+    target: NotifyTarget,
+    breached: bool,
+}
+
+impl BreachNotifier {
+    pub fn new(target: NotifyTarget) -> Self {
+        Self { target, breached: false }
+    }
+
+    /// Where this notifier's notifications go
+    pub fn target(&self) -> NotifyTarget {
+        self.target
+    }
+
+    /// Notification for the outcome of a cycle, if its health changed;
+    /// `problem` describes the breach of an unhealthy cycle
+    pub fn observe(&mut self, cycle: u32, problem: Option<&str>) -> Option<Notification> {
+        let breached = problem.is_some();
+        if breached == self.breached {
+            return None;
+        }
+        self.breached = breached;
+        Some(match problem {
+            Some(problem) => Notification {
+                title: format!("{}: checks failing", PKG_NAME),
+                body: format!("Cycle {}: {}", cycle, problem),
+            },
+            None => Notification {
+                title: format!("{}: recovered", PKG_NAME),
+                body: format!("Cycle {} passed the health check again", cycle),
+            },
+        })
+    }
+}
+
+/// Show a notification on `target`
+pub async fn send(target: NotifyTarget, notification: &Notification) -> Result<()> {
+    match target {
+        NotifyTarget::Desktop => {
+            let (program, args) = desktop_command(std::env::consts::OS, notification);
+            let output = Command::new(program)
+                .args(&args)
+                .output()
+                .await
+                .map_err(|e| AppError::io(format!("Failed to run {} for a desktop notification: {}", program, e)).with_source(e))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(AppError::io(format!("{} could not show a desktop notification: {}", program, stderr.trim())));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Program and arguments that show a desktop notification on `os`
+fn desktop_command(os: &str, notification: &Notification) -> (&'static str, Vec<String>) {
+    match os {
+        "macos" => {
+            let script = format!(
+                "display notification {} with title {}",
+                applescript_string(&notification.body),
+                applescript_string(&notification.title)
+            );
+            ("osascript", vec!["-e".to_string(), script])
+        }
+        "windows" => {
+            // Toasts need a registered app ID; PowerShell's own is always there
+            let script = format!(
+                "$manager = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]; \
+                 $template = $manager::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+                 $text = $template.GetElementsByTagName('text'); \
+                 $text.Item(0).AppendChild($template.CreateTextNode({})) > $null; \
+                 $text.Item(1).AppendChild($template.CreateTextNode({})) > $null; \
+                 $manager::CreateToastNotifier('{{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}}\\WindowsPowerShell\\v1.0\\powershell.exe')\
+                 .Show([Windows.UI.Notifications.ToastNotification]::new($template))",
+                powershell_string(&notification.title),
+                powershell_string(&notification.body)
+            );
+            ("powershell", vec!["-NoProfile".to_string(), "-NonInteractive".to_string(), "-Command".to_string(), script])
+        }
+        _ => (
+            "notify-send",
+            vec![
+                format!("--app-name={}", PKG_NAME),
+                // A title or body starting with a dash is not an option
+                "--".to_string(),
+                notification.title.clone(),
+                notification.body.clone(),
+            ],
+        ),
+    }
+}
+
+/// `text` as an AppleScript string literal
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `text` as a single-quoted PowerShell string literal; PowerShell also
+/// ends such a string at the typographic single quotes, which are doubled too
+fn powershell_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breach_notifications_on_change_only() {
+        assert_eq!(" Desktop ".parse::<NotifyTarget>().unwrap(), NotifyTarget::Desktop);
+        assert!("email".parse::<NotifyTarget>().unwrap_err().to_string().contains("expected one of: desktop"));
+
+        let mut notifier = BreachNotifier::new(NotifyTarget::Desktop);
+        assert_eq!(notifier.observe(1, None), None);
+        let breach = notifier.observe(2, Some("More than 50% of tests failed")).unwrap();
+        assert_eq!(breach.title, format!("{}: checks failing", PKG_NAME));
+        assert_eq!(breach.body, "Cycle 2: More than 50% of tests failed");
+        assert_eq!(notifier.observe(3, Some("More than 50% of tests failed")), None);
+        assert_eq!(notifier.observe(4, None).unwrap().body, "Cycle 4 passed the health check again");
+        assert_eq!(notifier.observe(5, None), None);
+    }
+
+    #[test]
+    fn test_desktop_commands_quote_text() {
+        let notification = Notification { title: "it's \"down\"".to_string(), body: "C:\\probe".to_string() };
+
+        let (program, args) = desktop_command("macos", &notification);
+        assert_eq!(program, "osascript");
+        assert_eq!(args[1], r#"display notification "C:\\probe" with title "it's \"down\"""#);
+
+        let (program, args) = desktop_command("windows", &notification);
+        assert_eq!(program, "powershell");
+        assert!(args[3].contains(r#"CreateTextNode('it''s "down"')"#), "{}", args[3]);
+        assert!(args[3].contains(r"\WindowsPowerShell\v1.0\powershell.exe')"));
+        assert_eq!(powershell_string("it\u{2019}s \u{2018}up\u{201B}, \u{201A}ok"), "'it\u{2019}\u{2019}s \u{2018}\u{2018}up\u{201B}\u{201B}, \u{201A}\u{201A}ok'");
+
+        let (program, args) = desktop_command("linux", &notification);
+        assert_eq!(program, "notify-send");
+        assert_eq!(args[1..], ["--".to_string(), notification.title.clone(), notification.body.clone()]);
+    }
+}
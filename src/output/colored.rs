@@ -4,13 +4,21 @@
 //! ANSI colors and Unicode symbols for enhanced visual presentation.
 
 use crate::{
-    error::{AppError, Result},
-    executor::{ExecutionResults, ExecutionSummary},
-    models::metrics::TestResult,
-    stats::StatisticalAnalysis,
-    diagnostics::DiagnosticReport,
+    error::{AppError, ErrorGroup, Result},
+    executor::{drift::{Delta, Trend}, CycleDrift, ExecutionResults, ExecutionSummary, ResultKey, SampleRef},
+    models::{metrics::TestResult, Reproduction},
+    stats::{StatisticalAnalysis, PACKET_LOSS_WARNING_PERCENT, RETRANSMIT_WARNING_PERCENT},
+    types::{MetricColumn, UserAgent},
+    utils::url::{display_url, truncate_chars},
+};
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::DiagnosticReport;
+use super::formatter::{
+    OutputFormatter, FormattingOptions, PlainFormatter, cache_split_description, error_sources, tls_resumption_description, percentile_text, phase_breakdown, sample_origin,
+    clock_warnings, interference_heading, network_warning, CONNECTION_REUSE_WARNING, response_size_description, socket_description, system_description, timeout_description, upload_description, informational_description,
+    server_phases, server_timing_description, throttling_description,
+    OVERLOAD_WARNING, TableEntry, TableView, metric_cell, metric_column,
 };
-use super::formatter::{OutputFormatter, FormattingOptions, PlainFormatter};
 use std::collections::HashMap;
 use std::fmt::Write as _;
 use colored::*;
@@ -239,24 +247,76 @@ impl ColoredFormatter {
     }
 
     /// Create a colored table with enhanced formatting grouped by target URL
-    fn create_colored_table(&self, results: &HashMap<String, TestResult>) -> Result<String> {
+    /// A change since an earlier cycle, colored by whether it got slower or faster
+    fn format_delta_colored(&self, delta: Option<Delta>) -> String {
+        let Some(delta) = delta else {
+            return self.dimmed("-").to_string();
+        };
+        let color = match delta.trend() {
+            Trend::Slower => self.color_scheme.warning,
+            Trend::Steady => self.color_scheme.muted,
+            Trend::Faster => self.color_scheme.success,
+        };
+        self.colorize(&delta.to_string(), color).to_string()
+    }
+
+    /// Header and width of a metric column
+    fn metric_header(metric: MetricColumn) -> (String, usize) {
+        match metric {
+            MetricColumn::Success => ("Success".to_string(), 12),
+            MetricColumn::Level => ("Level".to_string(), 12),
+            _ => {
+                let column = metric_column(metric);
+                (column.header, column.min_width)
+            }
+        }
+    }
+
+    /// Colored cell of a metric column for one result, with the width it is padded to
+    fn metric_cell(&self, metric: MetricColumn, result: &TestResult) -> (String, usize) {
+        match (metric, result.statistics.as_ref()) {
+            (MetricColumn::Success, _) => {
+                let success_rate = result.success_rate();
+                let cell = format!("{} {}",
+                    self.format_percentage_colored(success_rate),
+                    self.create_performance_bar(success_rate, 8));
+                (cell, 20)
+            }
+            (MetricColumn::Total, Some(stats)) => (self.format_duration_colored(stats.total_avg_ms).to_string(), 12),
+            (MetricColumn::Level, Some(stats)) => {
+                let perf_level = PerformanceLevel::from_response_time(stats.total_avg_ms);
+                let perf_display = format!("{} {}", perf_level.symbol(), perf_level.description());
+                (self.colorize(&perf_display, perf_level.color()).to_string(), 20)
+            }
+            (MetricColumn::Level, None) => (self.dimmed("Unknown").to_string(), 20),
+            _ => {
+                let cell = metric_cell(metric, result, |ms| self.format_duration(ms), |percentage| self.format_percentage(percentage));
+                let cell = if cell == "N/A" { self.dimmed(&cell).to_string() } else { cell };
+                (cell, Self::metric_header(metric).1)
+            }
+        }
+    }
+
+    fn create_colored_table(&self, results: &HashMap<ResultKey, TestResult>, view: &TableView, drift: Option<&CycleDrift>) -> Result<String> {
         if results.is_empty() {
             return Ok(self.colorize("No test results available.", self.color_scheme.muted).to_string());
         }
+        let drift = drift.filter(|drift| !drift.is_empty());
 
         let mut output = String::new();
 
         // Group results by URL
-        let mut results_by_url: std::collections::HashMap<String, Vec<&TestResult>> = std::collections::HashMap::new();
-        for result in results.values() {
-            results_by_url.entry(result.url.clone()).or_default().push(result);
+        let mut results_by_url: std::collections::HashMap<&str, Vec<&TestResult>> = std::collections::HashMap::new();
+        for (key, result) in results {
+            results_by_url.entry(key.url.as_str()).or_default().push(result);
         }
 
         // Sort URLs for consistent output
-        let mut sorted_urls: Vec<String> = results_by_url.keys().cloned().collect();
+        let mut sorted_urls: Vec<&str> = results_by_url.keys().copied().collect();
         sorted_urls.sort();
 
         let mut overall_rank = 0;
+        let metrics = view.columns(self.options.verbose_mode, results.values());
 
         for (url_index, url) in sorted_urls.iter().enumerate() {
             // Always add URL section header (for both single and multiple URLs)
@@ -264,19 +324,21 @@ impl ColoredFormatter {
                 writeln!(output)
                     .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
             }
-            let url_display = if url.len() > 80 {
-                format!("{}...", &url[..77])
-            } else {
-                url.clone()
-            };
+            let url_display = truncate_chars(&display_url(url), 80);
             writeln!(output, "🎯 Target: {}", self.bold(&url_display).color(self.color_scheme.info))
                 .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
             writeln!(output, "{}", "─".repeat(95).color(self.color_scheme.border))
                 .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
 
             // Header for each section
-            let header = format!("{:<40} {:>12} {:>12} {:>15} {:>12}",
-                "Configuration", "Success", "Avg Response", "Min/Max", "Level");
+            let mut header = format!("{:<40}", "Configuration");
+            for metric in &metrics {
+                let (title, width) = Self::metric_header(*metric);
+                header.push_str(&format!(" {:>width$}", title, width = width));
+            }
+            if drift.is_some() {
+                header.push_str(&format!(" {:>18}", "vs Prev / Base"));
+            }
             
             writeln!(output, "{}", self.bold(&header))
                 .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
@@ -316,7 +378,21 @@ impl ColoredFormatter {
             });
 
             // Data rows with colors
-            for (index, result) in url_results.iter().enumerate() {
+            let mut index = 0;
+            for entry in view.arrange(url_results) {
+                let result = match entry {
+                    TableEntry::Result(result) => result,
+                    TableEntry::Omitted(omitted) => {
+                        let (lowest, highest) = omitted.success_rate;
+                        let mut summary = format!("{}-{} success", self.format_percentage(lowest), self.format_percentage(highest));
+                        if let Some((fastest, slowest)) = omitted.mean_ms {
+                            summary.push_str(&format!(", {}-{} avg", self.format_duration(fastest), self.format_duration(slowest)));
+                        }
+                        writeln!(output, "   {}", self.dimmed(&format!("{} ({})", omitted.label(), summary)))
+                            .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
+                        continue;
+                    }
+                };
                 overall_rank += 1;
                 let rank_icon = match overall_rank {
                     1 => "🥇",
@@ -332,6 +408,7 @@ impl ColoredFormatter {
                         "  "
                     }
                 };
+                index += 1;
 
                 let config_name = format!("{} {}", rank_icon, result.config_name);
                 let config_display = if config_name.len() > 38 {
@@ -340,27 +417,24 @@ impl ColoredFormatter {
                     config_name
                 };
 
-                let success_rate = result.success_rate();
-                let success_display = format!("{} {}", 
-                    self.format_percentage_colored(success_rate),
-                    self.create_performance_bar(success_rate, 8));
-
-                let (avg_response, min_max, performance_level) = if let Some(ref stats) = result.statistics {
-                    let avg_colored = self.format_duration_colored(stats.total_avg_ms);
-                    let min_max = format!("{}/{}", 
-                        self.format_duration(stats.total_min_ms), 
-                        self.format_duration(stats.total_max_ms));
-                    let perf_level = PerformanceLevel::from_response_time(stats.total_avg_ms);
-                    let perf_display = format!("{} {}", perf_level.symbol(), perf_level.description());
-                    
-                    (avg_colored.to_string(), min_max, 
-                     self.colorize(&perf_display, perf_level.color()).to_string())
-                } else {
-                    (self.dimmed("N/A").to_string(), self.dimmed("N/A").to_string(), self.dimmed("Unknown").to_string())
-                };
-
-                writeln!(output, "{:<40} {:>20} {:>12} {:>15} {:>20}",
-                    config_display, success_display, avg_response, min_max, performance_level)
+                write!(output, "{:<40}", config_display)
+                    .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
+                for metric in &metrics {
+                    let (cell, width) = self.metric_cell(*metric, result);
+                    write!(output, " {:>width$}", cell, width = width)
+                        .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
+                }
+                if let Some(drift) = drift {
+                    let changes = match drift.get(&ResultKey::for_result(result)) {
+                        Some(change) => format!("{} / {}",
+                            self.format_delta_colored(change.previous),
+                            self.format_delta_colored(change.baseline)),
+                        None => self.dimmed("new").to_string(),
+                    };
+                    write!(output, " {:>18}", changes)
+                        .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
+                }
+                writeln!(output)
                     .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
             }
         }
@@ -417,15 +491,145 @@ impl OutputFormatter for ColoredFormatter {
         }
         
         if summary.skipped_tests > 0 {
-            write!(output, "⏭️  Skipped:      {}", 
+            writeln!(output, "⏭️  Skipped:      {}", 
                 self.dimmed(&summary.skipped_tests.to_string()))
                 .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
         }
+
+        if let Some(ref simulation) = summary.simulation {
+            writeln!(output, "🧪 Simulated:    {}", self.colorize(simulation, self.color_scheme.warning))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(shard) = summary.shard {
+            writeln!(output, "🧩 Shard:        {}", self.dimmed(&shard.to_string()))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(user_agent) = summary.user_agent.as_ref().filter(|user_agent| **user_agent != UserAgent::Tool) {
+            writeln!(output, "🪪 User agent:   {}", self.dimmed(&user_agent.to_string()))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(ref network) = summary.network {
+            writeln!(output, "🛜 Network:      {}", self.dimmed(&network.to_string()))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            if let Some(ref baseline) = summary.baseline_network {
+                writeln!(output, "⚠️  {}", self.colorize(&format!("WARNING: {}", network_warning(baseline)), self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if let Some(system) = summary.system.as_ref().filter(|_| self.options.verbose_mode) {
+            writeln!(output, "🖥️  Machine:      {}", self.dimmed(&system_description(system)))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            if system.is_overloaded() {
+                writeln!(output, "⚠️  {}", self.colorize(&format!("WARNING: {}", OVERLOAD_WARNING), self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if let Some(ref clock) = summary.clock {
+            if self.options.verbose_mode || clock.ntp_server.is_some() {
+                writeln!(output, "🕒 Clock:        {}", self.dimmed(&clock.to_string()))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+            for warning in clock_warnings(clock) {
+                writeln!(output, "⚠️  {}", self.colorize(&format!("WARNING: {}", warning), self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.interference.is_empty() {
+            let heading = format!("WARNING: {}", interference_heading(summary.interference.len()));
+            writeln!(output, "⚠️  {}", self.colorize(&heading, self.color_scheme.warning))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for interval in &summary.interference {
+                writeln!(output, "   {}", self.dimmed(&interval.to_string()))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.connection_reuse.is_empty() {
+            writeln!(output, "🔁 Connection Reuse:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, reuse) in &summary.connection_reuse {
+                let color = if reuse.matches_first_use() { self.color_scheme.info } else { self.color_scheme.warning };
+                writeln!(output, "   {:<30} {}", config_name, self.colorize(&reuse.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+            if summary.connection_reuse.values().any(|reuse| !reuse.matches_first_use()) {
+                let warning = format!("WARNING: {}", CONNECTION_REUSE_WARNING);
+                writeln!(output, "⚠️  {}", self.colorize(&warning, self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.resolver_failover.is_empty() {
+            writeln!(output, "🔀 Resolver Failover:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, failover) in &summary.resolver_failover {
+                let color = if failover.fallbacks() == 0 { self.color_scheme.info } else { self.color_scheme.warning };
+                writeln!(output, "   {:<30} {}", config_name, self.colorize(&failover.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.server_distribution.is_empty() {
+            writeln!(output, "🎯 Server Distribution:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, distribution) in &summary.server_distribution {
+                let color = if distribution.failed == 0 { self.color_scheme.info } else { self.color_scheme.warning };
+                writeln!(output, "   {:<30} {}", config_name, self.colorize(&distribution.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.packet_captures.is_empty() {
+            writeln!(output, "🦈 Packet Captures:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for capture in &summary.packet_captures {
+                let color = if capture.error.is_none() { self.color_scheme.info } else { self.color_scheme.warning };
+                writeln!(output, "   {}", self.colorize(&capture.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.packet_loss.is_empty() {
+            writeln!(output, "📉 Packet Loss:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for report in &summary.packet_loss {
+                let lossy = report.loss_percentage().is_none_or(|loss| loss >= PACKET_LOSS_WARNING_PERCENT);
+                let color = if lossy { self.color_scheme.warning } else { self.color_scheme.info };
+                writeln!(output, "   {:<30} {}: {}", report.config_name, report.target, self.colorize(&report.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.public_baseline.is_empty() {
+            writeln!(output, "🌍 Public Baseline:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for comparison in &summary.public_baseline {
+                let color = if comparison.is_abnormal() { self.color_scheme.warning } else { self.color_scheme.info };
+                writeln!(output, "   {:<30} {}", comparison.target, self.colorize(&comparison.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.physical_bounds.is_empty() {
+            writeln!(output, "💡 Speed of Light:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for bound in &summary.physical_bounds {
+                let color = if bound.is_indirect() { self.color_scheme.warning } else { self.color_scheme.info };
+                writeln!(output, "   {:<30} {}", format!("{} ({})", bound.target, bound.ip), self.colorize(&bound.to_string(), color))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
         
-        Ok(output)
+        Ok(output.trim_end().to_string())
     }
 
-    fn format_performance_table(&self, results: &HashMap<String, TestResult>) -> Result<String> {
+    fn format_performance_table(&self, results: &HashMap<ResultKey, TestResult>, view: &TableView, drift: Option<&CycleDrift>) -> Result<String> {
         let mut output = String::new();
         
         writeln!(output, "{}", self.create_section_header("Performance Results", "🚀"))
@@ -433,7 +637,7 @@ impl OutputFormatter for ColoredFormatter {
         writeln!(output)
             .map_err(|e| AppError::io(format!("Failed to format table: {}", e)))?;
         
-        output.push_str(&self.create_colored_table(results)?);
+        output.push_str(&self.create_colored_table(results, view, drift)?);
         
         Ok(output)
     }
@@ -473,6 +677,249 @@ impl OutputFormatter for ColoredFormatter {
             self.format_duration_colored(avg_response_time))
             .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
         
+        if analysis.url_breakdown.len() > 1 {
+            writeln!(output, "\n{}", self.dimmed("Per-URL Breakdown:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for breakdown in &analysis.url_breakdown {
+                writeln!(output, "  🎯 {}", self.bold(&display_url(&breakdown.url)).color(self.color_scheme.info))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                for (config_name, config_stats) in breakdown.ranked_configs() {
+                    let marker = if breakdown.best_config.as_ref() == Some(config_name) { "🏆" } else { "  " };
+                    let p95 = percentile_text(config_stats, "p95", |ms| self.format_duration(ms));
+                    writeln!(output, "     {} {:<30} {} (p95 {}) | Success: {}",
+                        marker,
+                        config_name,
+                        self.format_duration_colored(config_stats.basic.total_avg_ms),
+                        p95,
+                        self.format_percentage_colored(config_stats.reliability.success_rate))
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+            }
+        }
+
+        if !analysis.split_horizon.is_empty() {
+            writeln!(output, "\n{}", self.dimmed("Split-Horizon Answers:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for target in &analysis.split_horizon {
+                let (marker, color) = if target.material {
+                    ("⚠️ ", self.color_scheme.warning)
+                } else {
+                    ("🔀", self.color_scheme.muted)
+                };
+                writeln!(output, "  {} {} {}", marker, self.bold(&display_url(&target.url)), self.colorize(&format!("({})", target.verdict()), color))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                for (config_name, answer) in &target.answers {
+                    writeln!(output, "     {:<30} {}", config_name, answer)
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+                for divergence in analysis.comparative_analysis.route_divergence.iter().filter(|d| d.url == target.url) {
+                    writeln!(output, "     {:<30} {}", divergence.config_name, self.colorize(&divergence.to_string(), self.color_scheme.warning))
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+            }
+        }
+
+        if let Some(ref matrix) = analysis.interface_matrix {
+            writeln!(output, "\n{}", self.dimmed("Interfaces:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            let header: String = matrix.interfaces.iter().map(|interface| format!(" {:>10} ", interface)).collect();
+            writeln!(output, "     {:<30}{}", "", self.bold(&header))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for row in &matrix.rows {
+                let fastest = row.fastest();
+                let mut cells = String::new();
+                for interface in &matrix.interfaces {
+                    let cell = match row.avg_ms.get(interface) {
+                        Some(&avg_ms) if fastest == Some(interface.as_str()) => {
+                            self.colorize(&format!(" {:>10}*", self.format_duration(avg_ms)), self.color_scheme.success).to_string()
+                        }
+                        Some(&avg_ms) => format!(" {:>10} ", self.format_duration(avg_ms)),
+                        None => self.colorize(&format!(" {:>10} ", "-"), self.color_scheme.muted).to_string(),
+                    };
+                    cells.push_str(&cell);
+                }
+                writeln!(output, "  🔌 {:<30}{}", row.config_name, cells)
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+            if let Some((interface, wins)) = matrix.fastest_interface() {
+                writeln!(output, "     {}", self.dimmed(&format!("* fastest; {} was fastest for {} of {} configurations", interface, wins, matrix.rows.len())))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+        
+        let mut cold_starts: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.cold_start.as_ref().map(|split| (name, split)))
+            .collect();
+        if !cold_starts.is_empty() {
+            cold_starts.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("First Request vs Steady State:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in cold_starts {
+                let penalty = format!("{:+.0}ms", split.penalty_ms());
+                let penalty_color = if split.penalty_ms() > 0.0 {
+                    self.color_scheme.warning
+                } else {
+                    self.color_scheme.muted
+                };
+                writeln!(output, "  🧊 {:<30} first {} | steady {} | penalty {}",
+                    config_name,
+                    self.format_duration_colored(split.cold.total_avg_ms),
+                    self.format_duration_colored(split.warm.total_avg_ms),
+                    self.colorize(&penalty, penalty_color))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut cache_splits: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.cache_split.as_ref().map(|split| (name, split)))
+            .collect();
+        if !cache_splits.is_empty() {
+            cache_splits.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Cache Hits vs Misses:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in cache_splits {
+                writeln!(output, "  🗄️ {:<30} {}",
+                    config_name,
+                    cache_split_description(split, |ms| self.format_duration_colored(ms).to_string()))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut resumption_splits: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.tls_resumption.as_ref().map(|split| (name, split)))
+            .collect();
+        if !resumption_splits.is_empty() {
+            resumption_splits.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("TLS Resumed vs Full Handshakes:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in resumption_splits {
+                writeln!(output, "  🔐 {:<30} {}",
+                    config_name,
+                    tls_resumption_description(split, |ms| self.format_duration_colored(ms).to_string()))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut timeouts: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.timeouts.as_ref().map(|timeouts| (name, timeouts)))
+            .collect();
+        if !timeouts.is_empty() {
+            timeouts.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Timeouts:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, summary) in timeouts {
+                writeln!(output, "  ⏱️ {:<30} {}",
+                    config_name,
+                    self.colorize(&timeout_description(summary), self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut sizes: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.response_size.as_ref().map(|size| (name, size)))
+            .collect();
+        if !sizes.is_empty() {
+            sizes.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Response Size:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, size) in sizes {
+                let color = if size.is_uncompressed() { self.color_scheme.warning } else { self.color_scheme.info };
+                writeln!(output, "  📦 {:<30} {}",
+                    config_name,
+                    self.colorize(&response_size_description(size), color))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut sockets: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.socket.as_ref().map(|socket| (name, socket)))
+            .collect();
+        if !sockets.is_empty() {
+            sockets.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("TCP Socket Stats:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, socket) in sockets {
+                let color = if socket.affected_percentage() >= RETRANSMIT_WARNING_PERCENT {
+                    self.color_scheme.warning
+                } else {
+                    self.color_scheme.info
+                };
+                writeln!(output, "  📡 {:<30} {}",
+                    config_name,
+                    self.colorize(&socket_description(socket, |ms| format!("{:.1}ms", ms)), color))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut uploads: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.upload.as_ref().map(|upload| (name, upload)))
+            .collect();
+        if !uploads.is_empty() {
+            uploads.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Upload:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, upload) in uploads {
+                writeln!(output, "  📤 {:<30} {}",
+                    config_name,
+                    self.colorize(&upload_description(upload, |ms| format!("{:.1}ms", ms)), self.color_scheme.info))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut informational: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.informational.as_ref().map(|informational| (name, informational)))
+            .collect();
+        if !informational.is_empty() {
+            informational.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Early Hints:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, informational) in informational {
+                let color = if informational.early_hints > 0 { self.color_scheme.success } else { self.color_scheme.muted };
+                writeln!(output, "  💡 {:<30} {}",
+                    config_name,
+                    self.colorize(&informational_description(informational, |ms| format!("{:.1}ms", ms)), color))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut throttled: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.reliability.throttling.as_ref().map(|throttling| (name, throttling)))
+            .collect();
+        if !throttled.is_empty() {
+            throttled.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Throttling:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, throttling) in throttled {
+                writeln!(output, "  🚦 {:<30} {}",
+                    config_name,
+                    self.colorize(&throttling_description(throttling), self.color_scheme.warning))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut server_timing: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.server_timing.as_ref().map(|server_timing| (name, server_timing)))
+            .filter(|_| self.options.verbose_mode)
+            .collect();
+        if !server_timing.is_empty() {
+            server_timing.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("Server Timing:"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, server_timing) in server_timing {
+                writeln!(output, "  🧮 {:<30} {}",
+                    config_name,
+                    self.colorize(&server_timing_description(server_timing, |ms| format!("{:.1}ms", ms)), self.color_scheme.info))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                for phase in &server_timing.phases {
+                    writeln!(output, "     {:<28} {} {}",
+                        phase.description.as_deref().unwrap_or(&phase.name),
+                        self.format_duration_colored(phase.avg_ms),
+                        self.dimmed(&format!("({}/{})", phase.samples, server_timing.samples)))
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+            }
+        }
+
         if self.options.verbose_mode && !analysis.basic_stats.is_empty() {
             writeln!(output, "\n{}", self.dimmed("Detailed Analysis:"))
                 .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
@@ -495,6 +942,7 @@ impl OutputFormatter for ColoredFormatter {
         Ok(output)
     }
 
+    #[cfg(feature = "diagnostics")]
     fn format_diagnostics_report(&self, report: &DiagnosticReport) -> Result<String> {
         let mut output = String::new();
         
@@ -548,6 +996,54 @@ impl OutputFormatter for ColoredFormatter {
                     .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
             }
         }
+
+        if !report.connectivity_diagnostics.traceroutes.is_empty() {
+            writeln!(output, "\n{}", self.dimmed("Routes:"))
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for trace in &report.connectivity_diagnostics.traceroutes {
+                let icon = if trace.reached() { "✅" } else { "❌" };
+                writeln!(output, "  {} {}", icon, trace)
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+                if self.options.verbose_mode {
+                    for hop in trace.path() {
+                        writeln!(output, "     {}", self.dimmed(&hop.to_string()))
+                            .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+                    }
+                }
+            }
+        }
+
+        let mut resolutions: Vec<_> = report.dns_diagnostics.resolution_performance.iter()
+            .filter(|(_, result)| result.success)
+            .collect();
+        if self.options.verbose_mode && !resolutions.is_empty() {
+            resolutions.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("DNS Resolution:"))
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for (key, result) in resolutions {
+                let breakdown = if result.record_latencies.is_empty() {
+                    String::new()
+                } else {
+                    let records: Vec<_> = result.record_latencies.iter().map(|latency| latency.to_string()).collect();
+                    format!(" ({})", records.join(", "))
+                };
+                writeln!(output, "  🔎 {} - {}{}", key, self.format_duration(result.duration.as_secs_f64() * 1000.0), self.dimmed(&breakdown))
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            }
+        }
+
+        let mut doh_timings: Vec<_> = report.dns_diagnostics.doh_analysis.values()
+            .filter_map(|analysis| analysis.timing.as_ref().map(|timing| (&analysis.provider_name, timing)))
+            .collect();
+        if !doh_timings.is_empty() {
+            doh_timings.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\n{}", self.dimmed("DoH Providers:"))
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for (provider, timing) in doh_timings {
+                writeln!(output, "  🔐 {} - {}", provider, self.dimmed(&timing.to_string()))
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            }
+        }
         
         Ok(output)
     }
@@ -603,10 +1099,80 @@ impl OutputFormatter for ColoredFormatter {
         Ok(output)
     }
 
+    fn format_slowest_requests(&self, samples: &[SampleRef<'_>]) -> Result<String> {
+        let mut output = String::new();
+
+        writeln!(output, "{}", self.create_section_header("Slowest Requests", "🐢"))
+            .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+
+        if samples.is_empty() {
+            writeln!(output, "{}", self.dimmed("No requests recorded."))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            return Ok(output);
+        }
+
+        for (index, sample) in samples.iter().enumerate() {
+            let metrics = sample.metrics;
+            let status = format!("{:?}", metrics.status);
+            let status_colored = if metrics.is_successful() {
+                self.colorize(&status, self.color_scheme.success)
+            } else {
+                self.colorize(&status, self.color_scheme.error)
+            };
+
+            writeln!(output, "{:>2}. {}  {}  {} @ {}",
+                index + 1,
+                self.format_duration_colored(metrics.total_ms()),
+                status_colored,
+                self.colorize(sample.config_name, self.color_scheme.info),
+                display_url(sample.url))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            writeln!(output, "    {}", phase_breakdown(metrics, |ms| self.format_duration(ms)))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            writeln!(output, "    {}", self.dimmed(&sample_origin(metrics)))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            if let Some(phases) = server_phases(metrics, |ms| self.format_duration(ms)).filter(|_| self.options.verbose_mode) {
+                writeln!(output, "    {}", self.colorize(&phases, self.color_scheme.info))
+                    .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            }
+            if let Some(ref error) = metrics.error_message {
+                writeln!(output, "    ❌ {}", self.colorize(error, self.color_scheme.error))
+                    .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn format_error_summary(&self, groups: &[ErrorGroup]) -> Result<String> {
+        let mut output = String::new();
+
+        writeln!(output, "{}", self.create_section_header("Errors", "❌"))
+            .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+
+        for group in groups {
+            writeln!(output, "  {}", self.colorize(&group.to_string(), self.color_scheme.error))
+                .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+            writeln!(output, "    {}", self.dimmed(&format!("in {}", error_sources(group))))
+                .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+        }
+
+        Ok(output)
+    }
+
+    fn format_reproduction(&self, reproduction: &Reproduction) -> Result<String> {
+        Ok(format!(
+            "{} {}\n{}",
+            self.dimmed("Reproduce:"),
+            reproduction,
+            self.dimmed(&format!("Config hash: {}", reproduction.config_hash))
+        ))
+    }
+
     fn format_quick_summary(&self, results: &ExecutionResults) -> Result<String> {
         let success_colored = self.format_percentage_colored(results.execution_summary.success_rate);
         let duration_colored = self.format_duration_colored(results.execution_summary.total_duration.as_secs_f64() * 1000.0);
-        let best_config = results.best_config().unwrap_or("Unknown");
+        let best_config = results.best_config().unwrap_or("no clear winner");
 
         Ok(format!(
             "📊 {}/{} {} | 🏆 {} | ⏱️ {}",
@@ -626,12 +1192,16 @@ impl OutputFormatter for ColoredFormatter {
             self.dimmed("N/A").to_string()
         };
 
-        Ok(format!(
+        let mut line = format!(
             "🧪 {}: {} success, {} avg",
             self.colorize(&result.config_name, self.color_scheme.info),
             success_colored,
             avg_response
-        ))
+        );
+        if let Some(rtt) = result.ping_rtt_ms() {
+            line.push_str(&format!(", {} ping", self.format_duration_colored(rtt)));
+        }
+        Ok(line)
     }
 
     fn format_error(&self, error: &str) -> Result<String> {
@@ -0,0 +1,142 @@
+//! One CSV row per request
+//!
+//! The tables and exports summarize each target and configuration; checking
+//! a summary in a spreadsheet, or plotting latency over the course of a run,
+//! needs the requests themselves. [`CsvFormatter`] writes every measurement
+//! with its configuration, URL, phase timings, HTTP status and timestamps,
+//! streaming the rows so that a long run is not held in memory twice.
+
+use crate::{
+    error::{AppError, Result},
+    executor::ExecutionResults,
+    models::metrics::{TestResult, TimingMetrics},
+};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Columns of every CSV export
+pub const CSV_HEADER: &str = "config,url,status,started_at,completed_at,dns_ms,tcp_ms,tls_ms,first_byte_ms,total_ms,http_status,remote_ip,first_use,error";
+
+/// Writes individual measurements as CSV rows, ordered by configuration, URL and start time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvFormatter;
+
+impl CsvFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Write the header and a row per request to `writer`
+    pub fn write<W: Write>(&self, results: &ExecutionResults, mut writer: W) -> io::Result<()> {
+        let mut tests: Vec<&TestResult> = results.test_results.values().collect();
+        tests.sort_by(|a, b| a.config_name.cmp(&b.config_name).then_with(|| a.url.cmp(&b.url)));
+
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for result in tests {
+            let mut samples: Vec<&TimingMetrics> = result.individual_results.iter().collect();
+            samples.sort_by_key(|metrics| metrics.start_time());
+            for metrics in samples {
+                writeln!(writer, "{}", Self::row(result, metrics))?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// The whole export as a string
+    pub fn format(&self, results: &ExecutionResults) -> String {
+        let mut csv = Vec::new();
+        self.write(results, &mut csv).expect("writing to memory cannot fail");
+        String::from_utf8(csv).expect("CSV rows are UTF-8")
+    }
+
+    /// Write the export to the file at `path`
+    pub fn save(&self, results: &ExecutionResults, path: &Path) -> Result<()> {
+        let write_error = |e: io::Error| AppError::io(format!("Failed to write results to {}: {}", path.display(), e)).with_source(e);
+        let file = std::fs::File::create(path).map_err(write_error)?;
+        self.write(results, BufWriter::new(file)).map_err(write_error)
+    }
+
+    fn row(result: &TestResult, metrics: &TimingMetrics) -> String {
+        // Phases estimated from the total are left empty, like phases a request never reached
+        let phase = |ms: Option<f64>| match ms {
+            Some(ms) if !metrics.estimated_phases => format!("{:.3}", ms),
+            _ => String::new(),
+        };
+        let fields = [
+            csv_field(&result.config_name),
+            csv_field(&result.url),
+            format!("{:?}", metrics.status).to_lowercase(),
+            metrics.start_time().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            metrics.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            phase(Some(metrics.dns_ms())),
+            phase(Some(metrics.tcp_ms())),
+            phase(metrics.tls_ms()),
+            phase(Some(metrics.first_byte_ms())),
+            format!("{:.3}", metrics.total_ms()),
+            metrics.http_status.to_string(),
+            metrics.resolved_ip.map_or(String::new(), |ip| ip.to_string()),
+            metrics.is_first_use.to_string(),
+            csv_field(metrics.error_message.as_deref().unwrap_or_default()),
+        ];
+        fields.join(",")
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{executor::ExecutionSummary, types::DnsConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn test_csv_export_has_a_row_per_request_with_timestamps() {
+        let started = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let total = Duration::from_millis(250);
+        let mut sample = TimingMetrics::success(Duration::from_millis(20), Duration::ZERO, None, total, total, 200).with_started_at(started);
+        sample.timestamp = started + chrono::Duration::milliseconds(250);
+        let mut failed = TimingMetrics::failed("refused, \"closed\"".to_string()).with_total_duration(Duration::from_millis(5));
+        failed.timestamp = started + chrono::Duration::seconds(1);
+        assert_eq!(failed.start_time(), started + chrono::Duration::milliseconds(995));
+
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://a.example".to_string());
+        result.add_measurement(failed);
+        result.add_measurement(sample);
+        let summary = ExecutionSummary {
+            total_duration: Duration::from_secs(1),
+            total_tests: 2,
+            successful_tests: 1,
+            failed_tests: 1,
+            success_rate: 50.0,
+            ..Default::default()
+        };
+        let results = ExecutionResults::new(summary, vec![result]);
+        let csv = CsvFormatter::new().format(&results);
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1], "System DNS,https://a.example,success,2024-01-01T12:00:00.000Z,2024-01-01T12:00:00.250Z,20.000,0.000,,250.000,250.000,200,,false,");
+        assert!(lines[2].starts_with("System DNS,https://a.example,failed,2024-01-01T12:00:00.995Z,2024-01-01T12:00:01.000Z,"));
+        assert!(lines[2].ends_with(",\"refused, \"\"closed\"\"\""), "{}", lines[2]);
+
+        // Estimated phases are not exported as if they were measured
+        let mut result = TestResult::new("DoH".to_string(), DnsConfig::System, "https://a.example".to_string());
+        result.add_measurement(TimingMetrics::success(Duration::from_millis(25), Duration::from_millis(50), Some(Duration::from_millis(62)), Duration::from_millis(113), total, 200)
+            .with_started_at(started)
+            .with_estimated_phases(true));
+        let estimated = CsvFormatter::new().format(&ExecutionResults::new(ExecutionSummary::default(), vec![result]));
+        assert!(estimated.lines().nth(1).unwrap().contains("Z,,,,,250.000,200,"), "{}", estimated);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        CsvFormatter::new().save(&results, file.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), csv);
+    }
+}
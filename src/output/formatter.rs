@@ -4,12 +4,17 @@
 //! a plain text implementation with table formatting capabilities.
 
 use crate::{
-    error::{AppError, Result},
-    executor::{ExecutionResults, ExecutionSummary},
-    models::metrics::TestResult,
-    stats::StatisticalAnalysis,
-    diagnostics::DiagnosticReport,
+    error::{AppError, ErrorGroup, Result},
+    executor::{CycleDrift, ExecutionResults, ExecutionSummary, ResultKey, SampleRef, SystemSnapshot},
+    models::{metrics::{TestResult, TimingMetrics}, Reproduction},
+    network::NetworkProfile,
+    stats::{CacheSplit, ExtendedStatistics, TlsResumptionSplit, ResponseSizeSummary, InformationalSummary, ServerTimingSummary, SocketSummary, StatisticalAnalysis, ThrottlingSummary, TimeoutSummary, UploadSummary},
+    timing::ClockReport,
+    types::{MetricColumn, SortKey, UserAgent},
+    utils::{size::format_bytes, url::display_url},
 };
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::DiagnosticReport;
 use std::collections::HashMap;
 use std::fmt::Write as _;
 
@@ -21,17 +26,28 @@ pub trait OutputFormatter {
     /// Format execution summary
     fn format_execution_summary(&self, summary: &ExecutionSummary) -> Result<String>;
     
-    /// Format performance results as a table
-    fn format_performance_table(&self, results: &HashMap<String, TestResult>) -> Result<String>;
+    /// Format performance results as a table, ordered and trimmed by `view`,
+    /// with each row's change since earlier watch cycles when `drift` is given
+    fn format_performance_table(&self, results: &HashMap<ResultKey, TestResult>, view: &TableView, drift: Option<&CycleDrift>) -> Result<String>;
     
     /// Format statistical analysis
     fn format_statistical_analysis(&self, analysis: &StatisticalAnalysis) -> Result<String>;
     
     /// Format diagnostics report
+    #[cfg(feature = "diagnostics")]
     fn format_diagnostics_report(&self, report: &DiagnosticReport) -> Result<String>;
     
     /// Format recommendations
     fn format_recommendations(&self, results: &ExecutionResults) -> Result<String>;
+
+    /// Format the slowest individual requests with their phase breakdown
+    fn format_slowest_requests(&self, samples: &[SampleRef<'_>]) -> Result<String>;
+
+    /// Format failures grouped by identical error message
+    fn format_error_summary(&self, groups: &[ErrorGroup]) -> Result<String>;
+
+    /// Format the footer with the command line that reproduces the run
+    fn format_reproduction(&self, reproduction: &Reproduction) -> Result<String>;
     
     /// Format a quick summary for progress updates
     fn format_quick_summary(&self, results: &ExecutionResults) -> Result<String>;
@@ -49,6 +65,503 @@ pub trait OutputFormatter {
     fn format_success(&self, message: &str) -> Result<String>;
 }
 
+/// Which rows the performance table shows, and in what order
+#[derive(Debug, Clone, Default)]
+pub struct TableView {
+    /// Metric to rank each target's configurations by; unset keeps the formatter's own order
+    pub sort_by: Option<SortKey>,
+    /// Show only the best and the worst N configurations of each target and summarize the rest
+    pub top: Option<usize>,
+    /// Overall ranking score of each configuration, by name, for sorting by score
+    pub scores: HashMap<String, f64>,
+    /// Metric columns to show; empty means the defaults for the verbosity
+    pub columns: Vec<MetricColumn>,
+}
+
+/// A row of the performance table
+#[derive(Debug)]
+pub enum TableEntry<'a> {
+    Result(&'a TestResult),
+    /// Configurations between the best and the worst `--top`, summarized in one row
+    Omitted(OmittedRows),
+}
+
+/// Summary of the configurations `--top` leaves out of a target's table
+#[derive(Debug, Clone, PartialEq)]
+pub struct OmittedRows {
+    pub count: usize,
+    /// Lowest and highest mean response time, if any of them succeeded
+    pub mean_ms: Option<(f64, f64)>,
+    /// Lowest and highest success rate
+    pub success_rate: (f64, f64),
+}
+
+impl TableView {
+    pub fn new(sort_by: Option<SortKey>, top: Option<usize>) -> Self {
+        Self { sort_by, top, ..Self::default() }
+    }
+
+    /// Show these metric columns, in order, instead of the defaults
+    pub fn with_columns(mut self, columns: Vec<MetricColumn>) -> Self {
+        self.columns = Vec::with_capacity(columns.len());
+        for column in columns {
+            if !self.columns.contains(&column) {
+                self.columns.push(column);
+            }
+        }
+        self
+    }
+
+    /// Metric columns to show, those chosen or else the defaults for the
+    /// verbosity, followed by the early hints column when any of `results`
+    /// got an informational response and the ping column when any was pinged
+    pub fn columns<'a>(&self, verbose: bool, results: impl IntoIterator<Item = &'a TestResult>) -> Vec<MetricColumn> {
+        let mut columns = match (self.columns.is_empty(), verbose) {
+            (false, _) => return self.columns.clone(),
+            (true, false) => MetricColumn::DEFAULT.to_vec(),
+            (true, true) => MetricColumn::VERBOSE.to_vec(),
+        };
+        let results: Vec<&TestResult> = results.into_iter().collect();
+        let informed = results.iter()
+            .flat_map(|result| &result.individual_results)
+            .any(|metrics| metrics.informational.as_ref().is_some_and(|informational| informational.first.is_some()));
+        if informed {
+            columns.push(MetricColumn::EarlyHints);
+        }
+        if results.iter().any(|result| !result.ping.is_empty()) {
+            columns.push(MetricColumn::Ping);
+        }
+        columns
+    }
+
+    /// Take the ranking scores to sort by from a statistical analysis
+    pub fn with_scores(mut self, analysis: Option<&StatisticalAnalysis>) -> Self {
+        self.scores = analysis
+            .map(|analysis| analysis.comparative_analysis.performance_rankings.iter()
+                .map(|ranking| (ranking.config_name.clone(), ranking.score))
+                .collect())
+            .unwrap_or_default();
+        self
+    }
+
+    /// Order one target's results, already in the formatter's own order, and
+    /// fold all but the best and the worst `top` into one row
+    pub fn arrange<'a>(&self, mut results: Vec<&'a TestResult>) -> Vec<TableEntry<'a>> {
+        if self.sort_by.is_none() && self.top.is_none() {
+            return results.into_iter().map(TableEntry::Result).collect();
+        }
+        // Trimming keeps the extremes of a metric, so it needs an order by one
+        let key = self.sort_by.unwrap_or_default();
+        results.sort_by(|a, b| {
+            lower_first(self.rank_value(key, a), self.rank_value(key, b))
+                .then_with(|| lower_first(mean_ms(a), mean_ms(b)))
+        });
+
+        let Some(top) = self.top.filter(|top| results.len() > top * 2) else {
+            return results.into_iter().map(TableEntry::Result).collect();
+        };
+        let worst = results.split_off(results.len() - top);
+        let omitted = results.split_off(top);
+        let means: Vec<f64> = omitted.iter().filter_map(|result| mean_ms(result)).collect();
+        let rates: Vec<f64> = omitted.iter().map(|result| result.success_rate()).collect();
+        let summary = OmittedRows {
+            count: omitted.len(),
+            mean_ms: (!means.is_empty()).then(|| (means.iter().copied().fold(f64::INFINITY, f64::min), means.iter().copied().fold(f64::NEG_INFINITY, f64::max))),
+            success_rate: (rates.iter().copied().fold(f64::INFINITY, f64::min), rates.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        };
+
+        results.into_iter().map(TableEntry::Result)
+            .chain(std::iter::once(TableEntry::Omitted(summary)))
+            .chain(worst.into_iter().map(TableEntry::Result))
+            .collect()
+    }
+
+    /// Value of `key` for a result, lower meaning better
+    fn rank_value(&self, key: SortKey, result: &TestResult) -> Option<f64> {
+        match key {
+            SortKey::Mean => mean_ms(result),
+            SortKey::P95 => result.percentile_ms(95.0),
+            SortKey::Success => Some(-result.success_rate()),
+            SortKey::Score => self.scores.get(result.config_name.as_str()).map(|score| -score),
+        }
+    }
+}
+
+fn mean_ms(result: &TestResult) -> Option<f64> {
+    result.statistics.as_ref().map(|stats| stats.total_avg_ms)
+}
+
+/// Order values ascending, with missing values last
+fn lower_first(a: Option<f64>, b: Option<f64>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+/// Table layout of a metric column
+pub(super) fn metric_column(metric: MetricColumn) -> Column {
+    let (header, width, alignment) = match metric {
+        MetricColumn::Dns => ("DNS", 10, Alignment::Right),
+        MetricColumn::Tcp => ("TCP", 10, Alignment::Right),
+        MetricColumn::Ttfb => ("TTFB", 10, Alignment::Right),
+        MetricColumn::Total => ("Avg Response", 12, Alignment::Right),
+        MetricColumn::MinMax => ("Min/Max", 15, Alignment::Right),
+        MetricColumn::P95 => ("P95", 10, Alignment::Right),
+        MetricColumn::Success => ("Success Rate", 12, Alignment::Right),
+        MetricColumn::Level => ("Performance", 12, Alignment::Center),
+        MetricColumn::EarlyHints => ("1xx", 10, Alignment::Right),
+        MetricColumn::Ping => ("Ping RTT", 10, Alignment::Right),
+    };
+    Column {
+        header: header.to_string(),
+        alignment,
+        min_width: width,
+        max_width: width.max(15),
+        flexible: false,
+    }
+}
+
+/// Plain text of a metric for one result
+pub(super) fn metric_cell(
+    metric: MetricColumn,
+    result: &TestResult,
+    format_duration: impl Fn(f64) -> String,
+    format_percentage: impl Fn(f64) -> String,
+) -> String {
+    let stat = |value: fn(&crate::models::metrics::Statistics) -> f64| {
+        result.statistics.as_ref().map_or_else(|| "N/A".to_string(), |stats| format_duration(value(stats)))
+    };
+    match metric {
+        MetricColumn::Dns => stat(|stats| stats.dns_avg_ms),
+        MetricColumn::Tcp => stat(|stats| stats.tcp_avg_ms),
+        MetricColumn::Ttfb => stat(|stats| stats.first_byte_avg_ms),
+        MetricColumn::Total => stat(|stats| stats.total_avg_ms),
+        MetricColumn::MinMax => result.statistics.as_ref().map_or_else(
+            || "N/A".to_string(),
+            |stats| format!("{}/{}", format_duration(stats.total_min_ms), format_duration(stats.total_max_ms)),
+        ),
+        MetricColumn::P95 => result.percentile_ms(95.0).map_or_else(|| "N/A".to_string(), &format_duration),
+        MetricColumn::Success => format_percentage(result.success_rate()),
+        MetricColumn::Level => result.performance_level()
+            .map(|level| format!("{:?}", level))
+            .unwrap_or_else(|| "Unknown".to_string()),
+        MetricColumn::EarlyHints => {
+            let checked: Vec<_> = result.individual_results.iter().filter_map(|metrics| metrics.informational.as_ref()).collect();
+            let firsts: Vec<f64> = checked.iter().filter_map(|informational| informational.first).map(|first| first.as_secs_f64() * 1000.0).collect();
+            match (checked.is_empty(), firsts.is_empty()) {
+                (true, _) => "N/A".to_string(),
+                (false, true) => "-".to_string(),
+                (false, false) => format_duration(firsts.iter().sum::<f64>() / firsts.len() as f64),
+            }
+        }
+        MetricColumn::Ping => match (result.ping.is_empty(), result.ping_rtt_ms()) {
+            (true, _) => "N/A".to_string(),
+            (false, None) => "-".to_string(),
+            (false, Some(rtt)) => format_duration(rtt),
+        },
+    }
+}
+
+/// Plain text of a metric for the row summarizing omitted results; only
+/// success rate and mean response time are summarized
+pub(super) fn omitted_cell(
+    metric: MetricColumn,
+    omitted: &OmittedRows,
+    format_duration: impl Fn(f64) -> String,
+    format_percentage: impl Fn(f64) -> String,
+) -> String {
+    match metric {
+        MetricColumn::Success => format!("{}-{}", format_percentage(omitted.success_rate.0), format_percentage(omitted.success_rate.1)),
+        MetricColumn::Total => omitted.mean_ms.map_or_else(
+            || "N/A".to_string(),
+            |(fastest, slowest)| format!("{}-{}", format_duration(fastest), format_duration(slowest)),
+        ),
+        _ => String::new(),
+    }
+}
+
+impl OmittedRows {
+    /// Label for the summary row
+    pub fn label(&self) -> String {
+        format!("… {} more configuration{}", self.count, if self.count == 1 { "" } else { "s" })
+    }
+}
+
+/// Where an error group occurred, listing at most three sources
+pub(super) fn error_sources(group: &ErrorGroup) -> String {
+    const MAX_SOURCES: usize = 3;
+    let mut sources = group.sources.iter().take(MAX_SOURCES).cloned().collect::<Vec<_>>().join(", ");
+    if group.sources.len() > MAX_SOURCES {
+        sources.push_str(&format!(" and {} more", group.sources.len() - MAX_SOURCES));
+    }
+    sources
+}
+
+/// Format a percentile, marked with `≥` when censored timeouts make it a lower bound
+pub(super) fn percentile_text(stats: &ExtendedStatistics, name: &str, format_duration: impl Fn(f64) -> String) -> String {
+    match stats.percentiles.get(name) {
+        Some(&value) if stats.timeouts.as_ref().is_some_and(|t| t.is_lower_bound(value)) => {
+            format!("≥{}", format_duration(value))
+        }
+        Some(&value) => format_duration(value),
+        None => "N/A".to_string(),
+    }
+}
+
+/// Describe the timeouts of a configuration, e.g. `2 timed out (20.0%), mostly while connecting`
+pub(super) fn timeout_description(timeouts: &TimeoutSummary) -> String {
+    let mut description = format!("{} timed out ({:.1}%)", timeouts.count, timeouts.rate);
+    if let Some(phase) = timeouts.phase {
+        description.push_str(&format!(", mostly while {}", phase));
+    }
+    description
+}
+
+/// Describe the cache hits and misses of a configuration, e.g.
+/// `HIT 12 avg 40.0ms | MISS 3 avg 310.0ms | miss penalty +270.0ms`
+pub(super) fn cache_split_description(split: &CacheSplit, format_duration: impl Fn(f64) -> String) -> String {
+    let mut parts = Vec::new();
+    for (label, stats) in [("HIT", &split.hit), ("MISS", &split.miss)] {
+        match stats {
+            Some(stats) => parts.push(format!("{} {} avg {}", label, stats.sample_count, format_duration(stats.total_avg_ms))),
+            None => parts.push(format!("no {}", if label == "HIT" { "hits" } else { "misses" })),
+        }
+    }
+    if let Some(penalty) = split.miss_penalty_ms() {
+        parts.push(format!("miss penalty {:+.1}ms", penalty));
+    }
+    parts.join(" | ")
+}
+
+/// Describe the resumed and full TLS handshakes of a configuration, e.g.
+/// `resumed 9 avg 80.0ms | full 1 avg 140.0ms | resumption saves 60.0ms`
+pub(super) fn tls_resumption_description(split: &TlsResumptionSplit, format_duration: impl Fn(f64) -> String) -> String {
+    let mut parts = Vec::new();
+    for (label, stats) in [("resumed", &split.resumed), ("full", &split.full)] {
+        match stats {
+            Some(stats) => parts.push(format!("{} {} avg {}", label, stats.sample_count, format_duration(stats.total_avg_ms))),
+            None => parts.push(format!("no {}", if label == "resumed" { "resumed handshakes" } else { "full handshakes" })),
+        }
+    }
+    if let Some(saving) = split.resumption_saving_ms() {
+        parts.push(format!("resumption saves {:.1}ms", saving));
+    }
+    parts.join(" | ")
+}
+
+/// Describe the TCP statistics of a configuration, e.g.
+/// `RTT 24.1ms | 5 retransmits in 3/50 requests (6.0%) | +210.0ms when retransmitting`
+pub(super) fn socket_description(socket: &SocketSummary, format_duration: impl Fn(f64) -> String) -> String {
+    let mut parts = vec![format!("RTT {}", format_duration(socket.avg_rtt_ms))];
+    if socket.retransmits == 0 {
+        parts.push("no retransmits".to_string());
+    } else {
+        parts.push(format!(
+            "{} retransmits in {}/{} requests ({:.1}%)",
+            socket.retransmits, socket.affected_requests, socket.samples, socket.affected_percentage()
+        ));
+    }
+    if let Some(penalty) = socket.retransmit_penalty_ms() {
+        parts.push(format!("{:+.1}ms when retransmitting", penalty));
+    }
+    parts.join(" | ")
+}
+
+/// Describe the request bodies a configuration sent, e.g.
+/// `1.0 MiB | send 84.2ms (99.6 Mbit/s) | ack 12.5ms`
+pub(super) fn upload_description(upload: &UploadSummary, format_duration: impl Fn(f64) -> String) -> String {
+    let mut send = format!("send {}", format_duration(upload.send_avg_ms));
+    if let Some(throughput) = upload.throughput_mbps() {
+        send.push_str(&format!(" ({:.1} Mbit/s)", throughput));
+    }
+    [format_bytes(upload.bytes as f64), send, format!("ack {}", format_duration(upload.ack_avg_ms))].join(" | ")
+}
+
+/// Describe the informational responses of a configuration, e.g.
+/// `103 in 10/10 requests | first 1xx 12.4ms | final 85.3ms | 72.9ms ahead`
+pub(super) fn informational_description(informational: &InformationalSummary, format_duration: impl Fn(f64) -> String) -> String {
+    let mut parts = Vec::new();
+    if informational.early_hints > 0 {
+        parts.push(format!("103 in {}/{} requests", informational.early_hints, informational.samples));
+    } else if informational.informed > 0 {
+        parts.push(format!("1xx in {}/{} requests, no 103", informational.informed, informational.samples));
+    } else {
+        parts.push(format!("no 1xx in {} requests", informational.samples));
+    }
+    if let Some(first) = informational.first_avg_ms {
+        parts.push(format!("first 1xx {}", format_duration(first)));
+    }
+    parts.push(format!("final {}", format_duration(informational.response_avg_ms)));
+    if let Some(lead) = informational.lead_avg_ms {
+        parts.push(format!("{} ahead", format_duration(lead)));
+    }
+    parts.join(" | ")
+}
+
+/// Describe the throttled requests of a configuration, e.g.
+/// `3 throttled (15.0%) | 429 ×2, 503 ×1 | Retry-After up to 120s`
+pub(super) fn throttling_description(throttling: &ThrottlingSummary) -> String {
+    let mut statuses = Vec::new();
+    if throttling.too_many_requests > 0 {
+        statuses.push(format!("429 ×{}", throttling.too_many_requests));
+    }
+    if throttling.unavailable > 0 {
+        statuses.push(format!("503 ×{}", throttling.unavailable));
+    }
+    let mut parts = vec![format!("{} throttled ({:.1}%)", throttling.count, throttling.rate), statuses.join(", ")];
+    if let Some(seconds) = throttling.retry_after_max_secs {
+        parts.push(format!("Retry-After up to {}s", seconds));
+    }
+    parts.join(" | ")
+}
+
+/// Set what servers reported against the whole request, e.g.
+/// `server 47.2ms | network 38.1ms | total 85.3ms in 10 responses`
+pub(super) fn server_timing_description(server_timing: &ServerTimingSummary, format_duration: impl Fn(f64) -> String) -> String {
+    [
+        format!("server {}", format_duration(server_timing.server_avg_ms)),
+        format!("network {}", format_duration(server_timing.network_avg_ms())),
+        format!("total {} in {} responses", format_duration(server_timing.total_avg_ms), server_timing.samples),
+    ].join(" | ")
+}
+
+/// The phases a response's `Server-Timing` header reported, e.g.
+/// `Server: Cache Read 23.2ms | db 53.0ms | app`, or `None` without one
+pub(super) fn server_phases(metrics: &TimingMetrics, format_duration: impl Fn(f64) -> String) -> Option<String> {
+    if metrics.server_timing.is_empty() {
+        return None;
+    }
+    let phases: Vec<String> = metrics.server_timing.iter()
+        .map(|timing| match timing.duration_ms {
+            Some(ms) => format!("{} {}", timing.label(), format_duration(ms)),
+            None => timing.label().to_string(),
+        })
+        .collect();
+    Some(format!("Server: {}", phases.join(" | ")))
+}
+
+/// Describe the response bodies of a configuration, e.g. `avg 4.1 KiB | p95 4.3 KiB | gzip 3.8x`
+pub(super) fn response_size_description(size: &ResponseSizeSummary) -> String {
+    let mut parts = vec![
+        format!("avg {}", format_bytes(size.avg_bytes)),
+        format!("p95 {}", format_bytes(size.p95_bytes)),
+    ];
+    if size.encodings.is_empty() {
+        parts.push("uncompressed".to_string());
+    } else {
+        let mut encoding = size.encodings.join("/");
+        if size.compressed_percentage < 100.0 {
+            encoding.push_str(&format!(" on {:.0}%", size.compressed_percentage));
+        }
+        if let Some(ratio) = size.compression_ratio {
+            encoding.push_str(&format!(" {:.1}x", ratio));
+        }
+        parts.push(encoding);
+    }
+    parts.join(" | ")
+}
+
+/// Describe the phases of a single request, e.g. `DNS 5.0ms | TCP 20.0ms | First byte 80.0ms`,
+/// preceded by any redirects it followed, e.g. `301 42.0ms`
+///
+/// Phases estimated from the total are left out, as they say nothing about the request.
+pub(super) fn phase_breakdown(metrics: &TimingMetrics, format_duration: impl Fn(f64) -> String) -> String {
+    let mut phases: Vec<String> = metrics.redirects.iter()
+        .map(|hop| format!("{} {}", hop.status, format_duration(hop.duration.as_secs_f64() * 1000.0)))
+        .collect();
+    if metrics.estimated_phases {
+        phases.push("DNS, TCP, TLS and first byte not measured".to_string());
+    } else {
+        phases.push(format!("DNS {}", format_duration(metrics.dns_ms())));
+        phases.push(format!("TCP {}", format_duration(metrics.tcp_ms())));
+        if let Some(tls_ms) = metrics.tls_ms() {
+            phases.push(format!("TLS {}", format_duration(tls_ms)));
+        }
+        phases.push(format!("First byte {}", format_duration(metrics.first_byte_ms())));
+    }
+    if let Some(upload) = metrics.upload {
+        phases.push(format!("Upload {}", format_duration(upload.send.as_secs_f64() * 1000.0)));
+        phases.push(format!("Ack {}", format_duration(upload.ack.as_secs_f64() * 1000.0)));
+    }
+    if let Some(first) = metrics.informational.as_ref().and_then(|informational| informational.first) {
+        phases.push(format!("1xx {}", format_duration(first.as_secs_f64() * 1000.0)));
+    }
+    phases.join(" | ")
+}
+
+/// Warning shown when the test machine was busier than its CPU count
+pub(super) const OVERLOAD_WARNING: &str =
+    "load average exceeded the CPU core count; latencies may include time spent waiting for the CPU";
+
+/// Warning shown when connections were not reused the way the first-request split assumes
+pub(super) const CONNECTION_REUSE_WARNING: &str =
+    "connections were not reused as assumed; first-request and steady-state latencies both include some connection setup";
+
+/// Heading for the intervals in which the test machine was the bottleneck
+pub(super) fn interference_heading(count: usize) -> String {
+    format!(
+        "the test machine was the bottleneck in {} interval{}; samples from these periods may be skewed",
+        count,
+        if count == 1 { "" } else { "s" },
+    )
+}
+
+/// Warning for a run made on another network than the baseline
+pub(super) fn network_warning(baseline: &NetworkProfile) -> String {
+    format!("not on the baseline network ({}); drift compares with earlier runs on this network", baseline)
+}
+
+/// Warnings for a clock that was adjusted during the run or is off from NTP time
+pub(super) fn clock_warnings(clock: &ClockReport) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if clock.was_adjusted() {
+        warnings.push(format!(
+            "the system clock moved {:+.1}s during the run; timestamps follow the monotonic clock",
+            clock.drift_ms / 1000.0
+        ));
+    }
+    if let Some(offset) = clock.ntp_offset_ms.filter(|_| clock.is_offset()) {
+        warnings.push(format!(
+            "the system clock is {:+.1}s off NTP time; timestamps may not line up with other machines",
+            offset / 1000.0
+        ));
+    }
+    warnings
+}
+
+/// Describe the test machine, e.g. `4 cores | 1.5 GiB available | load 0.52 0.40 0.30 | linux/x86_64`
+pub(super) fn system_description(system: &SystemSnapshot) -> String {
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = 1024 * MIB;
+    let memory = if system.available_memory >= GIB {
+        format!("{:.1} GiB", system.available_memory as f64 / GIB as f64)
+    } else {
+        format!("{} MiB", system.available_memory / MIB)
+    };
+
+    let mut parts = vec![format!("{} cores", system.cpu_cores), format!("{} available", memory)];
+    if let Some([one, five, fifteen]) = system.load_average {
+        parts.push(format!("load {:.2} {:.2} {:.2}", one, five, fifteen));
+    }
+    parts.push(format!("{}/{}", system.os, system.arch));
+    parts.join(" | ")
+}
+
+/// Describe where and when a single request was made, e.g. `2024-01-01 12:00:00.123 UTC | IP 1.1.1.1 | HTTP 200`
+pub(super) fn sample_origin(metrics: &TimingMetrics) -> String {
+    let mut parts = vec![metrics.timestamp.format("%Y-%m-%d %H:%M:%S%.3f UTC").to_string()];
+    if let Some(ip) = metrics.resolved_ip {
+        parts.push(format!("IP {}", ip));
+    }
+    if metrics.http_status > 0 {
+        parts.push(format!("HTTP {}", metrics.http_status));
+    }
+    parts.join(" | ")
+}
+
 /// Configuration options for formatting
 #[derive(Debug, Clone)]
 pub struct FormattingOptions {
@@ -275,6 +788,72 @@ impl PlainFormatter {
         }
     }
 
+    /// Format the per-URL × per-configuration breakdown
+    fn format_url_breakdown(&self, analysis: &StatisticalAnalysis) -> Result<String> {
+        let mut output = String::new();
+
+        writeln!(output, "\nPer-URL Breakdown:")
+            .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+
+        let table_format = TableFormat {
+            columns: vec![
+                Column {
+                    header: "Configuration".to_string(),
+                    alignment: Alignment::Left,
+                    min_width: 15,
+                    max_width: 40,
+                    flexible: true,
+                },
+                Column {
+                    header: "Avg Response".to_string(),
+                    alignment: Alignment::Right,
+                    min_width: 12,
+                    max_width: 12,
+                    flexible: false,
+                },
+                Column {
+                    header: "P95".to_string(),
+                    alignment: Alignment::Right,
+                    min_width: 10,
+                    max_width: 10,
+                    flexible: false,
+                },
+                Column {
+                    header: "Success Rate".to_string(),
+                    alignment: Alignment::Right,
+                    min_width: 12,
+                    max_width: 12,
+                    flexible: false,
+                },
+            ],
+            show_borders: self.options.table_borders,
+            show_header: true,
+            min_column_width: 8,
+            max_column_width: 50,
+            padding: 1,
+        };
+
+        for breakdown in &analysis.url_breakdown {
+            writeln!(output, "{} (best: {})", display_url(&breakdown.url), breakdown.best_config.as_deref().unwrap_or("no clear winner"))
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+
+            let rows: Vec<RowData> = breakdown.ranked_configs()
+                .into_iter()
+                .map(|(config_name, stats)| vec![
+                    config_name.clone(),
+                    self.format_duration(stats.basic.total_avg_ms),
+                    percentile_text(stats, "p95", |ms| self.format_duration(ms)),
+                    self.format_percentage(stats.reliability.success_rate),
+                ])
+                .collect();
+
+            writeln!(output, "{}", self.create_table(&table_format, &rows)?)
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+        }
+
+        Ok(output)
+    }
+
     /// Format duration in human-readable format
     fn format_duration(&self, duration_ms: f64) -> String {
         if duration_ms < 1.0 {
@@ -336,53 +915,171 @@ impl OutputFormatter for PlainFormatter {
             .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
         write!(output, "Skipped:          {}", summary.skipped_tests)
             .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+
+        if let Some(ref simulation) = summary.simulation {
+            write!(output, "\nSimulated:        {}", simulation)
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(shard) = summary.shard {
+            write!(output, "\nShard:            {}", shard)
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(user_agent) = summary.user_agent.as_ref().filter(|user_agent| **user_agent != UserAgent::Tool) {
+            write!(output, "\nUser Agent:       {}", user_agent)
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+        }
+
+        if let Some(ref network) = summary.network {
+            write!(output, "\nNetwork:          {}", network)
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            if let Some(ref baseline) = summary.baseline_network {
+                write!(output, "\nWarning: {}", network_warning(baseline))
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if let Some(system) = summary.system.as_ref().filter(|_| self.options.verbose_mode) {
+            write!(output, "\nTest Machine:     {}", system_description(system))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            if system.is_overloaded() {
+                write!(output, "\nWarning: {}", OVERLOAD_WARNING)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if let Some(ref clock) = summary.clock {
+            if self.options.verbose_mode || clock.ntp_server.is_some() {
+                write!(output, "\nClock:            {}", clock)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+            for warning in clock_warnings(clock) {
+                write!(output, "\nWarning: {}", warning)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.interference.is_empty() {
+            write!(output, "\nWarning: {}", interference_heading(summary.interference.len()))
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for interval in &summary.interference {
+                write!(output, "\n  {}", interval)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.connection_reuse.is_empty() {
+            write!(output, "\nConnection Reuse:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, reuse) in &summary.connection_reuse {
+                write!(output, "\n  {:<30} {}", config_name, reuse)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+            if summary.connection_reuse.values().any(|reuse| !reuse.matches_first_use()) {
+                write!(output, "\nWarning: {}", CONNECTION_REUSE_WARNING)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.resolver_failover.is_empty() {
+            write!(output, "\nResolver Failover:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, failover) in &summary.resolver_failover {
+                write!(output, "\n  {:<30} {}", config_name, failover)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.server_distribution.is_empty() {
+            write!(output, "\nServer Distribution:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for (config_name, distribution) in &summary.server_distribution {
+                write!(output, "\n  {:<30} {}", config_name, distribution)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.packet_captures.is_empty() {
+            write!(output, "\nPacket Captures:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for capture in &summary.packet_captures {
+                write!(output, "\n  {}", capture)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.packet_loss.is_empty() {
+            write!(output, "\nPacket Loss:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for report in &summary.packet_loss {
+                write!(output, "\n  {:<30} {}: {}", report.config_name, report.target, report)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.public_baseline.is_empty() {
+            write!(output, "\nPublic Baseline:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for comparison in &summary.public_baseline {
+                write!(output, "\n  {:<30} {}", comparison.target, comparison)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
+
+        if !summary.physical_bounds.is_empty() {
+            write!(output, "\nSpeed of Light:")
+                .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            for bound in &summary.physical_bounds {
+                write!(output, "\n  {:<30} {}", format!("{} ({})", bound.target, bound.ip), bound)
+                    .map_err(|e| AppError::io(format!("Failed to format summary: {}", e)))?;
+            }
+        }
         
         Ok(output)
     }
 
-    fn format_performance_table(&self, results: &HashMap<String, TestResult>) -> Result<String> {
+    fn format_performance_table(&self, results: &HashMap<ResultKey, TestResult>, view: &TableView, drift: Option<&CycleDrift>) -> Result<String> {
         if results.is_empty() {
             return Ok("No test results available.".to_string());
         }
+        let drift = drift.filter(|drift| !drift.is_empty());
+
+        // Only spend a column on the target when more than one URL was tested
+        let first_url = results.keys().next().map(|k| k.url.as_str());
+        let multiple_urls = results.keys().any(|key| Some(key.url.as_str()) != first_url);
+
+        let mut columns = Vec::new();
+        if multiple_urls {
+            columns.push(Column {
+                header: "Target URL".to_string(),
+                alignment: Alignment::Left,
+                min_width: 10,
+                max_width: 40,
+                flexible: true,
+            });
+        }
+        let metrics = view.columns(self.options.verbose_mode, results.values());
+        columns.push(Column {
+            header: "Configuration".to_string(),
+            alignment: Alignment::Left,
+            min_width: 15,
+            max_width: 40,
+            flexible: true,
+        });
+        columns.extend(metrics.iter().map(|metric| metric_column(*metric)));
+        if drift.is_some() {
+            columns.push(Column {
+                header: "vs Prev / Base".to_string(),
+                alignment: Alignment::Right,
+                min_width: 17,
+                max_width: 20,
+                flexible: false,
+            });
+        }
 
         let table_format = TableFormat {
-            columns: vec![
-                Column {
-                    header: "Configuration".to_string(),
-                    alignment: Alignment::Left,
-                    min_width: 15,
-                    max_width: 40,
-                    flexible: true,
-                },
-                Column {
-                    header: "Success Rate".to_string(),
-                    alignment: Alignment::Right,
-                    min_width: 12,
-                    max_width: 12,
-                    flexible: false,
-                },
-                Column {
-                    header: "Avg Response".to_string(),
-                    alignment: Alignment::Right,
-                    min_width: 12,
-                    max_width: 12,
-                    flexible: false,
-                },
-                Column {
-                    header: "Min/Max".to_string(),
-                    alignment: Alignment::Right,
-                    min_width: 15,
-                    max_width: 15,
-                    flexible: false,
-                },
-                Column {
-                    header: "Performance".to_string(),
-                    alignment: Alignment::Center,
-                    min_width: 12,
-                    max_width: 12,
-                    flexible: false,
-                },
-            ],
+            columns,
             show_borders: self.options.table_borders,
             show_header: true,
             min_column_width: 8,
@@ -390,43 +1087,48 @@ impl OutputFormatter for PlainFormatter {
             padding: 1,
         };
 
-        let mut rows = Vec::new();
-        for result in results.values() {
-            let success_rate = self.format_percentage(result.success_rate());
-            let avg_response = if let Some(ref stats) = result.statistics {
-                self.format_duration(stats.total_avg_ms)
-            } else {
-                "N/A".to_string()
-            };
-            
-            let min_max = if let Some(ref stats) = result.statistics {
-                format!("{}/{}", 
-                    self.format_duration(stats.total_min_ms), 
-                    self.format_duration(stats.total_max_ms))
-            } else {
-                "N/A".to_string()
-            };
-            
-            let performance = result.performance_level()
-                .map(|p| format!("{:?}", p))
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            rows.push(vec![
-                result.config_name.clone(),
-                success_rate,
-                avg_response,
-                min_max,
-                performance,
-            ]);
-        }
-
-        // Sort by average response time (fastest first)
-        rows.sort_by(|a, b| {
-            let a_time = results.get(&a[0]).and_then(|r| r.statistics.as_ref()).map(|s| s.total_avg_ms).unwrap_or(f64::MAX);
-            let b_time = results.get(&b[0]).and_then(|r| r.statistics.as_ref()).map(|s| s.total_avg_ms).unwrap_or(f64::MAX);
-            a_time.partial_cmp(&b_time).unwrap_or(std::cmp::Ordering::Equal)
+        // Group by URL, then sort by average response time (fastest first)
+        let mut sorted_results: Vec<(&ResultKey, &TestResult)> = results.iter().collect();
+        sorted_results.sort_by(|a, b| {
+            let a_time = a.1.statistics.as_ref().map(|s| s.total_avg_ms).unwrap_or(f64::MAX);
+            let b_time = b.1.statistics.as_ref().map(|s| s.total_avg_ms).unwrap_or(f64::MAX);
+            a.0.url.cmp(&b.0.url)
+                .then(a_time.partial_cmp(&b_time).unwrap_or(std::cmp::Ordering::Equal))
         });
 
+        let mut entries = Vec::with_capacity(sorted_results.len());
+        for url_results in sorted_results.chunk_by(|a, b| a.0.url == b.0.url) {
+            let url = &url_results[0].0.url;
+            entries.extend(view.arrange(url_results.iter().map(|(_, result)| *result).collect()).into_iter().map(|entry| (url, entry)));
+        }
+
+        let format_duration = |ms| self.format_duration(ms);
+        let format_percentage = |percentage| self.format_percentage(percentage);
+        let mut rows = Vec::new();
+        for (url, entry) in entries {
+            let mut row = Vec::with_capacity(metrics.len() + 3);
+            if multiple_urls {
+                row.push(display_url(url));
+            }
+            match entry {
+                TableEntry::Result(result) => {
+                    row.push(result.config_name.to_string());
+                    row.extend(metrics.iter().map(|metric| metric_cell(*metric, result, format_duration, format_percentage)));
+                    if let Some(drift) = drift {
+                        row.push(drift.get(&ResultKey::for_result(result)).map_or_else(|| "new".to_string(), ToString::to_string));
+                    }
+                }
+                TableEntry::Omitted(omitted) => {
+                    row.push(omitted.label());
+                    row.extend(metrics.iter().map(|metric| omitted_cell(*metric, &omitted, format_duration, format_percentage)));
+                    if drift.is_some() {
+                        row.push(String::new());
+                    }
+                }
+            }
+            rows.push(row);
+        }
+
         self.create_table(&table_format, &rows)
     }
 
@@ -456,6 +1158,196 @@ impl OutputFormatter for PlainFormatter {
         writeln!(output, "Average Response Time:     {}", self.format_duration(avg_response_time))
             .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
         
+        if analysis.url_breakdown.len() > 1 {
+            output.push_str(&self.format_url_breakdown(analysis)?);
+        }
+
+        if !analysis.split_horizon.is_empty() {
+            writeln!(output, "\nSplit-Horizon Answers:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for target in &analysis.split_horizon {
+                writeln!(output, "  {} ({})", display_url(&target.url), target.verdict())
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                for (config_name, answer) in &target.answers {
+                    writeln!(output, "    {:<30} {}", config_name, answer)
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+                for divergence in analysis.comparative_analysis.route_divergence.iter().filter(|d| d.url == target.url) {
+                    writeln!(output, "    {:<30} {}", divergence.config_name, divergence)
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+            }
+        }
+
+        if let Some(ref matrix) = analysis.interface_matrix {
+            writeln!(output, "\nInterfaces:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            let header: String = matrix.interfaces.iter().map(|interface| format!(" {:>10} ", interface)).collect();
+            writeln!(output, "  {:<30}{}", "", header)
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for row in &matrix.rows {
+                let fastest = row.fastest();
+                let cells: String = matrix.interfaces.iter()
+                    .map(|interface| match row.avg_ms.get(interface) {
+                        Some(&avg_ms) => {
+                            let marker = if fastest == Some(interface.as_str()) { '*' } else { ' ' };
+                            format!(" {:>10}{}", self.format_duration(avg_ms), marker)
+                        }
+                        None => format!(" {:>10} ", "-"),
+                    })
+                    .collect();
+                writeln!(output, "  {:<30}{}", row.config_name, cells)
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+            if let Some((interface, wins)) = matrix.fastest_interface() {
+                writeln!(output, "  * fastest; {} was fastest for {} of {} configurations", interface, wins, matrix.rows.len())
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut cold_starts: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.cold_start.as_ref().map(|split| (name, split)))
+            .collect();
+        if !cold_starts.is_empty() {
+            cold_starts.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nFirst Request vs Steady State:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in cold_starts {
+                writeln!(output, "  {:<30} first {} | steady {} | penalty {:+.1}ms",
+                    config_name,
+                    self.format_duration(split.cold.total_avg_ms),
+                    self.format_duration(split.warm.total_avg_ms),
+                    split.penalty_ms())
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut cache_splits: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.cache_split.as_ref().map(|split| (name, split)))
+            .collect();
+        if !cache_splits.is_empty() {
+            cache_splits.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nCache Hits vs Misses:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in cache_splits {
+                writeln!(output, "  {:<30} {}", config_name, cache_split_description(split, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut resumption_splits: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.tls_resumption.as_ref().map(|split| (name, split)))
+            .collect();
+        if !resumption_splits.is_empty() {
+            resumption_splits.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nTLS Resumed vs Full Handshakes:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, split) in resumption_splits {
+                writeln!(output, "  {:<30} {}", config_name, tls_resumption_description(split, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut timeouts: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.timeouts.as_ref().map(|timeouts| (name, timeouts)))
+            .collect();
+        if !timeouts.is_empty() {
+            timeouts.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nTimeouts:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, summary) in timeouts {
+                writeln!(output, "  {:<30} {}", config_name, timeout_description(summary))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut sizes: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.response_size.as_ref().map(|size| (name, size)))
+            .collect();
+        if !sizes.is_empty() {
+            sizes.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nResponse Size:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, size) in sizes {
+                writeln!(output, "  {:<30} {}", config_name, response_size_description(size))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut sockets: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.socket.as_ref().map(|socket| (name, socket)))
+            .collect();
+        if !sockets.is_empty() {
+            sockets.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nTCP Socket Stats:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, socket) in sockets {
+                writeln!(output, "  {:<30} {}", config_name, socket_description(socket, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut uploads: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.upload.as_ref().map(|upload| (name, upload)))
+            .collect();
+        if !uploads.is_empty() {
+            uploads.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nUpload:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, upload) in uploads {
+                writeln!(output, "  {:<30} {}", config_name, upload_description(upload, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut informational: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.informational.as_ref().map(|informational| (name, informational)))
+            .collect();
+        if !informational.is_empty() {
+            informational.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nEarly Hints:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, informational) in informational {
+                writeln!(output, "  {:<30} {}", config_name, informational_description(informational, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut throttled: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.reliability.throttling.as_ref().map(|throttling| (name, throttling)))
+            .collect();
+        if !throttled.is_empty() {
+            throttled.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nThrottling:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, throttling) in throttled {
+                writeln!(output, "  {:<30} {}", config_name, throttling_description(throttling))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            }
+        }
+
+        let mut server_timing: Vec<_> = analysis.basic_stats.iter()
+            .filter_map(|(name, stats)| stats.server_timing.as_ref().map(|server_timing| (name, server_timing)))
+            .filter(|_| self.options.verbose_mode)
+            .collect();
+        if !server_timing.is_empty() {
+            server_timing.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nServer Timing:")
+                .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+            for (config_name, server_timing) in server_timing {
+                writeln!(output, "  {:<30} {}", config_name, server_timing_description(server_timing, |ms| self.format_duration(ms)))
+                    .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                for phase in &server_timing.phases {
+                    writeln!(output, "    {:<28} {} ({}/{})",
+                        phase.description.as_deref().unwrap_or(&phase.name),
+                        self.format_duration(phase.avg_ms),
+                        phase.samples,
+                        server_timing.samples)
+                        .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
+                }
+            }
+        }
+        
         if self.options.verbose_mode {
             writeln!(output, "\nDetailed Statistics:")
                 .map_err(|e| AppError::io(format!("Failed to format analysis: {}", e)))?;
@@ -474,6 +1366,7 @@ impl OutputFormatter for PlainFormatter {
         Ok(output)
     }
 
+    #[cfg(feature = "diagnostics")]
     fn format_diagnostics_report(&self, report: &DiagnosticReport) -> Result<String> {
         let mut output = String::new();
         
@@ -522,6 +1415,53 @@ impl OutputFormatter for PlainFormatter {
                     .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
             }
         }
+
+        if !report.connectivity_diagnostics.traceroutes.is_empty() {
+            writeln!(output, "\nRoutes:")
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for trace in &report.connectivity_diagnostics.traceroutes {
+                writeln!(output, "  {}", trace)
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+                if self.options.verbose_mode {
+                    for hop in trace.path() {
+                        writeln!(output, "    {}", hop)
+                            .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+                    }
+                }
+            }
+        }
+
+        let mut resolutions: Vec<_> = report.dns_diagnostics.resolution_performance.iter()
+            .filter(|(_, result)| result.success)
+            .collect();
+        if self.options.verbose_mode && !resolutions.is_empty() {
+            resolutions.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nDNS Resolution:")
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for (key, result) in resolutions {
+                let breakdown = if result.record_latencies.is_empty() {
+                    String::new()
+                } else {
+                    let records: Vec<_> = result.record_latencies.iter().map(|latency| latency.to_string()).collect();
+                    format!(" ({})", records.join(", "))
+                };
+                writeln!(output, "  {} - {}{}", key, self.format_duration(result.duration.as_secs_f64() * 1000.0), breakdown)
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            }
+        }
+
+        let mut doh_timings: Vec<_> = report.dns_diagnostics.doh_analysis.values()
+            .filter_map(|analysis| analysis.timing.as_ref().map(|timing| (&analysis.provider_name, timing)))
+            .collect();
+        if !doh_timings.is_empty() {
+            doh_timings.sort_by(|a, b| a.0.cmp(b.0));
+            writeln!(output, "\nDoH Providers:")
+                .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            for (provider, timing) in doh_timings {
+                writeln!(output, "  {} - {}", provider, timing)
+                    .map_err(|e| AppError::io(format!("Failed to format diagnostics: {}", e)))?;
+            }
+        }
         
         Ok(output)
     }
@@ -566,13 +1506,75 @@ impl OutputFormatter for PlainFormatter {
         Ok(output)
     }
 
+    fn format_slowest_requests(&self, samples: &[SampleRef<'_>]) -> Result<String> {
+        let mut output = String::new();
+
+        writeln!(output, "Slowest Requests:")
+            .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+        writeln!(output, "----------------")
+            .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+
+        if samples.is_empty() {
+            writeln!(output, "No requests recorded.")
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            return Ok(output);
+        }
+
+        for (index, sample) in samples.iter().enumerate() {
+            let metrics = sample.metrics;
+            writeln!(output, "{:>2}. {:>8}  {:<8} {} @ {}",
+                index + 1,
+                self.format_duration(metrics.total_ms()),
+                format!("{:?}", metrics.status),
+                sample.config_name,
+                display_url(sample.url))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            writeln!(output, "    {}", phase_breakdown(metrics, |ms| self.format_duration(ms)))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            writeln!(output, "    {}", sample_origin(metrics))
+                .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            if let Some(phases) = server_phases(metrics, |ms| self.format_duration(ms)).filter(|_| self.options.verbose_mode) {
+                writeln!(output, "    {}", phases)
+                    .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            }
+            if let Some(ref error) = metrics.error_message {
+                writeln!(output, "    Error: {}", error)
+                    .map_err(|e| AppError::io(format!("Failed to format slowest requests: {}", e)))?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn format_error_summary(&self, groups: &[ErrorGroup]) -> Result<String> {
+        let mut output = String::new();
+
+        writeln!(output, "Errors:")
+            .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+        writeln!(output, "-------")
+            .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+
+        for group in groups {
+            writeln!(output, "  {}", group)
+                .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+            writeln!(output, "    in {}", error_sources(group))
+                .map_err(|e| AppError::io(format!("Failed to format error summary: {}", e)))?;
+        }
+
+        Ok(output)
+    }
+
+    fn format_reproduction(&self, reproduction: &Reproduction) -> Result<String> {
+        Ok(format!("Reproduce: {}\nConfig hash: {}", reproduction, reproduction.config_hash))
+    }
+
     fn format_quick_summary(&self, results: &ExecutionResults) -> Result<String> {
         Ok(format!(
             "Tests: {}/{} successful ({:.1}%) | Best: {} | Duration: {:.2}s",
             results.execution_summary.successful_tests,
             results.execution_summary.total_tests,
             results.execution_summary.success_rate,
-            results.best_config().unwrap_or("Unknown"),
+            results.best_config().unwrap_or("no clear winner"),
             results.execution_summary.total_duration.as_secs_f64()
         ))
     }
@@ -585,12 +1587,16 @@ impl OutputFormatter for PlainFormatter {
             "N/A".to_string()
         };
 
-        Ok(format!(
+        let mut line = format!(
             "{}: {} success, {} avg response",
             result.config_name,
             success_rate,
             avg_response
-        ))
+        );
+        if let Some(rtt) = result.ping_rtt_ms() {
+            line.push_str(&format!(", {} ping", self.format_duration(rtt)));
+        }
+        Ok(line)
     }
 
     fn format_error(&self, error: &str) -> Result<String> {
@@ -604,4 +1610,102 @@ impl OutputFormatter for PlainFormatter {
     fn format_success(&self, message: &str) -> Result<String> {
         Ok(format!("SUCCESS: {}", message))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::{InformationalTiming, PingStats}, test_support::result_with_failures};
+    use std::time::Duration;
+
+    fn names(entries: &[TableEntry<'_>]) -> Vec<String> {
+        entries.iter().map(|entry| match entry {
+            TableEntry::Result(result) => result.config_name.to_string(),
+            TableEntry::Omitted(omitted) => omitted.label(),
+        }).collect()
+    }
+
+    #[test]
+    fn test_table_view_arrange() {
+        let results = [
+            result_with_failures("steady", "https://example.com", &[50, 50, 50], 0),
+            result_with_failures("spiky", "https://example.com", &[10, 10, 200], 0),
+            result_with_failures("flaky", "https://example.com", &[20, 20], 1),
+            result_with_failures("slow", "https://example.com", &[90, 90, 90], 0),
+            result_with_failures("down", "https://example.com", &[], 3),
+        ];
+        let refs: Vec<&TestResult> = results.iter().collect();
+
+        // Without options the formatter's own order is kept
+        assert_eq!(names(&TableView::default().arrange(refs.clone())), ["steady", "spiky", "flaky", "slow", "down"]);
+
+        let by = |sort_by| names(&TableView::new(Some(sort_by), None).arrange(refs.clone()));
+        assert_eq!(by(SortKey::Mean), ["flaky", "steady", "spiky", "slow", "down"]);
+        assert_eq!(by(SortKey::P95), ["flaky", "steady", "slow", "spiky", "down"]);
+        assert_eq!(by(SortKey::Success), ["steady", "spiky", "slow", "flaky", "down"]);
+
+        let mut view = TableView::new(Some(SortKey::Score), Some(1));
+        view.scores = HashMap::from([("slow".to_string(), 0.9), ("steady".to_string(), 0.5)]);
+        let entries = view.arrange(refs.clone());
+        assert_eq!(names(&entries), ["slow", "… 3 more configurations", "down"]);
+        let TableEntry::Omitted(ref omitted) = entries[1] else { panic!("expected omitted rows") };
+        assert_eq!(omitted.count, 3);
+        assert_eq!(omitted.success_rate, (66.66666666666666, 100.0));
+
+        // Nothing is folded when the best and worst N already cover every result
+        assert_eq!(TableView::new(None, Some(3)).arrange(refs).len(), 5);
+    }
+
+    #[test]
+    fn test_table_columns() {
+        let results = HashMap::from([(ResultKey::new("https://example.com".to_string(), "steady".to_string()), result_with_failures("steady", "https://example.com", &[50, 70], 0))]);
+        let header = |formatter: PlainFormatter, view: TableView| {
+            let table = formatter.format_performance_table(&results, &view, None).unwrap();
+            table.lines().find(|line| line.contains("Configuration")).unwrap().to_string()
+        };
+        let compact = || PlainFormatter::new(FormattingOptions::default());
+        let verbose = || PlainFormatter::new(FormattingOptions { verbose_mode: true, ..Default::default() });
+
+        assert!(header(compact(), TableView::default()).contains("Min/Max"));
+        let verbose_header = header(verbose(), TableView::default());
+        assert!(verbose_header.contains("TTFB") && verbose_header.contains("P95") && !verbose_header.contains("Min/Max"));
+
+        // Chosen columns appear once each, in the order given, at any verbosity
+        let view = TableView::default().with_columns(vec![MetricColumn::P95, MetricColumn::Dns, MetricColumn::P95]);
+        let chosen = header(compact(), view.clone());
+        assert!(chosen.find("P95").unwrap() < chosen.find("DNS").unwrap());
+        assert_eq!(chosen.matches("P95").count(), 1);
+        assert!(!chosen.contains("Success"));
+        let table = compact().format_performance_table(&results, &view, None).unwrap();
+        assert!(table.contains("69.0ms"));
+
+        // The early hints column joins the defaults once a 1xx response came
+        assert!(!header(compact(), TableView::default()).contains("1xx"));
+        let mut hinted = result_with_failures("hinted", "https://example.com", &[50], 0);
+        hinted.individual_results[0].informational = Some(InformationalTiming {
+            statuses: vec![103],
+            first: Some(Duration::from_millis(12)),
+            response: Duration::from_millis(48),
+        });
+        let results = HashMap::from([(ResultKey::new("https://example.com".to_string(), "hinted".to_string()), hinted)]);
+        let table = compact().format_performance_table(&results, &TableView::default(), None).unwrap();
+        assert!(table.lines().find(|line| line.contains("Configuration")).unwrap().contains("1xx"));
+        assert!(table.contains("12.0ms"));
+
+        // So does the ping column once `--ping` pinged the target
+        let mut pinged = result_with_failures("pinged", "https://example.com", &[50], 0);
+        pinged.ping = vec![PingStats::from_rtts("192.0.2.1".parse().unwrap(), 2, &[Duration::from_millis(8)])];
+        let results = HashMap::from([(ResultKey::new("https://example.com".to_string(), "pinged".to_string()), pinged)]);
+        let table = compact().format_performance_table(&results, &TableView::default(), None).unwrap();
+        assert!(table.lines().find(|line| line.contains("Configuration")).unwrap().contains("Ping RTT"));
+        assert!(table.contains("8.0ms"));
+    }
+
+    #[test]
+    fn test_phase_breakdown_leaves_out_estimates() {
+        let format = |ms: f64| format!("{:.1}ms", ms);
+        let metrics = TimingMetrics::success(Duration::from_millis(5), Duration::from_millis(20), None, Duration::from_millis(80), Duration::from_millis(105), 200);
+        assert_eq!(phase_breakdown(&metrics, format), "DNS 5.0ms | TCP 20.0ms | First byte 80.0ms");
+        assert_eq!(phase_breakdown(&metrics.with_estimated_phases(true), format), "DNS, TCP, TLS and first byte not measured");
+    }
 }
\ No newline at end of file
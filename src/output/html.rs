@@ -0,0 +1,182 @@
+//! Standalone HTML report of a run
+//!
+//! The report of a `--thorough` run goes to people who will not install the
+//! tool: support staff of an ISP or a hosting provider. [`render_html`] puts
+//! everything that backs up a complaint on one page without scripts or
+//! external resources, so that it can be attached to a ticket as it is:
+//! the results per target, the findings of the analysis and, when
+//! diagnostics ran, the issues found, the certificates and the routes.
+
+use crate::{
+    executor::ExecutionResults,
+    models::TestResult,
+    stats::heatmap::escape_html,
+    utils::duration::format_duration,
+    VERSION, PKG_NAME,
+};
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:70em;margin:auto}\
+    table{border-collapse:collapse;margin-bottom:1.5em}td,th{border:1px solid #ccc;padding:.2em .6em;text-align:left}\
+    td.num{text-align:right}tr.failed{background:#fdd}code{background:#eee;padding:0 .2em}";
+
+/// The whole report as one HTML page
+pub fn render_html(results: &ExecutionResults) -> String {
+    let summary = &results.execution_summary;
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Network latency report</title>\n");
+    html.push_str(&format!("<style>{}</style></head><body>\n", STYLE));
+    html.push_str("<h1>Network latency report</h1>\n");
+
+    let generated = results.statistical_analysis.as_ref()
+        .map_or_else(chrono::Utc::now, |analysis| analysis.generated_at);
+    html.push_str("<table>\n");
+    let mut row = |label: &str, value: String| html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+    row("Generated", generated.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+    row("Tool", format!("{} {}", PKG_NAME, VERSION));
+    row("Duration", format_duration(summary.total_duration));
+    row("Requests", format!("{} ({} successful, {} failed, {} timed out)",
+        summary.total_tests, summary.successful_tests, summary.failed_tests, summary.timeout_tests));
+    row("Success rate", format!("{:.1}%", summary.success_rate));
+    if let Some(ref network) = summary.network {
+        row("Network", network.to_string());
+    }
+    if let Some(ref clock) = summary.clock {
+        row("Clock", clock.to_string());
+    }
+    html.push_str("</table>\n");
+
+    let captures: Vec<String> = summary.packet_captures.iter().map(ToString::to_string).collect();
+    list(&mut html, "Packet captures", &captures);
+
+    html.push_str("<h2>Results</h2>\n<table>\n<tr><th>Target</th><th>DNS configuration</th><th>Mean</th><th>Min</th>\
+        <th>Max</th><th>Std dev</th><th>Success</th><th>Samples</th></tr>\n");
+    let mut ranked: Vec<&TestResult> = results.test_results.values().collect();
+    ranked.sort_by(|a, b| a.url.cmp(&b.url).then_with(|| {
+        let mean = |result: &TestResult| result.statistics.as_ref().map_or(f64::INFINITY, |stats| stats.total_avg_ms);
+        mean(a).total_cmp(&mean(b))
+    }));
+    for result in ranked {
+        let class = if result.success_count == 0 { " class=\"failed\"" } else { "" };
+        html.push_str(&format!("<tr{}><td>{}</td><td>{}</td>", class, escape_html(&result.url), escape_html(&result.config_name)));
+        match result.statistics {
+            Some(ref stats) => html.push_str(&format!(
+                "<td class=\"num\">{:.1}ms</td><td class=\"num\">{:.1}ms</td><td class=\"num\">{:.1}ms</td><td class=\"num\">{:.1}ms</td>",
+                stats.total_avg_ms, stats.total_min_ms, stats.total_max_ms, stats.total_std_dev_ms)),
+            None => html.push_str("<td colspan=\"4\">no successful request</td>"),
+        }
+        html.push_str(&format!("<td class=\"num\">{:.1}%</td><td class=\"num\">{}</td></tr>\n", result.success_rate(), result.total_count));
+    }
+    html.push_str("</table>\n");
+
+    if let Some(ref analysis) = results.statistical_analysis {
+        let findings = &analysis.summary;
+        html.push_str("<h2>Analysis</h2>\n");
+        if let Some(ref recommended) = findings.recommended_config {
+            html.push_str(&format!("<p>Recommended DNS configuration: <b>{}</b></p>\n", escape_html(recommended)));
+        }
+        list(&mut html, "Key findings", &findings.key_findings);
+        list(&mut html, "Warnings", &findings.warnings);
+        list(&mut html, "Recommendations", &findings.recommendations);
+    }
+
+    #[cfg(feature = "diagnostics")]
+    if let Some(ref report) = results.diagnostics_report {
+        render_diagnostics(&mut html, report);
+    }
+
+    if let Some(ref reproduction) = results.reproduction {
+        html.push_str(&format!("<footer>\n<p>Reproduce: <code>{}</code><br>Config hash: <code>{}</code></p>\n</footer>\n",
+            escape_html(&reproduction.command()), reproduction.config_hash));
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// A heading and a bulleted list, or nothing when there are no items
+fn list(html: &mut String, heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    html.push_str(&format!("<h3>{}</h3>\n<ul>\n", heading));
+    for item in items {
+        html.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+    }
+    html.push_str("</ul>\n");
+}
+
+#[cfg(feature = "diagnostics")]
+fn render_diagnostics(html: &mut String, report: &crate::diagnostics::DiagnosticReport) {
+    html.push_str("<h2>Diagnostics</h2>\n");
+    html.push_str(&format!("<p>Network health: {:?} (score {:.0}%)</p>\n", report.system_health.status, report.system_health.score * 100.0));
+
+    if !report.issues.is_empty() {
+        html.push_str("<h3>Issues</h3>\n<table>\n<tr><th>Severity</th><th>Issue</th><th>Details</th><th>Affects</th></tr>\n");
+        for issue in &report.issues {
+            html.push_str(&format!("<tr><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                issue.severity, escape_html(&issue.title), escape_html(&issue.description), escape_html(&issue.affected_components.join(", "))));
+        }
+        html.push_str("</table>\n");
+    }
+
+    let mut certificates: Vec<_> = report.http_diagnostics.certificate_analysis.iter().collect();
+    certificates.sort_by(|a, b| a.0.cmp(b.0));
+    if !certificates.is_empty() {
+        html.push_str("<h3>Certificates</h3>\n<table>\n<tr><th>Target</th><th>Subject</th><th>Issuer</th><th>Expires</th><th>Problems</th></tr>\n");
+        for (key, certificate) in certificates {
+            let class = if certificate.security_issues.is_empty() { "" } else { " class=\"failed\"" };
+            let expires = match (certificate.expiry_date, certificate.days_until_expiry) {
+                (Some(date), Some(days)) => format!("{} ({} days)", date.format("%Y-%m-%d"), days),
+                _ => "unknown".to_string(),
+            };
+            html.push_str(&format!("<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n", class,
+                escape_html(key), escape_html(&certificate.subject), escape_html(&certificate.issuer), expires,
+                escape_html(&certificate.security_issues.join("; "))));
+        }
+        html.push_str("</table>\n");
+    }
+
+    for trace in &report.connectivity_diagnostics.traceroutes {
+        html.push_str(&format!("<h3>Route to {}</h3>\n<table>\n<tr><th>Hop</th><th>Address</th><th>Round trip</th></tr>\n", escape_html(&trace.to_string())));
+        for hop in trace.path() {
+            let address = hop.address.map_or_else(|| "*".to_string(), |address| address.to_string());
+            let rtt = hop.rtt_ms.map_or_else(String::new, |rtt| format!("{:.1}ms", rtt));
+            html.push_str(&format!("<tr><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n", hop.ttl, address, rtt));
+        }
+        html.push_str("</table>\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{executor::ExecutionSummary, models::TimingMetrics, test_support::result_with_totals, types::DnsConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_html() {
+        let reached = result_with_totals("System DNS", "https://example.com/?a=1&b=<2>", &[42]);
+        let mut failed = TestResult::new("Custom DNS (8.8.8.8)".to_string(), DnsConfig::System, "https://example.com/?a=1&b=<2>".to_string());
+        failed.add_measurement(TimingMetrics::failed("Connection refused".to_string()));
+        failed.calculate_statistics();
+
+        let summary = ExecutionSummary {
+            total_duration: Duration::from_secs(3),
+            total_tests: 2,
+            successful_tests: 1,
+            failed_tests: 1,
+            success_rate: 50.0,
+            ..Default::default()
+        };
+        let results = ExecutionResults::new(summary, vec![failed, reached]);
+        let html = render_html(&results);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("https://example.com/?a=1&amp;b=&lt;2&gt;"));
+        assert!(!html.contains("<2>"));
+        // The reached configuration ranks first, the failed one is marked
+        let system = html.find("System DNS").unwrap();
+        let custom = html.find("Custom DNS").unwrap();
+        assert!(system < custom);
+        assert!(html.contains("42.0ms"));
+        assert!(html.contains("<tr class=\"failed\">"));
+    }
+}
@@ -6,6 +6,8 @@
 mod formatter;
 mod colored;
 mod verbose;
+mod html;
+mod csv;
 
 pub use formatter::{
     OutputFormatter, 
@@ -14,6 +16,9 @@ pub use formatter::{
     FormattingOptions,
     Column,
     RowData,
+    TableView,
+    TableEntry,
+    OmittedRows,
 };
 pub use colored::{
     ColoredFormatter,
@@ -21,11 +26,14 @@ pub use colored::{
     PerformanceLevel,
 };
 pub use verbose::VerboseTimingFormatter;
+pub use html::render_html;
+pub use csv::{CsvFormatter, CSV_HEADER};
 
 use crate::{
     error::Result,
-    executor::ExecutionResults,
+    executor::{CycleDrift, ExecutionResults},
     models::metrics::TestResult,
+    types::{MetricColumn, SortKey},
 };
 
 /// Output formatting factory for creating appropriate formatters
@@ -71,6 +79,10 @@ pub struct OutputCoordinator {
     formatter: Box<dyn OutputFormatter>,
     verbose_formatter: Option<VerboseTimingFormatter>,
     config: Option<crate::models::Config>,
+    slowest_requests: usize,
+    cycle_drift: Option<CycleDrift>,
+    table_view: TableView,
+    compact: bool,
 }
 
 impl OutputCoordinator {
@@ -80,6 +92,10 @@ impl OutputCoordinator {
             formatter,
             verbose_formatter: None,
             config: None,
+            slowest_requests: 0,
+            cycle_drift: None,
+            table_view: TableView::default(),
+            compact: false,
         }
     }
     
@@ -95,9 +111,46 @@ impl OutputCoordinator {
             formatter,
             verbose_formatter,
             config: Some(config.clone()),
+            slowest_requests: 0,
+            cycle_drift: None,
+            table_view: TableView::default(),
+            compact: false,
         }
     }
 
+    /// List the `count` slowest individual requests after the results (0 disables the section)
+    pub fn with_slowest_requests(mut self, count: usize) -> Self {
+        self.slowest_requests = count;
+        self
+    }
+
+    /// Rank the performance table by `sort_by` and show only the best and worst
+    /// `top` configurations of each target
+    pub fn with_table_view(mut self, sort_by: Option<SortKey>, top: Option<usize>) -> Self {
+        self.table_view.sort_by = sort_by;
+        self.table_view.top = top;
+        self
+    }
+
+    /// Show these metric columns in the performance table instead of the
+    /// defaults for the verbosity (none keeps the defaults)
+    pub fn with_columns(mut self, columns: Vec<MetricColumn>) -> Self {
+        self.table_view = self.table_view.with_columns(columns);
+        self
+    }
+
+    /// Show each result's change since the previous watch cycle and the session baseline
+    pub fn with_cycle_drift(mut self, drift: Option<CycleDrift>) -> Self {
+        self.cycle_drift = drift;
+        self
+    }
+
+    /// Show only the one-line summary and a line per result, as `--quick` does
+    pub fn with_compact_summary(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
     /// Display complete execution results
     pub async fn display_results(&self, results: &ExecutionResults) -> Result<String> {
         let mut output = String::new();
@@ -106,10 +159,18 @@ impl OutputCoordinator {
         if let (Some(verbose_formatter), Some(config)) = (&self.verbose_formatter, &self.config) {
             if config.verbose {
                 // Use comprehensive verbose timing output
-                return verbose_formatter.format_verbose_results(results).await;
+                let mut output = verbose_formatter.format_verbose_results(results).await?;
+                self.append_reproduction(&mut output, results)?;
+                return Ok(output);
             }
         }
 
+        if self.compact {
+            let mut output = self.display_compact_summary(results)?;
+            self.append_reproduction(&mut output, results)?;
+            return Ok(output);
+        }
+
         // Standard output formatting
         // Header
         output.push_str(&self.formatter.format_header("Network Latency Test Results")?);
@@ -120,7 +181,8 @@ impl OutputCoordinator {
         output.push_str("\n\n");
 
         // Performance table
-        output.push_str(&self.formatter.format_performance_table(&results.test_results)?);
+        let view = self.table_view.clone().with_scores(results.statistical_analysis.as_ref());
+        output.push_str(&self.formatter.format_performance_table(&results.test_results, &view, self.cycle_drift.as_ref())?);
         output.push_str("\n\n");
 
         // Statistical analysis
@@ -130,17 +192,64 @@ impl OutputCoordinator {
         }
 
         // Diagnostics report
+        #[cfg(feature = "diagnostics")]
         if let Some(ref diagnostics) = results.diagnostics_report {
             output.push_str(&self.formatter.format_diagnostics_report(diagnostics)?);
             output.push_str("\n\n");
         }
 
+        // Failures, with repeats of the same error collapsed into one line
+        let error_groups = results.error_summary();
+        if !error_groups.is_empty() {
+            output.push_str(&self.formatter.format_error_summary(&error_groups)?);
+            output.push_str("\n\n");
+        }
+
+        // Slowest individual requests
+        if self.slowest_requests > 0 {
+            let samples = results.slowest_samples(self.slowest_requests);
+            output.push_str(&self.formatter.format_slowest_requests(&samples)?);
+            output.push_str("\n\n");
+        }
+
         // Recommendations
         output.push_str(&self.formatter.format_recommendations(results)?);
+        self.append_reproduction(&mut output, results)?;
+
+        Ok(output)
+    }
 
+    /// The quick summary, then each target with its results from fastest to slowest
+    fn display_compact_summary(&self, results: &ExecutionResults) -> Result<String> {
+        use crate::utils::comparison::test_result_min_comparator;
+
+        let mut output = self.formatter.format_quick_summary(results)?;
+        output.push('\n');
+        let mut ranked: Vec<_> = results.test_results.iter().collect();
+        ranked.sort_by(|a, b| a.0.url.cmp(&b.0.url).then_with(|| test_result_min_comparator()(a, b)));
+        let mut url = None;
+        for (key, result) in ranked {
+            if url != Some(&key.url) {
+                url = Some(&key.url);
+                output.push_str(&format!("\n{}\n", key.url));
+            }
+            output.push_str(&format!("  {}\n", self.formatter.format_test_result(result)?));
+        }
         Ok(output)
     }
 
+    /// End a report with the command line that reproduces it, when known
+    fn append_reproduction(&self, output: &mut String, results: &ExecutionResults) -> Result<()> {
+        if let Some(ref reproduction) = results.reproduction {
+            if !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push('\n');
+            output.push_str(&self.formatter.format_reproduction(reproduction)?);
+        }
+        Ok(())
+    }
+
     /// Display a quick summary for progress updates
     pub async fn display_quick_summary(&self, results: &ExecutionResults) -> Result<String> {
         // Use verbose timing summary if available
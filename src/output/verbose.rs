@@ -7,11 +7,13 @@
 use crate::{
     error::{AppError, Result},
     logging::{Logger, PerformanceLogger},
+    output::formatter::{clock_warnings, interference_heading, system_description, OVERLOAD_WARNING},
     models::{Config, TestResult, TimingMetrics},
     stats::StatisticalAnalysis,
     executor::ExecutionResults,
-    diagnostics::DiagnosticReport,
 };
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::DiagnosticReport;
 use std::{
     fmt::Write as _,
 };
@@ -64,6 +66,7 @@ impl VerboseTimingFormatter {
         }
 
         // Diagnostic timing information
+        #[cfg(feature = "diagnostics")]
         if let Some(ref diagnostics) = results.diagnostics_report {
             output.push_str(&self.format_diagnostic_timings(diagnostics).await?);
             output.push_str("\n\n");
@@ -109,6 +112,55 @@ impl VerboseTimingFormatter {
             results.execution_summary.success_rate)
             .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
 
+        if let Some(ref simulation) = results.execution_summary.simulation {
+            writeln!(output, "Simulated:               {}", simulation)
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+        }
+
+        if let Some(shard) = results.execution_summary.shard {
+            writeln!(output, "Shard:                   {}", shard)
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+        }
+
+        if let Some(ref user_agent) = results.execution_summary.user_agent {
+            writeln!(output, "User Agent:              {}", user_agent)
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+        }
+
+        if let Some(ref system) = results.execution_summary.system {
+            writeln!(output, "Test Machine:            {}", system_description(system))
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            if system.is_overloaded() {
+                let warning = format!("Warning: {}", OVERLOAD_WARNING);
+                let warning = if self.use_color { warning.yellow().to_string() } else { warning };
+                writeln!(output, "{}", warning)
+                    .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            }
+        }
+
+        if let Some(ref clock) = results.execution_summary.clock {
+            writeln!(output, "Clock:                   {}", clock)
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            for warning in clock_warnings(clock) {
+                let warning = format!("Warning: {}", warning);
+                let warning = if self.use_color { warning.yellow().to_string() } else { warning };
+                writeln!(output, "{}", warning)
+                    .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            }
+        }
+
+        let interference = &results.execution_summary.interference;
+        if !interference.is_empty() {
+            let warning = format!("Warning: {}", interference_heading(interference.len()));
+            let warning = if self.use_color { warning.yellow().to_string() } else { warning };
+            writeln!(output, "{}", warning)
+                .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            for interval in interference {
+                writeln!(output, "  {}", interval)
+                    .map_err(|e| AppError::io(format!("Failed to format header: {}", e)))?;
+            }
+        }
+
         Ok(output)
     }
 
@@ -135,8 +187,8 @@ impl VerboseTimingFormatter {
             a_avg.partial_cmp(&b_avg).unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        for (config_name, test_result) in sorted_results {
-            output.push_str(&self.format_configuration_timing_details(config_name, test_result).await?);
+        for (key, test_result) in sorted_results {
+            output.push_str(&self.format_configuration_timing_details(&key.to_string(), test_result).await?);
             output.push('\n');
         }
 
@@ -230,10 +282,11 @@ impl VerboseTimingFormatter {
         writeln!(output, "{}", "-".repeat(35))
             .map_err(|e| AppError::io(format!("Failed to format individual timings: {}", e)))?;
 
-        for (config_name, test_result) in &results.test_results {
+        for (key, test_result) in &results.test_results {
             if test_result.individual_results.is_empty() {
                 continue;
             }
+            let config_name = key.to_string();
 
             let config_header = if self.use_color {
                 format!("\n📋 {} - Individual Test Results:", config_name).cyan().bold()
@@ -259,7 +312,7 @@ impl VerboseTimingFormatter {
             // Log individual timing analysis
             self.logger.info(&format!("Individual timing analysis for {}: {} tests, {} successful", 
                 config_name, test_result.total_count, test_result.success_count))
-                .field("config_name", config_name)
+                .field("config_name", &config_name)
                 .field("total_tests", test_result.total_count)
                 .field("successful_tests", test_result.success_count)
                 .log().await;
@@ -449,6 +502,7 @@ impl VerboseTimingFormatter {
     }
 
     /// Format diagnostic timing information
+    #[cfg(feature = "diagnostics")]
     async fn format_diagnostic_timings(&self, diagnostics: &DiagnosticReport) -> Result<String> {
         let mut output = String::new();
 
@@ -585,7 +639,7 @@ impl VerboseTimingFormatter {
             use crate::utils::comparison::test_result_min_comparator;
             results.test_results.iter().min_by(test_result_min_comparator())
         } {
-            recommendations.push(format!("• Use '{}' configuration for best performance", fastest_config.0));
+            recommendations.push(format!("• Use '{}' configuration for best performance", fastest_config.0.config_name));
         }
 
         // Success rate based timing recommendations
@@ -620,8 +674,10 @@ impl VerboseTimingFormatter {
     pub async fn format_console_timing_summary(&self, results: &ExecutionResults) -> Result<String> {
         let mut output = String::new();
 
-        let best_config = results.best_config().unwrap_or("Unknown");
-        let best_time = results.test_results.get(best_config)
+        let best_key = results.best_result();
+        let best_config = best_key.map(|key| key.config_name.as_str()).unwrap_or("no clear winner");
+        let best_time = best_key
+            .and_then(|key| results.test_results.get(key))
             .and_then(|r| r.statistics.as_ref())
             .map(|s| format!("{:.1}ms", s.total_avg_ms))
             .unwrap_or_else(|| "N/A".to_string());
@@ -658,8 +714,10 @@ mod tests {
     use super::*;
     use crate::{
         models::{TimingMetrics, Statistics, TestResult},
-        types::DnsConfig,
-        executor::{ExecutionSummary, ExecutionResults},
+        types::{DnsConfig, Shard},
+        executor::{ExecutionSummary, ExecutionResults, ResultKey, SystemSnapshot, InterferenceInterval, InterferenceReason},
+        timing::ClockReport,
+        test_support::result_with_totals,
     };
     use std::{time::Duration, collections::HashMap};
 
@@ -678,7 +736,7 @@ mod tests {
         let formatter = VerboseTimingFormatter::new(&config);
         
         assert!(!formatter.use_color);
-        assert_eq!(formatter.config.verbose, true);
+        assert!(formatter.config.verbose);
     }
 
     #[test]
@@ -716,17 +774,39 @@ mod tests {
             total_tests: 10,
             successful_tests: 9,
             failed_tests: 1,
-            timeout_tests: 0,
-            skipped_tests: 0,
             success_rate: 90.0,
-            performance_summary: HashMap::new(),
+            system: Some(SystemSnapshot {
+                cpu_cores: 2,
+                available_memory: 3 * 1024 * 1024 * 1024 / 2,
+                load_average: Some([4.0, 2.5, 1.0]),
+                os: "linux".to_string(),
+                arch: "aarch64".to_string(),
+            }),
+            interference: vec![InterferenceInterval {
+                started_at: chrono::DateTime::from_timestamp(3600, 0).unwrap(),
+                ended_at: chrono::DateTime::from_timestamp(3601, 0).unwrap(),
+                reasons: vec![InterferenceReason::CpuSaturated],
+                cpu_percent: Some(96.0),
+                requests: 12,
+                local_errors: 0,
+                concurrency: 4,
+            }],
+            clock: Some(ClockReport {
+                drift_ms: 2500.0,
+                ntp_server: Some("pool.ntp.org".to_string()),
+                ntp_offset_ms: Some(-3.5),
+            }),
+            shard: Some(Shard { index: 2, count: 4 }),
+            ..Default::default()
         };
         
         let results = ExecutionResults {
             execution_summary: summary,
             test_results: HashMap::new(),
             statistical_analysis: None,
+            #[cfg(feature = "diagnostics")]
             diagnostics_report: None,
+            reproduction: None,
         };
         
         let header = formatter.format_verbose_header(&results).unwrap();
@@ -735,6 +815,14 @@ mod tests {
         assert!(header.contains("5.000s"));  // Total execution time
         assert!(header.contains("0.500s"));  // Average per test
         assert!(header.contains("90.0%"));   // Success rate
+        assert!(header.contains("2 cores | 1.5 GiB available | load 4.00 2.50 1.00 | linux/aarch64"));
+        assert!(header.contains("load average exceeded the CPU core count"));
+        assert!(header.contains("the test machine was the bottleneck in 1 interval;"));
+        assert!(header.contains("01:00:00-01:00:01 UTC: CPU 96%; concurrency limited to 4"));
+        assert!(header.contains("drift +2500.0 ms | NTP offset -3.5 ms (pool.ntp.org)"));
+        assert!(header.contains("the system clock moved +2.5s during the run"));
+        assert!(!header.contains("off NTP time"));
+        assert!(header.contains("Shard:                   2/4"));
     }
 
     #[tokio::test]
@@ -777,46 +865,25 @@ mod tests {
         let config = create_test_config();
         let formatter = VerboseTimingFormatter::new(&config);
         
-        let stats = Statistics {
-            dns_avg_ms: 10.0,
-            tcp_avg_ms: 20.0,
-            first_byte_avg_ms: 50.0,
-            total_avg_ms: 100.0,
-            total_min_ms: 80.0,
-            total_max_ms: 120.0,
-            total_std_dev_ms: 15.0,
-            success_rate: 100.0,
-            sample_count: 5,
-        };
-        
-        let mut result = TestResult::new(
-            "Fast Config".to_string(),
-            DnsConfig::System,
-            "https://example.com".to_string(),
-        );
-        result.statistics = Some(stats);
-        result.success_count = 5;
-        result.total_count = 5;
+        let result = result_with_totals("Fast Config", "https://example.com", &[100; 5]);
         
-        let mut test_results = HashMap::new();
-        test_results.insert("Fast Config".to_string(), result);
+        let test_results = HashMap::from([(ResultKey::for_result(&result), result)]);
         
         let summary = ExecutionSummary {
             total_duration: Duration::from_secs(1),
             total_tests: 5,
             successful_tests: 5,
-            failed_tests: 0,
-            timeout_tests: 0,
-            skipped_tests: 0,
             success_rate: 100.0,
-            performance_summary: HashMap::new(),
+            ..Default::default()
         };
         
         let results = ExecutionResults {
             execution_summary: summary,
             test_results,
             statistical_analysis: None,
+            #[cfg(feature = "diagnostics")]
             diagnostics_report: None,
+            reproduction: None,
         };
         
         let console_summary = formatter.format_console_timing_summary(&results).await.unwrap();
@@ -0,0 +1,240 @@
+//! ICMP echo round trips for `--ping`
+//!
+//! An HTTP request's latency mixes the network path with the server's own
+//! work. An ICMP echo is answered by the host's kernel, so its round trip is
+//! the path alone; set next to the HTTP latency of the same address, it tells
+//! whether a slow target is far away or slow to answer.
+//!
+//! Echo requests go out over an unprivileged ICMP datagram socket where the
+//! system allows one (macOS, and Linux within `net.ipv4.ping_group_range`),
+//! and over a raw socket otherwise, which needs root or `CAP_NET_RAW`. When
+//! neither can be opened the address is reported as not pinged; on Windows,
+//! which has neither, every address is.
+
+use crate::models::{PingStats, TestResult};
+use futures::stream::{self, StreamExt};
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::IpAddr,
+    sync::atomic::{AtomicU16, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Time between the echo requests to an address, the shortest `ping` allows unprivileged users
+pub const ECHO_INTERVAL: Duration = Duration::from_millis(200);
+/// How long replies are awaited after the last echo request
+const REPLY_WAIT: Duration = Duration::from_secs(2);
+/// Addresses pinged at the same time
+const CONCURRENT_PINGS: usize = 8;
+/// Marks the payload of this tool's echo requests
+const PAYLOAD_MAGIC: [u8; 4] = *b"nlt1";
+/// Payload size of each echo request, the default of `ping`
+const PAYLOAD_LEN: usize = 56;
+
+/// Tells concurrent pings apart, since a raw socket receives every reply
+static NEXT_IDENTIFIER: AtomicU16 = AtomicU16::new(1);
+
+/// Send `count` echo requests to `address` and summarize the replies
+pub async fn ping(address: IpAddr, count: u16) -> PingStats {
+    let identifier = NEXT_IDENTIFIER.fetch_add(1, Ordering::Relaxed) ^ (std::process::id() as u16);
+    match echo(address, count, identifier).await {
+        Ok(rtts) => PingStats::from_rtts(address, u32::from(count), &rtts),
+        Err(e) => PingStats::failed(address, e.to_string()),
+    }
+}
+
+/// Ping every address the requests of `results` went to, each address once
+/// however many results share it, and record the statistics on the results
+pub async fn ping_results<'a>(results: impl IntoIterator<Item = &'a mut TestResult>, count: u16) {
+    let mut results: Vec<(&mut TestResult, BTreeSet<IpAddr>)> = results.into_iter()
+        .map(|result| {
+            let addresses = result.individual_results.iter().filter_map(|metrics| metrics.resolved_ip).collect();
+            (result, addresses)
+        })
+        .collect();
+    let addresses: BTreeSet<IpAddr> = results.iter().flat_map(|(_, addresses)| addresses.iter().copied()).collect();
+
+    let pinged: HashMap<IpAddr, PingStats> = stream::iter(addresses)
+        .map(|address| async move { (address, ping(address, count).await) })
+        .buffer_unordered(CONCURRENT_PINGS)
+        .collect()
+        .await;
+    for (result, addresses) in &mut results {
+        result.ping = addresses.iter().filter_map(|address| pinged.get(address).cloned()).collect();
+    }
+}
+
+/// Round trips of the echo requests to `address` that were answered
+#[cfg(unix)]
+async fn echo(address: IpAddr, count: u16, identifier: u16) -> std::io::Result<Vec<Duration>> {
+    let v6 = address.is_ipv6();
+    let socket = icmp_socket(v6)?;
+    let destination = std::net::SocketAddr::new(address, 0);
+
+    let start = Instant::now();
+    let deadline = start + ECHO_INTERVAL * u32::from(count) + REPLY_WAIT;
+    // Send times are needed by the receiving side, which runs concurrently
+    let sent_times = std::sync::Mutex::new(vec![None; usize::from(count)]);
+    let mut rtts: Vec<Option<Duration>> = vec![None; usize::from(count)];
+
+    let sending = async {
+        let mut ticker = tokio::time::interval(ECHO_INTERVAL);
+        for sequence in 0..count {
+            ticker.tick().await;
+            let sent_at = start.elapsed();
+            socket.send_to(&echo_request(v6, identifier, sequence), destination).await?;
+            sent_times.lock().unwrap()[usize::from(sequence)] = Some(sent_at);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+
+    let receiving = async {
+        let mut buffer = [0u8; 2048];
+        while rtts.iter().any(Option::is_none) {
+            let Ok(Ok((len, from))) = tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), socket.recv_from(&mut buffer)).await else {
+                break;
+            };
+            if from.ip() != address {
+                continue;
+            }
+            let Some(sequence) = echo_reply_sequence(v6, identifier, &buffer[..len]).filter(|&sequence| sequence < count) else {
+                continue;
+            };
+            let sent_at = sent_times.lock().unwrap()[usize::from(sequence)];
+            if let (Some(sent_at), None) = (sent_at, rtts[usize::from(sequence)]) {
+                rtts[usize::from(sequence)] = Some(start.elapsed().saturating_sub(sent_at));
+            }
+        }
+    };
+
+    let (sent, ()) = tokio::join!(sending, receiving);
+    sent?;
+    Ok(rtts.into_iter().flatten().collect())
+}
+
+#[cfg(not(unix))]
+async fn echo(_address: IpAddr, _count: u16, _identifier: u16) -> std::io::Result<Vec<Duration>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "ICMP ping needs Linux or macOS"))
+}
+
+/// An ICMP socket, a datagram one if the system allows it and a raw one otherwise
+#[cfg(unix)]
+fn icmp_socket(v6: bool) -> std::io::Result<tokio::net::UdpSocket> {
+    use std::os::fd::FromRawFd;
+
+    let (domain, protocol) = if v6 { (libc::AF_INET6, libc::IPPROTO_ICMPV6) } else { (libc::AF_INET, libc::IPPROTO_ICMP) };
+    let open = |kind| {
+        // SAFETY: socket() has no memory safety preconditions; the descriptor is checked below
+        let fd = unsafe { libc::socket(domain, kind, protocol) };
+        if fd < 0 { Err(std::io::Error::last_os_error()) } else { Ok(fd) }
+    };
+    let fd = open(libc::SOCK_DGRAM).or_else(|datagram_error| {
+        open(libc::SOCK_RAW).map_err(|_| std::io::Error::new(
+            datagram_error.kind(),
+            format!("ICMP sockets are not permitted ({}); allow them with net.ipv4.ping_group_range or run as root", datagram_error),
+        ))
+    })?;
+    // SAFETY: `fd` is a socket just opened and owned by nothing else. A datagram
+    // socket type is all the standard library and tokio rely on for send_to and recv_from.
+    let socket = unsafe { std::net::UdpSocket::from_raw_fd(fd) };
+    socket.set_nonblocking(true)?;
+    tokio::net::UdpSocket::from_std(socket)
+}
+
+/// Echo request number `sequence`, its payload marked as ours; the kernel
+/// fills in the checksum of ICMPv6 messages
+fn echo_request(v6: bool, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(8 + PAYLOAD_LEN);
+    packet.extend_from_slice(&[if v6 { 128 } else { 8 }, 0, 0, 0]);
+    packet.extend_from_slice(&identifier.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(&PAYLOAD_MAGIC);
+    // Datagram sockets replace the header's identifier, so the payload carries it as well
+    packet.extend_from_slice(&identifier.to_be_bytes());
+    packet.resize(8 + PAYLOAD_LEN, 0);
+    if !v6 {
+        let checksum = internet_checksum(&packet);
+        packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    packet
+}
+
+/// Sequence number of an echo reply to one of our requests, skipping the IPv4
+/// header that raw sockets (and datagram sockets on macOS) deliver with it
+fn echo_reply_sequence(v6: bool, identifier: u16, packet: &[u8]) -> Option<u16> {
+    let packet = if !v6 && packet.first()? >> 4 == 4 {
+        packet.get(usize::from(packet[0] & 0x0f) * 4..)?
+    } else {
+        packet
+    };
+    let is_reply = *packet.first()? == if v6 { 129 } else { 0 };
+    let ours = packet.get(8..12)? == PAYLOAD_MAGIC && packet.get(12..14)? == identifier.to_be_bytes();
+    (is_reply && ours).then(|| u16::from_be_bytes([packet[6], packet[7]]))
+}
+
+/// The one's complement sum of RFC 1071
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data.chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])))
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::TimingMetrics, types::DnsConfig};
+
+    #[test]
+    fn test_echo_packets() {
+        let request = echo_request(false, 0x1234, 7);
+        assert_eq!(request.len(), 64);
+        assert_eq!(&request[..2], [8, 0]);
+        // A packet with its checksum in place sums to zero
+        assert_eq!(internet_checksum(&request), 0);
+
+        // The reply echoes the request with type 0, behind the IPv4 header of a raw socket
+        let mut reply = request.clone();
+        reply[0] = 0;
+        assert_eq!(echo_reply_sequence(false, 0x1234, &reply), Some(7));
+        let mut with_header = vec![0x45; 1];
+        with_header.resize(20, 0);
+        with_header.extend_from_slice(&reply);
+        assert_eq!(echo_reply_sequence(false, 0x1234, &with_header), Some(7));
+        // Someone else's ping, or our own request looped back
+        assert_eq!(echo_reply_sequence(false, 0x4321, &reply), None);
+        assert_eq!(echo_reply_sequence(false, 0x1234, &request), None);
+
+        let mut reply = echo_request(true, 1, 3);
+        assert_eq!(reply[0], 128);
+        reply[0] = 129;
+        assert_eq!(echo_reply_sequence(true, 1, &reply), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_ping_results_share_addresses() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut results: Vec<TestResult> = ["System DNS", "Google DNS"].into_iter()
+            .map(|config_name| {
+                let mut result = TestResult::new(config_name, DnsConfig::System, "http://localhost/");
+                result.add_measurement(TimingMetrics::failed("refused".to_string()).with_resolved_ip(Some(loopback)));
+                result
+            })
+            .collect();
+        ping_results(results.iter_mut(), 3).await;
+
+        for result in &results {
+            let stats = &result.ping[0];
+            assert_eq!(stats.address, loopback);
+            // Where ICMP sockets are allowed, loopback answers every echo request
+            if stats.error.is_none() {
+                assert_eq!((stats.sent, stats.received), (3, 3), "{}", stats);
+                assert!(result.ping_rtt_ms().is_some());
+            }
+        }
+        assert_eq!(results[0].ping, results[1].ping);
+    }
+}
@@ -0,0 +1,215 @@
+//! Runtime-agnostic probing core
+//!
+//! [`Prober`] times requests made through any [`Fetch`] implementation and turns
+//! them into [`TestResult`]s that the [`stats`](crate::stats) engine can analyze.
+//! It uses no async runtime, timer or socket API of its own, so it builds without
+//! the `native` feature and can run on `wasm32-wasi` or inside an edge runtime
+//! whose host provides `fetch`. Timeouts are enforced by the [`Fetch`]
+//! implementation, which is handed the deadline with every request.
+
+use crate::{
+    error::{AppError, Result},
+    models::{
+        metrics::{RequestPhase, TestResult, TimingMetrics},
+        Config,
+    },
+    timing,
+    types::{DisplayNames, DnsConfig},
+};
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::{net::IpAddr, time::{Duration, Instant}};
+
+/// A request for a [`Fetch`] implementation to perform
+#[derive(Debug, Clone, Copy)]
+pub struct FetchRequest<'a> {
+    /// URL to request
+    pub url: &'a str,
+    /// Give up after this long and return [`AppError::Timeout`]
+    pub timeout: Duration,
+}
+
+/// Outcome of a completed request
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Address of the server that answered, when the platform exposes it
+    pub remote_ip: Option<IpAddr>,
+    /// Phase timings measured by the platform; the prober's own wall-clock
+    /// measurement is used when absent
+    pub timing: Option<TimingMetrics>,
+}
+
+impl FetchResponse {
+    /// A response with only a status code
+    pub fn new(status: u16) -> Self {
+        Self { status, remote_ip: None, timing: None }
+    }
+}
+
+/// Performs a single HTTP request
+///
+/// Implementations wrap whatever the platform offers: the host `fetch` on edge
+/// runtimes, an outbound HTTP handler on WASI, or `client::ClientFetch` over an
+/// `HttpClient` natively. Futures need not be
+/// `Send`, since single-threaded runtimes are the common case there.
+#[async_trait(?Send)]
+pub trait Fetch {
+    /// Perform `request`, returning [`AppError::Timeout`] if its timeout elapses
+    async fn fetch(&self, request: FetchRequest<'_>) -> Result<FetchResponse>;
+}
+
+/// Times repeated requests to a URL through a [`Fetch`] implementation
+pub struct Prober<F> {
+    fetch: F,
+    test_count: u32,
+    timeout: Duration,
+    dns_config: DnsConfig,
+    display_names: DisplayNames,
+}
+
+impl<F: Fetch> Prober<F> {
+    /// Create a prober using the test count, timeout and display names from `config`
+    pub fn new(fetch: F, config: &Config) -> Self {
+        Self {
+            fetch,
+            test_count: config.test_count,
+            timeout: config.timeout,
+            dns_config: DnsConfig::System,
+            display_names: config.display_names(),
+        }
+    }
+
+    /// Report results under `dns_config` instead of the system resolver
+    ///
+    /// The prober does not resolve names itself; this only labels the results
+    /// for a [`Fetch`] implementation that resolves through `dns_config`.
+    pub fn with_dns_config(mut self, dns_config: DnsConfig) -> Self {
+        self.dns_config = dns_config;
+        self
+    }
+
+    /// Probe `url` the configured number of times, one request after another
+    pub async fn probe(&self, url: &str) -> TestResult {
+        let config_name = self.display_names.resolve(&self.dns_config);
+        let mut result = TestResult::new(config_name, self.dns_config.clone(), url.to_string());
+        // Whether the previous request got a response, leaving a connection open
+        let mut connection_ready = false;
+
+        for iteration in 0..self.test_count {
+            let start = Instant::now();
+            let outcome = self.fetch.fetch(FetchRequest { url, timeout: self.timeout }).await;
+            let elapsed = start.elapsed();
+
+            let metrics = match outcome {
+                Ok(response) => {
+                    connection_ready = true;
+                    Self::response_metrics(response, elapsed)
+                }
+                Err(AppError::Timeout(..)) => {
+                    let phase = if connection_ready {
+                        RequestPhase::AwaitingResponse
+                    } else {
+                        RequestPhase::Connecting
+                    };
+                    connection_ready = false;
+                    TimingMetrics::timeout(self.timeout)
+                        .with_phase_reached(phase)
+                        .with_total_duration(elapsed.max(self.timeout))
+                }
+                Err(e) => {
+                    connection_ready = false;
+                    TimingMetrics::failed(e.display_chain()).with_total_duration(elapsed)
+                }
+            };
+            let started_at = timing::process_clock().timestamp_of(start);
+            result.add_measurement(metrics.with_started_at(started_at).with_first_use(iteration == 0));
+        }
+
+        result.calculate_statistics();
+        result
+    }
+
+    /// Probe every URL, interleaving the requests for different URLs
+    pub async fn probe_all(&self, urls: &[String]) -> Vec<TestResult> {
+        join_all(urls.iter().map(|url| self.probe(url))).await
+    }
+
+    fn response_metrics(response: FetchResponse, elapsed: Duration) -> TimingMetrics {
+        if !(200..300).contains(&response.status) {
+            let total = response.timing.map_or(elapsed, |t| t.total_duration);
+            let mut metrics = TimingMetrics::failed(format!("HTTP {}", response.status))
+                .with_total_duration(total)
+                .with_resolved_ip(response.remote_ip);
+            metrics.http_status = response.status;
+            return metrics;
+        }
+
+        match response.timing {
+            Some(timing) => timing.with_resolved_ip(response.remote_ip),
+            // Without phase timings the whole request counts as time to first byte
+            None => TimingMetrics::success(Duration::ZERO, Duration::ZERO, None, elapsed, elapsed, response.status)
+                .with_resolved_ip(response.remote_ip),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stats::StatisticsEngine, types::TestStatus};
+    use std::cell::RefCell;
+
+    /// Replays canned outcomes in order
+    struct ScriptedFetch(RefCell<Vec<Result<FetchResponse>>>);
+
+    #[async_trait(?Send)]
+    impl Fetch for ScriptedFetch {
+        async fn fetch(&self, _request: FetchRequest<'_>) -> Result<FetchResponse> {
+            self.0.borrow_mut().remove(0)
+        }
+    }
+
+    fn timed(status: u16, total_ms: u64) -> FetchResponse {
+        let total = Duration::from_millis(total_ms);
+        FetchResponse {
+            status,
+            remote_ip: None,
+            timing: Some(TimingMetrics::success(Duration::ZERO, Duration::ZERO, None, total, total, status)),
+        }
+    }
+
+    #[test]
+    fn test_probe_without_async_runtime() {
+        let fetch = ScriptedFetch(RefCell::new(vec![
+            Ok(timed(200, 120)),
+            Ok(FetchResponse::new(200)),
+            Err(AppError::timeout("deadline elapsed")),
+            Err(AppError::network("connection reset")),
+            Ok(timed(503, 30)),
+        ]));
+        let config = Config { test_count: 5, timeout: Duration::from_millis(500), ..Default::default() };
+        let prober = Prober::new(fetch, &config);
+
+        let result = futures::executor::block_on(prober.probe("https://api.example"));
+        assert_eq!(result.config_name, "System DNS");
+        let statuses: Vec<TestStatus> = result.individual_results.iter().map(|m| m.status).collect();
+        assert_eq!(statuses, [
+            TestStatus::Success, TestStatus::Success, TestStatus::Timeout, TestStatus::Failed, TestStatus::Failed,
+        ]);
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.total_count, 5);
+        assert!(result.individual_results[0].is_first_use);
+        assert_eq!(result.individual_results[0].total_duration, Duration::from_millis(120));
+        assert_eq!(result.individual_results[2].phase_reached, Some(RequestPhase::AwaitingResponse));
+        assert_eq!(result.individual_results[2].total_duration, Duration::from_millis(500));
+        assert!(result.individual_results[3].error_message.as_deref().unwrap().contains("connection reset"));
+        assert_eq!(result.individual_results[4].http_status, 503);
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![result]);
+        let analysis = engine.analyze().unwrap();
+        assert_eq!(analysis.basic_stats["System DNS"].reliability.success_rate, 40.0);
+    }
+}
@@ -0,0 +1,364 @@
+//! Filter expression parser and evaluator
+//!
+//! Grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! expr       := and ("OR" and)*
+//! and        := unary ("AND" unary)*
+//! unary      := "NOT" unary | "(" expr ")" | comparison
+//! comparison := FIELD op VALUE
+//! op         := "=" | "!=" | "~" | "!~" | "<" | "<=" | ">" | ">="
+//! ```
+//!
+//! Values may be bare words or single/double quoted strings. Text comparisons
+//! are case-insensitive; `~` tests for a substring.
+
+use super::{FieldKind, FieldValue, ResultRow};
+use crate::error::{AppError, Result};
+use std::fmt;
+
+/// Comparison operator in a filter expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    NotEq,
+    Contains,
+    NotContains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::NotEq => "!=",
+            Operator::Contains => "~",
+            Operator::NotContains => "!~",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+        }
+    }
+}
+
+/// Parsed filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: String,
+        op: Operator,
+        value: String,
+    },
+}
+
+impl Expr {
+    /// Parse and validate a filter expression
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expr = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(AppError::parse(format!("Unexpected '{}' in filter expression", token)));
+        }
+
+        Ok(expr)
+    }
+
+    /// Evaluate the expression against a result row
+    pub fn matches(&self, row: &ResultRow) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(row) && right.matches(row),
+            Expr::Or(left, right) => left.matches(row) || right.matches(row),
+            Expr::Not(inner) => !inner.matches(row),
+            Expr::Compare { field, op, value } => compare(row.field(field), *op, value),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::And(left, right) => write!(f, "({} AND {})", left, right),
+            Expr::Or(left, right) => write!(f, "({} OR {})", left, right),
+            Expr::Not(inner) => write!(f, "NOT {}", inner),
+            Expr::Compare { field, op, value } => write!(f, "{}{}{:?}", field, op.symbol(), value),
+        }
+    }
+}
+
+fn compare(actual: Option<FieldValue>, op: Operator, expected: &str) -> bool {
+    match actual {
+        Some(FieldValue::Text(text)) => {
+            let text = text.to_lowercase();
+            let expected = expected.to_lowercase();
+            match op {
+                Operator::Eq => text == expected,
+                Operator::NotEq => text != expected,
+                Operator::Contains => text.contains(&expected),
+                Operator::NotContains => !text.contains(&expected),
+                Operator::Lt => text < expected,
+                Operator::Le => text <= expected,
+                Operator::Gt => text > expected,
+                Operator::Ge => text >= expected,
+            }
+        }
+        Some(FieldValue::Number(number)) => {
+            // Values are validated as numeric when the expression is parsed
+            let Ok(expected) = expected.parse::<f64>() else {
+                return false;
+            };
+            match op {
+                Operator::Eq => number == expected,
+                Operator::NotEq => number != expected,
+                Operator::Lt => number < expected,
+                Operator::Le => number <= expected,
+                Operator::Gt => number > expected,
+                Operator::Ge => number >= expected,
+                Operator::Contains | Operator::NotContains => false,
+            }
+        }
+        // Metrics without successful samples never match
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Operator),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Quoted(text) => write!(f, "{:?}", text),
+            Token::Op(op) => write!(f, "{}", op.symbol()),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(AppError::parse("Unterminated quoted value in filter expression")),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            '=' | '~' | '<' | '>' | '!' => {
+                chars.next();
+                let followed_by_eq = chars.peek() == Some(&'=');
+                let op = match (c, followed_by_eq) {
+                    ('=', _) => Operator::Eq,
+                    ('~', _) => Operator::Contains,
+                    ('<', true) => Operator::Le,
+                    ('<', false) => Operator::Lt,
+                    ('>', true) => Operator::Ge,
+                    ('>', false) => Operator::Gt,
+                    ('!', true) => Operator::NotEq,
+                    ('!', false) if chars.peek() == Some(&'~') => Operator::NotContains,
+                    _ => return Err(AppError::parse("Expected '!=' or '!~' in filter expression")),
+                };
+                if matches!(op, Operator::Le | Operator::Ge | Operator::NotEq | Operator::NotContains) {
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "()=~<>!\"'".contains(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    if tokens.is_empty() {
+        return Err(AppError::parse("Filter expression cannot be empty"));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.eat_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.peek() == Some(&Token::LParen) {
+            self.position += 1;
+            let expr = self.parse_or()?;
+            return match self.next() {
+                Some(Token::RParen) => Ok(expr),
+                _ => Err(AppError::parse("Missing ')' in filter expression")),
+            };
+        }
+
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let field = match self.next() {
+            Some(Token::Word(word)) => word.to_lowercase(),
+            Some(token) => return Err(AppError::parse(format!("Expected a field name, found '{}'", token))),
+            None => return Err(AppError::parse("Filter expression ends unexpectedly")),
+        };
+
+        let kind = ResultRow::field_kind(&field).ok_or_else(|| {
+            AppError::parse(format!(
+                "Unknown field '{}' (available: {})",
+                field,
+                ResultRow::FIELDS.join(", ")
+            ))
+        })?;
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err(AppError::parse(format!("Expected an operator after '{}'", field))),
+        };
+
+        let value = match self.next() {
+            Some(Token::Word(word)) | Some(Token::Quoted(word)) => word,
+            _ => return Err(AppError::parse(format!("Expected a value after '{}{}'", field, op.symbol()))),
+        };
+
+        if kind == FieldKind::Number {
+            if matches!(op, Operator::Contains | Operator::NotContains) {
+                return Err(AppError::parse(format!("Operator '{}' cannot be used with numeric field '{}'", op.symbol(), field)));
+            }
+            if value.parse::<f64>().is_err() {
+                return Err(AppError::parse(format!("Field '{}' is numeric but '{}' is not a number", field, value)));
+            }
+        }
+
+        Ok(Expr::Compare { field, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(field: &str, op: Operator, value: &str) -> Expr {
+        Expr::Compare { field: field.to_string(), op, value: value.to_string() }
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr = Expr::parse("config=Cloudflare AND url~api OR NOT p95 >= 200").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Or(
+                Box::new(Expr::And(
+                    Box::new(compare("config", Operator::Eq, "Cloudflare")),
+                    Box::new(compare("url", Operator::Contains, "api")),
+                )),
+                Box::new(Expr::Not(Box::new(compare("p95", Operator::Ge, "200")))),
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_parentheses_and_quotes() {
+        let expr = Expr::parse("(config = 'System DNS' or config!~\"doh\") and success_rate<100").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "((config=\"System DNS\" OR config!~\"doh\") AND success_rate<\"100\")"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Expr::parse("").is_err());
+        assert!(Expr::parse("latency>5").is_err());
+        assert!(Expr::parse("p95~5").is_err());
+        assert!(Expr::parse("p95>fast").is_err());
+        assert!(Expr::parse("config=").is_err());
+        assert!(Expr::parse("(config=a").is_err());
+        assert!(Expr::parse("config='a").is_err());
+        assert!(Expr::parse("config=a url=b").is_err());
+        assert!(Expr::parse("config!a").is_err());
+    }
+}
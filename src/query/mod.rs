@@ -0,0 +1,408 @@
+//! Querying of exported test results
+//!
+//! Lets users slice a results file written with `--export` without external
+//! tools: rows (one per URL and DNS configuration) are filtered with a small
+//! expression language, reduced to a single metric and optionally grouped.
+
+pub mod expr;
+
+pub use expr::{Expr, Operator};
+
+use crate::{
+    error::{AppError, Result},
+    executor::ExecutionResults,
+    models::metrics::TestResult,
+    stats::StatisticsEngine,
+    types::DnsConfig,
+    utils::url::display_url,
+};
+use std::collections::BTreeMap;
+
+/// Type of a queryable field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Number,
+}
+
+/// Value of a field for a particular row
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Number(f64),
+}
+
+/// Queryable view of a single URL / DNS configuration result
+#[derive(Debug, Clone)]
+pub struct ResultRow<'a> {
+    result: &'a TestResult,
+    tags: &'a [String],
+    sorted_totals: Vec<f64>,
+}
+
+impl<'a> ResultRow<'a> {
+    /// All fields that can be used in filters, metrics and grouping
+    pub const FIELDS: [&'static str; 18] = [
+        "config", "url", "dns", "tags",
+        "avg", "min", "max", "std_dev", "p50", "p90", "p95", "p99",
+        "dns_ms", "tcp_ms", "first_byte_ms",
+        "success_rate", "samples", "attempts",
+    ];
+
+    /// Build a row from a test result and the tags of its URL
+    pub fn new(result: &'a TestResult, tags: &'a [String]) -> Self {
+        let mut sorted_totals: Vec<f64> = result.individual_results.iter()
+            .filter(|m| m.is_successful())
+            .map(|m| m.total_ms())
+            .collect();
+        sorted_totals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        Self { result, tags, sorted_totals }
+    }
+
+    /// Kind of a field, or None if the field does not exist
+    pub fn field_kind(field: &str) -> Option<FieldKind> {
+        match field {
+            "config" | "url" | "dns" | "tags" => Some(FieldKind::Text),
+            f if Self::FIELDS.contains(&f) => Some(FieldKind::Number),
+            _ => None,
+        }
+    }
+
+    /// Underlying test result
+    pub fn result(&self) -> &TestResult {
+        self.result
+    }
+
+    /// Value of a field; numeric timing fields are None when there were no successful samples
+    pub fn field(&self, field: &str) -> Option<FieldValue> {
+        let stats = self.result.statistics.as_ref().filter(|s| s.sample_count > 0);
+        let number = |value: Option<f64>| value.map(FieldValue::Number);
+
+        match field {
+            "config" => Some(FieldValue::Text(self.result.config_name.to_string())),
+            "url" => Some(FieldValue::Text(self.result.url.to_string())),
+            "dns" => Some(FieldValue::Text(match self.result.dns_config {
+                DnsConfig::System => "system",
+                DnsConfig::Custom { .. } => "custom",
+                DnsConfig::DoH { .. } => "doh",
+                DnsConfig::Chain { .. } => "chain",
+                DnsConfig::Mdns => "mdns",
+            }.to_string())),
+            "tags" => Some(FieldValue::Text(self.tags.join(","))),
+            "avg" => number(stats.map(|s| s.total_avg_ms)),
+            "min" => number(stats.map(|s| s.total_min_ms)),
+            "max" => number(stats.map(|s| s.total_max_ms)),
+            "std_dev" => number(stats.map(|s| s.total_std_dev_ms)),
+            "p50" => number(self.percentile(50.0)),
+            "p90" => number(self.percentile(90.0)),
+            "p95" => number(self.percentile(95.0)),
+            "p99" => number(self.percentile(99.0)),
+            "dns_ms" => number(stats.map(|s| s.dns_avg_ms)),
+            "tcp_ms" => number(stats.map(|s| s.tcp_avg_ms)),
+            "first_byte_ms" => number(stats.map(|s| s.first_byte_avg_ms)),
+            "success_rate" => Some(FieldValue::Number(self.result.success_rate())),
+            "samples" => Some(FieldValue::Number(self.result.success_count as f64)),
+            "attempts" => Some(FieldValue::Number(self.result.total_count as f64)),
+            _ => None,
+        }
+    }
+
+    /// Numeric value of a field, if it has one
+    pub fn number(&self, field: &str) -> Option<f64> {
+        match self.field(field) {
+            Some(FieldValue::Number(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Text value of a field (numbers are formatted)
+    pub fn text(&self, field: &str) -> String {
+        match self.field(field) {
+            Some(FieldValue::Text(text)) => text,
+            Some(FieldValue::Number(value)) => format!("{}", value),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Percentile of successful total times, interpolated as in the analysis
+    fn percentile(&self, percentile: f64) -> Option<f64> {
+        (!self.sorted_totals.is_empty()).then(|| StatisticsEngine::calculate_percentile(&self.sorted_totals, percentile))
+    }
+}
+
+/// A query over exported results
+#[derive(Debug, Clone)]
+pub struct Query {
+    /// Optional row filter
+    pub filter: Option<Expr>,
+    /// Numeric field to report
+    pub metric: String,
+    /// Optional text field to group rows by
+    pub group_by: Option<String>,
+}
+
+impl Query {
+    /// Build a query, validating the filter expression, metric and grouping field
+    pub fn new(filter: Option<&str>, metric: &str, group_by: Option<&str>) -> Result<Self> {
+        let filter = filter.map(Expr::parse).transpose()?;
+
+        let metric = metric.to_lowercase();
+        if ResultRow::field_kind(&metric) != Some(FieldKind::Number) {
+            return Err(AppError::validation(format!(
+                "Unknown metric '{}' (available: {})",
+                metric,
+                ResultRow::FIELDS.iter().filter(|f| ResultRow::field_kind(f) == Some(FieldKind::Number)).cloned().collect::<Vec<_>>().join(", ")
+            )));
+        }
+
+        let group_by = group_by.map(str::to_lowercase);
+        if let Some(ref field) = group_by {
+            if ResultRow::field_kind(field) != Some(FieldKind::Text) {
+                return Err(AppError::validation(format!(
+                    "Cannot group by '{}' (available: config, url, dns, tags)", field
+                )));
+            }
+        }
+
+        Ok(Self { filter, metric, group_by })
+    }
+
+    /// Run the query against a set of results
+    pub fn execute(&self, results: &ExecutionResults) -> QueryOutput {
+        let tags = &results.execution_summary.url_tags;
+        let mut rows: Vec<ResultRow> = results.test_results.values()
+            .map(|result| ResultRow::new(result, tags.get(result.url.as_str()).map_or(&[], Vec::as_slice)))
+            .filter(|row| self.filter.as_ref().is_none_or(|f| f.matches(row)))
+            .collect();
+
+        // Rows without a value for the metric sort last
+        rows.sort_by(|a, b| match (a.number(&self.metric), b.number(&self.metric)) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }.then_with(|| a.text("url").cmp(&b.text("url")))
+            .then_with(|| a.text("config").cmp(&b.text("config"))));
+
+        let to_output_row = |row: &ResultRow| QueryRow {
+            config: row.text("config"),
+            url: row.text("url"),
+            value: row.number(&self.metric),
+        };
+
+        let groups = match &self.group_by {
+            Some(field) => {
+                let mut grouped: BTreeMap<String, Vec<QueryRow>> = BTreeMap::new();
+                for row in &rows {
+                    grouped.entry(row.text(field)).or_default().push(to_output_row(row));
+                }
+                grouped.into_iter()
+                    .map(|(key, rows)| QueryGroup { key: Some(key), rows })
+                    .collect()
+            }
+            None => vec![QueryGroup { key: None, rows: rows.iter().map(to_output_row).collect() }],
+        };
+
+        QueryOutput {
+            metric: self.metric.clone(),
+            group_by: self.group_by.clone(),
+            groups,
+        }
+    }
+}
+
+/// One matching row of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryRow {
+    pub config: String,
+    pub url: String,
+    /// Metric value (None when the result has no successful samples)
+    pub value: Option<f64>,
+}
+
+/// Rows sharing the same grouping value
+#[derive(Debug, Clone)]
+pub struct QueryGroup {
+    /// Grouping value, or None when the query is not grouped
+    pub key: Option<String>,
+    pub rows: Vec<QueryRow>,
+}
+
+impl QueryGroup {
+    /// Minimum, mean and maximum of the metric over rows that have a value
+    pub fn summary(&self) -> Option<(f64, f64, f64)> {
+        let values: Vec<f64> = self.rows.iter().filter_map(|r| r.value).collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        Some((min, mean, max))
+    }
+}
+
+/// Result of running a query
+#[derive(Debug, Clone)]
+pub struct QueryOutput {
+    pub metric: String,
+    pub group_by: Option<String>,
+    pub groups: Vec<QueryGroup>,
+}
+
+impl QueryOutput {
+    /// Total number of matching rows
+    pub fn row_count(&self) -> usize {
+        self.groups.iter().map(|g| g.rows.len()).sum()
+    }
+
+    /// Render the output as a plain-text table
+    pub fn format_table(&self) -> String {
+        let mut output = String::new();
+
+        if self.row_count() == 0 {
+            output.push_str("No results match the query.\n");
+            return output;
+        }
+
+        let config_width = self.groups.iter()
+            .flat_map(|g| g.rows.iter().map(|r| r.config.chars().count()))
+            .chain(std::iter::once("Configuration".len()))
+            .max()
+            .unwrap_or(0);
+        let url_width = self.groups.iter()
+            .flat_map(|g| g.rows.iter().map(|r| display_url(&r.url).chars().count()))
+            .chain(std::iter::once("Target URL".len()))
+            .max()
+            .unwrap_or(0);
+
+        for group in &self.groups {
+            if let (Some(field), Some(key)) = (&self.group_by, &group.key) {
+                output.push_str(&format!("{} = {}\n", field, display_url(key)));
+            }
+
+            output.push_str(&format!(
+                "  {:<config_width$}  {:<url_width$}  {:>10}\n",
+                "Configuration", "Target URL", self.metric
+            ));
+            for row in &group.rows {
+                let value = row.value.map_or("-".to_string(), |v| format!("{:.1}", v));
+                output.push_str(&format!(
+                    "  {:<config_width$}  {:<url_width$}  {:>10}\n",
+                    row.config, display_url(&row.url), value
+                ));
+            }
+
+            if let Some((min, mean, max)) = group.summary() {
+                output.push_str(&format!(
+                    "  {} rows; {} min {:.1}, mean {:.1}, max {:.1}\n",
+                    group.rows.len(), self.metric, min, mean, max
+                ));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionSummary;
+    use crate::models::metrics::TimingMetrics;
+    use crate::test_support::result_with_totals_via;
+
+    fn sample_results() -> ExecutionResults {
+        let cloudflare = DnsConfig::DoH { url: "https://cloudflare-dns.com/dns-query".to_string() };
+        let mut failed = TestResult::new("Custom DNS (8.8.8.8)".to_string(), DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap()] }, "https://api.example.com".to_string());
+        failed.add_measurement(TimingMetrics::failed("refused".to_string()));
+        failed.calculate_statistics();
+
+        ExecutionResults::new(
+            ExecutionSummary {
+                url_tags: BTreeMap::from([
+                    ("https://api.example.com".to_string(), vec!["prod".to_string(), "api".to_string()]),
+                ]),
+                ..Default::default()
+            },
+            vec![
+                result_with_totals_via("System DNS", DnsConfig::System, "https://api.example.com", &[100, 110, 300]),
+                result_with_totals_via("DoH (Cloudflare)", cloudflare.clone(), "https://api.example.com", &[80, 90, 100]),
+                result_with_totals_via("System DNS", DnsConfig::System, "https://www.example.com", &[50, 60, 70]),
+                result_with_totals_via("DoH (Cloudflare)", cloudflare, "https://www.example.com", &[40, 45, 200]),
+                failed,
+            ],
+        )
+    }
+
+    #[test]
+    fn test_filter_and_metric() {
+        let results = sample_results();
+        let query = Query::new(Some("config~cloudflare AND url~api"), "p95", None).unwrap();
+        let output = query.execute(&results);
+
+        assert_eq!(output.row_count(), 1);
+        let row = &output.groups[0].rows[0];
+        assert_eq!(row.config, "DoH (Cloudflare)");
+        assert!((row.value.unwrap() - 99.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_group_by_url_sorted_by_metric() {
+        let results = sample_results();
+        let query = Query::new(None, "avg", Some("url")).unwrap();
+        let output = query.execute(&results);
+
+        assert_eq!(output.groups.len(), 2);
+        let api = &output.groups[0];
+        assert_eq!(api.key.as_deref(), Some("https://api.example.com"));
+        assert_eq!(api.rows.iter().map(|r| r.config.as_str()).collect::<Vec<_>>(),
+            vec!["DoH (Cloudflare)", "System DNS", "Custom DNS (8.8.8.8)"]);
+        assert_eq!(api.rows[2].value, None);
+        assert_eq!(api.summary(), Some((90.0, (90.0 + 170.0) / 2.0, 170.0)));
+
+        let table = output.format_table();
+        assert!(table.contains("url = https://www.example.com"));
+        assert!(table.contains("2 rows; avg min"));
+    }
+
+    #[test]
+    fn test_numeric_and_dns_filters() {
+        let results = sample_results();
+
+        let output = Query::new(Some("dns=system AND max > 100"), "max", None).unwrap().execute(&results);
+        assert_eq!(output.row_count(), 1);
+        assert_eq!(output.groups[0].rows[0].url, "https://api.example.com");
+
+        let output = Query::new(Some("success_rate < 100 OR NOT (dns = doh OR dns = system)"), "avg", None).unwrap().execute(&results);
+        assert_eq!(output.row_count(), 1);
+        assert_eq!(output.groups[0].rows[0].config, "Custom DNS (8.8.8.8)");
+
+        let output = Query::new(Some("config=nothing"), "avg", None).unwrap().execute(&results);
+        assert!(output.format_table().contains("No results match"));
+    }
+
+    #[test]
+    fn test_tags_filter_and_grouping() {
+        let results = sample_results();
+
+        let output = Query::new(Some("tags~prod"), "avg", None).unwrap().execute(&results);
+        assert_eq!(output.row_count(), 3);
+        assert!(output.groups[0].rows.iter().all(|r| r.url == "https://api.example.com"));
+
+        let output = Query::new(None, "avg", Some("tags")).unwrap().execute(&results);
+        let keys: Vec<_> = output.groups.iter().map(|g| g.key.as_deref().unwrap()).collect();
+        assert_eq!(keys, ["", "prod,api"]);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        assert!(Query::new(None, "config", None).is_err());
+        assert!(Query::new(None, "latency", None).is_err());
+        assert!(Query::new(None, "avg", Some("p95")).is_err());
+        assert!(Query::new(Some("p95 ~ 1"), "avg", None).is_err());
+    }
+}
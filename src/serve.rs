@@ -0,0 +1,596 @@
+//! HTTP endpoints for a watch-mode instance
+//!
+//! With `--serve ADDR`, a `--watch` run keeps the samples of its recent cycles
+//! in a [`SampleStore`] and answers HTTP requests for them, so that Grafana can
+//! chart the probe without a separate time-series database:
+//!
+//! - `GET /` answers `OK`, which is how the Grafana JSON datasource tests a connection
+//! - `POST /search` lists the series, named `CONFIG | URL`
+//! - `POST /query` returns the total latency of the requested series over the
+//!   dashboard's time range, as `[milliseconds, epoch milliseconds]` pairs; a
+//!   series named by its configuration alone covers all of its URLs
+//! - `GET /samples` returns samples as rows for the Infinity datasource,
+//!   filtered by the `config`, `url`, `from` and `to` query parameters, the
+//!   times as RFC 3339 or epoch milliseconds
+//!
+//! Only requests that succeeded are charted; `/samples` includes failures.
+//! Responses carry CORS headers only for the origin given with `--serve-cors`,
+//! so other web pages cannot read the samples from a browser.
+//!
+//! For running under an orchestrator such as Kubernetes there are also:
+//!
+//! - `GET /healthz`, the liveness probe, which fails once no cycle has
+//!   finished for three intervals plus [`STALL_GRACE`], unless paused
+//! - `GET /readyz`, the readiness probe, which succeeds from the first cycle
+//!   that finished without error on
+//! - `GET /status`, the agent's own metrics: uptime, cycles run and failed,
+//!   samples kept and the last error
+
+use crate::error::{AppError, Result};
+use crate::models::metrics::TestResult;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Semaphore,
+    task::JoinHandle,
+};
+
+/// Samples kept in memory, oldest dropped first; about a day of a typical watch
+pub const MAX_SAMPLES: usize = 200_000;
+/// Longest request head (request line and headers) accepted
+const MAX_HEAD_LEN: u64 = 16 * 1024;
+/// Longest request body accepted
+const MAX_BODY_LEN: usize = 1024 * 1024;
+/// Time a client has to send its whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections served at once; further ones wait in the listen backlog
+const MAX_CONNECTIONS: usize = 64;
+/// Separates the configuration from the URL in series names
+const SERIES_SEPARATOR: &str = " | ";
+/// Time a cycle may take on top of the interval before the agent counts as stalled
+pub const STALL_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// One request of a watch cycle
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    pub time: DateTime<Utc>,
+    pub config: String,
+    pub url: String,
+    pub total_ms: f64,
+    pub success: bool,
+}
+
+/// Which samples to return
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SampleFilter {
+    pub config: Option<String>,
+    pub url: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl SampleFilter {
+    fn matches(&self, sample: &Sample) -> bool {
+        self.config.as_ref().is_none_or(|config| *config == sample.config)
+            && self.url.as_ref().is_none_or(|url| *url == sample.url)
+            && self.from.is_none_or(|from| sample.time >= from)
+            && self.to.is_none_or(|to| sample.time <= to)
+    }
+}
+
+/// The samples of recent cycles, up to a fixed number
+#[derive(Debug, Clone)]
+pub struct SampleStore {
+    samples: VecDeque<Sample>,
+    capacity: usize,
+}
+
+/// How the watch loop is doing
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentStatus {
+    pub started_at: DateTime<Utc>,
+    /// Time between cycles
+    pub interval: Duration,
+    pub cycles: u32,
+    pub failed_cycles: u32,
+    pub last_cycle_at: Option<DateTime<Utc>>,
+    /// Whether a cycle has finished without error
+    pub ready: bool,
+    /// When the last failed cycle finished, and why it failed
+    pub last_error: Option<(DateTime<Utc>, String)>,
+    pub paused: bool,
+}
+
+impl AgentStatus {
+    pub fn new(started_at: DateTime<Utc>, interval: Duration) -> Self {
+        Self {
+            started_at,
+            interval,
+            cycles: 0,
+            failed_cycles: 0,
+            last_cycle_at: None,
+            ready: false,
+            last_error: None,
+            paused: false,
+        }
+    }
+
+    /// Note a finished cycle and its outcome
+    pub fn record_cycle(&mut self, at: DateTime<Utc>, error: Option<String>) {
+        self.cycles += 1;
+        self.last_cycle_at = Some(at);
+        match error {
+            Some(message) => {
+                self.failed_cycles += 1;
+                self.last_error = Some((at, message));
+            }
+            None => self.ready = true,
+        }
+    }
+
+    /// Whether cycles stopped finishing while not paused
+    pub fn is_stalled(&self, now: DateTime<Utc>) -> bool {
+        let since = self.last_cycle_at.unwrap_or(self.started_at);
+        let allowed = self.interval * 3 + STALL_GRACE;
+        !self.paused && (now - since).to_std().is_ok_and(|elapsed| elapsed > allowed)
+    }
+}
+
+/// What the HTTP server answers from
+#[derive(Debug, Clone)]
+pub struct Served {
+    pub samples: SampleStore,
+    pub status: AgentStatus,
+}
+
+/// State shared between the watch loop and the HTTP server
+pub type SharedState = Arc<Mutex<Served>>;
+
+impl SampleStore {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::new(), capacity }
+    }
+
+    /// Keep the requests of a cycle's results
+    pub fn record<'a>(&mut self, results: impl IntoIterator<Item = &'a TestResult>) {
+        for result in results {
+            for measurement in &result.individual_results {
+                self.samples.push_back(Sample {
+                    time: measurement.timestamp,
+                    config: result.config_name.to_string(),
+                    url: result.url.to_string(),
+                    total_ms: measurement.total_ms(),
+                    success: measurement.is_successful(),
+                });
+            }
+        }
+        let excess = self.samples.len().saturating_sub(self.capacity);
+        self.samples.drain(..excess);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Names of the series there are samples for, sorted
+    pub fn series(&self) -> Vec<String> {
+        let names: BTreeSet<String> = self.samples.iter()
+            .map(|sample| format!("{}{}{}", sample.config, SERIES_SEPARATOR, sample.url))
+            .collect();
+        names.into_iter().collect()
+    }
+
+    /// Samples matching `filter`, oldest first
+    pub fn query<'a>(&'a self, filter: &'a SampleFilter) -> impl Iterator<Item = &'a Sample> + 'a {
+        self.samples.iter().filter(move |sample| filter.matches(sample))
+    }
+}
+
+/// A series named in a Grafana query, `CONFIG` or `CONFIG | URL`
+fn series_filter(name: &str) -> SampleFilter {
+    let (config, url) = match name.split_once(SERIES_SEPARATOR) {
+        Some((config, url)) => (config, Some(url.to_string())),
+        None => (name, None),
+    };
+    SampleFilter { config: Some(config.trim().to_string()), url, ..Default::default() }
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryRequest {
+    #[serde(default)]
+    range: Option<QueryRange>,
+    #[serde(default)]
+    targets: Vec<QueryTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryTarget {
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    hide: bool,
+}
+
+/// An HTTP response
+#[derive(Debug, Clone, PartialEq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self { status, body: serde_json::json!({ "error": message }).to_string() }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            204 => "No Content",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Answer a request for `path` (with its query string) at `now`
+fn route(method: &str, path: &str, body: &str, served: &Served, now: DateTime<Utc>) -> Response {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let store = &served.samples;
+    let status = &served.status;
+    match (method, path) {
+        // Browsers ask before sending JSON from a dashboard on another origin
+        ("OPTIONS", _) => Response { status: 204, body: String::new() },
+        ("GET", "/") => Response::json(&"OK"),
+        ("POST", "/search") => Response::json(&store.series()),
+        ("POST", "/query") => match serde_json::from_str::<QueryRequest>(body) {
+            Ok(request) => Response::json(&query_series(&request, store)),
+            Err(e) => Response::error(400, &format!("invalid query: {}", e)),
+        },
+        ("GET", "/samples") => match samples_filter(query) {
+            Ok(filter) => Response::json(&store.query(&filter).collect::<Vec<_>>()),
+            Err(e) => Response::error(400, e.message()),
+        },
+        ("GET", "/healthz") if status.is_stalled(now) => Response::error(503, "stalled: no cycle finished for three intervals"),
+        ("GET", "/healthz") => Response::json(&serde_json::json!({ "status": "ok" })),
+        ("GET", "/readyz") if status.ready => Response::json(&serde_json::json!({ "status": "ready" })),
+        ("GET", "/readyz") => Response::error(503, "starting: no cycle has finished without error yet"),
+        ("GET", "/status") => Response::json(&status_report(served, now)),
+        (_, "/" | "/search" | "/query" | "/samples" | "/healthz" | "/readyz" | "/status") => {
+            Response::error(405, "method not allowed")
+        }
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// The agent's own metrics
+fn status_report(served: &Served, now: DateTime<Utc>) -> serde_json::Value {
+    let status = &served.status;
+    serde_json::json!({
+        "version": crate::VERSION,
+        "uptime_seconds": (now - status.started_at).num_seconds().max(0),
+        "interval_seconds": status.interval.as_secs_f64(),
+        "cycles": status.cycles,
+        "failed_cycles": status.failed_cycles,
+        "last_cycle_at": status.last_cycle_at,
+        "last_error": status.last_error.as_ref().map(|(at, message)| serde_json::json!({ "at": at, "message": message })),
+        "paused": status.paused,
+        "ready": status.ready,
+        "stalled": status.is_stalled(now),
+        "samples": served.samples.len(),
+    })
+}
+
+/// Datapoints of each series a Grafana query asks for
+fn query_series(request: &QueryRequest, store: &SampleStore) -> Vec<serde_json::Value> {
+    request.targets.iter()
+        .filter(|target| !target.hide && !target.target.is_empty())
+        .map(|target| {
+            let mut filter = series_filter(&target.target);
+            if let Some(ref range) = request.range {
+                filter.from = Some(range.from);
+                filter.to = Some(range.to);
+            }
+            let datapoints: Vec<(f64, i64)> = store.query(&filter)
+                .filter(|sample| sample.success)
+                .map(|sample| (sample.total_ms, sample.time.timestamp_millis()))
+                .collect();
+            serde_json::json!({ "target": target.target, "datapoints": datapoints })
+        })
+        .collect()
+}
+
+/// The filter given by the query string of a `/samples` request
+fn samples_filter(query: &str) -> Result<SampleFilter> {
+    let mut filter = SampleFilter::default();
+    for (name, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        match name.as_ref() {
+            "config" => filter.config = Some(value.into_owned()),
+            "url" => filter.url = Some(value.into_owned()),
+            "from" => filter.from = Some(parse_time(&value)?),
+            "to" => filter.to = Some(parse_time(&value)?),
+            _ => {}
+        }
+    }
+    Ok(filter)
+}
+
+/// An RFC 3339 time or epoch milliseconds, as Grafana's `${__from}` gives
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(millis) = value.parse::<i64>() {
+        return Utc.timestamp_millis_opt(millis).single()
+            .ok_or_else(|| AppError::validation(format!("time out of range: {}", value)));
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| AppError::validation(format!("invalid time '{}': {}", value, e)))
+}
+
+/// Serves a [`SharedState`] over HTTP until dropped
+pub struct Server {
+    addr: SocketAddr,
+    listener: JoinHandle<()>,
+}
+
+impl Server {
+    /// Start listening on `addr`; port 0 picks a free port. Browsers may read
+    /// the responses from pages of `cors_origin` only, and from none without one.
+    pub async fn bind(addr: SocketAddr, state: SharedState, cors_origin: Option<String>) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await
+            .map_err(|e| AppError::io(format!("Cannot listen on {}: {}", addr, e)).with_source(e))?;
+        let addr = listener.local_addr()
+            .map_err(|e| AppError::io(format!("Cannot listen on {}: {}", addr, e)).with_source(e))?;
+        let cors_origin = Arc::new(cors_origin);
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        let listener = tokio::spawn(async move {
+            loop {
+                let Ok(permit) = connections.clone().acquire_owned().await else { break };
+                let Ok((stream, _)) = listener.accept().await else { break };
+                let (state, cors_origin) = (state.clone(), cors_origin.clone());
+                tokio::spawn(async move {
+                    serve(stream, state, cors_origin.as_deref()).await;
+                    drop(permit);
+                });
+            }
+        });
+        Ok(Self { addr, listener })
+    }
+
+    /// Address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+/// Answer one request and close the connection
+async fn serve(stream: TcpStream, state: SharedState, cors_origin: Option<&str>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    // A client that connects and goes quiet must not hold its connection slot
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut reader)).await
+        .unwrap_or_else(|_| Err(Response::error(408, "the request took too long to arrive")));
+    let response = match request {
+        Ok((method, path, body)) => match state.lock() {
+            Ok(served) => route(&method, &path, &body, &served, Utc::now()),
+            Err(_) => Response::error(500, "the sample store is unavailable"),
+        },
+        Err(response) => response,
+    };
+    let cors = cors_origin.map_or_else(String::new, |origin| format!(
+        "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Headers: Content-Type\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\nVary: Origin\r\n",
+        origin
+    ));
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        response.status, response.reason(), response.body.len(), cors
+    );
+    let _ = writer.write_all(head.as_bytes()).await;
+    let _ = writer.write_all(response.body.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+/// Read the method, path and body of a request
+async fn read_request<R>(reader: &mut BufReader<R>) -> std::result::Result<(String, String, String), Response>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let bad_request = || Response::error(400, "malformed request");
+    let mut head = String::new();
+    let mut limited = reader.take(MAX_HEAD_LEN);
+    loop {
+        let read = limited.read_line(&mut head).await.map_err(|_| bad_request())?;
+        if read == 0 || head.ends_with("\r\n\r\n") || head.ends_with("\n\n") {
+            break;
+        }
+    }
+    let reader = limited.into_inner();
+
+    let mut lines = head.lines();
+    let mut request_line = lines.next().ok_or_else(bad_request)?.split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Err(bad_request());
+    };
+    let length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>().map_err(|_| bad_request()))
+        .transpose()?
+        .unwrap_or(0);
+    if length > MAX_BODY_LEN {
+        return Err(Response::error(413, "request body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.map_err(|_| bad_request())?;
+    let body = String::from_utf8(body).map_err(|_| bad_request())?;
+    Ok((method.to_string(), path.to_string(), body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::metrics::TimingMetrics;
+    use crate::test_support::success;
+    use crate::types::DnsConfig;
+    use std::time::Duration;
+
+    fn served() -> Served {
+        let started_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        Served { samples: store(), status: AgentStatus::new(started_at, Duration::from_secs(60)) }
+    }
+
+    fn store() -> SampleStore {
+        let mut store = SampleStore::new(4);
+        let mut results = Vec::new();
+        for (config, url, total) in [("System DNS", "https://a.example", 100), ("DoH", "https://a.example", 60), ("DoH", "https://b.example", 80)] {
+            let mut result = TestResult::new(config.to_string(), DnsConfig::System, url.to_string());
+            let mut sample = success(total);
+            sample.timestamp = Utc.timestamp_millis_opt(1_700_000_000_000 + total as i64).unwrap();
+            result.add_measurement(sample);
+            results.push(result);
+        }
+        let mut failed = TestResult::new("DoH".to_string(), DnsConfig::System, "https://b.example".to_string());
+        failed.add_measurement(TimingMetrics::failed("timeout".to_string()));
+        results.push(failed);
+        store.record(&results);
+        store
+    }
+
+    #[test]
+    fn test_sample_store() {
+        let mut store = store();
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.series(), ["DoH | https://a.example", "DoH | https://b.example", "System DNS | https://a.example"]);
+        let filter = SampleFilter { config: Some("DoH".to_string()), ..Default::default() };
+        assert_eq!(store.query(&filter).count(), 3);
+
+        // The oldest samples make way for new ones
+        let mut extra = TestResult::new("Extra".to_string(), DnsConfig::System, "https://c.example".to_string());
+        extra.add_measurement(TimingMetrics::failed("refused".to_string()));
+        store.record([&extra]);
+        assert_eq!(store.len(), 4);
+        assert!(!store.series().contains(&"System DNS | https://a.example".to_string()));
+    }
+
+    #[test]
+    fn test_grafana_endpoints() {
+        let served = served();
+        let now = Utc::now();
+        assert_eq!(route("GET", "/", "", &served, now).status, 200);
+        assert_eq!(route("POST", "/search", "{}", &served, now).body, serde_json::to_string(&served.samples.series()).unwrap());
+
+        let query = r#"{"range": {"from": "2023-11-14T22:13:20.000Z", "to": "2023-11-14T22:13:20.070Z"},
+                        "targets": [{"target": "DoH"}, {"target": "System DNS | https://a.example"}]}"#;
+        let response = route("POST", "/query", query, &served, now);
+        let series: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        // The range ends before the 80 ms and 100 ms samples, and the failure is not charted
+        assert_eq!(series[0]["datapoints"], serde_json::json!([[60.0, 1_700_000_000_060i64]]));
+        assert_eq!(series[1]["datapoints"], serde_json::json!([]));
+        assert_eq!(route("POST", "/query", "not json", &served, now).status, 400);
+
+        let response = route("GET", "/samples?config=DoH&url=https%3A%2F%2Fb.example&from=1700000000000", "", &served, now);
+        let rows: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 2);
+        assert_eq!(rows[1]["success"], false);
+        assert_eq!(route("GET", "/samples?from=yesterday", "", &served, now).status, 400);
+        assert_eq!(route("GET", "/query", "", &served, now).status, 405);
+        assert_eq!(route("GET", "/metrics", "", &served, now).status, 404);
+    }
+
+    #[test]
+    fn test_probes_and_status() {
+        let mut served = served();
+        let started_at = served.status.started_at;
+        let at = |seconds: i64| started_at + chrono::Duration::seconds(seconds);
+        assert_eq!(route("GET", "/healthz", "", &served, at(10)).status, 200);
+        assert_eq!(route("GET", "/readyz", "", &served, at(10)).status, 503);
+
+        served.status.record_cycle(at(30), Some("More than 50% of tests failed".to_string()));
+        assert_eq!(route("GET", "/readyz", "", &served, at(30)).status, 503);
+        served.status.record_cycle(at(100), None);
+        assert_eq!(route("GET", "/readyz", "", &served, at(100)).status, 200);
+
+        // Three 60 s intervals plus the grace period pass without a cycle
+        let stalled = at(100 + 180 + STALL_GRACE.as_secs() as i64 + 1);
+        assert_eq!(route("GET", "/healthz", "", &served, stalled).status, 503);
+        let response = route("GET", "/status", "", &served, stalled);
+        let status: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(status["cycles"], 2);
+        assert_eq!(status["failed_cycles"], 1);
+        assert_eq!(status["last_error"]["message"], "More than 50% of tests failed");
+        assert_eq!(status["samples"], 4);
+        assert_eq!(status["stalled"], true);
+
+        // A paused agent is alive, however long ago its last cycle was
+        served.status.paused = true;
+        assert_eq!(route("GET", "/healthz", "", &served, stalled).status, 200);
+        assert_eq!(route("POST", "/healthz", "", &served, stalled).status, 405);
+    }
+
+    #[tokio::test]
+    async fn test_server_answers_over_http() {
+        let search = |addr| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let body = r#"{"target": ""}"#;
+            let request = format!("POST /search HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let server = Server::bind("127.0.0.1:0".parse().unwrap(), Arc::new(Mutex::new(served())), None).await.unwrap();
+        let response = search(server.local_addr()).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"["DoH | https://a.example","DoH | https://b.example","System DNS | https://a.example"]"#));
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+
+        let origin = "https://grafana.example".to_string();
+        let server = Server::bind("127.0.0.1:0".parse().unwrap(), Arc::new(Mutex::new(served())), Some(origin)).await.unwrap();
+        assert!(search(server.local_addr()).await.contains("Access-Control-Allow-Origin: https://grafana.example\r\n"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_silent_client_times_out() {
+        let server = Server::bind("127.0.0.1:0".parse().unwrap(), Arc::new(Mutex::new(served())), None).await.unwrap();
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        stream.write_all(b"GET /status HTTP/1.1\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+    }
+}
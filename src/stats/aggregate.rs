@@ -0,0 +1,267 @@
+//! Single-pass aggregation of timing samples
+//!
+//! [`SampleTallies`] folds each sample into everything the extended statistics
+//! report that can be kept as running state: phase sums and Welford moments
+//! for all samples and for the cold/warm and cache hit/miss groups, the
+//! performance levels and the tallies of optional measurements. The batch
+//! engine and [`StreamingAnalyzer`](super::StreamingAnalyzer) both record
+//! through it, so a sample is looked at once however many figures it feeds;
+//! only the response times needed for percentiles are kept beside it.
+
+use super::{
+    BodyTally, CacheSplit, ColdStartSplit, TlsResumptionSplit, InformationalTally, PerformanceDistribution, ReliabilityMetrics, ServerTimingTally,
+    SocketTally, ThrottlingTally, TimeoutTally, UploadTally,
+};
+use crate::{
+    models::metrics::{CacheStatus, Statistics, TimingMetrics},
+    types::PerformanceLevel,
+};
+
+/// What a recorded sample adds to the response time populations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Recorded {
+    Success { total_ms: f64, body_bytes: Option<f64> },
+    /// A timeout, whose elapsed time is a lower bound when censoring
+    Timeout { total_ms: f64 },
+    Failed,
+}
+
+/// Running aggregates of every sample recorded
+#[derive(Debug, Clone, Default)]
+pub(super) struct SampleTallies {
+    pub all: PhaseAccumulator,
+    pub cold: PhaseAccumulator,
+    pub warm: PhaseAccumulator,
+    pub hits: PhaseAccumulator,
+    pub misses: PhaseAccumulator,
+    pub resumed: PhaseAccumulator,
+    pub full_handshakes: PhaseAccumulator,
+    pub timeouts: TimeoutTally,
+    pub bodies: BodyTally,
+    pub sockets: SocketTally,
+    pub uploads: UploadTally,
+    pub informational: InformationalTally,
+    pub server_timing: ServerTimingTally,
+    pub throttling: ThrottlingTally,
+    good_count: u64,
+    moderate_count: u64,
+    poor_count: u64,
+}
+
+impl SampleTallies {
+    /// Fold a sample into every aggregate it belongs to
+    pub fn record(&mut self, metrics: &TimingMetrics) -> Recorded {
+        self.throttling.record(metrics);
+        if self.timeouts.record(metrics) {
+            return Recorded::Timeout { total_ms: metrics.total_ms() };
+        }
+        if !metrics.is_successful() {
+            return Recorded::Failed;
+        }
+
+        self.all.push(metrics);
+        if metrics.is_first_use {
+            self.cold.push(metrics);
+        } else {
+            self.warm.push(metrics);
+        }
+        match metrics.cache {
+            Some(CacheStatus::Hit) => self.hits.push(metrics),
+            Some(CacheStatus::Miss) => self.misses.push(metrics),
+            None => {}
+        }
+        match metrics.tls_resumed {
+            Some(true) => self.resumed.push(metrics),
+            Some(false) => self.full_handshakes.push(metrics),
+            None => {}
+        }
+        let body_bytes = self.bodies.record(metrics);
+        self.sockets.record(metrics);
+        self.uploads.record(metrics);
+        self.informational.record(metrics);
+        self.server_timing.record(metrics);
+
+        match metrics.performance_level() {
+            PerformanceLevel::Good => self.good_count += 1,
+            PerformanceLevel::Moderate => self.moderate_count += 1,
+            PerformanceLevel::Poor => self.poor_count += 1,
+        }
+        Recorded::Success { total_ms: metrics.total_ms(), body_bytes }
+    }
+
+    /// Number of successful samples recorded
+    pub fn sample_count(&self) -> u64 {
+        self.all.total.count
+    }
+
+    pub fn cold_start(&self) -> Option<ColdStartSplit> {
+        match (self.cold.statistics(), self.warm.statistics()) {
+            (Some(cold), Some(warm)) => Some(ColdStartSplit { cold, warm }),
+            _ => None,
+        }
+    }
+
+    pub fn cache_split(&self) -> Option<CacheSplit> {
+        match (self.hits.statistics(), self.misses.statistics()) {
+            (None, None) => None,
+            (hit, miss) => Some(CacheSplit { hit, miss }),
+        }
+    }
+
+    pub fn tls_resumption(&self) -> Option<TlsResumptionSplit> {
+        match (self.resumed.statistics(), self.full_handshakes.statistics()) {
+            (None, None) => None,
+            (resumed, full) => Some(TlsResumptionSplit { resumed, full }),
+        }
+    }
+
+    pub fn performance_distribution(&self) -> PerformanceDistribution {
+        let total = self.sample_count() as f64;
+        let share = |count: u64| if total > 0.0 { (count as f64 / total) * 100.0 } else { 0.0 };
+        PerformanceDistribution {
+            good_percentage: share(self.good_count),
+            moderate_percentage: share(self.moderate_count),
+            poor_percentage: share(self.poor_count),
+        }
+    }
+
+    /// Reliability of `attempts` requests of which `success_rate` percent succeeded
+    pub fn reliability(&self, success_rate: f64, attempts: u64) -> ReliabilityMetrics {
+        let std_dev = self.all.total.sample_std_dev();
+        ReliabilityMetrics {
+            success_rate,
+            consistency_score: if self.all.total.mean > 0.0 { std_dev / self.all.total.mean } else { 0.0 },
+            jitter_ms: std_dev,
+            uptime_percentage: None, // Not applicable for individual tests
+            packet_loss_percentage: None,
+            throttling: self.throttling.summary(attempts),
+        }
+    }
+}
+
+/// Running sums of the request phases for one group of samples
+#[derive(Debug, Clone)]
+pub(super) struct PhaseAccumulator {
+    pub total: RunningMoments,
+    pub dns: RunningMoments,
+    tcp_sum: f64,
+    first_byte_sum: f64,
+    pub min_total_ms: f64,
+    pub max_total_ms: f64,
+}
+
+impl Default for PhaseAccumulator {
+    fn default() -> Self {
+        Self {
+            total: RunningMoments::default(),
+            dns: RunningMoments::default(),
+            tcp_sum: 0.0,
+            first_byte_sum: 0.0,
+            min_total_ms: f64::INFINITY,
+            max_total_ms: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl PhaseAccumulator {
+    fn push(&mut self, metrics: &TimingMetrics) {
+        let total_ms = metrics.total_ms();
+        self.total.push(total_ms);
+        self.dns.push(metrics.dns_ms());
+        self.tcp_sum += metrics.tcp_ms();
+        self.first_byte_sum += metrics.first_byte_ms();
+        self.min_total_ms = self.min_total_ms.min(total_ms);
+        self.max_total_ms = self.max_total_ms.max(total_ms);
+    }
+
+    /// Basic statistics of the recorded samples, `None` when there are none
+    pub fn statistics(&self) -> Option<Statistics> {
+        if self.total.count == 0 {
+            return None;
+        }
+
+        let count = self.total.count as f64;
+        Some(Statistics {
+            dns_avg_ms: self.dns.mean,
+            tcp_avg_ms: self.tcp_sum / count,
+            first_byte_avg_ms: self.first_byte_sum / count,
+            total_avg_ms: self.total.mean,
+            total_min_ms: self.min_total_ms,
+            total_max_ms: self.max_total_ms,
+            total_std_dev_ms: self.total.population_std_dev(),
+            success_rate: 100.0, // Only successful measurements contribute
+            sample_count: self.total.count as usize,
+        })
+    }
+}
+
+/// Welford's online mean and variance
+#[derive(Debug, Clone, Default)]
+pub(super) struct RunningMoments {
+    pub count: u64,
+    pub mean: f64,
+    m2: f64,
+}
+
+impl RunningMoments {
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn population_std_dev(&self) -> f64 {
+        if self.count <= 1 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+
+    pub fn sample_std_dev(&self) -> f64 {
+        if self.count <= 1 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sample_tallies() {
+        let timing = |total_ms: u64| TimingMetrics::success(
+            Duration::from_millis(10), Duration::from_millis(20), None, Duration::from_millis(50), Duration::from_millis(total_ms), 200,
+        );
+        let mut tallies = SampleTallies::default();
+        // Good, moderate and poor performance
+        for total_ms in [500, 2000, 5000] {
+            assert_eq!(tallies.record(&timing(total_ms)), Recorded::Success { total_ms: total_ms as f64, body_bytes: None });
+        }
+        assert_eq!(tallies.record(&TimingMetrics::timeout(Duration::from_secs(10))), Recorded::Timeout { total_ms: 10_000.0 });
+        assert_eq!(tallies.record(&TimingMetrics::failed("Connection refused".to_string())), Recorded::Failed);
+
+        assert_eq!(tallies.sample_count(), 3);
+        assert_eq!(tallies.timeouts.count, 1);
+        let distribution = tallies.performance_distribution();
+        assert!((distribution.good_percentage - 33.33).abs() < 0.1);
+        assert!((distribution.moderate_percentage - 33.33).abs() < 0.1);
+        assert!((distribution.poor_percentage - 33.33).abs() < 0.1);
+
+        let basic = tallies.all.statistics().unwrap();
+        assert_eq!(basic.total_avg_ms, 2500.0);
+        assert_eq!((basic.total_min_ms, basic.total_max_ms), (500.0, 5000.0));
+        assert_eq!(basic.dns_avg_ms, 10.0);
+        // Population deviation in the basic statistics, sample deviation as jitter
+        assert!((basic.total_std_dev_ms - 1870.8287).abs() < 1e-3);
+        assert!((tallies.reliability(60.0, 5).jitter_ms - 2291.2878).abs() < 1e-3);
+        assert!(tallies.cold_start().is_none());
+        assert!(tallies.cache_split().is_none());
+        assert!(tallies.tls_resumption().is_none());
+    }
+}
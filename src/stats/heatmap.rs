@@ -0,0 +1,277 @@
+//! Latency by hour of day and day of week
+//!
+//! Congestion tends to follow the clock: an ISP link that is fine at 10:00
+//! may be saturated every evening. [`LatencyHeatmap`] sorts the successful
+//! samples of one or more exported runs, such as the exports of a long watch
+//! session, into a week of hourly cells per configuration, in the time zone
+//! the run is viewed from. It renders as a terminal heatmap with
+//! [`LatencyHeatmap::render_text`] and as a standalone HTML chart with
+//! [`LatencyHeatmap::render_html`].
+
+use crate::models::{metrics::TestResult, Reproduction};
+use chrono::{Datelike, TimeZone, Timelike};
+use std::collections::BTreeMap;
+
+/// Day names, in the order rows are shown (ISO weeks start on Monday)
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// Shades from the quietest to the most congested cell of a configuration
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// Samples that fell into one hour of the week
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeatmapCell {
+    pub samples: u32,
+    pub total_ms: f64,
+}
+
+impl HeatmapCell {
+    /// Average response time of the cell, if it has samples
+    pub fn avg_ms(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.total_ms / self.samples as f64)
+    }
+
+    fn add(&mut self, other: HeatmapCell) {
+        self.samples += other.samples;
+        self.total_ms += other.total_ms;
+    }
+}
+
+/// A week of hourly cells for one configuration, indexed by weekday (Monday
+/// first) and hour
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeatmapGrid {
+    cells: [[HeatmapCell; 24]; 7],
+}
+
+impl HeatmapGrid {
+    /// Cell of a weekday (0 is Monday) and hour
+    pub fn cell(&self, weekday: usize, hour: usize) -> HeatmapCell {
+        self.cells[weekday][hour]
+    }
+
+    /// Samples of each hour of the day, over all weekdays
+    pub fn by_hour(&self) -> [HeatmapCell; 24] {
+        let mut hours = [HeatmapCell::default(); 24];
+        for day in &self.cells {
+            for (hour, cell) in day.iter().enumerate() {
+                hours[hour].add(*cell);
+            }
+        }
+        hours
+    }
+
+    /// Samples of each weekday (Monday first), over all hours
+    pub fn by_weekday(&self) -> [HeatmapCell; 7] {
+        let mut days = [HeatmapCell::default(); 7];
+        for (weekday, day) in self.cells.iter().enumerate() {
+            for cell in day {
+                days[weekday].add(*cell);
+            }
+        }
+        days
+    }
+
+    /// Slowest and fastest hour of the day by average, when any hour has samples
+    pub fn peak_and_quietest_hour(&self) -> Option<((usize, f64), (usize, f64))> {
+        let hours: Vec<(usize, f64)> = self.by_hour().iter().enumerate()
+            .filter_map(|(hour, cell)| cell.avg_ms().map(|avg| (hour, avg)))
+            .collect();
+        let peak = hours.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let quietest = hours.iter().copied().min_by(|a, b| a.1.total_cmp(&b.1))?;
+        Some((peak, quietest))
+    }
+
+    /// Lowest and highest cell average, the range that shades are scaled to
+    fn range(&self) -> Option<(f64, f64)> {
+        let averages = self.cells.iter().flatten().filter_map(HeatmapCell::avg_ms);
+        averages.fold(None, |range, avg| match range {
+            None => Some((avg, avg)),
+            Some((low, high)) => Some((low.min(avg), high.max(avg))),
+        })
+    }
+}
+
+/// Latency per configuration by hour of day and day of week
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHeatmap {
+    /// Name of the time zone that hours and weekdays are counted in
+    pub zone: String,
+    /// Grid of each configuration
+    pub configs: BTreeMap<String, HeatmapGrid>,
+    /// Distinct runs the samples came from, for the report footer
+    pub reproductions: Vec<Reproduction>,
+}
+
+impl LatencyHeatmap {
+    /// Sort the successful samples of `results` into hourly cells, counting
+    /// hours and weekdays in the time zone `tz`, named `zone`
+    ///
+    /// Each sample is converted on its own, so samples on either side of a
+    /// daylight saving change keep the offset in effect when they were taken.
+    pub fn from_results<'a, Tz: TimeZone>(results: impl IntoIterator<Item = &'a TestResult>, tz: &Tz, zone: impl Into<String>) -> Self {
+        let mut configs: BTreeMap<String, HeatmapGrid> = BTreeMap::new();
+        for result in results {
+            let grid = configs.entry(result.config_name.to_string()).or_default();
+            for sample in result.individual_results.iter().filter(|m| m.is_successful()) {
+                let local = sample.timestamp.with_timezone(tz);
+                let cell = &mut grid.cells[local.weekday().num_days_from_monday() as usize][local.hour() as usize];
+                cell.samples += 1;
+                cell.total_ms += sample.total_ms();
+            }
+        }
+        configs.retain(|_, grid| grid.range().is_some());
+        Self { zone: zone.into(), configs, reproductions: Vec::new() }
+    }
+
+    /// Name the runs the samples came from, once each
+    pub fn with_reproductions<'a>(mut self, reproductions: impl IntoIterator<Item = &'a Reproduction>) -> Self {
+        for reproduction in reproductions {
+            if !self.reproductions.iter().any(|known| known.config_hash == reproduction.config_hash) {
+                self.reproductions.push(reproduction.clone());
+            }
+        }
+        self
+    }
+
+    /// Whether no configuration has a successful sample
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Terminal heatmap: a row per weekday and a column per hour, shaded from
+    /// each configuration's quietest to its most congested hour
+    pub fn render_text(&self) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("Latency by hour of day ({}); darker is slower, · has no samples\n", self.zone));
+        for (config_name, grid) in &self.configs {
+            let Some((low, high)) = grid.range() else { continue };
+            output.push_str(&format!("\n{}  ({:.0}ms ░ … █ {:.0}ms)\n", config_name, low, high));
+            let hours: String = (0..24).map(|hour| if hour % 3 == 0 { format!("{:<3}", hour) } else { String::new() }).collect();
+            output.push_str(&format!("      {}\n", hours));
+            let days = grid.by_weekday();
+            for (weekday, name) in WEEKDAYS.iter().enumerate() {
+                let row: String = (0..24).map(|hour| shade(grid.cell(weekday, hour).avg_ms(), low, high)).collect();
+                let avg = days[weekday].avg_ms().map_or("-".to_string(), |avg| format!("{:.0}ms", avg));
+                output.push_str(&format!("  {}  {}  {}\n", name, row, avg));
+            }
+            if let Some(((peak_hour, peak), (quiet_hour, quiet))) = grid.peak_and_quietest_hour() {
+                output.push_str(&format!("  Peak {:02}:00 ({:.0}ms), quietest {:02}:00 ({:.0}ms)", peak_hour, peak, quiet_hour, quiet));
+                if quiet > 0.0 && peak > quiet {
+                    output.push_str(&format!(", {:.0}% slower at peak", (peak / quiet - 1.0) * 100.0));
+                }
+                output.push('\n');
+            }
+        }
+        for reproduction in &self.reproductions {
+            output.push_str(&format!("\nReproduce: {}\nConfig hash: {}\n", reproduction, reproduction.config_hash));
+        }
+        output
+    }
+
+    /// Standalone HTML page with a colored table per configuration
+    pub fn render_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Latency by hour of day</title>\n");
+        html.push_str("<style>body{font-family:sans-serif}table{border-collapse:collapse;margin-bottom:2em}\
+            td,th{width:2.2em;height:1.8em;text-align:center;font-size:11px}td.empty{background:#eee}</style></head><body>\n");
+        html.push_str(&format!("<h1>Latency by hour of day ({})</h1>\n", escape_html(&self.zone)));
+        for (config_name, grid) in &self.configs {
+            let Some((low, high)) = grid.range() else { continue };
+            html.push_str(&format!("<h2>{}</h2>\n<table>\n<tr><th></th>\n", escape_html(config_name)));
+            for hour in 0..24 {
+                html.push_str(&format!("<th>{:02}</th>", hour));
+            }
+            html.push_str("</tr>\n");
+            for (weekday, name) in WEEKDAYS.iter().enumerate() {
+                html.push_str(&format!("<tr><th>{}</th>", name));
+                for hour in 0..24 {
+                    let cell = grid.cell(weekday, hour);
+                    match cell.avg_ms() {
+                        Some(avg) => {
+                            // Green for the quietest hour through red for the most congested
+                            let hue = 120.0 * (1.0 - scale(avg, low, high));
+                            html.push_str(&format!("<td style=\"background:hsl({:.0},70%,60%)\" title=\"{} {:02}:00: {:.1}ms over {} samples\">{:.0}</td>",
+                                hue, name, hour, avg, cell.samples, avg));
+                        }
+                        None => html.push_str("<td class=\"empty\"></td>"),
+                    }
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+        if !self.reproductions.is_empty() {
+            html.push_str("<footer>\n");
+            for reproduction in &self.reproductions {
+                html.push_str(&format!("<p>Reproduce: <code>{}</code><br>Config hash: <code>{}</code></p>\n",
+                    escape_html(&reproduction.command()), reproduction.config_hash));
+            }
+            html.push_str("</footer>\n");
+        }
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+/// Position of `value` between `low` and `high`, from 0.0 to 1.0
+fn scale(value: f64, low: f64, high: f64) -> f64 {
+    if high > low { ((value - low) / (high - low)).clamp(0.0, 1.0) } else { 0.0 }
+}
+
+fn shade(avg_ms: Option<f64>, low: f64, high: f64) -> char {
+    match avg_ms {
+        Some(avg) => SHADES[(scale(avg, low, high) * (SHADES.len() - 1) as f64).round() as usize],
+        None => '·',
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::TimingMetrics, test_support::success, types::DnsConfig};
+    use chrono::{FixedOffset, Utc};
+
+    fn sample_at(result: &mut TestResult, rfc3339: &str, total_ms: u64) {
+        let mut sample = success(total_ms);
+        sample.timestamp = chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc);
+        result.add_measurement(sample);
+    }
+
+    #[test]
+    fn test_heatmap_cells_follow_local_time() {
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://example.com".to_string());
+        // Monday 2024-01-01, evenings are congested
+        sample_at(&mut result, "2024-01-01T09:15:00Z", 40);
+        sample_at(&mut result, "2024-01-01T09:45:00Z", 60);
+        sample_at(&mut result, "2024-01-01T18:30:00Z", 150);
+        sample_at(&mut result, "2024-01-02T18:10:00Z", 130);
+        result.add_measurement(TimingMetrics::failed("timeout".to_string()));
+
+        let heatmap = LatencyHeatmap::from_results([&result], &Utc, "UTC");
+        let grid = &heatmap.configs["System DNS"];
+        assert_eq!(grid.cell(0, 9), HeatmapCell { samples: 2, total_ms: 100.0 });
+        assert_eq!(grid.by_hour()[18].avg_ms(), Some(140.0));
+        assert_eq!(grid.by_weekday()[1].samples, 1);
+        assert_eq!(grid.peak_and_quietest_hour(), Some(((18, 140.0), (9, 50.0))));
+
+        let text = heatmap.render_text();
+        assert!(text.starts_with("Latency by hour of day (UTC); "), "{}", text);
+        assert!(text.contains("Peak 18:00 (140ms), quietest 09:00 (50ms), 180% slower at peak"), "{}", text);
+        assert!(text.contains("  Mon  ·········░········█·····  83ms"), "{}", text);
+        assert!(heatmap.render_html().contains("title=\"Tue 18:00: 130.0ms over 1 samples\""));
+
+        // Seen from UTC+8, the Monday evening samples fall early on Tuesday
+        let shifted = LatencyHeatmap::from_results([&result], &FixedOffset::east_opt(8 * 3600).unwrap(), "UTC+08:00");
+        assert_eq!(shifted.configs["System DNS"].cell(1, 2).samples, 1);
+
+        // Runs of the same configuration are named once
+        let run = Reproduction::from_config(&crate::models::Config::default());
+        let footed = shifted.with_reproductions([&run, &run]);
+        assert_eq!(footed.reproductions.len(), 1);
+        assert!(footed.render_html().contains(&format!("Config hash: <code>{}</code>", run.config_hash)));
+    }
+}
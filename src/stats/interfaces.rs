@@ -0,0 +1,138 @@
+//! Comparison of the network interfaces of an `--all-interfaces` run
+//!
+//! Each interface runs the whole matrix, so every DNS configuration is
+//! measured once per interface. [`InterfaceMatrix`] lines the interfaces up
+//! per configuration. An interface that failed a target would otherwise look
+//! faster for skipping it, so each cell averages only the targets that every
+//! interface reached with that configuration.
+
+use crate::models::metrics::TestResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Average response time of each DNS configuration from each interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceMatrix {
+    /// Interfaces in the order of the columns
+    pub interfaces: Vec<String>,
+    /// One row per DNS configuration, by name
+    pub rows: Vec<InterfaceRow>,
+}
+
+/// One DNS configuration measured from each interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceRow {
+    pub config_name: String,
+    /// Mean of the average response times of the compared targets, by
+    /// interface; empty when no target was reached from every interface
+    pub avg_ms: BTreeMap<String, f64>,
+    /// Number of targets compared
+    pub targets: usize,
+}
+
+impl InterfaceRow {
+    /// The interface with the lowest average
+    pub fn fastest(&self) -> Option<&str> {
+        self.avg_ms.iter()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(interface, _)| interface.as_str())
+    }
+}
+
+impl InterfaceMatrix {
+    /// The matrix of results measured from interfaces, if there are any
+    pub fn from_results<'a>(results: impl IntoIterator<Item = &'a TestResult>) -> Option<Self> {
+        let mut interfaces = BTreeSet::new();
+        // Configuration -> target -> interface -> average
+        let mut averages: BTreeMap<&str, BTreeMap<&str, BTreeMap<&str, f64>>> = BTreeMap::new();
+        for result in results {
+            let Some(ref interface) = result.interface else { continue };
+            interfaces.insert(interface.clone());
+            if let Some(ref stats) = result.statistics {
+                averages.entry(result.dns_config_name()).or_default()
+                    .entry(&result.url).or_default()
+                    .insert(interface, stats.total_avg_ms);
+            }
+        }
+        if interfaces.is_empty() {
+            return None;
+        }
+
+        let rows = averages.into_iter()
+            .map(|(config_name, targets)| {
+                let compared: Vec<&BTreeMap<&str, f64>> = targets.values()
+                    .filter(|by_interface| by_interface.len() == interfaces.len())
+                    .collect();
+                let avg_ms = if compared.is_empty() {
+                    BTreeMap::new()
+                } else {
+                    interfaces.iter()
+                        .map(|interface| {
+                            let total: f64 = compared.iter().map(|by_interface| by_interface[interface.as_str()]).sum();
+                            (interface.clone(), total / compared.len() as f64)
+                        })
+                        .collect()
+                };
+                InterfaceRow { config_name: config_name.to_string(), avg_ms, targets: compared.len() }
+            })
+            .collect();
+        Some(Self { interfaces: interfaces.into_iter().collect(), rows })
+    }
+
+    /// The interface that was fastest for the most configurations, with the
+    /// number of configurations
+    pub fn fastest_interface(&self) -> Option<(&str, usize)> {
+        let mut wins: BTreeMap<&str, usize> = BTreeMap::new();
+        for fastest in self.rows.iter().filter_map(InterfaceRow::fastest) {
+            *wins.entry(fastest).or_default() += 1;
+        }
+        // Ties go to the first interface in name order
+        wins.into_iter().rev().max_by_key(|(_, count)| *count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::metrics::TimingMetrics;
+    use crate::test_support::result_with_totals;
+    use crate::types::DnsConfig;
+
+    fn result(config_name: &str, url: &str, interface: &str, total_ms: Option<u64>) -> TestResult {
+        let mut result = result_with_totals(config_name, url, total_ms.as_slice());
+        if total_ms.is_none() {
+            result.add_measurement(TimingMetrics::failed("Network unreachable".to_string()));
+            result.calculate_statistics();
+        }
+        result.on_interface(interface)
+    }
+
+    #[test]
+    fn test_interface_matrix() {
+        let results = [
+            result("System DNS", "https://a.example", "eth0", Some(20)),
+            result("System DNS", "https://a.example", "wwan0", Some(80)),
+            result("System DNS", "https://b.example", "eth0", Some(40)),
+            // Unreachable over LTE, so b.example is left out of the comparison
+            result("System DNS", "https://b.example", "wwan0", None),
+            result("Custom DNS (8.8.8.8)", "https://a.example", "eth0", Some(30)),
+            result("Custom DNS (8.8.8.8)", "https://a.example", "wwan0", Some(25)),
+        ];
+        assert_eq!(results[0].config_name, "System DNS [eth0]");
+        assert_eq!(results[0].dns_config_name(), "System DNS");
+
+        let matrix = InterfaceMatrix::from_results(&results).unwrap();
+        assert_eq!(matrix.interfaces, ["eth0", "wwan0"]);
+        let system = matrix.rows.iter().find(|row| row.config_name == "System DNS").unwrap();
+        assert_eq!(system.targets, 1);
+        assert_eq!(system.avg_ms["eth0"], 20.0);
+        assert_eq!(system.fastest(), Some("eth0"));
+        let custom = matrix.rows.iter().find(|row| row.config_name == "Custom DNS (8.8.8.8)").unwrap();
+        assert_eq!(custom.fastest(), Some("wwan0"));
+        // One configuration each; the tie goes to eth0
+        assert_eq!(matrix.fastest_interface(), Some(("eth0", 1)));
+
+        let unbound = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://a.example".to_string());
+        assert!(InterfaceMatrix::from_results([&unbound]).is_none());
+    }
+}
@@ -1,25 +1,73 @@
 //! Statistical analysis and calculation engine for network latency tests
 
+mod aggregate;
+mod parallel;
+pub mod heatmap;
+pub mod interfaces;
 pub mod optimized;
+pub mod paired;
+pub mod physics;
+pub mod precision;
+pub mod split_horizon;
+pub mod streaming;
 
+use aggregate::{Recorded, SampleTallies};
 pub use optimized::{
-    OptimizedStatisticsCalculator, RollingStats, StatisticsMemoryPool,
-    BufferStats, PoolStats,
+    OptimizedStatisticsCalculator, RollingStats, MultiWindowStats, WindowSnapshot,
+    StatisticsMemoryPool, BufferStats, PoolStats, DEFAULT_ROLLING_WINDOWS,
 };
+pub use heatmap::LatencyHeatmap;
+pub use interfaces::{InterfaceMatrix, InterfaceRow};
+pub use physics::GeoPoint;
+pub use paired::PairedComparison;
+pub use precision::Precision;
+pub use split_horizon::{RouteDivergence, SplitHorizon};
+pub use parallel::PARALLEL_MIN_SAMPLES;
+pub use streaming::StreamingAnalyzer;
 
 use crate::{
     error::{AppError, Result},
-    types::PerformanceLevel,
-    models::metrics::{TimingMetrics, TestResult, Statistics},
+    types::{DnsConfig, TestStatus},
+    models::{
+        config::{Config, ScoringWeights},
+        metrics::{BaselineComparison, CacheStatus, LossReport, PhysicalBound, RequestPhase, TimingMetrics, TestResult, Statistics},
+        name::Name,
+    },
+    utils::size::format_bytes,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// Average body size from which uncompressed responses are flagged; below it
+/// compression rarely pays for its overhead
+pub const UNCOMPRESSED_BODY_BYTES: f64 = 1024.0;
+
+/// How many times larger than the smallest configuration's bodies for the same
+/// URL a configuration's bodies must be to be flagged as unexpectedly large
+pub const LARGE_BODY_FACTOR: f64 = 2.0;
+
+/// Share of requests with retransmissions (percent) from which a configuration
+/// is flagged for packet loss
+pub const RETRANSMIT_WARNING_PERCENT: f64 = 5.0;
+
+/// Share of UDP probe packets lost (percent) from which a configuration is
+/// flagged for packet loss
+pub const PACKET_LOSS_WARNING_PERCENT: f64 = 1.0;
+
 /// Comprehensive statistics engine for network latency analysis
 pub struct StatisticsEngine {
     /// Collected test results grouped by DNS configuration
-    results: HashMap<String, Vec<TestResult>>,
+    results: HashMap<Name, Vec<TestResult>>,
+    /// Packet loss measured by `--loss-probe`, by the configuration it belongs to
+    packet_loss: Vec<LossReport>,
+    /// Connect times compared with public measurements by `--compare-public`
+    public_baseline: Vec<BaselineComparison>,
+    /// Routes measured against the speed-of-light minimum by `--geo`
+    physical_bounds: Vec<PhysicalBound>,
+    /// Networks (ASNs) of addresses that were looked up, in place of the prefix heuristics
+    networks: BTreeMap<IpAddr, String>,
     /// Configuration for statistical calculations
     config: StatisticsConfig,
 }
@@ -37,6 +85,10 @@ pub struct StatisticsConfig {
     pub exclude_outliers: bool,
     /// Outlier detection method
     pub outlier_method: OutlierMethod,
+    /// Weights used to compute the overall ranking score
+    pub scoring_weights: ScoringWeights,
+    /// Count timed-out requests as lasting at least the timeout in percentiles
+    pub censor_timeouts: bool,
 }
 
 /// Methods for detecting outliers in timing data
@@ -50,6 +102,59 @@ pub enum OutlierMethod {
     ModifiedZScore { threshold: f64 },
 }
 
+impl OutlierMethod {
+    /// Thresholds reported alongside the outlier analysis
+    pub(crate) fn threshold_values(&self) -> HashMap<String, f64> {
+        let mut threshold_values = HashMap::new();
+        match self {
+            OutlierMethod::StandardDeviation { threshold } => {
+                threshold_values.insert("std_dev_threshold".to_string(), *threshold);
+            }
+            OutlierMethod::ModifiedZScore { threshold } => {
+                threshold_values.insert("z_score_threshold".to_string(), *threshold);
+            }
+            OutlierMethod::IQR => {
+                threshold_values.insert("iqr_multiplier".to_string(), 1.5);
+            }
+        }
+        threshold_values
+    }
+}
+
+/// Z-score for a two-sided confidence level
+pub(crate) fn z_score(confidence_level: f64) -> f64 {
+    // Common Z-scores for confidence levels
+    match confidence_level {
+        level if (level - 0.90).abs() < 0.01 => 1.645,
+        level if (level - 0.95).abs() < 0.01 => 1.96,
+        level if (level - 0.99).abs() < 0.01 => 2.576,
+        _ => 1.96, // Default to 95%
+    }
+}
+
+/// Wilson score interval for a success rate, in percent
+///
+/// Unlike the normal approximation it stays meaningful for small samples and
+/// for rates of exactly 0% or 100%.
+pub(crate) fn wilson_interval(successes: u64, attempts: u64, z: f64) -> (f64, f64) {
+    if attempts == 0 {
+        return (0.0, 0.0);
+    }
+
+    let n = attempts as f64;
+    let p = successes.min(attempts) as f64 / n;
+    let z2 = z * z;
+
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+
+    (
+        ((center - half_width) * 100.0).max(0.0),
+        ((center + half_width) * 100.0).min(100.0),
+    )
+}
+
 /// Comprehensive statistical analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatisticalAnalysis {
@@ -59,6 +164,15 @@ pub struct StatisticalAnalysis {
     pub comparative_analysis: ComparativeAnalysis,
     /// Trend analysis over time
     pub trend_analysis: Option<TrendAnalysis>,
+    /// Per-URL × per-configuration breakdown, sorted by URL
+    #[serde(default)]
+    pub url_breakdown: Vec<UrlBreakdown>,
+    /// Targets that resolved to different addresses under different configurations
+    #[serde(default)]
+    pub split_horizon: Vec<SplitHorizon>,
+    /// Each configuration measured from each interface, with `--all-interfaces`
+    #[serde(default)]
+    pub interface_matrix: Option<InterfaceMatrix>,
     /// Summary and recommendations
     pub summary: AnalysisSummary,
     /// When this analysis was generated
@@ -80,6 +194,650 @@ pub struct ExtendedStatistics {
     pub performance_distribution: PerformanceDistribution,
     /// Reliability metrics
     pub reliability: ReliabilityMetrics,
+    /// First-use and steady-state statistics, when both kinds of samples exist
+    #[serde(default)]
+    pub cold_start: Option<ColdStartSplit>,
+    /// Statistics of CDN cache hits and misses, when responses reported a cache status
+    #[serde(default)]
+    pub cache_split: Option<CacheSplit>,
+    /// Statistics of new TLS connections that resumed a session and of those
+    /// that made a full handshake, with `--tls-resumption`
+    #[serde(default)]
+    pub tls_resumption: Option<TlsResumptionSplit>,
+    /// Requests that timed out, if any did
+    #[serde(default)]
+    pub timeouts: Option<TimeoutSummary>,
+    /// Sizes of the response bodies received, when they were measured
+    #[serde(default)]
+    pub response_size: Option<ResponseSizeSummary>,
+    /// Kernel TCP statistics, when `--socket-stats` collected them
+    #[serde(default)]
+    pub socket: Option<SocketSummary>,
+    /// Request bodies sent, with `--upload`
+    #[serde(default)]
+    pub upload: Option<UploadSummary>,
+    /// Informational responses, with `--early-hints`
+    #[serde(default)]
+    pub informational: Option<InformationalSummary>,
+    /// Phases servers reported in `Server-Timing` headers
+    #[serde(default)]
+    pub server_timing: Option<ServerTimingSummary>,
+}
+
+/// Requests that hit the timeout and so have no measured latency of their own
+///
+/// A timed-out request is a right-censored sample: all we know is that it would
+/// have taken at least as long as it ran. With censoring enabled these samples
+/// enter the percentiles at their elapsed time, so percentiles at or above
+/// `censored_at_ms` are lower bounds rather than measurements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutSummary {
+    /// Number of timed-out requests
+    pub count: usize,
+    /// Share of attempted requests that timed out (0.0-100.0)
+    pub rate: f64,
+    /// Shortest time a timed-out request ran before giving up (milliseconds)
+    pub censored_at_ms: f64,
+    /// Whether timed-out requests were included in the percentiles
+    pub censored: bool,
+    /// Phase most timed-out requests were stuck in, when recorded
+    pub phase: Option<RequestPhase>,
+}
+
+impl TimeoutSummary {
+    /// Whether a percentile value is only known to be at least this large
+    pub fn is_lower_bound(&self, value_ms: f64) -> bool {
+        self.censored && value_ms >= self.censored_at_ms
+    }
+}
+
+/// Running tally of timed-out requests
+#[derive(Debug, Clone, Default)]
+struct TimeoutTally {
+    count: usize,
+    min_ms: f64,
+    max_ms: f64,
+    connecting: usize,
+    awaiting_response: usize,
+}
+
+impl TimeoutTally {
+    /// Count a measurement if it timed out; returns whether it did
+    fn record(&mut self, metrics: &TimingMetrics) -> bool {
+        if !matches!(metrics.status, TestStatus::Timeout) {
+            return false;
+        }
+
+        let elapsed = metrics.total_ms();
+        if self.count == 0 {
+            self.min_ms = elapsed;
+            self.max_ms = elapsed;
+        } else {
+            self.min_ms = self.min_ms.min(elapsed);
+            self.max_ms = self.max_ms.max(elapsed);
+        }
+        self.count += 1;
+
+        match metrics.phase_reached {
+            Some(RequestPhase::Connecting) => self.connecting += 1,
+            Some(RequestPhase::AwaitingResponse) => self.awaiting_response += 1,
+            None => {}
+        }
+        true
+    }
+
+    fn summary(&self, attempts: u64, censored: bool) -> Option<TimeoutSummary> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let phase = match (self.connecting, self.awaiting_response) {
+            (0, 0) => None,
+            (connecting, awaiting) if connecting >= awaiting => Some(RequestPhase::Connecting),
+            _ => Some(RequestPhase::AwaitingResponse),
+        };
+
+        Some(TimeoutSummary {
+            count: self.count,
+            rate: if attempts > 0 { self.count as f64 / attempts as f64 * 100.0 } else { 0.0 },
+            censored_at_ms: self.min_ms,
+            censored,
+            phase,
+        })
+    }
+}
+
+/// Sizes and encodings of the response bodies a configuration received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSizeSummary {
+    /// Number of bodies measured
+    pub samples: usize,
+    /// Average size on the wire (bytes)
+    pub avg_bytes: f64,
+    /// Median size on the wire (bytes)
+    pub p50_bytes: f64,
+    /// 95th percentile of the size on the wire (bytes)
+    pub p95_bytes: f64,
+    /// Largest body (bytes)
+    pub max_bytes: f64,
+    /// Share of bodies sent with a content encoding (0.0-100.0)
+    pub compressed_percentage: f64,
+    /// Content encodings seen, e.g. `gzip`
+    pub encodings: Vec<String>,
+    /// Decoded size divided by size on the wire, over compressed bodies that could be decoded
+    pub compression_ratio: Option<f64>,
+}
+
+impl ResponseSizeSummary {
+    /// Whether bodies large enough to be worth compressing all arrived uncompressed
+    pub fn is_uncompressed(&self) -> bool {
+        self.compressed_percentage == 0.0 && self.avg_bytes >= UNCOMPRESSED_BODY_BYTES
+    }
+}
+
+/// Running tally of response body sizes
+#[derive(Debug, Clone, Default)]
+struct BodyTally {
+    count: usize,
+    total_bytes: f64,
+    max_bytes: f64,
+    compressed: usize,
+    encodings: BTreeSet<String>,
+    /// Wire size of the compressed bodies that could be decoded
+    decodable_bytes: f64,
+    /// Decoded size of the same bodies
+    decoded_bytes: f64,
+}
+
+impl BodyTally {
+    /// Count the body of a measurement, returning its size if it has one
+    fn record(&mut self, metrics: &TimingMetrics) -> Option<f64> {
+        let body = metrics.body.as_ref()?;
+        let bytes = body.bytes as f64;
+        self.count += 1;
+        self.total_bytes += bytes;
+        self.max_bytes = self.max_bytes.max(bytes);
+        if let Some(ref encoding) = body.encoding {
+            self.compressed += 1;
+            self.encodings.insert(encoding.clone());
+            if let Some(decoded) = body.decoded_bytes {
+                self.decodable_bytes += bytes;
+                self.decoded_bytes += decoded as f64;
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Summarize the bodies counted, taking size percentiles from `percentile`
+    fn summary(&self, percentile: impl Fn(f64) -> f64) -> Option<ResponseSizeSummary> {
+        if self.count == 0 {
+            return None;
+        }
+
+        Some(ResponseSizeSummary {
+            samples: self.count,
+            avg_bytes: self.total_bytes / self.count as f64,
+            p50_bytes: percentile(50.0),
+            p95_bytes: percentile(95.0),
+            max_bytes: self.max_bytes,
+            compressed_percentage: self.compressed as f64 / self.count as f64 * 100.0,
+            encodings: self.encodings.iter().cloned().collect(),
+            compression_ratio: (self.decodable_bytes > 0.0).then(|| self.decoded_bytes / self.decodable_bytes),
+        })
+    }
+}
+
+/// Kernel TCP statistics of the requests of a configuration
+///
+/// A lost segment costs at least one retransmission timeout, so requests with
+/// retransmissions are compared against the rest to show what loss costs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketSummary {
+    /// Number of requests with TCP statistics
+    pub samples: usize,
+    /// Average smoothed RTT the kernel measured (milliseconds)
+    pub avg_rtt_ms: f64,
+    /// Segments retransmitted over all requests
+    pub retransmits: u64,
+    /// Requests during which at least one segment was retransmitted
+    pub affected_requests: usize,
+    /// Average latency of the requests with retransmissions (milliseconds)
+    pub affected_avg_ms: Option<f64>,
+    /// Average latency of the requests without (milliseconds)
+    pub clean_avg_ms: Option<f64>,
+}
+
+impl SocketSummary {
+    /// Share of requests with retransmissions (0.0-100.0)
+    pub fn affected_percentage(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.affected_requests as f64 / self.samples as f64 * 100.0
+    }
+
+    /// Extra average latency of requests with retransmissions (milliseconds)
+    pub fn retransmit_penalty_ms(&self) -> Option<f64> {
+        Some(self.affected_avg_ms? - self.clean_avg_ms?)
+    }
+}
+
+/// Running tally of kernel TCP statistics
+#[derive(Debug, Clone, Default)]
+struct SocketTally {
+    count: usize,
+    total_rtt_ms: f64,
+    retransmits: u64,
+    affected: usize,
+    affected_total_ms: f64,
+    clean_total_ms: f64,
+}
+
+impl SocketTally {
+    /// Count the TCP statistics of a measurement, if it has them
+    fn record(&mut self, metrics: &TimingMetrics) {
+        let Some(socket) = metrics.socket else {
+            return;
+        };
+        self.count += 1;
+        self.total_rtt_ms += socket.rtt_ms;
+        self.retransmits += u64::from(socket.retransmits);
+        if socket.retransmits > 0 {
+            self.affected += 1;
+            self.affected_total_ms += metrics.total_ms();
+        } else {
+            self.clean_total_ms += metrics.total_ms();
+        }
+    }
+
+    fn summary(&self) -> Option<SocketSummary> {
+        if self.count == 0 {
+            return None;
+        }
+        let clean = self.count - self.affected;
+        Some(SocketSummary {
+            samples: self.count,
+            avg_rtt_ms: self.total_rtt_ms / self.count as f64,
+            retransmits: self.retransmits,
+            affected_requests: self.affected,
+            affected_avg_ms: (self.affected > 0).then(|| self.affected_total_ms / self.affected as f64),
+            clean_avg_ms: (clean > 0).then(|| self.clean_total_ms / clean as f64),
+        })
+    }
+}
+
+/// How the request bodies of a configuration went up, with `--upload`
+///
+/// Sending the body and waiting for the answer are kept apart: the first is
+/// bound by the upstream of the link, the second by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSummary {
+    /// Number of requests that sent a body
+    pub samples: usize,
+    /// Size of each body
+    pub bytes: u64,
+    /// Average time to send the body (milliseconds)
+    pub send_avg_ms: f64,
+    /// Average time from the last byte sent to the response (milliseconds)
+    pub ack_avg_ms: f64,
+}
+
+impl UploadSummary {
+    /// Average rate the bodies went out at, in megabits per second
+    pub fn throughput_mbps(&self) -> Option<f64> {
+        (self.send_avg_ms > 0.0).then(|| self.bytes as f64 * 8.0 / (self.send_avg_ms / 1000.0) / 1_000_000.0)
+    }
+}
+
+/// Running tally of upload timings
+#[derive(Debug, Clone, Default)]
+struct UploadTally {
+    count: usize,
+    bytes: u64,
+    send_total_ms: f64,
+    ack_total_ms: f64,
+}
+
+impl UploadTally {
+    /// Count the upload timing of a measurement, if it has one
+    fn record(&mut self, metrics: &TimingMetrics) {
+        let Some(upload) = metrics.upload else {
+            return;
+        };
+        self.count += 1;
+        self.bytes = upload.bytes;
+        self.send_total_ms += upload.send.as_secs_f64() * 1000.0;
+        self.ack_total_ms += upload.ack.as_secs_f64() * 1000.0;
+    }
+
+    fn summary(&self) -> Option<UploadSummary> {
+        (self.count > 0).then(|| UploadSummary {
+            samples: self.count,
+            bytes: self.bytes,
+            send_avg_ms: self.send_total_ms / self.count as f64,
+            ack_avg_ms: self.ack_total_ms / self.count as f64,
+        })
+    }
+}
+
+/// Informational (1xx) responses of a configuration's targets, with `--early-hints`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InformationalSummary {
+    /// Number of requests checked for informational responses
+    pub samples: usize,
+    /// Requests that got at least one 1xx response
+    pub informed: usize,
+    /// Requests that got a `103 Early Hints`
+    pub early_hints: usize,
+    /// Average time to the first 1xx response, over the requests that got one (milliseconds)
+    pub first_avg_ms: Option<f64>,
+    /// Average time to the final response head (milliseconds)
+    pub response_avg_ms: f64,
+    /// Average lead of the first 1xx response over the final one (milliseconds)
+    pub lead_avg_ms: Option<f64>,
+}
+
+/// Running tally of informational responses
+#[derive(Debug, Clone, Default)]
+struct InformationalTally {
+    count: usize,
+    informed: usize,
+    early_hints: usize,
+    first_total_ms: f64,
+    response_total_ms: f64,
+    lead_total_ms: f64,
+}
+
+impl InformationalTally {
+    /// Count the informational responses of a measurement, if it was checked
+    fn record(&mut self, metrics: &TimingMetrics) {
+        let Some(ref informational) = metrics.informational else {
+            return;
+        };
+        self.count += 1;
+        self.response_total_ms += informational.response.as_secs_f64() * 1000.0;
+        if let (Some(first), Some(lead)) = (informational.first, informational.lead()) {
+            self.informed += 1;
+            self.first_total_ms += first.as_secs_f64() * 1000.0;
+            self.lead_total_ms += lead.as_secs_f64() * 1000.0;
+        }
+        if informational.early_hints() {
+            self.early_hints += 1;
+        }
+    }
+
+    fn summary(&self) -> Option<InformationalSummary> {
+        (self.count > 0).then(|| InformationalSummary {
+            samples: self.count,
+            informed: self.informed,
+            early_hints: self.early_hints,
+            first_avg_ms: (self.informed > 0).then(|| self.first_total_ms / self.informed as f64),
+            response_avg_ms: self.response_total_ms / self.count as f64,
+            lead_avg_ms: (self.informed > 0).then(|| self.lead_total_ms / self.informed as f64),
+        })
+    }
+}
+
+/// Phases servers reported in `Server-Timing` headers, over the requests that carried one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTimingSummary {
+    /// Number of responses with a `Server-Timing` header
+    pub samples: usize,
+    /// Average total time of those requests (milliseconds)
+    pub total_avg_ms: f64,
+    /// Average of the longest phase of each response (milliseconds); phases
+    /// may nest, so this is the least time the server says it spent
+    pub server_avg_ms: f64,
+    /// Each reported metric, in the order first seen
+    pub phases: Vec<ServerPhaseSummary>,
+}
+
+impl ServerTimingSummary {
+    /// Part of the total the server does not account for: the network, and
+    /// whatever the server leaves out
+    pub fn network_avg_ms(&self) -> f64 {
+        (self.total_avg_ms - self.server_avg_ms).max(0.0)
+    }
+}
+
+/// One `Server-Timing` metric averaged over the responses that reported it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerPhaseSummary {
+    pub name: String,
+    /// Description of the metric, as last reported
+    pub description: Option<String>,
+    /// Responses that reported a duration for it
+    pub samples: usize,
+    /// Average reported duration (milliseconds)
+    pub avg_ms: f64,
+}
+
+/// Running tally of `Server-Timing` phases
+#[derive(Debug, Clone, Default)]
+struct ServerTimingTally {
+    count: usize,
+    total_ms: f64,
+    server_ms: f64,
+    phases: Vec<(ServerPhaseSummary, f64)>,
+}
+
+impl ServerTimingTally {
+    /// Add the phases of a measurement, if its response reported any
+    fn record(&mut self, metrics: &TimingMetrics) {
+        if metrics.server_timing.is_empty() {
+            return;
+        }
+        self.count += 1;
+        self.total_ms += metrics.total_ms();
+        self.server_ms += metrics.server_timing.iter()
+            .filter_map(|timing| timing.duration_ms)
+            .fold(0.0, f64::max);
+        for timing in &metrics.server_timing {
+            let Some(duration_ms) = timing.duration_ms else { continue };
+            let index = match self.phases.iter().position(|(phase, _)| phase.name == timing.name) {
+                Some(index) => index,
+                None => {
+                    let phase = ServerPhaseSummary { name: timing.name.clone(), description: None, samples: 0, avg_ms: 0.0 };
+                    self.phases.push((phase, 0.0));
+                    self.phases.len() - 1
+                }
+            };
+            let (phase, total_ms) = &mut self.phases[index];
+            phase.samples += 1;
+            *total_ms += duration_ms;
+            if timing.description.is_some() {
+                phase.description = timing.description.clone();
+            }
+        }
+    }
+
+    fn summary(&self) -> Option<ServerTimingSummary> {
+        (self.count > 0).then(|| ServerTimingSummary {
+            samples: self.count,
+            total_avg_ms: self.total_ms / self.count as f64,
+            server_avg_ms: self.server_ms / self.count as f64,
+            phases: self.phases.iter()
+                .map(|(phase, total_ms)| ServerPhaseSummary { avg_ms: total_ms / phase.samples as f64, ..phase.clone() })
+                .collect(),
+        })
+    }
+}
+
+/// Statistics of first-use (cold) requests kept apart from steady-state (warm) ones
+///
+/// The first request over a fresh connection pays for TCP and TLS setup, which
+/// inflates aggregate numbers when only a handful of iterations are run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColdStartSplit {
+    /// Statistics of requests flagged as first use
+    pub cold: Statistics,
+    /// Statistics of the remaining requests
+    pub warm: Statistics,
+}
+
+impl ColdStartSplit {
+    /// Split successful measurements by their first-use flag
+    ///
+    /// Returns `None` unless there is at least one cold and one warm sample.
+    pub fn from_measurements(measurements: &[&TimingMetrics]) -> Option<Self> {
+        let (cold, warm): (Vec<&TimingMetrics>, Vec<&TimingMetrics>) = measurements
+            .iter()
+            .partition(|m| m.is_first_use);
+
+        if cold.is_empty() || warm.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            cold: Statistics::from_measurements(&cold),
+            warm: Statistics::from_measurements(&warm),
+        })
+    }
+
+    /// Extra average latency of first-use requests over steady state (milliseconds)
+    pub fn penalty_ms(&self) -> f64 {
+        self.cold.total_avg_ms - self.warm.total_avg_ms
+    }
+}
+
+/// Latency of responses served from a CDN cache versus fetched from the origin
+///
+/// The two populations usually differ by the round trip to the origin, so an
+/// average over both describes neither.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSplit {
+    /// Statistics of cache hits, if there were any
+    pub hit: Option<Statistics>,
+    /// Statistics of cache misses, which show the origin latency
+    pub miss: Option<Statistics>,
+}
+
+impl CacheSplit {
+    /// Split successful measurements by the cache status they reported
+    ///
+    /// Returns `None` when no measurement reported a cache status.
+    pub fn from_measurements(measurements: &[&TimingMetrics]) -> Option<Self> {
+        let segment = |status: CacheStatus| {
+            let matching: Vec<&TimingMetrics> = measurements.iter()
+                .copied()
+                .filter(|m| m.cache == Some(status))
+                .collect();
+            (!matching.is_empty()).then(|| Statistics::from_measurements(&matching))
+        };
+
+        let split = Self { hit: segment(CacheStatus::Hit), miss: segment(CacheStatus::Miss) };
+        (split.hit.is_some() || split.miss.is_some()).then_some(split)
+    }
+
+    /// Share of cache hits among the samples that reported a status (0.0-100.0)
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.hit.as_ref().map_or(0, |stats| stats.sample_count);
+        let misses = self.miss.as_ref().map_or(0, |stats| stats.sample_count);
+        if hits + misses == 0 {
+            return 0.0;
+        }
+        hits as f64 / (hits + misses) as f64 * 100.0
+    }
+
+    /// Extra average latency of misses over hits (milliseconds), when both occurred
+    pub fn miss_penalty_ms(&self) -> Option<f64> {
+        match (&self.hit, &self.miss) {
+            (Some(hit), Some(miss)) => Some(miss.total_avg_ms - hit.total_avg_ms),
+            _ => None,
+        }
+    }
+}
+
+/// Latency of new TLS connections that resumed a session versus those that
+/// made a full handshake
+///
+/// A resumed handshake saves round trips and the certificate check, so TLS
+/// times only compare across configurations within one of the two.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsResumptionSplit {
+    /// Statistics of resumed handshakes, if there were any
+    pub resumed: Option<Statistics>,
+    /// Statistics of full handshakes, if there were any
+    pub full: Option<Statistics>,
+}
+
+impl TlsResumptionSplit {
+    /// Average latency saved by resuming over a full handshake (milliseconds), when both occurred
+    pub fn resumption_saving_ms(&self) -> Option<f64> {
+        match (&self.resumed, &self.full) {
+            (Some(resumed), Some(full)) => Some(full.total_avg_ms - resumed.total_avg_ms),
+            _ => None,
+        }
+    }
+}
+
+/// Share of each connection phase in the average latency of a configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyBreakdown {
+    pub dns_percentage: f64,
+    pub tcp_percentage: f64,
+    pub tls_percentage: f64,
+    pub request_percentage: f64,
+    pub total_average_ms: f64,
+}
+
+impl LatencyBreakdown {
+    /// Share of each connection phase in the average latency of the given measurements
+    pub fn from_measurements<'a, I>(measurements: I) -> Self
+    where
+        I: IntoIterator<Item = &'a TimingMetrics>,
+    {
+        let measurements: Vec<&TimingMetrics> = measurements.into_iter().collect();
+
+        if measurements.is_empty() {
+            return Self {
+                dns_percentage: 0.0,
+                tcp_percentage: 0.0,
+                tls_percentage: 0.0,
+                request_percentage: 0.0,
+                total_average_ms: 0.0,
+            };
+        }
+
+        let total_count = measurements.len() as f64;
+        let avg_dns = measurements.iter().map(|m| m.dns_ms()).sum::<f64>() / total_count;
+        let avg_tcp = measurements.iter().map(|m| m.tcp_ms()).sum::<f64>() / total_count;
+        let avg_tls = measurements.iter()
+            .map(|m| m.tls_ms().unwrap_or(0.0))
+            .sum::<f64>() / total_count;
+        let avg_first_byte = measurements.iter().map(|m| m.first_byte_ms()).sum::<f64>() / total_count;
+        let avg_total = measurements.iter().map(|m| m.total_ms()).sum::<f64>() / total_count;
+
+        let total_breakdown = avg_dns + avg_tcp + avg_tls + avg_first_byte;
+
+        Self {
+            dns_percentage: if total_breakdown > 0.0 { (avg_dns / total_breakdown) * 100.0 } else { 0.0 },
+            tcp_percentage: if total_breakdown > 0.0 { (avg_tcp / total_breakdown) * 100.0 } else { 0.0 },
+            tls_percentage: if total_breakdown > 0.0 { (avg_tls / total_breakdown) * 100.0 } else { 0.0 },
+            request_percentage: if total_breakdown > 0.0 { (avg_first_byte / total_breakdown) * 100.0 } else { 0.0 },
+            total_average_ms: avg_total,
+        }
+    }
+}
+
+/// Statistics for every DNS configuration tested against a single URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlBreakdown {
+    /// Target URL
+    pub url: String,
+    /// Extended statistics for each configuration against this URL
+    pub config_stats: HashMap<String, ExtendedStatistics>,
+    /// Fastest configuration for this URL by average response time
+    pub best_config: Option<String>,
+}
+
+impl UrlBreakdown {
+    /// Configurations ordered fastest first
+    pub fn ranked_configs(&self) -> Vec<(&String, &ExtendedStatistics)> {
+        let mut ranked: Vec<_> = self.config_stats.iter().collect();
+        ranked.sort_by(|a, b| a.1.basic.total_avg_ms.partial_cmp(&b.1.basic.total_avg_ms).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
 }
 
 /// Confidence intervals for key metrics
@@ -130,6 +888,63 @@ pub struct ReliabilityMetrics {
     pub jitter_ms: f64,
     /// Uptime percentage (if applicable)
     pub uptime_percentage: Option<f64>,
+    /// Share of UDP probe packets lost, when `--loss-probe` measured it
+    #[serde(default)]
+    pub packet_loss_percentage: Option<f64>,
+    /// Requests the server turned away for coming too often
+    #[serde(default)]
+    pub throttling: Option<ThrottlingSummary>,
+}
+
+/// Requests a server throttled with `429 Too Many Requests`, or `503` with a
+/// `Retry-After`; they fail because of the client's request rate, not
+/// because of the network or the route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThrottlingSummary {
+    /// Number of throttled requests
+    pub count: usize,
+    /// Share of attempted requests that were throttled (0.0-100.0)
+    pub rate: f64,
+    /// Throttled requests answered with 429
+    pub too_many_requests: usize,
+    /// Throttled requests answered with 503
+    pub unavailable: usize,
+    /// Longest wait a `Retry-After` header asked for (seconds)
+    pub retry_after_max_secs: Option<u64>,
+}
+
+/// Running tally of throttled requests
+#[derive(Debug, Clone, Default)]
+struct ThrottlingTally {
+    too_many_requests: usize,
+    unavailable: usize,
+    retry_after_max: Option<std::time::Duration>,
+}
+
+impl ThrottlingTally {
+    /// Count a measurement if the server throttled it
+    fn record(&mut self, metrics: &TimingMetrics) {
+        let Some(throttling) = metrics.throttling else {
+            return;
+        };
+        if throttling.status == 429 {
+            self.too_many_requests += 1;
+        } else {
+            self.unavailable += 1;
+        }
+        self.retry_after_max = self.retry_after_max.max(throttling.retry_after);
+    }
+
+    fn summary(&self, attempts: u64) -> Option<ThrottlingSummary> {
+        let count = self.too_many_requests + self.unavailable;
+        (count > 0).then(|| ThrottlingSummary {
+            count,
+            rate: if attempts > 0 { count as f64 / attempts as f64 * 100.0 } else { 0.0 },
+            too_many_requests: self.too_many_requests,
+            unavailable: self.unavailable,
+            retry_after_max_secs: self.retry_after_max.map(|wait| wait.as_secs()),
+        })
+    }
 }
 
 /// Comparative analysis between DNS configurations
@@ -145,6 +960,29 @@ pub struct ComparativeAnalysis {
     pub performance_rankings: Vec<ConfigurationRanking>,
     /// Statistical significance tests
     pub significance_tests: Vec<SignificanceTest>,
+    /// Winner declarations withheld because the leader was not separated from the runner-up
+    #[serde(default)]
+    pub ties: Vec<WinnerTie>,
+    /// Latency differences between configurations that reached a target on
+    /// different networks, split into resolver and routing time
+    #[serde(default)]
+    pub route_divergence: Vec<RouteDivergence>,
+}
+
+/// A leader that could not be declared the winner with statistical confidence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WinnerTie {
+    /// Metric the tie applies to ("speed" or "reliability")
+    pub metric: String,
+    /// Configuration with the best observed value
+    pub leader: String,
+    /// Closest competitor
+    pub runner_up: String,
+    /// Observed difference between leader and runner-up (ms or percentage points)
+    pub difference: f64,
+    /// Additional separation needed for the confidence intervals to stop overlapping,
+    /// or None when there were too few samples to compute intervals
+    pub margin_needed: Option<f64>,
 }
 
 /// Ranking of DNS configurations
@@ -224,6 +1062,18 @@ impl Default for StatisticsConfig {
             percentiles: vec![50.0, 90.0, 95.0, 99.0],
             exclude_outliers: false,
             outlier_method: OutlierMethod::IQR,
+            scoring_weights: ScoringWeights::default(),
+            censor_timeouts: false,
+        }
+    }
+}
+
+impl From<&Config> for StatisticsConfig {
+    fn from(config: &Config) -> Self {
+        Self {
+            scoring_weights: config.scoring,
+            censor_timeouts: config.censor_timeouts,
+            ..Self::default()
         }
     }
 }
@@ -233,6 +1083,10 @@ impl StatisticsEngine {
     pub fn new(config: StatisticsConfig) -> Self {
         Self {
             results: HashMap::new(),
+            packet_loss: Vec::new(),
+            public_baseline: Vec::new(),
+            physical_bounds: Vec::new(),
+            networks: BTreeMap::new(),
             config,
         }
     }
@@ -256,171 +1110,289 @@ impl StatisticsEngine {
         self.results.entry(config_name).or_default().push(result);
     }
 
+    /// Add packet loss measurements, which count towards the reliability of
+    /// the configuration each belongs to
+    pub fn add_packet_loss(&mut self, reports: impl IntoIterator<Item = LossReport>) {
+        self.packet_loss.extend(reports);
+    }
+
+    /// Add the comparisons of `--compare-public`, to warn about hosts reached
+    /// much more slowly than from elsewhere
+    pub fn add_public_baseline(&mut self, comparisons: impl IntoIterator<Item = BaselineComparison>) {
+        self.public_baseline.extend(comparisons);
+    }
+
+    /// Add the routes measured against their physical minimum by `--geo`, to
+    /// warn about routes that obviously take a detour
+    pub fn add_physical_bounds(&mut self, bounds: impl IntoIterator<Item = PhysicalBound>) {
+        self.physical_bounds.extend(bounds);
+    }
+
+    /// Add the networks of addresses, such as the ASNs `--geo` looked up, to
+    /// tell apart configurations that reached a target on different networks
+    pub fn add_networks(&mut self, networks: impl IntoIterator<Item = (IpAddr, String)>) {
+        self.networks.extend(networks);
+    }
+
+    /// The fastest configuration over all results, declared the way the comparative
+    /// analysis declares it: `None` while it is not separated from the runner-up
+    pub fn fastest_config(&self) -> Option<String> {
+        let stats: HashMap<String, ExtendedStatistics> = self.results.iter()
+            .filter_map(|(config_name, results)| {
+                self.calculate_extended_statistics(results.iter())
+                    .ok()
+                    .map(|stats| (config_name.to_string(), stats))
+            })
+            .collect();
+
+        match stats.len() {
+            0 | 1 => stats.into_keys().next(),
+            _ => self.declare_speed_winner(&stats, &self.perform_significance_tests(&stats)).0,
+        }
+    }
+
     /// Generate comprehensive statistical analysis
     pub fn analyze(&self) -> Result<StatisticalAnalysis> {
         if self.results.is_empty() {
             return Err(AppError::validation("No test results available for analysis"));
         }
 
-        let mut basic_stats = HashMap::new();
-        
-        // Calculate extended statistics for each configuration
-        for (config_name, results) in &self.results {
-            if results.is_empty() {
-                continue;
-            }
-
-            let extended_stats = self.calculate_extended_statistics(results)?;
-            basic_stats.insert(config_name.clone(), extended_stats);
-        }
+        // Calculate extended statistics for each configuration, side by side for large sets
+        let configs: Vec<(&Name, &Vec<TestResult>)> = self.results.iter()
+            .filter(|(_, results)| !results.is_empty())
+            .collect();
+        let samples = configs.iter()
+            .flat_map(|(_, results)| results.iter())
+            .map(|result| result.individual_results.len())
+            .sum();
+        let basic_stats = parallel::map_items(&configs, samples, |(config_name, results)| {
+            let mut extended_stats = self.calculate_extended_statistics(results.iter())?;
+            extended_stats.reliability.packet_loss_percentage = LossReport::combined_loss(
+                self.packet_loss.iter().filter(|report| report.config_name == *config_name.as_str()),
+            );
+            Ok((config_name.to_string(), extended_stats))
+        }).into_iter().collect::<Result<HashMap<_, _>>>()?;
 
         // Perform comparative analysis
-        let comparative_analysis = self.perform_comparative_analysis(&basic_stats)?;
+        let mut comparative_analysis = self.perform_comparative_analysis(&basic_stats)?;
 
         // Perform trend analysis if we have temporal data
         let trend_analysis = self.perform_trend_analysis()?;
 
+        // Break results down per target URL
+        let url_breakdown = self.calculate_url_breakdown();
+
+        // Compare the addresses each configuration resolved the targets to
+        let split_horizon = SplitHorizon::detect_with_networks(self.results.values().flatten(), &self.networks);
+        comparative_analysis.route_divergence = RouteDivergence::detect(&split_horizon, self.results.values().flatten());
+
+        // Line the interfaces up per configuration
+        let interface_matrix = InterfaceMatrix::from_results(self.results.values().flatten());
+
         // Generate summary and recommendations
-        let summary = self.generate_summary(&basic_stats, &comparative_analysis)?;
+        let summary = self.generate_summary(&basic_stats, &comparative_analysis, &url_breakdown)?;
 
         Ok(StatisticalAnalysis {
             basic_stats,
             comparative_analysis,
             trend_analysis,
+            url_breakdown,
+            split_horizon,
+            interface_matrix,
             summary,
             generated_at: Utc::now(),
         })
     }
 
     /// Calculate extended statistics for a set of test results
-    fn calculate_extended_statistics(&self, results: &[TestResult]) -> Result<ExtendedStatistics> {
-        if results.is_empty() {
-            return Err(AppError::validation("No results provided for statistics calculation"));
-        }
-
-        // Collect all successful timing measurements
-        let mut all_timings = Vec::new();
+    ///
+    /// Every sample is looked at once: it is folded into the running
+    /// aggregates, and only the response times themselves are kept, sorted
+    /// once for the exact percentiles and the outlier analysis.
+    fn calculate_extended_statistics<'a>(&self, results: impl IntoIterator<Item = &'a TestResult>) -> Result<ExtendedStatistics> {
+        let mut tallies = SampleTallies::default();
+        // Successful response times, and timeouts as lower bounds when censoring
+        let mut totals = Vec::new();
+        let mut censored = Vec::new();
+        let mut sizes = Vec::new();
+        let mut total_attempts: u32 = 0;
+        let mut successful_attempts: u32 = 0;
+        let mut any_results = false;
         for result in results {
+            any_results = true;
+            total_attempts += result.total_count;
+            successful_attempts += result.success_count;
+            totals.reserve(result.individual_results.len());
             for timing in &result.individual_results {
-                if timing.is_successful() {
-                    all_timings.push(timing);
+                match tallies.record(timing) {
+                    Recorded::Success { total_ms, body_bytes } => {
+                        totals.push(total_ms);
+                        sizes.extend(body_bytes);
+                    }
+                    Recorded::Timeout { total_ms } if self.config.censor_timeouts => censored.push(total_ms),
+                    Recorded::Timeout { .. } | Recorded::Failed => {}
                 }
             }
         }
 
-        if all_timings.is_empty() {
+        if !any_results {
+            return Err(AppError::validation("No results provided for statistics calculation"));
+        }
+        if totals.is_empty() {
             return Err(AppError::validation("No successful measurements for statistics calculation"));
         }
 
-        // Calculate basic statistics using existing implementation
-        let basic = Statistics::from_measurements(&all_timings);
-
-        // Calculate percentiles
-        let percentiles = self.calculate_percentiles(&all_timings)?;
-
-        // Calculate confidence intervals
-        let confidence_intervals = self.calculate_confidence_intervals(&all_timings)?;
-
-        // Perform outlier analysis
-        let outlier_analysis = self.detect_outliers(&all_timings)?;
-
-        // Calculate performance distribution
-        let performance_distribution = self.calculate_performance_distribution(&all_timings);
+        parallel::sort_samples(&mut totals);
+        parallel::sort_samples(&mut sizes);
+        let percentiles = if censored.is_empty() {
+            self.calculate_percentiles(&totals)
+        } else {
+            let mut with_censored = [totals.as_slice(), censored.as_slice()].concat();
+            parallel::sort_samples(&mut with_censored);
+            self.calculate_percentiles(&with_censored)
+        };
 
-        // Calculate reliability metrics
-        let reliability = self.calculate_reliability_metrics(results, &all_timings);
+        let success_rate = if total_attempts > 0 {
+            (successful_attempts as f64 / total_attempts as f64) * 100.0
+        } else {
+            0.0
+        };
 
         Ok(ExtendedStatistics {
-            basic,
+            basic: tallies.all.statistics().unwrap_or_else(Statistics::empty),
             percentiles,
-            confidence_intervals,
-            outlier_analysis,
-            performance_distribution,
-            reliability,
+            confidence_intervals: self.calculate_confidence_intervals(&tallies, total_attempts),
+            outlier_analysis: self.detect_outliers(&totals),
+            performance_distribution: tallies.performance_distribution(),
+            reliability: tallies.reliability(success_rate, total_attempts as u64),
+            // Keep connection setup from hiding in the aggregate numbers
+            cold_start: tallies.cold_start(),
+            cache_split: tallies.cache_split(),
+            tls_resumption: tallies.tls_resumption(),
+            timeouts: tallies.timeouts.summary(total_attempts as u64, self.config.censor_timeouts),
+            response_size: tallies.bodies.summary(|p| Self::calculate_percentile(&sizes, p)),
+            socket: tallies.sockets.summary(),
+            upload: tallies.uploads.summary(),
+            informational: tallies.informational.summary(),
+            server_timing: tallies.server_timing.summary(),
         })
     }
 
-    /// Calculate percentiles for timing measurements
-    fn calculate_percentiles(&self, timings: &[&TimingMetrics]) -> Result<HashMap<String, f64>> {
-        let mut total_times: Vec<f64> = timings.iter().map(|t| t.total_ms()).collect();
-        total_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        let mut percentiles = HashMap::new();
-        
-        for &p in &self.config.percentiles {
-            let value = self.calculate_percentile(&total_times, p);
-            percentiles.insert(format!("p{:.0}", p), value);
+    /// Calculate per-URL statistics for each configuration
+    fn calculate_url_breakdown(&self) -> Vec<UrlBreakdown> {
+        let mut by_url: HashMap<&str, HashMap<&str, Vec<&TestResult>>> = HashMap::new();
+        for (config_name, results) in &self.results {
+            for result in results {
+                by_url.entry(result.url.as_str())
+                    .or_default()
+                    .entry(config_name.as_str())
+                    .or_default()
+                    .push(result);
+            }
         }
 
-        Ok(percentiles)
+        let samples = self.results.values()
+            .flat_map(|results| results.iter())
+            .map(|result| result.individual_results.len())
+            .sum();
+        let by_url: Vec<(&str, HashMap<&str, Vec<&TestResult>>)> = by_url.into_iter().collect();
+        let mut breakdown = parallel::map_items(&by_url, samples, |(url, configs)| {
+            // Configurations without a single successful measurement have no statistics
+            let config_stats: HashMap<String, ExtendedStatistics> = configs.iter()
+                .filter_map(|(config_name, results)| {
+                    self.calculate_extended_statistics(results.iter().copied())
+                        .ok()
+                        .map(|stats| (config_name.to_string(), stats))
+                })
+                .collect();
+
+            // Only a configuration separated from the runner-up is the best for the URL
+            let best_config = match config_stats.len() {
+                0 | 1 => config_stats.keys().next().cloned(),
+                _ => self.declare_speed_winner(&config_stats, &self.perform_significance_tests(&config_stats)).0,
+            };
+
+            UrlBreakdown {
+                url: url.to_string(),
+                config_stats,
+                best_config,
+            }
+        });
+
+        breakdown.sort_by(|a, b| a.url.cmp(&b.url));
+        breakdown
     }
 
-    /// Calculate a specific percentile
-    fn calculate_percentile(&self, sorted_values: &[f64], percentile: f64) -> f64 {
-        if sorted_values.is_empty() {
+    /// Calculate the configured percentiles of response times in ascending order
+    fn calculate_percentiles(&self, sorted_values: &[f64]) -> HashMap<String, f64> {
+        self.config.percentiles.iter()
+            .map(|&p| (format!("p{:.0}", p), Self::calculate_percentile(sorted_values, p)))
+            .collect()
+    }
+
+    /// Calculate a specific percentile of values in ascending order
+    pub(crate) fn calculate_percentile(sorted_values: &[f64], percentile: f64) -> f64 {
+        Self::percentile_by_rank(sorted_values.len(), percentile, |rank| sorted_values[rank])
+    }
+
+    /// Percentile of `count` values in ascending order, looked up by rank with
+    /// `value_at`, interpolated linearly between the two nearest ranks
+    pub(crate) fn percentile_by_rank(count: usize, percentile: f64, value_at: impl Fn(usize) -> f64) -> f64 {
+        if count == 0 {
             return 0.0;
         }
 
-        let index = (percentile / 100.0) * (sorted_values.len() as f64 - 1.0);
+        let index = (percentile.clamp(0.0, 100.0) / 100.0) * (count as f64 - 1.0);
         let lower_index = index.floor() as usize;
         let upper_index = index.ceil() as usize;
 
         if lower_index == upper_index {
-            sorted_values[lower_index]
+            value_at(lower_index)
         } else {
-            let lower_value = sorted_values[lower_index];
-            let upper_value = sorted_values[upper_index];
+            let lower_value = value_at(lower_index);
+            let upper_value = value_at(upper_index);
             let weight = index - lower_index as f64;
             lower_value + weight * (upper_value - lower_value)
         }
     }
 
     /// Calculate confidence intervals for key metrics
-    fn calculate_confidence_intervals(&self, timings: &[&TimingMetrics]) -> Result<ConfidenceIntervals> {
-        if timings.len() < self.config.min_samples {
-            return Ok(ConfidenceIntervals {
+    fn calculate_confidence_intervals(&self, tallies: &SampleTallies, total_attempts: u32) -> ConfidenceIntervals {
+        let samples = tallies.sample_count();
+        let success_rate = self.calculate_success_rate_interval(samples as u32, total_attempts);
+
+        if (samples as usize) < self.config.min_samples {
+            return ConfidenceIntervals {
                 level: self.config.confidence_level,
                 avg_response_time: (0.0, 0.0),
-                success_rate: (0.0, 0.0),
+                success_rate,
                 dns_resolution_time: (0.0, 0.0),
-            });
+            };
         }
 
-        let n = timings.len() as f64;
+        let sqrt_n = (samples as f64).sqrt();
         let z_score = self.get_z_score(self.config.confidence_level);
+        let total = &tallies.all.total;
+        let dns = &tallies.all.dns;
+        let response_margin = z_score * total.sample_std_dev() / sqrt_n;
+        let dns_margin = z_score * dns.sample_std_dev() / sqrt_n;
 
-        // Response time confidence interval
-        let response_times: Vec<f64> = timings.iter().map(|t| t.total_ms()).collect();
-        let response_mean = response_times.iter().sum::<f64>() / n;
-        let response_std = self.calculate_standard_deviation(&response_times, response_mean);
-        let response_margin = z_score * response_std / n.sqrt();
-
-        // DNS resolution time confidence interval
-        let dns_times: Vec<f64> = timings.iter().map(|t| t.dns_ms()).collect();
-        let dns_mean = dns_times.iter().sum::<f64>() / n;
-        let dns_std = self.calculate_standard_deviation(&dns_times, dns_mean);
-        let dns_margin = z_score * dns_std / n.sqrt();
-
-        // Success rate is 100% for successful measurements (these are pre-filtered)
-        let success_margin = z_score * (100.0 * 0.0 / n).sqrt(); // No variation in success rate
-
-        Ok(ConfidenceIntervals {
+        ConfidenceIntervals {
             level: self.config.confidence_level,
-            avg_response_time: (response_mean - response_margin, response_mean + response_margin),
-            success_rate: (100.0 - success_margin, 100.0 + success_margin),
-            dns_resolution_time: (dns_mean - dns_margin, dns_mean + dns_margin),
-        })
+            avg_response_time: (total.mean - response_margin, total.mean + response_margin),
+            success_rate,
+            dns_resolution_time: (dns.mean - dns_margin, dns.mean + dns_margin),
+        }
+    }
+
+    /// Wilson score interval for the success rate, in percent
+    fn calculate_success_rate_interval(&self, successes: u32, attempts: u32) -> (f64, f64) {
+        wilson_interval(successes as u64, attempts as u64, self.get_z_score(self.config.confidence_level))
     }
 
     /// Get Z-score for given confidence level
     fn get_z_score(&self, confidence_level: f64) -> f64 {
-        // Common Z-scores for confidence levels
-        match confidence_level {
-            level if (level - 0.90).abs() < 0.01 => 1.645,
-            level if (level - 0.95).abs() < 0.01 => 1.96,
-            level if (level - 0.99).abs() < 0.01 => 2.576,
-            _ => 1.96, // Default to 95%
-        }
+        z_score(confidence_level)
     }
 
     /// Calculate standard deviation
@@ -436,16 +1408,14 @@ impl StatisticsEngine {
         variance.sqrt()
     }
 
-    /// Detect outliers in timing measurements
-    fn detect_outliers(&self, timings: &[&TimingMetrics]) -> Result<OutlierAnalysis> {
-        let total_times: Vec<f64> = timings.iter().map(|t| t.total_ms()).collect();
-        
+    /// Detect outliers among response times in ascending order
+    fn detect_outliers(&self, total_times: &[f64]) -> OutlierAnalysis {
         let outlier_count = match self.config.outlier_method {
-            OutlierMethod::IQR => self.detect_outliers_iqr(&total_times),
+            OutlierMethod::IQR => self.detect_outliers_iqr(total_times),
             OutlierMethod::StandardDeviation { threshold } => 
-                self.detect_outliers_std_dev(&total_times, threshold),
+                self.detect_outliers_std_dev(total_times, threshold),
             OutlierMethod::ModifiedZScore { threshold } => 
-                self.detect_outliers_modified_z_score(&total_times, threshold),
+                self.detect_outliers_modified_z_score(total_times, threshold),
         };
 
         let outlier_percentage = if total_times.is_empty() {
@@ -454,44 +1424,28 @@ impl StatisticsEngine {
             (outlier_count as f64 / total_times.len() as f64) * 100.0
         };
 
-        let mut threshold_values = HashMap::new();
-        match self.config.outlier_method {
-            OutlierMethod::StandardDeviation { threshold } => {
-                threshold_values.insert("std_dev_threshold".to_string(), threshold);
-            }
-            OutlierMethod::ModifiedZScore { threshold } => {
-                threshold_values.insert("z_score_threshold".to_string(), threshold);
-            }
-            OutlierMethod::IQR => {
-                threshold_values.insert("iqr_multiplier".to_string(), 1.5);
-            }
-        }
-
-        Ok(OutlierAnalysis {
+        OutlierAnalysis {
             outlier_count,
             outlier_percentage,
             detection_method: format!("{:?}", self.config.outlier_method),
-            threshold_values,
-        })
+            threshold_values: self.config.outlier_method.threshold_values(),
+        }
     }
 
-    /// Detect outliers using interquartile range method
-    fn detect_outliers_iqr(&self, values: &[f64]) -> usize {
-        if values.len() < 4 {
+    /// Detect outliers using interquartile range method on values in ascending order
+    fn detect_outliers_iqr(&self, sorted_values: &[f64]) -> usize {
+        if sorted_values.len() < 4 {
             return 0;
         }
 
-        let mut sorted_values = values.to_vec();
-        sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        let q1 = self.calculate_percentile(&sorted_values, 25.0);
-        let q3 = self.calculate_percentile(&sorted_values, 75.0);
+        let q1 = Self::calculate_percentile(sorted_values, 25.0);
+        let q3 = Self::calculate_percentile(sorted_values, 75.0);
         let iqr = q3 - q1;
         
         let lower_bound = q1 - 1.5 * iqr;
         let upper_bound = q3 + 1.5 * iqr;
 
-        values.iter()
+        sorted_values.iter()
             .filter(|&&x| x < lower_bound || x > upper_bound)
             .count()
     }
@@ -510,30 +1464,26 @@ impl StatisticsEngine {
             .count()
     }
 
-    /// Detect outliers using modified Z-score method
-    fn detect_outliers_modified_z_score(&self, values: &[f64], threshold: f64) -> usize {
-        if values.is_empty() {
+    /// Detect outliers using modified Z-score method on values in ascending order
+    fn detect_outliers_modified_z_score(&self, sorted_values: &[f64], threshold: f64) -> usize {
+        if sorted_values.is_empty() {
             return 0;
         }
 
-        let mut sorted_values = values.to_vec();
-        sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        
-        let median = self.calculate_percentile(&sorted_values, 50.0);
+        let median = Self::calculate_percentile(sorted_values, 50.0);
         let mad = {
-            let deviations: Vec<f64> = values.iter()
+            let mut deviations: Vec<f64> = sorted_values.iter()
                 .map(|&x| (x - median).abs())
                 .collect();
-            let mut sorted_deviations = deviations;
-            sorted_deviations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-            self.calculate_percentile(&sorted_deviations, 50.0)
+            parallel::sort_samples(&mut deviations);
+            Self::calculate_percentile(&deviations, 50.0)
         };
 
         if mad == 0.0 {
             return 0;
         }
 
-        values.iter()
+        sorted_values.iter()
             .filter(|&&x| {
                 let modified_z_score = 0.6745 * (x - median) / mad;
                 modified_z_score.abs() > threshold
@@ -541,77 +1491,6 @@ impl StatisticsEngine {
             .count()
     }
 
-    /// Calculate performance distribution
-    fn calculate_performance_distribution(&self, timings: &[&TimingMetrics]) -> PerformanceDistribution {
-        if timings.is_empty() {
-            return PerformanceDistribution {
-                good_percentage: 0.0,
-                moderate_percentage: 0.0,
-                poor_percentage: 0.0,
-            };
-        }
-
-        let total = timings.len() as f64;
-        let mut good_count = 0;
-        let mut moderate_count = 0;
-        let mut poor_count = 0;
-
-        for timing in timings {
-            match timing.performance_level() {
-                PerformanceLevel::Good => good_count += 1,
-                PerformanceLevel::Moderate => moderate_count += 1,
-                PerformanceLevel::Poor => poor_count += 1,
-            }
-        }
-
-        PerformanceDistribution {
-            good_percentage: (good_count as f64 / total) * 100.0,
-            moderate_percentage: (moderate_count as f64 / total) * 100.0,
-            poor_percentage: (poor_count as f64 / total) * 100.0,
-        }
-    }
-
-    /// Calculate reliability metrics
-    fn calculate_reliability_metrics(&self, results: &[TestResult], successful_timings: &[&TimingMetrics]) -> ReliabilityMetrics {
-        let total_attempts: u32 = results.iter().map(|r| r.total_count).sum();
-        let successful_attempts: u32 = results.iter().map(|r| r.success_count).sum();
-
-        let success_rate = if total_attempts > 0 {
-            (successful_attempts as f64 / total_attempts as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        // Calculate consistency score (coefficient of variation)
-        let response_times: Vec<f64> = successful_timings.iter().map(|t| t.total_ms()).collect();
-        let consistency_score = if !response_times.is_empty() {
-            let mean = response_times.iter().sum::<f64>() / response_times.len() as f64;
-            if mean > 0.0 {
-                let std_dev = self.calculate_standard_deviation(&response_times, mean);
-                std_dev / mean
-            } else {
-                0.0
-            }
-        } else {
-            0.0
-        };
-
-        // Calculate jitter (standard deviation of response times)
-        let jitter_ms = if response_times.len() > 1 {
-            let mean = response_times.iter().sum::<f64>() / response_times.len() as f64;
-            self.calculate_standard_deviation(&response_times, mean)
-        } else {
-            0.0
-        };
-
-        ReliabilityMetrics {
-            success_rate,
-            consistency_score,
-            jitter_ms,
-            uptime_percentage: None, // Not applicable for individual tests
-        }
-    }
-
     /// Perform comparative analysis between configurations
     fn perform_comparative_analysis(&self, stats: &HashMap<String, ExtendedStatistics>) -> Result<ComparativeAnalysis> {
         if stats.len() < 2 {
@@ -621,17 +1500,29 @@ impl StatisticsEngine {
                 most_consistent_config: stats.keys().next().cloned(),
                 performance_rankings: Vec::new(),
                 significance_tests: Vec::new(),
+                ties: Vec::new(),
+                route_divergence: Vec::new(),
             });
         }
 
-        // Find best configurations by different metrics
-        let fastest_config = stats.iter()
-            .min_by(|a, b| a.1.basic.total_avg_ms.partial_cmp(&b.1.basic.total_avg_ms).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(name, _)| name.clone());
+        // Perform significance tests (simplified implementation)
+        let significance_tests = self.perform_significance_tests(stats);
 
-        let most_reliable_config = stats.iter()
-            .max_by(|a, b| a.1.reliability.success_rate.partial_cmp(&b.1.reliability.success_rate).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(name, _)| name.clone());
+        // Only declare winners that are separated from the runner-up; report ties otherwise
+        let mut ties = Vec::new();
+
+        let (fastest_config, speed_tie) = self.declare_speed_winner(stats, &significance_tests);
+        ties.extend(speed_tie);
+
+        let (most_reliable_config, reliability_tie) = self.declare_winner(
+            stats,
+            "reliability",
+            |s| s.reliability.success_rate,
+            |s| Some(s.confidence_intervals.success_rate),
+            false,
+            &[],
+        );
+        ties.extend(reliability_tie);
 
         let most_consistent_config = stats.iter()
             .min_by(|a, b| a.1.reliability.consistency_score.partial_cmp(&b.1.reliability.consistency_score).unwrap_or(std::cmp::Ordering::Equal))
@@ -640,21 +1531,99 @@ impl StatisticsEngine {
         // Create performance rankings
         let performance_rankings = self.calculate_performance_rankings(stats);
 
-        // Perform significance tests (simplified implementation)
-        let significance_tests = self.perform_significance_tests(stats);
-
         Ok(ComparativeAnalysis {
             fastest_config,
             most_reliable_config,
             most_consistent_config,
             performance_rankings,
             significance_tests,
+            ties,
+            route_divergence: Vec::new(),
         })
     }
 
+    /// Declare the fastest configuration by average response time, with a confidence
+    /// interval only for configurations that have enough samples
+    fn declare_speed_winner(
+        &self,
+        stats: &HashMap<String, ExtendedStatistics>,
+        significance_tests: &[SignificanceTest],
+    ) -> (Option<String>, Option<WinnerTie>) {
+        self.declare_winner(
+            stats,
+            "speed",
+            |s| s.basic.total_avg_ms,
+            |s| (s.basic.sample_count >= self.config.min_samples).then_some(s.confidence_intervals.avg_response_time),
+            true,
+            significance_tests,
+        )
+    }
+
+    /// Pick the leader for a metric, but only declare it the winner when its confidence
+    /// interval does not overlap the runner-up's or a significance test separates them
+    ///
+    /// Configurations with identical values are neither a winner nor a tie.
+    fn declare_winner<V, I>(
+        &self,
+        stats: &HashMap<String, ExtendedStatistics>,
+        metric: &str,
+        value: V,
+        interval: I,
+        lower_is_better: bool,
+        significance_tests: &[SignificanceTest],
+    ) -> (Option<String>, Option<WinnerTie>)
+    where
+        V: Fn(&ExtendedStatistics) -> f64,
+        I: Fn(&ExtendedStatistics) -> Option<(f64, f64)>,
+    {
+        let mut candidates: Vec<(&String, &ExtendedStatistics)> = stats.iter().collect();
+        candidates.sort_by(|a, b| {
+            let ordering = value(a.1).partial_cmp(&value(b.1)).unwrap_or(std::cmp::Ordering::Equal);
+            let ordering = if lower_is_better { ordering } else { ordering.reverse() };
+            ordering.then_with(|| a.0.cmp(b.0))
+        });
+
+        let (leader, leader_stats) = candidates[0];
+        let Some(&(runner_up, runner_up_stats)) = candidates.get(1) else {
+            return (Some(leader.clone()), None);
+        };
+
+        let difference = (value(leader_stats) - value(runner_up_stats)).abs();
+        if difference == 0.0 {
+            return (None, None);
+        }
+        let significant = significance_tests.iter().any(|t| {
+            t.is_significant
+                && ((&t.configurations.0 == leader && &t.configurations.1 == runner_up)
+                    || (&t.configurations.0 == runner_up && &t.configurations.1 == leader))
+        });
+
+        // Positive overlap means the intervals still intersect by that amount
+        let overlap = match (interval(leader_stats), interval(runner_up_stats)) {
+            (Some((_, leader_upper)), Some((runner_up_lower, _))) if lower_is_better => Some(leader_upper - runner_up_lower),
+            (Some((leader_lower, _)), Some((_, runner_up_upper))) => Some(runner_up_upper - leader_lower),
+            _ => None,
+        };
+
+        let separated = significant || overlap.is_some_and(|o| o < 0.0);
+        if separated {
+            return (Some(leader.clone()), None);
+        }
+
+        (None, Some(WinnerTie {
+            metric: metric.to_string(),
+            leader: leader.clone(),
+            runner_up: runner_up.clone(),
+            difference,
+            margin_needed: overlap.map(|o| o.max(0.0)),
+        }))
+    }
+
     /// Calculate performance rankings for all configurations
     fn calculate_performance_rankings(&self, stats: &HashMap<String, ExtendedStatistics>) -> Vec<ConfigurationRanking> {
         let mut rankings = Vec::new();
+        let weights = &self.config.scoring_weights;
+        let total_weight = weights.total();
 
         for (config_name, config_stats) in stats {
             let mut metric_scores = HashMap::new();
@@ -663,13 +1632,23 @@ impl StatisticsEngine {
             let speed_score = self.calculate_speed_score(config_stats, stats);
             let reliability_score = config_stats.reliability.success_rate / 100.0;
             let consistency_score = 1.0 - config_stats.reliability.consistency_score.min(1.0);
+            let p95_score = Self::relative_score(config_stats, stats, Self::p95_ms);
+            let jitter_score = Self::relative_score(config_stats, stats, |s| s.reliability.jitter_ms);
+            // Configurations whose loss was not measured are not penalized
+            let loss_score = 1.0 - config_stats.reliability.packet_loss_percentage.unwrap_or(0.0) / 100.0;
 
             metric_scores.insert("speed".to_string(), speed_score);
             metric_scores.insert("reliability".to_string(), reliability_score);
             metric_scores.insert("consistency".to_string(), consistency_score);
+            metric_scores.insert("p95".to_string(), p95_score);
+            metric_scores.insert("jitter".to_string(), jitter_score);
+            metric_scores.insert("loss".to_string(), loss_score);
 
             // Calculate overall score (weighted average)
-            let overall_score = (speed_score * 0.4) + (reliability_score * 0.35) + (consistency_score * 0.25);
+            let weighted_sum: f64 = metric_scores.iter()
+                .map(|(dimension, score)| score * weights.get(dimension).unwrap_or(0.0))
+                .sum();
+            let overall_score = if total_weight > 0.0 { weighted_sum / total_weight } else { 0.0 };
 
             rankings.push(ConfigurationRanking {
                 config_name: config_name.clone(),
@@ -690,18 +1669,31 @@ impl StatisticsEngine {
 
     /// Calculate speed score relative to other configurations
     fn calculate_speed_score(&self, config_stats: &ExtendedStatistics, all_stats: &HashMap<String, ExtendedStatistics>) -> f64 {
-        let avg_time = config_stats.basic.total_avg_ms;
-        let min_time = all_stats.values()
-            .map(|s| s.basic.total_avg_ms)
+        Self::relative_score(config_stats, all_stats, |s| s.basic.total_avg_ms)
+    }
+
+    /// 95th percentile response time, falling back to the maximum when p95 was not calculated
+    fn p95_ms(stats: &ExtendedStatistics) -> f64 {
+        stats.percentiles.get("p95").copied().unwrap_or(stats.basic.total_max_ms)
+    }
+
+    /// Min-max normalized score of a lower-is-better metric (1.0 = best of all configurations)
+    fn relative_score<F>(config_stats: &ExtendedStatistics, all_stats: &HashMap<String, ExtendedStatistics>, metric: F) -> f64
+    where
+        F: Fn(&ExtendedStatistics) -> f64,
+    {
+        let value = metric(config_stats);
+        let min_value = all_stats.values()
+            .map(&metric)
             .fold(f64::INFINITY, f64::min);
-        let max_time = all_stats.values()
-            .map(|s| s.basic.total_avg_ms)
+        let max_value = all_stats.values()
+            .map(&metric)
             .fold(f64::NEG_INFINITY, f64::max);
 
-        if max_time <= min_time {
+        if max_value <= min_value {
             1.0
         } else {
-            1.0 - ((avg_time - min_time) / (max_time - min_time))
+            1.0 - ((value - min_value) / (max_value - min_value))
         }
     }
 
@@ -762,8 +1754,8 @@ impl StatisticsEngine {
 
         for (config_name, results) in &self.results {
             if results.len() < 3 {
-                trends.insert(config_name.clone(), TrendDirection::NoTrend);
-                temporal_correlations.insert(config_name.clone(), 0.0);
+                trends.insert(config_name.to_string(), TrendDirection::NoTrend);
+                temporal_correlations.insert(config_name.to_string(), 0.0);
                 continue;
             }
 
@@ -780,8 +1772,8 @@ impl StatisticsEngine {
             data_points.sort_by_key(|&(timestamp, _)| timestamp);
             
             if data_points.len() < 3 {
-                trends.insert(config_name.clone(), TrendDirection::NoTrend);
-                temporal_correlations.insert(config_name.clone(), 0.0);
+                trends.insert(config_name.to_string(), TrendDirection::NoTrend);
+                temporal_correlations.insert(config_name.to_string(), 0.0);
                 continue;
             }
 
@@ -790,11 +1782,11 @@ impl StatisticsEngine {
 
             // Calculate trend direction
             let trend_direction = self.calculate_trend_direction(&data_points);
-            trends.insert(config_name.clone(), trend_direction);
+            trends.insert(config_name.to_string(), trend_direction);
 
             // Calculate temporal correlation
             let correlation = self.calculate_temporal_correlation(&data_points);
-            temporal_correlations.insert(config_name.clone(), correlation);
+            temporal_correlations.insert(config_name.to_string(), correlation);
         }
 
         let time_period = if all_timestamps.is_empty() {
@@ -890,7 +1882,12 @@ impl StatisticsEngine {
     }
 
     /// Generate analysis summary and recommendations
-    fn generate_summary(&self, stats: &HashMap<String, ExtendedStatistics>, comparative: &ComparativeAnalysis) -> Result<AnalysisSummary> {
+    fn generate_summary(
+        &self,
+        stats: &HashMap<String, ExtendedStatistics>,
+        comparative: &ComparativeAnalysis,
+        url_breakdown: &[UrlBreakdown],
+    ) -> Result<AnalysisSummary> {
         let mut key_findings = Vec::new();
         let mut insights = Vec::new();
         let mut recommendations = Vec::new();
@@ -911,6 +1908,10 @@ impl StatisticsEngine {
             }
         }
 
+        for tie in &comparative.ties {
+            key_findings.push(self.describe_tie(tie));
+        }
+
         // Generate insights
         let total_configs = stats.len();
         let good_performance_configs = stats.values()
@@ -926,6 +1927,24 @@ impl StatisticsEngine {
                 recommended.config_name, recommended.score));
         }
 
+        // The fastest resolver frequently differs by target, so call that out per URL
+        let per_url_best: Vec<(&str, &str)> = url_breakdown.iter()
+            .filter_map(|b| b.best_config.as_deref().map(|best| (b.url.as_str(), best)))
+            .collect();
+        let winners_differ = per_url_best.windows(2).any(|w| w[0].1 != w[1].1);
+        if winners_differ {
+            insights.push("Fastest DNS configuration differs between target URLs".to_string());
+            for breakdown in url_breakdown {
+                if let Some(best) = &breakdown.best_config {
+                    let avg_ms = breakdown.config_stats[best].basic.total_avg_ms;
+                    recommendations.push(format!("For {}: use {} ({:.1}ms average)", breakdown.url, best, avg_ms));
+                }
+            }
+        }
+
+        // Quantify what switching resolvers would save
+        recommendations.extend(self.switch_recommendations(stats));
+
         // Check for performance issues
         let avg_success_rate: f64 = stats.values().map(|s| s.reliability.success_rate).sum::<f64>() / stats.len() as f64;
         if avg_success_rate < 95.0 {
@@ -942,8 +1961,65 @@ impl StatisticsEngine {
             warnings.push(format!("High response time variability detected in: {}", high_jitter_configs.join(", ")));
         }
 
+        warnings.extend(self.response_size_warnings(url_breakdown));
+
+        let mut probe_loss: Vec<(&String, f64)> = stats.iter()
+            .filter_map(|(name, s)| s.reliability.packet_loss_percentage.map(|loss| (name, loss)))
+            .filter(|(_, loss)| *loss >= PACKET_LOSS_WARNING_PERCENT)
+            .collect();
+        probe_loss.sort_by(|a, b| a.0.cmp(b.0));
+        for (config_name, loss) in probe_loss {
+            warnings.push(format!("Packet loss via {}: {:.1}% of UDP probe packets went unanswered", config_name, loss));
+        }
+
+        let mut throttled: Vec<(&String, &ThrottlingSummary)> = stats.iter()
+            .filter_map(|(name, s)| s.reliability.throttling.as_ref().map(|throttling| (name, throttling)))
+            .collect();
+        throttled.sort_by(|a, b| a.0.cmp(b.0));
+        for (config_name, throttling) in throttled {
+            warnings.push(format!(
+                "Throttled via {}: the server turned away {:.1}% of requests for coming too often; \
+                 these failures reflect the request rate, not the network",
+                config_name, throttling.rate
+            ));
+        }
+
+        // Retransmissions are packet loss, which shows up as latency spikes
+        let mut lossy: Vec<(&String, &SocketSummary)> = stats.iter()
+            .filter_map(|(name, s)| s.socket.as_ref().map(|socket| (name, socket)))
+            .filter(|(_, socket)| socket.affected_percentage() >= RETRANSMIT_WARNING_PERCENT)
+            .collect();
+        lossy.sort_by(|a, b| a.0.cmp(b.0));
+        for (config_name, socket) in lossy {
+            let mut warning = format!(
+                "Packet loss via {}: TCP retransmissions in {:.1}% of requests",
+                config_name, socket.affected_percentage()
+            );
+            if let Some(penalty) = socket.retransmit_penalty_ms() {
+                warning.push_str(&format!(", which were {:.1}ms slower on average", penalty));
+            }
+            warnings.push(warning);
+        }
+
+        for comparison in self.public_baseline.iter().filter(|comparison| comparison.is_abnormal()) {
+            warnings.push(format!(
+                "Slow path to {}: connects take {:.1}x the median of public probes ({}), so the delay is likely on this network",
+                comparison.target, comparison.ratio().unwrap_or_default(), comparison.source
+            ));
+        }
+
+        for bound in self.physical_bounds.iter().filter(|bound| bound.is_indirect()) {
+            warnings.push(format!(
+                "Indirect route to {} ({}, {}): connects take {:.1}x the {:.1}ms light needs for {:.0}km, so packets likely detour through another region",
+                bound.target, bound.ip, bound.location, bound.ratio().unwrap_or_default(), bound.min_rtt_ms, bound.distance_km
+            ));
+        }
+
         Ok(AnalysisSummary {
-            recommended_config: comparative.performance_rankings.first().map(|r| r.config_name.clone()),
+            // No recommendation while the leaders are not separated from their runners-up
+            recommended_config: comparative.ties.is_empty()
+                .then(|| comparative.performance_rankings.first().map(|r| r.config_name.clone()))
+                .flatten(),
             key_findings,
             insights,
             recommendations,
@@ -951,10 +2027,119 @@ impl StatisticsEngine {
         })
     }
 
+    /// Flag routes (a URL under a configuration) that received uncompressed or
+    /// unexpectedly large bodies
+    ///
+    /// A body is unexpectedly large when it is several times the size the
+    /// smallest configuration received from the same URL, which usually means
+    /// the route reached a different server or skipped compression.
+    fn response_size_warnings(&self, url_breakdown: &[UrlBreakdown]) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for breakdown in url_breakdown {
+            let mut sizes: Vec<(&String, &ResponseSizeSummary)> = breakdown.config_stats.iter()
+                .filter_map(|(name, stats)| stats.response_size.as_ref().map(|size| (name, size)))
+                .collect();
+            if sizes.is_empty() {
+                continue;
+            }
+            sizes.sort_by(|a, b| a.0.cmp(b.0));
+
+            let uncompressed: Vec<&(&String, &ResponseSizeSummary)> = sizes.iter().filter(|(_, size)| size.is_uncompressed()).collect();
+            if !uncompressed.is_empty() && uncompressed.len() == sizes.len() {
+                let avg_bytes = uncompressed.iter().map(|(_, size)| size.avg_bytes).sum::<f64>() / uncompressed.len() as f64;
+                warnings.push(format!(
+                    "Uncompressed responses from {} ({} average); enable gzip or brotli on the server",
+                    breakdown.url, format_bytes(avg_bytes)
+                ));
+            } else {
+                for (config_name, size) in uncompressed {
+                    warnings.push(format!(
+                        "Uncompressed responses from {} via {} ({} average), while other configurations received compressed bodies",
+                        breakdown.url, config_name, format_bytes(size.avg_bytes)
+                    ));
+                }
+            }
+
+            let smallest = sizes.iter().map(|(_, size)| size.avg_bytes).fold(f64::INFINITY, f64::min);
+            for (config_name, size) in &sizes {
+                if smallest > 0.0
+                    && size.avg_bytes >= smallest * LARGE_BODY_FACTOR
+                    && size.avg_bytes - smallest >= UNCOMPRESSED_BODY_BYTES
+                {
+                    warnings.push(format!(
+                        "Unexpectedly large responses from {} via {}: {} average, {:.1}x the smallest configuration ({})",
+                        breakdown.url, config_name, format_bytes(size.avg_bytes),
+                        size.avg_bytes / smallest, format_bytes(smallest)
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Describe a withheld winner declaration and what it would take to resolve it
+    fn describe_tie(&self, tie: &WinnerTie) -> String {
+        let (label, unit) = match tie.metric.as_str() {
+            "speed" => ("fastest", "ms"),
+            "reliability" => ("most reliable", " percentage points"),
+            other => (other, ""),
+        };
+        let resolution = match tie.margin_needed {
+            Some(margin) => format!(
+                "needs {:.1}{} more separation at {:.0}% confidence",
+                margin, unit, self.config.confidence_level * 100.0
+            ),
+            None => format!("fewer than {} samples to compare", self.config.min_samples),
+        };
+
+        format!(
+            "No clear {} configuration: {} and {} are tied ({:.1}{} apart; {})",
+            label, tie.leader, tie.runner_up, tie.difference, unit, resolution
+        )
+    }
+
+    /// Recommend the configuration with the lowest p50 by how much it saves
+    /// over the system resolver, when that was tested, or the slowest configuration
+    ///
+    /// Only the p50 difference is attributed: the phase durations behind each
+    /// total are estimated, not timed, so they cannot say where the time went.
+    fn switch_recommendations(&self, stats: &HashMap<String, ExtendedStatistics>) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let p50_ms = |s: &ExtendedStatistics| s.percentiles.get("p50").copied().unwrap_or(s.basic.total_avg_ms);
+
+        let fastest = stats.iter()
+            .min_by(|a, b| p50_ms(a.1).partial_cmp(&p50_ms(b.1)).unwrap_or(std::cmp::Ordering::Equal));
+        let Some((fastest_name, fastest_stats)) = fastest else {
+            return recommendations;
+        };
+
+        let baseline = self.results.iter()
+            .find(|(name, results)| {
+                stats.contains_key(name.as_str()) && results.iter().any(|r| r.dns_config == DnsConfig::System)
+            })
+            .and_then(|(name, _)| stats.get_key_value(name.as_str()))
+            .or_else(|| stats.iter()
+                .max_by(|a, b| p50_ms(a.1).partial_cmp(&p50_ms(b.1)).unwrap_or(std::cmp::Ordering::Equal)));
+
+        if let Some((baseline_name, baseline_stats)) = baseline {
+            let savings_ms = p50_ms(baseline_stats) - p50_ms(fastest_stats);
+            if baseline_name != fastest_name && savings_ms >= 1.0 {
+                recommendations.push(format!(
+                    "Switching from {} to {} saves ~{:.0}ms p50",
+                    baseline_name, fastest_name, savings_ms
+                ));
+            }
+        }
+
+        recommendations
+    }
+
     /// Export statistical analysis to JSON
     pub fn export_json(&self, analysis: &StatisticalAnalysis) -> Result<String> {
         serde_json::to_string_pretty(analysis)
-            .map_err(|e| AppError::io(format!("Failed to export analysis to JSON: {}", e)))
+            .map_err(|e| AppError::io(format!("Failed to export analysis to JSON: {}", e)).with_source(e))
     }
 
     /// Clear all collected results
@@ -976,7 +2161,7 @@ impl StatisticsEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::DnsConfig;
+    use crate::{models::{InformationalTiming, ResponseBody, ServerTiming, SocketStats, Throttling, UploadTiming}, test_support::{result_with_totals, result_with_totals_via, success}, types::DnsConfig};
     use std::time::Duration;
 
     #[test]
@@ -1012,13 +2197,473 @@ mod tests {
     }
 
     #[test]
-    fn test_percentile_calculation() {
+    fn test_url_breakdown_per_target_winner() {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_totals("System DNS", "https://a.example", &[100, 101, 99, 100, 102]),
+            result_with_totals("DoH", "https://a.example", &[300, 301, 299, 300, 302]),
+            result_with_totals("System DNS", "https://b.example", &[400, 401, 399, 400, 402]),
+            result_with_totals("DoH", "https://b.example", &[150; 5]),
+            result_with_totals("System DNS", "https://c.example", &[100, 300]),
+            result_with_totals("DoH", "https://c.example", &[110, 310]),
+        ]);
+
+        let analysis = engine.analyze().unwrap();
+
+        assert_eq!(analysis.url_breakdown.len(), 3);
+        let a = &analysis.url_breakdown[0];
+        assert_eq!(a.url, "https://a.example");
+        assert_eq!(a.best_config.as_deref(), Some("System DNS"));
+        assert_eq!(a.config_stats.len(), 2);
+        assert_eq!(a.ranked_configs()[0].0, "System DNS");
+
+        let b = &analysis.url_breakdown[1];
+        assert_eq!(b.best_config.as_deref(), Some("DoH"));
+        assert_eq!(b.config_stats["DoH"].basic.total_avg_ms, 150.0);
+
+        // Too close to call for the third target
+        assert_eq!(analysis.url_breakdown[2].best_config, None);
+
+        assert!(analysis.summary.recommendations.iter().any(|r| r.contains("https://b.example") && r.contains("DoH")));
+    }
+
+    #[test]
+    fn test_timeouts_as_censored_samples() {
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://example.com".to_string());
+        for total_ms in [100, 110, 120, 130, 140, 150, 160, 170] {
+            result.add_measurement(TimingMetrics::success(
+                Duration::from_millis(5),
+                Duration::from_millis(10),
+                None,
+                Duration::from_millis(20),
+                Duration::from_millis(total_ms),
+                200,
+            ));
+        }
+        for _ in 0..2 {
+            result.add_measurement(TimingMetrics::timeout(Duration::from_secs(2))
+                .with_phase_reached(RequestPhase::Connecting));
+        }
+        result.calculate_statistics();
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result.clone());
+        let plain = engine.analyze().unwrap().basic_stats.remove("System DNS").unwrap();
+        let timeouts = plain.timeouts.as_ref().unwrap();
+        assert_eq!(timeouts.count, 2);
+        assert_eq!(timeouts.rate, 20.0);
+        assert_eq!(timeouts.censored_at_ms, 2000.0);
+        assert_eq!(timeouts.phase, Some(RequestPhase::Connecting));
+        assert!(plain.percentiles["p95"] < 200.0);
+        assert!(!timeouts.is_lower_bound(plain.percentiles["p95"]));
+
+        let mut engine = StatisticsEngine::new(StatisticsConfig { censor_timeouts: true, ..StatisticsConfig::default() });
+        engine.add_result(result);
+        let censored = engine.analyze().unwrap().basic_stats.remove("System DNS").unwrap();
+        let timeouts = censored.timeouts.as_ref().unwrap();
+        assert_eq!(censored.percentiles["p95"], 2000.0);
+        assert!(timeouts.is_lower_bound(censored.percentiles["p95"]));
+        assert!(!timeouts.is_lower_bound(censored.percentiles["p50"]));
+        // Censoring only affects percentiles, the mean stays over measured samples
+        assert_eq!(censored.basic.total_avg_ms, plain.basic.total_avg_ms);
+    }
+
+    fn result_with_bodies(config_name: &str, url: &str, bytes: u64, encoding: Option<&str>) -> TestResult {
+        let mut result = TestResult::new(config_name.to_string(), DnsConfig::System, url.to_string());
+        for _ in 0..4 {
+            result.add_measurement(success(100)
+                .with_body(ResponseBody {
+                    bytes,
+                    encoding: encoding.map(str::to_string),
+                    decoded_bytes: encoding.map(|_| bytes * 4),
+                }));
+        }
+        result.calculate_statistics();
+        result
+    }
+
+    #[test]
+    fn test_response_size_summary_and_warnings() {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_bodies("System DNS", "https://a.example", 2048, Some("gzip")),
+            result_with_bodies("DoH", "https://a.example", 8192, None),
+            result_with_bodies("System DNS", "https://b.example", 4096, None),
+            result_with_bodies("DoH", "https://b.example", 4096, None),
+            result_with_bodies("System DNS", "https://c.example", 200, None),
+        ]);
+        let analysis = engine.analyze().unwrap();
+
+        let size = analysis.url_breakdown[0].config_stats["System DNS"].response_size.as_ref().unwrap();
+        assert_eq!((size.samples, size.avg_bytes, size.p95_bytes), (4, 2048.0, 2048.0));
+        assert_eq!(size.encodings, ["gzip"]);
+        assert_eq!(size.compression_ratio, Some(4.0));
+        assert!(!size.is_uncompressed());
+
+        let warnings = &analysis.summary.warnings;
+        let about = |url: &str| warnings.iter().filter(|w| w.contains(url)).cloned().collect::<Vec<_>>();
+        assert_eq!(about("https://a.example"), [
+            "Uncompressed responses from https://a.example via DoH (8.0 KiB average), while other configurations received compressed bodies",
+            "Unexpectedly large responses from https://a.example via DoH: 8.0 KiB average, 4.0x the smallest configuration (2.0 KiB)",
+        ]);
+        assert_eq!(about("https://b.example"), [
+            "Uncompressed responses from https://b.example (4.0 KiB average); enable gzip or brotli on the server",
+        ]);
+        // Small bodies are not worth compressing
+        assert!(about("https://c.example").is_empty());
+    }
+
+    #[test]
+    fn test_socket_summary_and_warning() {
+        let mut lossy = result_with_totals("DoH", "https://example.com", &[100, 100, 100, 400]);
+        let mut clean = result_with_totals("System DNS", "https://example.com", &[50, 50]);
+        for (measurement, retransmits) in lossy.individual_results.iter_mut().zip([0, 0, 0, 2]) {
+            measurement.socket = Some(SocketStats { rtt_ms: 20.0, rtt_var_ms: 5.0, cwnd: 10, retransmits, total_retransmits: retransmits });
+        }
+        for measurement in &mut clean.individual_results {
+            measurement.socket = Some(SocketStats { rtt_ms: 10.0, rtt_var_ms: 1.0, cwnd: 10, retransmits: 0, total_retransmits: 0 });
+        }
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![lossy, clean, result_with_totals("Custom DNS", "https://example.com", &[80])]);
+        let analysis = engine.analyze().unwrap();
+
+        let socket = analysis.basic_stats["DoH"].socket.as_ref().unwrap();
+        assert_eq!((socket.samples, socket.retransmits, socket.affected_requests), (4, 2, 1));
+        assert_eq!(socket.avg_rtt_ms, 20.0);
+        assert_eq!(socket.affected_percentage(), 25.0);
+        assert_eq!(socket.retransmit_penalty_ms(), Some(300.0));
+        assert_eq!(analysis.basic_stats["System DNS"].socket.as_ref().unwrap().retransmit_penalty_ms(), None);
+        assert!(analysis.basic_stats["Custom DNS"].socket.is_none());
+
+        let warnings: Vec<_> = analysis.summary.warnings.iter().filter(|w| w.starts_with("Packet loss")).collect();
+        assert_eq!(warnings, ["Packet loss via DoH: TCP retransmissions in 25.0% of requests, which were 300.0ms slower on average"]);
+    }
+
+    #[test]
+    fn test_upload_summary() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[100, 120]);
+        for (measurement, send) in result.individual_results.iter_mut().zip([60, 80]) {
+            measurement.upload = Some(UploadTiming { bytes: 1_000_000, send: Duration::from_millis(send), ack: Duration::from_millis(30) });
+        }
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![result, result_with_totals("DoH", "https://example.com", &[80])]);
+        let analysis = engine.analyze().unwrap();
+
+        let upload = analysis.basic_stats["System DNS"].upload.as_ref().unwrap();
+        assert_eq!((upload.samples, upload.bytes), (2, 1_000_000));
+        assert!((upload.send_avg_ms - 70.0).abs() < 1e-9);
+        assert!((upload.ack_avg_ms - 30.0).abs() < 1e-9);
+        // 8 Mbit in 70ms
+        assert!((upload.throughput_mbps().unwrap() - 114.285).abs() < 0.01);
+        assert!(analysis.basic_stats["DoH"].upload.is_none());
+    }
+
+    #[test]
+    fn test_informational_summary() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[100, 100, 100]);
+        let timings = [(vec![103], Some(20)), (vec![100, 103], Some(40)), (vec![], None)];
+        for (measurement, (statuses, first)) in result.individual_results.iter_mut().zip(timings) {
+            measurement.informational = Some(InformationalTiming {
+                statuses,
+                first: first.map(Duration::from_millis),
+                response: Duration::from_millis(90),
+            });
+        }
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![result]);
+        let analysis = engine.analyze().unwrap();
+
+        let informational = analysis.basic_stats["System DNS"].informational.as_ref().unwrap();
+        assert_eq!((informational.samples, informational.informed, informational.early_hints), (3, 2, 2));
+        assert_eq!(informational.first_avg_ms, Some(30.0));
+        assert_eq!(informational.lead_avg_ms, Some(60.0));
+        assert!((informational.response_avg_ms - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_server_timing_summary() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[100, 200, 300]);
+        let headers = ["db;dur=20, app;dur=50", r#"db;dur=40;desc="Database", app;dur=90, cache"#, ""];
+        for (measurement, header) in result.individual_results.iter_mut().zip(headers) {
+            measurement.server_timing = ServerTiming::parse(header);
+        }
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![result]);
+        let analysis = engine.analyze().unwrap();
+
+        let summary = analysis.basic_stats["System DNS"].server_timing.as_ref().unwrap();
+        assert_eq!(summary.samples, 2);
+        assert!((summary.total_avg_ms - 150.0).abs() < 1e-9);
+        // The longest phase of each response: 50 and 90
+        assert!((summary.server_avg_ms - 70.0).abs() < 1e-9);
+        assert!((summary.network_avg_ms() - 80.0).abs() < 1e-9);
+        let phases: Vec<_> = summary.phases.iter().map(|phase| (phase.name.as_str(), phase.samples, phase.avg_ms)).collect();
+        assert_eq!(phases, [("db", 2, 30.0), ("app", 2, 70.0)]);
+        assert_eq!(summary.phases[0].description.as_deref(), Some("Database"));
+    }
+
+    #[test]
+    fn test_throttling_summary() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[100, 100]);
+        let now = Utc::now();
+        for (status, retry_after) in [(429, Some("30")), (429, None), (503, Some("120")), (503, None)] {
+            let mut metrics = TimingMetrics::failed(format!("HTTP {}", status))
+                .with_throttling(Throttling::from_response(status, retry_after, now));
+            metrics.http_status = status;
+            result.add_measurement(metrics);
+        }
+        result.calculate_statistics();
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![result]);
+        let analysis = engine.analyze().unwrap();
+
+        // The 503 without Retry-After is an outage and not counted
+        let throttling = analysis.basic_stats["System DNS"].reliability.throttling.as_ref().unwrap();
+        assert_eq!((throttling.count, throttling.too_many_requests, throttling.unavailable), (3, 2, 1));
+        assert_eq!(throttling.rate, 50.0);
+        assert_eq!(throttling.retry_after_max_secs, Some(120));
+        assert!(analysis.summary.warnings.iter().any(|warning| warning.starts_with("Throttled via System DNS")));
+    }
+
+    #[test]
+    fn test_packet_loss_feeds_reliability_and_scoring() {
+        let report = |config_name: &str, target: &str, received: u32| LossReport {
+            config_name: config_name.to_string(),
+            target: target.to_string(),
+            sent: 50,
+            received,
+            reordered: 0,
+            duplicates: 0,
+            avg_rtt_ms: (received > 0).then_some(20.0),
+        };
+        let mut engine = StatisticsEngine::new(StatisticsConfig {
+            scoring_weights: "loss=1".parse().unwrap(),
+            ..Default::default()
+        });
+        engine.add_results(vec![result_with_totals("DoH", "https://example.com", &[100, 100]), result_with_totals("System DNS", "https://example.com", &[100, 100])]);
+        engine.add_packet_loss(vec![
+            report("DoH", "https://example.com (93.184.216.34)", 45),
+            // A resolver that never answers the probe says nothing about loss
+            report("DoH", "resolver 1.1.1.1", 0),
+            report("System DNS", "https://example.com (93.184.216.34)", 50),
+        ]);
+        let analysis = engine.analyze().unwrap();
+
+        assert_eq!(analysis.basic_stats["DoH"].reliability.packet_loss_percentage, Some(10.0));
+        assert_eq!(analysis.basic_stats["System DNS"].reliability.packet_loss_percentage, Some(0.0));
+        let rankings = &analysis.comparative_analysis.performance_rankings;
+        assert_eq!(rankings[0].config_name, "System DNS");
+        assert!((rankings[1].score - 0.9).abs() < 1e-9);
+
+        let warnings: Vec<_> = analysis.summary.warnings.iter().filter(|w| w.starts_with("Packet loss")).collect();
+        assert_eq!(warnings, ["Packet loss via DoH: 10.0% of UDP probe packets went unanswered"]);
+    }
+
+    #[test]
+    fn test_public_baseline_warning() {
+        let comparison = |target: &str, local_median_ms: f64| BaselineComparison {
+            target: target.to_string(),
+            source: "RIPE Atlas #1001".to_string(),
+            region: None,
+            probes: 30,
+            public_median_ms: 20.0,
+            local_median_ms: Some(local_median_ms),
+        };
+        let mut engine = StatisticsEngine::new(StatisticsConfig::default());
+        engine.add_results(vec![result_with_totals("System DNS", "https://example.com", &[100, 100])]);
+        engine.add_public_baseline(vec![comparison("a.example", 70.0), comparison("b.example", 25.0)]);
+        let analysis = engine.analyze().unwrap();
+
+        let warnings: Vec<_> = analysis.summary.warnings.iter().filter(|w| w.starts_with("Slow path")).collect();
+        assert_eq!(warnings, [
+            "Slow path to a.example: connects take 3.5x the median of public probes (RIPE Atlas #1001), so the delay is likely on this network",
+        ]);
+    }
+
+    #[test]
+    fn test_indirect_route_warning() {
+        let bound = |ip: &str, distance_km: f64, connect_ms: f64| PhysicalBound {
+            target: "example.com".to_string(),
+            ip: ip.parse().unwrap(),
+            location: "Frankfurt am Main, DE".to_string(),
+            asn: None,
+            distance_km,
+            min_rtt_ms: physics::min_rtt_ms(distance_km),
+            connect_ms: Some(connect_ms),
+        };
+        let mut engine = StatisticsEngine::new(StatisticsConfig::default());
+        engine.add_results(vec![result_with_totals("System DNS", "https://example.com", &[100, 100])]);
+        engine.add_physical_bounds(vec![
+            // 5ms minimum: 150ms is a detour
+            bound("192.0.2.1", 500.0, 150.0),
+            // Direct enough
+            bound("192.0.2.2", 500.0, 9.0),
+            // Next door, where the access network dominates
+            bound("192.0.2.3", 20.0, 15.0),
+        ]);
+        let analysis = engine.analyze().unwrap();
+
+        let warnings: Vec<_> = analysis.summary.warnings.iter().filter(|w| w.starts_with("Indirect route")).collect();
+        assert_eq!(warnings, [
+            "Indirect route to example.com (192.0.2.1, Frankfurt am Main, DE): connects take 30.0x the 5.0ms light needs for 500km, so packets likely detour through another region",
+        ]);
+    }
+
+    #[test]
+    fn test_switch_recommendations() {
+        let doh = DnsConfig::DoH { url: "https://cloudflare-dns.com/dns-query".to_string() };
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_totals("System DNS", "https://example.com", &[200; 5]),
+            result_with_totals_via("DoH (cloudflare)", doh, "https://example.com", &[130; 5]),
+        ]);
+
+        let summary = engine.analyze().unwrap().summary;
+        assert!(summary.recommendations.iter().any(|r| r == "Switching from System DNS to DoH (cloudflare) saves ~70ms p50"));
+        // The estimated phases are not presented as a latency budget
+        let budget = summary.recommendations.iter().chain(&summary.insights).find(|line| line.contains("of total latency") || line.starts_with("Latency budget"));
+        assert_eq!(budget, None);
+    }
+
+    #[test]
+    fn test_cold_start_split() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[100, 110, 90]);
+        result.individual_results[0] = result.individual_results[0].clone().with_first_use(true);
+        result.individual_results[0].total_duration = Duration::from_millis(400);
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result);
+        let analysis = engine.analyze().unwrap();
+        let stats = &analysis.basic_stats["System DNS"];
+
+        let split = stats.cold_start.as_ref().unwrap();
+        assert_eq!(split.cold.sample_count, 1);
+        assert_eq!(split.warm.sample_count, 2);
+        assert_eq!(split.warm.total_avg_ms, 100.0);
+        assert_eq!(split.penalty_ms(), 300.0);
+        assert_eq!(stats.basic.sample_count, 3);
+
+        // Without first-use flags there is nothing to split
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result_with_totals("System DNS", "https://example.com", &[100, 110, 90]));
+        assert!(engine.analyze().unwrap().basic_stats["System DNS"].cold_start.is_none());
+    }
+
+    #[test]
+    fn test_cache_split() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[40, 60, 300, 90]);
+        for (measurement, status) in result.individual_results.iter_mut().zip([CacheStatus::Hit, CacheStatus::Hit, CacheStatus::Miss]) {
+            measurement.cache = Some(status);
+        }
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result);
+        let analysis = engine.analyze().unwrap();
+        let split = analysis.basic_stats["System DNS"].cache_split.as_ref().unwrap();
+        assert_eq!(split.hit.as_ref().unwrap().total_avg_ms, 50.0);
+        assert_eq!(split.miss.as_ref().unwrap().sample_count, 1);
+        assert_eq!(split.miss_penalty_ms(), Some(250.0));
+        // The sample without a cache status is in neither population
+        assert!((split.hit_rate() - 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tls_resumption_split() {
+        let mut result = result_with_totals("System DNS", "https://example.com", &[120, 40, 60, 70]);
+        for (measurement, resumed) in result.individual_results.iter_mut().zip([false, true, true]) {
+            measurement.tls_resumed = Some(resumed);
+        }
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result);
+        let analysis = engine.analyze().unwrap();
+        let split = analysis.basic_stats["System DNS"].tls_resumption.as_ref().unwrap();
+        assert_eq!(split.resumed.as_ref().unwrap().total_avg_ms, 50.0);
+        // The request on a reused connection made no handshake and is in neither population
+        assert_eq!(split.full.as_ref().unwrap().sample_count, 1);
+        assert_eq!(split.resumption_saving_ms(), Some(70.0));
+    }
+
+    #[test]
+    fn test_overlapping_intervals_report_tie() {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_totals("A", "https://example.com", &[100, 300, 120, 280, 150]),
+            result_with_totals("B", "https://example.com", &[110, 310, 130, 290, 160]),
+        ]);
+
+        let analysis = engine.analyze().unwrap();
+        let comparative = &analysis.comparative_analysis;
+
+        assert_eq!(comparative.fastest_config, None);
+        let tie = comparative.ties.iter().find(|t| t.metric == "speed").unwrap();
+        assert_eq!(tie.leader, "A");
+        assert_eq!(tie.runner_up, "B");
+        assert!((tie.difference - 10.0).abs() < 1e-9);
+        assert!(tie.margin_needed.unwrap() > 0.0);
+        assert!(analysis.summary.key_findings.iter().any(|f| f.starts_with("No clear fastest configuration: A and B are tied (10.0ms apart; needs")));
+
+        // Both succeeded every time: neither is more reliable, nor are they tied
+        assert_eq!(comparative.most_reliable_config, None);
+        assert!(comparative.ties.iter().all(|t| t.metric != "reliability"));
+
+        // Nothing is recommended while the fastest is undecided
+        assert_eq!(analysis.summary.recommended_config, None);
+    }
+
+    #[test]
+    fn test_separated_intervals_declare_winner() {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_totals("Fast", "https://example.com", &[100, 101, 99, 100, 102]),
+            result_with_totals("Slow", "https://example.com", &[200, 201, 199, 200, 202]),
+        ]);
+
+        let analysis = engine.analyze().unwrap();
+        assert_eq!(analysis.comparative_analysis.fastest_config.as_deref(), Some("Fast"));
+        assert!(analysis.comparative_analysis.ties.iter().all(|t| t.metric != "speed"));
+        assert!(analysis.summary.key_findings.iter().any(|f| f.starts_with("Fastest configuration: Fast")));
+        assert_eq!(analysis.summary.recommended_config.as_deref(), Some("Fast"));
+    }
+
+    #[test]
+    fn test_small_samples_without_significance_report_tie() {
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_results(vec![
+            result_with_totals("A", "https://example.com", &[100, 140]),
+            result_with_totals("B", "https://example.com", &[110, 150]),
+        ]);
+
+        let analysis = engine.analyze().unwrap();
+        let tie = analysis.comparative_analysis.ties.iter().find(|t| t.metric == "speed").unwrap();
+        assert_eq!(tie.margin_needed, None);
+        assert!(analysis.summary.key_findings.iter().any(|f| f.contains("fewer than 5 samples")));
+    }
+
+    #[test]
+    fn test_success_rate_interval() {
         let engine = StatisticsEngine::with_defaults();
+
+        let (lower, upper) = engine.calculate_success_rate_interval(5, 5);
+        assert!(lower > 50.0 && lower < 100.0);
+        assert_eq!(upper, 100.0);
+
+        let (lower, upper) = engine.calculate_success_rate_interval(50, 100);
+        assert!(lower < 50.0 && upper > 50.0);
+        assert!(((lower + upper) / 2.0 - 50.0).abs() < 1e-9);
+
+        assert_eq!(engine.calculate_success_rate_interval(0, 0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_percentile_calculation() {
         let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
         
-        assert_eq!(engine.calculate_percentile(&values, 50.0), 5.5);
-        assert_eq!(engine.calculate_percentile(&values, 90.0), 9.1);
-        assert_eq!(engine.calculate_percentile(&values, 100.0), 10.0);
+        assert_eq!(StatisticsEngine::calculate_percentile(&values, 50.0), 5.5);
+        assert_eq!(StatisticsEngine::calculate_percentile(&values, 90.0), 9.1);
+        assert_eq!(StatisticsEngine::calculate_percentile(&values, 100.0), 10.0);
+        assert_eq!(StatisticsEngine::calculate_percentile(&[], 50.0), 0.0);
     }
 
     #[test]
@@ -1070,8 +2715,11 @@ mod tests {
             200,
         );
         
-        let timings = vec![&timing1, &timing2, &timing3];
-        let distribution = engine.calculate_performance_distribution(&timings);
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://example.com".to_string());
+        for timing in [timing1, timing2, timing3] {
+            result.add_measurement(timing);
+        }
+        let distribution = engine.calculate_extended_statistics([&result]).unwrap().performance_distribution;
         
         assert!((distribution.good_percentage - 33.33).abs() < 0.1);
         assert!((distribution.moderate_percentage - 33.33).abs() < 0.1);
@@ -1120,7 +2768,7 @@ mod tests {
         let result = engine.analyze();
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::Validation(_)));
+        assert!(matches!(result.unwrap_err(), AppError::Validation(..)));
     }
 
     #[test]
@@ -1162,7 +2810,18 @@ mod tests {
                 consistency_score: 0.1,
                 jitter_ms: 10.0,
                 uptime_percentage: None,
+                packet_loss_percentage: None,
+                throttling: None,
             },
+            cold_start: None,
+            cache_split: None,
+            tls_resumption: None,
+            timeouts: None,
+            response_size: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            server_timing: None,
         };
         
         stats.insert("Good Config".to_string(), good_stats);
@@ -1175,6 +2834,82 @@ mod tests {
         assert_eq!(rankings[0].rank, 1);
         assert!(rankings[0].score > 0.8); // Should have high score
     }
+
+    fn ranking_stats(avg_ms: f64, p95_ms: f64, jitter_ms: f64) -> ExtendedStatistics {
+        ExtendedStatistics {
+            basic: Statistics {
+                total_avg_ms: avg_ms,
+                total_std_dev_ms: jitter_ms,
+                success_rate: 100.0,
+                sample_count: 10,
+                dns_avg_ms: 10.0,
+                tcp_avg_ms: 20.0,
+                first_byte_avg_ms: 50.0,
+                total_min_ms: avg_ms,
+                total_max_ms: p95_ms,
+            },
+            percentiles: HashMap::from([("p95".to_string(), p95_ms)]),
+            confidence_intervals: ConfidenceIntervals {
+                level: 0.95,
+                avg_response_time: (avg_ms, avg_ms),
+                success_rate: (100.0, 100.0),
+                dns_resolution_time: (10.0, 10.0),
+            },
+            outlier_analysis: OutlierAnalysis {
+                outlier_count: 0,
+                outlier_percentage: 0.0,
+                detection_method: "IQR".to_string(),
+                threshold_values: HashMap::new(),
+            },
+            performance_distribution: PerformanceDistribution {
+                good_percentage: 100.0,
+                moderate_percentage: 0.0,
+                poor_percentage: 0.0,
+            },
+            reliability: ReliabilityMetrics {
+                success_rate: 100.0,
+                consistency_score: 0.1,
+                jitter_ms,
+                uptime_percentage: None,
+                packet_loss_percentage: None,
+                throttling: None,
+            },
+            cold_start: None,
+            cache_split: None,
+            tls_resumption: None,
+            timeouts: None,
+            response_size: None,
+            socket: None,
+            upload: None,
+            informational: None,
+            server_timing: None,
+        }
+    }
+
+    #[test]
+    fn test_configuration_ranking_custom_weights() {
+        // "Fast" wins on average but has a long tail and high jitter
+        let stats = HashMap::from([
+            ("Fast".to_string(), ranking_stats(100.0, 400.0, 80.0)),
+            ("Steady".to_string(), ranking_stats(120.0, 130.0, 5.0)),
+        ]);
+
+        let engine = StatisticsEngine::with_defaults();
+        let rankings = engine.calculate_performance_rankings(&stats);
+        assert_eq!(rankings[0].config_name, "Fast");
+        assert_eq!(rankings[0].metric_scores["p95"], 0.0);
+        assert_eq!(rankings[1].metric_scores["jitter"], 1.0);
+
+        let config = StatisticsConfig {
+            scoring_weights: "speed=0.3,p95=0.5,jitter=0.2".parse().unwrap(),
+            ..StatisticsConfig::default()
+        };
+        let engine = StatisticsEngine::new(config);
+        let rankings = engine.calculate_performance_rankings(&stats);
+        assert_eq!(rankings[0].config_name, "Steady");
+        assert!((rankings[0].score - 0.7).abs() < 1e-9);
+        assert!((rankings[1].score - 0.3).abs() < 1e-9);
+    }
 }
 
 // Additional comprehensive tests in separate module
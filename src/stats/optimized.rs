@@ -4,6 +4,11 @@
 //! that minimize memory allocations and reduce computational complexity.
 
 use crate::models::{TimingMetrics, Statistics};
+use crate::utils::duration::format_duration;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
 
 /// Optimized statistics calculator that minimizes memory allocations
 pub struct OptimizedStatisticsCalculator {
@@ -223,6 +228,7 @@ impl Default for OptimizedStatisticsCalculator {
 }
 
 /// Rolling statistics calculator for streaming data
+#[derive(Debug, Clone, PartialEq)]
 pub struct RollingStats {
     /// Sum of values in current window
     pub total_sum: f64,
@@ -314,6 +320,18 @@ impl RollingStats {
         }
     }
     
+    /// Add the values of another tracker, as if they had been added here
+    pub fn merge(&mut self, other: &RollingStats) {
+        self.total_sum += other.total_sum;
+        self.dns_sum += other.dns_sum;
+        self.tcp_sum += other.tcp_sum;
+        self.first_byte_sum += other.first_byte_sum;
+        self.count += other.count;
+        self.min_value = self.min_value.min(other.min_value);
+        self.max_value = self.max_value.max(other.max_value);
+        self.sum_squared += other.sum_squared;
+    }
+    
     /// Reset all values
     pub fn reset(&mut self) {
         *self = Self::new();
@@ -326,6 +344,149 @@ impl Default for RollingStats {
     }
 }
 
+/// Windows kept by [`MultiWindowStats::new`]: the last minute, 15 minutes, hour and day
+pub const DEFAULT_ROLLING_WINDOWS: [Duration; 4] = [
+    Duration::from_secs(60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(24 * 60 * 60),
+];
+
+/// Buckets each window is divided into; a window's edge is accurate to one bucket
+const BUCKETS_PER_WINDOW: i64 = 60;
+
+/// Measurements of one time bucket
+#[derive(Debug, Clone, Default)]
+struct WindowBucket {
+    stats: RollingStats,
+    failures: usize,
+}
+
+/// One window, kept as the buckets that fall into it
+#[derive(Debug, Clone)]
+struct RollingWindow {
+    span: Duration,
+    /// Bucket width in milliseconds
+    width_ms: i64,
+    /// Buckets by index, the bucket's start divided by its width
+    buckets: BTreeMap<i64, WindowBucket>,
+}
+
+impl RollingWindow {
+    fn new(span: Duration) -> Self {
+        let width_ms = (span.as_millis() as i64 / BUCKETS_PER_WINDOW).max(1);
+        Self { span, width_ms, buckets: BTreeMap::new() }
+    }
+
+    fn bucket_index(&self, at: DateTime<Utc>) -> i64 {
+        at.timestamp_millis().div_euclid(self.width_ms)
+    }
+
+    /// First bucket still inside the window when `now` falls in bucket `latest`
+    fn oldest_index(latest: i64) -> i64 {
+        latest - BUCKETS_PER_WINDOW + 1
+    }
+
+    fn record(&mut self, measurement: &TimingMetrics) {
+        let index = self.bucket_index(measurement.timestamp);
+        // Drop buckets the newest measurement has left behind, so memory stays bounded
+        let newest = self.buckets.keys().next_back().copied().unwrap_or(index).max(index);
+        if index < Self::oldest_index(newest) {
+            return;
+        }
+        self.buckets = self.buckets.split_off(&Self::oldest_index(newest));
+
+        let bucket = self.buckets.entry(index).or_default();
+        if measurement.is_successful() {
+            bucket.stats.add_value(measurement.total_ms());
+            bucket.stats.dns_sum += measurement.dns_ms();
+            bucket.stats.tcp_sum += measurement.tcp_ms();
+            bucket.stats.first_byte_sum += measurement.first_byte_ms();
+        } else {
+            bucket.failures += 1;
+        }
+    }
+
+    fn snapshot(&self, now: DateTime<Utc>) -> WindowSnapshot {
+        let latest = self.bucket_index(now);
+        let mut total = WindowBucket::default();
+        for bucket in self.buckets.range(Self::oldest_index(latest)..=latest).map(|(_, bucket)| bucket) {
+            total.stats.merge(&bucket.stats);
+            total.failures += bucket.failures;
+        }
+        let mut statistics = total.stats.to_statistics();
+        let attempts = total.stats.count + total.failures;
+        statistics.success_rate = if attempts > 0 { total.stats.count as f64 / attempts as f64 * 100.0 } else { 0.0 };
+        WindowSnapshot { span: self.span, attempts, statistics }
+    }
+}
+
+/// Rolling statistics over several windows at once, such as the last minute
+/// and the last day
+///
+/// Each window is divided into 60 time buckets holding a [`RollingStats`], so
+/// a snapshot merges at most that many buckets per window
+/// instead of revisiting measurements, and memory does not grow with the
+/// number of measurements. Measurements are placed by their own timestamp.
+#[derive(Debug, Clone)]
+pub struct MultiWindowStats {
+    windows: Vec<RollingWindow>,
+}
+
+impl MultiWindowStats {
+    /// Track the last minute, 15 minutes, hour and day
+    pub fn new() -> Self {
+        Self::with_windows(&DEFAULT_ROLLING_WINDOWS)
+    }
+
+    /// Track the given window lengths, shortest first
+    pub fn with_windows(spans: &[Duration]) -> Self {
+        let mut spans = spans.to_vec();
+        spans.sort();
+        spans.dedup();
+        Self { windows: spans.into_iter().map(RollingWindow::new).collect() }
+    }
+
+    /// Add a measurement to every window
+    pub fn record(&mut self, measurement: &TimingMetrics) {
+        for window in &mut self.windows {
+            window.record(measurement);
+        }
+    }
+
+    /// Statistics of each window ending at `now`, shortest window first
+    pub fn snapshot(&self, now: DateTime<Utc>) -> Vec<WindowSnapshot> {
+        self.windows.iter().map(|window| window.snapshot(now)).collect()
+    }
+}
+
+impl Default for MultiWindowStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Statistics of one window of a [`MultiWindowStats`]
+#[derive(Debug, Clone)]
+pub struct WindowSnapshot {
+    /// Length of the window
+    pub span: Duration,
+    /// Measurements in the window, successful or not
+    pub attempts: usize,
+    /// Statistics of the successful measurements in the window
+    pub statistics: Statistics,
+}
+
+impl fmt::Display for WindowSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = format_duration(self.span);
+        if self.statistics.sample_count == 0 {
+            return write!(f, "{} -", span);
+        }
+        write!(f, "{} {:.0}ms ({:.0}% of {})", span, self.statistics.total_avg_ms, self.statistics.success_rate, self.attempts)
+    }
+}
+
 /// Buffer usage statistics for memory optimization
 #[derive(Debug, Clone)]
 pub struct BufferStats {
@@ -395,6 +556,7 @@ pub struct PoolStats {
 mod tests {
     use super::*;
     use crate::models::TimingMetrics;
+    use crate::test_support::success;
     use std::time::Duration;
     
     fn create_test_metrics(count: usize) -> Vec<TimingMetrics> {
@@ -452,6 +614,37 @@ mod tests {
         assert!(rolling.std_dev() >= 0.0);
     }
     
+    #[test]
+    fn test_multi_window_statistics() {
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |seconds: i64, total_ms: u64| {
+            let mut metric = success(total_ms);
+            metric.timestamp = start + chrono::Duration::seconds(seconds);
+            metric
+        };
+        let mut windows = MultiWindowStats::new();
+        // Slow an hour ago, fast in the last minute
+        windows.record(&at(0, 300));
+        windows.record(&at(2_000, 100));
+        let mut failed = TimingMetrics::failed("timeout".to_string());
+        failed.timestamp = start + chrono::Duration::seconds(3_590);
+        windows.record(&failed);
+        windows.record(&at(3_595, 20));
+
+        let now = start + chrono::Duration::seconds(3_600);
+        let snapshot = windows.snapshot(now);
+        let spans: Vec<String> = snapshot.iter().map(ToString::to_string).collect();
+        assert_eq!(spans, ["1m 20ms (50% of 2)", "15m 20ms (50% of 2)", "1h 60ms (67% of 3)", "24h 140ms (75% of 4)"]);
+        assert_eq!(snapshot[3].statistics.total_max_ms, 300.0);
+
+        // A day later only the longest window remembers anything, and old buckets are dropped
+        windows.record(&at(90_000, 50));
+        let later = windows.snapshot(start + chrono::Duration::seconds(90_000));
+        assert_eq!(later[0].to_string(), "1m 50ms (100% of 1)");
+        assert_eq!(later[3].attempts, 1);
+        assert!(windows.windows.iter().all(|window| window.buckets.len() <= BUCKETS_PER_WINDOW as usize));
+    }
+
     #[test]
     fn test_memory_pool() {
         let mut pool = StatisticsMemoryPool::new(5, 100);
@@ -0,0 +1,126 @@
+//! Paired comparison of two variants for the `ab` subcommand
+//!
+//! When both variants of an experiment are measured in the same round, the
+//! network conditions of that round affect both samples alike. Comparing the
+//! per-round differences instead of the two means takes those conditions out:
+//! [`PairedComparison`] reports the mean difference with the confidence
+//! interval of a paired t-test, and how often each variant won its round.
+
+use std::fmt;
+
+/// Confidence level of the interval around the mean difference
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Two-sided 95% critical values of Student's t for 1 to 30 degrees of freedom
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// Critical value for `degrees` degrees of freedom; the normal one from 31 on
+fn t_critical(degrees: usize) -> f64 {
+    T_CRITICAL_95.get(degrees.wrapping_sub(1)).copied().unwrap_or_else(|| super::z_score(CONFIDENCE_LEVEL))
+}
+
+/// Variant B compared with variant A over samples taken in pairs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairedComparison {
+    /// Rounds in which both variants got a sample
+    pub pairs: usize,
+    pub a_mean_ms: f64,
+    pub b_mean_ms: f64,
+    /// Mean of B minus A; negative when B is faster
+    pub difference_ms: f64,
+    /// Half-width of the 95% confidence interval of the difference, once
+    /// there are two pairs
+    pub margin_ms: Option<f64>,
+    /// Rounds in which B was faster than A
+    pub b_faster: usize,
+}
+
+impl PairedComparison {
+    /// Compare the `(a, b)` samples of each round; none without samples
+    pub fn of(pairs: &[(f64, f64)]) -> Option<Self> {
+        if pairs.is_empty() {
+            return None;
+        }
+        let n = pairs.len() as f64;
+        let a_mean_ms = pairs.iter().map(|(a, _)| a).sum::<f64>() / n;
+        let b_mean_ms = pairs.iter().map(|(_, b)| b).sum::<f64>() / n;
+        let difference_ms = b_mean_ms - a_mean_ms;
+        let margin_ms = (pairs.len() >= 2).then(|| {
+            let variance = pairs.iter()
+                .map(|(a, b)| (b - a - difference_ms).powi(2))
+                .sum::<f64>() / (n - 1.0);
+            t_critical(pairs.len() - 1) * (variance / n).sqrt()
+        });
+        Some(Self {
+            pairs: pairs.len(),
+            a_mean_ms,
+            b_mean_ms,
+            difference_ms,
+            margin_ms,
+            b_faster: pairs.iter().filter(|(a, b)| b < a).count(),
+        })
+    }
+
+    /// Whether the confidence interval of the difference excludes zero
+    pub fn is_significant(&self) -> bool {
+        self.margin_ms.is_some_and(|margin| self.difference_ms.abs() > margin)
+    }
+
+    /// Difference in percent of A's mean
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.a_mean_ms > 0.0).then(|| self.difference_ms / self.a_mean_ms * 100.0)
+    }
+}
+
+impl fmt::Display for PairedComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A {:.1}ms, B {:.1}ms, B {:+.1}ms", self.a_mean_ms, self.b_mean_ms, self.difference_ms)?;
+        if let Some(margin) = self.margin_ms {
+            write!(f, " ±{:.1}ms", margin)?;
+        }
+        if let Some(change) = self.change_percent() {
+            write!(f, " ({:+.1}%)", change)?;
+        }
+        write!(f, ", B faster in {}/{} rounds", self.b_faster, self.pairs)?;
+        let verdict = match (self.margin_ms, self.is_significant()) {
+            (None, _) => "too few rounds to tell",
+            (_, true) => "significant",
+            (_, false) => "not significant",
+        };
+        write!(f, ", {}", verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paired_comparison() {
+        // B is 10ms faster in every round, however much the rounds vary
+        let pairs = [(100.0, 90.0), (150.0, 139.0), (80.0, 71.0), (120.0, 110.0)];
+        let comparison = PairedComparison::of(&pairs).unwrap();
+        assert_eq!(comparison.pairs, 4);
+        assert!((comparison.difference_ms + 10.0).abs() < 1e-9);
+        // t(3) × 0.8165 / √4
+        assert!((comparison.margin_ms.unwrap() - 1.299).abs() < 0.01, "{:?}", comparison);
+        assert_eq!(comparison.b_faster, 4);
+        assert!(comparison.is_significant());
+        assert_eq!(comparison.to_string(),
+            "A 112.5ms, B 102.5ms, B -10.0ms ±1.3ms (-8.9%), B faster in 4/4 rounds, significant");
+
+        // Differences that go both ways
+        let mixed = PairedComparison::of(&[(100.0, 110.0), (100.0, 90.0), (100.0, 104.0)]).unwrap();
+        assert!(!mixed.is_significant());
+        assert_eq!(mixed.b_faster, 1);
+
+        let single = PairedComparison::of(&[(100.0, 90.0)]).unwrap();
+        assert_eq!(single.margin_ms, None);
+        assert!(single.to_string().ends_with("too few rounds to tell"));
+        assert_eq!(PairedComparison::of(&[]), None);
+    }
+}
@@ -0,0 +1,61 @@
+//! Parallel paths of the statistics engine for large result sets
+//!
+//! Reports aggregated over stored history can cover millions of samples,
+//! where sorting the response times for exact percentiles dominates the
+//! analysis. From [`PARALLEL_MIN_SAMPLES`] samples on, and with the `parallel`
+//! feature, the samples are sorted on all cores and the configurations are
+//! analyzed side by side. Smaller sets stay on the calling thread, where
+//! handing work to a thread pool costs more than it saves. The results are
+//! the same either way.
+
+/// Samples from which the analysis runs in parallel
+pub const PARALLEL_MIN_SAMPLES: usize = 100_000;
+
+/// Sort response times in ascending order
+pub(super) fn sort_samples(values: &mut [f64]) {
+    #[cfg(feature = "parallel")]
+    if values.len() >= PARALLEL_MIN_SAMPLES {
+        use rayon::slice::ParallelSliceMut;
+        values.par_sort_unstable_by(f64::total_cmp);
+        return;
+    }
+    values.sort_unstable_by(f64::total_cmp);
+}
+
+/// Apply `f` to every item, in parallel when they hold `samples` samples
+/// between them; the results keep the order of the items
+pub(super) fn map_items<T, R, F>(items: &[T], samples: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if samples >= PARALLEL_MIN_SAMPLES && items.len() > 1 {
+        use rayon::prelude::*;
+        return items.par_iter().map(f).collect();
+    }
+    let _ = samples;
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_paths_match_sequential() {
+        // A set large enough to take the parallel path when it is built in
+        let mut values: Vec<f64> = (0..PARALLEL_MIN_SAMPLES as u64)
+            .map(|i| (i * 7919 % 100_003) as f64 / 10.0)
+            .collect();
+        let mut expected = values.clone();
+        expected.sort_by(f64::total_cmp);
+        sort_samples(&mut values);
+        assert_eq!(values, expected);
+
+        let items = [3, 1, 2];
+        assert_eq!(map_items(&items, PARALLEL_MIN_SAMPLES, |x| x * 10), [30, 10, 20]);
+        assert_eq!(map_items(&items, 0, |x| x * 10), [30, 10, 20]);
+    }
+}
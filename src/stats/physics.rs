@@ -0,0 +1,90 @@
+//! The physical lower bound on round-trip times
+//!
+//! Nothing crosses a network faster than light in optical fibre, about two
+//! thirds of its speed in vacuum, so the great-circle distance between client
+//! and server sets a round-trip time no route can beat. Real routes follow
+//! roads, railways and sea beds and take about 1.5 to 2 times as long; a round
+//! trip several times the minimum means the packets make a detour, for
+//! instance through another continent.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Mean radius of the Earth
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+/// Distance light travels in fibre per millisecond, at a refractive index of 1.5
+pub const FIBER_KM_PER_MS: f64 = 299_792.458 / 1.5 / 1000.0;
+
+/// A position on the Earth in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(AppError::validation(format!(
+                "Invalid position {},{}: latitude must be within ±90 and longitude within ±180", latitude, longitude
+            )));
+        }
+        Ok(Self { latitude, longitude })
+    }
+
+    /// Great-circle distance to `other` (haversine formula)
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.longitude - self.longitude).to_radians() / 2.0;
+        let a = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+impl FromStr for GeoPoint {
+    type Err = AppError;
+
+    /// `LATITUDE,LONGITUDE`, e.g. `52.52,13.40`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || AppError::validation(format!("Invalid position '{}': expected LATITUDE,LONGITUDE such as 52.52,13.40", s));
+        let (latitude, longitude) = s.split_once(',').ok_or_else(invalid)?;
+        let degrees = |value: &str| value.trim().parse::<f64>().ok().filter(|value| value.is_finite()).ok_or_else(invalid);
+        Self::new(degrees(latitude)?, degrees(longitude)?)
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.4},{:.4}", self.latitude, self.longitude)
+    }
+}
+
+/// Shortest possible round trip over `distance_km`, there and back through fibre
+pub fn min_rtt_ms(distance_km: f64) -> f64 {
+    2.0 * distance_km / FIBER_KM_PER_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_physical_minimum() {
+        let berlin: GeoPoint = "52.52,13.405".parse().unwrap();
+        let new_york: GeoPoint = "40.7128, -74.006".parse().unwrap();
+        let distance = berlin.distance_km(&new_york);
+        assert!((distance - 6385.0).abs() < 10.0, "{}", distance);
+        assert_eq!(new_york.distance_km(&berlin), distance);
+        assert_eq!(berlin.distance_km(&berlin), 0.0);
+        // About 64ms there and back, while the fastest real routes take around 90ms
+        assert!((min_rtt_ms(distance) - 63.9).abs() < 0.2);
+
+        assert_eq!(berlin.to_string(), "52.5200,13.4050");
+        assert!("91,0".parse::<GeoPoint>().is_err());
+        assert!("52.52".parse::<GeoPoint>().is_err());
+        assert!("north,east".parse::<GeoPoint>().is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Stopping rule for `--auto-count`
+//!
+//! Instead of a fixed number of iterations, a test can keep going until its
+//! estimates are precise enough to compare: the 95% confidence intervals of
+//! the mean and of the p95 must both be narrower than a target, given as a
+//! percentage of the estimate. The mean interval comes from [`RollingStats`];
+//! the p95 interval is distribution-free, from the order statistics whose ranks
+//! bracket the percentile, so it needs roughly 80 successful samples before it
+//! can be narrow at all.
+
+use super::{z_score, RollingStats};
+use std::fmt;
+
+/// Confidence level of the intervals the stopping rule compares
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Precision of the mean and p95 of a set of samples, as confidence interval
+/// half-widths in percent of the estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precision {
+    /// Mean precision, once there are at least two samples
+    pub mean_percent: Option<f64>,
+    /// p95 precision, once there are enough samples to bracket the p95
+    pub p95_percent: Option<f64>,
+}
+
+impl Precision {
+    /// Precision of `rolling`, whose samples are also given in `sorted` order
+    pub fn of(rolling: &RollingStats, sorted: &[f64]) -> Self {
+        let z = z_score(CONFIDENCE_LEVEL);
+        Self {
+            mean_percent: mean_margin(rolling, z),
+            p95_percent: percentile_margin(sorted, 95.0, z),
+        }
+    }
+
+    /// Whether both estimates are within `target_percent`
+    pub fn is_within(&self, target_percent: f64) -> bool {
+        [self.mean_percent, self.p95_percent]
+            .iter()
+            .all(|margin| margin.is_some_and(|margin| margin <= target_percent))
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |margin: Option<f64>| margin.map_or("unknown".to_string(), |margin| format!("±{:.1}%", margin));
+        write!(f, "mean {}, p95 {}", describe(self.mean_percent), describe(self.p95_percent))
+    }
+}
+
+/// Half-width of the confidence interval of the mean, in percent of the mean
+fn mean_margin(rolling: &RollingStats, z: f64) -> Option<f64> {
+    let mean = rolling.average();
+    if rolling.count < 2 || mean <= 0.0 {
+        return None;
+    }
+    let n = rolling.count as f64;
+    // RollingStats keeps the population variance; the interval needs the sample variance
+    let sample_variance = (rolling.variance() * n / (n - 1.0)).max(0.0);
+    Some(z * (sample_variance / n).sqrt() / mean * 100.0)
+}
+
+/// Half-width of the distribution-free confidence interval of a percentile, in
+/// percent of the percentile
+///
+/// The interval runs between the order statistics whose ranks are `z` binomial
+/// standard deviations either side of `n × p`. It is unknown while either rank
+/// falls outside the samples.
+fn percentile_margin(sorted: &[f64], percentile: f64, z: f64) -> Option<f64> {
+    let n = sorted.len() as f64;
+    let p = percentile / 100.0;
+    let spread = z * (n * p * (1.0 - p)).sqrt();
+    let lower = (n * p - spread).floor();
+    let upper = (n * p + spread).ceil();
+    if lower < 1.0 || upper > n {
+        return None;
+    }
+
+    let estimate = sorted[((n * p).ceil() as usize).clamp(1, sorted.len()) - 1];
+    if estimate <= 0.0 {
+        return None;
+    }
+    let half_width = (sorted[upper as usize - 1] - sorted[lower as usize - 1]) / 2.0;
+    Some(half_width / estimate * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn precision_of(values: &[f64]) -> Precision {
+        let mut rolling = RollingStats::new();
+        for &value in values {
+            rolling.add_value(value);
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        Precision::of(&rolling, &sorted)
+    }
+
+    #[test]
+    fn test_precision_narrows_with_samples() {
+        // Alternating 95/105ms: the mean margin is 1.96 × 5 / √n percent
+        let few = precision_of(&[95.0, 105.0, 95.0, 105.0]);
+        assert!((few.mean_percent.unwrap() - 5.66).abs() < 0.01, "{:?}", few);
+        assert!(few.p95_percent.is_none());
+        assert!(!few.is_within(10.0));
+
+        let values: Vec<f64> = (0..100).map(|i| if i % 2 == 0 { 95.0 } else { 105.0 }).collect();
+        let many = precision_of(&values);
+        assert!(many.mean_percent.unwrap() < 1.0);
+        assert_eq!(many.p95_percent, Some(0.0));
+        assert!(many.is_within(5.0));
+        assert_eq!(many.to_string(), "mean ±1.0%, p95 ±0.0%");
+
+        // A heavy tail keeps the p95 uncertain even when the mean has settled
+        let tail: Vec<f64> = (0..100).map(|i| if i % 10 == 0 { 100.0 + i as f64 * 10.0 } else { 100.0 }).collect();
+        let tail = precision_of(&tail);
+        assert!(tail.p95_percent.unwrap() > 5.0);
+        assert!(!tail.is_within(5.0));
+        assert_eq!(precision_of(&[100.0]).to_string(), "mean unknown, p95 unknown");
+    }
+}
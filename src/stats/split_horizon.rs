@@ -0,0 +1,345 @@
+//! Targets that resolve differently depending on the resolver
+//!
+//! Split-horizon DNS, GeoDNS and CDN steering all hand different resolvers
+//! different answers for the same name. Slightly different addresses of the
+//! same CDN are normal; answers on unrelated networks mean a configuration
+//! is measuring a different server, not a faster route to the same one.
+//! [`SplitHorizon::detect`] compares the addresses each configuration's
+//! requests went to and groups them by the network they belong to, and
+//! [`RouteDivergence::detect`] splits the latency difference between
+//! configurations on different networks into resolver and routing time,
+//! where the lookups were timed.
+
+use crate::models::metrics::TestResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+/// Address ranges of large providers, so that addresses of one CDN in
+/// different prefixes still count as the same network
+const PROVIDER_RANGES: &[(&str, &str, u8)] = &[
+    ("Cloudflare", "104.16.0.0", 13),
+    ("Cloudflare", "172.64.0.0", 13),
+    ("Cloudflare", "162.158.0.0", 15),
+    ("Cloudflare", "2606:4700::", 32),
+    ("Google", "142.250.0.0", 15),
+    ("Google", "172.217.0.0", 16),
+    ("Google", "216.58.192.0", 19),
+    ("Google", "2607:f8b0::", 32),
+    ("Akamai", "23.32.0.0", 11),
+    ("Akamai", "2.16.0.0", 13),
+    ("Fastly", "151.101.0.0", 16),
+    ("Fastly", "2a04:4e42::", 32),
+    ("Amazon CloudFront", "13.32.0.0", 15),
+    ("Amazon CloudFront", "18.160.0.0", 15),
+];
+
+/// The network an address belongs to: a known provider, a private network,
+/// or else its /16 (IPv4) or /32 (IPv6) prefix
+///
+/// A stand-in for the address's autonomous system, for when no ASN was looked up.
+pub fn network_of(ip: IpAddr) -> String {
+    if let Some((provider, _, _)) = PROVIDER_RANGES.iter().find(|(_, base, bits)| {
+        base.parse().is_ok_and(|base| in_prefix(ip, base, *bits))
+    }) {
+        return provider.to_string();
+    }
+    match ip {
+        IpAddr::V4(v4) if v4.is_private() || v4.is_loopback() || v4.is_link_local() => "private network".to_string(),
+        IpAddr::V6(v6) if v6.is_loopback() || (v6.segments()[0] & 0xfe00) == 0xfc00 => "private network".to_string(),
+        IpAddr::V4(v4) => {
+            let [a, b, _, _] = v4.octets();
+            format!("{}.{}.0.0/16", a, b)
+        }
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            format!("{:x}:{:x}::/32", segments[0], segments[1])
+        }
+    }
+}
+
+fn in_prefix(ip: IpAddr, base: IpAddr, bits: u8) -> bool {
+    match (ip, base) {
+        (IpAddr::V4(ip), IpAddr::V4(base)) => {
+            let mask = u32::MAX.checked_shl(32 - bits as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(base) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(base)) => {
+            let mask = u128::MAX.checked_shl(128 - bits as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(base) & mask
+        }
+        _ => false,
+    }
+}
+
+/// The addresses one configuration's requests to a target went to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolverAnswer {
+    pub ips: BTreeSet<IpAddr>,
+    /// Networks of the addresses, see [`network_of`]
+    pub networks: BTreeSet<String>,
+}
+
+impl std::fmt::Display for ResolverAnswer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ips: Vec<String> = self.ips.iter().map(ToString::to_string).collect();
+        let networks: Vec<&str> = self.networks.iter().map(String::as_str).collect();
+        write!(f, "{} ({})", ips.join(", "), networks.join(", "))
+    }
+}
+
+/// A target whose address depended on the configuration it was tested with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitHorizon {
+    pub url: String,
+    /// Answer of each configuration that reached the target
+    pub answers: BTreeMap<String, ResolverAnswer>,
+    /// Whether some configurations share no network at all, i.e. reached a
+    /// different provider rather than another address of the same one
+    pub material: bool,
+}
+
+impl SplitHorizon {
+    /// Targets whose configurations did not all reach the same addresses,
+    /// sorted with material differences first and then by URL
+    pub fn detect<'a>(results: impl IntoIterator<Item = &'a TestResult>) -> Vec<SplitHorizon> {
+        Self::detect_with_networks(results, &BTreeMap::new())
+    }
+
+    /// [`SplitHorizon::detect`] with the networks of some addresses known, such
+    /// as their ASNs, in place of [`network_of`]
+    pub fn detect_with_networks<'a>(
+        results: impl IntoIterator<Item = &'a TestResult>,
+        known: &BTreeMap<IpAddr, String>,
+    ) -> Vec<SplitHorizon> {
+        let mut by_url: BTreeMap<&str, BTreeMap<&str, BTreeSet<IpAddr>>> = BTreeMap::new();
+        for result in results {
+            let ips: BTreeSet<IpAddr> = result.individual_results.iter().filter_map(|timing| timing.resolved_ip).collect();
+            if !ips.is_empty() {
+                by_url.entry(result.url.as_str())
+                    .or_default()
+                    .entry(result.config_name.as_str())
+                    .or_default()
+                    .extend(ips);
+            }
+        }
+
+        let mut targets: Vec<SplitHorizon> = by_url.into_iter()
+            .filter(|(_, answers)| {
+                let mut sets = answers.values();
+                sets.next().is_some_and(|first| sets.any(|ips| ips != first))
+            })
+            .map(|(url, answers)| {
+                let answers: BTreeMap<String, ResolverAnswer> = answers.into_iter()
+                    .map(|(config_name, ips)| {
+                        let networks = ips.iter().map(|ip| known.get(ip).cloned().unwrap_or_else(|| network_of(*ip))).collect();
+                        (config_name.to_string(), ResolverAnswer { ips, networks })
+                    })
+                    .collect();
+                let material = answers.values().any(|a| {
+                    answers.values().any(|b| a.networks.is_disjoint(&b.networks))
+                });
+                SplitHorizon { url: url.to_string(), answers, material }
+            })
+            .collect();
+
+        targets.sort_by(|a, b| b.material.cmp(&a.material).then_with(|| a.url.cmp(&b.url)));
+        targets
+    }
+
+    /// How far apart the answers are, for reports
+    pub fn verdict(&self) -> &'static str {
+        if self.material {
+            "different networks"
+        } else {
+            "same network, different addresses"
+        }
+    }
+}
+
+/// The latency difference between a configuration and the fastest one to a
+/// target they reached on different networks, split into the time spent
+/// resolving the name and the rest, which comes from the route and server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteDivergence {
+    pub url: String,
+    /// Fastest configuration to the target, which the other is compared with
+    pub reference: String,
+    pub reference_networks: BTreeSet<String>,
+    pub config_name: String,
+    pub networks: BTreeSet<String>,
+    /// Difference in average response time (milliseconds)
+    pub delta_ms: f64,
+    /// Part of the difference spent resolving the name (milliseconds), when
+    /// both configurations' lookups were timed
+    pub resolver_ms: Option<f64>,
+    /// Rest of the difference: connecting to, and being served from, another
+    /// network (milliseconds), when the resolver's part is known
+    pub routing_ms: Option<f64>,
+}
+
+impl RouteDivergence {
+    /// Configurations that reached a target of `targets` on networks the
+    /// fastest configuration to it did not use, compared with that configuration
+    ///
+    /// Only lookups that were timed count towards the resolver's part; DNS
+    /// durations estimated from the total leave the split unknown.
+    pub fn detect<'a>(targets: &[SplitHorizon], results: impl IntoIterator<Item = &'a TestResult>) -> Vec<RouteDivergence> {
+        let averages: BTreeMap<(&str, &str), (f64, Option<f64>)> = results.into_iter()
+            .filter_map(|result| {
+                let stats = result.statistics.as_ref()?;
+                Some(((result.url.as_str(), result.config_name.as_str()), (stats.total_avg_ms, measured_dns_avg_ms(result))))
+            })
+            .collect();
+
+        let mut divergences = Vec::new();
+        for target in targets.iter().filter(|target| target.material) {
+            let timed: Vec<_> = target.answers.iter()
+                .filter_map(|(config_name, answer)| {
+                    averages.get(&(target.url.as_str(), config_name.as_str())).map(|&averages| (config_name, answer, averages))
+                })
+                .collect();
+            let Some(&(reference, reference_answer, (reference_total, reference_dns))) =
+                timed.iter().min_by(|a, b| a.2.0.total_cmp(&b.2.0))
+            else {
+                continue;
+            };
+            for &(config_name, answer, (total, dns)) in &timed {
+                if !answer.networks.is_disjoint(&reference_answer.networks) {
+                    continue;
+                }
+                let delta_ms = total - reference_total;
+                let resolver_ms = dns.zip(reference_dns).map(|(dns, reference_dns)| dns - reference_dns);
+                divergences.push(RouteDivergence {
+                    url: target.url.clone(),
+                    reference: reference.clone(),
+                    reference_networks: reference_answer.networks.clone(),
+                    config_name: config_name.clone(),
+                    networks: answer.networks.clone(),
+                    delta_ms,
+                    resolver_ms,
+                    routing_ms: resolver_ms.map(|resolver_ms| delta_ms - resolver_ms),
+                });
+            }
+        }
+        divergences
+    }
+}
+
+/// Average DNS time of the successful requests whose lookups were timed
+fn measured_dns_avg_ms(result: &TestResult) -> Option<f64> {
+    let measured: Vec<f64> = result.individual_results.iter()
+        .filter(|metrics| metrics.is_successful() && !metrics.estimated_phases)
+        .map(|metrics| metrics.dns_ms())
+        .collect();
+    (!measured.is_empty()).then(|| measured.iter().sum::<f64>() / measured.len() as f64)
+}
+
+impl std::fmt::Display for RouteDivergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let networks = |networks: &BTreeSet<String>| networks.iter().map(String::as_str).collect::<Vec<_>>().join(", ");
+        match (self.routing_ms, self.resolver_ms) {
+            (Some(routing_ms), Some(resolver_ms)) => write!(
+                f, "{:+.1}ms vs {}: {:+.1}ms routing ({} vs {}), {:+.1}ms resolver",
+                self.delta_ms, self.reference, routing_ms, networks(&self.networks), networks(&self.reference_networks), resolver_ms
+            ),
+            _ => write!(
+                f, "{:+.1}ms vs {} on another network ({} vs {}); lookups were not timed to split it",
+                self.delta_ms, self.reference, networks(&self.networks), networks(&self.reference_networks)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::metrics::TimingMetrics;
+    use crate::types::DnsConfig;
+    use std::time::Duration;
+
+    fn result(config_name: &str, url: &str, ips: &[&str]) -> TestResult {
+        let mut result = TestResult::new(config_name.to_string(), DnsConfig::System, url.to_string());
+        for ip in ips {
+            result.add_measurement(
+                TimingMetrics::success(Duration::from_millis(5), Duration::from_millis(10), Some(Duration::from_millis(20)), Duration::from_millis(30), Duration::from_millis(80), 200)
+                    .with_resolved_ip(Some(ip.parse().unwrap())),
+            );
+        }
+        result
+    }
+
+    #[test]
+    fn test_network_of() {
+        assert_eq!(network_of("104.18.32.7".parse().unwrap()), "Cloudflare");
+        assert_eq!(network_of("2606:4700::6810:84e5".parse().unwrap()), "Cloudflare");
+        assert_eq!(network_of("93.184.216.34".parse().unwrap()), "93.184.0.0/16");
+        assert_eq!(network_of("2001:db8::1".parse().unwrap()), "2001:db8::/32");
+        assert_eq!(network_of("10.1.2.3".parse().unwrap()), "private network");
+    }
+
+    #[test]
+    fn test_detect_split_horizon() {
+        let results = [
+            // Same provider, different addresses
+            result("System DNS", "https://cdn.example", &["104.18.32.7"]),
+            result("Custom DNS (8.8.8.8)", "https://cdn.example", &["172.64.155.249"]),
+            // An internal answer against a public one
+            result("System DNS", "https://intranet.example", &["10.0.0.5"]),
+            result("Custom DNS (8.8.8.8)", "https://intranet.example", &["93.184.216.34", "93.184.215.14"]),
+            // Everyone agrees
+            result("System DNS", "https://same.example", &["93.184.216.34"]),
+            result("Custom DNS (8.8.8.8)", "https://same.example", &["93.184.216.34"]),
+            // A configuration that never connected has no answer
+            result("DoH (dns.google)", "https://same.example", &[]),
+        ];
+
+        let targets = SplitHorizon::detect(&results);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].url, "https://intranet.example");
+        assert!(targets[0].material);
+        assert_eq!(targets[0].answers["Custom DNS (8.8.8.8)"].networks, BTreeSet::from(["93.184.0.0/16".to_string()]));
+        assert_eq!(targets[1].url, "https://cdn.example");
+        assert!(!targets[1].material);
+        assert_eq!(targets[1].answers["System DNS"].to_string(), "104.18.32.7 (Cloudflare)");
+
+        // Known ASNs take the place of the prefix heuristics
+        let known = BTreeMap::from([("172.64.155.249".parse().unwrap(), "AS13335 Cloudflare, Inc.".to_string())]);
+        let targets = SplitHorizon::detect_with_networks(&results, &known);
+        assert!(targets.iter().all(|target| target.material));
+    }
+
+    #[test]
+    fn test_route_divergence() {
+        let timed = |config_name: &str, ip: &str, dns_ms: u64, total_ms: u64| {
+            let mut result = TestResult::new(config_name.to_string(), DnsConfig::System, "https://cdn.example".to_string());
+            let metrics = TimingMetrics::success(Duration::from_millis(dns_ms), Duration::from_millis(10), None, Duration::from_millis(20), Duration::from_millis(total_ms), 200);
+            result.add_measurement(metrics.with_resolved_ip(Some(ip.parse().unwrap())));
+            result.calculate_statistics();
+            result
+        };
+        let results = [
+            timed("System DNS", "104.18.32.7", 5, 50),
+            timed("DoH (dns.google)", "93.184.216.34", 25, 110),
+            // Same network as the fastest, so only resolver speed differs
+            timed("Custom DNS (1.1.1.1)", "172.64.155.249", 8, 55),
+        ];
+        let targets = SplitHorizon::detect(&results);
+        let divergences = RouteDivergence::detect(&targets, &results);
+
+        assert_eq!(divergences.len(), 1);
+        let divergence = &divergences[0];
+        assert_eq!((divergence.reference.as_str(), divergence.config_name.as_str()), ("System DNS", "DoH (dns.google)"));
+        assert_eq!((divergence.delta_ms, divergence.resolver_ms, divergence.routing_ms), (60.0, Some(20.0), Some(40.0)));
+        assert_eq!(divergence.to_string(), "+60.0ms vs System DNS: +40.0ms routing (93.184.0.0/16 vs Cloudflare), +20.0ms resolver");
+
+        // Estimated DNS durations do not split the difference
+        let mut estimated = results.clone();
+        for metrics in &mut estimated[1].individual_results {
+            metrics.estimated_phases = true;
+        }
+        let divergence = &RouteDivergence::detect(&targets, &estimated)[0];
+        assert_eq!((divergence.delta_ms, divergence.resolver_ms, divergence.routing_ms), (60.0, None, None));
+        assert_eq!(divergence.to_string(), "+60.0ms vs System DNS on another network (93.184.0.0/16 vs Cloudflare); lookups were not timed to split it");
+    }
+}
@@ -0,0 +1,389 @@
+//! Streaming statistics for long-lived embedders
+//!
+//! [`StreamingAnalyzer`] accepts timing measurements one at a time and can
+//! produce an [`ExtendedStatistics`] snapshot at any point. Means and variances
+//! are tracked incrementally and percentiles come from a log-bucketed histogram,
+//! so memory stays bounded no matter how many samples are recorded.
+
+use super::{
+    aggregate::{Recorded, SampleTallies},
+    wilson_interval, z_score, ConfidenceIntervals, ExtendedStatistics, OutlierAnalysis, OutlierMethod, ReliabilityMetrics,
+    StatisticsConfig, StatisticsEngine,
+};
+use crate::{
+    error::{AppError, Result},
+    models::metrics::{Statistics, TimingMetrics},
+};
+use std::collections::BTreeMap;
+
+/// Relative width of a histogram bucket (1% precision for percentiles)
+const BUCKET_GROWTH: f64 = 1.01;
+
+/// Incremental statistics analyzer that does not retain individual samples
+#[derive(Debug, Clone)]
+pub struct StreamingAnalyzer {
+    config: StatisticsConfig,
+    attempts: u64,
+    tallies: SampleTallies,
+    histogram: LogHistogram,
+    body_sizes: LogHistogram,
+}
+
+impl StreamingAnalyzer {
+    /// Create a new streaming analyzer
+    pub fn new(config: StatisticsConfig) -> Self {
+        Self {
+            config,
+            attempts: 0,
+            tallies: SampleTallies::default(),
+            histogram: LogHistogram::default(),
+            body_sizes: LogHistogram::default(),
+        }
+    }
+
+    /// Create a streaming analyzer with default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(StatisticsConfig::default())
+    }
+
+    /// Record a single measurement; failed measurements only count as attempts
+    pub fn record(&mut self, metrics: &TimingMetrics) {
+        self.attempts += 1;
+        match self.tallies.record(metrics) {
+            Recorded::Success { total_ms, body_bytes } => {
+                self.histogram.record(total_ms);
+                if let Some(bytes) = body_bytes {
+                    self.body_sizes.record(bytes);
+                }
+            }
+            Recorded::Timeout { total_ms } if self.config.censor_timeouts => self.histogram.record(total_ms),
+            Recorded::Timeout { .. } | Recorded::Failed => {}
+        }
+    }
+
+    /// Number of successful measurements recorded
+    pub fn sample_count(&self) -> usize {
+        self.tallies.sample_count() as usize
+    }
+
+    /// Number of measurements recorded, including failures
+    pub fn attempt_count(&self) -> u64 {
+        self.attempts
+    }
+
+    /// Discard everything recorded so far
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    /// Produce statistics for everything recorded so far
+    pub fn snapshot(&self) -> Result<ExtendedStatistics> {
+        let tallies = &self.tallies;
+        if tallies.sample_count() == 0 {
+            return Err(AppError::validation("No successful measurements for statistics calculation"));
+        }
+
+        let percentiles = self.config.percentiles.iter()
+            .map(|&p| (format!("p{:.0}", p), self.percentile(p)))
+            .collect();
+
+        Ok(ExtendedStatistics {
+            basic: tallies.all.statistics().unwrap_or_else(Statistics::empty),
+            percentiles,
+            confidence_intervals: self.confidence_intervals(),
+            outlier_analysis: self.outlier_analysis(),
+            performance_distribution: tallies.performance_distribution(),
+            reliability: self.reliability(),
+            cold_start: tallies.cold_start(),
+            cache_split: tallies.cache_split(),
+            tls_resumption: tallies.tls_resumption(),
+            timeouts: tallies.timeouts.summary(self.attempts, self.config.censor_timeouts),
+            response_size: tallies.bodies.summary(|p| self.body_sizes.percentile(p).min(tallies.bodies.max_bytes)),
+            socket: tallies.sockets.summary(),
+            upload: tallies.uploads.summary(),
+            informational: tallies.informational.summary(),
+            server_timing: tallies.server_timing.summary(),
+        })
+    }
+
+    /// Approximate percentile of total response time, clamped to the observed range
+    fn percentile(&self, percentile: f64) -> f64 {
+        let all = &self.tallies.all;
+        let mut max_ms = all.max_total_ms;
+        if self.config.censor_timeouts && self.tallies.timeouts.count > 0 {
+            max_ms = max_ms.max(self.tallies.timeouts.max_ms);
+        }
+        self.histogram
+            .percentile(percentile)
+            .clamp(all.min_total_ms, max_ms)
+    }
+
+    fn confidence_intervals(&self) -> ConfidenceIntervals {
+        let all = &self.tallies.all;
+        let z = z_score(self.config.confidence_level);
+        let success_rate = wilson_interval(all.total.count, self.attempts, z);
+
+        if self.sample_count() < self.config.min_samples {
+            return ConfidenceIntervals {
+                level: self.config.confidence_level,
+                avg_response_time: (0.0, 0.0),
+                success_rate,
+                dns_resolution_time: (0.0, 0.0),
+            };
+        }
+
+        let sqrt_n = (all.total.count as f64).sqrt();
+        let response_margin = z * all.total.sample_std_dev() / sqrt_n;
+        let dns_margin = z * all.dns.sample_std_dev() / sqrt_n;
+
+        ConfidenceIntervals {
+            level: self.config.confidence_level,
+            avg_response_time: (all.total.mean - response_margin, all.total.mean + response_margin),
+            success_rate,
+            dns_resolution_time: (all.dns.mean - dns_margin, all.dns.mean + dns_margin),
+        }
+    }
+
+    fn outlier_analysis(&self) -> OutlierAnalysis {
+        let total = &self.tallies.all.total;
+        let outlier_count = match self.config.outlier_method {
+            OutlierMethod::IQR if total.count >= 4 => {
+                let q1 = self.percentile(25.0);
+                let q3 = self.percentile(75.0);
+                let iqr = q3 - q1;
+                self.histogram.count_where(|x| x < q1 - 1.5 * iqr || x > q3 + 1.5 * iqr)
+            }
+            OutlierMethod::IQR => 0,
+            OutlierMethod::StandardDeviation { threshold } => {
+                let mean = total.mean;
+                let limit = threshold * total.sample_std_dev();
+                self.histogram.count_where(|x| (x - mean).abs() > limit)
+            }
+            OutlierMethod::ModifiedZScore { threshold } => {
+                let median = self.percentile(50.0);
+                let mad = self.histogram.median_absolute_deviation(median);
+                if mad == 0.0 {
+                    0
+                } else {
+                    self.histogram.count_where(|x| (0.6745 * (x - median) / mad).abs() > threshold)
+                }
+            }
+        };
+
+        OutlierAnalysis {
+            outlier_count: outlier_count as usize,
+            outlier_percentage: (outlier_count as f64 / total.count as f64) * 100.0,
+            detection_method: format!("{:?}", self.config.outlier_method),
+            threshold_values: self.config.outlier_method.threshold_values(),
+        }
+    }
+
+    fn reliability(&self) -> ReliabilityMetrics {
+        let success_rate = (self.tallies.sample_count() as f64 / self.attempts as f64) * 100.0;
+        self.tallies.reliability(success_rate, self.attempts)
+    }
+}
+
+impl Default for StreamingAnalyzer {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// Sparse histogram with logarithmically sized buckets
+#[derive(Debug, Clone, Default)]
+struct LogHistogram {
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+}
+
+impl LogHistogram {
+    /// Bucket holding non-positive values
+    const ZERO_BUCKET: i32 = i32::MIN;
+
+    fn bucket_index(value: f64) -> i32 {
+        if value <= 0.0 {
+            Self::ZERO_BUCKET
+        } else {
+            (value.ln() / BUCKET_GROWTH.ln()).floor() as i32
+        }
+    }
+
+    /// Geometric midpoint of a bucket, used as the value of every sample in it
+    fn bucket_value(index: i32) -> f64 {
+        if index == Self::ZERO_BUCKET {
+            0.0
+        } else {
+            BUCKET_GROWTH.powf(index as f64 + 0.5)
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        *self.buckets.entry(Self::bucket_index(value)).or_insert(0) += 1;
+        self.count += 1;
+    }
+
+    /// Percentile interpolated between ranks as in the batch engine, with the
+    /// bucket's value standing in for each sample
+    fn percentile(&self, percentile: f64) -> f64 {
+        StatisticsEngine::percentile_by_rank(self.count as usize, percentile, |rank| self.value_at_rank(rank as u64))
+    }
+
+    /// Value of the sample at `rank` in ascending order
+    fn value_at_rank(&self, rank: u64) -> f64 {
+        let mut seen = 0;
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen > rank {
+                return Self::bucket_value(index);
+            }
+        }
+        self.buckets.keys().next_back().map_or(0.0, |&index| Self::bucket_value(index))
+    }
+
+    fn count_where<F: Fn(f64) -> bool>(&self, predicate: F) -> u64 {
+        self.buckets.iter()
+            .filter(|(&index, _)| predicate(Self::bucket_value(index)))
+            .map(|(_, &count)| count)
+            .sum()
+    }
+
+    fn median_absolute_deviation(&self, median: f64) -> f64 {
+        let mut deviations: Vec<(f64, u64)> = self.buckets.iter()
+            .map(|(&index, &count)| ((Self::bucket_value(index) - median).abs(), count))
+            .collect();
+        deviations.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let rank = (self.count - 1) / 2;
+        let mut seen = 0;
+        for (deviation, count) in deviations {
+            seen += count;
+            if seen > rank {
+                return deviation;
+            }
+        }
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::StatisticsEngine;
+    use crate::models::metrics::{CacheStatus, TestResult};
+    use crate::types::DnsConfig;
+    use std::time::Duration;
+
+    fn timing(total_ms: u64) -> TimingMetrics {
+        TimingMetrics::success(
+            Duration::from_millis(total_ms / 10),
+            Duration::from_millis(10),
+            None,
+            Duration::from_millis(20),
+            Duration::from_millis(total_ms),
+            200,
+        )
+    }
+
+    #[test]
+    fn test_empty_snapshot_is_error() {
+        let mut analyzer = StreamingAnalyzer::with_defaults();
+        assert!(analyzer.snapshot().is_err());
+
+        analyzer.record(&TimingMetrics::failed("connection refused".to_string()));
+        assert_eq!(analyzer.attempt_count(), 1);
+        assert_eq!(analyzer.sample_count(), 0);
+        assert!(analyzer.snapshot().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_matches_batch_engine() {
+        let totals: Vec<u64> = (0..200).map(|i| 80 + (i * 37) % 150).chain([2500, 4000]).collect();
+
+        let mut analyzer = StreamingAnalyzer::with_defaults();
+        let mut result = TestResult::new("System DNS".to_string(), DnsConfig::System, "https://example.com".to_string());
+        for &total in &totals {
+            analyzer.record(&timing(total));
+            result.add_measurement(timing(total));
+        }
+        analyzer.record(&TimingMetrics::timeout(Duration::from_secs(10)));
+        result.add_measurement(TimingMetrics::timeout(Duration::from_secs(10)));
+        result.calculate_statistics();
+
+        let mut engine = StatisticsEngine::with_defaults();
+        engine.add_result(result);
+        let batch = engine.analyze().unwrap().basic_stats.remove("System DNS").unwrap();
+        let streamed = analyzer.snapshot().unwrap();
+
+        let close = |a: f64, b: f64, tolerance: f64| (a - b).abs() <= tolerance * b.abs().max(1.0);
+
+        assert_eq!(streamed.basic.sample_count, batch.basic.sample_count);
+        assert!(close(streamed.basic.total_avg_ms, batch.basic.total_avg_ms, 1e-9));
+        assert!(close(streamed.basic.total_std_dev_ms, batch.basic.total_std_dev_ms, 1e-9));
+        assert!(close(streamed.basic.dns_avg_ms, batch.basic.dns_avg_ms, 1e-9));
+        assert_eq!(streamed.basic.total_min_ms, batch.basic.total_min_ms);
+        assert_eq!(streamed.basic.total_max_ms, batch.basic.total_max_ms);
+
+        for key in ["p50", "p90", "p95", "p99"] {
+            assert!(close(streamed.percentiles[key], batch.percentiles[key], 0.02), "{} differs", key);
+        }
+
+        assert!(close(streamed.confidence_intervals.avg_response_time.0, batch.confidence_intervals.avg_response_time.0, 1e-9));
+        assert!(close(streamed.confidence_intervals.success_rate.0, batch.confidence_intervals.success_rate.0, 1e-9));
+        assert!(close(streamed.reliability.success_rate, batch.reliability.success_rate, 1e-9));
+        assert!(close(streamed.reliability.jitter_ms, batch.reliability.jitter_ms, 1e-9));
+        assert_eq!(streamed.outlier_analysis.outlier_count, batch.outlier_analysis.outlier_count);
+        assert_eq!(streamed.performance_distribution.poor_percentage, batch.performance_distribution.poor_percentage);
+        assert_eq!(streamed.timeouts.as_ref().unwrap().count, batch.timeouts.as_ref().unwrap().count);
+        assert!(close(streamed.timeouts.unwrap().rate, batch.timeouts.unwrap().rate, 1e-9));
+    }
+
+    #[test]
+    fn test_cold_start_split() {
+        let mut analyzer = StreamingAnalyzer::with_defaults();
+        for total in [100, 110, 90] {
+            analyzer.record(&timing(total));
+        }
+        assert!(analyzer.snapshot().unwrap().cold_start.is_none());
+
+        analyzer.record(&timing(400).with_first_use(true));
+        let split = analyzer.snapshot().unwrap().cold_start.unwrap();
+        assert_eq!(split.cold.sample_count, 1);
+        assert_eq!(split.warm.sample_count, 3);
+        assert_eq!(split.warm.total_avg_ms, 100.0);
+        assert_eq!(split.penalty_ms(), 300.0);
+    }
+
+    #[test]
+    fn test_cache_split() {
+        let mut analyzer = StreamingAnalyzer::with_defaults();
+        analyzer.record(&timing(500));
+        assert!(analyzer.snapshot().unwrap().cache_split.is_none());
+
+        for total in [40, 60] {
+            analyzer.record(&timing(total).with_cache_status(Some(CacheStatus::Hit)));
+        }
+        let split = analyzer.snapshot().unwrap().cache_split.unwrap();
+        assert_eq!(split.hit.as_ref().unwrap().total_avg_ms, 50.0);
+        assert!(split.miss.is_none());
+        assert_eq!(split.hit_rate(), 100.0);
+
+        analyzer.record(&timing(350).with_cache_status(Some(CacheStatus::Miss)));
+        let split = analyzer.snapshot().unwrap().cache_split.unwrap();
+        assert_eq!(split.miss_penalty_ms(), Some(300.0));
+        assert!((split.hit_rate() - 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reset_clears_samples() {
+        let mut analyzer = StreamingAnalyzer::with_defaults();
+        for total in [100, 200, 300] {
+            analyzer.record(&timing(total));
+        }
+        assert_eq!(analyzer.snapshot().unwrap().basic.total_avg_ms, 200.0);
+
+        analyzer.reset();
+        assert_eq!(analyzer.attempt_count(), 0);
+        assert!(analyzer.snapshot().is_err());
+    }
+}
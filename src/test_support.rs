@@ -0,0 +1,38 @@
+//! Fixtures shared by the unit tests
+
+use crate::models::{TestResult, TimingMetrics};
+use crate::types::DnsConfig;
+use std::time::Duration;
+
+/// A successful measurement whose first byte and total both took `total_ms`
+pub(crate) fn success(total_ms: u64) -> TimingMetrics {
+    let total = Duration::from_millis(total_ms);
+    TimingMetrics::success(Duration::ZERO, Duration::ZERO, None, total, total, 200)
+}
+
+/// A system DNS result with one successful measurement per entry of
+/// `totals_ms`, statistics already calculated
+pub(crate) fn result_with_totals(config_name: &str, url: &str, totals_ms: &[u64]) -> TestResult {
+    result_with_totals_via(config_name, DnsConfig::System, url, totals_ms)
+}
+
+/// [`result_with_totals`] for a configuration resolving through `dns_config`
+pub(crate) fn result_with_totals_via(config_name: &str, dns_config: DnsConfig, url: &str, totals_ms: &[u64]) -> TestResult {
+    let mut result = TestResult::new(config_name.to_string(), dns_config, url.to_string());
+    for &total_ms in totals_ms {
+        result.add_measurement(success(total_ms));
+    }
+    result.calculate_statistics();
+    result
+}
+
+/// [`result_with_totals`] followed by `failures` refused connections
+#[cfg(feature = "native")]
+pub(crate) fn result_with_failures(config_name: &str, url: &str, totals_ms: &[u64], failures: usize) -> TestResult {
+    let mut result = result_with_totals(config_name, url, totals_ms);
+    for _ in 0..failures {
+        result.add_measurement(TimingMetrics::failed("Connection refused".to_string()));
+    }
+    result.calculate_statistics();
+    result
+}
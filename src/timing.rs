@@ -0,0 +1,288 @@
+//! Time sources for durations and timestamps
+//!
+//! Latencies are only ever measured with the monotonic [`Instant`] clock, which
+//! does not jump when NTP steps the system clock or someone changes it by hand.
+//! The wall clock is read once, when the process first asks for a timestamp;
+//! every later timestamp is that anchor plus monotonic time elapsed since. A
+//! clock adjustment during a long run therefore neither distorts measured
+//! durations nor reorders samples, and [`clock_drift`] reports how far the
+//! system clock has moved away from the timestamps the run used.
+//!
+//! With the `native` feature, `ntp_offset` additionally asks an NTP server how
+//! far the system clock is from true time, which matters when timestamps are
+//! compared with another machine's.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// Drift or offset beyond which timestamps are flagged as unreliable
+pub const CLOCK_WARNING_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// A monotonic clock anchored to the wall-clock time at which it started
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    started: Instant,
+    started_at: DateTime<Utc>,
+}
+
+impl MonotonicClock {
+    /// Start a clock anchored to the current wall-clock time
+    pub fn start() -> Self {
+        Self { started: Instant::now(), started_at: Utc::now() }
+    }
+
+    /// Wall-clock time at which the clock started
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// Monotonic time elapsed since the clock started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Current time, derived from the anchor and the monotonic clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.timestamp_of(Instant::now())
+    }
+
+    /// Wall-clock time corresponding to `instant`
+    pub fn timestamp_of(&self, instant: Instant) -> DateTime<Utc> {
+        match instant.checked_duration_since(self.started) {
+            Some(after) => self.started_at + chrono::Duration::from_std(after).unwrap_or(chrono::Duration::MAX),
+            None => self.started_at - chrono::Duration::from_std(self.started - instant).unwrap_or(chrono::Duration::MAX),
+        }
+    }
+
+    /// How far the system clock is ahead of this clock; negative when behind
+    pub fn drift(&self) -> chrono::Duration {
+        Utc::now() - self.now()
+    }
+}
+
+static PROCESS_CLOCK: OnceLock<MonotonicClock> = OnceLock::new();
+
+/// Clock shared by the whole process, started on first use
+pub fn process_clock() -> &'static MonotonicClock {
+    PROCESS_CLOCK.get_or_init(MonotonicClock::start)
+}
+
+/// Timestamp for a sample or event, immune to system clock adjustments
+pub fn timestamp() -> DateTime<Utc> {
+    process_clock().now()
+}
+
+/// How far the system clock has moved away from [`timestamp`] since the process started
+pub fn clock_drift() -> chrono::Duration {
+    process_clock().drift()
+}
+
+/// Clock health recorded with a run's results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClockReport {
+    /// System clock minus the run's timestamps when the run finished, in milliseconds
+    pub drift_ms: f64,
+    /// NTP server that was queried
+    #[serde(default)]
+    pub ntp_server: Option<String>,
+    /// System clock minus NTP time in milliseconds, if the server answered
+    #[serde(default)]
+    pub ntp_offset_ms: Option<f64>,
+}
+
+impl ClockReport {
+    /// Report the drift accumulated by the process clock so far
+    pub fn capture() -> Self {
+        Self {
+            drift_ms: signed_millis(clock_drift()),
+            ntp_server: None,
+            ntp_offset_ms: None,
+        }
+    }
+
+    /// Whether the system clock was adjusted during the run
+    pub fn was_adjusted(&self) -> bool {
+        self.drift_ms.abs() >= CLOCK_WARNING_THRESHOLD.as_secs_f64() * 1000.0
+    }
+
+    /// Whether the system clock is off from NTP time
+    pub fn is_offset(&self) -> bool {
+        self.ntp_offset_ms
+            .is_some_and(|offset| offset.abs() >= CLOCK_WARNING_THRESHOLD.as_secs_f64() * 1000.0)
+    }
+}
+
+#[cfg(feature = "native")]
+impl ClockReport {
+    /// Capture the drift and, when `ntp_server` is given, its offset from NTP time
+    pub async fn check(ntp_server: Option<&str>, timeout: Duration) -> Self {
+        let mut report = Self::capture();
+        if let Some(server) = ntp_server {
+            report.ntp_server = Some(server.to_string());
+            report.ntp_offset_ms = ntp_offset(server, timeout).await.ok().map(signed_millis);
+        }
+        report
+    }
+}
+
+impl fmt::Display for ClockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "drift {:+.1} ms", self.drift_ms)?;
+        match (&self.ntp_server, self.ntp_offset_ms) {
+            (Some(server), Some(offset)) => write!(f, " | NTP offset {:+.1} ms ({})", offset, server),
+            (Some(server), None) => write!(f, " | NTP offset unavailable ({})", server),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn signed_millis(duration: chrono::Duration) -> f64 {
+    duration.num_microseconds().map_or(duration.num_milliseconds() as f64, |us| us as f64 / 1000.0)
+}
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970)
+#[cfg(feature = "native")]
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+/// Size of an NTP packet without extensions
+#[cfg(feature = "native")]
+const NTP_PACKET_LEN: usize = 48;
+/// Port NTP servers listen on
+#[cfg(feature = "native")]
+const NTP_PORT: u16 = 123;
+
+/// Build an SNTP client request
+#[cfg(feature = "native")]
+fn ntp_request() -> [u8; NTP_PACKET_LEN] {
+    let mut packet = [0u8; NTP_PACKET_LEN];
+    // Leap indicator 0, version 4, mode 3 (client)
+    packet[0] = 0b00_100_011;
+    packet
+}
+
+/// Read a 64-bit NTP timestamp at `offset` in `packet`
+#[cfg(feature = "native")]
+fn ntp_timestamp(packet: &[u8], offset: usize) -> Option<DateTime<Utc>> {
+    let seconds = u32::from_be_bytes(packet[offset..offset + 4].try_into().ok()?);
+    let fraction = u32::from_be_bytes(packet[offset + 4..offset + 8].try_into().ok()?);
+    if seconds == 0 && fraction == 0 {
+        return None;
+    }
+    let nanos = (u64::from(fraction) * 1_000_000_000) >> 32;
+    DateTime::from_timestamp(i64::from(seconds) - NTP_UNIX_OFFSET, nanos as u32)
+}
+
+/// Offset of the system clock from an NTP server's, given a `response` to a
+/// request sent at `sent` and received at `received` (system clock times)
+#[cfg(feature = "native")]
+fn ntp_response_offset(
+    response: &[u8],
+    sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+) -> std::result::Result<chrono::Duration, String> {
+    if response.len() < NTP_PACKET_LEN {
+        return Err(format!("short NTP response ({} bytes)", response.len()));
+    }
+    let mode = response[0] & 0b111;
+    let stratum = response[1];
+    if mode != 4 || stratum == 0 {
+        return Err(format!("unusable NTP response (mode {}, stratum {})", mode, stratum));
+    }
+    let server_received = ntp_timestamp(response, 32).ok_or("NTP response has no receive timestamp")?;
+    let server_sent = ntp_timestamp(response, 40).ok_or("NTP response has no transmit timestamp")?;
+
+    // Standard SNTP offset, with the sign flipped so positive means the local clock is ahead
+    let server_offset = ((server_received - sent) + (server_sent - received)) / 2;
+    Ok(-server_offset)
+}
+
+/// Ask `server` (`host` or `host:port`, port 123 by default) how far the
+/// system clock is from true time; positive when the system clock is ahead
+#[cfg(feature = "native")]
+pub async fn ntp_offset(server: &str, timeout: Duration) -> crate::Result<chrono::Duration> {
+    use crate::error::AppError;
+    use tokio::net::UdpSocket;
+
+    let address = match server.parse::<std::net::IpAddr>() {
+        Ok(ip) => std::net::SocketAddr::new(ip, NTP_PORT).to_string(),
+        // host:port or [v6]:port
+        Err(_) if server.contains(':') => server.to_string(),
+        Err(_) => format!("{}:{}", server, NTP_PORT),
+    };
+
+    let exchange = async {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(&address).await?;
+        // The round trip is timed monotonically, so only the system clock's
+        // reading when the request leaves counts towards the offset
+        let clock = MonotonicClock::start();
+        socket.send(&ntp_request()).await?;
+        let mut response = [0u8; NTP_PACKET_LEN];
+        let len = socket.recv(&mut response).await?;
+        let received = clock.now();
+        Ok::<_, std::io::Error>((response, len, clock.started_at(), received))
+    };
+
+    let (response, len, sent, received) = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| AppError::timeout(format!("NTP server {} did not answer within {:?}", server, timeout)))?
+        .map_err(|e| AppError::network(format!("NTP query to {} failed: {}", server, e)))?;
+
+    ntp_response_offset(&response[..len], sent, received)
+        .map_err(|e| AppError::network(format!("NTP query to {} failed: {}", server, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monotonic_clock_timestamps() {
+        let clock = MonotonicClock::start();
+        let before = Instant::now();
+        std::thread::sleep(Duration::from_millis(5));
+        let now = clock.now();
+
+        assert!(now >= clock.started_at() + chrono::Duration::milliseconds(5));
+        assert!(clock.timestamp_of(before) <= now);
+        assert!(clock.drift().num_milliseconds().abs() < 1000);
+        assert!(timestamp() <= timestamp());
+
+        let report = ClockReport { drift_ms: -1500.0, ntp_server: Some("pool.ntp.org".to_string()), ntp_offset_ms: Some(12.25) };
+        assert!(report.was_adjusted());
+        assert!(!report.is_offset());
+        assert_eq!(report.to_string(), "drift -1500.0 ms | NTP offset +12.2 ms (pool.ntp.org)");
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_ntp_response_offset() {
+        fn write_timestamp(packet: &mut [u8], offset: usize, time: DateTime<Utc>) {
+            let seconds = (time.timestamp() + NTP_UNIX_OFFSET) as u32;
+            let fraction = ((u64::from(time.timestamp_subsec_nanos()) << 32) / 1_000_000_000) as u32;
+            packet[offset..offset + 4].copy_from_slice(&seconds.to_be_bytes());
+            packet[offset + 4..offset + 8].copy_from_slice(&fraction.to_be_bytes());
+        }
+
+        // The server is 2s behind the local clock and the round trip takes 100ms
+        let sent = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let received = sent + chrono::Duration::milliseconds(100);
+        let mut response = [0u8; NTP_PACKET_LEN];
+        response[0] = 0b00_100_100;
+        response[1] = 2;
+        write_timestamp(&mut response, 32, sent + chrono::Duration::milliseconds(50 - 2000));
+        write_timestamp(&mut response, 40, sent + chrono::Duration::milliseconds(50 - 2000));
+
+        let offset = ntp_response_offset(&response, sent, received).unwrap();
+        assert!((offset.num_milliseconds() - 2000).abs() <= 1);
+
+        response[1] = 0;
+        assert!(ntp_response_offset(&response, sent, received).unwrap_err().contains("stratum 0"));
+        assert!(ntp_response_offset(&response[..20], sent, received).is_err());
+        assert_eq!(ntp_request()[0] & 0b111, 3);
+    }
+}
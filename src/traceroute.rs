@@ -0,0 +1,175 @@
+//! The routers between the machine and a target
+//!
+//! A support ticket about slow or failing connections is usually answered
+//! with a request for a traceroute. [`Traceroute::run`] runs the platform's
+//! own tool (`traceroute`, `tracepath` when that is missing, or `tracert` on
+//! Windows) with numeric output and one probe per hop, and keeps each hop's
+//! address and round trip time, so that the trace travels with the results.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Hops to trace at most
+pub const MAX_HOPS: u8 = 30;
+/// How long to wait for each hop to answer
+const HOP_WAIT: Duration = Duration::from_secs(2);
+
+/// One router on the path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hop {
+    /// Distance from the machine, starting at 1
+    pub ttl: u8,
+    /// Address that answered; none when no probe was answered in time
+    pub address: Option<IpAddr>,
+    /// Fastest round trip of the hop's probes
+    pub rtt_ms: Option<f64>,
+}
+
+/// The path to one host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Traceroute {
+    pub host: String,
+    /// Address the trace was sent to
+    pub destination: Option<IpAddr>,
+    pub hops: Vec<Hop>,
+}
+
+impl Traceroute {
+    /// Trace the path to `host`
+    pub async fn run(host: &str) -> Result<Self> {
+        let max_hops = MAX_HOPS.to_string();
+        let wait_ms = HOP_WAIT.as_millis().to_string();
+        let wait = HOP_WAIT.as_secs().to_string();
+        let attempts: Vec<(&str, Vec<&str>)> = if cfg!(windows) {
+            vec![("tracert", vec!["-d", "-h", &max_hops, "-w", &wait_ms, host])]
+        } else {
+            vec![
+                ("traceroute", vec!["-n", "-q", "1", "-w", &wait, "-m", &max_hops, host]),
+                ("tracepath", vec!["-n", "-m", &max_hops, host]),
+            ]
+        };
+
+        // Every hop may time out, with three probes each for tracert
+        let limit = HOP_WAIT * 3 * MAX_HOPS as u32;
+        let mut failure = None;
+        for (program, args) in attempts {
+            match tokio::time::timeout(limit, Command::new(program).args(&args).output()).await {
+                Ok(Ok(output)) if output.status.success() || !output.stdout.is_empty() => {
+                    return Ok(Self::parse(host, &String::from_utf8_lossy(&output.stdout)));
+                }
+                Ok(Ok(output)) => failure = Some(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim())),
+                Ok(Err(e)) => failure = Some(format!("{} is not available: {}", program, e)),
+                Err(_) => failure = Some(format!("{} took longer than {}s", program, limit.as_secs())),
+            }
+        }
+        Err(AppError::network(format!("Traceroute to {} failed: {}", host, failure.unwrap_or_default())))
+    }
+
+    /// Read `traceroute -n`, `tracepath -n` or `tracert -d` output
+    pub fn parse(host: &str, output: &str) -> Self {
+        let mut lines = output.lines();
+        // e.g. `traceroute to example.com (93.184.215.14), 30 hops max` or `Tracing route to 93.184.215.14 over ...`
+        let destination = lines.clone()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|header| header.split_whitespace().find_map(|word| word.trim_matches(|c| "()[],".contains(c)).parse().ok()));
+
+        let mut hops: Vec<Hop> = Vec::new();
+        for line in lines.by_ref() {
+            let mut words = line.split_whitespace();
+            let Some(ttl) = words.next().and_then(|word| word.trim_end_matches(':').parse::<u8>().ok()) else { continue };
+            // tracepath reports a hop again when it learns more about the path
+            if hops.iter().any(|hop| hop.ttl == ttl) {
+                continue;
+            }
+            let words: Vec<&str> = words.collect();
+            let address = words.iter().find_map(|word| word.parse::<IpAddr>().ok());
+            let rtt_ms = words.iter().enumerate()
+                .filter_map(|(i, word)| match word.strip_suffix("ms") {
+                    Some("") => words.get(i.wrapping_sub(1)).copied(),
+                    Some(number) => Some(number),
+                    None => None,
+                })
+                .filter_map(|number| number.trim_start_matches('<').parse::<f64>().ok())
+                .min_by(f64::total_cmp);
+            hops.push(Hop { ttl, address, rtt_ms: address.and(rtt_ms) });
+        }
+        Self { host: host.to_string(), destination, hops }
+    }
+
+    /// Whether the trace got as far as the destination
+    pub fn reached(&self) -> bool {
+        self.destination.is_some() && self.hops.iter().any(|hop| hop.address == self.destination)
+    }
+
+    /// Hops up to and including the destination, without the unanswered
+    /// probes some tools keep sending after it
+    pub fn path(&self) -> &[Hop] {
+        match self.hops.iter().position(|hop| self.destination.is_some() && hop.address == self.destination) {
+            Some(last) => &self.hops[..=last],
+            None => &self.hops,
+        }
+    }
+}
+
+impl fmt::Display for Hop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.address, self.rtt_ms) {
+            (Some(address), Some(rtt_ms)) => write!(f, "{:>2}  {:<39} {:.1}ms", self.ttl, address, rtt_ms),
+            (Some(address), None) => write!(f, "{:>2}  {}", self.ttl, address),
+            (None, _) => write!(f, "{:>2}  *", self.ttl),
+        }
+    }
+}
+
+impl fmt::Display for Traceroute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = if self.reached() { "reached" } else { "destination not reached" };
+        write!(f, "{}: {} hops, {}", self.host, self.path().len(), outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_traceroute() {
+        let traceroute = "traceroute to example.com (93.184.215.14), 30 hops max, 60 byte packets\n \
+            1  192.168.1.1  0.512 ms\n \
+            2  *\n \
+            3  10.20.0.1  9.870 ms\n \
+            4  93.184.215.14  24.302 ms\n";
+        let trace = Traceroute::parse("example.com", traceroute);
+        assert_eq!(trace.destination, Some("93.184.215.14".parse().unwrap()));
+        assert_eq!(trace.hops.len(), 4);
+        assert_eq!(trace.hops[1], Hop { ttl: 2, address: None, rtt_ms: None });
+        assert_eq!(trace.hops[2].rtt_ms, Some(9.87));
+        assert!(trace.reached());
+        assert_eq!(trace.to_string(), "example.com: 4 hops, reached");
+
+        let tracert = "\nTracing route to 93.184.215.14 over a maximum of 30 hops\n\n  \
+            1    <1 ms     1 ms    <1 ms  192.168.1.1\n  \
+            2     *        *        *     Request timed out.\n  \
+            3    25 ms    24 ms    26 ms  93.184.215.14\n\nTrace complete.\n";
+        let trace = Traceroute::parse("93.184.215.14", tracert);
+        assert_eq!(trace.hops[0].rtt_ms, Some(1.0));
+        assert_eq!(trace.hops[2].rtt_ms, Some(24.0));
+        assert!(trace.reached());
+
+        let tracepath = " 1?: [LOCALHOST]                      pmtu 1500\n \
+            1:  192.168.1.1                                           0.612ms\n \
+            1:  192.168.1.1                                           0.498ms\n \
+            2:  no reply\n";
+        let trace = Traceroute::parse("example.com", tracepath);
+        assert_eq!(trace.hops, [
+            Hop { ttl: 1, address: Some("192.168.1.1".parse().unwrap()), rtt_ms: Some(0.612) },
+            Hop { ttl: 2, address: None, rtt_ms: None },
+        ]);
+        assert!(!trace.reached());
+        assert_eq!(trace.to_string(), "example.com: 2 hops, destination not reached");
+    }
+}
@@ -1,6 +1,9 @@
 //! Type definitions and aliases
 
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
 
@@ -16,10 +19,89 @@ pub enum DnsConfig {
     Custom { servers: Vec<IpAddr> },
     /// Use DNS-over-HTTPS with specified URL
     DoH { url: String },
+    /// Try resolvers in order, falling back to the next when one fails, as
+    /// operating system stub resolvers do
+    Chain { resolvers: Vec<DnsConfig> },
+    /// Ask the local network over multicast DNS for `.local` names, and the
+    /// system resolver for other names
+    Mdns,
 }
 
 impl DnsConfig {
-    /// Get a human-readable name for this DNS configuration
+    /// Stable identity of this configuration: `system`, the server addresses
+    /// joined with `+`, the DoH URL, the ids of a chain's resolvers joined
+    /// with `>`, or `mdns`
+    ///
+    /// Unlike display names it never depends on the locale or user labels, so
+    /// it is what labels are keyed by and what identifies a configuration
+    /// across runs.
+    pub fn id(&self) -> String {
+        match self {
+            DnsConfig::System => "system".to_string(),
+            DnsConfig::Custom { servers } => {
+                servers.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join("+")
+            }
+            DnsConfig::DoH { url } => url.clone(),
+            DnsConfig::Chain { resolvers } => {
+                resolvers.iter().map(DnsConfig::id).collect::<Vec<_>>().join(">")
+            }
+            DnsConfig::Mdns => "mdns".to_string(),
+        }
+    }
+
+    /// Parse a resolver chain written like its id, e.g.
+    /// `223.5.5.5>8.8.8.8>https://dns.google/dns-query`
+    ///
+    /// Each resolver is `system`, `mdns`, a DNS server address (several
+    /// joined with `+`) or an HTTPS DoH URL; a chain needs at least two.
+    pub fn parse_chain(spec: &str) -> Result<Self> {
+        let resolvers = spec.split('>')
+            .map(|resolver| Self::parse_chain_resolver(resolver.trim(), spec))
+            .collect::<Result<Vec<_>>>()?;
+        if resolvers.len() < 2 {
+            return Err(AppError::config(format!(
+                "Resolver chain '{}' needs at least two resolvers separated by '>'", spec
+            )));
+        }
+        Ok(DnsConfig::Chain { resolvers })
+    }
+
+    fn parse_chain_resolver(resolver: &str, spec: &str) -> Result<Self> {
+        let invalid = || AppError::config(format!(
+            "Invalid resolver '{}' in chain '{}': expected system, mdns, DNS server addresses or an HTTPS DoH URL",
+            resolver, spec
+        ));
+        if resolver.eq_ignore_ascii_case("system") {
+            return Ok(DnsConfig::System);
+        }
+        if resolver.eq_ignore_ascii_case("mdns") {
+            return Ok(DnsConfig::Mdns);
+        }
+        if resolver.contains("://") {
+            return match url::Url::parse(resolver) {
+                Ok(url) if url.scheme() == "https" => Ok(DnsConfig::DoH { url: resolver.to_string() }),
+                _ => Err(invalid()),
+            };
+        }
+        Self::parse_servers(resolver).map_err(|_| invalid())
+    }
+
+    /// Parse a DNS server entry: one address, or several joined with `+`
+    /// that make up one configuration
+    pub fn parse_servers(entry: &str) -> Result<Self> {
+        let servers = entry.split('+')
+            .map(|server| server.trim().parse::<IpAddr>().map_err(|e| {
+                AppError::config(format!("Invalid DNS server '{}' in '{}': {}", server.trim(), entry, e)).with_source(e)
+            }))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(DnsConfig::Custom { servers })
+    }
+
+    /// Get a short Chinese summary for console listings
+    ///
+    /// Different configurations can share a summary (two server lists of the
+    /// same length, two DoH URLs on one host), so results are named with
+    /// [`DisplayNames`] instead.
     pub fn name(&self) -> String {
         match self {
             DnsConfig::System => "系统默认".to_string(),
@@ -34,7 +116,7 @@ impl DnsConfig {
                 // Extract hostname from URL for display
                 if let Ok(parsed) = url::Url::parse(url) {
                     if let Some(host) = parsed.host_str() {
-                        format!("DoH ({})", host)
+                        format!("DoH ({})", crate::utils::url::display_domain(host))
                     } else {
                         "DoH".to_string()
                     }
@@ -42,8 +124,1076 @@ impl DnsConfig {
                     "DoH".to_string()
                 }
             }
+            DnsConfig::Chain { resolvers } => {
+                format!("解析链 ({})", resolvers.iter().map(DnsConfig::name).collect::<Vec<_>>().join(" → "))
+            }
+            DnsConfig::Mdns => "mDNS (局域网)".to_string(),
+        }
+    }
+}
+
+/// Language of the default DNS configuration names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// Simplified Chinese
+    Zh,
+}
+
+impl Locale {
+    /// Locale of the environment (`LC_ALL`, `LC_MESSAGES`, then `LANG`), English if unset or unsupported
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Locale {
+    type Err = AppError;
+
+    /// Parse `en`, `zh` or a POSIX locale such as `zh_CN.UTF-8`
+    fn from_str(s: &str) -> Result<Self> {
+        let language = s.split(['_', '-', '.', '@']).next().unwrap_or_default().to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Ok(Self::En),
+            "zh" => Ok(Self::Zh),
+            _ => Err(AppError::config(format!("Unsupported locale '{}' (supported: en, zh)", s))),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::En => write!(f, "en"),
+            Self::Zh => write!(f, "zh"),
+        }
+    }
+}
+
+/// How requests treat HTTP redirects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RedirectPolicy {
+    /// Follow redirects and measure up to the final endpoint
+    #[default]
+    Follow,
+    /// Measure the first response, even when it is a redirect
+    None,
+    /// Follow redirects, but fail a request that would leave HTTPS
+    HttpsOnly,
+}
+
+impl RedirectPolicy {
+    /// All policies, in the order they are documented
+    pub const ALL: [RedirectPolicy; 3] = [Self::Follow, Self::None, Self::HttpsOnly];
+
+    /// Whether redirects are followed at all
+    pub fn follows(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+
+    /// Why a redirect to `target` may not be followed, if it may not
+    pub fn refusal(&self, target: &url::Url) -> Option<String> {
+        match self {
+            Self::None => Some("redirects are not followed".to_string()),
+            Self::HttpsOnly if target.scheme() != "https" => Some(format!("redirect to {} leaves HTTPS", target)),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for RedirectPolicy {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::config(format!("Unknown redirect policy '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+impl fmt::Display for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Follow => write!(f, "follow"),
+            Self::None => write!(f, "none"),
+            Self::HttpsOnly => write!(f, "https-only"),
+        }
+    }
+}
+
+/// Metric the performance table ranks configurations by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortKey {
+    /// Mean total response time, fastest first
+    #[default]
+    Mean,
+    /// 95th percentile of total response time, fastest first
+    P95,
+    /// Success rate, most reliable first
+    Success,
+    /// Overall ranking score, highest first
+    Score,
+}
+
+impl SortKey {
+    /// All metrics, in the order they are documented
+    pub const ALL: [SortKey; 4] = [Self::Mean, Self::P95, Self::Success, Self::Score];
+}
+
+impl FromStr for SortKey {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::config(format!("Unknown sort metric '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mean => write!(f, "mean"),
+            Self::P95 => write!(f, "p95"),
+            Self::Success => write!(f, "success"),
+            Self::Score => write!(f, "score"),
+        }
+    }
+}
+
+/// Metric shown as a column of the performance table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetricColumn {
+    /// Mean DNS resolution time
+    Dns,
+    /// Mean TCP connect time
+    Tcp,
+    /// Mean time to first byte
+    Ttfb,
+    /// Mean total response time
+    Total,
+    /// Fastest and slowest total response time
+    MinMax,
+    /// 95th percentile of total response time
+    P95,
+    /// Success rate
+    Success,
+    /// Performance level of the mean total response time
+    Level,
+    /// Mean time to the first informational (1xx) response, with `--early-hints`
+    EarlyHints,
+    /// Mean ICMP round trip to the addresses the requests went to, with `--ping`
+    Ping,
+}
+
+impl MetricColumn {
+    /// All columns, in the order they are documented
+    pub const ALL: [MetricColumn; 10] = [
+        Self::Dns, Self::Tcp, Self::Ttfb, Self::Total, Self::MinMax, Self::P95, Self::Success, Self::Level, Self::EarlyHints, Self::Ping,
+    ];
+    /// Columns shown by default
+    pub const DEFAULT: [MetricColumn; 4] = [Self::Success, Self::Total, Self::MinMax, Self::Level];
+    /// Columns shown by default in verbose mode, with the phases of each request
+    pub const VERBOSE: [MetricColumn; 6] = [Self::Dns, Self::Tcp, Self::Ttfb, Self::Total, Self::P95, Self::Success];
+}
+
+impl FromStr for MetricColumn {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|column| column.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::config(format!("Unknown column '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+impl fmt::Display for MetricColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dns => write!(f, "dns"),
+            Self::Tcp => write!(f, "tcp"),
+            Self::Ttfb => write!(f, "ttfb"),
+            Self::Total => write!(f, "total"),
+            Self::MinMax => write!(f, "min-max"),
+            Self::P95 => write!(f, "p95"),
+            Self::Success => write!(f, "success"),
+            Self::Level => write!(f, "level"),
+            Self::EarlyHints => write!(f, "early-hints"),
+            Self::Ping => write!(f, "ping"),
+        }
+    }
+}
+
+/// Supported TLS versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    #[serde(rename = "1.2")]
+    V1_2,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    V1_3,
+}
+
+impl FromStr for TlsVersion {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().trim_start_matches("tls").trim_start_matches(['v', ' ']) {
+            "1.2" => Ok(Self::V1_2),
+            "1.3" => Ok(Self::V1_3),
+            _ => Err(AppError::config(format!("Unknown TLS version '{}'; expected one of: 1.2, 1.3", s.trim()))),
+        }
+    }
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V1_2 => write!(f, "1.2"),
+            Self::V1_3 => write!(f, "1.3"),
+        }
+    }
+}
+
+/// Protocol choices of the HTTP client, to isolate protocol-specific latency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolOptions {
+    /// Speak HTTP/2 from the first byte: h2c on `http://` URLs, and only `h2`
+    /// offered through ALPN on `https://` URLs
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+    /// Accept a connection on which ALPN settled on HTTP/1.1; when off, a
+    /// request not answered over HTTP/2 fails
+    #[serde(default = "default_alpn_fallback")]
+    pub alpn_fallback: bool,
+    /// Oldest TLS version to negotiate
+    #[serde(default)]
+    pub tls_min_version: Option<TlsVersion>,
+    /// Newest TLS version to negotiate
+    #[serde(default)]
+    pub tls_max_version: Option<TlsVersion>,
+    /// Resume TLS sessions from earlier connections, or never; when set the
+    /// clients use rustls and tell resumed handshakes from full ones
+    #[serde(default)]
+    pub tls_resumption: Option<bool>,
+}
+
+fn default_alpn_fallback() -> bool {
+    true
+}
+
+impl Default for ProtocolOptions {
+    fn default() -> Self {
+        Self {
+            http2_prior_knowledge: false,
+            alpn_fallback: default_alpn_fallback(),
+            tls_min_version: None,
+            tls_max_version: None,
+            tls_resumption: None,
+        }
+    }
+}
+
+impl ProtocolOptions {
+    /// Whether any option differs from the client's defaults
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fail when the TLS version range is empty
+    pub fn validate(&self) -> Result<()> {
+        match (self.tls_min_version, self.tls_max_version) {
+            (Some(min), Some(max)) if min > max => Err(AppError::config(format!(
+                "Minimum TLS version {} is newer than the maximum {}",
+                min, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ProtocolOptions {
+    /// Describe the options that differ from the defaults, e.g. "h2 prior knowledge, TLS 1.3"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.http2_prior_knowledge {
+            parts.push("h2 prior knowledge".to_string());
+        }
+        if !self.alpn_fallback {
+            parts.push("no ALPN fallback".to_string());
+        }
+        match (self.tls_min_version, self.tls_max_version) {
+            (Some(min), Some(max)) if min == max => parts.push(format!("TLS {}", min)),
+            (Some(min), Some(max)) => parts.push(format!("TLS {}-{}", min, max)),
+            (Some(min), None) => parts.push(format!("TLS {}+", min)),
+            (None, Some(max)) => parts.push(format!("TLS up to {}", max)),
+            (None, None) => {}
+        }
+        match self.tls_resumption {
+            Some(true) => parts.push("TLS resumption".to_string()),
+            Some(false) => parts.push("no TLS resumption".to_string()),
+            None => {}
+        }
+        if parts.is_empty() {
+            write!(f, "default")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// User-Agent and accompanying headers requests are sent with
+///
+/// Some CDNs route or serve differently by client, so a browser preset
+/// measures what a browser would get. `Accept-Encoding` stays the tool's own
+/// in every preset, so body sizes remain comparable.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UserAgent {
+    /// Identify as network-latency-tester
+    #[default]
+    Tool,
+    /// curl, which many servers answer like any command-line client
+    Curl,
+    /// Desktop Chrome on Windows, with its navigation headers
+    Chrome,
+    /// Safari on iPhone, with its navigation headers
+    MobileSafari,
+    /// A User-Agent string given by the user, sent with curl's headers
+    Custom(String),
+}
+
+impl UserAgent {
+    /// The presets, in the order they are documented
+    pub const PRESETS: [UserAgent; 4] = [Self::Tool, Self::Curl, Self::Chrome, Self::MobileSafari];
+
+    /// The `User-Agent` header value
+    pub fn header(&self) -> &str {
+        match self {
+            Self::Tool => concat!("network-latency-tester/", env!("CARGO_PKG_VERSION")),
+            Self::Curl => "curl/8.7.1",
+            Self::Chrome => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36",
+            Self::MobileSafari => "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 Mobile/15E148 Safari/604.1",
+            Self::Custom(user_agent) => user_agent,
+        }
+    }
+
+    /// Other headers the client sends, as lowercase name and value
+    pub fn headers(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Tool | Self::Curl | Self::Custom(_) => &[("accept", "*/*")],
+            Self::Chrome => &[
+                ("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8"),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("sec-ch-ua", "\"Google Chrome\";v=\"129\", \"Not=A?Brand\";v=\"8\", \"Chromium\";v=\"129\""),
+                ("sec-ch-ua-mobile", "?0"),
+                ("sec-ch-ua-platform", "\"Windows\""),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+                ("sec-fetch-user", "?1"),
+                ("upgrade-insecure-requests", "1"),
+            ],
+            Self::MobileSafari => &[
+                ("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("sec-fetch-dest", "document"),
+                ("sec-fetch-mode", "navigate"),
+                ("sec-fetch-site", "none"),
+            ],
+        }
+    }
+}
+
+impl FromStr for UserAgent {
+    type Err = AppError;
+
+    /// Parse a preset name, or take a value containing '/' or a space as a
+    /// custom User-Agent string
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(preset) = Self::PRESETS.into_iter().find(|preset| preset.to_string() == s.to_lowercase()) {
+            return Ok(preset);
+        }
+        if s.chars().any(char::is_control) {
+            return Err(AppError::config("User agent cannot contain control characters"));
+        }
+        if s.contains(['/', ' ']) {
+            return Ok(Self::Custom(s.to_string()));
+        }
+        let names: Vec<String> = Self::PRESETS.iter().map(ToString::to_string).collect();
+        Err(AppError::config(format!(
+            "Unknown user agent preset '{}'; expected one of: {}, or a User-Agent string such as 'my-probe/1.0'",
+            s,
+            names.join(", ")
+        )))
+    }
+}
+
+impl fmt::Display for UserAgent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tool => write!(f, "tool"),
+            Self::Curl => write!(f, "curl"),
+            Self::Chrome => write!(f, "chrome"),
+            Self::MobileSafari => write!(f, "mobile-safari"),
+            Self::Custom(user_agent) => write!(f, "{}", user_agent),
+        }
+    }
+}
+
+/// Resolves the names DNS configurations are reported under
+///
+/// A user label for the configuration's [`DnsConfig::id`] wins; otherwise the
+/// name is built in the configured locale and includes every server address
+/// or the full DoH URL, so distinct configurations never share a name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DisplayNames {
+    /// Language of the default names
+    #[serde(default)]
+    pub locale: Locale,
+    /// User labels keyed by configuration id
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl DisplayNames {
+    /// Default names in `locale`, without labels
+    pub fn new(locale: Locale) -> Self {
+        Self { locale, labels: BTreeMap::new() }
+    }
+
+    /// Use `labels`, keyed by configuration id, in place of the default names
+    pub fn with_labels(mut self, labels: BTreeMap<String, String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Name to report `dns_config` under
+    pub fn resolve(&self, dns_config: &DnsConfig) -> String {
+        if let Some(label) = self.labels.get(&dns_config.id()) {
+            return label.clone();
+        }
+
+        let servers = |servers: &[IpAddr]| servers.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(",");
+        match (self.locale, dns_config) {
+            (Locale::En, DnsConfig::System) => "System DNS".to_string(),
+            (Locale::Zh, DnsConfig::System) => "系统默认".to_string(),
+            (Locale::En, DnsConfig::Custom { servers: list }) => format!("Custom DNS ({})", servers(list)),
+            (Locale::Zh, DnsConfig::Custom { servers: list }) => format!("自定义DNS ({})", servers(list)),
+            (_, DnsConfig::DoH { url }) => format!("DoH ({})", url),
+            (Locale::En, DnsConfig::Mdns) => "mDNS (LAN)".to_string(),
+            (Locale::Zh, DnsConfig::Mdns) => "mDNS (局域网)".to_string(),
+            (locale, DnsConfig::Chain { resolvers }) => {
+                let resolvers: Vec<_> = resolvers.iter().map(|resolver| self.resolve(resolver)).collect();
+                match locale {
+                    Locale::En => format!("Chain ({})", resolvers.join(" → ")),
+                    Locale::Zh => format!("解析链 ({})", resolvers.join(" → ")),
+                }
+            }
         }
     }
+
+    /// Parse labels given as comma-separated `ID=LABEL` pairs, e.g.
+    /// `8.8.8.8=Google,https://cloudflare-dns.com/dns-query=Cloudflare`
+    pub fn parse_labels(s: &str) -> Result<BTreeMap<String, String>> {
+        let mut labels = BTreeMap::new();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (id, label) = Self::parse_label(pair)?;
+            labels.insert(id, label);
+        }
+        Ok(labels)
+    }
+
+    /// Parse a single `ID=LABEL` pair
+    pub fn parse_label(pair: &str) -> Result<(String, String)> {
+        let (id, label) = pair.rsplit_once('=')
+            .map(|(id, label)| (id.trim(), label.trim()))
+            .filter(|(id, label)| !id.is_empty() && !label.is_empty())
+            .ok_or_else(|| AppError::config(format!("Invalid DNS label '{}': expected ID=LABEL", pair)))?;
+        Ok((id.to_string(), label.to_string()))
+    }
+}
+
+/// One of `count` processes sharing a target list, written `index/count` with `index` from 1
+///
+/// Every shard sees the same list, deduplicates and sorts it, and takes every
+/// `count`-th URL starting at position `index`. The split therefore does not
+/// depend on the order in which each machine lists its targets, and shard
+/// sizes differ by at most one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Shard {
+    /// Position of this shard, from 1 to `count`
+    pub index: u32,
+    /// Number of shards
+    pub count: u32,
+}
+
+impl Shard {
+    /// Create shard `index` of `count`, both counted from 1
+    pub fn new(index: u32, count: u32) -> Result<Self> {
+        if count == 0 || index == 0 || index > count {
+            return Err(AppError::config(format!(
+                "Invalid shard {}/{}: expected i/n with 1 <= i <= n",
+                index, count
+            )));
+        }
+        Ok(Self { index, count })
+    }
+
+    /// This shard's share of `urls`, in their original order
+    pub fn select(&self, urls: &[String]) -> Vec<String> {
+        let mut all: Vec<&str> = urls.iter().map(String::as_str).collect();
+        all.sort_unstable();
+        all.dedup();
+        let mut mine: HashSet<&str> = all
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| *position as u64 % u64::from(self.count) == u64::from(self.index - 1))
+            .map(|(_, url)| url)
+            .collect();
+
+        // Removing each URL as it is taken also drops duplicates
+        urls.iter().filter(|url| mine.remove(url.as_str())).cloned().collect()
+    }
+}
+
+impl FromStr for Shard {
+    type Err = AppError;
+
+    /// Parse `i/n`, e.g. `2/4`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || AppError::config(format!("Invalid shard '{}': expected i/n, e.g. 2/4", s));
+        let (index, count) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let index = index.trim().parse().map_err(|_| invalid())?;
+        let count = count.trim().parse().map_err(|_| invalid())?;
+        Self::new(index, count)
+    }
+}
+
+impl TryFrom<String> for Shard {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Shard> for String {
+    fn from(shard: Shard) -> Self {
+        shard.to_string()
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// What requests are sent from, as given to `--bind`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum BindTarget {
+    /// A local address; connections leave through whichever interface has it
+    Address(IpAddr),
+    /// A network interface by name, e.g. `eth0` or `en0` (Linux and macOS)
+    Interface(String),
+}
+
+impl FromStr for BindTarget {
+    type Err = AppError;
+
+    /// An IP address, or else an interface name
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Ok(address) = s.parse() {
+            return Ok(BindTarget::Address(address));
+        }
+        // Interface names are at most 15 bytes on Linux (IFNAMSIZ) and never contain spaces
+        if s.is_empty() || s.len() > 15 || s.contains(char::is_whitespace) || s.contains('/') {
+            return Err(AppError::config(format!(
+                "Invalid bind target '{}': expected a network interface name or a local IP address", s
+            )));
+        }
+        Ok(BindTarget::Interface(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for BindTarget {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<BindTarget> for String {
+    fn from(bind: BindTarget) -> Self {
+        bind.to_string()
+    }
+}
+
+impl fmt::Display for BindTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindTarget::Address(address) => write!(f, "{}", address),
+            BindTarget::Interface(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// HTTP method of an upload request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UploadMethod {
+    #[default]
+    Post,
+    Put,
+}
+
+impl fmt::Display for UploadMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadMethod::Post => write!(f, "post"),
+            UploadMethod::Put => write!(f, "put"),
+        }
+    }
+}
+
+/// Generated request body to upload, as given to `--upload`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Upload {
+    pub method: UploadMethod,
+    /// Size of the body
+    pub bytes: u64,
+}
+
+impl FromStr for Upload {
+    type Err = AppError;
+
+    /// `[post|put:]SIZE`, e.g. `256KiB` or `put:10MB`
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (method, size) = match s.split_once(':') {
+            Some((method, size)) => match method.trim().to_lowercase().as_str() {
+                "post" => (UploadMethod::Post, size),
+                "put" => (UploadMethod::Put, size),
+                other => return Err(AppError::config(format!("Unknown upload method '{}'; expected post or put", other))),
+            },
+            None => (UploadMethod::Post, s),
+        };
+        let bytes = crate::utils::size::parse_bytes(size)?;
+        if bytes == 0 || bytes > crate::defaults::MAX_UPLOAD_BYTES {
+            return Err(AppError::config(format!(
+                "Upload size must be between 1 byte and {}, got '{}'",
+                crate::utils::size::format_bytes(crate::defaults::MAX_UPLOAD_BYTES as f64), size.trim()
+            )));
+        }
+        Ok(Upload { method, bytes })
+    }
+}
+
+impl TryFrom<String> for Upload {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Upload> for String {
+    fn from(upload: Upload) -> Self {
+        upload.to_string()
+    }
+}
+
+impl fmt::Display for Upload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The largest binary unit the size is a whole multiple of, so the text parses back
+        let (size, unit) = [(1 << 30, "GiB"), (1 << 20, "MiB"), (1 << 10, "KiB")]
+            .into_iter()
+            .find(|(unit, _)| self.bytes.is_multiple_of(*unit))
+            .map_or((self.bytes, "B"), |(unit, name)| (self.bytes / unit, name));
+        write!(f, "{}:{}{}", self.method, size, unit)
+    }
+}
+
+/// How the host name of a DoH provider is looked up, as given to `--doh-bootstrap`
+///
+/// A DoH URL names a host that has to be resolved before the first query can
+/// be sent; left to happen on the first query, that lookup is timed as part of
+/// it. Bootstrapping resolves the host up front instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DohBootstrap {
+    /// Look the host up through the system resolver before any query
+    System,
+    /// Send the queries to this address without looking the host up
+    Pinned(IpAddr),
+}
+
+impl DohBootstrap {
+    /// Parse bootstraps given as comma-separated `PROVIDER=IP` or
+    /// `PROVIDER=system` pairs, keyed by the provider's host name
+    pub fn parse_list(s: &str) -> Result<BTreeMap<String, Self>> {
+        let mut bootstraps = BTreeMap::new();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (host, bootstrap) = Self::parse_pair(pair)?;
+            bootstraps.insert(host, bootstrap);
+        }
+        Ok(bootstraps)
+    }
+
+    /// Parse a single `PROVIDER=IP` or `PROVIDER=system` pair, where PROVIDER
+    /// is a DoH URL or its host name
+    pub fn parse_pair(pair: &str) -> Result<(String, Self)> {
+        let invalid = || AppError::config(format!("Invalid DoH bootstrap '{}': expected PROVIDER=IP or PROVIDER=system", pair));
+        let (provider, target) = pair.rsplit_once('=').ok_or_else(invalid)?;
+        let host = Self::provider_host(provider.trim()).ok_or_else(invalid)?;
+        Ok((host, target.parse()?))
+    }
+
+    /// Host name of a DoH provider given as a URL or as the host name itself
+    pub fn provider_host(provider: &str) -> Option<String> {
+        let host = if provider.contains("://") {
+            url::Url::parse(provider).ok()?.host_str()?.to_string()
+        } else {
+            provider.to_string()
+        };
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        (!host.is_empty() && !host.contains(['/', ':', '=', ' '])).then_some(host)
+    }
+}
+
+impl FromStr for DohBootstrap {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("system") {
+            return Ok(Self::System);
+        }
+        s.trim_matches(|c| c == '[' || c == ']').parse().map(Self::Pinned).map_err(|_| {
+            AppError::config(format!("Invalid DoH bootstrap target '{}': expected an IP address or 'system'", s))
+        })
+    }
+}
+
+impl TryFrom<String> for DohBootstrap {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<DohBootstrap> for String {
+    fn from(bootstrap: DohBootstrap) -> Self {
+        bootstrap.to_string()
+    }
+}
+
+impl fmt::Display for DohBootstrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DohBootstrap::System => write!(f, "system"),
+            DohBootstrap::Pinned(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+/// How a DNS configuration with several servers spreads its lookups over them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerStrategy {
+    /// Ask the servers in order, each only when the ones before it failed
+    #[default]
+    First,
+    /// Start each lookup at the next server in turn
+    RoundRobin,
+    /// Ask every server at once and take the first answer
+    RaceAll,
+    /// Start each lookup at a server picked at random
+    Random,
+}
+
+impl ServerStrategy {
+    /// All strategies, in the order they are documented
+    pub const ALL: [ServerStrategy; 4] = [Self::First, Self::RoundRobin, Self::RaceAll, Self::Random];
+}
+
+impl FromStr for ServerStrategy {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|strategy| strategy.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::ALL.iter().map(ToString::to_string).collect();
+                AppError::config(format!("Unknown server strategy '{}'; expected one of: {}", s.trim(), names.join(", ")))
+            })
+    }
+}
+
+impl fmt::Display for ServerStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "first"),
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::RaceAll => write!(f, "race-all"),
+            Self::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// Resolver settings of a DNS configuration, written like the `options` line
+/// of resolv.conf: `timeout:2 attempts:3 rotate cache:0`
+///
+/// Unset settings keep the resolver's defaults, which for the system resolver
+/// are the ones read from the system configuration. A `strategy` makes the
+/// tool spread the lookups of a configuration with several servers itself,
+/// timing each server, instead of leaving the servers to the resolver.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResolverOptions {
+    /// How long to wait for an answer to each query
+    pub timeout: Option<Duration>,
+    /// How often to retry a failed lookup
+    pub attempts: Option<usize>,
+    /// Spread queries over the servers instead of asking the first one first
+    pub rotate: bool,
+    /// Answers to cache; 0 sends every lookup to the servers
+    pub cache_size: Option<usize>,
+    /// How lookups are spread over several servers
+    pub strategy: Option<ServerStrategy>,
+}
+
+impl ResolverOptions {
+    /// Parse options given as comma-separated `ID=OPTIONS` pairs, keyed by
+    /// the id of the DNS configuration: `system` or its servers joined by `+`
+    pub fn parse_list(s: &str) -> Result<BTreeMap<String, Self>> {
+        let mut options = BTreeMap::new();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (id, pair_options) = Self::parse_pair(pair)?;
+            options.insert(id, pair_options);
+        }
+        Ok(options)
+    }
+
+    /// Parse a single `ID=OPTIONS` pair
+    pub fn parse_pair(pair: &str) -> Result<(String, Self)> {
+        let (id, options) = pair.split_once('=')
+            .ok_or_else(|| AppError::config(format!("Invalid resolver options '{}': expected ID=OPTIONS", pair)))?;
+        let id = id.trim();
+        let id = if id.eq_ignore_ascii_case("system") {
+            DnsConfig::System.id()
+        } else {
+            let servers = id.split('+')
+                .map(|server| server.trim().parse::<IpAddr>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| AppError::config(format!(
+                    "Invalid resolver options '{}': ID must be 'system' or DNS server addresses joined by '+'", pair
+                )))?;
+            DnsConfig::Custom { servers }.id()
+        };
+        Ok((id, options.parse()?))
+    }
+}
+
+impl FromStr for ResolverOptions {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for option in s.split_whitespace() {
+            let invalid = |reason: &str| AppError::config(format!("Invalid resolver option '{}': {}", option, reason));
+            let count = |value: &str| value.parse::<usize>().map_err(|_| invalid("expected a whole number"));
+            match option.split_once(':') {
+                Some(("timeout", value)) => {
+                    let timeout = crate::utils::duration::parse_duration(value)?;
+                    if timeout.is_zero() {
+                        return Err(invalid("the timeout must be longer than zero"));
+                    }
+                    options.timeout = Some(timeout);
+                }
+                Some(("attempts", value)) => options.attempts = Some(count(value)?),
+                Some(("cache", value)) => options.cache_size = Some(count(value)?),
+                Some(("strategy", value)) => options.strategy = Some(value.parse()?),
+                None if option == "rotate" => options.rotate = true,
+                _ => return Err(invalid("expected timeout:DURATION, attempts:N, rotate, cache:N or strategy:NAME")),
+            }
+        }
+        if options == Self::default() {
+            return Err(AppError::config(format!("No resolver options in '{}'", s.trim())));
+        }
+        Ok(options)
+    }
+}
+
+impl TryFrom<String> for ResolverOptions {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<ResolverOptions> for String {
+    fn from(options: ResolverOptions) -> Self {
+        options.to_string()
+    }
+}
+
+impl fmt::Display for ResolverOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = Vec::new();
+        if let Some(timeout) = self.timeout {
+            options.push(match timeout.subsec_millis() {
+                0 => format!("timeout:{}s", timeout.as_secs()),
+                _ => format!("timeout:{}ms", timeout.as_millis()),
+            });
+        }
+        if let Some(attempts) = self.attempts {
+            options.push(format!("attempts:{}", attempts));
+        }
+        if self.rotate {
+            options.push("rotate".to_string());
+        }
+        if let Some(cache_size) = self.cache_size {
+            options.push(format!("cache:{}", cache_size));
+        }
+        if let Some(strategy) = self.strategy {
+            options.push(format!("strategy:{}", strategy));
+        }
+        write!(f, "{}", options.join(" "))
+    }
+}
+
+/// Parse a setting that is `on` or `off` (also `true` or `false`)
+pub fn parse_switch(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "on" | "true" => Some(true),
+        "off" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Connection pool settings of the HTTP clients
+///
+/// The defaults reuse connections for 90 seconds and keep up to ten idle
+/// per host, which hides the TCP and TLS handshakes that a cold-path
+/// measurement is after, while a watch session at high concurrency may want
+/// more idle connections kept for longer. Parsed from space-separated
+/// options such as `idle-per-host:0 keepalive:off nodelay:off`; unset ones
+/// keep the values chosen for the run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PoolOptions {
+    /// Idle connections kept per host; 0 opens a new connection for every request
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept; `Some(None)` keeps it until the server closes it
+    pub idle_timeout: Option<Option<Duration>>,
+    /// Interval of TCP keep-alive probes; `Some(None)` sends none
+    pub tcp_keepalive: Option<Option<Duration>>,
+    /// Send small writes at once instead of coalescing them (Nagle's algorithm off)
+    pub tcp_nodelay: Option<bool>,
+}
+
+impl FromStr for PoolOptions {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for option in s.split_whitespace() {
+            let invalid = |reason: &str| AppError::config(format!("Invalid pool option '{}': {}", option, reason));
+            // A duration above zero, or `off`
+            let interval = |value: &str| -> Result<Option<Duration>> {
+                if value.eq_ignore_ascii_case("off") {
+                    return Ok(None);
+                }
+                match crate::utils::duration::parse_duration(value)? {
+                    duration if duration.is_zero() => Err(invalid("use off instead of a zero duration")),
+                    duration => Ok(Some(duration)),
+                }
+            };
+            match option.split_once(':') {
+                Some(("idle-per-host", value)) => {
+                    options.max_idle_per_host = Some(value.parse().map_err(|_| invalid("expected a whole number"))?);
+                }
+                Some(("idle-timeout", value)) => options.idle_timeout = Some(interval(value)?),
+                Some(("keepalive", value)) => options.tcp_keepalive = Some(interval(value)?),
+                Some(("nodelay", value)) => {
+                    options.tcp_nodelay = Some(parse_switch(value).ok_or_else(|| invalid("expected on or off"))?);
+                }
+                _ => return Err(invalid("expected idle-per-host:N, idle-timeout:DURATION, keepalive:DURATION or nodelay:on|off")),
+            }
+        }
+        if options == Self::default() {
+            return Err(AppError::config(format!("No pool options in '{}'", s.trim())));
+        }
+        Ok(options)
+    }
+}
+
+impl TryFrom<String> for PoolOptions {
+    type Error = AppError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<PoolOptions> for String {
+    fn from(options: PoolOptions) -> Self {
+        options.to_string()
+    }
+}
+
+impl fmt::Display for PoolOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let interval = |value: Option<Duration>| value.map_or_else(|| "off".to_string(), crate::utils::duration::format_duration);
+        let mut options = Vec::new();
+        if let Some(idle) = self.max_idle_per_host {
+            options.push(format!("idle-per-host:{}", idle));
+        }
+        if let Some(timeout) = self.idle_timeout {
+            options.push(format!("idle-timeout:{}", interval(timeout)));
+        }
+        if let Some(keepalive) = self.tcp_keepalive {
+            options.push(format!("keepalive:{}", interval(keepalive)));
+        }
+        if let Some(nodelay) = self.tcp_nodelay {
+            options.push(format!("nodelay:{}", if nodelay { "on" } else { "off" }));
+        }
+        write!(f, "{}", options.join(" "))
+    }
 }
 
 /// Performance classification based on timing results
@@ -82,4 +1232,217 @@ pub enum TestStatus {
     Skipped,
     /// Test timed out
     Timeout,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_names() {
+        let google = DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap()] };
+        let pair = DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap(), "1.1.1.1".parse().unwrap()] };
+        let doh = DnsConfig::DoH { url: "https://dns.google/dns-query".to_string() };
+        assert_eq!(DnsConfig::System.id(), "system");
+        assert_eq!(pair.id(), "8.8.8.8+1.1.1.1");
+        assert_eq!(doh.id(), "https://dns.google/dns-query");
+
+        let english = DisplayNames::default();
+        assert_eq!(english.resolve(&DnsConfig::System), "System DNS");
+        assert_eq!(english.resolve(&pair), "Custom DNS (8.8.8.8,1.1.1.1)");
+        assert_eq!(english.resolve(&doh), "DoH (https://dns.google/dns-query)");
+        assert_eq!(english.resolve(&DnsConfig::Mdns), "mDNS (LAN)");
+
+        let labels = DisplayNames::parse_labels("8.8.8.8=Google, system = ISP").unwrap();
+        let chinese = DisplayNames::new(Locale::Zh).with_labels(labels);
+        assert_eq!(chinese.resolve(&google), "Google");
+        assert_eq!(chinese.resolve(&DnsConfig::System), "ISP");
+        assert_eq!(chinese.resolve(&pair), "自定义DNS (8.8.8.8,1.1.1.1)");
+        assert!(DisplayNames::parse_labels("8.8.8.8").is_err());
+        assert!(DisplayNames::parse_labels("8.8.8.8=").is_err());
+
+        // A chain is named after its resolvers, labels included
+        let chain = DnsConfig::parse_chain("8.8.8.8 > system").unwrap();
+        assert_eq!(chain.id(), "8.8.8.8>system");
+        assert_eq!(chinese.resolve(&chain), "解析链 (Google → ISP)");
+        assert_eq!(english.resolve(&chain), "Chain (Custom DNS (8.8.8.8) → System DNS)");
+
+        assert_eq!("zh_CN.UTF-8".parse::<Locale>().unwrap(), Locale::Zh);
+        assert_eq!("en-US".parse::<Locale>().unwrap(), Locale::En);
+        assert!("fr".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_parse_resolver_chain() {
+        let chain = DnsConfig::parse_chain("223.5.5.5>8.8.8.8+8.8.4.4>https://dns.google/dns-query").unwrap();
+        assert_eq!(chain, DnsConfig::Chain { resolvers: vec![
+            DnsConfig::Custom { servers: vec!["223.5.5.5".parse().unwrap()] },
+            DnsConfig::Custom { servers: vec!["8.8.8.8".parse().unwrap(), "8.8.4.4".parse().unwrap()] },
+            DnsConfig::DoH { url: "https://dns.google/dns-query".to_string() },
+        ] });
+        // The id reads back as the same chain
+        assert_eq!(DnsConfig::parse_chain(&chain.id()).unwrap(), chain);
+
+        assert_eq!(DnsConfig::parse_chain("mdns>system").unwrap().id(), "mdns>system");
+        assert!(DnsConfig::parse_chain("8.8.8.8").is_err());
+        assert!(DnsConfig::parse_chain("8.8.8.8>").is_err());
+        assert!(DnsConfig::parse_chain("8.8.8.8>dns.google").is_err());
+        assert!(DnsConfig::parse_chain("8.8.8.8>http://dns.google/dns-query").is_err());
+    }
+
+    #[test]
+    fn test_shard_selection() {
+        let urls: Vec<String> = ["https://e.example", "https://a.example", "https://d.example", "https://b.example", "https://c.example"]
+            .iter()
+            .map(|url| url.to_string())
+            .collect();
+        let shards: Vec<Shard> = (1..=2).map(|index| Shard::new(index, 2).unwrap()).collect();
+        assert_eq!(shards[0].select(&urls), ["https://e.example", "https://a.example", "https://c.example"]);
+        assert_eq!(shards[1].select(&urls), ["https://d.example", "https://b.example"]);
+
+        // Another machine listing the targets in a different order gets the same split
+        let mut reordered = urls.clone();
+        reordered.reverse();
+        reordered.push("https://a.example".to_string());
+        assert_eq!(shards[1].select(&reordered), ["https://b.example", "https://d.example"]);
+
+        assert_eq!("2/4".parse::<Shard>().unwrap(), Shard { index: 2, count: 4 });
+        assert_eq!(Shard::new(3, 4).unwrap().to_string(), "3/4");
+        for invalid in ["0/4", "5/4", "1/0", "2", "a/b"] {
+            assert!(invalid.parse::<Shard>().is_err(), "{}", invalid);
+        }
+        assert_eq!(serde_json::to_string(&shards[1]).unwrap(), "\"2/2\"");
+    }
+
+    #[test]
+    fn test_bind_target() {
+        assert_eq!("192.168.1.20".parse::<BindTarget>().unwrap(), BindTarget::Address("192.168.1.20".parse().unwrap()));
+        assert_eq!(" wlan0 ".parse::<BindTarget>().unwrap(), BindTarget::Interface("wlan0".to_string()));
+        for invalid in ["", "Wi Fi", "eth0/1", "a-very-long-interface-name"] {
+            assert!(invalid.parse::<BindTarget>().is_err(), "{}", invalid);
+        }
+        assert_eq!(serde_json::to_string(&BindTarget::Interface("en0".to_string())).unwrap(), "\"en0\"");
+    }
+
+    #[test]
+    fn test_upload() {
+        assert_eq!("64KiB".parse::<Upload>().unwrap(), Upload { method: UploadMethod::Post, bytes: 65536 });
+        assert_eq!("PUT:1MB".parse::<Upload>().unwrap(), Upload { method: UploadMethod::Put, bytes: 1_000_000 });
+        for invalid in ["0", "patch:1KiB", "1TiB", "lots"] {
+            assert!(invalid.parse::<Upload>().is_err(), "{}", invalid);
+        }
+        assert_eq!(Upload { method: UploadMethod::Put, bytes: 2 << 20 }.to_string(), "put:2MiB");
+        assert_eq!(Upload { method: UploadMethod::Post, bytes: 1500 }.to_string(), "post:1500B");
+        assert_eq!(serde_json::to_string(&"put:3KiB".parse::<Upload>().unwrap()).unwrap(), "\"put:3KiB\"");
+    }
+
+    #[test]
+    fn test_doh_bootstrap() {
+        let bootstraps = DohBootstrap::parse_list("https://Cloudflare-DNS.com/dns-query=1.1.1.1, dns.google=system,doh.example=[2001:db8::1]").unwrap();
+        assert_eq!(bootstraps["cloudflare-dns.com"], DohBootstrap::Pinned("1.1.1.1".parse().unwrap()));
+        assert_eq!(bootstraps["dns.google"], DohBootstrap::System);
+        assert_eq!(bootstraps["doh.example"].to_string(), "2001:db8::1");
+        for invalid in ["dns.google", "dns.google=resolver", "=1.1.1.1", "https://=1.1.1.1"] {
+            assert!(DohBootstrap::parse_pair(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_resolver_options() {
+        let options = ResolverOptions::parse_list("system=timeout:1 attempts:1, 8.8.8.8 + 1.1.1.1=timeout:500ms rotate cache:0").unwrap();
+        assert_eq!(options["system"], ResolverOptions { timeout: Some(Duration::from_secs(1)), attempts: Some(1), ..Default::default() });
+        let custom = &options["8.8.8.8+1.1.1.1"];
+        assert!(custom.rotate);
+        assert_eq!(custom.cache_size, Some(0));
+        assert_eq!(custom.to_string(), "timeout:500ms rotate cache:0");
+        let (_, options) = ResolverOptions::parse_pair("8.8.8.8+1.1.1.1=strategy:Race-All").unwrap();
+        assert_eq!(options.strategy, Some(ServerStrategy::RaceAll));
+        assert_eq!(options.to_string(), "strategy:race-all");
+        for invalid in ["system", "dns.google=timeout:1", "system=", "system=timeout:0", "system=attempts:many", "system=ndots:2", "system=strategy:fastest"] {
+            assert!(ResolverOptions::parse_pair(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_pool_options() {
+        let options: PoolOptions = "idle-per-host:0 keepalive:off nodelay:off".parse().unwrap();
+        assert_eq!(options, PoolOptions {
+            max_idle_per_host: Some(0),
+            tcp_keepalive: Some(None),
+            tcp_nodelay: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(options.to_string(), "idle-per-host:0 keepalive:off nodelay:off");
+        let options: PoolOptions = "idle-timeout:5m keepalive:30s".parse().unwrap();
+        assert_eq!(options.idle_timeout, Some(Some(Duration::from_secs(300))));
+        assert_eq!(serde_json::to_string(&options).unwrap(), "\"idle-timeout:5m keepalive:30s\"");
+        for invalid in ["", "idle-per-host:many", "keepalive:0s", "nodelay:maybe", "max-connections:10"] {
+            assert!(invalid.parse::<PoolOptions>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_redirect_policy() {
+        let http = url::Url::parse("http://example.com/").unwrap();
+        let https = url::Url::parse("https://example.com/").unwrap();
+        assert_eq!("HTTPS-Only".parse::<RedirectPolicy>().unwrap(), RedirectPolicy::HttpsOnly);
+        assert!("manual".parse::<RedirectPolicy>().unwrap_err().message().contains("follow, none, https-only"));
+        assert_eq!(RedirectPolicy::Follow.refusal(&http), None);
+        assert_eq!(RedirectPolicy::HttpsOnly.refusal(&https), None);
+        assert!(RedirectPolicy::HttpsOnly.refusal(&http).unwrap().contains("leaves HTTPS"));
+        assert!(!RedirectPolicy::None.follows());
+        assert_eq!(serde_json::to_string(&RedirectPolicy::HttpsOnly).unwrap(), "\"https-only\"");
+    }
+
+    #[test]
+    fn test_sort_key() {
+        assert_eq!("P95".parse::<SortKey>().unwrap(), SortKey::P95);
+        assert_eq!(" score ".parse::<SortKey>().unwrap(), SortKey::Score);
+        assert!("median".parse::<SortKey>().unwrap_err().message().contains("mean, p95, success, score"));
+        assert_eq!(SortKey::default(), SortKey::Mean);
+    }
+
+    #[test]
+    fn test_metric_column() {
+        assert_eq!("TTFB".parse::<MetricColumn>().unwrap(), MetricColumn::Ttfb);
+        assert_eq!("min-max".parse::<MetricColumn>().unwrap(), MetricColumn::MinMax);
+        assert!("tls".parse::<MetricColumn>().unwrap_err().message().contains("dns, tcp, ttfb, total, min-max, p95, success, level"));
+    }
+
+    #[test]
+    fn test_protocol_options() {
+        assert_eq!("TLSv1.3".parse::<TlsVersion>().unwrap(), TlsVersion::V1_3);
+        assert_eq!("1.2".parse::<TlsVersion>().unwrap(), TlsVersion::V1_2);
+        assert!("1.1".parse::<TlsVersion>().is_err());
+
+        let mut options = ProtocolOptions::default();
+        assert!(options.is_default());
+        assert_eq!(options.to_string(), "default");
+        options.http2_prior_knowledge = true;
+        options.tls_min_version = Some(TlsVersion::V1_3);
+        options.tls_max_version = Some(TlsVersion::V1_3);
+        assert_eq!(options.to_string(), "h2 prior knowledge, TLS 1.3");
+        assert!(options.validate().is_ok());
+        options.tls_min_version = Some(TlsVersion::V1_3);
+        options.tls_max_version = Some(TlsVersion::V1_2);
+        assert!(options.validate().is_err());
+        assert_eq!(serde_json::to_string(&TlsVersion::V1_2).unwrap(), "\"1.2\"");
+    }
+
+    #[test]
+    fn test_user_agent_presets() {
+        assert_eq!("Mobile-Safari".parse::<UserAgent>().unwrap(), UserAgent::MobileSafari);
+        assert_eq!("my-probe/1.0".parse::<UserAgent>().unwrap(), UserAgent::Custom("my-probe/1.0".to_string()));
+        assert!("firefox".parse::<UserAgent>().unwrap_err().message().contains("tool, curl, chrome, mobile-safari"));
+        assert!("bad/\nagent".parse::<UserAgent>().is_err());
+
+        assert!(UserAgent::Tool.header().starts_with("network-latency-tester/"));
+        assert!(UserAgent::Chrome.headers().iter().any(|(name, _)| *name == "sec-ch-ua"));
+        // Every preset keeps the tool's own Accept-Encoding
+        for preset in UserAgent::PRESETS {
+            assert!(preset.headers().iter().all(|(name, _)| *name != "accept-encoding"));
+            assert_eq!(preset.to_string().parse::<UserAgent>().unwrap(), preset);
+        }
+        assert_eq!(serde_json::to_string(&UserAgent::MobileSafari).unwrap(), "\"mobile-safari\"");
+    }
+}
@@ -8,7 +8,7 @@ use crate::updater::types::Release;
 use crate::{AppError, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Default cache expiration time (24 hours)
@@ -135,14 +135,14 @@ impl CacheManager {
                     eprintln!("[CACHE] Creating cache directory: {}", parent.display());
                 }
                 fs::create_dir_all(parent)
-                    .map_err(|e| AppError::cache(format!("Failed to create cache directory '{}': {}", parent.display(), e)))?;
+                    .map_err(|e| AppError::cache(format!("Failed to create cache directory '{}': {}", parent.display(), e)).with_source(e))?;
             }
         }
         Ok(())
     }
 
-    /// Load cached data if available and valid
-    pub fn load_cache(&self) -> Result<Option<CacheData>> {
+    /// Load cached data even if it has expired, e.g. to revalidate it with its ETag
+    pub fn load_stale_cache(&self) -> Result<Option<CacheData>> {
         if !self.cache_path.exists() {
             if self.verbose {
                 eprintln!("[CACHE] No cache file found at: {}", self.cache_path.display());
@@ -155,10 +155,10 @@ impl CacheManager {
         }
 
         let content = fs::read_to_string(&self.cache_path)
-            .map_err(|e| AppError::cache(format!("Failed to read cache file '{}': {}", self.cache_path.display(), e)))?;
+            .map_err(|e| AppError::cache(format!("Failed to read cache file '{}': {}", self.cache_path.display(), e)).with_source(e))?;
 
         let cache_data: CacheData = serde_json::from_str(&content)
-            .map_err(|e| AppError::cache(format!("Failed to parse cache file '{}': {}", self.cache_path.display(), e)))?;
+            .map_err(|e| AppError::cache(format!("Failed to parse cache file '{}': {}", self.cache_path.display(), e)).with_source(e))?;
 
         // Check cache compatibility
         if !cache_data.is_compatible() {
@@ -168,6 +168,15 @@ impl CacheManager {
             return Ok(None);
         }
 
+        Ok(Some(cache_data))
+    }
+
+    /// Load cached data if available and valid
+    pub fn load_cache(&self) -> Result<Option<CacheData>> {
+        let Some(cache_data) = self.load_stale_cache()? else {
+            return Ok(None);
+        };
+
         // Check cache validity
         if !cache_data.is_valid() {
             if self.verbose {
@@ -197,7 +206,7 @@ impl CacheManager {
         };
 
         let content = serde_json::to_string_pretty(&cache_data)
-            .map_err(|e| AppError::cache(format!("Failed to serialize cache data: {}", e)))?;
+            .map_err(|e| AppError::cache(format!("Failed to serialize cache data: {}", e)).with_source(e))?;
 
         if self.verbose {
             eprintln!("[CACHE] Saving {} releases to cache: {}", 
@@ -205,7 +214,7 @@ impl CacheManager {
         }
 
         fs::write(&self.cache_path, content)
-            .map_err(|e| AppError::cache(format!("Failed to write cache file '{}': {}", self.cache_path.display(), e)))?;
+            .map_err(|e| AppError::cache(format!("Failed to write cache file '{}': {}", self.cache_path.display(), e)).with_source(e))?;
 
         if self.verbose {
             eprintln!("[CACHE] Cache saved successfully");
@@ -232,10 +241,7 @@ impl CacheManager {
 
     /// Check if cache is valid without loading data
     pub fn is_cache_valid(&self) -> bool {
-        match self.load_cache() {
-            Ok(Some(_)) => true,
-            _ => false,
-        }
+        matches!(self.load_cache(), Ok(Some(_)))
     }
 
     /// Clear/invalidate the cache
@@ -245,11 +251,16 @@ impl CacheManager {
                 eprintln!("[CACHE] Clearing cache: {}", self.cache_path.display());
             }
             fs::remove_file(&self.cache_path)
-                .map_err(|e| AppError::cache(format!("Failed to remove cache file '{}': {}", self.cache_path.display(), e)))?;
+                .map_err(|e| AppError::cache(format!("Failed to remove cache file '{}': {}", self.cache_path.display(), e)).with_source(e))?;
         }
         Ok(())
     }
 
+    /// Path of the cache file
+    pub fn path(&self) -> &Path {
+        &self.cache_path
+    }
+
     /// Get cache file size in bytes
     pub fn get_cache_size(&self) -> Result<u64> {
         if !self.cache_path.exists() {
@@ -257,17 +268,17 @@ impl CacheManager {
         }
 
         let metadata = fs::metadata(&self.cache_path)
-            .map_err(|e| AppError::cache(format!("Failed to get cache metadata '{}': {}", self.cache_path.display(), e)))?;
+            .map_err(|e| AppError::cache(format!("Failed to get cache metadata '{}': {}", self.cache_path.display(), e)).with_source(e))?;
 
         Ok(metadata.len())
     }
 
-    /// Get cache statistics
+    /// Get cache statistics, including the age and entries of an expired cache
     pub fn get_cache_stats(&self) -> Result<CacheStats> {
         let exists = self.cache_path.exists();
         let size = if exists { self.get_cache_size()? } else { 0 };
         
-        let (valid, age_seconds, release_count, etag) = if let Ok(Some(cache_data)) = self.load_cache() {
+        let (valid, age_seconds, release_count, etag) = if let Ok(Some(cache_data)) = self.load_stale_cache() {
             (
                 cache_data.is_valid(),
                 Some(cache_data.age_seconds()),
@@ -624,6 +635,22 @@ mod tests {
         assert!(!cache_path.exists());
     }
 
+    #[test]
+    fn test_stale_cache_keeps_etag() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_manager = CacheManager::with_config(
+            Some(temp_dir.path().join("test_cache.json")),
+            false,
+            Some(Duration::ZERO), // Expired as soon as it is written
+        ).unwrap();
+
+        cache_manager.save_cache(&[create_test_release("v1.0.0")], Some("\"abc\"".to_string())).unwrap();
+        assert!(cache_manager.load_cache().unwrap().is_none());
+        let stale = cache_manager.load_stale_cache().unwrap().unwrap();
+        assert_eq!(stale.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(stale.releases.len(), 1);
+    }
+
     #[test]
     fn test_corrupted_cache_handling() {
         let temp_dir = TempDir::new().unwrap();
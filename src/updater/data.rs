@@ -1,28 +1,108 @@
 //! Data source management layer with platform detection
 //!
-//! This module orchestrates data retrieval from multiple sources (GitHub Atom feeds,
-//! REST API, and local cache) with intelligent fallback mechanisms and automatic
-//! platform-specific asset filtering.
+//! This module orchestrates data retrieval from multiple sources (local cache,
+//! GitHub REST API and Atom feeds) with intelligent fallback mechanisms and
+//! automatic platform-specific asset filtering.
+//!
+//! Sources are tried in [`DataSourcePriority`] order. Every try is timed and
+//! recorded, both in the [`DataSourceStatus`] of the operation, so a flaky
+//! update check shows which sources failed and how long each took, and in a
+//! running [`SourceHealth`] per source for the lifetime of the manager.
 
-use crate::{AppError, Result};
+use crate::{cancel::{cancellable, CancellationToken}, error::UpdateErrorKind, AppError, Result};
 use super::{
     cache::{CacheManager, CacheStats},
     feeds::{FeedsClient, FeedStats},
-    github::{GitHubApiClient, GitHubApiStats},
+    github::{GitHubApiClient, GitHubApiStats, ReleasesFetch},
     types::{Release, ReleaseAsset, PlatformInfo},
 };
+use std::collections::BTreeMap;
+use std::fmt;
 use std::time::{Duration, Instant};
-use std::path::PathBuf;
+
+/// Failures in a row after which a source is reported as unhealthy
+const UNHEALTHY_AFTER: u32 = 3;
 
 /// Data source priority levels for fallback logic
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DataSourcePriority {
     /// Cache has highest priority for performance
     Cache = 1,
-    /// Atom feeds have second priority (no rate limits)
-    Feeds = 2,
-    /// REST API has lowest priority (rate limited)
-    Api = 3,
+    /// REST API has second priority, since only it lists release assets
+    Api = 2,
+    /// Atom feeds are the last resort (no rate limits, but no assets either)
+    Feeds = 3,
+}
+
+impl fmt::Display for DataSourcePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataSourcePriority::Cache => "cache",
+            DataSourcePriority::Api => "GitHub API",
+            DataSourcePriority::Feeds => "Atom feeds",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One try of a data source during an operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceAttempt {
+    /// Source that was tried
+    pub source: DataSourcePriority,
+    /// How long the try took
+    pub latency: Duration,
+    /// Why the source did not serve the data, if it did not
+    pub error: Option<String>,
+}
+
+impl fmt::Display for SourceAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error {
+            Some(ref error) => write!(f, "{} failed after {}ms: {}", self.source, self.latency.as_millis(), error),
+            None => write!(f, "{} served in {}ms", self.source, self.latency.as_millis()),
+        }
+    }
+}
+
+/// How a data source has fared over the lifetime of a manager
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceHealth {
+    /// Times the source was tried
+    pub attempts: u32,
+    /// Tries that failed
+    pub failures: u32,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Time spent on all tries
+    pub total_latency: Duration,
+    /// Error of the latest failure
+    pub last_error: Option<String>,
+}
+
+impl SourceHealth {
+    fn record(&mut self, attempt: &SourceAttempt) {
+        self.attempts += 1;
+        self.total_latency += attempt.latency;
+        match attempt.error {
+            Some(ref error) => {
+                self.failures += 1;
+                self.consecutive_failures += 1;
+                self.last_error = Some(error.clone());
+            }
+            None => self.consecutive_failures = 0,
+        }
+    }
+
+    /// Average time per try, if the source was tried
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.attempts > 0).then(|| self.total_latency / self.attempts)
+    }
+
+    /// Whether the source has not failed several times in a row
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures < UNHEALTHY_AFTER
+    }
 }
 
 /// Data source status tracking
@@ -36,6 +116,19 @@ pub struct DataSourceStatus {
     pub success: bool,
     /// Additional context or error message
     pub message: Option<String>,
+    /// Sources tried, in order, ending with the one that served the data
+    pub attempts: Vec<SourceAttempt>,
+}
+
+impl fmt::Display for DataSourceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}ms", self.source, self.response_time.as_millis())?;
+        let failed: Vec<String> = self.attempts.iter().filter(|a| a.error.is_some()).map(ToString::to_string).collect();
+        if !failed.is_empty() {
+            write!(f, " ({})", failed.join("; "))?;
+        }
+        Ok(())
+    }
 }
 
 /// Comprehensive statistics from data source operations
@@ -69,23 +162,30 @@ pub struct DataSourceManager {
     start_time: Instant,
     /// Verbose output mode
     verbose: bool,
+    /// Health of each source tried so far
+    health: BTreeMap<DataSourcePriority, SourceHealth>,
+    /// Stops remote fetches in flight when cancelled
+    cancel: CancellationToken,
 }
 
 impl DataSourceManager {
     /// Create a new DataSourceManager with default configuration
     pub fn new() -> Self {
-        let github_client = GitHubApiClient::new("MaurUppi".to_string(), "network-latency-tester".to_string())
-            .unwrap_or_else(|_| panic!("Failed to create GitHub API client"));
+        let github_client = GitHubApiClient::with_config(
+            "MaurUppi".to_string(),
+            "network-latency-tester".to_string(),
+            false,
+            None,
+            GitHubApiClient::token_from_env(),
+        ).unwrap_or_else(|_| panic!("Failed to create GitHub API client"));
             
-        Self {
-            cache_manager: CacheManager::new().unwrap_or_else(|_| panic!("Failed to create cache manager")),
-            feeds_client: FeedsClient::new("https://github.com/MaurUppi/network-latency-tester".to_string())
+        Self::with_sources(
+            CacheManager::new().unwrap_or_else(|_| panic!("Failed to create cache manager")),
+            FeedsClient::new("https://github.com/MaurUppi/network-latency-tester".to_string())
                 .unwrap_or_else(|_| panic!("Failed to create feeds client")),
             github_client,
-            platform_info: PlatformInfo::current(),
-            start_time: Instant::now(),
-            verbose: false,
-        }
+            false,
+        )
     }
 
     /// Create a new DataSourceManager with custom configuration
@@ -95,25 +195,54 @@ impl DataSourceManager {
             "network-latency-tester".to_string(),
             verbose,
             None,
-            None,
+            GitHubApiClient::token_from_env(),
         ).unwrap_or_else(|_| panic!("Failed to create GitHub API client"));
         
-        Self {
-            cache_manager: CacheManager::with_config(None, verbose, None)
+        Self::with_sources(
+            CacheManager::with_config(None, verbose, None)
                 .unwrap_or_else(|_| panic!("Failed to create cache manager")),
-            feeds_client: FeedsClient::with_config(
+            FeedsClient::with_config(
                 "https://github.com/MaurUppi/network-latency-tester".to_string(),
                 verbose,
                 None
             ).unwrap_or_else(|_| panic!("Failed to create feeds client")),
             github_client,
+            verbose,
+        )
+    }
+
+    /// Create a DataSourceManager over the given sources, e.g. a cache in
+    /// another directory or clients pointed at a mirror
+    pub fn with_sources(
+        cache_manager: CacheManager,
+        feeds_client: FeedsClient,
+        github_client: GitHubApiClient,
+        verbose: bool,
+    ) -> Self {
+        Self {
+            cache_manager,
+            feeds_client,
+            github_client,
             platform_info: PlatformInfo::current(),
             start_time: Instant::now(),
             verbose,
+            health: BTreeMap::new(),
+            cancel: crate::cancel::global().child_token(),
         }
     }
 
-    /// Get releases with intelligent fallback from cache -> feeds -> API
+    /// Stop remote fetches, which then fail as cancelled instead of falling
+    /// back to the next source, once `token` is cancelled
+    pub fn with_cancellation(self, token: &CancellationToken) -> Self {
+        Self { cancel: token.child_token(), ..self }
+    }
+
+    /// Health of a source, default if it has not been tried
+    pub fn source_health(&self, source: DataSourcePriority) -> SourceHealth {
+        self.health.get(&source).cloned().unwrap_or_default()
+    }
+
+    /// Get releases with intelligent fallback from cache -> API -> feeds
     pub async fn get_releases(&mut self, limit: usize) -> Result<(Vec<Release>, DataSourceStats)> {
         let start_time = Instant::now();
         
@@ -123,126 +252,158 @@ impl DataSourceManager {
         }
 
         // Strategy 1: Try cache first for best performance
-        if let Ok(Some(cached_releases)) = self.cache_manager.get_cached_releases() {
-            if self.verbose {
-                self.log_success(&format!("Found {} cached releases", cached_releases.len()));
-            }
-
-            let filtered_releases = self.filter_releases_by_platform(&cached_releases);
-            
-            let stats = DataSourceStats {
-                cache_stats: self.cache_manager.get_cache_stats().ok(),
-                feeds_stats: None,
-                api_stats: None,
-                operation_status: DataSourceStatus {
-                    source: DataSourcePriority::Cache,
-                    response_time: start_time.elapsed(),
-                    success: true,
-                    message: Some(format!("Retrieved {} releases from cache", cached_releases.len())),
-                },
-                platform_info: self.platform_info.clone(),
-                platform_filtered_assets: filtered_releases.iter()
-                    .map(|r| r.assets.len())
-                    .sum(),
-            };
-
-            return Ok((filtered_releases, stats));
+        let started = Instant::now();
+        let cached = match self.cache_manager.get_cached_releases() {
+            Ok(Some(cached_releases)) => Ok(cached_releases),
+            Ok(None) => Err(AppError::cache("no valid cache")),
+            Err(e) => Err(e),
+        };
+        let mut attempts = vec![self.record(DataSourcePriority::Cache, started, cached.as_ref().err())];
+        if let Ok(cached_releases) = cached {
+            return Ok(self.served(DataSourcePriority::Cache, cached_releases, start_time, attempts, ""));
         }
 
         if self.verbose {
-            self.log_info("Cache miss or expired, trying Atom feeds...");
+            self.log_info("Cache miss or expired, trying remote sources...");
         }
 
-        // Strategy 2: Try Atom feeds (no rate limits)
-        match self.feeds_client.fetch_releases().await {
-            Ok(feeds_releases) => {
-                if self.verbose {
-                    self.log_success(&format!("Retrieved {} releases from Atom feeds", feeds_releases.len()));
-                }
+        self.fetch_remote(start_time, &mut attempts, "").await.map_err(|(kind, e)| {
+            if e.is_cancelled() {
+                return e;
+            }
+            let failures: Vec<String> = attempts.iter().map(ToString::to_string).collect();
+            AppError::update_error(kind, format!("All data sources failed: {}", failures.join("; ")))
+        })
+    }
 
-                // Cache the successful result
-                if let Err(e) = self.cache_manager.save_cache(&feeds_releases, None) {
+    /// Strategies 2 and 3: the GitHub API, which also lists assets, then the
+    /// Atom feeds. On failure, returns the kind of error to report and the
+    /// last error.
+    async fn fetch_remote(
+        &mut self,
+        start_time: Instant,
+        attempts: &mut Vec<SourceAttempt>,
+        context: &str,
+    ) -> std::result::Result<(Vec<Release>, DataSourceStats), (UpdateErrorKind, AppError)> {
+        let mut kind = UpdateErrorKind::SourceUnavailable;
+        let mut last_error = None;
+        for source in [DataSourcePriority::Api, DataSourcePriority::Feeds] {
+            let started = Instant::now();
+            let fetched = match source {
+                DataSourcePriority::Api => cancellable(&self.cancel, "release check", self.fetch_from_api()).await,
+                _ => cancellable(&self.cancel, "release check", self.fetch_from_feeds()).await,
+            };
+            // A cancelled try says nothing about the source's health, and no other source should be tried
+            let fetched = match fetched {
+                Err(e) if e.is_cancelled() => return Err((kind, e)),
+                fetched => fetched,
+            };
+            attempts.push(self.record(source, started, fetched.as_ref().err()));
+            match fetched {
+                Ok(releases) => return Ok(self.served(source, releases, start_time, attempts.clone(), context)),
+                Err(e) => {
                     if self.verbose {
-                        self.log_warning(&format!("Failed to cache feeds results: {}", e));
+                        self.log_warning(&format!("{}{} failed: {}", context, source, e));
                     }
+                    // A rate limit tells the user more than "unavailable", so keep it
+                    if e.update_kind() == Some(UpdateErrorKind::RateLimited) {
+                        kind = UpdateErrorKind::RateLimited;
+                    }
+                    last_error = Some(e);
                 }
+            }
+        }
+        if self.verbose {
+            self.log_error(&format!("{}No remote source served the releases", context));
+        }
+        Err((kind, last_error.unwrap_or_else(|| AppError::update("no remote sources"))))
+    }
 
-                let filtered_releases = self.filter_releases_by_platform(&feeds_releases);
-
-                let stats = DataSourceStats {
-                    cache_stats: self.cache_manager.get_cache_stats().ok(),
-                    feeds_stats: None, // FeedsClient doesn't have get_stats yet
-                    api_stats: None,
-                    operation_status: DataSourceStatus {
-                        source: DataSourcePriority::Feeds,
-                        response_time: start_time.elapsed(),
-                        success: true,
-                        message: Some(format!("Retrieved {} releases from Atom feeds", feeds_releases.len())),
-                    },
-                    platform_info: self.platform_info.clone(),
-                    platform_filtered_assets: filtered_releases.iter()
-                        .map(|r| r.assets.len())
-                        .sum(),
-                };
+    /// Releases from the GitHub API, revalidating an expired cache with its
+    /// ETag and caching the answer
+    async fn fetch_from_api(&self) -> Result<Vec<Release>> {
+        let stale = self.cache_manager.load_stale_cache().ok().flatten();
+        let cached_etag = stale.as_ref().and_then(|cache| cache.etag.clone());
 
-                return Ok((filtered_releases, stats));
-            }
-            Err(e) => {
+        let (releases, etag) = match self.github_client.fetch_releases_if_changed(cached_etag.as_deref()).await? {
+            ReleasesFetch::Modified { releases, etag } => (releases, etag),
+            ReleasesFetch::NotModified => {
                 if self.verbose {
-                    self.log_warning(&format!("Atom feeds failed: {}", e));
+                    self.log_info("Cached releases are still current");
                 }
+                (stale.map(|cache| cache.releases).unwrap_or_default(), cached_etag)
+            }
+        };
+
+        if let Err(e) = self.cache_manager.save_cache(&releases, etag) {
+            if self.verbose {
+                self.log_warning(&format!("Failed to cache API results: {}", e));
             }
         }
+        Ok(releases)
+    }
 
-        if self.verbose {
-            self.log_info("Atom feeds unavailable, trying GitHub REST API...");
+    /// Releases from the Atom feeds, cached without an ETag
+    async fn fetch_from_feeds(&self) -> Result<Vec<Release>> {
+        let releases = self.feeds_client.fetch_releases().await?;
+        if let Err(e) = self.cache_manager.save_cache(&releases, None) {
+            if self.verbose {
+                self.log_warning(&format!("Failed to cache feeds results: {}", e));
+            }
         }
+        Ok(releases)
+    }
 
-        // Strategy 3: Try GitHub REST API (rate limited, last resort)
-        match self.github_client.fetch_releases().await {
-            Ok(api_releases) => {
-                if self.verbose {
-                    self.log_success(&format!("Retrieved {} releases from GitHub API", api_releases.len()));
-                }
+    /// Record a try of `source` that began at `started` in its health
+    fn record(&mut self, source: DataSourcePriority, started: Instant, error: Option<&AppError>) -> SourceAttempt {
+        let attempt = SourceAttempt {
+            source,
+            latency: started.elapsed(),
+            error: error.map(ToString::to_string),
+        };
+        let health = self.health.entry(source).or_default();
+        health.record(&attempt);
+        let failures_in_a_row = (!health.is_healthy()).then_some(health.consecutive_failures);
+        if let (true, Some(failures)) = (self.verbose, failures_in_a_row) {
+            self.log_warning(&format!("{} has failed {} times in a row", source, failures));
+        }
+        attempt
+    }
 
-                // Cache the successful result
-                if let Err(e) = self.cache_manager.save_cache(&api_releases, None) {
-                    if self.verbose {
-                        self.log_warning(&format!("Failed to cache API results: {}", e));
-                    }
-                }
+    /// Platform-filtered releases and stats for the data `source` served
+    fn served(
+        &self,
+        source: DataSourcePriority,
+        releases: Vec<Release>,
+        start_time: Instant,
+        attempts: Vec<SourceAttempt>,
+        context: &str,
+    ) -> (Vec<Release>, DataSourceStats) {
+        let message = format!("{}Retrieved {} releases from {}", context, releases.len(), source);
+        if self.verbose {
+            self.log_success(&message);
+        }
 
-                let filtered_releases = self.filter_releases_by_platform(&api_releases);
-
-                let stats = DataSourceStats {
-                    cache_stats: self.cache_manager.get_cache_stats().ok(),
-                    feeds_stats: None, // FeedsClient doesn't have get_stats yet
-                    api_stats: None, // GitHubApiClient doesn't have get_stats yet
-                    operation_status: DataSourceStatus {
-                        source: DataSourcePriority::Api,
-                        response_time: start_time.elapsed(),
-                        success: true,
-                        message: Some(format!("Retrieved {} releases from GitHub API", api_releases.len())),
-                    },
-                    platform_info: self.platform_info.clone(),
-                    platform_filtered_assets: filtered_releases.iter()
-                        .map(|r| r.assets.len())
-                        .sum(),
-                };
+        let filtered_releases = self.filter_releases_by_platform(&releases);
 
-                return Ok((filtered_releases, stats));
-            }
-            Err(e) => {
-                if self.verbose {
-                    self.log_error(&format!("GitHub API failed: {}", e));
-                }
-            }
-        }
+        let stats = DataSourceStats {
+            cache_stats: self.cache_manager.get_cache_stats().ok(),
+            feeds_stats: None, // FeedsClient doesn't have get_stats yet
+            api_stats: (source == DataSourcePriority::Api).then(|| self.github_client.get_stats(releases.len())),
+            operation_status: DataSourceStatus {
+                source,
+                response_time: start_time.elapsed(),
+                success: true,
+                message: Some(message),
+                attempts,
+            },
+            platform_info: self.platform_info.clone(),
+            platform_filtered_assets: filtered_releases.iter()
+                .map(|r| r.assets.len())
+                .sum(),
+        };
 
-        // All sources failed
-        let error_msg = "All data sources failed: cache miss/expired, Atom feeds unavailable, and GitHub API unavailable";
-        
-        Err(AppError::update(error_msg.to_string()))
+        (filtered_releases, stats)
     }
 
     /// Get a specific release by version with intelligent fallback
@@ -364,6 +525,7 @@ impl DataSourceManager {
                 response_time: self.start_time.elapsed(),
                 success: true,
                 message: Some("Comprehensive stats snapshot".to_string()),
+                attempts: Vec::new(),
             },
             platform_info: self.platform_info.clone(),
             platform_filtered_assets: 0,
@@ -380,10 +542,7 @@ impl DataSourceManager {
         
         // For feeds and API, we'll do quick connectivity checks
         // For feeds, try a simple fetch to check availability
-        let feeds_available = match self.feeds_client.fetch_releases().await {
-            Ok(_) => true,
-            Err(_) => false,
-        };
+        let feeds_available = self.feeds_client.fetch_releases().await.is_ok();
 
         let api_available = match self.github_client.check_api_availability().await {
             Ok(availability) => availability.available,
@@ -408,101 +567,14 @@ impl DataSourceManager {
             self.log_info("Force refreshing from remote sources (bypassing cache)...");
         }
 
-        // Try feeds first (no rate limits)
-        match self.feeds_client.fetch_releases().await {
-            Ok(feeds_releases) => {
-                if self.verbose {
-                    self.log_success(&format!("Force refresh: Retrieved {} releases from Atom feeds", feeds_releases.len()));
-                }
-
-                // Update cache with fresh data
-                if let Err(e) = self.cache_manager.save_cache(&feeds_releases, None) {
-                    if self.verbose {
-                        self.log_warning(&format!("Failed to update cache after force refresh: {}", e));
-                    }
-                }
-
-                let filtered_releases = self.filter_releases_by_platform(&feeds_releases);
-
-                let stats = DataSourceStats {
-                    cache_stats: self.cache_manager.get_cache_stats().ok(),
-                    feeds_stats: None,
-                    api_stats: None,
-                    operation_status: DataSourceStatus {
-                        source: DataSourcePriority::Feeds,
-                        response_time: start_time.elapsed(),
-                        success: true,
-                        message: Some(format!("Force refresh: Retrieved {} releases from Atom feeds", feeds_releases.len())),
-                    },
-                    platform_info: self.platform_info.clone(),
-                    platform_filtered_assets: filtered_releases.iter()
-                        .map(|r| r.assets.len())
-                        .sum(),
-                };
-
-                return Ok((filtered_releases, stats));
-            }
-            Err(e) => {
-                if self.verbose {
-                    self.log_warning(&format!("Force refresh: Atom feeds failed: {}", e));
-                }
-            }
-        }
-
-        // Fallback to API for force refresh
-        match self.github_client.fetch_releases().await {
-            Ok(api_releases) => {
-                if self.verbose {
-                    self.log_success(&format!("Force refresh: Retrieved {} releases from GitHub API", api_releases.len()));
-                }
-
-                // Update cache with fresh data
-                if let Err(e) = self.cache_manager.save_cache(&api_releases, None) {
-                    if self.verbose {
-                        self.log_warning(&format!("Failed to update cache after force refresh: {}", e));
-                    }
-                }
-
-                let filtered_releases = self.filter_releases_by_platform(&api_releases);
-
-                let stats = DataSourceStats {
-                    cache_stats: self.cache_manager.get_cache_stats().ok(),
-                    feeds_stats: None,
-                    api_stats: None,
-                    operation_status: DataSourceStatus {
-                        source: DataSourcePriority::Api,
-                        response_time: start_time.elapsed(),
-                        success: true,
-                        message: Some(format!("Force refresh: Retrieved {} releases from GitHub API", api_releases.len())),
-                    },
-                    platform_info: self.platform_info.clone(),
-                    platform_filtered_assets: filtered_releases.iter()
-                        .map(|r| r.assets.len())
-                        .sum(),
-                };
-
-                Ok((filtered_releases, stats))
-            }
-            Err(e) => {
-                let error_msg = format!("Force refresh failed: both Atom feeds and GitHub API unavailable. Last API error: {}", e);
-                
-                let _stats = DataSourceStats {
-                    cache_stats: self.cache_manager.get_cache_stats().ok(),
-                    feeds_stats: None,
-                    api_stats: None,
-                    operation_status: DataSourceStatus {
-                        source: DataSourcePriority::Api,
-                        response_time: start_time.elapsed(),
-                        success: false,
-                        message: Some(error_msg.clone()),
-                    },
-                    platform_info: self.platform_info.clone(),
-                    platform_filtered_assets: 0,
-                };
-
-                Err(AppError::update(error_msg))
+        let mut attempts = Vec::new();
+        self.fetch_remote(start_time, &mut attempts, "Force refresh: ").await.map_err(|(kind, e)| {
+            if e.is_cancelled() {
+                return e;
             }
-        }
+            let error_msg = format!("Force refresh failed: both GitHub API and Atom feeds unavailable. Last error: {}", e);
+            AppError::update_error(kind, error_msg).with_source(e)
+        })
     }
 
     /// Log info message with optional color (reusing existing patterns)
@@ -540,12 +612,14 @@ impl std::fmt::Debug for DataSourceManager {
             .field("verbose", &self.verbose)
             .field("cache_stats", &self.cache_manager.get_cache_stats().ok())
             .field("feeds_stats", &"Not implemented yet")
-            .field("api_stats", &"Not implemented yet")
+            .field("api_rate_limit", &self.github_client.rate_limit())
+            .field("health", &self.health)
             .finish()
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use super::*;
     use crate::updater::types::{ReleaseAsset, Release};
@@ -674,8 +748,8 @@ mod tests {
     #[test]
     fn test_data_source_priority_ordering() {
         assert_eq!(DataSourcePriority::Cache as u8, 1);
-        assert_eq!(DataSourcePriority::Feeds as u8, 2);
-        assert_eq!(DataSourcePriority::Api as u8, 3);
+        assert_eq!(DataSourcePriority::Api as u8, 2);
+        assert_eq!(DataSourcePriority::Feeds as u8, 3);
     }
 
     #[test]
@@ -685,6 +759,7 @@ mod tests {
             response_time: Duration::from_millis(100),
             success: true,
             message: Some("Test message".to_string()),
+            attempts: Vec::new(),
         };
 
         assert_eq!(status.source, DataSourcePriority::Cache);
@@ -746,7 +821,7 @@ mod tests {
                 // Note: In a test environment, we might get cached data or actual network data
                 // The limit is more of a suggestion, and platform filtering can affect final count
                 // Verify we get some kind of reasonable response
-                assert!(stats.platform_info.os.len() > 0);
+                assert!(!stats.platform_info.os.is_empty());
                 assert!(stats.operation_status.response_time <= Duration::from_secs(30));
                 
                 // Verify all returned releases have platform-compatible assets
@@ -883,7 +958,7 @@ mod tests {
             age_seconds: Some(3600),
             release_count: Some(5),
             etag: Some("test-etag".to_string()),
-            path: PathBuf::from("/tmp/test_cache.json"),
+            path: std::path::PathBuf::from("/tmp/test_cache.json"),
         };
         
         let operation_status = DataSourceStatus {
@@ -891,6 +966,7 @@ mod tests {
             response_time: Duration::from_millis(250),
             success: true,
             message: Some("Test operation successful".to_string()),
+            attempts: Vec::new(),
         };
         
         let stats = DataSourceStats {
@@ -1056,8 +1132,8 @@ mod tests {
     #[test]
     fn test_data_source_priority_comparison() {
         // Test that priorities can be compared for fallback logic
-        assert!((DataSourcePriority::Cache as u8) < (DataSourcePriority::Feeds as u8));
-        assert!((DataSourcePriority::Feeds as u8) < (DataSourcePriority::Api as u8));
+        assert!((DataSourcePriority::Cache as u8) < (DataSourcePriority::Api as u8));
+        assert!((DataSourcePriority::Api as u8) < (DataSourcePriority::Feeds as u8));
         
         // Test equality
         assert_eq!(DataSourcePriority::Cache, DataSourcePriority::Cache);
@@ -1084,6 +1160,7 @@ mod tests {
             response_time: Duration::from_millis(1500),
             success: false,
             message: Some("API rate limit exceeded".to_string()),
+            attempts: Vec::new(),
         };
         
         assert_eq!(status.source, DataSourcePriority::Api);
@@ -1168,26 +1245,26 @@ mod tests {
     fn test_simulated_fallback_scenario() {
         // Simulate the fallback logic decision tree
         let scenarios = vec![
-            // (cache_available, feeds_success, api_success, expected_source)
+            // (cache_available, api_success, feeds_success, expected_source)
             (true, false, false, DataSourcePriority::Cache),
-            (false, true, false, DataSourcePriority::Feeds),
-            (false, false, true, DataSourcePriority::Api),
+            (false, true, false, DataSourcePriority::Api),
+            (false, false, true, DataSourcePriority::Feeds),
         ];
         
-        for (cache_available, feeds_success, api_success, expected_source) in scenarios {
+        for (cache_available, api_success, feeds_success, expected_source) in scenarios {
             // This simulates the decision logic that would be used in get_releases
             let selected_source = if cache_available {
                 DataSourcePriority::Cache
-            } else if feeds_success {
-                DataSourcePriority::Feeds
             } else if api_success {
                 DataSourcePriority::Api
+            } else if feeds_success {
+                DataSourcePriority::Feeds
             } else {
                 // This would result in an error in the actual implementation
-                DataSourcePriority::Api // placeholder for the test
+                DataSourcePriority::Feeds // placeholder for the test
             };
             
-            if cache_available || feeds_success || api_success {
+            if cache_available || api_success || feeds_success {
                 assert_eq!(selected_source, expected_source);
             }
         }
@@ -1222,4 +1299,57 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_fallback_from_rate_limited_api_to_feeds() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // The API has used up its rate limit; the feed lists one release
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = vec![0u8; 4096];
+                let len = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..len]).to_string();
+                let (status, headers, body) = if request.starts_with("GET /releases.atom") {
+                    ("200 OK", "", r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom"><id>tag:github.com,2008:releases</id><title>Releases</title><updated>2024-01-01T00:00:00Z</updated>
+<entry><id>tag:github.com,2008:Repository/1/v1.2.0</id><updated>2024-01-01T00:00:00Z</updated><title>v1.2.0</title>
+<link rel="alternate" type="text/html" href="https://github.com/owner/repo/releases/tag/v1.2.0"/></entry></feed>"#)
+                } else {
+                    ("403 Forbidden", "x-ratelimit-limit: 60\r\nx-ratelimit-remaining: 0\r\nx-ratelimit-reset: 4102444800\r\n", "{}")
+                };
+                let response = format!("HTTP/1.1 {}\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}", status, headers, body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let timeout = Some(Duration::from_secs(5));
+        let mut manager = DataSourceManager::with_sources(
+            CacheManager::with_config(Some(temp_dir.path().join("cache.json")), false, None).unwrap(),
+            FeedsClient::with_config(base.clone(), false, timeout).unwrap(),
+            GitHubApiClient::with_config("owner".to_string(), "repo".to_string(), false, timeout, None).unwrap().with_api_base(base),
+            false,
+        );
+
+        let (releases, stats) = manager.get_releases(10).await.unwrap();
+        assert_eq!(releases.len(), 1);
+        let status = &stats.operation_status;
+        assert_eq!(status.source, DataSourcePriority::Feeds);
+        let tried: Vec<_> = status.attempts.iter().map(|attempt| (attempt.source, attempt.error.is_some())).collect();
+        assert_eq!(tried, [(DataSourcePriority::Cache, true), (DataSourcePriority::Api, true), (DataSourcePriority::Feeds, false)]);
+        assert!(status.attempts[1].error.as_ref().unwrap().contains("rate limit"));
+        assert!(status.to_string().starts_with("Atom feeds in "));
+
+        // The feed's answer was cached, so the next check needs no network
+        let (_, stats) = manager.get_releases(10).await.unwrap();
+        assert_eq!(stats.operation_status.source, DataSourcePriority::Cache);
+        let api = manager.source_health(DataSourcePriority::Api);
+        assert_eq!((api.attempts, api.failures, api.consecutive_failures), (1, 1, 1));
+        assert!(api.is_healthy());
+        assert_eq!(manager.source_health(DataSourcePriority::Cache).attempts, 2);
+        assert!(manager.source_health(DataSourcePriority::Feeds).average_latency().is_some());
+    }
 }
\ No newline at end of file
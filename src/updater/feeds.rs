@@ -1,10 +1,11 @@
 //! GitHub Atom feeds client for version information retrieval
 //!
 //! This module provides functionality to parse GitHub releases from Atom feeds,
-//! which offers unlimited access without API rate limits. It serves as the primary
-//! data source for version information.
+//! which offers unlimited access without API rate limits. It serves as the
+//! fallback data source for version information when the REST API is
+//! unavailable or rate-limited.
 
-use crate::{AppError, Result};
+use crate::{error::UpdateErrorKind, AppError, Result};
 use crate::updater::types::{Release, ReleaseAsset};
 use feed_rs::parser;
 use reqwest::Client;
@@ -34,7 +35,7 @@ impl FeedsClient {
                 crate::VERSION
             ))
             .build()
-            .map_err(|e| AppError::update(format!("Failed to create HTTP client: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
 
         Ok(Self {
             client,
@@ -54,7 +55,7 @@ impl FeedsClient {
                 crate::VERSION
             ))
             .build()
-            .map_err(|e| AppError::update(format!("Failed to create HTTP client: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
 
         Ok(Self {
             client,
@@ -76,11 +77,11 @@ impl FeedsClient {
             .get(&feed_url)
             .send()
             .await
-            .map_err(|e| AppError::update(format!("Failed to fetch Atom feed from '{}': {}", feed_url, e)))?;
+            .map_err(|e| AppError::update_error(UpdateErrorKind::SourceUnavailable, format!("Failed to fetch Atom feed from '{}': {}", feed_url, e)).with_source(e))?;
 
         // Check response status
         if !response.status().is_success() {
-            return Err(AppError::update(format!(
+            return Err(AppError::update_error(UpdateErrorKind::SourceUnavailable, format!(
                 "Failed to fetch Atom feed: HTTP {} from '{}'",
                 response.status(),
                 feed_url
@@ -91,7 +92,7 @@ impl FeedsClient {
         let feed_content = response
             .text()
             .await
-            .map_err(|e| AppError::update(format!("Failed to read Atom feed content: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to read Atom feed content: {}", e)).with_source(e))?;
 
         if self.verbose {
             eprintln!("[FEEDS] Received {} bytes of feed content", feed_content.len());
@@ -99,7 +100,7 @@ impl FeedsClient {
 
         // Parse the Atom feed
         let feed = parser::parse(feed_content.as_bytes())
-            .map_err(|e| AppError::update(format!("Failed to parse Atom feed: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to parse Atom feed: {}", e)).with_source(e))?;
 
         if self.verbose {
             eprintln!("[FEEDS] Parsed feed with {} entries", feed.entries.len());
@@ -221,7 +222,7 @@ impl FeedsClient {
     /// Extract tag name from feed entry
     fn extract_tag_name(&self, entry: &feed_rs::model::Entry) -> Option<String> {
         // Try to extract from entry ID first (usually contains the tag)
-        if let Some(tag) = entry.id.split('/').last() {
+        if let Some(tag) = entry.id.split('/').next_back() {
             if !tag.is_empty() {
                 return Some(tag.to_string());
             }
@@ -241,8 +242,8 @@ impl FeedsClient {
 
         // Try to extract from first link that looks like a release tag
         for link in &entry.links {
-            if let Some(tag) = link.href.split('/').last() {
-                if tag.starts_with('v') || tag.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+            if let Some(tag) = link.href.split('/').next_back() {
+                if tag.starts_with('v') || tag.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                     return Some(tag.to_string());
                 }
             }
@@ -31,7 +31,7 @@ impl GeographicDetector {
                 env!("CARGO_PKG_VERSION")
             ))
             .build()
-            .map_err(|e| AppError::geographic(format!("Failed to create HTTP client: {}", e)))?;
+            .map_err(|e| AppError::geographic(format!("Failed to create HTTP client: {}", e)).with_source(e))?;
 
         Ok(Self { client })
     }
@@ -58,7 +58,7 @@ impl GeographicDetector {
             .get(IP_DETECTION_URL)
             .send()
             .await
-            .map_err(|e| AppError::geographic(format!("IP detection request failed: {}", e)))?;
+            .map_err(|e| AppError::geographic(format!("IP detection request failed: {}", e)).with_source(e))?;
 
         if !response.status().is_success() {
             return Err(AppError::geographic(format!(
@@ -70,7 +70,7 @@ impl GeographicDetector {
         let response_text = response
             .text()
             .await
-            .map_err(|e| AppError::geographic(format!("Failed to read IP detection response: {}", e)))?;
+            .map_err(|e| AppError::geographic(format!("Failed to read IP detection response: {}", e)).with_source(e))?;
 
         Ok(self.parse_location(&response_text))
     }
@@ -1,15 +1,29 @@
 //! GitHub REST API client for detailed release information
 //!
-//! This module provides a GitHub REST API client that serves as a fallback
-//! data source when GitHub Atom feeds don't provide sufficient detail about
-//! releases and their assets. It includes rate limit handling, error recovery,
-//! and comprehensive release asset information.
-
-use crate::{AppError, Result};
+//! This module provides a GitHub REST API client, the first remote data source
+//! since only it provides details about releases and their assets. It includes
+//! rate limit handling, error recovery, and comprehensive release asset
+//! information.
+//!
+//! Requests are authenticated with `GITHUB_TOKEN` when it is set, which raises
+//! the limit from 60 to 5000 requests an hour. The client remembers the rate
+//! limit GitHub last reported and refuses to send requests it knows will be
+//! rejected, so callers can fall back to the Atom feeds straight away. Release
+//! lists can be revalidated with an ETag; GitHub does not count a
+//! `304 Not Modified` answer against the limit.
+
+use crate::{error::UpdateErrorKind, utils::duration::format_duration, AppError, Result};
 use crate::updater::types::{Release, ReleaseAsset};
-use reqwest::{Client, Response, StatusCode};
+use reqwest::{
+    header::{HeaderMap, ETAG, IF_NONE_MATCH},
+    Client, Response, StatusCode,
+};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Mutex,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Default timeout for GitHub API requests
 const GITHUB_API_TIMEOUT: Duration = Duration::from_secs(10);
@@ -17,8 +31,14 @@ const GITHUB_API_TIMEOUT: Duration = Duration::from_secs(10);
 /// GitHub API base URL
 const GITHUB_API_BASE: &str = "https://api.github.com";
 
+/// Environment variable holding a token for authenticated requests
+pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// Remaining requests below which a low rate limit is reported
+const RATE_LIMIT_WARNING: u32 = 10;
+
 /// Rate limit information from GitHub API headers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RateLimitInfo {
     /// Remaining requests in current window
     pub remaining: u32,
@@ -28,6 +48,40 @@ pub struct RateLimitInfo {
     pub limit: u32,
 }
 
+impl RateLimitInfo {
+    /// Read the `x-ratelimit-*` headers of a response
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| headers.get(name)?.to_str().ok()?.parse::<u64>().ok();
+        Some(Self {
+            remaining: u32::try_from(number("x-ratelimit-remaining")?).ok()?,
+            reset: number("x-ratelimit-reset")?,
+            limit: u32::try_from(number("x-ratelimit-limit")?).ok()?,
+        })
+    }
+
+    /// Whether no requests are left at `now` (a Unix timestamp) and the window has not reset yet
+    pub fn is_exhausted_at(&self, now: u64) -> bool {
+        self.remaining == 0 && now < self.reset
+    }
+
+    /// Time from `now` (a Unix timestamp) until the window resets
+    pub fn resets_in(&self, now: u64) -> Duration {
+        Duration::from_secs(self.reset.saturating_sub(now))
+    }
+}
+
+/// Outcome of a conditional request for the release list
+#[derive(Debug, Clone)]
+pub enum ReleasesFetch {
+    /// The releases, with the ETag to revalidate them with next time
+    Modified {
+        releases: Vec<Release>,
+        etag: Option<String>,
+    },
+    /// The releases have not changed since the ETag that was sent
+    NotModified,
+}
+
 /// GitHub API release response structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct GitHubApiRelease {
@@ -61,6 +115,12 @@ pub struct GitHubApiClient {
     verbose: bool,
     /// API token for authenticated requests (optional)
     api_token: Option<String>,
+    /// Base URL of the API
+    api_base: String,
+    /// Rate limit reported by the last response
+    rate_limit: Mutex<Option<RateLimitInfo>>,
+    /// Requests sent so far
+    requests_made: AtomicU32,
 }
 
 impl GitHubApiClient {
@@ -73,7 +133,7 @@ impl GitHubApiClient {
                 crate::VERSION
             ))
             .build()
-            .map_err(|e| AppError::update(format!("Failed to create GitHub API client: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to create GitHub API client: {}", e)).with_source(e))?;
 
         Ok(Self {
             client,
@@ -81,6 +141,9 @@ impl GitHubApiClient {
             repo_name,
             verbose: false,
             api_token: None,
+            api_base: GITHUB_API_BASE.to_string(),
+            rate_limit: Mutex::new(None),
+            requests_made: AtomicU32::new(0),
         })
     }
 
@@ -101,7 +164,7 @@ impl GitHubApiClient {
                 crate::VERSION
             ))
             .build()
-            .map_err(|e| AppError::update(format!("Failed to create GitHub API client: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to create GitHub API client: {}", e)).with_source(e))?;
 
         Ok(Self {
             client,
@@ -109,36 +172,81 @@ impl GitHubApiClient {
             repo_name,
             verbose,
             api_token,
+            api_base: GITHUB_API_BASE.to_string(),
+            rate_limit: Mutex::new(None),
+            requests_made: AtomicU32::new(0),
         })
     }
 
+    /// Send requests to another API base URL, such as a GitHub Enterprise server
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Token from the `GITHUB_TOKEN` environment variable, if it is set and not blank
+    pub fn token_from_env() -> Option<String> {
+        std::env::var(GITHUB_TOKEN_ENV).ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    }
+
+    /// Whether requests are sent with a token
+    pub fn is_authenticated(&self) -> bool {
+        self.api_token.is_some()
+    }
+
+    /// Rate limit reported by the last response, if any
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        self.rate_limit.lock().ok().and_then(|limit| limit.clone())
+    }
+
+    /// Whether the last response used up the rate limit and it has not reset yet
+    pub fn is_rate_limited(&self) -> bool {
+        self.rate_limit().is_some_and(|limit| limit.is_exhausted_at(unix_now()))
+    }
+
+    /// Statistics of the requests made so far
+    pub fn get_stats(&self, releases_count: usize) -> GitHubApiStats {
+        GitHubApiStats {
+            releases_count,
+            requests_made: self.requests_made.load(Ordering::Relaxed),
+            api_available: !self.is_rate_limited(),
+            rate_limit_remaining: self.rate_limit().map(|limit| limit.remaining),
+            response_time_ms: None,
+        }
+    }
+
     /// Fetch releases from GitHub REST API
     pub async fn fetch_releases(&self) -> Result<Vec<Release>> {
-        let url = format!("{}/repos/{}/{}/releases", GITHUB_API_BASE, self.repo_owner, self.repo_name);
+        match self.fetch_releases_if_changed(None).await? {
+            ReleasesFetch::Modified { releases, .. } => Ok(releases),
+            ReleasesFetch::NotModified => Err(AppError::update("GitHub API answered Not Modified to an unconditional request")),
+        }
+    }
+
+    /// Fetch releases unless they are unchanged since `etag`
+    pub async fn fetch_releases_if_changed(&self, etag: Option<&str>) -> Result<ReleasesFetch> {
+        let url = format!("{}/repos/{}/{}/releases", self.api_base, self.repo_owner, self.repo_name);
         
         if self.verbose {
             eprintln!("[GITHUB] Fetching releases from: {}", url);
         }
 
-        let response = self.make_api_request(&url).await?;
-        
-        // Check rate limit before processing response
-        if let Some(rate_limit) = self.extract_rate_limit_info(&response) {
+        let response = self.make_conditional_request(&url, etag).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
             if self.verbose {
-                eprintln!("[GITHUB] Rate limit: {}/{} remaining, resets at {}", 
-                    rate_limit.remaining, rate_limit.limit, rate_limit.reset);
-            }
-            
-            if rate_limit.remaining < 10 {
-                eprintln!("[GITHUB] WARNING: GitHub API rate limit low ({} remaining)", rate_limit.remaining);
+                eprintln!("[GITHUB] Releases unchanged since ETag {}", etag.unwrap_or_default());
             }
+            return Ok(ReleasesFetch::NotModified);
         }
+        let etag = response.headers().get(ETAG).and_then(|value| value.to_str().ok()).map(str::to_string);
 
         // Parse response
         let github_releases: Vec<GitHubApiRelease> = response
             .json()
             .await
-            .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)).with_source(e))?;
 
         if self.verbose {
             eprintln!("[GITHUB] Parsed {} releases from API", github_releases.len());
@@ -155,13 +263,13 @@ impl GitHubApiClient {
             eprintln!("[GITHUB] Converted {} releases (excluding drafts)", releases.len());
         }
 
-        Ok(releases)
+        Ok(ReleasesFetch::Modified { releases, etag })
     }
 
     /// Fetch a specific number of recent releases
     pub async fn fetch_recent_releases(&self, limit: usize) -> Result<Vec<Release>> {
         let url = format!("{}/repos/{}/{}/releases?per_page={}", 
-            GITHUB_API_BASE, self.repo_owner, self.repo_name, limit.min(100));
+            self.api_base, self.repo_owner, self.repo_name, limit.min(100));
         
         if self.verbose {
             eprintln!("[GITHUB] Fetching {} recent releases from: {}", limit, url);
@@ -172,7 +280,7 @@ impl GitHubApiClient {
         let github_releases: Vec<GitHubApiRelease> = response
             .json()
             .await
-            .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)).with_source(e))?;
 
         let releases: Vec<Release> = github_releases
             .into_iter()
@@ -191,7 +299,7 @@ impl GitHubApiClient {
     /// Find a specific release by tag name
     pub async fn find_release_by_tag(&self, tag_name: &str) -> Result<Option<Release>> {
         let url = format!("{}/repos/{}/{}/releases/tags/{}", 
-            GITHUB_API_BASE, self.repo_owner, self.repo_name, tag_name);
+            self.api_base, self.repo_owner, self.repo_name, tag_name);
         
         if self.verbose {
             eprintln!("[GITHUB] Fetching release for tag '{}': {}", tag_name, url);
@@ -202,7 +310,7 @@ impl GitHubApiClient {
                 let github_release: GitHubApiRelease = response
                     .json()
                     .await
-                    .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)))?;
+                    .map_err(|e| AppError::update(format!("Failed to parse GitHub API response: {}", e)).with_source(e))?;
 
                 if github_release.draft {
                     if self.verbose {
@@ -241,15 +349,15 @@ impl GitHubApiClient {
 
     /// Check GitHub API availability and rate limits
     pub async fn check_api_availability(&self) -> Result<ApiAvailability> {
-        let url = format!("{}/repos/{}/{}", GITHUB_API_BASE, self.repo_owner, self.repo_name);
+        let url = format!("{}/repos/{}/{}", self.api_base, self.repo_owner, self.repo_name);
         
         if self.verbose {
             eprintln!("[GITHUB] Checking API availability: {}", url);
         }
 
         match self.make_api_request(&url).await {
-            Ok(response) => {
-                let rate_limit = self.extract_rate_limit_info(&response);
+            Ok(_) => {
+                let rate_limit = self.rate_limit();
                 
                 if self.verbose {
                     if let Some(ref limit_info) = rate_limit {
@@ -272,7 +380,7 @@ impl GitHubApiClient {
                 }
                 Ok(ApiAvailability {
                     available: false,
-                    rate_limit: None,
+                    rate_limit: self.rate_limit(),
                     error_message: Some(e.to_string()),
                 })
             }
@@ -281,38 +389,76 @@ impl GitHubApiClient {
 
     /// Make an authenticated API request with rate limit handling
     async fn make_api_request(&self, url: &str) -> Result<Response> {
+        self.make_conditional_request(url, None).await
+    }
+
+    /// Make an API request, sending `etag` as `If-None-Match` when given
+    ///
+    /// A `304 Not Modified` answer is returned like a successful one. Fails
+    /// without sending anything while the last known rate limit is used up.
+    async fn make_conditional_request(&self, url: &str, etag: Option<&str>) -> Result<Response> {
+        let now = unix_now();
+        if let Some(rate_limit) = self.rate_limit().filter(|limit| limit.is_exhausted_at(now)) {
+            return Err(self.rate_limited_error(&rate_limit, now));
+        }
+
         let mut request = self.client.get(url);
 
         // Add authentication header if token is available
         if let Some(ref token) = self.api_token {
-            request = request.header("Authorization", format!("token {}", token));
+            request = request.header("Authorization", format!("Bearer {}", token));
         }
 
         // Add GitHub API version header
         request = request.header("Accept", "application/vnd.github.v3+json");
 
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
         let response = request
             .send()
             .await
-            .map_err(|e| AppError::update(format!("GitHub API request failed for '{}': {}", url, e)))?;
-
-        // Handle rate limiting
-        if response.status() == StatusCode::FORBIDDEN {
-            if let Some(rate_limit) = self.extract_rate_limit_info(&response) {
-                if rate_limit.remaining == 0 {
-                    return Err(AppError::update(format!(
-                        "GitHub API rate limit exceeded. Limit resets at Unix timestamp: {}",
-                        rate_limit.reset
-                    )));
+            .map_err(|e| AppError::update_error(UpdateErrorKind::SourceUnavailable, format!("GitHub API request failed for '{}': {}", url, e)).with_source(e))?;
+        self.requests_made.fetch_add(1, Ordering::Relaxed);
+
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
+        if let Some(ref rate_limit) = rate_limit {
+            if self.verbose {
+                eprintln!("[GITHUB] Rate limit: {}/{} remaining, resets at {}", 
+                    rate_limit.remaining, rate_limit.limit, rate_limit.reset);
+            }
+            if rate_limit.remaining > 0 && rate_limit.remaining < RATE_LIMIT_WARNING {
+                eprintln!("[GITHUB] WARNING: GitHub API rate limit low ({} remaining)", rate_limit.remaining);
+            }
+            if let Ok(mut known) = self.rate_limit.lock() {
+                *known = Some(rate_limit.clone());
+            }
+        }
+
+        // Handle rate limiting; secondary limits answer 429 without using up the primary one
+        let status = response.status();
+        if status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS {
+            match rate_limit {
+                Some(rate_limit) if rate_limit.remaining == 0 => {
+                    return Err(self.rate_limited_error(&rate_limit, now));
                 }
+                _ if status == StatusCode::TOO_MANY_REQUESTS => {
+                    return Err(AppError::update_error(UpdateErrorKind::RateLimited, "GitHub API is throttling requests (HTTP 429)"));
+                }
+                _ => {}
             }
         }
 
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(response);
+        }
+
         // Check for other error status codes
-        if !response.status().is_success() {
-            return Err(AppError::update(format!(
+        if !status.is_success() {
+            return Err(AppError::update_error(UpdateErrorKind::SourceUnavailable, format!(
                 "GitHub API request failed: HTTP {} for '{}'",
-                response.status(),
+                status,
                 url
             )));
         }
@@ -320,30 +466,19 @@ impl GitHubApiClient {
         Ok(response)
     }
 
-    /// Extract rate limit information from response headers
-    fn extract_rate_limit_info(&self, response: &Response) -> Option<RateLimitInfo> {
-        let headers = response.headers();
-        
-        let remaining = headers
-            .get("x-ratelimit-remaining")?
-            .to_str().ok()?
-            .parse::<u32>().ok()?;
-            
-        let reset = headers
-            .get("x-ratelimit-reset")?
-            .to_str().ok()?
-            .parse::<u64>().ok()?;
-            
-        let limit = headers
-            .get("x-ratelimit-limit")?
-            .to_str().ok()?
-            .parse::<u32>().ok()?;
-
-        Some(RateLimitInfo {
-            remaining,
-            reset,
-            limit,
-        })
+    /// Error for a used-up rate limit, saying when it resets
+    fn rate_limited_error(&self, rate_limit: &RateLimitInfo, now: u64) -> AppError {
+        let hint = if self.is_authenticated() {
+            String::new()
+        } else {
+            format!("; set {} for a higher limit", GITHUB_TOKEN_ENV)
+        };
+        AppError::update_error(UpdateErrorKind::RateLimited, format!(
+            "GitHub API rate limit of {} requests exceeded, resets in {}{}",
+            rate_limit.limit,
+            format_duration(rate_limit.resets_in(now)),
+            hint
+        ))
     }
 
     /// Convert GitHub API release to our Release format
@@ -382,11 +517,19 @@ impl Default for GitHubApiClient {
                 repo_name: "network-latency-tester".to_string(),
                 verbose: false,
                 api_token: None,
+                api_base: GITHUB_API_BASE.to_string(),
+                rate_limit: Mutex::new(None),
+                requests_made: AtomicU32::new(0),
             }
         })
     }
 }
 
+/// Current time as a Unix timestamp, for comparing with rate limit resets
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 /// API availability information
 #[derive(Debug, Clone)]
 pub struct ApiAvailability {
@@ -646,10 +789,74 @@ mod tests {
         assert!(formatted.contains("Requests Made: 1"));
     }
 
-    // Note: More comprehensive tests involving actual HTTP requests would require
-    // a test helper that mocks the GitHub API responses. For now, we focus on
-    // testing the core client functionality and data conversion logic.
-    
-    // Integration tests that make actual HTTP requests would be in a separate
-    // integration test file to avoid network dependencies in unit tests.
+    /// Serve `responses` in order, one connection each, returning the requests received
+    async fn serve(responses: Vec<String>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0u8; 4096];
+                let len = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..len]).to_lowercase());
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (base, server)
+    }
+
+    fn response(status: &str, remaining: u32, extra_headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nx-ratelimit-limit: 5000\r\nx-ratelimit-remaining: {}\r\nx-ratelimit-reset: 4102444800\r\n{}\
+             content-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status, remaining, extra_headers, body.len(), body
+        )
+    }
+
+    #[tokio::test]
+    async fn test_conditional_requests_and_rate_limit() {
+        let body = r#"[{"tag_name":"v1.0.0","name":null,"published_at":null,"html_url":"https://example.com","assets":[],"prerelease":false,"draft":false}]"#;
+        let (base, server) = serve(vec![
+            response("200 OK", 2, "etag: \"v1\"\r\n", body),
+            response("304 Not Modified", 2, "", ""),
+            response("403 Forbidden", 0, "", r#"{"message":"API rate limit exceeded"}"#),
+        ]).await;
+
+        let client = GitHubApiClient::with_config(
+            "owner".to_string(), "repo".to_string(), false, Some(Duration::from_secs(5)), Some("secret".to_string()),
+        ).unwrap().with_api_base(base);
+
+        let ReleasesFetch::Modified { releases, etag } = client.fetch_releases_if_changed(None).await.unwrap() else {
+            panic!("expected releases");
+        };
+        assert_eq!(releases.len(), 1);
+        assert_eq!(etag.as_deref(), Some("\"v1\""));
+        assert!(matches!(client.fetch_releases_if_changed(etag.as_deref()).await.unwrap(), ReleasesFetch::NotModified));
+
+        let error = client.fetch_releases().await.unwrap_err();
+        assert_eq!(error.update_kind(), Some(UpdateErrorKind::RateLimited));
+        assert!(client.is_rate_limited());
+        // The used-up limit is known, so nothing more is sent until it resets
+        assert_eq!(client.fetch_releases().await.unwrap_err().update_kind(), Some(UpdateErrorKind::RateLimited));
+        assert_eq!(client.get_stats(0).requests_made, 3);
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("authorization: bearer secret"));
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+
+    #[test]
+    fn test_rate_limit_reset() {
+        let rate_limit = RateLimitInfo { remaining: 0, reset: 1_000, limit: 60 };
+        assert!(rate_limit.is_exhausted_at(940));
+        assert_eq!(rate_limit.resets_in(940), Duration::from_secs(60));
+        assert!(!rate_limit.is_exhausted_at(1_000));
+        assert!(!RateLimitInfo { remaining: 1, ..rate_limit }.is_exhausted_at(940));
+    }
 }
\ No newline at end of file
@@ -5,7 +5,7 @@
 //! the dialoguer crate for enhanced interactive experience with graceful fallback
 //! to basic stdio when dialoguer is not available.
 
-use crate::{AppError, Result};
+use crate::{error::UpdateErrorKind, AppError, Result};
 use crate::updater::types::{Release, Version, VersionChoice};
 use std::io::{self, Write};
 
@@ -20,7 +20,7 @@ pub struct InteractiveUI {
 impl InteractiveUI {
     /// Create a new InteractiveUI instance
     pub fn new(use_colors: bool) -> Self {
-        let use_enhanced = cfg!(feature = "dialoguer");
+        let use_enhanced = cfg!(feature = "tui");
         Self {
             use_colors,
             use_enhanced,
@@ -33,7 +33,7 @@ impl InteractiveUI {
     /// indicators for current/latest status and includes a custom version option.
     pub fn display_version_menu(&self, releases: &[Release], current_version: &Version) -> Result<VersionChoice> {
         if releases.is_empty() {
-            return Err(AppError::update("No releases available for selection"));
+            return Err(AppError::update_error(UpdateErrorKind::NoReleases, "No releases available for selection"));
         }
 
         // Take the 3 most recent releases for display
@@ -214,7 +214,7 @@ impl InteractiveUI {
     }
 
     /// Get user selection using dialoguer (enhanced)
-    #[cfg(feature = "dialoguer")]
+    #[cfg(feature = "tui")]
     fn get_enhanced_selection(&self, max_options: usize) -> Result<VersionChoice> {
         use dialoguer::{Input, Select};
         
@@ -232,14 +232,14 @@ impl InteractiveUI {
             .items(&items)
             .default(0)
             .interact()
-            .map_err(|e| AppError::update(format!("Selection failed: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Selection failed: {}", e)).with_source(e))?;
 
         if selection + 1 == max_options {
             // Custom version selected
             let custom_version: String = Input::new()
                 .with_prompt("Enter version (e.g., 0.1.7 or v0.1.7)")
                 .interact_text()
-                .map_err(|e| AppError::update(format!("Input failed: {}", e)))?;
+                .map_err(|e| AppError::update(format!("Input failed: {}", e)).with_source(e))?;
             
             if custom_version.trim().is_empty() {
                 Ok(VersionChoice::Cancel)
@@ -252,7 +252,7 @@ impl InteractiveUI {
     }
 
     /// Fallback implementation when dialoguer is not available
-    #[cfg(not(feature = "dialoguer"))]
+    #[cfg(not(feature = "tui"))]
     fn get_enhanced_selection(&self, max_options: usize) -> Result<VersionChoice> {
         self.get_basic_selection(max_options)
     }
@@ -267,11 +267,11 @@ impl InteractiveUI {
                 print!("\nSelect option (1-{} or 'q' to quit): ", max_options);
             }
             
-            io::stdout().flush().map_err(|e| AppError::update(format!("Failed to flush stdout: {}", e)))?;
+            io::stdout().flush().map_err(|e| AppError::update(format!("Failed to flush stdout: {}", e)).with_source(e))?;
             
             let mut input = String::new();
             io::stdin().read_line(&mut input)
-                .map_err(|e| AppError::update(format!("Failed to read input: {}", e)))?;
+                .map_err(|e| AppError::update(format!("Failed to read input: {}", e)).with_source(e))?;
             
             let input = input.trim();
             
@@ -306,11 +306,11 @@ impl InteractiveUI {
             print!("Enter version (e.g., 0.1.7 or v0.1.7): ");
         }
         
-        io::stdout().flush().map_err(|e| AppError::update(format!("Failed to flush stdout: {}", e)))?;
+        io::stdout().flush().map_err(|e| AppError::update(format!("Failed to flush stdout: {}", e)).with_source(e))?;
         
         let mut input = String::new();
         io::stdin().read_line(&mut input)
-            .map_err(|e| AppError::update(format!("Failed to read input: {}", e)))?;
+            .map_err(|e| AppError::update(format!("Failed to read input: {}", e)).with_source(e))?;
         
         let input = input.trim();
         if input.is_empty() {
@@ -413,13 +413,11 @@ mod tests {
 
     #[test]
     fn test_version_menu_display_structure() {
-        let ui = InteractiveUI::new(false);
+        let _ui = InteractiveUI::new(false);
         
-        let releases = vec![
-            create_test_release("v1.2.0", "2024-01-20T10:00:00Z"),
+        let releases = [create_test_release("v1.2.0", "2024-01-20T10:00:00Z"),
             create_test_release("v1.1.0", "2024-01-15T10:00:00Z"),
-            create_test_release("v1.0.0", "2024-01-10T10:00:00Z"),
-        ];
+            create_test_release("v1.0.0", "2024-01-10T10:00:00Z")];
         
         let current_version = Version::parse("1.1.0").unwrap();
         
@@ -477,10 +475,10 @@ mod tests {
         let ui = InteractiveUI::new(true);
         
         // Test that enhanced feature detection doesn't cause compilation issues
-        #[cfg(feature = "dialoguer")]
+        #[cfg(feature = "tui")]
         assert!(ui.use_enhanced);
         
-        #[cfg(not(feature = "dialoguer"))]
+        #[cfg(not(feature = "tui"))]
         assert!(!ui.use_enhanced);
     }
 }
\ No newline at end of file
@@ -8,7 +8,7 @@
 //!
 //! The updater module follows a layered architecture:
 //! - **UpdateCoordinator**: Main orchestration component managing the update workflow
-//! - **Data Sources**: Multiple data sources (GitHub Atom feeds, REST API, cache)
+//! - **Data Sources**: Multiple data sources (cache, REST API, GitHub Atom feeds)
 //! - **Version Management**: Version comparison, validation, and upgrade/downgrade logic
 //! - **Geographic Detection**: Location-aware download acceleration
 //! - **Interactive UI**: User interaction for version selection and progress display
@@ -23,7 +23,7 @@
 //! coordinator.execute_update_flow(&args).await?;
 //! ```
 
-use crate::{AppError, Result};
+use crate::{error::UpdateErrorKind, AppError, Result};
 use std::time::{Duration, Instant};
 
 // Core types module
@@ -46,12 +46,15 @@ pub use types::{
 pub use version::VersionManager;
 pub use cache::{CacheManager, CacheStats};
 pub use feeds::{FeedsClient, FeedStats};
-pub use github::{GitHubApiClient, GitHubApiStats, ApiAvailability, RateLimitInfo};
-pub use data::{DataSourceManager, DataSourceStats, DataSourceStatus, DataSourcePriority};
+pub use github::{GitHubApiClient, GitHubApiStats, ApiAvailability, RateLimitInfo, ReleasesFetch};
+pub use data::{DataSourceManager, DataSourceStats, DataSourceStatus, DataSourcePriority, SourceAttempt, SourceHealth};
 pub use geo::GeographicDetector;
 pub use interactive::InteractiveUI;
 
 /// Update operation results
+///
+/// Results built from release data name the source that served it, for
+/// debugging flaky update checks.
 #[derive(Debug, Clone)]
 pub enum UpdateResult {
     /// Update check completed successfully
@@ -59,24 +62,41 @@ pub enum UpdateResult {
         current: Version,
         latest: Release,
         download_url: String,
+        source: DataSourceStatus,
     },
     /// Already up to date
     AlreadyUpToDate {
         current: Version,
+        /// None when no release data was needed to decide
+        source: Option<DataSourceStatus>,
     },
     /// Downgrade operation
     DowngradeAvailable {
         current: Version,
         target: Release,
         download_url: String,
+        source: DataSourceStatus,
     },
     /// Interactive mode - user needs to select version
     InteractiveSelection {
         current: Version,
         available_releases: Vec<Release>,
+        source: DataSourceStatus,
     },
 }
 
+impl UpdateResult {
+    /// Data source that served the release data, if any was fetched
+    pub fn source(&self) -> Option<&DataSourceStatus> {
+        match self {
+            UpdateResult::UpdateAvailable { source, .. }
+            | UpdateResult::DowngradeAvailable { source, .. }
+            | UpdateResult::InteractiveSelection { source, .. } => Some(source),
+            UpdateResult::AlreadyUpToDate { source, .. } => source.as_ref(),
+        }
+    }
+}
+
 /// Update operation modes
 #[derive(Debug, Clone)]
 pub enum UpdateMode {
@@ -180,6 +200,7 @@ impl UpdateCoordinator {
         // For now, return already up to date
         Ok(UpdateResult::AlreadyUpToDate {
             current: current_version,
+            source: None,
         })
     }
 
@@ -206,6 +227,7 @@ impl UpdateCoordinator {
                 }
                 Ok(UpdateResult::AlreadyUpToDate {
                     current: current_version,
+                    source: None,
                 })
             }
             types::VersionRelation::Upgrade => {
@@ -213,11 +235,11 @@ impl UpdateCoordinator {
                     self.log_info(&format!("Upgrade available: {} -> {}", current_version.original, target_version.original));
                 }
                 // TODO: In subsequent tasks, this will fetch the actual release data
-                Ok(UpdateResult::AlreadyUpToDate { current: current_version })
+                Ok(UpdateResult::AlreadyUpToDate { current: current_version, source: None })
             }
             types::VersionRelation::Downgrade => {
                 // This should not happen since check_downgrade_safety would catch it
-                Err(AppError::version(format!(
+                Err(AppError::update_error(UpdateErrorKind::DowngradeRequiresForce, format!(
                     "Downgrade detected from {} to {}. Use --force to proceed.",
                     current_version.original, target_version.original
                 )))
@@ -238,6 +260,7 @@ impl UpdateCoordinator {
         // For now, return already up to date
         Ok(UpdateResult::AlreadyUpToDate {
             current: current_version,
+            source: None,
         })
     }
 
@@ -264,6 +287,7 @@ impl UpdateCoordinator {
                 }
                 Ok(UpdateResult::AlreadyUpToDate {
                     current: current_version,
+                    source: None,
                 })
             }
             types::VersionRelation::Upgrade => {
@@ -271,7 +295,7 @@ impl UpdateCoordinator {
                     self.log_info(&format!("Forced upgrade: {} -> {}", current_version.original, target_version.original));
                 }
                 // TODO: In subsequent tasks, this will fetch the actual release data
-                Ok(UpdateResult::AlreadyUpToDate { current: current_version })
+                Ok(UpdateResult::AlreadyUpToDate { current: current_version, source: None })
             }
             types::VersionRelation::Downgrade => {
                 if self.verbose {
@@ -279,7 +303,7 @@ impl UpdateCoordinator {
                     self.log_warning("WARNING: Downgrades may introduce security vulnerabilities or remove features");
                 }
                 // TODO: In subsequent tasks, this will fetch the actual release data
-                Ok(UpdateResult::AlreadyUpToDate { current: current_version })
+                Ok(UpdateResult::AlreadyUpToDate { current: current_version, source: None })
             }
         }
     }
@@ -289,7 +313,7 @@ impl UpdateCoordinator {
         // Get version from the crate environment variable
         let current_version_str = crate::VERSION;
         self.version_manager.parse_version(current_version_str)
-            .map_err(|e| AppError::version(format!("Failed to parse current version '{}': {}", current_version_str, e)))
+            .map_err(|e| AppError::version(format!("Failed to parse current version '{}': {}", current_version_str, e)).with_source(e))
     }
 
     /// Get operation duration
@@ -493,14 +517,27 @@ mod tests {
             false,
         );
         
+        let source = DataSourceStatus {
+            source: DataSourcePriority::Feeds,
+            response_time: Duration::from_millis(900),
+            success: true,
+            message: None,
+            attempts: vec![data::SourceAttempt {
+                source: DataSourcePriority::Api,
+                latency: Duration::from_millis(600),
+                error: Some("rate limited".to_string()),
+            }],
+        };
         let result = UpdateResult::UpdateAvailable {
             current: current_version.clone(),
             latest: release.clone(),
             download_url: "https://download.com".to_string(),
+            source,
         };
+        assert_eq!(result.source().unwrap().to_string(), "Atom feeds in 900ms (GitHub API failed after 600ms: rate limited)");
         
         match result {
-            UpdateResult::UpdateAvailable { current, latest, download_url } => {
+            UpdateResult::UpdateAvailable { current, latest, download_url, .. } => {
                 assert_eq!(current.major, 1);
                 assert_eq!(latest.tag_name, "v1.1.0");
                 assert_eq!(download_url, "https://download.com");
@@ -511,10 +548,12 @@ mod tests {
         // Test AlreadyUpToDate
         let result = UpdateResult::AlreadyUpToDate {
             current: current_version.clone(),
+            source: None,
         };
+        assert!(result.source().is_none());
         
         match result {
-            UpdateResult::AlreadyUpToDate { current } => {
+            UpdateResult::AlreadyUpToDate { current, .. } => {
                 assert_eq!(current.major, 1);
             }
             _ => panic!("Expected AlreadyUpToDate"),
@@ -4,7 +4,7 @@
 //! rules from the semver crate. It handles version parsing, comparison, upgrade/downgrade 
 //! validation, and string normalization.
 
-use crate::{AppError, Result};
+use crate::{error::UpdateErrorKind, AppError, Result};
 use crate::updater::types::{Version, VersionRelation};
 use semver::Version as SemVer;
 use std::str::FromStr;
@@ -45,11 +45,11 @@ impl VersionManager {
         
         // Validate using semver first for strict semantic version rules
         let semver = SemVer::from_str(&normalized)
-            .map_err(|e| AppError::version(format!("Invalid semantic version '{}': {}", version_str, e)))?;
+            .map_err(|e| AppError::version(format!("Invalid semantic version '{}': {}", version_str, e)).with_source(e))?;
 
         // Check pre-release policy
         if !self.allow_prerelease && !semver.pre.is_empty() {
-            return Err(AppError::version(format!(
+            return Err(AppError::update_error(UpdateErrorKind::PrereleaseNotAllowed, format!(
                 "Pre-release versions not allowed: '{}'. Use --allow-prerelease to enable.",
                 version_str
             )));
@@ -118,7 +118,7 @@ impl VersionManager {
                     }
                     Ok(())
                 } else {
-                    Err(AppError::version(format!(
+                    Err(AppError::update_error(UpdateErrorKind::DowngradeRequiresForce, format!(
                         "Downgrade detected from {} to {}. Use --force to proceed with downgrade.",
                         current.original, target.original
                     )))
@@ -198,7 +198,7 @@ impl VersionManager {
         let semver_version = self.to_semver(version)?;
         
         let req = semver::VersionReq::parse(requirement)
-            .map_err(|e| AppError::version(format!("Invalid version requirement '{}': {}", requirement, e)))?;
+            .map_err(|e| AppError::version(format!("Invalid version requirement '{}': {}", requirement, e)).with_source(e))?;
 
         Ok(req.matches(&semver_version))
     }
@@ -244,7 +244,7 @@ impl VersionManager {
         };
 
         SemVer::from_str(&version_str)
-            .map_err(|e| AppError::version(format!("Failed to convert version '{}' to semver: {}", version_str, e)))
+            .map_err(|e| AppError::version(format!("Failed to convert version '{}' to semver: {}", version_str, e)).with_source(e))
     }
 
     /// Convert semver::Version to our Version struct
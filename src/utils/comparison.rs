@@ -39,8 +39,8 @@ pub fn extract_test_result_avg_ms_with_default(result: &TestResult, default: f64
     result.statistics.as_ref().map(|s| s.total_avg_ms).unwrap_or(default)
 }
 
-/// Create a comparator for TestResult tuples (&String, &TestResult) for minimum response time
-pub fn test_result_min_comparator() -> impl Fn(&(&String, &TestResult), &(&String, &TestResult)) -> Ordering {
+/// Create a comparator for keyed TestResult tuples (key, &TestResult) for minimum response time
+pub fn test_result_min_comparator<K>() -> impl Fn(&(K, &TestResult), &(K, &TestResult)) -> Ordering {
     |a, b| {
         let a_time = extract_test_result_avg_ms(a.1);
         let b_time = extract_test_result_avg_ms(b.1);
@@ -48,8 +48,8 @@ pub fn test_result_min_comparator() -> impl Fn(&(&String, &TestResult), &(&Strin
     }
 }
 
-/// Create a comparator for TestResult tuples (&String, &TestResult) for maximum response time
-pub fn test_result_max_comparator() -> impl Fn(&(&String, &TestResult), &(&String, &TestResult)) -> Ordering {
+/// Create a comparator for keyed TestResult tuples (key, &TestResult) for maximum response time
+pub fn test_result_max_comparator<K>() -> impl Fn(&(K, &TestResult), &(K, &TestResult)) -> Ordering {
     |a, b| {
         let a_time = extract_test_result_avg_ms_with_default(a.1, 0.0);
         let b_time = extract_test_result_avg_ms_with_default(b.1, 0.0);
@@ -0,0 +1,172 @@
+//! Human-friendly duration parsing and formatting
+//!
+//! Durations are written as one or more `<number><unit>` components, such as
+//! `500ms`, `2s`, `1.5s` or `1m30s`. Supported units are `ms`, `s`, `m` and `h`.
+//! A bare whole number is read as seconds for compatibility with older
+//! configuration files.
+
+use crate::error::{AppError, Result};
+use std::time::Duration;
+
+/// Parse a duration such as `500ms`, `2s` or `1m30s`
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(AppError::parse("Duration cannot be empty"));
+    }
+
+    // Bare whole numbers are seconds
+    if input.bytes().all(|b| b.is_ascii_digit()) {
+        return input
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .map_err(|_| AppError::parse(format!("Invalid duration '{}'", input)));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let unit_len = rest[number_len..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len() - number_len);
+
+        let number = &rest[..number_len];
+        let unit = &rest[number_len..number_len + unit_len];
+        rest = &rest[number_len + unit_len..];
+
+        if number.is_empty() || number.starts_with('.') || number.ends_with('.') {
+            return Err(AppError::parse(format!("Invalid duration '{}'", input)));
+        }
+        let value: f64 = number
+            .parse()
+            .map_err(|_| AppError::parse(format!("Invalid duration '{}'", input)))?;
+
+        let seconds_per_unit = match unit {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "" => {
+                return Err(AppError::parse(format!(
+                    "Duration '{}' needs a unit (ms, s, m or h) after '{}'",
+                    input, number
+                )))
+            }
+            _ => {
+                return Err(AppError::parse(format!(
+                    "Unknown duration unit '{}' in '{}' (expected ms, s, m or h)",
+                    unit, input
+                )))
+            }
+        };
+
+        total += Duration::try_from_secs_f64(value * seconds_per_unit)
+            .map_err(|_| AppError::parse(format!("Duration '{}' is out of range", input)))?;
+    }
+
+    Ok(total)
+}
+
+/// Format a duration in the same notation accepted by [`parse_duration`]
+///
+/// Precision below one millisecond is dropped.
+pub fn format_duration(duration: Duration) -> String {
+    let total_ms = duration.as_millis();
+    if total_ms == 0 {
+        return "0s".to_string();
+    }
+
+    let hours = total_ms / 3_600_000;
+    let minutes = total_ms / 60_000 % 60;
+    let seconds = total_ms / 1000 % 60;
+    let millis = total_ms % 1000;
+
+    let mut output = String::new();
+    for (value, unit) in [(hours, "h"), (minutes, "m"), (seconds, "s"), (millis, "ms")] {
+        if value > 0 {
+            output.push_str(&format!("{}{}", value, unit));
+        }
+    }
+    output
+}
+
+/// Serde adapter storing durations as strings like `"1m30s"`
+///
+/// Whole numbers are also accepted when deserializing and read as seconds.
+pub mod serde_duration {
+    use super::{format_duration, parse_duration};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_duration(*duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Seconds(u64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Seconds(seconds) => Ok(Duration::from_secs(seconds)),
+            Repr::Text(text) => parse_duration(&text).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Serde adapter for optional durations, using the same notation as [`serde_duration`]
+pub mod serde_option_duration {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super::serde_duration")] Duration);
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::serde_duration::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(duration)| duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2s").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration(" 10 ").unwrap(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        for input in ["", "abc", "10.5", "-5", "+10", "0x10", "5sec", "1m30", "ms", ".5s", "5.s"] {
+            assert!(parse_duration(input).is_err(), "'{}' should not parse", input);
+        }
+    }
+
+    #[test]
+    fn test_format_duration_roundtrip() {
+        for text in ["250ms", "10s", "1m30s", "2h5m", "1s500ms"] {
+            let duration = parse_duration(text).unwrap();
+            assert_eq!(format_duration(duration), text);
+        }
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+}
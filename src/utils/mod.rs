@@ -1 +1,4 @@
-pub mod comparison;
\ No newline at end of file
+pub mod comparison;
+pub mod duration;
+pub mod size;
+pub mod url;
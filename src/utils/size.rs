@@ -0,0 +1,62 @@
+//! Human-friendly byte counts
+
+use crate::error::{AppError, Result};
+
+/// Format a byte count with a binary unit, e.g. `512 B`, `1.5 KiB` or `2.0 MiB`
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024.0 {
+        return format!("{:.0} B", bytes);
+    }
+    let mut value = bytes / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Parse a byte count such as `4096`, `64KiB`, `1.5MB` or `2m`; `KB`/`MB`/`GB`
+/// are decimal, `KiB`/`MiB`/`GiB` and the single letters binary
+pub fn parse_bytes(text: &str) -> Result<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000 * 1000,
+        "gb" => 1000 * 1000 * 1000,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        other => return Err(AppError::config(format!("Unknown size unit '{}' in '{}'; expected B, KB, KiB, MB, MiB, GB or GiB", other, text))),
+    };
+    let number: f64 = number.parse()
+        .map_err(|_| AppError::config(format!("Invalid size '{}'; expected a number with an optional unit, e.g. 64KiB", text)))?;
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0.0), "0 B");
+        assert_eq!(format_bytes(512.4), "512 B");
+        assert_eq!(format_bytes(1536.0), "1.5 KiB");
+        assert_eq!(format_bytes(3.0 * 1024.0 * 1024.0), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("4096").unwrap(), 4096);
+        assert_eq!(parse_bytes("64KiB").unwrap(), 65536);
+        assert_eq!(parse_bytes(" 1.5 MB ").unwrap(), 1_500_000);
+        assert_eq!(parse_bytes("2m").unwrap(), 2 * 1024 * 1024);
+        assert!(parse_bytes("10 parsecs").unwrap_err().to_string().contains("Unknown size unit"));
+        assert!(parse_bytes("MiB").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Normalization of user-supplied target URLs
+//!
+//! Targets come from the command line, `TARGET_URLS` and URL files, where
+//! `example.com`, a stray space or an internationalized domain are common.
+//! [`normalize_url`] accepts what it can fix unambiguously: surrounding
+//! whitespace is dropped, `https://` is added to a bare host (`http://` to a
+//! bare LAN host, see [`is_lan_host`]) and domains are converted to punycode. Anything else is reported as a [`UrlIssue`] that
+//! names the problem and, where one is likely, the intended URL.
+//!
+//! Internationalized domains travel in their ASCII (punycode) form, which is
+//! what DNS queries, TLS SNI and the `Host` header need; [`display_url`] and
+//! [`display_domain`] turn them back into Unicode for reports.
+
+use crate::error::{AppError, Result};
+use std::fmt;
+use std::net::IpAddr;
+
+/// Why a target URL was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlIssue {
+    /// The entry as given
+    pub input: String,
+    /// What is wrong with it
+    pub reason: String,
+    /// Corrected URL, when the intended one is likely
+    pub suggestion: Option<String>,
+}
+
+impl UrlIssue {
+    fn new(input: &str, reason: impl Into<String>) -> Self {
+        Self { input: input.to_string(), reason: reason.into(), suggestion: None }
+    }
+
+    fn suggest(mut self, suggestion: Option<String>) -> Self {
+        self.suggestion = suggestion;
+        self
+    }
+}
+
+impl fmt::Display for UrlIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}': {}", self.input, self.reason)?;
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, " (did you mean '{}'?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Normalize a target URL, e.g. ` example.com ` to `https://example.com`
+///
+/// URLs that are already well formed are returned as given, so that output
+/// and exports show them as the user wrote them.
+pub fn normalize_url(input: &str) -> std::result::Result<String, UrlIssue> {
+    let trimmed = input.trim().trim_start_matches('\u{feff}');
+    if trimmed.is_empty() {
+        return Err(UrlIssue::new(input, "URL is empty"));
+    }
+    if trimmed.contains(char::is_whitespace) {
+        let joined: String = trimmed.split_whitespace().collect();
+        return Err(UrlIssue::new(trimmed, "URL contains whitespace").suggest(normalize_url(&joined).ok()));
+    }
+
+    let candidate = match trimmed.split_once("://") {
+        Some((scheme, rest)) => {
+            let scheme = scheme.to_ascii_lowercase();
+            if scheme != "http" && scheme != "https" {
+                let reason = format!("unsupported scheme '{}'; only http and https can be tested", scheme);
+                let intended = ["https", "http"].into_iter().find(|known| edit_distance(&scheme, known) <= 1);
+                let suggestion = intended.and_then(|known| normalize_url(&format!("{}://{}", known, rest)).ok());
+                return Err(UrlIssue::new(trimmed, reason).suggest(suggestion));
+            }
+            trimmed.to_string()
+        }
+        None => {
+            // `https//host` and `https:/host` are a mistyped separator, not a host
+            let lower = trimmed.to_ascii_lowercase();
+            let scheme = ["https", "http"].into_iter().find(|scheme| lower.starts_with(scheme));
+            if let Some(scheme) = scheme {
+                let rest = trimmed[scheme.len()..].trim_start_matches([':', '/']);
+                if rest.len() + scheme.len() < trimmed.len() {
+                    return Err(UrlIssue::new(trimmed, "malformed scheme separator; expected '://'")
+                        .suggest(normalize_url(&format!("{}://{}", scheme, rest)).ok()));
+                }
+            }
+            if !looks_like_host(trimmed) {
+                return Err(UrlIssue::new(trimmed, "not a URL; expected something like https://example.com"));
+            }
+            // LAN devices such as NAS boxes and printers rarely have a certificate that verifies
+            let authority = trimmed.split(['/', '?', '#']).next().unwrap_or_default();
+            let host = authority.rsplit_once(':').map_or(authority, |(host, _)| host);
+            let scheme = if is_lan_host(host) { "http" } else { "https" };
+            format!("{}://{}", scheme, trimmed)
+        }
+    };
+
+    let parsed = url::Url::parse(&candidate).map_err(|e| UrlIssue::new(trimmed, e.to_string()))?;
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(UrlIssue::new(trimmed, "URL has no host"));
+    }
+    if candidate.is_ascii() {
+        return Ok(candidate);
+    }
+
+    // Use the punycode form of the domain, without the root path the parser adds
+    let mut normalized = parsed.to_string();
+    let after_host = candidate.split_once("://").map_or("", |(_, rest)| rest);
+    if parsed.path() == "/" && parsed.query().is_none() && parsed.fragment().is_none() && !after_host.contains('/') {
+        normalized.pop();
+    }
+    Ok(normalized)
+}
+
+/// Likely intended URL for an entry, if normalizing it changes or fixes it
+pub fn suggest_url(input: &str) -> Option<String> {
+    match normalize_url(input) {
+        Ok(normalized) => Some(normalized).filter(|normalized| normalized != input),
+        Err(issue) => issue.suggestion,
+    }
+}
+
+/// ASCII (punycode) form of a domain name, as DNS queries need it
+pub fn ascii_domain(domain: &str) -> Result<String> {
+    if domain.is_ascii() {
+        return Ok(domain.to_string());
+    }
+    idna::domain_to_ascii(domain)
+        .map_err(|e| AppError::validation(format!("Invalid domain name '{}': {}", domain, e)))
+}
+
+/// Unicode form of a domain name, e.g. `xn--r8jz45g.xn--zckzah` to `例え.テスト`
+///
+/// Names that do not decode cleanly are returned as given.
+pub fn display_domain(domain: &str) -> String {
+    if !domain.to_ascii_lowercase().contains("xn--") {
+        return domain.to_string();
+    }
+    match idna::domain_to_unicode(domain) {
+        (unicode, Ok(())) => unicode,
+        (_, Err(_)) => domain.to_string(),
+    }
+}
+
+/// A URL with its host shown in Unicode, leaving the rest as given
+pub fn display_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_len = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(authority_len);
+    let (userinfo, host_port) = match authority.rsplit_once('@') {
+        Some((userinfo, host_port)) => (&authority[..=userinfo.len()], host_port),
+        None => ("", authority),
+    };
+    if host_port.starts_with('[') {
+        return url.to_string();
+    }
+    let (host, port) = match host_port.rsplit_once(':') {
+        Some((host, port)) => (host, &host_port[host.len()..][..=port.len()]),
+        None => (host_port, ""),
+    };
+    format!("{}://{}{}{}{}", scheme, userinfo, display_domain(host), port, path)
+}
+
+/// Truncate `text` to at most `max` characters, marking the cut with `...`
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+/// Whether a scheme-less entry starts with something that can only be a host,
+/// such as `example.com`, `localhost:8080` or an IP address
+/// Whether `host` is an mDNS name, i.e. in the `.local` domain
+pub fn is_mdns_name(host: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    host.len() > ".local".len() && host.ends_with(".local")
+}
+
+/// Whether `host` is on the local network: an mDNS name or a private or link-local address
+pub fn is_lan_host(host: &str) -> bool {
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => (ip.segments()[0] & 0xfe00) == 0xfc00 || (ip.segments()[0] & 0xffc0) == 0xfe80,
+        Err(_) => is_mdns_name(host),
+    }
+}
+
+fn looks_like_host(input: &str) -> bool {
+    let authority = input.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.starts_with('[') {
+        return true;
+    }
+    let host = authority.rsplit_once(':').map_or(authority, |(host, _)| host);
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok()
+        || (host.contains('.') && !host.starts_with('.') && !host.ends_with('.'))
+}
+
+/// Number of single-character edits between two short strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url_fixes() {
+        assert_eq!(normalize_url("https://example.com").unwrap(), "https://example.com");
+        assert_eq!(normalize_url("  example.com/path?q=1 \n").unwrap(), "https://example.com/path?q=1");
+        assert_eq!(normalize_url("HTTP://Example.com").unwrap(), "HTTP://Example.com");
+        assert_eq!(normalize_url("localhost:8080").unwrap(), "https://localhost:8080");
+        assert_eq!(normalize_url("192.168.1.1").unwrap(), "http://192.168.1.1");
+        assert_eq!(normalize_url("nas.local:5000/status").unwrap(), "http://nas.local:5000/status");
+        assert_eq!(normalize_url("https://nas.local").unwrap(), "https://nas.local");
+        assert_eq!(normalize_url("8.8.8.8").unwrap(), "https://8.8.8.8");
+        assert_eq!(normalize_url("https://not-a-domain").unwrap(), "https://not-a-domain");
+        assert_eq!(normalize_url("münchen.de").unwrap(), "https://xn--mnchen-3ya.de");
+        assert_eq!(normalize_url("https://bücher.example/").unwrap(), "https://xn--bcher-kva.example/");
+    }
+
+    #[test]
+    fn test_lan_hosts() {
+        assert!(is_mdns_name("nas.local") && is_mdns_name("Printer.LOCAL."));
+        assert!(!is_mdns_name("local") && !is_mdns_name("example.localhost"));
+        assert!(is_lan_host("10.0.0.2") && is_lan_host("[fe80::1]") && is_lan_host("fd00::5"));
+        assert!(!is_lan_host("1.1.1.1") && !is_lan_host("127.0.0.1") && !is_lan_host("example.com"));
+    }
+
+    #[test]
+    fn test_normalize_url_issues() {
+        let issue = |input: &str| normalize_url(input).unwrap_err();
+
+        assert_eq!(issue("https://exa mple.com").suggestion.as_deref(), Some("https://example.com"));
+        assert_eq!(issue("htps://example.com").suggestion.as_deref(), Some("https://example.com"));
+        assert_eq!(issue("https//example.com").suggestion.as_deref(), Some("https://example.com"));
+        assert_eq!(issue("http:/example.com").suggestion.as_deref(), Some("http://example.com"));
+        assert_eq!(
+            issue("ftp://files.example.com").to_string(),
+            "'ftp://files.example.com': unsupported scheme 'ftp'; only http and https can be tested"
+        );
+        assert!(issue("not-a-url").reason.starts_with("not a URL"));
+        assert!(issue("https://").suggestion.is_none());
+        assert_eq!(issue("  ").reason, "URL is empty");
+
+        assert_eq!(suggest_url("example.com").as_deref(), Some("https://example.com"));
+        assert_eq!(suggest_url("https://example.com"), None);
+    }
+
+    #[test]
+    fn test_idn_round_trip() {
+        let url = normalize_url("https://例え.テスト/パス?q=1").unwrap();
+        assert_eq!(url, "https://xn--r8jz45g.xn--zckzah/%E3%83%91%E3%82%B9?q=1");
+        assert_eq!(display_url(&url), "https://例え.テスト/%E3%83%91%E3%82%B9?q=1");
+        assert_eq!(display_url("https://user@xn--mnchen-3ya.de:8443"), "https://user@münchen.de:8443");
+        assert_eq!(display_url("https://example.com/xn--path"), "https://example.com/xn--path");
+        assert_eq!(display_url("https://[::1]:8080/"), "https://[::1]:8080/");
+
+        assert_eq!(ascii_domain("例え.テスト").unwrap(), "xn--r8jz45g.xn--zckzah");
+        assert_eq!(ascii_domain("example.com").unwrap(), "example.com");
+        assert_eq!(display_domain("xn--r8jz45g.xn--zckzah"), "例え.テスト");
+        assert_eq!(display_domain("xn--invalid-"), "xn--invalid-");
+        assert_eq!(truncate_chars("例え.テスト", 5), "例え...");
+    }
+}
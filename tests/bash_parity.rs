@@ -14,6 +14,7 @@ fn create_test_cmd() -> Command {
 }
 
 /// Expected features that should match the original bash script
+#[allow(dead_code)]
 struct BashScriptFeatures {
     /// Should support multiple target URLs
     pub multiple_urls: bool,
@@ -347,7 +348,7 @@ fn test_success_rate_parity() {
     for percentage_match in percentage_matches {
         let percentage_str = percentage_match.as_str().replace("%", "");
         if let Ok(percentage) = percentage_str.trim().parse::<f64>() {
-            assert!(percentage >= 0.0 && percentage <= 100.0, 
+            assert!((0.0..=100.0).contains(&percentage), 
                 "Success rate should be between 0% and 100%: {}%", percentage);
         }
     }
@@ -551,7 +552,7 @@ fn test_bilingual_output_parity() {
         "Should contain Chinese text for bilingual support");
     
     // Should contain English technical terms
-    let english_terms = vec!["ms", "DNS", "TCP", "HTTP", "TLS"];
+    let english_terms = ["ms", "DNS", "TCP", "HTTP", "TLS"];
     let has_english = english_terms.iter().any(|term| stdout.contains(term));
     assert!(has_english, "Should contain English technical terms");
     
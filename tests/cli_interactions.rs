@@ -167,9 +167,9 @@ fn test_count_timeout_combinations() {
             .arg("--url")
             .arg("https://httpbin.org/delay/0.1")
             .arg("--count")
-            .arg(&count.to_string())
+            .arg(count.to_string())
             .arg("--timeout")
-            .arg(&timeout.to_string())
+            .arg(timeout.to_string())
             .assert()
             .success()
             .stdout(predicate::str::contains("网络延迟测试结果"));
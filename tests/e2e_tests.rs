@@ -7,13 +7,12 @@
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use std::process::Command;
-use std::time::Duration;
 use std::fs;
 use std::env;
 use tempfile::TempDir;
-use serde_json::Value;
 
 /// Test configuration for end-to-end tests
+#[allow(dead_code)]
 struct E2ETestConfig {
     /// Test URLs that should be accessible
     pub test_urls: Vec<&'static str>,
@@ -138,7 +137,7 @@ fn test_custom_dns() {
         .assert()
         .success()
         .stdout(predicate::str::contains("网络延迟测试结果"))
-        .stdout(predicate::str::contains(&format!("自定义DNS ({})", config.test_dns_servers[0])));
+        .stdout(predicate::str::contains(format!("自定义DNS ({})", config.test_dns_servers[0])));
 }
 
 /// Test execution with multiple DNS servers
@@ -511,7 +510,7 @@ fn test_graceful_shutdown() {
     
     let start = Instant::now();
     child.kill().unwrap();
-    let result = child.wait().unwrap();
+    let _result = child.wait().unwrap();
     let elapsed = start.elapsed();
     
     // Should terminate quickly (within 5 seconds) when signaled
@@ -4,6 +4,8 @@
 //! including parameter parsing, validation, error handling, and short/long parameter equivalence.
 //! Tests verify CLI integration works correctly with all update parameters and combinations.
 
+#![cfg(feature = "updater")]
+
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use std::process::Command;
@@ -5,6 +5,8 @@
 //! selection, and error recovery mechanisms. Tests verify that the complete update system
 //! functions correctly with all components working together.
 
+#![cfg(feature = "updater")]
+
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use std::process::Command;
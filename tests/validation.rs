@@ -4,7 +4,6 @@
 //! expectations and ensures consistent formatting across different scenarios.
 
 use assert_cmd::prelude::*;
-use predicates::prelude::*;
 use std::process::Command;
 use regex::Regex;
 use serde_json::Value;
@@ -15,6 +14,7 @@ fn create_test_cmd() -> Command {
 }
 
 /// Validation patterns for different output formats
+#[allow(dead_code)]
 struct OutputPatterns {
     /// Pattern for timing values (e.g., "123.456ms")
     pub timing_pattern: Regex,
@@ -388,7 +388,7 @@ fn test_statistics_output_format() {
         let percentage_str = percentage_match.as_str();
         let number_str = percentage_str.replace("%", "").trim().to_string();
         if let Ok(percentage) = number_str.parse::<f64>() {
-            assert!(percentage >= 0.0 && percentage <= 100.0, 
+            assert!((0.0..=100.0).contains(&percentage), 
                 "Percentage values should be between 0 and 100");
         }
     }
@@ -412,7 +412,7 @@ fn test_output_consistency() {
             .arg("--dns")
             .arg(dns)
             .arg("--count")
-            .arg(&count.to_string())
+            .arg(count.to_string())
             .arg("--timeout")
             .arg("10")
             .output()